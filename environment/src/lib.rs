@@ -38,8 +38,10 @@ pub trait Environment: 'static + Clone + Debug + Send + Sync {
     type Network: Network;
     /// The specified type of node.
     const NODE_TYPE: NodeType;
-    /// The version of the network protocol; it can be incremented in order to force users to update.
-    const MESSAGE_VERSION: u32 = 0;
+    /// The version of the network protocol.
+    const MESSAGE_VERSION: u32 = 4;
+    /// The minimum version of the network protocol of a peer; it can be incremented in order to force users to update.
+    const MINIMUM_MESSAGE_VERSION: u32 = 0;
     /// If `true`, a mining node will craft public coinbase transactions.
     const COINBASE_IS_PUBLIC: bool = false;
 
@@ -52,6 +54,8 @@ pub trait Environment: 'static + Clone + Debug + Send + Sync {
     const BEACON_NODES: &'static [&'static str] = &["127.0.0.1:4135"];
     /// The list of nodes to attempt to maintain connections with.
     const TRUSTED_NODES: &'static [&'static str] = &[];
+    /// The list of DNS seeds, whose A and AAAA records are the candidate peers to bootstrap the node server with.
+    const DNS_SEEDS: &'static [&'static str] = &[];
 
     /// The duration in seconds to sleep in between heartbeat executions.
     const HEARTBEAT_IN_SECS: u64 = 9;
@@ -77,9 +81,28 @@ pub trait Environment: 'static + Clone + Debug + Send + Sync {
 
     /// The maximum number of blocks that may be fetched in one request.
     const MAXIMUM_BLOCK_REQUEST: u32 = 250;
+    /// The duration in seconds after which an unanswered block request is reassigned to another peer.
+    const BLOCK_REQUEST_TIMEOUT_IN_SECS: u64 = 30;
     /// The maximum number of failures tolerated before disconnecting from a peer.
     const MAXIMUM_NUMBER_OF_FAILURES: usize = 1024;
 
+    /// The score penalty of a peer that sends a malformed message, or a message that violates the protocol.
+    const PEER_PENALTY_INVALID_MESSAGE: u32 = 20;
+    /// The score penalty of a peer that fails the handshake.
+    const PEER_PENALTY_FAILED_HANDSHAKE: u32 = 5;
+    /// The score penalty of a peer that serves an invalid block.
+    const PEER_PENALTY_INVALID_BLOCK: u32 = 50;
+    /// The score penalty of a peer that spams the node with messages or requests.
+    const PEER_PENALTY_SPAM: u32 = 25;
+    /// The score penalty of a peer that causes a failure in the ledger.
+    const PEER_PENALTY_FAILURE: u32 = 2;
+    /// The score penalty at which a peer is disconnected and its IP is temporarily banned.
+    const PEER_BAN_THRESHOLD: u32 = 100;
+    /// The duration in seconds after which the score of a peer has decayed halfway back to neutral.
+    const PEER_SCORE_HALF_LIFE_IN_SECS: u64 = 3600; // 1 hour
+    /// The duration in seconds for which the IP of a peer is banned, once it reaches the ban threshold.
+    const PEER_BAN_DURATION_IN_SECS: u64 = 86400; // 24 hours
+
     /// Returns the list of sync nodes to bootstrap the node server with.
     fn beacon_nodes() -> &'static HashSet<SocketAddr> {
         static NODES: OnceCell<HashSet<SocketAddr>> = OnceCell::new();
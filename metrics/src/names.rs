@@ -14,33 +14,73 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-pub const GAUGES: [&str; 4] = [blocks::HEIGHT, peers::CONNECTED, peers::CANDIDATE, peers::RESTRICTED];
+pub const GAUGES: [&str; 5] = [
+    blocks::HEIGHT,
+    peers::CONNECTED,
+    peers::CANDIDATE,
+    peers::RESTRICTED,
+    peers::COMPRESSION_RATIO,
+];
 pub const HISTOGRAMS: [&str; 4] = [
     internal_rtt::PING,
     internal_rtt::PONG,
     internal_rtt::PEER_REQUEST,
     internal_rtt::BLOCK_REQUEST,
 ];
-pub const COUNTERS: [&str; 9] = [
+pub const COUNTERS: [&str; 27] = [
+    blocks::ORPHANS_STORED,
+    blocks::ORPHANS_CONNECTED,
+    blocks::ORPHANS_EVICTED,
+    ledger_cache::BLOCK_HITS,
+    ledger_cache::BLOCK_MISSES,
+    ledger_cache::HEADER_HITS,
+    ledger_cache::HEADER_MISSES,
     message_counts::PING,
     message_counts::PONG,
     message_counts::PEER_REQUEST,
     message_counts::PEER_RESPONSE,
     message_counts::BLOCK_REQUEST,
     message_counts::BLOCK_RESPONSE,
+    message_counts::BLOCK_BATCH_RESPONSE,
+    message_counts::HEADERS_REQUEST,
+    message_counts::HEADERS_RESPONSE,
     message_counts::UNCONFIRMED_BLOCK,
     message_counts::UNCONFIRMED_TRANSACTION,
     message_counts::DISCONNECT,
+    peers::REFUSED_IP_LIMIT,
+    peers::REFUSED_SUBNET_LIMIT,
+    peers::COMPRESSION_UNCOMPRESSED_BYTES,
+    peers::COMPRESSION_COMPRESSED_BYTES,
+    peers::COMPRESSION_BYTES_SAVED,
+    prover::DROPPED_TRANSACTIONS,
+    seen_cache::BLOCK_HITS,
+    seen_cache::TRANSACTION_HITS,
 ];
 
 pub mod blocks {
     pub const HEIGHT: &str = "snarkos_blocks_height_total";
+    pub const ORPHANS_STORED: &str = "snarkos_blocks_orphans_stored_total";
+    pub const ORPHANS_CONNECTED: &str = "snarkos_blocks_orphans_connected_total";
+    pub const ORPHANS_EVICTED: &str = "snarkos_blocks_orphans_evicted_total";
+}
+
+pub mod ledger_cache {
+    pub const BLOCK_HITS: &str = "snarkos_ledger_cache_block_hits_total";
+    pub const BLOCK_MISSES: &str = "snarkos_ledger_cache_block_misses_total";
+    pub const HEADER_HITS: &str = "snarkos_ledger_cache_header_hits_total";
+    pub const HEADER_MISSES: &str = "snarkos_ledger_cache_header_misses_total";
 }
 
 pub mod peers {
     pub const CONNECTED: &str = "snarkos_peers_connected_total";
     pub const CANDIDATE: &str = "snarkos_peers_candidate_total";
     pub const RESTRICTED: &str = "snarkos_peers_restricted_total";
+    pub const REFUSED_IP_LIMIT: &str = "snarkos_peers_refused_ip_limit_total";
+    pub const REFUSED_SUBNET_LIMIT: &str = "snarkos_peers_refused_subnet_limit_total";
+    pub const COMPRESSION_UNCOMPRESSED_BYTES: &str = "snarkos_peers_compression_uncompressed_bytes_total";
+    pub const COMPRESSION_COMPRESSED_BYTES: &str = "snarkos_peers_compression_compressed_bytes_total";
+    pub const COMPRESSION_BYTES_SAVED: &str = "snarkos_peers_compression_bytes_saved_total";
+    pub const COMPRESSION_RATIO: &str = "snarkos_peers_compression_ratio";
 }
 
 pub mod internal_rtt {
@@ -57,7 +97,19 @@ pub mod message_counts {
     pub const PEER_RESPONSE: &str = "snarkos_message_counts_peer_response";
     pub const BLOCK_REQUEST: &str = "snarkos_message_counts_block_request";
     pub const BLOCK_RESPONSE: &str = "snarkos_message_counts_block_response";
+    pub const BLOCK_BATCH_RESPONSE: &str = "snarkos_message_counts_block_batch_response";
+    pub const HEADERS_REQUEST: &str = "snarkos_message_counts_headers_request";
+    pub const HEADERS_RESPONSE: &str = "snarkos_message_counts_headers_response";
     pub const UNCONFIRMED_BLOCK: &str = "snarkos_message_counts_unconfirmed_block";
     pub const UNCONFIRMED_TRANSACTION: &str = "snarkos_message_counts_unconfirmed_transaction";
     pub const DISCONNECT: &str = "snarkos_message_counts_disconnect";
 }
+
+pub mod prover {
+    pub const DROPPED_TRANSACTIONS: &str = "snarkos_prover_dropped_transactions_total";
+}
+
+pub mod seen_cache {
+    pub const BLOCK_HITS: &str = "snarkos_seen_cache_block_hits_total";
+    pub const TRANSACTION_HITS: &str = "snarkos_seen_cache_transaction_hits_total";
+}
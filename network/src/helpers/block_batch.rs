@@ -0,0 +1,177 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::{prelude::Network, Block};
+
+use ::bytes::Bytes;
+use anyhow::{anyhow, ensure, Result};
+use std::io::Write;
+use tokio::task;
+
+/// The minimum message version of a peer for it to be served batched block responses.
+pub const BLOCK_BATCH_MESSAGE_VERSION: u32 = 1;
+/// The minimum message version of a peer for it to be answered with an empty batched block response,
+/// when it requests blocks beyond the latest block.
+pub const EMPTY_BLOCK_BATCH_MESSAGE_VERSION: u32 = 4;
+/// The maximum number of blocks in a batched block response.
+pub const MAXIMUM_BLOCKS_PER_BATCH: u32 = 50;
+/// The maximum size of the serialized blocks in a batched block response, prior to compression.
+pub const MAXIMUM_BLOCK_BATCH_SIZE: usize = 32 * 1024 * 1024; // 32 MiB
+/// The zstd compression level of a batched block response.
+const BLOCK_BATCH_COMPRESSION_LEVEL: i32 = 3;
+
+///
+/// A batch of consecutive blocks for a block response, which is sent as zstd-compressed bytes.
+///
+/// Similar to `Data`, the compression and decompression are deferred, so that they can be
+/// performed on the blocking thread pool instead of the network task.
+///
+#[derive(Clone, Debug)]
+pub enum BlockBatch<N: Network> {
+    Blocks(Vec<Block<N>>),
+    Compressed(Bytes),
+}
+
+impl<N: Network> BlockBatch<N> {
+    ///
+    /// Splits the given consecutive blocks into batches of at most `MAXIMUM_BLOCKS_PER_BATCH` blocks,
+    /// and at most `MAXIMUM_BLOCK_BATCH_SIZE` bytes of serialized blocks.
+    ///
+    pub fn split(blocks: Vec<Block<N>>) -> Result<Vec<Self>> {
+        let mut batches = Vec::new();
+        let mut batch = Vec::new();
+        let mut batch_size = 0;
+
+        for block in blocks {
+            let block_size = bincode::serialized_size(&block)? as usize;
+            ensure!(
+                block_size <= MAXIMUM_BLOCK_BATCH_SIZE,
+                "Block {} exceeds the maximum size of a block batch",
+                block.header().height()
+            );
+
+            // Start a new batch, if the block does not fit in the current batch.
+            if batch.len() == MAXIMUM_BLOCKS_PER_BATCH as usize || batch_size + block_size > MAXIMUM_BLOCK_BATCH_SIZE {
+                batches.push(Self::Blocks(std::mem::take(&mut batch)));
+                batch_size = 0;
+            }
+
+            batch_size += block_size;
+            batch.push(block);
+        }
+        if !batch.is_empty() {
+            batches.push(Self::Blocks(batch));
+        }
+
+        Ok(batches)
+    }
+
+    /// Writes the compressed blocks into the given writer.
+    pub fn serialize_blocking_into<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Self::Blocks(blocks) => {
+                let bytes = bincode::serialize(blocks)?;
+                Ok(writer.write_all(&zstd::bulk::compress(&bytes, BLOCK_BATCH_COMPRESSION_LEVEL)?)?)
+            }
+            Self::Compressed(bytes) => Ok(writer.write_all(bytes)?),
+        }
+    }
+
+    ///
+    /// Returns the blocks of the batch, decompressing them if necessary.
+    ///
+    /// The decompressed size is bounded by `MAXIMUM_BLOCK_BATCH_SIZE`, and the blocks must be consecutive.
+    /// An empty batch answers a block request that starts beyond the latest block of the peer.
+    ///
+    pub fn deserialize_blocking(self) -> Result<Vec<Block<N>>> {
+        let bytes = match self {
+            Self::Blocks(blocks) => return Ok(blocks),
+            Self::Compressed(bytes) => zstd::bulk::decompress(&bytes, MAXIMUM_BLOCK_BATCH_SIZE)?,
+        };
+
+        let blocks: Vec<Block<N>> = bincode::deserialize(&bytes)?;
+        ensure!(
+            blocks.len() <= MAXIMUM_BLOCKS_PER_BATCH as usize,
+            "Invalid number of blocks ({}) in a block batch",
+            blocks.len()
+        );
+        ensure!(
+            blocks.windows(2).all(|pair| pair[0].header().height().checked_add(1) == Some(pair[1].header().height())),
+            "The blocks in a block batch are not consecutive"
+        );
+
+        Ok(blocks)
+    }
+
+    /// Compresses the blocks of the batch on the blocking thread pool (if they haven't been compressed yet).
+    pub async fn compress(self) -> Result<Self> {
+        match self {
+            Self::Blocks(..) => task::spawn_blocking(move || {
+                let mut bytes = Vec::new();
+                self.serialize_blocking_into(&mut bytes)?;
+                Ok(Self::Compressed(bytes.into()))
+            })
+            .await
+            .map_err(|error| anyhow!("Dedicated compression failed: {}", error))?,
+            Self::Compressed(..) => Ok(self),
+        }
+    }
+
+    /// Returns the blocks of the batch, decompressing them on the blocking thread pool.
+    pub async fn deserialize(self) -> Result<Vec<Block<N>>> {
+        task::spawn_blocking(move || self.deserialize_blocking())
+            .await
+            .map_err(|error| anyhow!("Dedicated decompression failed: {}", error))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::{sample_block, CurrentNetwork};
+
+    #[test]
+    fn test_block_batch_round_trip() {
+        let block = sample_block();
+
+        let batches = BlockBatch::split(vec![block.clone()]).unwrap();
+        assert_eq!(batches.len(), 1);
+
+        let mut bytes = Vec::new();
+        batches[0].serialize_blocking_into(&mut bytes).unwrap();
+
+        let blocks = BlockBatch::<CurrentNetwork>::Compressed(bytes.into()).deserialize_blocking().unwrap();
+        assert_eq!(blocks, vec![block]);
+    }
+
+    #[test]
+    fn test_block_batch_rejects_invalid_batches() {
+        let block = sample_block();
+
+        // The blocks of a batch must be consecutive.
+        let mut bytes = Vec::new();
+        BlockBatch::Blocks(vec![block.clone(), block]).serialize_blocking_into(&mut bytes).unwrap();
+        assert!(BlockBatch::<CurrentNetwork>::Compressed(bytes.into()).deserialize_blocking().is_err());
+
+        // Bytes that are not zstd-compressed are rejected.
+        assert!(BlockBatch::<CurrentNetwork>::Compressed(vec![0u8; 64].into()).deserialize_blocking().is_err());
+    }
+
+    #[test]
+    fn test_split_empty_blocks() {
+        assert!(BlockBatch::<CurrentNetwork>::split(vec![]).unwrap().is_empty());
+    }
+}
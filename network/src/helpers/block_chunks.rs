@@ -0,0 +1,281 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::HashSet, net::SocketAddr};
+
+///
+/// Returns the range of block heights (inclusive) to request, given the `missing_blocks` along the header chain,
+/// the latest block height in storage, and the maximum number of blocks to hold ahead of the latest block.
+///
+/// The range always starts after the latest block height, so that the blocks in storage are never requested again,
+/// even if the missing blocks were determined before the latest blocks were stored, such as after a restart.
+///
+pub fn blocks_to_request(missing_blocks: Option<(u32, u32)>, latest_block_height: u32, maximum_blocks_ahead: u32) -> Option<(u32, u32)> {
+    let (start_block_height, end_block_height) = missing_blocks?;
+    let start_block_height = start_block_height.max(latest_block_height.saturating_add(1));
+    let end_block_height = end_block_height.min(latest_block_height.saturating_add(maximum_blocks_ahead));
+    match start_block_height <= end_block_height {
+        true => Some((start_block_height, end_block_height)),
+        false => None,
+    }
+}
+
+///
+/// Splits the blocks from `start_block_height` to `end_block_height` (inclusive) into chunks of at most
+/// `chunk_size` blocks, and assigns each chunk to one of the given `(peer_ip, block_height)` peers.
+///
+/// A chunk is only assigned to a peer whose latest block height covers the entire chunk, and each chunk
+/// goes to the eligible peer with the fewest assigned chunks, so that the requests are spread across peers.
+/// Chunks that no peer is able to serve are left unassigned.
+///
+/// Returns the assignments as `(peer_ip, start_block_height, end_block_height)`, in increasing block height.
+///
+pub fn assign_block_chunks(
+    start_block_height: u32,
+    end_block_height: u32,
+    chunk_size: u32,
+    peers: &[(SocketAddr, u32)],
+) -> Vec<(SocketAddr, u32, u32)> {
+    let peers = peers.iter().map(|(peer_ip, block_height)| (*peer_ip, *block_height, 1.0)).collect::<Vec<_>>();
+    assign_weighted_block_chunks(start_block_height, end_block_height, chunk_size, &peers)
+}
+
+///
+/// Splits the blocks from `start_block_height` to `end_block_height` (inclusive) into chunks, and assigns
+/// each chunk to one of the given `(peer_ip, block_height, weight)` peers, in proportion to their weights.
+///
+/// The chunks of the peer with the highest weight have `chunk_size` blocks, while the chunks of the other
+/// peers shrink with their weight, so that a slow peer holds up fewer blocks. Each chunk goes to the eligible
+/// peer that would finish it first, given the blocks already assigned to each peer and its weight.
+/// With equal weights, this is the same as `assign_block_chunks`.
+///
+/// Returns the assignments as `(peer_ip, start_block_height, end_block_height)`, in increasing block height.
+///
+pub fn assign_weighted_block_chunks(
+    start_block_height: u32,
+    end_block_height: u32,
+    chunk_size: u32,
+    peers: &[(SocketAddr, u32, f64)],
+) -> Vec<(SocketAddr, u32, u32)> {
+    let mut assignments = Vec::new();
+    if chunk_size == 0 || start_block_height > end_block_height {
+        return assignments;
+    }
+
+    // Determine the chunk size of each peer, relative to the peer with the highest weight.
+    let peers = peers.iter().filter(|(_, _, weight)| *weight > 0.0).collect::<Vec<_>>();
+    let maximum_weight = peers.iter().map(|(_, _, weight)| *weight).fold(0.0, f64::max);
+    let chunk_sizes = peers
+        .iter()
+        .map(|(_, _, weight)| ((chunk_size as f64 * weight / maximum_weight).ceil() as u32).clamp(1, chunk_size))
+        .collect::<Vec<_>>();
+
+    // The number of blocks assigned to each peer.
+    let mut number_of_blocks = vec![0u32; peers.len()];
+
+    let mut chunk_start = start_block_height;
+    while chunk_start <= end_block_height {
+        // Select the peer that would finish its chunk first, among the peers that are able to serve their entire chunk.
+        let peer = peers
+            .iter()
+            .enumerate()
+            .map(|(index, (peer_ip, block_height, weight))| {
+                let chunk_end = std::cmp::min(chunk_start.saturating_add(chunk_sizes[index] - 1), end_block_height);
+                let finish = (number_of_blocks[index] + chunk_end - chunk_start + 1) as f64 / weight;
+                (index, peer_ip, *block_height, chunk_end, finish)
+            })
+            .filter(|(_, _, block_height, chunk_end, _)| block_height >= chunk_end)
+            .min_by(|(.., a), (.., b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let chunk_end = match peer {
+            Some((index, peer_ip, _, chunk_end, _)) => {
+                number_of_blocks[index] += chunk_end - chunk_start + 1;
+                assignments.push((*peer_ip, chunk_start, chunk_end));
+                chunk_end
+            }
+            // As the chunks are in increasing block height, no peer is able to serve the remaining chunks.
+            None => break,
+        };
+
+        chunk_start = match chunk_end.checked_add(1) {
+            Some(next_chunk_start) => next_chunk_start,
+            None => break,
+        };
+    }
+
+    assignments
+}
+
+///
+/// Assigns the blocks from `start_block_height` to `end_block_height` (inclusive) to the given
+/// `(peer_ip, block_height, weight)` peers, as in `assign_weighted_block_chunks`, and returns the block requests
+/// to send as `(peer_ip, start_block_height, end_block_height)`, in increasing block height.
+///
+/// The `skipped_block_heights` are the blocks that are already requested or pending, and are never requested again.
+/// A chunk that contains skipped blocks is split around them, so that only its missing blocks are requested.
+/// Once the requests of a peer expire, its blocks are no longer skipped, and are reassigned to the given peers.
+///
+pub fn assign_block_requests(
+    start_block_height: u32,
+    end_block_height: u32,
+    chunk_size: u32,
+    peers: &[(SocketAddr, u32, f64)],
+    skipped_block_heights: &HashSet<u32>,
+) -> Vec<(SocketAddr, u32, u32)> {
+    let mut requests = Vec::new();
+    for (peer_ip, chunk_start, chunk_end) in assign_weighted_block_chunks(start_block_height, end_block_height, chunk_size, peers) {
+        let mut request: Option<(u32, u32)> = None;
+        for block_height in chunk_start..=chunk_end {
+            match (skipped_block_heights.contains(&block_height), request.as_mut()) {
+                (true, _) => requests.extend(request.take().map(|(start, end)| (peer_ip, start, end))),
+                (false, Some((_, end))) => *end = block_height,
+                (false, None) => request = Some((block_height, block_height)),
+            }
+        }
+        requests.extend(request.map(|(start, end)| (peer_ip, start, end)));
+    }
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::peer;
+
+    #[test]
+    fn test_assign_block_chunks_across_peers() {
+        let peers = [(peer(4130), 1000), (peer(4131), 1000), (peer(4132), 1000)];
+
+        let assignments = assign_block_chunks(1, 250, 50, &peers);
+        assert_eq!(assignments, vec![
+            (peer(4130), 1, 50),
+            (peer(4131), 51, 100),
+            (peer(4132), 101, 150),
+            (peer(4130), 151, 200),
+            (peer(4131), 201, 250),
+        ]);
+    }
+
+    #[test]
+    fn test_assign_block_chunks_respects_peer_heights() {
+        let peers = [(peer(4130), 60), (peer(4131), 120)];
+
+        // The first peer is only able to serve the first chunk, and no peer is able to serve blocks beyond 120.
+        let assignments = assign_block_chunks(1, 200, 50, &peers);
+        assert_eq!(assignments, vec![(peer(4130), 1, 50), (peer(4131), 51, 100)]);
+
+        // The last chunk is shorter than the chunk size.
+        let assignments = assign_block_chunks(1, 120, 50, &peers);
+        assert_eq!(assignments, vec![(peer(4130), 1, 50), (peer(4131), 51, 100), (peer(4131), 101, 120)]);
+    }
+
+    #[test]
+    fn test_assign_block_chunks_without_peers() {
+        assert!(assign_block_chunks(1, 100, 50, &[]).is_empty());
+        assert!(assign_block_chunks(100, 1, 50, &[(peer(4130), 1000)]).is_empty());
+        assert!(assign_block_chunks(1, 100, 0, &[(peer(4130), 1000)]).is_empty());
+    }
+
+    #[test]
+    fn test_assign_weighted_block_chunks() {
+        // The second peer is a quarter as fast as the first peer, and is assigned a quarter of the blocks of the first peer.
+        let peers = [(peer(4130), 1000, 1.0), (peer(4131), 1000, 0.25)];
+        let assignments = assign_weighted_block_chunks(1, 250, 40, &peers);
+        assert_eq!(assignments, vec![
+            (peer(4130), 1, 40),
+            (peer(4131), 41, 50),
+            (peer(4130), 51, 90),
+            (peer(4131), 91, 100),
+            (peer(4130), 101, 140),
+            (peer(4131), 141, 150),
+            (peer(4130), 151, 190),
+            (peer(4131), 191, 200),
+            (peer(4130), 201, 240),
+            (peer(4131), 241, 250),
+        ]);
+
+        // Peers without weight are not assigned chunks.
+        assert!(assign_weighted_block_chunks(1, 100, 50, &[(peer(4130), 1000, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_restart_does_not_request_stored_blocks() {
+        let peers = [(peer(4130), 10_000), (peer(4131), 10_000)];
+
+        // Restart at an arbitrary block height, where the missing blocks may still cover the stored blocks.
+        let latest_block_height = 4321;
+        for missing_blocks in [(1, 10_000), (4300, 4400), (4322, 4400)] {
+            let (start, end) = blocks_to_request(Some(missing_blocks), latest_block_height, 250).unwrap();
+            assert_eq!(start, latest_block_height + 1);
+
+            // No block request covers a stored block, and every missing block up to the limit is requested once.
+            let chunks = assign_block_chunks(start, end, 50, &peers);
+            assert!(chunks.iter().all(|(_, chunk_start, _)| *chunk_start > latest_block_height));
+            let number_of_blocks: u32 = chunks.iter().map(|(_, chunk_start, chunk_end)| chunk_end - chunk_start + 1).sum();
+            assert_eq!(number_of_blocks, std::cmp::min(missing_blocks.1, latest_block_height + 250) - latest_block_height);
+        }
+
+        // A node that has stored every missing block requests nothing.
+        assert_eq!(blocks_to_request(Some((1, 4321)), latest_block_height, 250), None);
+        assert_eq!(blocks_to_request(None, latest_block_height, 250), None);
+    }
+
+    #[test]
+    fn test_assign_block_requests_skips_requested_blocks() {
+        let peers = [(peer(4130), 1000, 1.0), (peer(4131), 1000, 1.0)];
+
+        // Without skipped blocks, every chunk is requested in full.
+        let requests = assign_block_requests(1, 100, 50, &peers, &HashSet::new());
+        assert_eq!(requests, vec![(peer(4130), 1, 50), (peer(4131), 51, 100)]);
+
+        // The requested and pending blocks are skipped, and a chunk is split around them.
+        let skipped_block_heights = (1..=10).chain(20..=29).chain(100..=100).collect::<HashSet<_>>();
+        let requests = assign_block_requests(1, 100, 50, &peers, &skipped_block_heights);
+        assert_eq!(requests, vec![(peer(4130), 11, 19), (peer(4130), 30, 50), (peer(4131), 51, 99)]);
+
+        // A block is never requested twice.
+        let mut requested_block_heights = skipped_block_heights.clone();
+        for (_, start, end) in &requests {
+            assert!((*start..=*end).all(|block_height| requested_block_heights.insert(block_height)));
+        }
+        assert_eq!(requested_block_heights, (1..=100).collect());
+
+        // Once every block is requested, nothing is requested again.
+        assert!(assign_block_requests(1, 100, 50, &peers, &requested_block_heights).is_empty());
+    }
+
+    #[test]
+    fn test_assign_block_requests_reassigns_expired_requests() {
+        let (fast_peer, slow_peer) = (peer(4130), peer(4131));
+        let peers = [(fast_peer, 1000, 1.0), (slow_peer, 1000, 1.0)];
+
+        // The blocks are requested from both peers.
+        let requests = assign_block_requests(1, 100, 50, &peers, &HashSet::new());
+        assert_eq!(requests, vec![(fast_peer, 1, 50), (slow_peer, 51, 100)]);
+
+        // The fast peer responds, while the requests of the slow peer expire and lower its weight.
+        let skipped_block_heights = (1..=50).collect::<HashSet<_>>();
+        let peers = [(fast_peer, 1000, 1.0), (slow_peer, 1000, 0.25)];
+
+        // The blocks of the slow peer are requested again, and the slow peer is assigned a smaller chunk.
+        let requests = assign_block_requests(1, 100, 50, &peers, &skipped_block_heights);
+        assert_eq!(requests, vec![(slow_peer, 51, 63), (fast_peer, 64, 100)]);
+
+        // A peer that disconnected is not assigned blocks, and blocks beyond the remaining peers are not requested.
+        let requests = assign_block_requests(1, 150, 50, &[(slow_peer, 120, 1.0)], &skipped_block_heights);
+        assert_eq!(requests, vec![(slow_peer, 51, 100)]);
+    }
+}
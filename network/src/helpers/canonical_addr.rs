@@ -0,0 +1,60 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+///
+/// Returns the canonical form of the given IP address, which is the IPv4 address for an IPv4-mapped IPv6 address.
+///
+/// A dual-stack listener reports the IPv4 peers as IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`), so the addresses
+/// are canonicalized before they are compared, in order for a peer to have the same address regardless of the listener.
+///
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => match ipv6.segments() {
+            [0, 0, 0, 0, 0, 0xffff, high, low] => IpAddr::V4(Ipv4Addr::from(((high as u32) << 16) | low as u32)),
+            _ => ip,
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Returns the canonical form of the given socket address, which has the canonical form of its IP address.
+pub fn canonical_addr(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(canonical_ip(addr.ip()), addr.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn test_canonical_addr() {
+        // The IPv4-mapped IPv6 addresses are converted to IPv4 addresses.
+        assert_eq!(canonical_addr(addr("[::ffff:203.0.113.7]:4133")), addr("203.0.113.7:4133"));
+        assert_eq!(canonical_addr(addr("[::ffff:127.0.0.1]:4133")), addr("127.0.0.1:4133"));
+
+        // The other addresses are unchanged.
+        assert_eq!(canonical_addr(addr("203.0.113.7:4133")), addr("203.0.113.7:4133"));
+        assert_eq!(canonical_addr(addr("[2001:db8::1]:4133")), addr("[2001:db8::1]:4133"));
+        assert_eq!(canonical_addr(addr("[::1]:4133")), addr("[::1]:4133"));
+        assert_eq!(canonical_addr(addr("[::]:4133")), addr("[::]:4133"));
+    }
+}
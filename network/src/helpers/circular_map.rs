@@ -66,6 +66,13 @@ impl<K: Clone + PartialEq + Eq + Hash, V: Clone, const N: u32> CircularMap<K, V,
         self.map.get(key)
     }
 
+    ///
+    /// Returns an iterator over the values in the circular map, in arbitrary order.
+    ///
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.map.values()
+    }
+
     ///
     /// Inserts the given key-value pair into the circular map, returning a `bool`
     /// indicating whether the insertion took place.
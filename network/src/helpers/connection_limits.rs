@@ -0,0 +1,170 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::helpers::canonical_ip;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The default maximum number of connections from a single IP address.
+pub const DEFAULT_MAXIMUM_CONNECTIONS_PER_IP: usize = 2;
+/// The default maximum number of connections from a single /24 IPv4 subnet, or /48 IPv6 subnet.
+pub const DEFAULT_MAXIMUM_CONNECTIONS_PER_SUBNET: usize = 8;
+
+///
+/// The reason an inbound connection is refused by the connection limits.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionRefusal {
+    /// The IP address of the peer has reached the maximum number of connections.
+    IpLimit,
+    /// The subnet of the peer has reached the maximum number of connections.
+    SubnetLimit,
+}
+
+///
+/// The maximum number of connections from a single IP address and from a single subnet,
+/// so that a single machine, or a small block of addresses, is unable to occupy all of the inbound slots.
+/// A maximum of 0 disables the corresponding limit.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// The maximum number of connections from a single IP address.
+    maximum_per_ip: usize,
+    /// The maximum number of connections from a single /24 IPv4 subnet, or /48 IPv6 subnet.
+    maximum_per_subnet: usize,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAXIMUM_CONNECTIONS_PER_IP, DEFAULT_MAXIMUM_CONNECTIONS_PER_SUBNET)
+    }
+}
+
+impl ConnectionLimits {
+    /// Initializes new connection limits with the given maximum numbers of connections per IP address and per subnet.
+    pub fn new(maximum_per_ip: usize, maximum_per_subnet: usize) -> Self {
+        Self {
+            maximum_per_ip,
+            maximum_per_subnet,
+        }
+    }
+
+    ///
+    /// Returns `Ok(())` if a new connection from the given peer IP is within the limits,
+    /// given the IP addresses of the existing connections.
+    ///
+    pub fn check<I: IntoIterator<Item = IpAddr>>(&self, peer_ip: IpAddr, connected_ips: I) -> Result<(), ConnectionRefusal> {
+        let peer_ip = canonical_ip(peer_ip);
+        let subnet = subnet_of(peer_ip);
+        let (mut number_by_ip, mut number_by_subnet) = (0, 0);
+        for connected_ip in connected_ips.into_iter().map(canonical_ip) {
+            if connected_ip == peer_ip {
+                number_by_ip += 1;
+            }
+            if subnet_of(connected_ip) == subnet {
+                number_by_subnet += 1;
+            }
+        }
+
+        if self.maximum_per_ip > 0 && number_by_ip >= self.maximum_per_ip {
+            Err(ConnectionRefusal::IpLimit)
+        } else if self.maximum_per_subnet > 0 && number_by_subnet >= self.maximum_per_subnet {
+            Err(ConnectionRefusal::SubnetLimit)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns the /24 subnet of the given IPv4 address, or the /48 subnet of the given IPv6 address.
+fn subnet_of(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            IpAddr::V4(Ipv4Addr::new(a, b, c, 0))
+        }
+        IpAddr::V6(ip) => {
+            let [a, b, c, ..] = ip.segments();
+            IpAddr::V6(Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ip_limit() {
+        let limits = ConnectionLimits::new(2, 8);
+
+        // The connections from the same IP address are accepted up to the limit.
+        assert_eq!(limits.check(ip("203.0.113.7"), vec![]), Ok(()));
+        assert_eq!(limits.check(ip("203.0.113.7"), vec![ip("203.0.113.7")]), Ok(()));
+        let connected_ips = vec![ip("203.0.113.7"), ip("203.0.113.7")];
+        assert_eq!(limits.check(ip("203.0.113.7"), connected_ips.clone()), Err(ConnectionRefusal::IpLimit));
+
+        // Another IP address in the same subnet is accepted.
+        assert_eq!(limits.check(ip("203.0.113.8"), connected_ips), Ok(()));
+    }
+
+    #[test]
+    fn test_ipv4_subnet_limit() {
+        let limits = ConnectionLimits::new(2, 8);
+
+        // Fill the /24 subnet up to the limit, with one connection per IP address.
+        let connected_ips = (1..8).map(|i| ip(&format!("203.0.113.{}", i))).collect::<Vec<_>>();
+        assert_eq!(limits.check(ip("203.0.113.200"), connected_ips.clone()), Ok(()));
+
+        let connected_ips = (1..=8).map(|i| ip(&format!("203.0.113.{}", i))).collect::<Vec<_>>();
+        assert_eq!(limits.check(ip("203.0.113.200"), connected_ips.clone()), Err(ConnectionRefusal::SubnetLimit));
+
+        // An IP address in a neighboring subnet is accepted.
+        assert_eq!(limits.check(ip("203.0.114.1"), connected_ips), Ok(()));
+    }
+
+    #[test]
+    fn test_ipv6_subnet_limit() {
+        let limits = ConnectionLimits::new(2, 2);
+
+        // The IPv6 addresses that share the first 48 bits are in the same subnet.
+        let connected_ips = vec![ip("2001:db8:1:1::1"), ip("2001:db8:1:ffff::2")];
+        assert_eq!(limits.check(ip("2001:db8:1:2::3"), connected_ips.clone()), Err(ConnectionRefusal::SubnetLimit));
+        assert_eq!(limits.check(ip("2001:db8:2::1"), connected_ips), Ok(()));
+    }
+
+    #[test]
+    fn test_ipv4_mapped_limits() {
+        let limits = ConnectionLimits::new(2, 8);
+
+        // The IPv4-mapped IPv6 addresses of a dual-stack listener count toward the limits of their IPv4 addresses.
+        let connected_ips = vec![ip("203.0.113.7"), ip("::ffff:203.0.113.7")];
+        assert_eq!(limits.check(ip("::ffff:203.0.113.7"), connected_ips.clone()), Err(ConnectionRefusal::IpLimit));
+        assert_eq!(limits.check(ip("::ffff:198.51.100.1"), connected_ips), Ok(()));
+    }
+
+    #[test]
+    fn test_disabled_limits() {
+        let limits = ConnectionLimits::new(0, 0);
+
+        let connected_ips = vec![ip("203.0.113.7"); 100];
+        assert_eq!(limits.check(ip("203.0.113.7"), connected_ips), Ok(()));
+    }
+}
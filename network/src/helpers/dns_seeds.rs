@@ -0,0 +1,110 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, net::SocketAddr, time::Duration};
+use tokio::{net::lookup_host, time::timeout};
+
+/// The maximum duration to wait for the resolution of a single DNS seed.
+pub const DNS_SEED_TIMEOUT: Duration = Duration::from_secs(5);
+/// The minimum duration in between resolutions of the DNS seeds, when the node is low on candidate peers.
+pub const DNS_SEED_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+///
+/// The source from which a candidate peer was learned, for diagnostics.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerSource {
+    /// The peer was resolved from a DNS seed.
+    DnsSeed,
+    /// The peer is a bootnode, one of the sync nodes of the environment.
+    Bootnode,
+    /// The peer was shared by a connected peer.
+    Gossip,
+    /// The peer was known from before the restart.
+    KnownPeer,
+    /// The peer was connected, and has disconnected.
+    Disconnected,
+}
+
+///
+/// Resolves the given DNS seeds concurrently, returning the deduplicated addresses of their A and AAAA records.
+/// A seed is a hostname, with an optional port, and the given default port is used for a seed without a port.
+/// A seed that fails to resolve within the timeout is skipped.
+///
+pub async fn resolve_dns_seeds(seeds: &[String], default_port: u16) -> Vec<SocketAddr> {
+    let resolutions = seeds.iter().map(|seed| async move {
+        let address = seed_address(seed, default_port);
+        match timeout(DNS_SEED_TIMEOUT, lookup_host(address.as_str())).await {
+            Ok(Ok(peer_ips)) => {
+                let peer_ips = peer_ips.collect::<Vec<_>>();
+                debug!("Resolved {} peers from the DNS seed {}", peer_ips.len(), seed);
+                peer_ips
+            }
+            Ok(Err(error)) => {
+                warn!("Failed to resolve the DNS seed {}: {}", seed, error);
+                vec![]
+            }
+            Err(_) => {
+                warn!("Failed to resolve the DNS seed {} within {:?}", seed, DNS_SEED_TIMEOUT);
+                vec![]
+            }
+        }
+    });
+
+    let mut seen = HashSet::new();
+    futures::future::join_all(resolutions)
+        .await
+        .into_iter()
+        .flatten()
+        .filter(|peer_ip| seen.insert(*peer_ip))
+        .collect()
+}
+
+/// Returns the `host:port` address of the given seed, with the given default port if the seed has no port.
+fn seed_address(seed: &str, default_port: u16) -> String {
+    match seed.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => seed.to_string(),
+        _ => format!("{}:{}", seed, default_port),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_address() {
+        assert_eq!(seed_address("seed.example.com", 4132), "seed.example.com:4132");
+        assert_eq!(seed_address("seed.example.com:4135", 4132), "seed.example.com:4135");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_dns_seeds() {
+        let seeds = vec!["localhost".to_string(), "localhost:4135".to_string(), "localhost".to_string()];
+        let peer_ips = resolve_dns_seeds(&seeds, 4132).await;
+
+        // The records of each seed are deduplicated, and use the default port unless the seed specifies one.
+        assert!(peer_ips.iter().any(|peer_ip| peer_ip.ip().is_loopback() && peer_ip.port() == 4132));
+        assert!(peer_ips.iter().any(|peer_ip| peer_ip.ip().is_loopback() && peer_ip.port() == 4135));
+        assert_eq!(peer_ips.len(), peer_ips.iter().collect::<HashSet<_>>().len());
+
+        // A seed that fails to resolve is skipped.
+        let seeds = vec!["invalid.invalid".to_string()];
+        assert!(resolve_dns_seeds(&seeds, 4132).await.is_empty());
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::{prelude::Network, Block, Header};
+
+use anyhow::{ensure, Result};
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use time::OffsetDateTime;
+
+/// The minimum message version of a peer for it to be sent header requests.
+pub const HEADERS_MESSAGE_VERSION: u32 = 2;
+/// The maximum number of block headers in a headers response.
+pub const MAXIMUM_HEADERS_PER_RESPONSE: u32 = 2000;
+/// The maximum number of block headers held ahead of the canon chain.
+pub const MAXIMUM_HEADER_CHAIN_LENGTH: u32 = 50_000;
+/// The duration after which a headers request without a response is sent again.
+const HEADERS_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// The number of seconds that the timestamp of a block header may be ahead of the local time.
+const MAXIMUM_FUTURE_TIMESTAMP_IN_SECS: i64 = 120;
+/// The maximum number of abandoned block hashes that are remembered.
+const MAXIMUM_ABANDONED_BLOCK_HASHES: usize = 10_000;
+
+/// A block header, along with its previous block hash and its block hash.
+/// HeaderLink := (previous_block_hash, block_hash, block_header)
+pub type HeaderLink<N> = (<N as Network>::BlockHash, <N as Network>::BlockHash, Header<N>);
+
+///
+/// The chain of block headers ahead of the canon chain, which is downloaded and validated
+/// before the full blocks, so that blocks are only requested along a valid chain of headers.
+///
+/// As the block headers of this network do not carry a proof of work, the header chain is validated
+/// by its linkage, heights, and timestamps. The full blocks must then match the header chain,
+/// and a header branch whose blocks turn out to be invalid is abandoned.
+///
+#[derive(Debug)]
+pub struct HeaderChain<N: Network> {
+    /// The block height, block hash, and timestamp of the canon block that the header chain extends.
+    anchor: (u32, N::BlockHash, i64),
+    /// The headers ahead of the anchor, keyed by block height.
+    headers: BTreeMap<u32, HeaderLink<N>>,
+    /// The peer that served the headers, if any.
+    header_peer: Option<SocketAddr>,
+    /// The outstanding headers request := (peer_ip, timestamp)
+    pending_request: Option<(SocketAddr, Instant)>,
+    /// The peer whose last headers request timed out, if any.
+    timed_out_peer: Option<SocketAddr>,
+    /// The block hashes of abandoned header branches, which are never accepted again.
+    abandoned: HashSet<N::BlockHash>,
+}
+
+impl<N: Network> HeaderChain<N> {
+    /// Initializes a new header chain extending the given canon block.
+    pub fn new(block: &Block<N>) -> Self {
+        Self {
+            anchor: (block.header().height(), block.hash(), block.header().timestamp()),
+            headers: Default::default(),
+            header_peer: None,
+            pending_request: None,
+            timed_out_peer: None,
+            abandoned: Default::default(),
+        }
+    }
+
+    ///
+    /// Initializes a header chain extending the given canon block, restoring the given stored headers,
+    /// ordered by block height, up to the first header that does not extend it.
+    ///
+    pub fn load(block: &Block<N>, links: Vec<HeaderLink<N>>) -> Self {
+        let mut header_chain = Self::new(block);
+
+        let (anchor_block_height, mut previous_block_hash) = header_chain.tip();
+        let mut previous_block_height = anchor_block_height;
+        for link in links.into_iter().skip_while(|(_, _, header)| header.height() <= anchor_block_height) {
+            let block_height = link.2.height();
+            if block_height != previous_block_height + 1 || link.0 != previous_block_hash {
+                break;
+            }
+            previous_block_height = block_height;
+            previous_block_hash = link.1;
+            header_chain.headers.insert(block_height, link);
+        }
+        header_chain
+    }
+
+    /// Returns the headers ahead of the canon chain, ordered by block height.
+    pub fn links(&self) -> Vec<HeaderLink<N>> {
+        self.headers.values().cloned().collect()
+    }
+
+    /// Returns the number of headers ahead of the canon chain.
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Returns `true` if there are no headers ahead of the canon chain.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Returns the block height and block hash of the tip of the header chain.
+    pub fn tip(&self) -> (u32, N::BlockHash) {
+        match self.headers.values().next_back() {
+            Some((_, block_hash, header)) => (header.height(), *block_hash),
+            None => (self.anchor.0, self.anchor.1),
+        }
+    }
+
+    /// Returns the block hash of the header at the given block height, if it is ahead of the canon chain.
+    pub fn get_block_hash(&self, block_height: u32) -> Option<N::BlockHash> {
+        self.headers.get(&block_height).map(|(_, block_hash, _)| *block_hash)
+    }
+
+    /// Returns the range of block heights (inclusive) of the blocks to download along the header chain.
+    pub fn missing_blocks(&self) -> Option<(u32, u32)> {
+        match (self.headers.keys().next(), self.headers.keys().next_back()) {
+            (Some(start_block_height), Some(end_block_height)) => Some((*start_block_height, *end_block_height)),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the peer and the range of block heights (inclusive) of the next headers request,
+    /// given the `(peer_ip, block_height)` of the peers to sync from, if no other headers request is outstanding.
+    ///
+    /// The request goes to the peer with the highest block height, skipping the peer whose last request timed out,
+    /// as it may not support headers.
+    ///
+    pub fn next_request(&mut self, peers: &[(SocketAddr, u32)]) -> Option<(SocketAddr, u32, u32)> {
+        if let Some((peer_ip, timestamp)) = self.pending_request {
+            if timestamp.elapsed() < HEADERS_REQUEST_TIMEOUT {
+                return None;
+            }
+            self.pending_request = None;
+            self.timed_out_peer = Some(peer_ip);
+        }
+
+        let (tip_block_height, _) = self.tip();
+        let (peer_ip, peer_block_height) = peers
+            .iter()
+            .filter(|(peer_ip, block_height)| Some(*peer_ip) != self.timed_out_peer && *block_height > tip_block_height)
+            .max_by_key(|(_, block_height)| *block_height)?;
+
+        let maximum_block_height = self.anchor.0.saturating_add(MAXIMUM_HEADER_CHAIN_LENGTH);
+        let end_block_height = (*peer_block_height)
+            .min(tip_block_height.saturating_add(MAXIMUM_HEADERS_PER_RESPONSE))
+            .min(maximum_block_height);
+        if end_block_height <= tip_block_height {
+            return None;
+        }
+
+        self.pending_request = Some((*peer_ip, Instant::now()));
+        Some((*peer_ip, tip_block_height + 1, end_block_height))
+    }
+
+    ///
+    /// Extends the header chain with the given headers from the given peer.
+    /// The headers must answer the outstanding headers request, and extend the tip of the header chain.
+    ///
+    pub fn extend(&mut self, peer_ip: SocketAddr, links: Vec<HeaderLink<N>>) -> Result<()> {
+        ensure!(
+            matches!(self.pending_request, Some((pending_peer_ip, _)) if pending_peer_ip == peer_ip),
+            "Received unsolicited headers"
+        );
+        self.pending_request = None;
+        self.timed_out_peer = None;
+
+        ensure!(
+            !links.is_empty() && links.len() <= MAXIMUM_HEADERS_PER_RESPONSE as usize,
+            "Invalid number of headers ({})",
+            links.len()
+        );
+        ensure!(
+            self.headers.len() + links.len() <= MAXIMUM_HEADER_CHAIN_LENGTH as usize,
+            "The header chain exceeds the maximum length"
+        );
+
+        // Validate every header before extending the header chain.
+        let (mut previous_block_height, mut previous_block_hash) = self.tip();
+        let mut previous_timestamp = match self.headers.values().next_back() {
+            Some((_, _, header)) => header.timestamp(),
+            None => self.anchor.2,
+        };
+        let maximum_timestamp = OffsetDateTime::now_utc().unix_timestamp() + MAXIMUM_FUTURE_TIMESTAMP_IN_SECS;
+        for (link_previous_block_hash, block_hash, header) in &links {
+            let block_height = header.height();
+            ensure!(block_height == previous_block_height + 1, "Header {} is out of order", block_height);
+            ensure!(*link_previous_block_hash == previous_block_hash, "Header {} does not extend the header chain", block_height);
+            ensure!(!self.abandoned.contains(block_hash), "Header {} is on an abandoned branch", block_height);
+            ensure!(
+                header.timestamp() > previous_timestamp && header.timestamp() <= maximum_timestamp,
+                "Header {} has an invalid timestamp",
+                block_height
+            );
+
+            previous_block_height = block_height;
+            previous_block_hash = *block_hash;
+            previous_timestamp = header.timestamp();
+        }
+
+        for link in links {
+            self.headers.insert(link.2.height(), link);
+        }
+        self.header_peer = Some(peer_ip);
+        Ok(())
+    }
+
+    ///
+    /// Advances the header chain to the given latest canon block, removing the headers that are no longer ahead of it,
+    /// and returns the range of block heights (inclusive) of the removed headers, if any.
+    /// If the canon chain does not follow the header chain, the header chain is discarded.
+    ///
+    pub fn advance(&mut self, latest_block: &Block<N>) -> Option<(u32, u32)> {
+        let latest_block_height = latest_block.header().height();
+        let is_on_header_chain = match self.headers.get(&latest_block_height) {
+            Some((_, block_hash, _)) => *block_hash == latest_block.hash(),
+            None => latest_block_height == self.anchor.0 && latest_block.hash() == self.anchor.1,
+        };
+
+        let removed_headers = match is_on_header_chain {
+            true => {
+                let headers = self.headers.split_off(&(latest_block_height + 1));
+                std::mem::replace(&mut self.headers, headers)
+            }
+            false => {
+                self.header_peer = None;
+                std::mem::take(&mut self.headers)
+            }
+        };
+        self.anchor = (latest_block_height, latest_block.hash(), latest_block.header().timestamp());
+
+        match (removed_headers.keys().next(), removed_headers.keys().next_back()) {
+            (Some(start_block_height), Some(end_block_height)) => Some((*start_block_height, *end_block_height)),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Abandons the header branch from the given block height, whose block turned out to be invalid,
+    /// and returns the peer that served the abandoned headers.
+    ///
+    pub fn abandon(&mut self, block_height: u32) -> Option<SocketAddr> {
+        let abandoned_headers = self.headers.split_off(&block_height);
+        if abandoned_headers.is_empty() {
+            return None;
+        }
+
+        if self.abandoned.len() + abandoned_headers.len() > MAXIMUM_ABANDONED_BLOCK_HASHES {
+            self.abandoned.clear();
+        }
+        self.abandoned.extend(abandoned_headers.values().map(|(_, block_hash, _)| *block_hash));
+
+        match self.headers.is_empty() {
+            true => self.header_peer.take(),
+            false => self.header_peer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::{header_link, sample_block, sample_blocks};
+
+    #[test]
+    fn test_header_requests() {
+        let peer_ip = "127.0.0.1:4133".parse().unwrap();
+        let other_peer_ip = "127.0.0.1:4134".parse().unwrap();
+        let mut header_chain = HeaderChain::new(&sample_block());
+
+        // A peer that is not ahead of the header chain is not sent a request.
+        assert_eq!(header_chain.next_request(&[(peer_ip, 0)]), None);
+
+        // The request goes to the highest peer, and is bounded by the size of a headers response.
+        let peers = [(peer_ip, 10_000), (other_peer_ip, 20)];
+        assert_eq!(header_chain.next_request(&peers), Some((peer_ip, 1, MAXIMUM_HEADERS_PER_RESPONSE)));
+
+        // The request is not repeated while it is outstanding.
+        assert_eq!(header_chain.next_request(&peers), None);
+    }
+
+    #[test]
+    fn test_extend_rejects_invalid_headers() {
+        let peer_ip = "127.0.0.1:4133".parse().unwrap();
+        let other_peer_ip = "127.0.0.1:4134".parse().unwrap();
+        let block = sample_block();
+        let mut header_chain = HeaderChain::new(&block);
+
+        // Unsolicited headers are rejected.
+        let link = (block.previous_hash(), block.hash(), block.header().clone());
+        assert!(header_chain.extend(peer_ip, vec![link.clone()]).is_err());
+
+        // Headers from another peer are rejected.
+        header_chain.next_request(&[(peer_ip, 10)]);
+        assert!(header_chain.extend(other_peer_ip, vec![link.clone()]).is_err());
+
+        // Headers that do not extend the tip of the header chain are rejected.
+        header_chain.next_request(&[(peer_ip, 10)]);
+        assert!(header_chain.extend(peer_ip, vec![link]).is_err());
+        assert!(header_chain.is_empty());
+        assert_eq!(header_chain.tip(), (0, block.hash()));
+    }
+
+    #[test]
+    fn test_extend() {
+        let peer_ip = "127.0.0.1:4133".parse().unwrap();
+        let block = sample_block();
+        let blocks = sample_blocks(&block, 5);
+        let links = blocks.iter().map(header_link).collect::<Vec<_>>();
+        let mut header_chain = HeaderChain::new(&block);
+
+        // The headers are added in consecutive responses.
+        assert_eq!(header_chain.next_request(&[(peer_ip, 5)]), Some((peer_ip, 1, 5)));
+        header_chain.extend(peer_ip, links[..3].to_vec()).expect("Failed to extend the header chain");
+        assert_eq!(header_chain.len(), 3);
+        assert_eq!(header_chain.tip(), (3, blocks[2].hash()));
+        assert_eq!(header_chain.get_block_hash(2), Some(blocks[1].hash()));
+        assert_eq!(header_chain.missing_blocks(), Some((1, 3)));
+
+        assert_eq!(header_chain.next_request(&[(peer_ip, 5)]), Some((peer_ip, 4, 5)));
+        header_chain.extend(peer_ip, links[3..].to_vec()).expect("Failed to extend the header chain");
+        assert_eq!(header_chain.tip(), (5, blocks[4].hash()));
+        assert_eq!(header_chain.missing_blocks(), Some((1, 5)));
+        assert_eq!(header_chain.links(), links);
+
+        // A peer that is not ahead of the tip is not sent a request.
+        assert_eq!(header_chain.next_request(&[(peer_ip, 5)]), None);
+    }
+
+    #[test]
+    fn test_extend_rejects_gaps() {
+        let peer_ip = "127.0.0.1:4133".parse().unwrap();
+        let block = sample_block();
+        let blocks = sample_blocks(&block, 3);
+        let mut header_chain = HeaderChain::new(&block);
+
+        // A response with a missing header is rejected as a whole.
+        header_chain.next_request(&[(peer_ip, 3)]);
+        assert!(header_chain.extend(peer_ip, vec![header_link(&blocks[0]), header_link(&blocks[2])]).is_err());
+        assert!(header_chain.is_empty());
+        assert_eq!(header_chain.tip(), (0, block.hash()));
+    }
+
+    #[test]
+    fn test_advance() {
+        let peer_ip = "127.0.0.1:4133".parse().unwrap();
+        let block = sample_block();
+        let blocks = sample_blocks(&block, 5);
+        let mut header_chain = HeaderChain::new(&block);
+        header_chain.next_request(&[(peer_ip, 5)]);
+        header_chain.extend(peer_ip, blocks.iter().map(header_link).collect()).unwrap();
+
+        // Advancing to the anchor keeps the header chain.
+        assert_eq!(header_chain.advance(&block), None);
+        assert_eq!(header_chain.len(), 5);
+
+        // Advancing along the header chain removes the headers of the added blocks.
+        assert_eq!(header_chain.advance(&blocks[1]), Some((1, 2)));
+        assert_eq!(header_chain.len(), 3);
+        assert_eq!(header_chain.tip(), (5, blocks[4].hash()));
+        assert_eq!(header_chain.missing_blocks(), Some((3, 5)));
+        assert_eq!(header_chain.get_block_hash(2), None);
+        assert_eq!(header_chain.advance(&blocks[1]), None);
+
+        // Advancing to a block that is not on the header chain discards the header chain.
+        assert_eq!(header_chain.advance(&block), Some((3, 5)));
+        assert!(header_chain.is_empty());
+        assert_eq!(header_chain.tip(), (0, block.hash()));
+        assert_eq!(header_chain.missing_blocks(), None);
+    }
+
+    #[test]
+    fn test_abandon() {
+        let peer_ip = "127.0.0.1:4133".parse().unwrap();
+        let block = sample_block();
+        let blocks = sample_blocks(&block, 5);
+        let links = blocks.iter().map(header_link).collect::<Vec<_>>();
+        let mut header_chain = HeaderChain::new(&block);
+        header_chain.next_request(&[(peer_ip, 5)]);
+        header_chain.extend(peer_ip, links.clone()).unwrap();
+
+        // There is nothing to abandon beyond the tip.
+        assert_eq!(header_chain.abandon(6), None);
+        assert_eq!(header_chain.len(), 5);
+
+        // Abandoning a header removes it and every header after it, and reports the peer that served them.
+        assert_eq!(header_chain.abandon(3), Some(peer_ip));
+        assert_eq!(header_chain.tip(), (2, blocks[1].hash()));
+        assert_eq!(header_chain.missing_blocks(), Some((1, 2)));
+
+        // The abandoned headers are never accepted again.
+        assert_eq!(header_chain.next_request(&[(peer_ip, 5)]), Some((peer_ip, 3, 5)));
+        assert!(header_chain.extend(peer_ip, links[2..].to_vec()).is_err());
+        assert_eq!(header_chain.tip(), (2, blocks[1].hash()));
+
+        // Abandoning the whole header chain reports the peer once.
+        assert_eq!(header_chain.abandon(1), Some(peer_ip));
+        assert!(header_chain.is_empty());
+        assert_eq!(header_chain.abandon(1), None);
+    }
+
+    #[test]
+    fn test_load() {
+        let block = sample_block();
+        let blocks = sample_blocks(&block, 5);
+        let links = blocks.iter().map(header_link).collect::<Vec<_>>();
+
+        // The stored headers are restored on top of the canon block.
+        let header_chain = HeaderChain::load(&block, links.clone());
+        assert_eq!(header_chain.links(), links);
+        assert_eq!(header_chain.tip(), (5, blocks[4].hash()));
+
+        // The stored headers of blocks that were added to the canon chain are skipped.
+        let header_chain = HeaderChain::load(&blocks[1], links.clone());
+        assert_eq!(header_chain.links(), links[2..]);
+        assert_eq!(header_chain.missing_blocks(), Some((3, 5)));
+
+        // The stored headers are restored up to the first gap.
+        let mut gapped_links = links.clone();
+        gapped_links.remove(2);
+        let header_chain = HeaderChain::load(&block, gapped_links);
+        assert_eq!(header_chain.links(), links[..2]);
+
+        // Stored headers that do not extend the canon block are dropped.
+        let header_chain = HeaderChain::load(&block, links[1..].to_vec());
+        assert!(header_chain.is_empty());
+        assert_eq!(header_chain.tip(), (0, block.hash()));
+    }
+}
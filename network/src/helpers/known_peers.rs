@@ -0,0 +1,312 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_environment::helpers::NodeType;
+
+use ::time::OffsetDateTime;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io,
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    time::Duration,
+};
+
+/// The interval at which the known peers are written to disk.
+pub const KNOWN_PEERS_SAVE_INTERVAL: Duration = Duration::from_secs(300);
+/// The maximum number of known peers, beyond which the least recently seen peers are dropped.
+const MAXIMUM_KNOWN_PEERS: usize = 1000;
+
+/// A peer known to the node, as it is written to disk.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownPeer {
+    /// The IP address of the peer.
+    pub peer_ip: SocketAddr,
+    /// The node type of the peer, if the node has connected to it.
+    pub node_type: Option<NodeType>,
+    /// The UNIX timestamp at which the peer was last seen.
+    pub last_seen: i64,
+}
+
+/// A banned peer IP, as it is written to disk.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BannedPeer {
+    /// The IP address of the peer.
+    pub peer_ip: IpAddr,
+    /// The UNIX timestamp at which the ban expires.
+    pub banned_until: i64,
+}
+
+/// The known peers and banned peer IPs, as they are written to disk.
+#[derive(Default, Serialize, Deserialize)]
+struct KnownPeersFile {
+    peers: Vec<KnownPeer>,
+    #[serde(default)]
+    bans: Vec<BannedPeer>,
+}
+
+///
+/// The peers known to the node, with the node type and last-seen timestamp of each peer,
+/// which are kept across restarts, so that the node reconnects to them before falling back to the sync nodes.
+/// The banned peer IPs are kept alongside, so that a ban survives a restart.
+///
+#[derive(Debug, Default)]
+pub struct KnownPeers {
+    /// The map of each peer to its node type and last-seen timestamp.
+    peers: HashMap<SocketAddr, (Option<NodeType>, i64)>,
+    /// The map of each banned peer IP to the timestamp at which its ban expires.
+    bans: HashMap<IpAddr, i64>,
+}
+
+impl KnownPeers {
+    ///
+    /// Loads the known peers from the given file, dropping the peers that were not seen within `maximum_age`,
+    /// and the bans that have expired. Returns no known peers if the file does not exist yet.
+    ///
+    pub fn load(path: &Path, maximum_age: Duration) -> Result<Self> {
+        Self::load_at(path, maximum_age, OffsetDateTime::now_utc().unix_timestamp())
+    }
+
+    fn load_at(path: &Path, maximum_age: Duration, now: i64) -> Result<Self> {
+        let file: KnownPeersFile = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(error) => return Err(error.into()),
+        };
+
+        let oldest_last_seen = now.saturating_sub(maximum_age.as_secs() as i64);
+        let mut peers = Self::default();
+        for peer in file.peers.into_iter().filter(|peer| peer.last_seen >= oldest_last_seen) {
+            peers.insert(peer.peer_ip, peer.node_type, peer.last_seen);
+        }
+        for ban in file.bans.into_iter().filter(|ban| ban.banned_until > now) {
+            peers.bans.insert(ban.peer_ip, ban.banned_until);
+        }
+        Ok(peers)
+    }
+
+    ///
+    /// Writes the known peers to the given file, in decreasing order of their last-seen timestamp,
+    /// along with the bans that have not expired yet.
+    /// The file is replaced atomically, so that a crash never leaves a partially written file behind.
+    ///
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut peers = self
+            .peers
+            .iter()
+            .map(|(peer_ip, (node_type, last_seen))| KnownPeer {
+                peer_ip: *peer_ip,
+                node_type: *node_type,
+                last_seen: *last_seen,
+            })
+            .collect::<Vec<_>>();
+        peers.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then(a.peer_ip.cmp(&b.peer_ip)));
+        let file = KnownPeersFile { peers, bans: self.bans() };
+
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, serde_json::to_vec_pretty(&file)?)?;
+        fs::rename(&temporary_path, path)?;
+        Ok(())
+    }
+
+    ///
+    /// Records the given peer as seen now, with its node type, if it is known.
+    ///
+    pub fn seen(&mut self, peer_ip: SocketAddr, node_type: Option<NodeType>) {
+        self.insert(peer_ip, node_type, OffsetDateTime::now_utc().unix_timestamp());
+    }
+
+    ///
+    /// Records the given peer as seen at the given timestamp, keeping its previous node type if the given one is unknown.
+    /// If there are more than `MAXIMUM_KNOWN_PEERS` peers, the least recently seen peer is dropped.
+    ///
+    pub fn insert(&mut self, peer_ip: SocketAddr, node_type: Option<NodeType>, last_seen: i64) {
+        let entry = self.peers.entry(peer_ip).or_insert((node_type, last_seen));
+        entry.0 = node_type.or(entry.0);
+        entry.1 = entry.1.max(last_seen);
+
+        if self.peers.len() > MAXIMUM_KNOWN_PEERS {
+            if let Some(oldest_peer_ip) = self.peers.iter().min_by_key(|(_, (_, last_seen))| *last_seen).map(|(peer_ip, _)| *peer_ip) {
+                self.peers.remove(&oldest_peer_ip);
+            }
+        }
+    }
+
+    /// Returns `true` if the given peer is known.
+    pub fn contains(&self, peer_ip: &SocketAddr) -> bool {
+        self.peers.contains_key(peer_ip)
+    }
+
+    /// Removes the given peer from the known peers.
+    pub fn remove(&mut self, peer_ip: &SocketAddr) {
+        self.peers.remove(peer_ip);
+    }
+
+    /// Removes all known peers, keeping the bans.
+    pub fn clear(&mut self) {
+        self.peers.clear();
+    }
+
+    /// Returns the known peers, in decreasing order of their last-seen timestamp.
+    pub fn most_recent(&self) -> Vec<SocketAddr> {
+        let mut peers = self.peers.iter().map(|(peer_ip, (_, last_seen))| (*last_seen, *peer_ip)).collect::<Vec<_>>();
+        peers.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        peers.into_iter().map(|(_, peer_ip)| peer_ip).collect()
+    }
+
+    ///
+    /// Bans the given peer IP until the given timestamp, removing its known peers, so that it is not reconnected to.
+    /// A longer existing ban of the peer IP is kept.
+    ///
+    pub fn ban(&mut self, peer_ip: IpAddr, banned_until: i64) {
+        let ban = self.bans.entry(peer_ip).or_insert(banned_until);
+        *ban = (*ban).max(banned_until);
+        self.peers.retain(|known_peer_ip, _| known_peer_ip.ip() != peer_ip);
+    }
+
+    /// Lifts the ban of the given peer IP, returning `true` if it was banned.
+    pub fn unban(&mut self, peer_ip: &IpAddr) -> bool {
+        self.bans.remove(peer_ip).is_some()
+    }
+
+    /// Lifts all bans, returning the number of lifted bans.
+    pub fn clear_bans(&mut self) -> usize {
+        let number_of_bans = self.bans.len();
+        self.bans.clear();
+        number_of_bans
+    }
+
+    /// Returns `true` if the given peer IP is banned.
+    pub fn is_banned(&self, peer_ip: &IpAddr) -> bool {
+        self.is_banned_at(peer_ip, OffsetDateTime::now_utc().unix_timestamp())
+    }
+
+    fn is_banned_at(&self, peer_ip: &IpAddr, now: i64) -> bool {
+        matches!(self.bans.get(peer_ip), Some(banned_until) if *banned_until > now)
+    }
+
+    /// Returns the bans that have not expired yet, in increasing order of their peer IP.
+    pub fn bans(&self) -> Vec<BannedPeer> {
+        self.bans_at(OffsetDateTime::now_utc().unix_timestamp())
+    }
+
+    fn bans_at(&self, now: i64) -> Vec<BannedPeer> {
+        let mut bans = self
+            .bans
+            .iter()
+            .filter(|(_, banned_until)| **banned_until > now)
+            .map(|(peer_ip, banned_until)| BannedPeer {
+                peer_ip: *peer_ip,
+                banned_until: *banned_until,
+            })
+            .collect::<Vec<_>>();
+        bans.sort_by_key(|ban| ban.peer_ip);
+        bans
+    }
+
+    /// Returns the number of known peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Returns `true` if there are no known peers.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::peer;
+    use rand::{thread_rng, Rng};
+    use std::path::PathBuf;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("snarkos-test-known-peers-{}.json", thread_rng().gen::<u64>()))
+    }
+
+    #[test]
+    fn test_save_and_load_known_peers() {
+        let path = temp_path();
+        let hour = 3600;
+
+        // A node without a peers file has no known peers.
+        assert!(KnownPeers::load_at(&path, Duration::from_secs(24 * hour as u64), 0).unwrap().is_empty());
+
+        let mut known_peers = KnownPeers::default();
+        known_peers.insert(peer(4130), Some(NodeType::Client), 100 * hour);
+        known_peers.insert(peer(4131), Some(NodeType::Beacon), 90 * hour);
+        known_peers.insert(peer(4132), None, 50 * hour);
+        // A peer without a known node type keeps its previous node type.
+        known_peers.insert(peer(4131), None, 95 * hour);
+        known_peers.save(&path).unwrap();
+
+        // The peers that were not seen within the maximum age are dropped on load.
+        let loaded = KnownPeers::load_at(&path, Duration::from_secs(24 * hour as u64), 110 * hour).unwrap();
+        assert_eq!(loaded.most_recent(), vec![peer(4130), peer(4131)]);
+        assert_eq!(loaded.peers[&peer(4131)], (Some(NodeType::Beacon), 95 * hour));
+        assert!(!loaded.contains(&peer(4132)));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_and_load_bans() {
+        let path = temp_path();
+        let (now, hour) = (OffsetDateTime::now_utc().unix_timestamp(), 3600);
+
+        let mut known_peers = KnownPeers::default();
+        known_peers.insert(peer(4130), Some(NodeType::Client), now);
+        known_peers.insert(peer(4131), Some(NodeType::Client), now);
+        known_peers.ban(peer(4130).ip(), now + hour);
+        known_peers.ban("127.0.0.2".parse().unwrap(), now - hour);
+
+        // Banning a peer IP removes all of its known peers.
+        assert!(known_peers.is_empty());
+        assert!(known_peers.is_banned_at(&peer(4130).ip(), now));
+        assert!(!known_peers.is_banned_at(&peer(4130).ip(), now + hour));
+        known_peers.save(&path).unwrap();
+
+        // The expired bans are dropped on load, and the bans are kept when the known peers are cleared.
+        let mut loaded = KnownPeers::load_at(&path, Duration::from_secs(24 * hour as u64), now).unwrap();
+        assert_eq!(loaded.bans_at(now), vec![BannedPeer { peer_ip: peer(4130).ip(), banned_until: now + hour }]);
+        loaded.clear();
+        assert!(loaded.is_banned_at(&peer(4130).ip(), now));
+
+        assert!(loaded.unban(&peer(4130).ip()));
+        assert!(!loaded.is_banned_at(&peer(4130).ip(), now));
+        assert_eq!(loaded.clear_bans(), 0);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_known_peers_limit() {
+        let mut known_peers = KnownPeers::default();
+        for i in 0..=MAXIMUM_KNOWN_PEERS {
+            known_peers.insert(peer(i as u16), None, i as i64);
+        }
+
+        // The least recently seen peer is dropped.
+        assert_eq!(known_peers.len(), MAXIMUM_KNOWN_PEERS);
+        assert!(!known_peers.contains(&peer(0)));
+        assert_eq!(known_peers.most_recent()[0], peer(MAXIMUM_KNOWN_PEERS as u16));
+    }
+}
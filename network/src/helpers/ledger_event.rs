@@ -0,0 +1,222 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::prelude::Network;
+
+use serde::{Deserialize, Serialize};
+
+///
+/// An event published by the ledger as its canon chain changes.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEvent<N: Network> {
+    /// A block extended the canon chain.
+    BlockAdded { block_height: u32, block_hash: N::BlockHash },
+    /// The canon chain switched from the disconnected blocks to the connected blocks,
+    /// which both extend the common ancestor. The blocks are listed as (block_height, block_hash), in ascending order.
+    Reorg {
+        common_ancestor: (u32, N::BlockHash),
+        disconnected: Vec<(u32, N::BlockHash)>,
+        connected: Vec<(u32, N::BlockHash)>,
+    },
+}
+
+/// A reorg of the canon chain that is in progress.
+#[derive(Debug)]
+struct PendingReorg<N: Network> {
+    common_ancestor: (u32, N::BlockHash),
+    disconnected: Vec<(u32, N::BlockHash)>,
+    connected: Vec<(u32, N::BlockHash)>,
+}
+
+///
+/// Tracks the changes to the canon chain, to publish them as ledger events.
+///
+/// As the ledger switches to a fork by reverting to the common ancestor and then adding the blocks of the fork,
+/// a reorg is published once the new chain reaches the height of the disconnected chain.
+///
+#[derive(Debug)]
+pub struct ReorgTracker<N: Network> {
+    pending_reorg: Option<PendingReorg<N>>,
+}
+
+impl<N: Network> Default for ReorgTracker<N> {
+    fn default() -> Self {
+        Self { pending_reorg: None }
+    }
+}
+
+impl<N: Network> ReorgTracker<N> {
+    /// Returns `true` if a reorg is in progress.
+    pub fn is_pending(&self) -> bool {
+        self.pending_reorg.is_some()
+    }
+
+    ///
+    /// Records that the ledger reverted to the given common ancestor, removing the given blocks.
+    ///
+    pub fn revert(&mut self, common_ancestor: (u32, N::BlockHash), mut removed_blocks: Vec<(u32, N::BlockHash)>) {
+        if removed_blocks.is_empty() {
+            return;
+        }
+        removed_blocks.sort_by_key(|(block_height, _)| *block_height);
+
+        match &mut self.pending_reorg {
+            // The revert only removes blocks that were connected during the pending reorg.
+            Some(pending_reorg) if common_ancestor.0 >= pending_reorg.common_ancestor.0 => {
+                pending_reorg.connected.retain(|(block_height, _)| *block_height <= common_ancestor.0);
+            }
+            // The revert goes below the common ancestor of the pending reorg, so the reorg now starts deeper.
+            Some(pending_reorg) => {
+                let previous_ancestor_height = pending_reorg.common_ancestor.0;
+                let mut disconnected: Vec<_> =
+                    removed_blocks.into_iter().filter(|(block_height, _)| *block_height <= previous_ancestor_height).collect();
+                disconnected.append(&mut pending_reorg.disconnected);
+
+                *pending_reorg = PendingReorg { common_ancestor, disconnected, connected: Vec::new() };
+            }
+            None => {
+                self.pending_reorg = Some(PendingReorg {
+                    common_ancestor,
+                    disconnected: removed_blocks,
+                    connected: Vec::new(),
+                })
+            }
+        }
+    }
+
+    ///
+    /// Records that the given block extended the canon chain, returning the ledger events to publish.
+    ///
+    pub fn add(&mut self, block_height: u32, block_hash: N::BlockHash) -> Vec<LedgerEvent<N>> {
+        let mut pending_reorg = match self.pending_reorg.take() {
+            Some(pending_reorg) => pending_reorg,
+            None => return vec![LedgerEvent::BlockAdded { block_height, block_hash }],
+        };
+
+        pending_reorg.connected.push((block_height, block_hash));
+
+        // Wait until the new chain reaches the height of the disconnected chain.
+        let disconnected_height = pending_reorg.disconnected.last().map(|(block_height, _)| *block_height).unwrap_or(0);
+        if block_height < disconnected_height {
+            self.pending_reorg = Some(pending_reorg);
+            return vec![];
+        }
+
+        // Advance the common ancestor past the blocks that were reconnected unchanged.
+        let PendingReorg { mut common_ancestor, mut disconnected, mut connected } = pending_reorg;
+        let num_unchanged = disconnected.iter().zip(connected.iter()).take_while(|(a, b)| a == b).count();
+        if num_unchanged > 0 {
+            common_ancestor = disconnected[num_unchanged - 1];
+            disconnected.drain(..num_unchanged);
+            connected.drain(..num_unchanged);
+        }
+
+        match disconnected.is_empty() {
+            // The revert was undone, so the remaining blocks simply extend the canon chain.
+            true => connected
+                .into_iter()
+                .map(|(block_height, block_hash)| LedgerEvent::BlockAdded { block_height, block_hash })
+                .collect(),
+            false => vec![LedgerEvent::Reorg { common_ancestor, disconnected, connected }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{FromBytes, Testnet3};
+
+    type CurrentNetwork = Testnet3;
+    type BlockHash = <CurrentNetwork as Network>::BlockHash;
+
+    /// Returns a sample block at the given height, on the given fork.
+    fn block(block_height: u32, fork: u8) -> (u32, BlockHash) {
+        let mut bytes = [0u8; 32];
+        bytes[..4].copy_from_slice(&block_height.to_le_bytes());
+        bytes[4] = fork;
+        (block_height, BlockHash::from_bytes_le(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_block_added() {
+        let mut tracker = ReorgTracker::<CurrentNetwork>::default();
+        let (block_height, block_hash) = block(1, 0);
+        assert_eq!(tracker.add(block_height, block_hash), vec![LedgerEvent::BlockAdded { block_height, block_hash }]);
+        assert!(!tracker.is_pending());
+    }
+
+    #[test]
+    fn test_reorg() {
+        let mut tracker = ReorgTracker::<CurrentNetwork>::default();
+
+        // Revert from block 12 to block 10.
+        tracker.revert(block(10, 0), vec![block(12, 0), block(11, 0)]);
+        assert!(tracker.is_pending());
+
+        // The reorg is published once the fork reaches block 12.
+        assert!(tracker.add(block(11, 1).0, block(11, 1).1).is_empty());
+        assert_eq!(tracker.add(block(12, 1).0, block(12, 1).1), vec![LedgerEvent::Reorg {
+            common_ancestor: block(10, 0),
+            disconnected: vec![block(11, 0), block(12, 0)],
+            connected: vec![block(11, 1), block(12, 1)],
+        }]);
+        assert!(!tracker.is_pending());
+    }
+
+    #[test]
+    fn test_undone_revert() {
+        let mut tracker = ReorgTracker::<CurrentNetwork>::default();
+
+        // Revert from block 12 to block 10, and then reconnect block 11 and a new block 12.
+        tracker.revert(block(10, 0), vec![block(11, 0), block(12, 0)]);
+        assert!(tracker.add(block(11, 0).0, block(11, 0).1).is_empty());
+        assert_eq!(tracker.add(block(12, 1).0, block(12, 1).1), vec![LedgerEvent::Reorg {
+            common_ancestor: block(11, 0),
+            disconnected: vec![block(12, 0)],
+            connected: vec![block(12, 1)],
+        }]);
+
+        // Revert from block 12 to block 11, and then reconnect the same block 12.
+        tracker.revert(block(11, 0), vec![block(12, 1)]);
+        assert_eq!(tracker.add(block(12, 1).0, block(12, 1).1), vec![LedgerEvent::BlockAdded {
+            block_height: 12,
+            block_hash: block(12, 1).1
+        }]);
+    }
+
+    #[test]
+    fn test_nested_revert() {
+        let mut tracker = ReorgTracker::<CurrentNetwork>::default();
+
+        // Revert from block 12 to block 10, connect block 11, and then revert further to block 8.
+        tracker.revert(block(10, 0), vec![block(11, 0), block(12, 0)]);
+        assert!(tracker.add(block(11, 1).0, block(11, 1).1).is_empty());
+        tracker.revert(block(8, 0), vec![block(9, 0), block(10, 0), block(11, 1)]);
+
+        // The reorg now disconnects the original chain from block 9.
+        for (block_height, fork) in [(9, 1), (10, 1), (11, 1)] {
+            assert!(tracker.add(block_height, block(block_height, fork).1).is_empty());
+        }
+        assert_eq!(tracker.add(block(12, 1).0, block(12, 1).1), vec![LedgerEvent::Reorg {
+            common_ancestor: block(8, 0),
+            disconnected: vec![block(9, 0), block(10, 0), block(11, 0), block(12, 0)],
+            connected: vec![block(9, 1), block(10, 1), block(11, 1), block(12, 1)],
+        }]);
+    }
+}
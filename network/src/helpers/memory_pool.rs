@@ -0,0 +1,118 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::prelude::{Network, Transaction};
+
+use anyhow::{ensure, Result};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+
+/// The maximum number of unconfirmed transactions in the memory pool.
+pub const MAXIMUM_MEMORY_POOL_TRANSACTIONS: usize = 10_000;
+
+///
+/// The memory pool of unconfirmed transactions, which are not in the canon chain,
+/// ordered by the time they were added.
+///
+#[derive(Debug)]
+pub struct MemoryPool<N: Network> {
+    /// The unconfirmed transactions, and the set of their transaction IDs.
+    transactions: RwLock<(Vec<Transaction<N>>, HashSet<N::TransactionID>)>,
+}
+
+impl<N: Network> Default for MemoryPool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Network> MemoryPool<N> {
+    /// Initializes a new, empty memory pool.
+    pub fn new() -> Self {
+        Self {
+            transactions: Default::default(),
+        }
+    }
+
+    /// Returns the number of unconfirmed transactions in the memory pool.
+    pub fn len(&self) -> usize {
+        self.transactions.read().0.len()
+    }
+
+    /// Returns `true` if there are no unconfirmed transactions in the memory pool.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.read().0.is_empty()
+    }
+
+    /// Returns `true` if the given transaction ID is in the memory pool.
+    pub fn contains_transaction(&self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.read().1.contains(transaction_id)
+    }
+
+    /// Returns the unconfirmed transactions in the memory pool, in the order they were added.
+    pub fn transactions(&self) -> Vec<Transaction<N>> {
+        self.transactions.read().0.clone()
+    }
+
+    ///
+    /// Adds the given unconfirmed transaction to the memory pool.
+    /// The transaction must be new, and the memory pool must not be full.
+    ///
+    pub fn add_transaction(&self, transaction: &Transaction<N>) -> Result<()> {
+        let mut transactions = self.transactions.write();
+        ensure!(!transactions.1.contains(&transaction.id()), "Transaction {} is already in the memory pool", transaction.id());
+        ensure!(transactions.0.len() < MAXIMUM_MEMORY_POOL_TRANSACTIONS, "The memory pool is full");
+
+        transactions.1.insert(transaction.id());
+        transactions.0.push(transaction.clone());
+        Ok(())
+    }
+
+    /// Removes the given transactions from the memory pool, such as once they are added to the canon chain.
+    pub fn remove_transactions<'a>(&self, removed_transactions: impl IntoIterator<Item = &'a Transaction<N>>) {
+        let removed_transaction_ids = removed_transactions.into_iter().map(|transaction| transaction.id()).collect::<HashSet<_>>();
+
+        let (transactions, transaction_ids) = &mut *self.transactions.write();
+        transactions.retain(|transaction| !removed_transaction_ids.contains(&transaction.id()));
+        transaction_ids.retain(|transaction_id| !removed_transaction_ids.contains(transaction_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::{sample_block, sample_canon_blocks, CurrentNetwork};
+
+    #[test]
+    fn test_add_and_remove_transactions() {
+        let memory_pool = MemoryPool::<CurrentNetwork>::new();
+        let transaction = sample_block().transactions().iter().map(|(_, transaction)| transaction.clone()).next().unwrap();
+        let other_transaction = sample_canon_blocks(1)[0].transactions().iter().map(|(_, transaction)| transaction.clone()).next().unwrap();
+
+        // A transaction is added once, in the order it was received.
+        memory_pool.add_transaction(&transaction).unwrap();
+        assert!(memory_pool.add_transaction(&transaction).is_err());
+        memory_pool.add_transaction(&other_transaction).unwrap();
+        assert_eq!(memory_pool.transactions(), vec![transaction.clone(), other_transaction.clone()]);
+
+        // A removed transaction may be added again, such as when its block is reverted.
+        memory_pool.remove_transactions([&transaction]);
+        assert!(!memory_pool.contains_transaction(&transaction.id()));
+        assert!(memory_pool.contains_transaction(&other_transaction.id()));
+        memory_pool.add_transaction(&transaction).unwrap();
+        assert_eq!(memory_pool.len(), 2);
+    }
+}
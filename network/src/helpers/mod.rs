@@ -14,11 +14,74 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+mod block_batch;
+pub use block_batch::*;
+
+mod block_chunks;
+pub use block_chunks::*;
+
 mod block_request;
 pub use block_request::*;
 
 // mod block_requests;
 // pub use block_requests::*;
 
+mod canonical_addr;
+pub use canonical_addr::*;
+
 mod circular_map;
 pub use circular_map::*;
+
+mod connection_limits;
+pub use connection_limits::*;
+
+mod dns_seeds;
+pub use dns_seeds::*;
+
+mod header_chain;
+pub use header_chain::*;
+
+mod known_peers;
+pub use known_peers::*;
+
+mod ledger_event;
+pub use ledger_event::*;
+
+mod memory_pool;
+pub use memory_pool::*;
+
+mod orphan_pool;
+pub use orphan_pool::*;
+
+mod peer_latency;
+pub use peer_latency::*;
+
+mod peer_scores;
+pub use peer_scores::*;
+
+mod peer_stats;
+pub use peer_stats::*;
+
+mod port_mapping;
+pub use port_mapping::*;
+
+mod priority_channel;
+pub use priority_channel::*;
+
+mod reorg_limit;
+pub use reorg_limit::*;
+
+mod served_blocks;
+pub use served_blocks::*;
+
+mod seen_cache;
+pub use seen_cache::*;
+
+mod sync_status;
+pub use sync_status::*;
+
+#[cfg(test)]
+pub(crate) mod test_helpers;
+
+mod trusted_peers;
+pub use trusted_peers::*;
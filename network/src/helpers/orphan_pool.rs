@@ -0,0 +1,230 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::{prelude::Network, Block};
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The maximum number of orphan blocks that can be held by the ledger.
+pub const MAXIMUM_ORPHAN_BLOCKS: usize = 100;
+/// The maximum number of orphan blocks that can be held from a single peer.
+pub const MAXIMUM_ORPHAN_BLOCKS_PER_PEER: usize = 10;
+/// The duration after which an orphan block is evicted.
+const ORPHAN_BLOCK_EXPIRY: Duration = Duration::from_secs(300);
+/// The number of blocks below the latest block height at which an orphan block is evicted.
+const ORPHAN_BLOCK_HEIGHT_MARGIN: u32 = 10;
+
+/// An orphan block, along with the peer that sent it and the time it was received.
+#[derive(Clone, Debug)]
+struct OrphanBlock<N: Network> {
+    block: Block<N>,
+    peer_ip: SocketAddr,
+    timestamp: Instant,
+}
+
+///
+/// A bounded pool of blocks whose previous block is not yet known to the ledger,
+/// keyed by their previous block hash.
+///
+#[derive(Debug)]
+pub struct OrphanPool<N: Network> {
+    /// The map of previous block hashes to their orphan blocks.
+    orphans: HashMap<N::BlockHash, Vec<OrphanBlock<N>>>,
+    /// The map of each peer to the number of orphan blocks held from them.
+    orphans_per_peer: HashMap<SocketAddr, usize>,
+    /// The maximum number of orphan blocks in the pool.
+    maximum_orphans: usize,
+    /// The maximum number of orphan blocks in the pool from a single peer.
+    maximum_orphans_per_peer: usize,
+}
+
+impl<N: Network> Default for OrphanPool<N> {
+    fn default() -> Self {
+        Self::new(MAXIMUM_ORPHAN_BLOCKS, MAXIMUM_ORPHAN_BLOCKS_PER_PEER)
+    }
+}
+
+impl<N: Network> OrphanPool<N> {
+    /// Initializes a new orphan pool with the given limits.
+    pub fn new(maximum_orphans: usize, maximum_orphans_per_peer: usize) -> Self {
+        Self {
+            orphans: Default::default(),
+            orphans_per_peer: Default::default(),
+            maximum_orphans,
+            maximum_orphans_per_peer,
+        }
+    }
+
+    /// Returns the number of orphan blocks in the pool.
+    pub fn len(&self) -> usize {
+        self.orphans.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the pool contains no orphan blocks.
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty()
+    }
+
+    /// Returns `true` if the pool contains the given block hash.
+    pub fn contains(&self, block_hash: &N::BlockHash) -> bool {
+        self.orphans.values().flatten().any(|orphan| orphan.block.hash() == *block_hash)
+    }
+
+    /// Returns the orphan blocks in the pool.
+    pub fn blocks(&self) -> impl Iterator<Item = &Block<N>> {
+        self.orphans.values().flatten().map(|orphan| &orphan.block)
+    }
+
+    ///
+    /// Adds the given block from the given peer to the pool, evicting the oldest orphan block if the pool is full.
+    ///
+    /// Returns `false` if the block is already in the pool, or if the peer has reached its limit of orphan blocks.
+    ///
+    pub fn insert(&mut self, peer_ip: SocketAddr, block: Block<N>) -> bool {
+        // Ensure the block is new, and the peer has not reached its limit.
+        if self.contains(&block.hash()) || self.orphans_per_peer.get(&peer_ip).copied().unwrap_or(0) >= self.maximum_orphans_per_peer {
+            return false;
+        }
+
+        // If the pool is full, evict the oldest orphan block.
+        if self.len() >= self.maximum_orphans {
+            let oldest = self
+                .orphans
+                .values()
+                .flatten()
+                .min_by_key(|orphan| orphan.timestamp)
+                .map(|orphan| orphan.block.hash());
+            if let Some(block_hash) = oldest {
+                self.remove_where(|orphan| orphan.block.hash() == block_hash);
+            }
+        }
+
+        *self.orphans_per_peer.entry(peer_ip).or_default() += 1;
+        self.orphans.entry(block.previous_hash()).or_default().push(OrphanBlock {
+            block,
+            peer_ip,
+            timestamp: Instant::now(),
+        });
+        true
+    }
+
+    /// Removes and returns the orphan blocks whose previous block hash is the given block hash.
+    pub fn remove_children(&mut self, block_hash: &N::BlockHash) -> Vec<Block<N>> {
+        let children = self.orphans.remove(block_hash).unwrap_or_default();
+        for orphan in &children {
+            self.decrement_peer(orphan.peer_ip);
+        }
+        children.into_iter().map(|orphan| orphan.block).collect()
+    }
+
+    ///
+    /// Removes the orphan blocks that have expired, or that are buried too far below the given latest block height
+    /// to ever be connected, returning the number of evicted orphan blocks.
+    ///
+    pub fn remove_expired(&mut self, latest_block_height: u32) -> usize {
+        let minimum_block_height = latest_block_height.saturating_sub(ORPHAN_BLOCK_HEIGHT_MARGIN);
+        self.remove_where(|orphan| {
+            orphan.timestamp.elapsed() > ORPHAN_BLOCK_EXPIRY || orphan.block.header().height() < minimum_block_height
+        })
+    }
+
+    /// Removes all orphan blocks from the pool.
+    pub fn clear(&mut self) {
+        self.orphans.clear();
+        self.orphans_per_peer.clear();
+    }
+
+    /// Removes the orphan blocks that satisfy the given predicate, returning the number of removed orphan blocks.
+    fn remove_where<F: Fn(&OrphanBlock<N>) -> bool>(&mut self, predicate: F) -> usize {
+        let mut removed_peers = Vec::new();
+        self.orphans.retain(|_, orphans| {
+            orphans.retain(|orphan| match predicate(orphan) {
+                true => {
+                    removed_peers.push(orphan.peer_ip);
+                    false
+                }
+                false => true,
+            });
+            !orphans.is_empty()
+        });
+
+        let num_removed = removed_peers.len();
+        for peer_ip in removed_peers {
+            self.decrement_peer(peer_ip);
+        }
+        num_removed
+    }
+
+    /// Decrements the number of orphan blocks held from the given peer.
+    fn decrement_peer(&mut self, peer_ip: SocketAddr) {
+        if let Some(count) = self.orphans_per_peer.get_mut(&peer_ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.orphans_per_peer.remove(&peer_ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::{sample_block, CurrentNetwork};
+
+    #[test]
+    fn test_orphan_pool() {
+        let block = sample_block();
+        let peer_ip = "127.0.0.1:4130".parse().unwrap();
+
+        let mut orphan_pool = OrphanPool::<CurrentNetwork>::default();
+        assert!(orphan_pool.insert(peer_ip, block.clone()));
+        assert!(orphan_pool.contains(&block.hash()));
+        assert_eq!(orphan_pool.len(), 1);
+
+        // A duplicate block is rejected.
+        assert!(!orphan_pool.insert(peer_ip, block.clone()));
+        assert_eq!(orphan_pool.len(), 1);
+
+        // The orphan block is removed once its previous block is known.
+        assert_eq!(orphan_pool.remove_children(&block.previous_hash()), vec![block]);
+        assert!(orphan_pool.is_empty());
+    }
+
+    #[test]
+    fn test_orphan_pool_limits() {
+        let block = sample_block();
+        let peer_ip = "127.0.0.1:4130".parse().unwrap();
+
+        // A peer cannot exceed its limit of orphan blocks.
+        let mut orphan_pool = OrphanPool::<CurrentNetwork>::new(MAXIMUM_ORPHAN_BLOCKS, 0);
+        assert!(!orphan_pool.insert(peer_ip, block.clone()));
+        assert!(orphan_pool.is_empty());
+
+        // An orphan block buried below the latest block height is evicted.
+        let mut orphan_pool = OrphanPool::<CurrentNetwork>::default();
+        assert!(orphan_pool.insert(peer_ip, block.clone()));
+        assert_eq!(orphan_pool.remove_expired(ORPHAN_BLOCK_HEIGHT_MARGIN), 0);
+        assert_eq!(orphan_pool.remove_expired(ORPHAN_BLOCK_HEIGHT_MARGIN + 1), 1);
+        assert!(orphan_pool.is_empty());
+
+        // The peer may send orphan blocks again once its orphan blocks are evicted.
+        assert!(orphan_pool.insert(peer_ip, block));
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The minimum message version of a peer for it to answer a `Ping` with a `Pong` that echoes its nonce.
+pub const PING_NONCE_MESSAGE_VERSION: u32 = 3;
+/// The default maximum duration to wait for the `Pong` of a `Ping`, before the `Ping` counts as missed.
+pub const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(30);
+/// The number of consecutive missed pongs after which a peer is disconnected.
+pub const MAXIMUM_MISSED_PONGS: u32 = 3;
+/// The weight of a new sample in the moving average of the round-trip time of a peer.
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// The minimum sync weight of a peer with a high round-trip time, or that is unresponsive.
+const MINIMUM_LATENCY_WEIGHT: f64 = 0.25;
+
+///
+/// The round-trip time of a peer, as reported by the RPC server.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerLatency {
+    /// The IP address of the peer.
+    pub peer_ip: SocketAddr,
+    /// The moving average of the time from a `Ping` to its `Pong`, in milliseconds.
+    pub average_rtt_in_ms: Option<f64>,
+    /// The number of consecutive pings of the peer that were not answered within the timeout.
+    pub missed_pongs: u32,
+}
+
+/// The pings of a single peer.
+#[derive(Clone, Debug, Default)]
+struct PeerPings {
+    /// The nonce and the time of the outstanding `Ping`, if any.
+    pending: Option<(u64, Instant)>,
+    /// The time of the latest `Ping`.
+    last_ping_at: Option<Instant>,
+    /// The moving average of the round-trip time, in seconds.
+    rtt_in_secs: Option<f64>,
+    /// The number of consecutive missed pongs.
+    missed_pongs: u32,
+}
+
+///
+/// The round-trip times of the connected peers, which are measured by pairing each `Ping` with
+/// the `Pong` that echoes its nonce. A `Ping` that is not answered within the timeout counts as
+/// missed, and a peer that misses `MAXIMUM_MISSED_PONGS` consecutive pongs is unresponsive.
+///
+#[derive(Debug, Default)]
+pub struct PeerLatencies {
+    /// The map of each peer to its pings.
+    peers: HashMap<SocketAddr, PeerPings>,
+}
+
+impl PeerLatencies {
+    ///
+    /// Returns the nonce of a new `Ping` to the given peer, if the peer has no outstanding `Ping`,
+    /// and its latest `Ping` was sent at least the given interval ago, and records the `Ping` as sent.
+    ///
+    pub fn next_ping(&mut self, peer_ip: SocketAddr, interval: Duration) -> Option<u64> {
+        self.next_ping_at(Instant::now(), peer_ip, interval, rand::random())
+    }
+
+    /// Records the `Pong` of the given peer, returning the round-trip time if it answers the outstanding `Ping`.
+    pub fn record_pong(&mut self, peer_ip: SocketAddr, nonce: u64) -> Option<Duration> {
+        self.record_pong_at(Instant::now(), peer_ip, nonce)
+    }
+
+    ///
+    /// Counts the outstanding pings that are older than the given timeout as missed,
+    /// and returns the peers that have missed `MAXIMUM_MISSED_PONGS` consecutive pongs.
+    ///
+    pub fn expire_pings(&mut self, timeout: Duration) -> Vec<SocketAddr> {
+        self.expire_pings_at(Instant::now(), timeout)
+    }
+
+    /// Removes the given peer, once it is disconnected.
+    pub fn remove(&mut self, peer_ip: &SocketAddr) {
+        self.peers.remove(peer_ip);
+    }
+
+    /// Returns the moving average of the round-trip time of the given peer, if it answered a `Ping`.
+    pub fn average_rtt(&self, peer_ip: &SocketAddr) -> Option<Duration> {
+        self.peers.get(peer_ip)?.rtt_in_secs.map(Duration::from_secs_f64)
+    }
+
+    /// Returns `true` if the given peer answered its latest expired `Ping`, or has not been pinged yet.
+    pub fn is_responsive(&self, peer_ip: &SocketAddr) -> bool {
+        self.peers.get(peer_ip).map_or(true, |pings| pings.missed_pongs == 0)
+    }
+
+    /// Returns the round-trip time of the given peer.
+    pub fn get(&self, peer_ip: SocketAddr) -> PeerLatency {
+        let pings = self.peers.get(&peer_ip);
+        PeerLatency {
+            peer_ip,
+            average_rtt_in_ms: pings.and_then(|pings| pings.rtt_in_secs).map(|rtt| rtt * 1000.0),
+            missed_pongs: pings.map_or(0, |pings| pings.missed_pongs),
+        }
+    }
+
+    ///
+    /// Returns the relative share of the sync work for the given peer, which is `1.0` for a peer
+    /// with an average or lower round-trip time, and decreases for a peer with a higher one.
+    ///
+    pub fn weight(&self, peer_ip: &SocketAddr) -> f64 {
+        let pings = match self.peers.get(peer_ip) {
+            Some(pings) => pings,
+            None => return 1.0,
+        };
+        if pings.missed_pongs > 0 {
+            return MINIMUM_LATENCY_WEIGHT;
+        }
+        match (pings.rtt_in_secs, self.average_rtt_in_secs()) {
+            (Some(rtt), Some(average)) if rtt > 0.0 => (average / rtt).clamp(MINIMUM_LATENCY_WEIGHT, 1.0),
+            _ => 1.0,
+        }
+    }
+
+    ///
+    /// Returns the given peers in the order in which to evict them: the unresponsive peers first,
+    /// then the peers with the lowest value, as given by `value_of`, and then the peers with the
+    /// highest round-trip time, before the peers whose round-trip time is unknown.
+    ///
+    pub fn eviction_order<F: Fn(&SocketAddr) -> f64>(&self, mut peer_ips: Vec<SocketAddr>, value_of: F) -> Vec<SocketAddr> {
+        let missed_pongs = |peer_ip: &SocketAddr| self.peers.get(peer_ip).map_or(0, |pings| pings.missed_pongs);
+        let rtt = |peer_ip: &SocketAddr| self.peers.get(peer_ip).and_then(|pings| pings.rtt_in_secs);
+        peer_ips.sort_by(|a, b| {
+            missed_pongs(b)
+                .cmp(&missed_pongs(a))
+                .then_with(|| value_of(a).partial_cmp(&value_of(b)).unwrap_or(Ordering::Equal))
+                .then_with(|| rtt(b).partial_cmp(&rtt(a)).unwrap_or(Ordering::Equal))
+        });
+        peer_ips
+    }
+
+    /// Returns the number of peers with pings.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Returns `true` if no peer has pings.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    fn next_ping_at(&mut self, now: Instant, peer_ip: SocketAddr, interval: Duration, nonce: u64) -> Option<u64> {
+        let pings = self.peers.entry(peer_ip).or_default();
+        let is_due = match pings.last_ping_at {
+            Some(last_ping_at) => now.saturating_duration_since(last_ping_at) >= interval,
+            None => true,
+        };
+        match pings.pending.is_none() && is_due {
+            true => {
+                pings.pending = Some((nonce, now));
+                pings.last_ping_at = Some(now);
+                Some(nonce)
+            }
+            false => None,
+        }
+    }
+
+    fn record_pong_at(&mut self, now: Instant, peer_ip: SocketAddr, nonce: u64) -> Option<Duration> {
+        let pings = self.peers.get_mut(&peer_ip)?;
+        let sent_at = match pings.pending {
+            Some((pending_nonce, sent_at)) if pending_nonce == nonce => sent_at,
+            // A stale or unsolicited `Pong` is ignored.
+            _ => return None,
+        };
+        pings.pending = None;
+        pings.missed_pongs = 0;
+
+        let rtt = now.saturating_duration_since(sent_at);
+        pings.rtt_in_secs = Some(match pings.rtt_in_secs {
+            Some(average) => average + (rtt.as_secs_f64() - average) * LATENCY_SMOOTHING,
+            None => rtt.as_secs_f64(),
+        });
+        Some(rtt)
+    }
+
+    fn expire_pings_at(&mut self, now: Instant, timeout: Duration) -> Vec<SocketAddr> {
+        let mut unresponsive_peers = Vec::new();
+        for (peer_ip, pings) in self.peers.iter_mut() {
+            if let Some((_, sent_at)) = pings.pending {
+                if now.saturating_duration_since(sent_at) >= timeout {
+                    pings.pending = None;
+                    pings.missed_pongs = pings.missed_pongs.saturating_add(1);
+                    if pings.missed_pongs >= MAXIMUM_MISSED_PONGS {
+                        unresponsive_peers.push(*peer_ip);
+                    }
+                }
+            }
+        }
+        unresponsive_peers.sort();
+        unresponsive_peers
+    }
+
+    /// Returns the average round-trip time of the peers that answered a `Ping`, if any.
+    fn average_rtt_in_secs(&self) -> Option<f64> {
+        let rtts = self.peers.values().filter_map(|pings| pings.rtt_in_secs).collect::<Vec<_>>();
+        match rtts.is_empty() {
+            true => None,
+            false => Some(rtts.iter().sum::<f64>() / rtts.len() as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::peer;
+
+    #[test]
+    fn test_round_trip_time() {
+        let mut latencies = PeerLatencies::default();
+        let (interval, now) = (Duration::from_secs(60), Instant::now());
+
+        // A ping is sent immediately, and not again while it is outstanding.
+        assert_eq!(latencies.next_ping_at(now, peer(4130), interval, 7), Some(7));
+        assert_eq!(latencies.next_ping_at(now + 2 * interval, peer(4130), interval, 8), None);
+
+        // A pong with another nonce is ignored, and the matching pong records the round-trip time.
+        assert_eq!(latencies.record_pong_at(now + Duration::from_millis(100), peer(4130), 8), None);
+        assert_eq!(latencies.record_pong_at(now + Duration::from_millis(200), peer(4130), 7), Some(Duration::from_millis(200)));
+        assert_eq!(latencies.get(peer(4130)).average_rtt_in_ms, Some(200.0));
+
+        // The next ping is sent after the interval, and its round-trip time is averaged in.
+        assert_eq!(latencies.next_ping_at(now + Duration::from_secs(30), peer(4130), interval, 9), None);
+        let later = now + interval;
+        assert_eq!(latencies.next_ping_at(later, peer(4130), interval, 9), Some(9));
+        latencies.record_pong_at(later + Duration::from_millis(1200), peer(4130), 9);
+        let average_rtt_in_ms = latencies.get(peer(4130)).average_rtt_in_ms.unwrap();
+        assert!((average_rtt_in_ms - 400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_missed_pongs() {
+        let mut latencies = PeerLatencies::default();
+        let (interval, timeout, mut now) = (Duration::from_secs(60), Duration::from_secs(30), Instant::now());
+
+        // Each ping that times out counts as missed, until the peer is unresponsive.
+        for missed_pongs in 1..=MAXIMUM_MISSED_PONGS {
+            assert!(latencies.next_ping_at(now, peer(4130), interval, missed_pongs as u64).is_some());
+            assert!(latencies.expire_pings_at(now + timeout / 2, timeout).is_empty());
+            let unresponsive_peers = latencies.expire_pings_at(now + timeout, timeout);
+            assert_eq!(latencies.get(peer(4130)).missed_pongs, missed_pongs);
+            assert!(!latencies.is_responsive(&peer(4130)));
+            assert_eq!(unresponsive_peers.is_empty(), missed_pongs < MAXIMUM_MISSED_PONGS);
+            now += interval;
+        }
+
+        // A late pong is ignored, and a timely pong resets the missed pongs.
+        assert_eq!(latencies.record_pong_at(now, peer(4130), 1), None);
+        assert_eq!(latencies.next_ping_at(now, peer(4130), interval, 42), Some(42));
+        assert!(latencies.record_pong_at(now + Duration::from_millis(50), peer(4130), 42).is_some());
+        assert!(latencies.is_responsive(&peer(4130)));
+    }
+
+    #[test]
+    fn test_weight_and_eviction_order() {
+        let mut latencies = PeerLatencies::default();
+        let (interval, now) = (Duration::from_secs(60), Instant::now());
+        for (port, rtt_in_ms) in [(4130, 100), (4131, 100), (4132, 1000)] {
+            latencies.next_ping_at(now, peer(port), interval, port as u64);
+            latencies.record_pong_at(now + Duration::from_millis(rtt_in_ms), peer(port), port as u64);
+        }
+        latencies.next_ping_at(now, peer(4133), interval, 4133);
+        latencies.expire_pings_at(now + DEFAULT_PONG_TIMEOUT, DEFAULT_PONG_TIMEOUT);
+
+        // The peers with a higher than average round-trip time, or that are unresponsive, are assigned less sync work.
+        assert_eq!(latencies.weight(&peer(4130)), 1.0);
+        assert!(latencies.weight(&peer(4132)) < 1.0);
+        assert_eq!(latencies.weight(&peer(4133)), MINIMUM_LATENCY_WEIGHT);
+        assert_eq!(latencies.weight(&peer(4134)), 1.0);
+
+        // The unresponsive peers are evicted first, then the low-value peers, and then the high-latency peers.
+        let peer_ips = vec![peer(4130), peer(4131), peer(4132), peer(4133), peer(4134)];
+        let value_of = |peer_ip: &SocketAddr| if *peer_ip == peer(4131) { -10.0 } else { 0.0 };
+        let eviction_order = latencies.eviction_order(peer_ips, value_of);
+        assert_eq!(eviction_order[..3], [peer(4133), peer(4131), peer(4132)]);
+
+        // A disconnected peer is forgotten.
+        latencies.remove(&peer(4133));
+        assert!(latencies.is_responsive(&peer(4133)));
+        assert_eq!(latencies.len(), 3);
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_environment::Environment;
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// The score magnitude below which the score of a peer is considered neutral, and is forgotten.
+const MINIMUM_PEER_SCORE: f64 = 0.5;
+/// The duration after which the invalid block strikes of a peer have decayed by half.
+pub const INVALID_BLOCK_STRIKE_HALF_LIFE: Duration = Duration::from_secs(60 * 60);
+/// The number of decayed strikes at which a peer remains banned, even after its restriction has expired.
+/// A peer reaches it with a second strike within one half-life of the first.
+pub const MAXIMUM_INVALID_BLOCK_STRIKES: f64 = 1.5;
+/// The number of decayed strikes below which the strikes of a peer are forgotten.
+const MINIMUM_INVALID_BLOCK_STRIKES: f64 = 0.05;
+
+///
+/// A misbehavior of a peer, which lowers its score by the penalty of the environment.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Misbehavior {
+    /// The peer sent a malformed message, or a message that violates the protocol.
+    InvalidMessage,
+    /// The peer failed the handshake.
+    FailedHandshake,
+    /// The peer served an invalid block.
+    InvalidBlock,
+    /// The peer spammed the node with messages or requests.
+    Spam,
+    /// The peer caused a failure in the ledger.
+    Failure,
+}
+
+impl Misbehavior {
+    /// Returns the score penalty of the misbehavior in the given environment.
+    pub fn penalty<E: Environment>(&self) -> u32 {
+        match self {
+            Self::InvalidMessage => E::PEER_PENALTY_INVALID_MESSAGE,
+            Self::FailedHandshake => E::PEER_PENALTY_FAILED_HANDSHAKE,
+            Self::InvalidBlock => E::PEER_PENALTY_INVALID_BLOCK,
+            Self::Spam => E::PEER_PENALTY_SPAM,
+            Self::Failure => E::PEER_PENALTY_FAILURE,
+        }
+    }
+}
+
+///
+/// The score of each peer IP, which is lowered by the penalties of its misbehaviors,
+/// and decays exponentially back toward neutral over time, so that only a peer that
+/// misbehaves repeatedly within a short period reaches the ban threshold.
+///
+/// Each peer IP that served an invalid block also receives a strike, which decays separately,
+/// so that a peer that repeatedly serves invalid blocks is banned for longer than a one-off offender.
+///
+/// The scores and strikes are kept per IP address and across disconnects, so that a peer is unable
+/// to reset them by reconnecting from another port.
+///
+#[derive(Debug)]
+pub struct PeerScores {
+    /// The map of each peer IP to its score, as of the given time.
+    scores: HashMap<IpAddr, (f64, Instant)>,
+    /// The map of each peer IP to its invalid block strikes, as of the given time.
+    strikes: HashMap<IpAddr, (f64, Instant)>,
+    /// The duration after which a score has decayed halfway back to neutral.
+    half_life: Duration,
+    /// The duration after which the strikes have decayed by half.
+    strike_half_life: Duration,
+    /// The penalty at which a peer reaches the ban threshold.
+    ban_threshold: u32,
+}
+
+impl PeerScores {
+    /// Initializes a new tracker of peer scores with the given half-lives and ban threshold.
+    pub fn new(half_life: Duration, strike_half_life: Duration, ban_threshold: u32) -> Self {
+        Self {
+            scores: Default::default(),
+            strikes: Default::default(),
+            half_life,
+            strike_half_life,
+            ban_threshold,
+        }
+    }
+
+    ///
+    /// Lowers the score of the given peer IP by the given penalty, returning `true` if the peer reached the ban threshold.
+    /// The score of a peer that reaches the ban threshold is reset to neutral, as the peer is banned.
+    ///
+    pub fn penalize(&mut self, peer_ip: IpAddr, penalty: u32) -> bool {
+        let score = self.get(&peer_ip) - penalty as f64;
+        match score <= -(self.ban_threshold as f64) {
+            true => {
+                self.scores.remove(&peer_ip);
+                true
+            }
+            false => {
+                self.scores.insert(peer_ip, (score, Instant::now()));
+                false
+            }
+        }
+    }
+
+    /// Returns the decayed score of the given peer IP, which is zero for a neutral peer, and negative otherwise.
+    pub fn get(&self, peer_ip: &IpAddr) -> f64 {
+        match self.scores.get(peer_ip) {
+            Some((score, timestamp)) => decay(*score, timestamp.elapsed(), self.half_life),
+            None => 0.0,
+        }
+    }
+
+    /// Adds an invalid block strike to the given peer IP, returning its decayed number of strikes.
+    pub fn add_strike(&mut self, peer_ip: IpAddr) -> f64 {
+        let strikes = self.strikes(&peer_ip) + 1.0;
+        self.strikes.insert(peer_ip, (strikes, Instant::now()));
+        strikes
+    }
+
+    /// Returns the decayed number of invalid block strikes of the given peer IP.
+    pub fn strikes(&self, peer_ip: &IpAddr) -> f64 {
+        match self.strikes.get(peer_ip) {
+            Some((strikes, timestamp)) => decay(*strikes, timestamp.elapsed(), self.strike_half_life),
+            None => 0.0,
+        }
+    }
+
+    /// Returns `true` if the given peer IP has served too many invalid blocks recently, and remains banned.
+    pub fn is_struck_out(&self, peer_ip: &IpAddr) -> bool {
+        self.strikes(peer_ip) >= MAXIMUM_INVALID_BLOCK_STRIKES
+    }
+
+    /// Forgets the strikes of the given peer IP, such as when its ban is lifted.
+    /// The score of the peer IP is kept, so that a peer that keeps misbehaving is banned again.
+    pub fn remove_strikes(&mut self, peer_ip: &IpAddr) {
+        self.strikes.remove(peer_ip);
+    }
+
+    /// Forgets the strikes of every peer IP.
+    pub fn clear_strikes(&mut self) {
+        self.strikes.clear();
+    }
+
+    /// Removes the peer IPs whose scores and strikes have decayed, returning the number of removed peer IPs.
+    pub fn remove_expired(&mut self) -> usize {
+        let number_of_peers = self.len();
+        let (half_life, strike_half_life) = (self.half_life, self.strike_half_life);
+        self.scores
+            .retain(|_, (score, timestamp)| decay(*score, timestamp.elapsed(), half_life).abs() >= MINIMUM_PEER_SCORE);
+        self.strikes
+            .retain(|_, (strikes, timestamp)| decay(*strikes, timestamp.elapsed(), strike_half_life) >= MINIMUM_INVALID_BLOCK_STRIKES);
+        number_of_peers - self.len()
+    }
+
+    /// Returns the number of peer IPs with a score or strikes.
+    pub fn len(&self) -> usize {
+        self.scores.len() + self.strikes.keys().filter(|peer_ip| !self.scores.contains_key(peer_ip)).count()
+    }
+
+    /// Returns `true` if no peer IP has a score or strikes.
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty() && self.strikes.is_empty()
+    }
+}
+
+/// Returns the given value, after decaying by half every `half_life` for the given duration.
+pub(crate) fn decay(value: f64, elapsed: Duration, half_life: Duration) -> f64 {
+    value * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_penalties_reach_the_ban_threshold() {
+        let peer_ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let other_peer_ip: IpAddr = "127.0.0.2".parse().unwrap();
+
+        let mut scores = PeerScores::new(Duration::from_secs(3600), INVALID_BLOCK_STRIKE_HALF_LIFE, 100);
+        assert!(!scores.penalize(peer_ip, 40));
+        assert!(!scores.penalize(peer_ip, 40));
+        assert!(scores.get(&peer_ip) < -79.0);
+
+        // The scores of each peer IP are counted separately.
+        assert!(!scores.penalize(other_peer_ip, 40));
+
+        // The peer is reset to neutral once it reaches the ban threshold.
+        assert!(scores.penalize(peer_ip, 40));
+        assert_eq!(scores.get(&peer_ip), 0.0);
+        assert_eq!(scores.len(), 1);
+    }
+
+    #[test]
+    fn test_scores_decay_to_neutral() {
+        let peer_ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+        assert_eq!(decay(-80.0, Duration::from_secs(60), Duration::from_secs(60)), -40.0);
+        assert_eq!(decay(-80.0, Duration::from_secs(120), Duration::from_secs(60)), -20.0);
+
+        // Scores with a short half-life decay back toward neutral, so that the same penalties no longer reach the ban threshold.
+        let mut scores = PeerScores::new(Duration::from_millis(10), INVALID_BLOCK_STRIKE_HALF_LIFE, 100);
+        assert!(!scores.penalize(peer_ip, 60));
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!scores.penalize(peer_ip, 60));
+
+        // The score is forgotten once it has decayed to neutral.
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(scores.remove_expired(), 1);
+        assert!(scores.is_empty());
+    }
+
+    #[test]
+    fn test_strikes_accumulate() {
+        let peer_ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let other_peer_ip: IpAddr = "127.0.0.2".parse().unwrap();
+
+        let mut scores = PeerScores::new(Duration::from_secs(3600), INVALID_BLOCK_STRIKE_HALF_LIFE, 100);
+        assert!(scores.add_strike(peer_ip) < MAXIMUM_INVALID_BLOCK_STRIKES);
+        assert!(!scores.is_struck_out(&peer_ip));
+        assert!(scores.add_strike(peer_ip) >= MAXIMUM_INVALID_BLOCK_STRIKES);
+        assert!(scores.is_struck_out(&peer_ip));
+
+        // The strikes of each peer IP are counted separately, and apart from the scores.
+        assert_eq!(scores.strikes(&other_peer_ip), 0.0);
+        assert!(!scores.is_struck_out(&other_peer_ip));
+        assert_eq!(scores.get(&peer_ip), 0.0);
+
+        // Lifting the ban of the peer IP forgets its strikes, and keeps its score.
+        assert!(!scores.penalize(peer_ip, 40));
+        assert_eq!(scores.len(), 1);
+        scores.remove_strikes(&peer_ip);
+        assert!(!scores.is_struck_out(&peer_ip));
+        assert!(scores.get(&peer_ip) < -39.0);
+        assert_eq!(scores.len(), 1);
+    }
+
+    #[test]
+    fn test_strikes_decay() {
+        let peer_ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+        assert_eq!(decay(4.0, INVALID_BLOCK_STRIKE_HALF_LIFE, INVALID_BLOCK_STRIKE_HALF_LIFE), 2.0);
+        assert_eq!(decay(4.0, 2 * INVALID_BLOCK_STRIKE_HALF_LIFE, INVALID_BLOCK_STRIKE_HALF_LIFE), 1.0);
+
+        // Strikes with a short half-life decay below the ban, and are then forgotten.
+        let mut scores = PeerScores::new(Duration::from_secs(3600), Duration::from_millis(10), 100);
+        scores.add_strike(peer_ip);
+        scores.add_strike(peer_ip);
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!scores.is_struck_out(&peer_ip));
+        assert_eq!(scores.remove_expired(), 1);
+        assert!(scores.is_empty());
+    }
+}
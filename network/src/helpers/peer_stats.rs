@@ -0,0 +1,334 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::helpers::decay;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The duration after which the statistics of a peer have decayed halfway back to those of an unknown peer.
+pub const PEER_STATS_HALF_LIFE: Duration = Duration::from_secs(10 * 60);
+/// The weight of a new sample in the moving average of the latency of a peer.
+const PEER_STATS_SMOOTHING: f64 = 0.2;
+/// The minimum sync weight of a peer, so that a slow or flaky peer is still assigned work eventually.
+const MINIMUM_PEER_WEIGHT: f64 = 0.05;
+/// The confidence below which the statistics of a peer are forgotten.
+const MINIMUM_PEER_STATS_CONFIDENCE: f64 = 0.01;
+
+///
+/// The block download statistics of a peer, as reported by the RPC server.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerSyncStats {
+    /// The IP address of the peer.
+    pub peer_ip: SocketAddr,
+    /// The throughput of the block responses of the peer, averaged over the decayed time spent awaiting them.
+    pub bytes_per_second: Option<f64>,
+    /// The moving average of the time from a block request to its response, in milliseconds.
+    pub average_latency_in_ms: Option<f64>,
+    /// The decayed number of failed block requests of the peer.
+    pub failures: f64,
+    /// The relative share of the blocks that are requested from the peer, where `1.0` is an average peer.
+    pub weight: f64,
+    /// If `true`, the peer is a trusted peer, which is never evicted or banned.
+    pub is_trusted: bool,
+}
+
+/// The samples of a single peer, as of the given time.
+#[derive(Clone, Debug)]
+struct PeerSamples {
+    /// The decayed number of bytes of the block responses.
+    number_of_bytes: f64,
+    /// The decayed number of seconds spent awaiting the block responses.
+    busy_time_in_secs: f64,
+    /// The moving average of the request-to-response latency, in seconds.
+    latency_in_secs: Option<f64>,
+    /// The decayed number of failed block requests.
+    failures: f64,
+    /// The time of the latest block response.
+    last_response_at: Option<Instant>,
+    /// The time of the latest sample.
+    updated_at: Instant,
+}
+
+impl PeerSamples {
+    fn new(now: Instant) -> Self {
+        Self {
+            number_of_bytes: 0.0,
+            busy_time_in_secs: 0.0,
+            latency_in_secs: None,
+            failures: 0.0,
+            last_response_at: None,
+            updated_at: now,
+        }
+    }
+
+    /// Returns the throughput of the block responses, if any.
+    fn bytes_per_second(&self) -> Option<f64> {
+        match self.busy_time_in_secs > 0.0 {
+            true => Some(self.number_of_bytes / self.busy_time_in_secs),
+            false => None,
+        }
+    }
+
+    /// Decays the samples to the given time, returning the remaining confidence in the samples.
+    fn decay_to(&mut self, now: Instant, half_life: Duration) -> f64 {
+        let confidence = decay(1.0, now.saturating_duration_since(self.updated_at), half_life);
+        self.number_of_bytes *= confidence;
+        self.busy_time_in_secs *= confidence;
+        self.failures *= confidence;
+        self.updated_at = now;
+        confidence
+    }
+}
+
+///
+/// The block download statistics of each peer, which weigh the sync work assigned to each peer,
+/// so that slow or flaky peers are assigned fewer and smaller chunks of blocks.
+///
+/// The statistics decay over time towards those of an average peer, so that a peer that recovers
+/// is assigned more work again. Like invalid block strikes, the statistics are kept across disconnects.
+///
+#[derive(Debug)]
+pub struct SyncPeerStats {
+    /// The map of each peer to its samples.
+    peers: HashMap<SocketAddr, PeerSamples>,
+    /// The duration after which the samples have decayed halfway.
+    half_life: Duration,
+}
+
+impl Default for SyncPeerStats {
+    fn default() -> Self {
+        Self::new(PEER_STATS_HALF_LIFE)
+    }
+}
+
+impl SyncPeerStats {
+    /// Initializes a new tracker of peer statistics with the given half-life.
+    pub fn new(half_life: Duration) -> Self {
+        Self {
+            peers: Default::default(),
+            half_life,
+        }
+    }
+
+    /// Records a block response of the given size from the given peer, for a block requested at the given time.
+    pub fn record_response(&mut self, peer_ip: SocketAddr, number_of_bytes: usize, requested_at: Instant) {
+        self.record_response_at(Instant::now(), peer_ip, number_of_bytes, requested_at)
+    }
+
+    /// Records a failed block request of the given peer, such as a timeout or an invalid block.
+    pub fn record_failure(&mut self, peer_ip: SocketAddr) {
+        self.record_failure_at(Instant::now(), peer_ip)
+    }
+
+    /// Returns the relative share of the blocks to request from the given peer, where `1.0` is an average peer.
+    pub fn weight(&self, peer_ip: &SocketAddr) -> f64 {
+        self.weight_at(Instant::now(), peer_ip)
+    }
+
+    /// Returns the statistics of the given peer.
+    pub fn get(&self, peer_ip: SocketAddr) -> PeerSyncStats {
+        self.get_at(Instant::now(), peer_ip)
+    }
+
+    /// Removes the peers whose statistics have decayed, returning the number of removed peers.
+    pub fn remove_expired(&mut self) -> usize {
+        let (number_of_peers, now, half_life) = (self.peers.len(), Instant::now(), self.half_life);
+        self.peers.retain(|_, samples| {
+            decay(1.0, now.saturating_duration_since(samples.updated_at), half_life) >= MINIMUM_PEER_STATS_CONFIDENCE
+        });
+        number_of_peers - self.peers.len()
+    }
+
+    /// Returns the number of peers with statistics.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Returns `true` if no peer has statistics.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    fn record_response_at(&mut self, now: Instant, peer_ip: SocketAddr, number_of_bytes: usize, requested_at: Instant) {
+        let half_life = self.half_life;
+        let samples = self.peers.entry(peer_ip).or_insert_with(|| PeerSamples::new(now));
+        let confidence = samples.decay_to(now, half_life);
+
+        // The peer is busy from the later of the request and its previous response, so that the blocks
+        // of a batched response, which arrive at once, are measured over the time of the entire batch.
+        let busy_since = match samples.last_response_at {
+            Some(last_response_at) => requested_at.max(last_response_at),
+            None => requested_at,
+        };
+        samples.number_of_bytes += number_of_bytes as f64;
+        samples.busy_time_in_secs += now.saturating_duration_since(busy_since).as_secs_f64();
+        samples.last_response_at = Some(now);
+
+        // A new sample replaces the moving average of the latency as it becomes stale.
+        let latency_in_secs = now.saturating_duration_since(requested_at).as_secs_f64();
+        let smoothing = PEER_STATS_SMOOTHING.max(1.0 - confidence);
+        samples.latency_in_secs = Some(match samples.latency_in_secs {
+            Some(average) => average + (latency_in_secs - average) * smoothing,
+            None => latency_in_secs,
+        });
+    }
+
+    fn record_failure_at(&mut self, now: Instant, peer_ip: SocketAddr) {
+        let half_life = self.half_life;
+        let samples = self.peers.entry(peer_ip).or_insert_with(|| PeerSamples::new(now));
+        samples.decay_to(now, half_life);
+        samples.failures += 1.0;
+    }
+
+    fn weight_at(&self, now: Instant, peer_ip: &SocketAddr) -> f64 {
+        let samples = match self.peers.get(peer_ip) {
+            Some(samples) => samples,
+            None => return 1.0,
+        };
+        let confidence = decay(1.0, now.saturating_duration_since(samples.updated_at), self.half_life);
+
+        // Compare the throughput of the peer to the average throughput of the known peers,
+        // and blend it towards the average as the samples age.
+        let relative_throughput = match (samples.bytes_per_second(), self.average_bytes_per_second()) {
+            (Some(bytes_per_second), Some(average)) if average > 0.0 => bytes_per_second / average,
+            _ => 1.0,
+        };
+        let throughput = 1.0 + (relative_throughput - 1.0) * confidence;
+
+        (throughput / (1.0 + samples.failures * confidence)).max(MINIMUM_PEER_WEIGHT)
+    }
+
+    fn get_at(&self, now: Instant, peer_ip: SocketAddr) -> PeerSyncStats {
+        match self.peers.get(&peer_ip) {
+            Some(samples) => PeerSyncStats {
+                peer_ip,
+                bytes_per_second: samples.bytes_per_second(),
+                average_latency_in_ms: samples.latency_in_secs.map(|latency| latency * 1000.0),
+                failures: decay(samples.failures, now.saturating_duration_since(samples.updated_at), self.half_life),
+                weight: self.weight_at(now, &peer_ip),
+                is_trusted: false,
+            },
+            None => PeerSyncStats {
+                peer_ip,
+                bytes_per_second: None,
+                average_latency_in_ms: None,
+                failures: 0.0,
+                weight: 1.0,
+                is_trusted: false,
+            },
+        }
+    }
+
+    /// Returns the average throughput of the peers with block responses, if any.
+    fn average_bytes_per_second(&self) -> Option<f64> {
+        let throughputs = self.peers.values().filter_map(|samples| samples.bytes_per_second()).collect::<Vec<_>>();
+        match throughputs.is_empty() {
+            true => None,
+            false => Some(throughputs.iter().sum::<f64>() / throughputs.len() as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{assign_weighted_block_chunks, test_helpers::peer};
+
+    #[test]
+    fn test_faster_peers_receive_more_work() {
+        const BLOCK_SIZE: f64 = 20_000.0;
+
+        // The mocked peers, as (peer_ip, bytes_per_second, is_flaky), where a flaky peer times out on every other chunk.
+        let mocked_peers = [(peer(4130), 2_000_000.0, false), (peer(4131), 200_000.0, false), (peer(4132), 2_000_000.0, true)];
+        let mut stats = SyncPeerStats::default();
+        let mut number_of_blocks = HashMap::<SocketAddr, u32>::new();
+        let mut number_of_chunks = HashMap::<SocketAddr, u32>::new();
+
+        // Simulate rounds of block requests, in which each peer streams its assigned chunks at its own speed.
+        let mut now = Instant::now();
+        for round in 0..20 {
+            let peers = mocked_peers.iter().map(|(peer_ip, ..)| (*peer_ip, u32::MAX, stats.weight_at(now, peer_ip))).collect::<Vec<_>>();
+            let mut round_end = now + Duration::from_secs(1);
+            let mut peer_times = HashMap::<SocketAddr, Instant>::new();
+
+            for (peer_ip, chunk_start, chunk_end) in assign_weighted_block_chunks(round * 300 + 1, round * 300 + 300, 50, &peers) {
+                let (_, bytes_per_second, is_flaky) = mocked_peers.iter().find(|(ip, ..)| *ip == peer_ip).unwrap();
+                *number_of_blocks.entry(peer_ip).or_default() += chunk_end - chunk_start + 1;
+                *number_of_chunks.entry(peer_ip).or_default() += 1;
+
+                if *is_flaky && number_of_chunks[&peer_ip] % 2 == 1 {
+                    stats.record_failure_at(now, peer_ip);
+                    continue;
+                }
+                let peer_time = peer_times.entry(peer_ip).or_insert(now);
+                for _ in chunk_start..=chunk_end {
+                    *peer_time += Duration::from_secs_f64(BLOCK_SIZE / bytes_per_second);
+                    stats.record_response_at(*peer_time, peer_ip, BLOCK_SIZE as usize, now);
+                }
+                round_end = round_end.max(*peer_time);
+            }
+            now = round_end;
+        }
+
+        // The fast peer receives more work than the slow and flaky peers.
+        assert!(number_of_blocks[&peer(4130)] > 2 * number_of_blocks[&peer(4131)]);
+        assert!(number_of_blocks[&peer(4130)] > 2 * number_of_blocks[&peer(4132)]);
+
+        // The slow peer is assigned chunks of about a tenth of the size of the chunks of the fast peer.
+        let peers = mocked_peers.iter().map(|(peer_ip, ..)| (*peer_ip, u32::MAX, stats.weight_at(now, peer_ip))).collect::<Vec<_>>();
+        let assignments = assign_weighted_block_chunks(1, 1000, 50, &peers);
+        assert_eq!(assignments[0], (peer(4130), 1, 50));
+        for (_, chunk_start, chunk_end) in assignments.iter().filter(|(peer_ip, ..)| *peer_ip == peer(4131)) {
+            assert!(chunk_end - chunk_start < 6);
+        }
+
+        let (fast, slow, flaky) = (stats.get_at(now, peer(4130)), stats.get_at(now, peer(4131)), stats.get_at(now, peer(4132)));
+        assert!((fast.bytes_per_second.unwrap() - 2_000_000.0).abs() < 1.0);
+        assert!((slow.bytes_per_second.unwrap() - 200_000.0).abs() < 1.0);
+        assert!(fast.weight > slow.weight && fast.weight > flaky.weight);
+        assert!(flaky.failures > 1.0);
+    }
+
+    #[test]
+    fn test_peer_stats_decay() {
+        let start = Instant::now();
+        let now = start + Duration::from_secs(1);
+        let mut stats = SyncPeerStats::default();
+        stats.record_response_at(now, peer(4130), 1_000_000, start);
+        stats.record_response_at(now, peer(4131), 10_000, start);
+        stats.record_failure_at(now, peer(4131));
+
+        // An unknown peer is an average peer.
+        assert_eq!(stats.weight_at(now, &peer(4132)), 1.0);
+        assert!(stats.weight_at(now, &peer(4131)) < 0.1);
+        assert_eq!(stats.get_at(now, peer(4131)).average_latency_in_ms, Some(1000.0));
+
+        // A slow peer recovers its weight as its samples decay, and a fast peer loses its advantage.
+        let later = now + 10 * PEER_STATS_HALF_LIFE;
+        assert!((stats.weight_at(later, &peer(4131)) - 1.0).abs() < 0.01);
+        assert!((stats.weight_at(later, &peer(4130)) - 1.0).abs() < 0.01);
+
+        // A new sample of a recovered peer replaces its stale samples.
+        stats.record_response_at(later + Duration::from_secs(1), peer(4131), 1_000_000, later);
+        assert!(stats.get_at(later, peer(4131)).bytes_per_second.unwrap() > 900_000.0);
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, bail, Result};
+use igd::{aio::Gateway, PortMappingProtocol, SearchOptions};
+use natpmp::{new_tokio_natpmp, Protocol, Response};
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    time::Duration,
+};
+use tokio::time::timeout;
+
+/// The lease duration of a port mapping that is requested from the gateway.
+pub const PORT_MAPPING_LEASE: Duration = Duration::from_secs(60 * 60);
+/// The maximum duration to wait for a response from the gateway.
+const PORT_MAPPING_TIMEOUT: Duration = Duration::from_secs(5);
+/// The minimum duration in between renewals of a port mapping, guarding against a lease of zero from the gateway.
+const MINIMUM_RENEWAL_INTERVAL: Duration = Duration::from_secs(1);
+/// The maximum duration to wait before retrying a failed renewal of a port mapping.
+const MAXIMUM_RENEWAL_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// The description of the port mapping on the gateway.
+const PORT_MAPPING_DESCRIPTION: &str = "snarkOS";
+
+///
+/// The protocol used to map the listener port of the node on the gateway.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PortMappingKind {
+    /// The port is mapped with UPnP (IGD).
+    Upnp,
+    /// The port is mapped with NAT-PMP.
+    NatPmp,
+}
+
+impl fmt::Display for PortMappingKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Upnp => write!(f, "UPnP"),
+            Self::NatPmp => write!(f, "NAT-PMP"),
+        }
+    }
+}
+
+/// The gateway that holds a port mapping.
+#[derive(Clone, Debug)]
+enum PortMapper {
+    /// The UPnP gateway, and the local address that the port is mapped to.
+    Upnp(Gateway, SocketAddrV4),
+    /// The NAT-PMP gateway, which is discovered again for each request.
+    NatPmp,
+}
+
+///
+/// A mapping of the listener port of the node on the gateway, so that a node behind NAT
+/// is able to accept inbound connections. The mapping is leased, and must be renewed
+/// before its lease expires, at the interval returned by `renewal_interval`.
+///
+#[derive(Clone, Debug)]
+pub struct PortMapping {
+    /// The gateway that holds the port mapping.
+    mapper: PortMapper,
+    /// The local listener port of the node.
+    local_port: u16,
+    /// The external IP address and port of the node, as mapped on the gateway.
+    external_ip: SocketAddr,
+    /// The lease duration granted by the gateway.
+    lease: Duration,
+}
+
+impl PortMapping {
+    ///
+    /// Maps the given local listener port on the gateway, using UPnP, with NAT-PMP as the fallback.
+    /// The external port is the same as the local port, unless the gateway already maps it elsewhere.
+    ///
+    pub async fn new(local_port: u16) -> Result<Self> {
+        match Self::map_upnp(local_port).await {
+            Ok(port_mapping) => Ok(port_mapping),
+            Err(upnp_error) => match Self::map_natpmp(local_port).await {
+                Ok(port_mapping) => Ok(port_mapping),
+                Err(natpmp_error) => bail!("UPnP failed ({}), and NAT-PMP failed ({})", upnp_error, natpmp_error),
+            },
+        }
+    }
+
+    /// Returns the protocol used to map the port.
+    pub fn kind(&self) -> PortMappingKind {
+        match self.mapper {
+            PortMapper::Upnp(..) => PortMappingKind::Upnp,
+            PortMapper::NatPmp => PortMappingKind::NatPmp,
+        }
+    }
+
+    /// Returns the external IP address and port of the node, as mapped on the gateway.
+    pub fn external_ip(&self) -> SocketAddr {
+        self.external_ip
+    }
+
+    /// Returns the duration to wait before renewing the port mapping.
+    pub fn renewal_interval(&self) -> Duration {
+        renewal_interval(self.lease)
+    }
+
+    /// Returns the duration to wait before retrying a failed renewal of the port mapping.
+    pub fn renewal_retry_interval(&self) -> Duration {
+        renewal_retry_interval(self.lease)
+    }
+
+    /// Renews the lease of the port mapping, and refreshes the external IP address of the node.
+    pub async fn renew(&mut self) -> Result<()> {
+        match &self.mapper {
+            PortMapper::Upnp(gateway, local_addr) => {
+                let lease_in_secs = self.lease.as_secs() as u32;
+                let description = PORT_MAPPING_DESCRIPTION;
+                let request = gateway.add_port(PortMappingProtocol::TCP, self.external_ip.port(), *local_addr, lease_in_secs, description);
+                timeout(PORT_MAPPING_TIMEOUT, request).await??;
+                let external_ip = timeout(PORT_MAPPING_TIMEOUT, gateway.get_external_ip()).await??;
+                self.external_ip.set_ip(IpAddr::V4(external_ip));
+            }
+            PortMapper::NatPmp => {
+                let external_ip = natpmp_external_ip().await?;
+                let (external_port, lease) = natpmp_map(self.local_port, self.external_ip.port(), PORT_MAPPING_LEASE).await?;
+                self.external_ip = SocketAddr::from((external_ip, external_port));
+                self.lease = lease;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the port mapping from the gateway.
+    pub async fn remove(self) -> Result<()> {
+        match self.mapper {
+            PortMapper::Upnp(gateway, _) => {
+                let request = gateway.remove_port(PortMappingProtocol::TCP, self.external_ip.port());
+                Ok(timeout(PORT_MAPPING_TIMEOUT, request).await??)
+            }
+            // A mapping request with a lifetime of zero removes the mapping.
+            PortMapper::NatPmp => natpmp_map(self.local_port, 0, Duration::ZERO).await.map(|_| ()),
+        }
+    }
+
+    /// Maps the given local listener port on the gateway with UPnP.
+    async fn map_upnp(local_port: u16) -> Result<Self> {
+        let options = SearchOptions {
+            timeout: Some(PORT_MAPPING_TIMEOUT),
+            ..Default::default()
+        };
+        let gateway = igd::aio::search_gateway(options).await?;
+        let local_addr = SocketAddrV4::new(local_ip_toward(*gateway.addr.ip())?, local_port);
+
+        let (lease_in_secs, description) = (PORT_MAPPING_LEASE.as_secs() as u32, PORT_MAPPING_DESCRIPTION);
+        // Request the same external port as the local port, or any external port if it is taken.
+        let request = gateway.add_port(PortMappingProtocol::TCP, local_port, local_addr, lease_in_secs, description);
+        let external_port = match timeout(PORT_MAPPING_TIMEOUT, request).await? {
+            Ok(()) => local_port,
+            Err(_) => {
+                let request = gateway.add_any_port(PortMappingProtocol::TCP, local_addr, lease_in_secs, description);
+                timeout(PORT_MAPPING_TIMEOUT, request).await??
+            }
+        };
+        let external_ip = timeout(PORT_MAPPING_TIMEOUT, gateway.get_external_ip()).await??;
+
+        Ok(Self {
+            mapper: PortMapper::Upnp(gateway, local_addr),
+            local_port,
+            external_ip: SocketAddr::from((external_ip, external_port)),
+            lease: PORT_MAPPING_LEASE,
+        })
+    }
+
+    /// Maps the given local listener port on the gateway with NAT-PMP.
+    async fn map_natpmp(local_port: u16) -> Result<Self> {
+        let external_ip = natpmp_external_ip().await?;
+        let (external_port, lease) = natpmp_map(local_port, local_port, PORT_MAPPING_LEASE).await?;
+
+        Ok(Self {
+            mapper: PortMapper::NatPmp,
+            local_port,
+            external_ip: SocketAddr::from((external_ip, external_port)),
+            lease,
+        })
+    }
+}
+
+/// Returns the external IP address of the NAT-PMP gateway.
+async fn natpmp_external_ip() -> Result<Ipv4Addr> {
+    let mut natpmp = new_tokio_natpmp().await.map_err(|error| anyhow!("{:?}", error))?;
+    natpmp.send_public_address_request().await.map_err(|error| anyhow!("{:?}", error))?;
+    match timeout(PORT_MAPPING_TIMEOUT, natpmp.recv()).await? {
+        Ok(Response::Gateway(response)) => Ok(*response.public_address()),
+        Ok(_) => bail!("Received an unexpected response from the NAT-PMP gateway"),
+        Err(error) => bail!("{:?}", error),
+    }
+}
+
+/// Maps the given local port to the given external port on the NAT-PMP gateway, returning the mapped external port and lease.
+async fn natpmp_map(local_port: u16, external_port: u16, lease: Duration) -> Result<(u16, Duration)> {
+    let mut natpmp = new_tokio_natpmp().await.map_err(|error| anyhow!("{:?}", error))?;
+    natpmp
+        .send_port_mapping_request(Protocol::TCP, local_port, external_port, lease.as_secs() as u32)
+        .await
+        .map_err(|error| anyhow!("{:?}", error))?;
+    match timeout(PORT_MAPPING_TIMEOUT, natpmp.recv()).await? {
+        Ok(Response::TCP(response)) => Ok((response.public_port(), *response.lifetime())),
+        Ok(_) => bail!("Received an unexpected response from the NAT-PMP gateway"),
+        Err(error) => bail!("{:?}", error),
+    }
+}
+
+/// Returns the local IP address of the interface that routes to the given gateway.
+fn local_ip_toward(gateway_ip: Ipv4Addr) -> Result<Ipv4Addr> {
+    // Connecting a UDP socket selects the route to the gateway, without sending any packets.
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.connect((gateway_ip, 1))?;
+    match socket.local_addr()?.ip() {
+        IpAddr::V4(local_ip) => Ok(local_ip),
+        IpAddr::V6(local_ip) => bail!("Expected a local IPv4 address toward the gateway, found {}", local_ip),
+    }
+}
+
+/// Returns the duration to wait before renewing a port mapping with the given lease, which is halfway through the lease.
+fn renewal_interval(lease: Duration) -> Duration {
+    (lease / 2).max(MINIMUM_RENEWAL_INTERVAL)
+}
+
+/// Returns the duration to wait before retrying a failed renewal of a port mapping with the given lease,
+/// which leaves several attempts before the lease expires.
+fn renewal_retry_interval(lease: Duration) -> Duration {
+    (lease / 8).clamp(MINIMUM_RENEWAL_INTERVAL, MAXIMUM_RENEWAL_RETRY_INTERVAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renewal_interval() {
+        assert_eq!(renewal_interval(PORT_MAPPING_LEASE), PORT_MAPPING_LEASE / 2);
+        assert_eq!(renewal_interval(Duration::from_secs(7200)), Duration::from_secs(3600));
+
+        // A short lease granted by the gateway is still renewed before it expires.
+        assert_eq!(renewal_interval(Duration::from_secs(10)), Duration::from_secs(5));
+        assert_eq!(renewal_interval(Duration::from_secs(2)), Duration::from_secs(1));
+        assert_eq!(renewal_interval(Duration::ZERO), MINIMUM_RENEWAL_INTERVAL);
+    }
+
+    #[test]
+    fn test_renewal_retry_interval() {
+        // A failed renewal is retried well before the remaining half of the lease runs out.
+        assert_eq!(renewal_retry_interval(PORT_MAPPING_LEASE), MAXIMUM_RENEWAL_RETRY_INTERVAL);
+        assert_eq!(renewal_retry_interval(Duration::from_secs(80)), Duration::from_secs(10));
+        assert!(renewal_retry_interval(Duration::from_secs(10)) < renewal_interval(Duration::from_secs(10)));
+        assert_eq!(renewal_retry_interval(Duration::ZERO), MINIMUM_RENEWAL_INTERVAL);
+    }
+
+    #[test]
+    fn test_local_ip_toward() {
+        assert_eq!(local_ip_toward(Ipv4Addr::LOCALHOST).unwrap(), Ipv4Addr::LOCALHOST);
+    }
+}
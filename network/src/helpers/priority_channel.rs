@@ -0,0 +1,167 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use tokio::sync::mpsc::{self, error::SendError};
+
+/// The maximum number of consecutive priority messages that are received, while a bulk message is waiting.
+pub const MAXIMUM_CONSECUTIVE_PRIORITY_MESSAGES: usize = 8;
+
+///
+/// Initializes a new channel with a priority lane and a bulk lane, each with the given capacity.
+/// The given function determines the lane of each message.
+///
+pub fn priority_channel<T>(capacity: usize, is_priority: fn(&T) -> bool) -> (PrioritySender<T>, PriorityReceiver<T>) {
+    let (priority_sender, priority_receiver) = mpsc::channel(capacity);
+    let (bulk_sender, bulk_receiver) = mpsc::channel(capacity);
+    (
+        PrioritySender {
+            priority_sender,
+            bulk_sender,
+            is_priority,
+        },
+        PriorityReceiver {
+            priority_receiver,
+            bulk_receiver,
+            consecutive_priority_messages: 0,
+        },
+    )
+}
+
+///
+/// The sending half of a priority channel, which routes each message to its lane.
+///
+pub struct PrioritySender<T> {
+    /// The sender of the priority lane.
+    priority_sender: mpsc::Sender<T>,
+    /// The sender of the bulk lane.
+    bulk_sender: mpsc::Sender<T>,
+    /// Returns `true` if the given message belongs to the priority lane.
+    is_priority: fn(&T) -> bool,
+}
+
+impl<T> Clone for PrioritySender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            priority_sender: self.priority_sender.clone(),
+            bulk_sender: self.bulk_sender.clone(),
+            is_priority: self.is_priority,
+        }
+    }
+}
+
+impl<T> PrioritySender<T> {
+    /// Sends the given message on its lane, waiting for capacity if the lane is full.
+    pub async fn send(&self, message: T) -> Result<(), SendError<T>> {
+        match (self.is_priority)(&message) {
+            true => self.priority_sender.send(message).await,
+            false => self.bulk_sender.send(message).await,
+        }
+    }
+}
+
+///
+/// The receiving half of a priority channel.
+///
+/// Messages of the priority lane are received before messages of the bulk lane, while the messages
+/// of each lane are received in the order they were sent. To prevent starvation of the bulk lane,
+/// a waiting bulk message is received after `MAXIMUM_CONSECUTIVE_PRIORITY_MESSAGES` priority messages.
+///
+#[derive(Debug)]
+pub struct PriorityReceiver<T> {
+    /// The receiver of the priority lane.
+    priority_receiver: mpsc::Receiver<T>,
+    /// The receiver of the bulk lane.
+    bulk_receiver: mpsc::Receiver<T>,
+    /// The number of priority messages received since the last bulk message.
+    consecutive_priority_messages: usize,
+}
+
+impl<T> PriorityReceiver<T> {
+    /// Receives the next message, returning `None` once both lanes are closed and empty.
+    pub async fn recv(&mut self) -> Option<T> {
+        // Receive a waiting bulk message, if the priority lane has been favoured for too long.
+        if self.consecutive_priority_messages >= MAXIMUM_CONSECUTIVE_PRIORITY_MESSAGES {
+            if let Ok(message) = self.bulk_receiver.try_recv() {
+                self.consecutive_priority_messages = 0;
+                return Some(message);
+            }
+        }
+
+        let (message, is_priority) = tokio::select! {
+            biased;
+            Some(message) = self.priority_receiver.recv() => (message, true),
+            Some(message) = self.bulk_receiver.recv() => (message, false),
+            else => return None,
+        };
+        self.consecutive_priority_messages = match is_priority {
+            true => self.consecutive_priority_messages + 1,
+            false => 0,
+        };
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Even messages are sent on the priority lane.
+    fn is_even(message: &u32) -> bool {
+        message % 2 == 0
+    }
+
+    #[tokio::test]
+    async fn test_priority_message_overtakes_bulk_backlog() {
+        let (sender, mut receiver) = priority_channel(1024, is_even);
+
+        // Queue a large backlog of bulk messages, followed by a priority message.
+        for message in (1..1000).step_by(2) {
+            sender.send(message).await.unwrap();
+        }
+        sender.send(1000).await.unwrap();
+
+        // The priority message is received first, and the backlog is received in order.
+        assert_eq!(receiver.recv().await, Some(1000));
+        for message in (1..1000).step_by(2) {
+            assert_eq!(receiver.recv().await, Some(message));
+        }
+
+        // Both lanes are closed once the sender is dropped.
+        drop(sender);
+        assert_eq!(receiver.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_lane_is_not_starved() {
+        let (sender, mut receiver) = priority_channel(1024, is_even);
+
+        for message in [1, 3] {
+            sender.send(message).await.unwrap();
+        }
+        for message in (0..100).step_by(2) {
+            sender.send(message).await.unwrap();
+        }
+
+        // A bulk message is received after each run of consecutive priority messages.
+        let mut messages = Vec::new();
+        for _ in 0..2 * (MAXIMUM_CONSECUTIVE_PRIORITY_MESSAGES + 1) {
+            messages.push(receiver.recv().await.unwrap());
+        }
+        assert_eq!(messages[MAXIMUM_CONSECUTIVE_PRIORITY_MESSAGES], 1);
+        assert_eq!(messages[2 * MAXIMUM_CONSECUTIVE_PRIORITY_MESSAGES + 1], 3);
+        assert_eq!(messages.iter().filter(|message| !is_even(message)).count(), 2);
+    }
+}
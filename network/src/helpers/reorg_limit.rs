@@ -0,0 +1,108 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_storage::ALEO_MAXIMUM_FORK_DEPTH;
+
+/// The default maximum number of blocks that the ledger reverts in order to switch to a fork.
+pub const DEFAULT_MAXIMUM_REORG_DEPTH: u32 = ALEO_MAXIMUM_FORK_DEPTH;
+
+///
+/// The limit on the depth of a reorg, which protects the ledger from switching to an alternative chain
+/// that forks too far back, unless deep reorgs are explicitly allowed to recover from a genuine split.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReorgLimit {
+    /// The maximum number of blocks that are reverted to switch to a fork.
+    maximum_depth: u32,
+    /// If `true`, the ledger switches to a fork regardless of its depth.
+    allow_deep_reorgs: bool,
+}
+
+impl Default for ReorgLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAXIMUM_REORG_DEPTH, false)
+    }
+}
+
+impl ReorgLimit {
+    /// Initializes a new reorg limit with the given maximum depth.
+    pub fn new(maximum_depth: u32, allow_deep_reorgs: bool) -> Self {
+        Self {
+            maximum_depth,
+            allow_deep_reorgs,
+        }
+    }
+
+    /// Returns the maximum number of blocks that are reverted to switch to a fork.
+    pub fn maximum_depth(&self) -> u32 {
+        self.maximum_depth
+    }
+
+    /// Returns `true` if the ledger switches to a fork regardless of its depth.
+    pub fn allows_deep_reorgs(&self) -> bool {
+        self.allow_deep_reorgs
+    }
+
+    /// Returns the maximum number of blocks that the ledger may revert.
+    pub fn maximum_revert_depth(&self) -> u32 {
+        match self.allow_deep_reorgs {
+            true => u32::MAX,
+            false => self.maximum_depth,
+        }
+    }
+
+    ///
+    /// Returns `true` if the ledger is permitted to revert from the given latest block height
+    /// to the given common ancestor, in order to switch to a fork.
+    ///
+    pub fn permits(&self, latest_block_height: u32, common_ancestor: u32) -> bool {
+        latest_block_height.saturating_sub(common_ancestor) <= self.maximum_revert_depth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorg_at_the_limit() {
+        let limit = ReorgLimit::new(100, false);
+        assert!(limit.permits(1000, 900));
+        assert!(limit.permits(50, 0));
+        // A common ancestor above the latest block height does not revert any blocks.
+        assert!(limit.permits(1000, 1001));
+    }
+
+    #[test]
+    fn test_reorg_beyond_the_limit() {
+        let limit = ReorgLimit::new(100, false);
+        assert!(!limit.permits(1000, 899));
+        assert!(!limit.permits(u32::MAX, 0));
+
+        // The default limit matches the maximum fork depth.
+        let limit = ReorgLimit::default();
+        assert!(limit.permits(ALEO_MAXIMUM_FORK_DEPTH, 0));
+        assert!(!limit.permits(ALEO_MAXIMUM_FORK_DEPTH + 1, 0));
+    }
+
+    #[test]
+    fn test_deep_reorg_override() {
+        let limit = ReorgLimit::new(100, true);
+        assert!(limit.permits(1000, 899));
+        assert!(limit.permits(u32::MAX, 0));
+        assert_eq!(limit.maximum_depth(), 100);
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+/// The duration for which a received unconfirmed block or transaction is considered a duplicate.
+pub const SEEN_CACHE_EXPIRY: Duration = Duration::from_secs(30);
+/// The maximum number of entries in a seen cache.
+pub const MAXIMUM_SEEN_CACHE_ENTRIES: usize = 10_000;
+
+/// The key of the transaction digests, which is random for each process.
+static TRANSACTION_DIGEST_KEY: Lazy<RandomState> = Lazy::new(RandomState::new);
+
+///
+/// A time-bounded cache of recently received unconfirmed blocks or transactions, shared by all peers,
+/// so that copies of the same item arriving from several peers are dropped before they are deserialized.
+///
+#[derive(Debug)]
+pub struct SeenCache<K: Eq + Hash> {
+    /// The map of each key to the time it was first seen.
+    entries: Mutex<HashMap<K, Instant>>,
+    /// The duration for which a key is considered seen.
+    expiry: Duration,
+    /// The maximum number of keys in the cache.
+    maximum_entries: usize,
+}
+
+impl<K: Eq + Hash> Default for SeenCache<K> {
+    fn default() -> Self {
+        Self::new(SEEN_CACHE_EXPIRY, MAXIMUM_SEEN_CACHE_ENTRIES)
+    }
+}
+
+impl<K: Eq + Hash> SeenCache<K> {
+    /// Initializes a new seen cache with the given expiry and capacity.
+    pub fn new(expiry: Duration, maximum_entries: usize) -> Self {
+        Self {
+            entries: Default::default(),
+            expiry,
+            maximum_entries,
+        }
+    }
+
+    ///
+    /// Returns `true` if the given key was seen within the expiry, and otherwise marks it as seen.
+    ///
+    pub fn check_and_insert(&self, key: K) -> bool {
+        let mut entries = self.entries.lock();
+        if let Some(timestamp) = entries.get(&key) {
+            if timestamp.elapsed() <= self.expiry {
+                return true;
+            }
+        }
+
+        // Evict the expired keys, once the cache is full.
+        if entries.len() >= self.maximum_entries {
+            let expiry = self.expiry;
+            entries.retain(|_, timestamp| timestamp.elapsed() <= expiry);
+        }
+        // If the cache is still full, the key is processed without being cached.
+        if entries.len() < self.maximum_entries {
+            entries.insert(key, Instant::now());
+        }
+        false
+    }
+
+    ///
+    /// Forgets the given key, so that the next copy of the item is processed again.
+    /// This is used when an item was dropped or rejected for a transient reason.
+    ///
+    pub fn remove(&self, key: &K) {
+        self.entries.lock().remove(key);
+    }
+
+    /// Returns the number of keys in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Returns `true` if the cache contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+///
+/// A digest of the serialized bytes of an unconfirmed transaction, which is identical for each copy of the
+/// same transaction. As the transaction ID is only known after deserialization, the digest is used in its place.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TransactionDigest(u64);
+
+impl TransactionDigest {
+    /// Computes the digest of the given serialized transaction.
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut hasher = TRANSACTION_DIGEST_KEY.build_hasher();
+        bytes.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seen_cache_drops_duplicates() {
+        let cache = SeenCache::<u32>::default();
+        assert!(!cache.check_and_insert(1));
+        assert!(cache.check_and_insert(1));
+        assert!(!cache.check_and_insert(2));
+
+        // A forgotten key is processed again.
+        cache.remove(&1);
+        assert!(!cache.check_and_insert(1));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_seen_cache_expiry_and_capacity() {
+        // An expired key is processed again.
+        let cache = SeenCache::<u32>::new(Duration::from_secs(0), 10);
+        assert!(!cache.check_and_insert(1));
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(!cache.check_and_insert(1));
+
+        // A full cache does not grow beyond its capacity.
+        let cache = SeenCache::<u32>::new(Duration::from_secs(60), 2);
+        assert!(!cache.check_and_insert(1));
+        assert!(!cache.check_and_insert(2));
+        assert!(!cache.check_and_insert(3));
+        assert!(!cache.check_and_insert(3));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_transaction_digest() {
+        assert_eq!(TransactionDigest::new(b"transaction"), TransactionDigest::new(b"transaction"));
+        assert_ne!(TransactionDigest::new(b"transaction"), TransactionDigest::new(b"other"));
+    }
+}
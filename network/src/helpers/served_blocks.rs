@@ -0,0 +1,149 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, Result};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// The maximum number of blocks that are served to a single peer within the served blocks window.
+/// A syncing peer requests at most `MAXIMUM_BLOCKS_PER_BATCH` blocks at a time, which this allows 240 times a minute.
+pub const MAXIMUM_BLOCKS_SERVED_PER_MINUTE: u32 = 12_000;
+/// The window of time over which the blocks served to a peer are counted.
+const SERVED_BLOCKS_WINDOW: Duration = Duration::from_secs(60);
+
+///
+/// Ensures the given block request is well-formed, and returns the range of blocks to serve, if any.
+///
+/// The request must not exceed `maximum_block_request` blocks. A request that extends past the latest block height
+/// is truncated, and a request that starts past it is answered with no blocks, as the peer may be ahead of this node,
+/// or racing a reorg.
+///
+pub fn check_block_request(
+    start_block_height: u32,
+    end_block_height: u32,
+    latest_block_height: u32,
+    maximum_block_request: u32,
+) -> Result<Option<(u32, u32)>> {
+    if start_block_height > end_block_height {
+        return Err(anyhow!("Attempted to request blocks {} to {}", start_block_height, end_block_height));
+    }
+    if end_block_height - start_block_height >= maximum_block_request {
+        return Err(anyhow!(
+            "Attempted to request blocks {} to {}, which exceeds the maximum of {} blocks",
+            start_block_height,
+            end_block_height,
+            maximum_block_request
+        ));
+    }
+    match start_block_height > latest_block_height {
+        true => Ok(None),
+        false => Ok(Some((start_block_height, end_block_height.min(latest_block_height)))),
+    }
+}
+
+///
+/// The number of blocks served to a peer within the last `SERVED_BLOCKS_WINDOW`, which limits the rate
+/// at which a peer is able to make this node read and serialize blocks.
+///
+#[derive(Debug, Default)]
+pub struct ServedBlocks {
+    /// The time and number of blocks of each served block request, in increasing time.
+    requests: VecDeque<(Instant, u32)>,
+    /// The total number of blocks of the served block requests.
+    number_of_blocks: u32,
+}
+
+impl ServedBlocks {
+    ///
+    /// Records the given number of blocks as served, if the peer is within `MAXIMUM_BLOCKS_SERVED_PER_MINUTE`.
+    /// Returns `false` if serving the blocks would exceed the limit, in which case nothing is recorded.
+    ///
+    pub fn try_serve(&mut self, number_of_blocks: u32) -> bool {
+        self.try_serve_at(Instant::now(), number_of_blocks)
+    }
+
+    fn try_serve_at(&mut self, now: Instant, number_of_blocks: u32) -> bool {
+        // Forget the block requests outside of the window.
+        while let Some((time, served)) = self.requests.front() {
+            match now.saturating_duration_since(*time) >= SERVED_BLOCKS_WINDOW {
+                true => {
+                    self.number_of_blocks -= served;
+                    self.requests.pop_front();
+                }
+                false => break,
+            }
+        }
+
+        if self.number_of_blocks.saturating_add(number_of_blocks) > MAXIMUM_BLOCKS_SERVED_PER_MINUTE {
+            return false;
+        }
+        self.requests.push_back((now, number_of_blocks));
+        self.number_of_blocks += number_of_blocks;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::MAXIMUM_BLOCKS_PER_BATCH;
+    use snarkos_environment::{Client, Environment};
+    use snarkvm::prelude::Testnet3;
+
+    type CurrentNetwork = Testnet3;
+
+    #[test]
+    fn test_check_block_request() {
+        assert_eq!(check_block_request(1, 50, 100, 250).unwrap(), Some((1, 50)));
+        // A request past the latest block is truncated.
+        assert_eq!(check_block_request(91, 140, 100, 250).unwrap(), Some((91, 100)));
+        // A request that starts past the latest block is well-formed, and has no blocks to serve.
+        assert_eq!(check_block_request(101, 150, 100, 250).unwrap(), None);
+        assert_eq!(check_block_request(101, 101, 100, 250).unwrap(), None);
+
+        // Malformed and oversized requests are rejected.
+        assert!(check_block_request(50, 1, 100, 250).is_err());
+        assert!(check_block_request(0, 250, 1000, 250).is_err());
+        assert!(check_block_request(0, u32::MAX, 1000, 250).is_err());
+        assert!(check_block_request(150, 101, 100, 250).is_err());
+
+        // The block requests of this node's own sync are always within the limit.
+        let maximum_block_request = Client::<CurrentNetwork>::MAXIMUM_BLOCK_REQUEST;
+        assert!(check_block_request(1, MAXIMUM_BLOCKS_PER_BATCH, 1000, maximum_block_request).is_ok());
+    }
+
+    #[test]
+    fn test_served_blocks_rate_limit() {
+        let start = Instant::now();
+        let mut served_blocks = ServedBlocks::default();
+
+        // A peer that syncs a full batch every 300 milliseconds stays within the limit.
+        for i in 0..1000 {
+            assert!(served_blocks.try_serve_at(start + Duration::from_millis(300 * i), MAXIMUM_BLOCKS_PER_BATCH));
+        }
+
+        // A peer that requests blocks in a burst is declined once it exceeds the limit, until the window passes.
+        let mut served_blocks = ServedBlocks::default();
+        let number_of_requests = MAXIMUM_BLOCKS_SERVED_PER_MINUTE / 250;
+        for _ in 0..number_of_requests {
+            assert!(served_blocks.try_serve_at(start, 250));
+        }
+        assert!(!served_blocks.try_serve_at(start + Duration::from_secs(30), 1));
+        assert!(served_blocks.try_serve_at(start + SERVED_BLOCKS_WINDOW, 250));
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The window of time over which the rate of added blocks is averaged.
+const BLOCK_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+///
+/// The sync progress of the ledger.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncStatus {
+    /// The latest block height of the ledger.
+    pub current_height: u32,
+    /// The latest block height known among the connected peers.
+    pub target_height: u32,
+    /// `true` if the ledger is not out of date with respect to the connected peers.
+    pub is_synced: bool,
+    /// The moving average of the number of blocks added to the ledger per second.
+    pub blocks_per_second: f64,
+    /// The estimated time to reach the target height at the current rate, if the ledger is making progress.
+    pub estimated_seconds_remaining: Option<u64>,
+    /// The peers with outstanding block requests.
+    pub syncing_from_peers: Vec<SocketAddr>,
+}
+
+impl SyncStatus {
+    /// Returns the sync progress as a percentage of the target height.
+    pub fn percentage(&self) -> f64 {
+        match self.target_height {
+            0 => 100.0,
+            target_height => (self.current_height as f64 / target_height as f64 * 100.0).min(100.0),
+        }
+    }
+}
+
+///
+/// A moving average of the number of blocks added to the ledger per second.
+///
+#[derive(Debug, Default)]
+pub struct BlockRate {
+    /// The block height of the ledger at each recorded time, in increasing time.
+    samples: VecDeque<(Instant, u32)>,
+}
+
+impl BlockRate {
+    /// Records the given block height as the latest block height of the ledger.
+    pub fn record(&mut self, block_height: u32) {
+        self.record_at(Instant::now(), block_height)
+    }
+
+    /// Returns the moving average of the number of blocks added per second.
+    pub fn blocks_per_second(&self) -> f64 {
+        self.blocks_per_second_at(Instant::now())
+    }
+
+    /// Clears the recorded block heights, as the ledger has reverted.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    fn record_at(&mut self, now: Instant, block_height: u32) {
+        self.samples.push_back((now, block_height));
+        // Retain the samples within the window, along with the latest sample preceding it.
+        while self.samples.len() > 1 && now.duration_since(self.samples[1].0) > BLOCK_RATE_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    fn blocks_per_second_at(&self, now: Instant) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((first_time, first_height)), Some((_, last_height))) => {
+                // Average over the entire window, so that the rate decays when the ledger stalls.
+                let elapsed = now.duration_since(*first_time).min(BLOCK_RATE_WINDOW).as_secs_f64();
+                match elapsed > 0.0 {
+                    true => last_height.saturating_sub(*first_height) as f64 / elapsed,
+                    false => 0.0,
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_rate() {
+        let start = Instant::now();
+        let mut block_rate = BlockRate::default();
+        assert_eq!(block_rate.blocks_per_second_at(start), 0.0);
+
+        // Add 10 blocks per second for 10 seconds.
+        for i in 0..=10 {
+            block_rate.record_at(start + Duration::from_secs(i), 10 * i as u32);
+        }
+        assert_eq!(block_rate.blocks_per_second_at(start + Duration::from_secs(10)), 10.0);
+
+        // The rate decays while the ledger stalls.
+        assert_eq!(block_rate.blocks_per_second_at(start + Duration::from_secs(20)), 5.0);
+
+        // Samples outside of the window are discarded.
+        block_rate.record_at(start + Duration::from_secs(100), 100);
+        assert_eq!(block_rate.samples.len(), 2);
+
+        block_rate.reset();
+        assert_eq!(block_rate.blocks_per_second_at(start + Duration::from_secs(100)), 0.0);
+    }
+
+    #[test]
+    fn test_sync_status_percentage() {
+        let mut status = SyncStatus {
+            current_height: 50,
+            target_height: 200,
+            is_synced: false,
+            blocks_per_second: 0.0,
+            estimated_seconds_remaining: None,
+            syncing_from_peers: vec![],
+        };
+        assert_eq!(status.percentage(), 25.0);
+
+        status.target_height = 0;
+        assert_eq!(status.percentage(), 100.0);
+    }
+}
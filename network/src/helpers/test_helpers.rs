@@ -0,0 +1,106 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::helpers::HeaderLink;
+use snarkvm::{
+    prelude::{Network, PrivateKey, Testnet3},
+    utilities::test_crypto_rng_fixed,
+    Block,
+    Header,
+    Metadata,
+    VM,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use std::net::SocketAddr;
+
+pub(crate) type CurrentNetwork = Testnet3;
+
+/// Returns the genesis block of a fresh VM, sampled with a fixed RNG.
+pub(crate) fn sample_block() -> Block<CurrentNetwork> {
+    let mut vm = VM::<CurrentNetwork>::new().unwrap();
+    let rng = &mut test_crypto_rng_fixed();
+    let caller_private_key = PrivateKey::<CurrentNetwork>::new(rng).unwrap();
+    Block::genesis(&mut vm, &caller_private_key, rng).unwrap()
+}
+
+/// Returns a chain of `num_blocks` blocks extending the given block, each holding the transactions of the given block.
+pub(crate) fn sample_blocks(block: &Block<CurrentNetwork>, num_blocks: u32) -> Vec<Block<CurrentNetwork>> {
+    let mut blocks: Vec<Block<CurrentNetwork>> = Vec::with_capacity(num_blocks as usize);
+    let mut previous_block = block.clone();
+    let transactions = block.transactions().clone();
+
+    for block_height in block.header().height() + 1..=block.header().height() + num_blocks {
+        let previous_header = previous_block.header();
+        let metadata = Metadata::new(
+            CurrentNetwork::ID,
+            previous_header.round() + 1,
+            block_height,
+            previous_header.coinbase_target(),
+            previous_header.proof_target(),
+            previous_header.timestamp() + 1,
+        )
+        .unwrap();
+        let header = Header::from(*previous_header.previous_state_root(), transactions.to_root().unwrap(), metadata).unwrap();
+
+        let block = Block::from(previous_block.hash(), header, transactions.clone()).unwrap();
+        blocks.push(block.clone());
+        previous_block = block;
+    }
+    blocks
+}
+
+/// Returns a chain of `num_blocks` blocks extending the genesis block, each holding the transactions of a distinct caller,
+/// so that the blocks are accepted by the ledger.
+pub(crate) fn sample_canon_blocks(num_blocks: u32) -> Vec<Block<CurrentNetwork>> {
+    let mut blocks: Vec<Block<CurrentNetwork>> = Vec::with_capacity(num_blocks as usize);
+    let mut previous_block = sample_block();
+
+    for block_height in 1..=num_blocks {
+        // Sample the transactions of a new caller, with an RNG that is distinct from the genesis RNG.
+        let mut vm = VM::<CurrentNetwork>::new().unwrap();
+        let rng = &mut StdRng::seed_from_u64(block_height as u64);
+        let caller_private_key = PrivateKey::<CurrentNetwork>::new(rng).unwrap();
+        let transactions = Block::genesis(&mut vm, &caller_private_key, rng).unwrap().transactions().clone();
+
+        let previous_header = previous_block.header();
+        let metadata = Metadata::new(
+            CurrentNetwork::ID,
+            previous_header.round() + 1,
+            block_height,
+            previous_header.coinbase_target(),
+            previous_header.proof_target(),
+            previous_header.timestamp() + 1,
+        )
+        .unwrap();
+        let header = Header::from(*previous_header.previous_state_root(), transactions.to_root().unwrap(), metadata).unwrap();
+
+        let block = Block::from(previous_block.hash(), header, transactions).unwrap();
+        blocks.push(block.clone());
+        previous_block = block;
+    }
+    blocks
+}
+
+/// Returns the header link of the given block.
+pub(crate) fn header_link(block: &Block<CurrentNetwork>) -> HeaderLink<CurrentNetwork> {
+    (block.previous_hash(), block.hash(), block.header().clone())
+}
+
+/// Returns a loopback peer IP with the given port.
+pub(crate) fn peer(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
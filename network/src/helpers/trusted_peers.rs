@@ -0,0 +1,164 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+/// The duration to wait before reconnecting to a trusted peer after the first failed attempt.
+pub const TRUSTED_PEER_MINIMUM_BACKOFF: Duration = Duration::from_secs(5);
+/// The maximum duration to wait in between attempts to reconnect to a trusted peer.
+pub const TRUSTED_PEER_MAXIMUM_BACKOFF: Duration = Duration::from_secs(300);
+
+///
+/// The peers that the node keeps permanently connected to, regardless of peer churn or peer scores,
+/// with the number of consecutive connection attempts and the earliest time of the next attempt of each,
+/// so that a lost trusted peer is reconnected to with exponential backoff.
+///
+#[derive(Debug, Default)]
+pub struct TrustedPeers {
+    /// The map of each trusted peer to its number of consecutive attempts, and the earliest time of its next attempt.
+    peers: HashMap<SocketAddr, (u32, Instant)>,
+}
+
+impl TrustedPeers {
+    /// Initializes the given trusted peers, which are due for a connection attempt immediately.
+    pub fn new<I: IntoIterator<Item = SocketAddr>>(peers: I) -> Self {
+        let mut trusted_peers = Self::default();
+        trusted_peers.extend(peers);
+        trusted_peers
+    }
+
+    /// Adds the given trusted peers, which are due for a connection attempt immediately.
+    pub fn extend<I: IntoIterator<Item = SocketAddr>>(&mut self, peers: I) {
+        let now = Instant::now();
+        for peer_ip in peers {
+            self.peers.entry(peer_ip).or_insert((0, now));
+        }
+    }
+
+    /// Returns `true` if the given peer is trusted.
+    pub fn contains(&self, peer_ip: &SocketAddr) -> bool {
+        self.peers.contains_key(peer_ip)
+    }
+
+    /// Returns `true` if the given IP address belongs to a trusted peer.
+    pub fn contains_ip(&self, ip: &IpAddr) -> bool {
+        self.peers.keys().any(|peer_ip| peer_ip.ip() == *ip)
+    }
+
+    /// Returns the trusted peers, in increasing order.
+    pub fn peers(&self) -> Vec<SocketAddr> {
+        let mut peers = self.peers.keys().copied().collect::<Vec<_>>();
+        peers.sort();
+        peers
+    }
+
+    ///
+    /// Returns the trusted peers that are not connected, and are due for a connection attempt,
+    /// and records an attempt for each of them, which pushes back its next attempt by the backoff.
+    ///
+    pub fn next_attempts(&mut self, connected_peers: &HashSet<SocketAddr>) -> Vec<SocketAddr> {
+        self.next_attempts_at(connected_peers, Instant::now())
+    }
+
+    fn next_attempts_at(&mut self, connected_peers: &HashSet<SocketAddr>, now: Instant) -> Vec<SocketAddr> {
+        let mut peer_ips = Vec::new();
+        for (peer_ip, (attempts, next_attempt)) in self.peers.iter_mut() {
+            if !connected_peers.contains(peer_ip) && *next_attempt <= now {
+                *attempts = attempts.saturating_add(1);
+                *next_attempt = now + backoff(*attempts);
+                peer_ips.push(*peer_ip);
+            }
+        }
+        peer_ips.sort();
+        peer_ips
+    }
+
+    /// Resets the backoff of the given trusted peer, once it is connected.
+    pub fn reset(&mut self, peer_ip: &SocketAddr) {
+        if let Some(entry) = self.peers.get_mut(peer_ip) {
+            *entry = (0, Instant::now());
+        }
+    }
+
+    /// Returns the duration until the next connection attempt to the given trusted peer.
+    pub fn backoff_of(&self, peer_ip: &SocketAddr) -> Option<Duration> {
+        self.peers.get(peer_ip).map(|(_, next_attempt)| next_attempt.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns the number of trusted peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Returns `true` if there are no trusted peers.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+/// Returns the duration to wait after the given number of consecutive connection attempts, which doubles with each attempt.
+fn backoff(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    TRUSTED_PEER_MINIMUM_BACKOFF.saturating_mul(1 << exponent).min(TRUSTED_PEER_MAXIMUM_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::peer;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), TRUSTED_PEER_MINIMUM_BACKOFF);
+        assert_eq!(backoff(2), 2 * TRUSTED_PEER_MINIMUM_BACKOFF);
+        assert_eq!(backoff(3), 4 * TRUSTED_PEER_MINIMUM_BACKOFF);
+        assert_eq!(backoff(u32::MAX), TRUSTED_PEER_MAXIMUM_BACKOFF);
+    }
+
+    #[test]
+    fn test_next_attempts() {
+        let mut trusted_peers = TrustedPeers::new(vec![peer(4130), peer(4131)]);
+        let connected_peers = vec![peer(4131)].into_iter().collect::<HashSet<_>>();
+        let now = Instant::now();
+
+        // Only the disconnected trusted peer is attempted, and then backs off.
+        assert_eq!(trusted_peers.next_attempts_at(&connected_peers, now), vec![peer(4130)]);
+        assert!(trusted_peers.next_attempts_at(&connected_peers, now).is_empty());
+        let retry_at = now + TRUSTED_PEER_MINIMUM_BACKOFF;
+        assert_eq!(trusted_peers.next_attempts_at(&connected_peers, retry_at), vec![peer(4130)]);
+
+        // The backoff doubles with each consecutive attempt.
+        assert!(trusted_peers.next_attempts_at(&connected_peers, retry_at + TRUSTED_PEER_MINIMUM_BACKOFF).is_empty());
+        assert_eq!(trusted_peers.next_attempts_at(&connected_peers, retry_at + 2 * TRUSTED_PEER_MINIMUM_BACKOFF), vec![peer(4130)]);
+
+        // A connected trusted peer is attempted immediately after it is lost.
+        trusted_peers.reset(&peer(4130));
+        assert_eq!(trusted_peers.next_attempts(&HashSet::new()), vec![peer(4130), peer(4131)]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let trusted_peers = TrustedPeers::new(vec![peer(4130)]);
+        assert!(trusted_peers.contains(&peer(4130)));
+        assert!(!trusted_peers.contains(&peer(4131)));
+        assert!(trusted_peers.contains_ip(&peer(4131).ip()));
+        assert!(!trusted_peers.contains_ip(&"127.0.0.2".parse().unwrap()));
+    }
+}
@@ -27,32 +27,45 @@ use snarkos_environment::{
 use snarkos_storage::{
     storage::{rocksdb::RocksDB, ReadOnly, ReadWrite},
     LedgerState,
+    DEFAULT_BLOCK_CACHE_CAPACITY,
+    DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS,
+    MINIMUM_PRUNING_DEPTH,
 };
-use snarkvm::{prelude::*, Block};
+use snarkvm::{console::types::Field, prelude::*, Block};
 
 #[cfg(any(feature = "test", feature = "prometheus"))]
 use snarkos_metrics as metrics;
 
 use ::time::OffsetDateTime;
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     net::SocketAddr,
     path::Path,
     sync::{atomic::Ordering, Arc},
     time::{Duration, Instant},
 };
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::sync::{broadcast, oneshot, watch, Mutex, RwLock};
 
 /// The maximum number of unconfirmed blocks that can be held by the ledger.
 const MAXIMUM_UNCONFIRMED_BLOCKS: u32 = 250;
+/// The maximum number of blocks that are verified in parallel and added to the ledger as one batch.
+const MAXIMUM_BLOCK_BATCH_SIZE: usize = 50;
+/// The maximum number of ledger events that are buffered for a lagging subscriber.
+const LEDGER_EVENTS_CAPACITY: usize = 1024;
+/// The maximum number of blocks that are returned for a single block range query.
+pub const MAXIMUM_BLOCK_RANGE: u32 = 1000;
+/// The number of blocks that are read from storage at a time, to answer a block range query.
+const BLOCK_RANGE_CHUNK_SIZE: u32 = 50;
+/// The maximum number of blocks pruned in a single ledger update, bounding the time the canon lock is held.
+const MAXIMUM_BLOCKS_PRUNED_PER_UPDATE: u32 = 2 * MAXIMUM_UNCONFIRMED_BLOCKS;
 
 pub type LedgerReader<N> = std::sync::Arc<snarkos_storage::LedgerState<N, ReadOnly>>;
 
 /// Shorthand for the parent half of the `Ledger` message channel.
-pub type LedgerRouter<N> = mpsc::Sender<LedgerRequest<N>>;
+pub type LedgerRouter<N> = PrioritySender<LedgerRequest<N>>;
 /// Shorthand for the child half of the `Ledger` message channel.
-pub type LedgerHandler<N> = mpsc::Receiver<LedgerRequest<N>>;
+pub type LedgerHandler<N> = PriorityReceiver<LedgerRequest<N>>;
 
 ///
 /// An enum of requests that the `Ledger` struct processes.
@@ -61,10 +74,16 @@ pub type LedgerHandler<N> = mpsc::Receiver<LedgerRequest<N>>;
 pub enum LedgerRequest<N: Network> {
     /// BlockResponse := (peer_ip, block)
     BlockResponse(SocketAddr, Block<N>),
+    /// BlocksUnavailable := (peer_ip)
+    BlocksUnavailable(SocketAddr),
     /// Disconnect := (peer_ip, reason)
     Disconnect(SocketAddr, DisconnectReason),
     /// Failure := (peer_ip, failure)
     Failure(SocketAddr, String),
+    /// GetBlockRange := (start_block_height, end_block_height (inclusive), response_router)
+    GetBlockRange(u32, u32, oneshot::Sender<Result<Vec<Block<N>>>>),
+    /// HeadersResponse := (peer_ip, \[(previous_block_hash, block_hash, block_header)\])
+    HeadersResponse(SocketAddr, Vec<HeaderLink<N>>),
     /// Heartbeat
     Heartbeat,
     /// Pong := (peer_ip, node_type, status, is_fork, block_locators)
@@ -73,6 +92,48 @@ pub enum LedgerRequest<N: Network> {
     UnconfirmedBlock(SocketAddr, Block<N>),
 }
 
+impl<N: Network> LedgerRequest<N> {
+    ///
+    /// Returns `true` if the request is relevant to the tip of the network, and is processed ahead of queued sync requests.
+    /// During sync, an unconfirmed block for the tip would otherwise wait behind hundreds of block responses.
+    ///
+    pub fn is_priority(&self) -> bool {
+        matches!(self, Self::UnconfirmedBlock(..))
+    }
+}
+
+///
+/// The configuration of the ledger.
+///
+#[derive(Clone, Debug)]
+pub struct LedgerConfig {
+    /// The depth beyond which the transactions of blocks are pruned, or `None` to keep every block in full.
+    pub pruning_depth: Option<u32>,
+    /// The number of recently accessed blocks held in memory by the ledger reader.
+    pub block_cache_capacity: usize,
+    /// The limit on the number of blocks reverted to switch to a fork.
+    pub reorg_limit: ReorgLimit,
+    /// If `true`, the index of transactions by record owner and commitment is maintained.
+    pub transaction_index: bool,
+    /// If `true`, each storage batch is synced to disk before it returns.
+    pub sync_writes: bool,
+    /// The number of seconds that the timestamp of a block may be ahead of the local clock.
+    pub maximum_future_block_time: u32,
+}
+
+impl Default for LedgerConfig {
+    fn default() -> Self {
+        Self {
+            pruning_depth: None,
+            block_cache_capacity: DEFAULT_BLOCK_CACHE_CAPACITY,
+            reorg_limit: ReorgLimit::default(),
+            transaction_index: false,
+            sync_writes: false,
+            maximum_future_block_time: DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS,
+        }
+    }
+}
+
 pub type PeersState<N> = HashMap<SocketAddr, Option<(NodeType, Status, Option<bool>, u32, BlockLocators<N>)>>;
 
 ///
@@ -87,36 +148,102 @@ pub struct Ledger<N: Network, E: Environment> {
     /// The canonical chain of blocks in read-only mode.
     canon_reader: LedgerReader<N>,
     /// A lock to ensure methods that need to be mutually-exclusive are enforced.
-    /// In this context, `add_block`, and `revert_to_block_height` must be mutually-exclusive.
+    /// In this context, `add_block`, `add_blocks`, and `revert_to_block_height` must be mutually-exclusive.
     canon_lock: Mutex<()>,
     /// A map of previous block hashes to unconfirmed blocks.
     unconfirmed_blocks: RwLock<CircularMap<N::BlockHash, Block<N>, { MAXIMUM_UNCONFIRMED_BLOCKS }>>,
+    /// A pool of blocks whose previous block is not yet known to the ledger, keyed by previous block hash.
+    orphan_blocks: RwLock<OrphanPool<N>>,
+    /// A map of block hashes to the peers that sent the block in a block response.
+    block_responders: RwLock<CircularMap<N::BlockHash, SocketAddr, { MAXIMUM_UNCONFIRMED_BLOCKS }>>,
     /// The map of each peer to their ledger state := (node_type, status, is_fork, latest_block_height, block_locators).
     peers_state: RwLock<PeersState<N>>,
-    /// The map of each peer to their block requests := HashMap<(block_height, block_hash), timestamp>
-    block_requests: RwLock<HashMap<SocketAddr, HashMap<BlockRequest<N>, i64>>>,
+    /// The map of each peer to their block requests := HashMap<(block_height, block_hash), time_of_request>
+    block_requests: RwLock<HashMap<SocketAddr, HashMap<BlockRequest<N>, Instant>>>,
+    /// The chain of validated block headers ahead of the canon chain, along which blocks are requested.
+    header_chain: RwLock<HeaderChain<N>>,
     /// A lock to ensure methods that need to be mutually-exclusive are enforced.
-    /// In this context, `update_ledger`, `add_block`, and `update_block_requests` must be mutually-exclusive.
+    /// In this context, `update_ledger`, `add_block`, `add_blocks`, and `update_block_requests` must be mutually-exclusive.
     block_requests_lock: Mutex<()>,
     /// The timestamp of the last successful block update.
     last_block_update_timestamp: RwLock<Instant>,
+    /// The moving average of the number of blocks added to the ledger per second.
+    block_rate: RwLock<BlockRate>,
+    /// The depth beyond which the transactions of blocks are pruned, if the ledger is in pruned mode.
+    pruning_depth: Option<u32>,
+    /// The limit on the number of blocks reverted to switch to a fork.
+    reorg_limit: ReorgLimit,
     /// The map of each peer to their failure messages := (failure_message, timestamp).
     failures: RwLock<HashMap<SocketAddr, Vec<(String, i64)>>>,
+    /// The decaying block download statistics of each peer, which weigh the blocks requested from each peer.
+    peer_stats: RwLock<SyncPeerStats>,
+    /// The sender of the ledger events, which are published as the canon chain changes.
+    ledger_events: broadcast::Sender<LedgerEvent<N>>,
+    /// The tracker of the reorg in progress, if the ledger has reverted.
+    reorg_tracker: RwLock<ReorgTracker<N>>,
+    /// The sender of the latest block height and block hash, which is updated as the canon chain advances or reverts.
+    canon_tip: watch::Sender<(u32, N::BlockHash)>,
+    /// The highest block height of the peers before the node last shut down, which is the sync target until peers report.
+    sync_target_height: Option<u32>,
     /// The shared state of the owning node.
     state: State<N, E>,
 }
 
 impl<N: Network, E: Environment> Ledger<N, E> {
     /// Initializes a new instance of the ledger, paired with its handler.
-    pub async fn open<P: AsRef<Path> + Copy>(path: P, state: State<N, E>) -> Result<(Self, mpsc::Receiver<LedgerRequest<N>>)> {
-        // Initialize an mpsc channel for sending requests to the `Ledger` struct.
-        let (ledger_router, ledger_handler) = mpsc::channel(1024);
+    pub async fn open<P: AsRef<Path> + Copy>(path: P, config: LedgerConfig, state: State<N, E>) -> Result<(Self, LedgerHandler<N>)> {
+        let LedgerConfig {
+            pruning_depth,
+            block_cache_capacity,
+            reorg_limit,
+            transaction_index,
+            sync_writes,
+            maximum_future_block_time,
+        } = config;
+
+        // Ensure pruned blocks can never be reverted.
+        if let Some(pruning_depth) = pruning_depth {
+            ensure!(
+                pruning_depth >= MINIMUM_PRUNING_DEPTH,
+                "The pruning depth must be at least {}, found {}",
+                MINIMUM_PRUNING_DEPTH,
+                pruning_depth
+            );
+            ensure!(
+                reorg_limit.maximum_depth() <= pruning_depth,
+                "The maximum reorg depth must be at most the pruning depth of {}, found {}",
+                pruning_depth,
+                reorg_limit.maximum_depth()
+            );
+        }
+
+        // Initialize a priority channel for sending requests to the `Ledger` struct.
+        let (ledger_router, ledger_handler) = priority_channel(1024, LedgerRequest::is_priority);
 
         let canon = LedgerState::open_writer::<RocksDB, P>(path)?;
-        let (canon_reader, reader_resource) = LedgerState::open_reader::<RocksDB<ReadOnly>, P>(path)?;
+        canon.set_sync_writes(sync_writes);
+        canon.set_maximum_future_block_time(maximum_future_block_time);
+        // Build or resume the transaction index if it is enabled, and ensure a stale index is never served otherwise.
+        match transaction_index {
+            true => canon.enable_transaction_index()?,
+            false => canon.disable_transaction_index()?,
+        }
+        let (canon_reader, reader_resource) =
+            LedgerState::open_reader_with_cache_capacity::<RocksDB<ReadOnly>, P>(path, block_cache_capacity)?;
         // Register the thread; no need to provide an id, as it will run indefinitely.
         E::resources().register(reader_resource, None);
 
+        // Initialize the canon tip from the latest block.
+        let (canon_tip, _) = watch::channel((canon.latest_block_height(), canon.latest_block_hash()));
+        // Resume the sync progress from the highest block height of the peers, as last seen before shutdown.
+        let sync_target_height = canon.sync_target_height()?;
+        // Initialize the header chain from the latest block, so that the stored blocks are never requested again,
+        // and restore the stored headers that extend it, dropping the stale ones from storage.
+        let header_chain = HeaderChain::load(&canon.latest_block(), canon.get_header_links()?);
+        canon.clear_header_links()?;
+        canon.add_header_links(&header_chain.links())?;
+        let header_chain = RwLock::new(header_chain);
+
         // Initialize the ledger.
         let ledger = Self {
             ledger_router,
@@ -124,11 +251,22 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             canon_reader,
             canon_lock: Default::default(),
             unconfirmed_blocks: Default::default(),
+            orphan_blocks: Default::default(),
+            block_responders: Default::default(),
             peers_state: Default::default(),
             block_requests: Default::default(),
+            header_chain,
             block_requests_lock: Default::default(),
             last_block_update_timestamp: RwLock::new(Instant::now()),
+            block_rate: Default::default(),
+            pruning_depth,
+            reorg_limit,
             failures: Default::default(),
+            peer_stats: Default::default(),
+            ledger_events: broadcast::channel(LEDGER_EVENTS_CAPACITY).0,
+            reorg_tracker: Default::default(),
+            canon_tip,
+            sync_target_height,
             state,
         };
 
@@ -145,6 +283,85 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         &self.ledger_router
     }
 
+    /// Returns a new subscription to the ledger events.
+    pub fn subscribe(&self) -> broadcast::Receiver<LedgerEvent<N>> {
+        self.ledger_events.subscribe()
+    }
+
+    ///
+    /// Returns a new subscription to the latest block height and block hash of the canon chain.
+    /// Only the latest value is retained, so a slow subscriber never holds back the ledger.
+    ///
+    pub fn subscribe_canon_tip(&self) -> watch::Receiver<(u32, N::BlockHash)> {
+        self.canon_tip.subscribe()
+    }
+
+    /// Returns the sync progress of the ledger.
+    pub async fn sync_status(&self) -> SyncStatus {
+        let current_height = self.canon.latest_block_height();
+
+        // Determine the target height from the block locators of the connected peers,
+        // or from the peers before the last shutdown, until a connected peer reports its block height.
+        let (maximum_block_height_of_peers, number_of_peers) = {
+            let peers_state = self.peers_state.read().await;
+            (maximum_block_height_of_peers(&*peers_state), peers_state.len())
+        };
+        let target_height = maximum_block_height_of_peers.or(self.sync_target_height).unwrap_or_default().max(current_height);
+
+        // The ledger is synced under the same conditions that `update_status` sets the status to `Ready`.
+        let is_synced = number_of_peers >= E::MINIMUM_NUMBER_OF_PEERS && !is_out_of_date(current_height, maximum_block_height_of_peers);
+
+        let blocks_per_second = self.block_rate.read().await.blocks_per_second();
+        let estimated_seconds_remaining = match target_height - current_height {
+            0 => Some(0),
+            _ if blocks_per_second > 0.0 => Some(((target_height - current_height) as f64 / blocks_per_second).ceil() as u64),
+            _ => None,
+        };
+
+        let syncing_from_peers = self
+            .block_requests
+            .read()
+            .await
+            .iter()
+            .filter(|(_, requests)| !requests.is_empty())
+            .map(|(peer_ip, _)| *peer_ip)
+            .collect();
+
+        SyncStatus {
+            current_height,
+            target_height,
+            is_synced,
+            blocks_per_second,
+            estimated_seconds_remaining,
+            syncing_from_peers,
+        }
+    }
+
+    ///
+    /// Returns `true` if the given record commitment is created by a block that is not in the canon chain,
+    /// which is either awaiting its previous blocks, or is an orphan block.
+    ///
+    pub async fn contains_non_canon_commitment(&self, commitment: &Field<N>) -> bool {
+        let is_created_by = |block: &Block<N>| block.transactions().commitments().any(|candidate| candidate == commitment);
+        self.unconfirmed_blocks.read().await.values().any(is_created_by) || self.orphan_blocks.read().await.blocks().any(is_created_by)
+    }
+
+    /// Returns the block download statistics of the connected peers, marking the trusted peers.
+    pub async fn peer_sync_stats(&self) -> Vec<PeerSyncStats> {
+        let mut peer_ips = self.peers_state.read().await.keys().copied().collect::<Vec<_>>();
+        peer_ips.sort();
+
+        let trusted_peers = self.state.peers().trusted_peers().await;
+        let peer_stats = self.peer_stats.read().await;
+        peer_ips
+            .into_iter()
+            .map(|peer_ip| PeerSyncStats {
+                is_trusted: trusted_peers.contains(&peer_ip),
+                ..peer_stats.get(peer_ip)
+            })
+            .collect()
+    }
+
     pub async fn shut_down(&self) {
         debug!("Ledger is shutting down...");
 
@@ -154,7 +371,16 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
         // Clear the unconfirmed blocks.
         self.unconfirmed_blocks.write().await.clear();
-        trace!("[ShuttingDown] Pending queue has been cleared");
+        self.orphan_blocks.write().await.clear();
+        trace!("[ShuttingDown] Pending queue and orphan pool have been cleared");
+
+        // Store the highest block height of the peers, so that the sync progress is known when the node restarts.
+        if let Some(block_height) = maximum_block_height_of_peers(&*self.peers_state.read().await) {
+            match self.canon.set_sync_target_height(block_height) {
+                Ok(()) => trace!("[ShuttingDown] Sync target height {} has been stored", block_height),
+                Err(error) => warn!("[ShuttingDown] Failed to store the sync target height: {}", error),
+            }
+        }
 
         // Disconnect all connected peers.
         let connected_peers = self.peers_state.read().await.keys().copied().collect::<Vec<_>>();
@@ -171,10 +397,46 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     pub(super) async fn update(&self, request: LedgerRequest<N>) {
         match request {
             LedgerRequest::BlockResponse(peer_ip, block) => {
+                let block_height = block.header().height();
+
+                // Ensure the block matches the block hash requested from this peer, if one was specified.
+                if let Some(requested_block_hash) = self.requested_block_hash(peer_ip, block_height).await {
+                    if requested_block_hash != block.hash() {
+                        // Ban the peer, which removes its block requests, so that the block is requested from another peer.
+                        self.ban_invalid_block_peer(peer_ip, format!("Received a mismatched block {}", block_height))
+                            .await;
+                        return;
+                    }
+                }
+
                 // Remove the block request from the ledger.
-                if self.remove_block_request(peer_ip, block.header().height()).await {
-                    // On success, process the block response.
-                    self.add_block(block).await;
+                if let Some(time_of_request) = self.remove_block_request(peer_ip, block_height).await {
+                    // Record the download performance of the peer, which weighs the blocks requested from it.
+                    match bincode::serialized_size(&block) {
+                        Ok(number_of_bytes) => {
+                            self.peer_stats.write().await.record_response(peer_ip, number_of_bytes as usize, time_of_request)
+                        }
+                        Err(error) => warn!("Failed to determine the size of block {}: {}", block_height, error),
+                    }
+
+                    // Track the peer that sent the block, so that it may be penalized if the block is invalid.
+                    self.block_responders.write().await.insert(block.hash(), peer_ip);
+
+                    // On success, process the block response. While syncing, the block is queued,
+                    // so that the ledger can verify the block responses in parallel batches.
+                    match E::status().is_syncing() {
+                        // Bound the pending queue, as chunks of blocks may arrive out of order from multiple peers.
+                        // A dropped block is requested again once the ledger has caught up to it.
+                        true => match block_height > self.canon.latest_block_height() + MAXIMUM_UNCONFIRMED_BLOCKS {
+                            true => trace!("Dropping block {} from {}, as it is too far ahead of the ledger", block_height, peer_ip),
+                            false => self.add_unconfirmed_block(block).await,
+                        },
+                        false => {
+                            if self.add_block(block).await {
+                                self.connect_orphan_blocks().await;
+                            }
+                        }
+                    }
                     // Check if syncing with this peer is complete.
                     if self
                         .block_requests
@@ -185,17 +447,58 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                         .unwrap_or(false)
                     {
                         trace!("All block requests with {} have been processed", peer_ip);
+                        // Add the queued block responses to the ledger.
+                        self.update_ledger().await;
                         // TODO (raychu86): Reintroduce block requests.
                         // self.update_block_requests().await;
                     }
                 }
             }
+            LedgerRequest::BlocksUnavailable(peer_ip) => {
+                debug!("{} has none of the requested blocks, as they are beyond its latest block", peer_ip);
+                // Forget the block requests of the peer, so that the blocks are requested from other peers.
+                {
+                    let _block_requests_lock = self.block_requests_lock.lock().await;
+                    if let Some(requests) = self.block_requests.write().await.get_mut(&peer_ip) {
+                        *requests = Default::default();
+                    }
+                }
+                self.update_header_sync().await;
+            }
             LedgerRequest::Disconnect(peer_ip, reason) => {
                 self.disconnect(peer_ip, reason).await;
             }
             LedgerRequest::Failure(peer_ip, failure) => {
                 self.add_failure(peer_ip, failure).await;
             }
+            LedgerRequest::GetBlockRange(start_block_height, end_block_height, response_router) => {
+                let response = self.get_block_range(start_block_height, end_block_height).await;
+                if response_router.send(response).is_err() {
+                    warn!("[GetBlockRange] The requester of blocks {} to {} has dropped", start_block_height, end_block_height);
+                }
+            }
+            LedgerRequest::HeadersResponse(peer_ip, links) => {
+                let number_of_headers = links.len();
+                let result = {
+                    let mut header_chain = self.header_chain.write().await;
+                    let result = header_chain.extend(peer_ip, links.clone());
+                    // Store the headers, so that they are not downloaded again after a restart.
+                    if result.is_ok() {
+                        if let Err(error) = self.canon.add_header_links(&links) {
+                            warn!("Failed to store the headers: {}", error);
+                        }
+                    }
+                    result
+                };
+                match result {
+                    Ok(()) => {
+                        debug!("Received {} headers from {}", number_of_headers, peer_ip);
+                        // Request the next headers, and the blocks along the extended header chain.
+                        self.update_header_sync().await;
+                    }
+                    Err(error) => self.add_failure(peer_ip, format!("{}", error)).await,
+                }
+            }
             LedgerRequest::Heartbeat => {
                 // Update for sync nodes.
                 self.update_beacon_nodes().await;
@@ -203,15 +506,38 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 self.update_ledger().await;
                 // Update the status of the ledger.
                 self.update_status().await;
+                // Log the sync progress of the ledger.
+                if E::status().is_syncing() {
+                    let sync_status = self.sync_status().await;
+                    let estimated_time_remaining = match sync_status.estimated_seconds_remaining {
+                        Some(seconds) => format!("{}s", seconds),
+                        None => "unknown".to_string(),
+                    };
+                    info!(
+                        "Syncing at block {} of {} ({:.2}%), {:.2} blocks/s, {} remaining, from {} peers",
+                        sync_status.current_height,
+                        sync_status.target_height,
+                        sync_status.percentage(),
+                        sync_status.blocks_per_second,
+                        estimated_time_remaining,
+                        sync_status.syncing_from_peers.len()
+                    );
+                }
                 // Remove expired block requests.
                 self.remove_expired_block_requests().await;
+                // Remove expired orphan blocks.
+                self.remove_expired_orphan_blocks().await;
                 // Remove expired failures.
                 self.remove_expired_failures().await;
+                // Remove the download statistics of peers that have decayed.
+                self.peer_stats.write().await.remove_expired();
                 // Disconnect from peers with frequent failures.
                 self.disconnect_from_failing_peers().await;
                 // Update the block requests.
                 // TODO (raychu86): Reintroduce block requests.
                 // self.update_block_requests().await;
+                // Update the header chain, and request the blocks along it.
+                self.update_header_sync().await;
 
                 let block_requests = self.number_of_block_requests().await;
                 let connected_peers = self.peers_state.read().await.len();
@@ -226,6 +552,12 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 );
             }
             LedgerRequest::Pong(peer_ip, node_type, status, is_fork, block_locators, _rtt_start) => {
+                // Ensure a peer that repeatedly served invalid blocks remains banned, until its strikes have decayed.
+                // The trusted peers are never banned.
+                if !self.state.peers().is_trusted(&peer_ip).await && self.state.peers().is_struck_out(peer_ip).await {
+                    self.disconnect_and_restrict(peer_ip, DisconnectReason::TooManyFailures).await;
+                    return;
+                }
                 // Ensure the peer has been initialized in the ledger.
                 self.initialize_peer(peer_ip).await;
                 // Process the pong.
@@ -241,14 +573,21 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             LedgerRequest::UnconfirmedBlock(peer_ip, block) => {
                 // Ensure the node is not peering.
                 if !E::status().is_peering() {
-                    // Process the unconfirmed block.
-                    self.add_block(block.clone()).await;
+                    // Process the unconfirmed block, holding it in the orphan pool if its previous block is unknown.
+                    if self.is_orphan_block(&block).await {
+                        self.add_orphan_block(peer_ip, block.clone()).await;
+                    } else if self.add_block(block.clone()).await {
+                        self.connect_orphan_blocks().await;
+                    }
                     // Propagate the unconfirmed block to the connected peers.
                     let message = Message::UnconfirmedBlock(block.header().height(), block.hash(), Data::Object(block));
                     let request = PeersRequest::MessagePropagate(peer_ip, message);
                     if let Err(error) = self.state.peers().router().send(request).await {
                         warn!("[UnconfirmedBlock] {}", error);
                     }
+                } else {
+                    // Forget the skipped block, so that it is processed if it is received again.
+                    self.state.seen_blocks().remove(&block.hash());
                 }
             }
         }
@@ -347,13 +686,45 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     async fn update_ledger(&self) {
         // Check for candidate blocks to fast forward the ledger.
         let mut block_hash = self.canon.latest_block_hash();
-        let unconfirmed_blocks_snapshot = self.unconfirmed_blocks.read().await.clone();
-        while let Some(unconfirmed_block) = unconfirmed_blocks_snapshot.get(&block_hash) {
-            // Attempt to add the unconfirmed block.
-            match self.add_block(unconfirmed_block.clone()).await {
-                // Upon success, update the block hash iterator.
-                true => block_hash = unconfirmed_block.hash(),
-                false => break,
+        let mut candidate_blocks = Vec::new();
+        {
+            let unconfirmed_blocks = self.unconfirmed_blocks.read().await;
+            while let Some(unconfirmed_block) = unconfirmed_blocks.get(&block_hash) {
+                block_hash = unconfirmed_block.hash();
+                candidate_blocks.push(unconfirmed_block.clone());
+            }
+        }
+
+        // Attempt to add the candidate blocks in batches, stopping at the first batch that fails.
+        for batch in candidate_blocks.chunks(MAXIMUM_BLOCK_BATCH_SIZE) {
+            let is_success = match batch.len() {
+                1 => self.add_block(batch[0].clone()).await,
+                _ => self.add_blocks(batch).await,
+            };
+            if !is_success {
+                // Add the blocks of a rejected batch one at a time, to isolate the invalid block.
+                if batch.len() > 1 {
+                    for block in batch {
+                        if !self.add_block(block.clone()).await {
+                            break;
+                        }
+                    }
+                }
+                break;
+            }
+        }
+
+        // Attempt to connect the orphan blocks to the new canon chain.
+        self.connect_orphan_blocks().await;
+
+        // Prune the transactions of the blocks buried beyond the pruning depth, if enabled.
+        // The pruning is capped per update, so a large backlog is pruned over several updates.
+        if let Some(pruning_depth) = self.pruning_depth {
+            // Acquire the lock for the canon chain.
+            let _canon_lock = self.canon_lock.lock().await;
+
+            if let Err(error) = self.canon.prune_to_depth(pruning_depth, Some(MAXIMUM_BLOCKS_PRUNED_PER_UPDATE)) {
+                warn!("Failed to prune the ledger: {}", error);
             }
         }
 
@@ -411,16 +782,11 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
             // Retrieve the latest block height of this node.
             let latest_block_height = self.canon.latest_block_height();
-            // Iterate through the connected peers, to determine if the ledger state is out of date.
-            for (_, peer_state) in self.peers_state.read().await.iter() {
-                if let Some((_, _, Some(_), block_height, block_locators)) = peer_state {
-                    // If the block height is greater than MAXIMUM_LINEAR_BLOCK_LOCATORS, set the status to `Syncing`.
-                    if block_height.saturating_sub(latest_block_height) > MAXIMUM_LINEAR_BLOCK_LOCATORS {
-                        // Set the status to `Syncing`.
-                        status = Status::Syncing;
-                        break;
-                    }
-                }
+            // Determine if the ledger state is out of date with respect to the connected peers.
+            let maximum_block_height_of_peers = maximum_block_height_of_peers(&*self.peers_state.read().await);
+            if is_out_of_date(latest_block_height, maximum_block_height_of_peers) {
+                // Set the status to `Syncing`.
+                status = Status::Syncing;
             }
         }
 
@@ -435,6 +801,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
         // Update the ledger to the determined status.
         E::status().update(status);
+        self.canon_reader.set_synced(status == Status::Ready || status == Status::Mining);
     }
 
     ///
@@ -451,6 +818,8 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         let unconfirmed_block_hash = unconfirmed_block.hash();
         // Retrieve the unconfirmed previous block hash.
         let unconfirmed_previous_block_hash = unconfirmed_block.previous_hash();
+        // The peer that served the block, if the block is invalid. It is banned once the locks are released.
+        let mut invalid_block_responder = None;
 
         // Ensure the given block is new.
         if let Ok(true) = self.canon.contains_block_hash(&unconfirmed_block_hash) {
@@ -467,24 +836,8 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             // Acquire the lock for the canon chain.
             let _canon_lock = self.canon_lock.lock().await;
 
-            // Ensure the block height is not part of a block request on a fork.
-            let mut is_block_on_fork = false;
-            'outer: for requests in self.block_requests.read().await.values() {
-                for request in requests.keys() {
-                    // If the unconfirmed block conflicts with a requested block on a fork, skip.
-                    if request.block_height() == unconfirmed_block_height {
-                        if let Some(requested_block_hash) = request.block_hash() {
-                            if unconfirmed_block.hash() != requested_block_hash {
-                                is_block_on_fork = true;
-                                break 'outer;
-                            }
-                        }
-                    }
-                }
-            }
-
             // If the unconfirmed block is not on a fork, attempt to add it as the next block.
-            match is_block_on_fork {
+            match self.is_block_on_fork(&unconfirmed_block).await {
                 // Filter out the undesirable unconfirmed blocks, if it exists.
                 true => self.unconfirmed_blocks.write().await.remove(&unconfirmed_previous_block_hash),
                 // Attempt to add the unconfirmed block as the next block in the canonical chain.
@@ -502,48 +855,315 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
                         // Update the timestamp of the last block increment.
                         *self.last_block_update_timestamp.write().await = Instant::now();
+                        // Update the rate of added blocks.
+                        self.block_rate.write().await.record(latest_block_height);
+                        // Publish the ledger events of the added block.
+                        self.publish_added_blocks(std::slice::from_ref(&unconfirmed_block)).await;
                         // Set the terminator bit to `true` to ensure the miner updates state.
                         E::terminator().store(true, Ordering::SeqCst);
                         // On success, filter the unconfirmed blocks of this block, if it exists.
                         self.unconfirmed_blocks.write().await.remove(&unconfirmed_previous_block_hash);
-
-                        // TODO (raychu86): Reintroduce this once provers are implemented.
-                        // // On success, filter the memory pool of its transactions, if they exist.
-                        // if let Err(error) = self
-                        //     .state
-                        //     .prover()
-                        //     .router()
-                        //     .send(ProverRequest::MemoryPoolClear(Some(unconfirmed_block)))
-                        //     .await
-                        // {
-                        //     error!("[MemoryPoolClear]: {}", error);
-                        // }
+                        // On success, filter the memory pool of its transactions, if they exist.
+                        self.state
+                            .memory_pool()
+                            .remove_transactions(unconfirmed_block.transactions().iter().map(|(_, transaction)| transaction));
 
                         return true;
                     }
-                    Err(error) => warn!("{}", error),
+                    Err(error) => {
+                        warn!("{}", error);
+                        // Filter out the invalid block, so that it is requested again.
+                        self.unconfirmed_blocks.write().await.remove(&unconfirmed_previous_block_hash);
+                        // Forget the rejected block, as it may be rejected for a transient reason.
+                        self.state.seen_blocks().remove(&unconfirmed_block_hash);
+                        // Ban the peer that sent the invalid block, if it was received in a block response.
+                        invalid_block_responder = self.block_responders.read().await.get(&unconfirmed_block_hash).copied();
+                        // Abandon the header branch of the invalid block, and penalize the peer that served its headers.
+                        let header_peer = {
+                            let mut header_chain = self.header_chain.write().await;
+                            match header_chain.get_block_hash(unconfirmed_block_height) == Some(unconfirmed_block_hash) {
+                                true => {
+                                    let (tip_block_height, _) = header_chain.tip();
+                                    if let Err(error) = self.canon.remove_header_links(unconfirmed_block_height, tip_block_height) {
+                                        warn!("Failed to remove the stored headers: {}", error);
+                                    }
+                                    header_chain.abandon(unconfirmed_block_height)
+                                }
+                                false => None,
+                            }
+                        };
+                        if let Some(peer_ip) = header_peer {
+                            warn!("Abandoning the header chain from block {}", unconfirmed_block_height);
+                            self.add_failure(peer_ip, format!("Sent the headers of an invalid block {}", unconfirmed_block_height))
+                                .await;
+                        }
+                    }
                 },
             }
         } else {
             // Add the block to the unconfirmed blocks.
-            if self
-                .unconfirmed_blocks
-                .write()
-                .await
-                .insert(unconfirmed_previous_block_hash, unconfirmed_block)
-            {
-                trace!("Added unconfirmed block {} to the pending queue", unconfirmed_block_height);
-            } else {
-                trace!(
-                    "Pending queue already contains unconfirmed block {} ({})",
-                    unconfirmed_block_height,
-                    unconfirmed_block_hash
+            self.add_unconfirmed_block(unconfirmed_block).await;
+        }
+
+        if let Some(peer_ip) = invalid_block_responder {
+            self.ban_invalid_block_peer(peer_ip, format!("Sent an invalid block {}", unconfirmed_block_height))
+                .await;
+        }
+        false
+    }
+
+    ///
+    /// Adds the given blocks, which must form a chain extending the canon chain, as the next blocks in the ledger.
+    /// The blocks are verified in parallel, and if any block in the batch is invalid, the entire batch is rejected.
+    ///
+    /// Returns `true` if the given blocks are successfully added to the *canon* chain.
+    ///
+    async fn add_blocks(&self, unconfirmed_blocks: &[Block<N>]) -> bool {
+        // Acquire the lock for block requests.
+        let _block_requests_lock = self.block_requests_lock.lock().await;
+        // Acquire the lock for the canon chain.
+        let _canon_lock = self.canon_lock.lock().await;
+
+        // Ensure the blocks are not part of a block request on a fork.
+        for unconfirmed_block in unconfirmed_blocks {
+            if self.is_block_on_fork(unconfirmed_block).await {
+                // Filter out the undesirable unconfirmed block.
+                self.unconfirmed_blocks.write().await.remove(&unconfirmed_block.previous_hash());
+                return false;
+            }
+        }
+
+        // Attempt to add the unconfirmed blocks as the next blocks in the canonical chain.
+        match self.canon.add_next_blocks(unconfirmed_blocks, E::thread_pool()) {
+            Ok(()) => {
+                let latest_block_height = self.canon.latest_block_height();
+                info!(
+                    "Ledger successfully advanced to block {} ({})",
+                    latest_block_height,
+                    self.canon.latest_block_hash()
+                );
+
+                #[cfg(any(feature = "test", feature = "prometheus"))]
+                metrics::gauge!(metrics::blocks::HEIGHT, latest_block_height as f64);
+
+                // Update the timestamp of the last block increment.
+                *self.last_block_update_timestamp.write().await = Instant::now();
+                // Update the rate of added blocks.
+                self.block_rate.write().await.record(latest_block_height);
+                // Publish the ledger events of the added blocks.
+                self.publish_added_blocks(unconfirmed_blocks).await;
+                // Set the terminator bit to `true` to ensure the miner updates state.
+                E::terminator().store(true, Ordering::SeqCst);
+                // On success, filter the unconfirmed blocks of these blocks.
+                let mut unconfirmed_blocks_queue = self.unconfirmed_blocks.write().await;
+                for unconfirmed_block in unconfirmed_blocks {
+                    unconfirmed_blocks_queue.remove(&unconfirmed_block.previous_hash());
+                }
+                // On success, filter the memory pool of their transactions, if they exist.
+                self.state.memory_pool().remove_transactions(
+                    unconfirmed_blocks
+                        .iter()
+                        .flat_map(|block| block.transactions().iter().map(|(_, transaction)| transaction)),
                 );
+                true
+            }
+            Err(error) => {
+                warn!("{}", error);
+                false
+            }
+        }
+    }
+
+    ///
+    /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive),
+    /// which are all read from the same view of the canon chain.
+    ///
+    async fn get_block_range(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>> {
+        ensure!(
+            start_block_height <= end_block_height,
+            "Invalid block range {} to {}",
+            start_block_height,
+            end_block_height
+        );
+        ensure!(
+            end_block_height - start_block_height < MAXIMUM_BLOCK_RANGE,
+            "Requested {} blocks, exceeding the maximum of {} blocks",
+            end_block_height as u64 - start_block_height as u64 + 1,
+            MAXIMUM_BLOCK_RANGE
+        );
+
+        // Acquire the lock for the canon chain, so that the blocks are not reverted during the query.
+        let _canon_lock = self.canon_lock.lock().await;
+        ensure!(
+            end_block_height <= self.canon.latest_block_height(),
+            "Block {} does not exist in the canon chain",
+            end_block_height
+        );
+
+        // Read the blocks in chunks, yielding between chunks so that a large range does not stall the other tasks.
+        let mut blocks: Vec<Block<N>> = Vec::with_capacity((end_block_height - start_block_height + 1) as usize);
+        let mut chunk_start = start_block_height;
+        while chunk_start <= end_block_height {
+            let chunk_end = std::cmp::min(chunk_start.saturating_add(BLOCK_RANGE_CHUNK_SIZE - 1), end_block_height);
+            let chunk = self.canon.get_blocks(chunk_start, chunk_end)?;
+
+            // Ensure the chunk extends the blocks that were read so far.
+            if let (Some(previous_block), Some(block)) = (blocks.last(), chunk.first()) {
+                ensure!(
+                    block.previous_hash() == previous_block.hash(),
+                    "The canon chain changed while reading blocks {} to {}",
+                    start_block_height,
+                    end_block_height
+                );
+            }
+            blocks.extend(chunk);
+
+            chunk_start = chunk_end + 1;
+            tokio::task::yield_now().await;
+        }
+
+        Ok(blocks)
+    }
+
+    ///
+    /// Returns `true` if the given block conflicts with a block request on a fork.
+    ///
+    async fn is_block_on_fork(&self, block: &Block<N>) -> bool {
+        let block_height = block.header().height();
+        for requests in self.block_requests.read().await.values() {
+            for request in requests.keys() {
+                // If the block conflicts with a requested block on a fork, skip.
+                if request.block_height() == block_height {
+                    if let Some(requested_block_hash) = request.block_hash() {
+                        if block.hash() != requested_block_hash {
+                            return true;
+                        }
+                    }
+                }
             }
         }
         false
     }
 
+    ///
+    /// Adds the given block to the pending queue of unconfirmed blocks.
+    ///
+    async fn add_unconfirmed_block(&self, unconfirmed_block: Block<N>) {
+        let unconfirmed_block_height = unconfirmed_block.header().height();
+        let unconfirmed_block_hash = unconfirmed_block.hash();
+        if self
+            .unconfirmed_blocks
+            .write()
+            .await
+            .insert(unconfirmed_block.previous_hash(), unconfirmed_block)
+        {
+            trace!("Added unconfirmed block {} to the pending queue", unconfirmed_block_height);
+        } else {
+            trace!(
+                "Pending queue already contains unconfirmed block {} ({})",
+                unconfirmed_block_height,
+                unconfirmed_block_hash
+            );
+        }
+    }
+
+    ///
+    /// Returns `true` if the previous block of the given block is neither in the canon chain nor pending.
+    ///
+    async fn is_orphan_block(&self, block: &Block<N>) -> bool {
+        let previous_block_hash = block.previous_hash();
+        if let Ok(true) = self.canon.contains_block_hash(&previous_block_hash) {
+            return false;
+        }
+        !self
+            .unconfirmed_blocks
+            .read()
+            .await
+            .values()
+            .any(|unconfirmed_block| unconfirmed_block.hash() == previous_block_hash)
+    }
+
+    ///
+    /// Adds the given block from the given peer to the orphan pool.
+    ///
+    async fn add_orphan_block(&self, peer_ip: SocketAddr, block: Block<N>) {
+        let (block_height, block_hash) = (block.header().height(), block.hash());
+        match self.orphan_blocks.write().await.insert(peer_ip, block) {
+            true => {
+                trace!("Added orphan block {} from {} to the orphan pool", block_height, peer_ip);
+
+                #[cfg(any(feature = "test", feature = "prometheus"))]
+                metrics::increment_counter!(metrics::blocks::ORPHANS_STORED);
+            }
+            false => {
+                trace!("Skipping orphan block {} from {}", block_height, peer_ip);
+                // Forget the skipped block, so that it is processed if it is received again once its parent is known.
+                self.state.seen_blocks().remove(&block_hash);
+            }
+        }
+    }
+
+    ///
+    /// Adds the orphan blocks that extend the canon chain to the ledger, until no orphan block extends the canon chain.
+    ///
+    async fn connect_orphan_blocks(&self) {
+        loop {
+            let children = self.orphan_blocks.write().await.remove_children(&self.canon.latest_block_hash());
+            if children.is_empty() {
+                break;
+            }
+
+            // Add the first valid child, as the remaining children are now on a fork of the canon chain.
+            let mut is_connected = false;
+            for child in children {
+                if self.add_block(child).await {
+                    is_connected = true;
+
+                    #[cfg(any(feature = "test", feature = "prometheus"))]
+                    metrics::increment_counter!(metrics::blocks::ORPHANS_CONNECTED);
+
+                    break;
+                }
+            }
+            if !is_connected {
+                break;
+            }
+        }
+    }
+
+    ///
+    /// Removes the orphan blocks that have expired or fallen too far behind the canon chain.
+    ///
+    async fn remove_expired_orphan_blocks(&self) {
+        let num_evicted = self.orphan_blocks.write().await.remove_expired(self.canon.latest_block_height());
+        if num_evicted > 0 {
+            trace!("Evicted {} orphan blocks from the orphan pool", num_evicted);
+
+            #[cfg(any(feature = "test", feature = "prometheus"))]
+            metrics::counter!(metrics::blocks::ORPHANS_EVICTED, num_evicted as u64);
+        }
+    }
+
+    ///
+    /// Publishes the ledger events of the given blocks, which were added to the canon chain.
+    ///
+    async fn publish_added_blocks(&self, blocks: &[Block<N>]) {
+        let mut reorg_tracker = self.reorg_tracker.write().await;
+        for block in blocks {
+            for event in reorg_tracker.add(block.header().height(), block.hash()) {
+                // An error only indicates that there are no subscribers.
+                let _ = self.ledger_events.send(event);
+            }
+        }
+        self.publish_canon_tip();
+    }
+
+    /// Publishes the latest block height and block hash of the canon chain to the subscribers.
+    fn publish_canon_tip(&self) {
+        let (latest_block_height, latest_block_hash) = (self.canon.latest_block_height(), self.canon.latest_block_hash());
+        self.canon_tip.send_replace((latest_block_height, latest_block_hash));
+    }
+
     ///
     /// Reverts the ledger state back to height `block_height`, returning `true` on success.
     ///
@@ -551,7 +1171,10 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         // Acquire the lock for the canon chain.
         let _canon_lock = self.canon_lock.lock().await;
 
-        match self.canon.revert_to_block_height(block_height) {
+        match self
+            .canon
+            .revert_to_block_height_with_maximum_depth(block_height, self.reorg_limit.maximum_revert_depth())
+        {
             Ok(removed_blocks) => {
                 let latest_block_height = self.canon.latest_block_height();
                 info!("Ledger successfully reverted to block {}", latest_block_height);
@@ -561,6 +1184,19 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
                 // Update the last block update timestamp.
                 *self.last_block_update_timestamp.write().await = Instant::now();
+                // Reset the rate of added blocks, as the ledger has reverted.
+                self.block_rate.write().await.reset();
+                // Track the reorg, which is published once the new chain catches up to the removed blocks.
+                self.reorg_tracker.write().await.revert(
+                    (latest_block_height, self.canon.latest_block_hash()),
+                    removed_blocks.iter().map(|block| (block.header().height(), block.hash())).collect(),
+                );
+                // Publish the reverted canon tip.
+                self.publish_canon_tip();
+
+                // Return the transactions of the removed blocks to the memory pool.
+                self.restore_transactions(&removed_blocks);
+
                 // Set the terminator bit to `true` to ensure the miner resets state.
                 E::terminator().store(true, Ordering::SeqCst);
 
@@ -586,6 +1222,119 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         }
     }
 
+    ///
+    /// Adds the transactions of the given blocks, which were removed from the canon chain, back to the memory pool.
+    /// The transactions that are no longer valid against the canon chain, and the coinbase transactions, are skipped.
+    ///
+    fn restore_transactions(&self, blocks: &[Block<N>]) {
+        for transaction in self.canon.get_disconnected_transactions(blocks) {
+            match self.state.memory_pool().add_transaction(&transaction) {
+                Ok(()) => debug!("Returned transaction {} of a disconnected block to the memory pool", transaction.id()),
+                Err(error) => trace!("Skipping transaction {} of a disconnected block: {}", transaction.id(), error),
+            }
+        }
+    }
+
+    ///
+    /// Reverts the ledger to the given common ancestor, in order to switch to the fork of the given peer, returning `true` on success.
+    ///
+    /// If the switch exceeds the maximum reorg depth, it is refused, and the peers serving the fork are disconnected.
+    ///
+    async fn revert_to_fork(&self, common_ancestor: u32, peer_ip: SocketAddr, fork_tip: (u32, Option<N::BlockHash>)) -> bool {
+        let latest_block_height = self.canon.latest_block_height();
+        if self.reorg_limit.permits(latest_block_height, common_ancestor) {
+            if latest_block_height.saturating_sub(common_ancestor) > self.reorg_limit.maximum_depth() {
+                warn!(
+                    "Switching to a fork from {} that reverts {} blocks, as deep reorgs are allowed",
+                    peer_ip,
+                    latest_block_height.saturating_sub(common_ancestor)
+                );
+            }
+            return self.revert_to_block_height(common_ancestor).await;
+        }
+
+        let (fork_block_height, fork_block_hash) = fork_tip;
+        error!(
+            "SECURITY WARNING: Refusing to switch to a fork from {} that reverts {} blocks, beyond the maximum reorg depth of {}. \
+             The local tip is block {} ({}), the fork tip is block {} ({}). If this is a genuine split of the network, \
+             restart the node with '--allow-deep-reorgs' to switch to the fork",
+            peer_ip,
+            latest_block_height.saturating_sub(common_ancestor),
+            self.reorg_limit.maximum_depth(),
+            latest_block_height,
+            self.canon.latest_block_hash(),
+            fork_block_height,
+            fork_block_hash.map(|block_hash| block_hash.to_string()).unwrap_or_else(|| "unknown".to_string()),
+        );
+
+        // Disconnect from the peers serving the fork.
+        let mut fork_peers = vec![peer_ip];
+        if let Some(fork_block_hash) = fork_block_hash {
+            fork_peers.extend(self.peers_state.read().await.iter().filter_map(|(ip, state)| match state {
+                Some((_, _, Some(true), _, locators)) if locators.get_block_hash(fork_block_height) == Some(fork_block_hash) => Some(*ip),
+                _ => None,
+            }));
+        }
+        fork_peers.sort();
+        fork_peers.dedup();
+        for fork_peer_ip in fork_peers {
+            self.disconnect_and_restrict(fork_peer_ip, DisconnectReason::ExceededForkRange).await;
+        }
+
+        false
+    }
+
+    ///
+    /// Switches to the fork of the highest peer that is ahead of this ledger on a fork, if any,
+    /// by reverting to the common ancestor of its block locators, returning `true` on success.
+    ///
+    async fn switch_to_fork(&self) -> bool {
+        let latest_block_height = self.canon.latest_block_height();
+
+        // Determine the highest peer that is on a fork, and ahead of this ledger.
+        let fork_peer = self
+            .peers_state
+            .read()
+            .await
+            .iter()
+            .filter_map(|(peer_ip, state)| match state {
+                Some((_, _, Some(true), block_height, block_locators)) if *block_height > latest_block_height => {
+                    Some((*peer_ip, *block_height, block_locators.clone()))
+                }
+                _ => None,
+            })
+            .max_by_key(|(_, block_height, _)| *block_height);
+        let (peer_ip, fork_block_height, block_locators) = match fork_peer {
+            Some(fork_peer) => fork_peer,
+            None => return false,
+        };
+
+        // Determine the common ancestor, as the highest block locator of the peer that is in the canon chain.
+        let common_ancestor = block_locators
+            .iter()
+            .filter_map(|(block_height, (block_hash, _))| match self.canon.get_block_height(block_hash) {
+                Ok(height) if height == *block_height => Some(height),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        // Revert to the common ancestor, unless the peer already extends the canon chain.
+        if common_ancestor < latest_block_height {
+            debug!("Switching to the fork of {} from block {}", peer_ip, common_ancestor);
+            let fork_tip = (fork_block_height, block_locators.get_block_hash(fork_block_height));
+            if !self.revert_to_fork(common_ancestor, peer_ip, fork_tip).await {
+                return false;
+            }
+        }
+
+        // Sync from the peer, as its chain now extends the canon chain.
+        if let Some(Some((_, _, is_fork, _, _))) = self.peers_state.write().await.get_mut(&peer_ip) {
+            *is_fork = Some(false);
+        }
+        true
+    }
+
     ///
     /// Adds an entry for the given peer IP to every data structure in `State`.
     ///
@@ -694,129 +1443,114 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         }
     }
 
-    // ///
-    // /// Proceeds to send block requests to a connected peer, if the ledger is out of date.
-    // ///
-    // async fn update_block_requests(&self) {
-    //     // Ensure the ledger is not awaiting responses from outstanding block requests.
-    //     if self.number_of_block_requests().await > 0 {
-    //         return;
-    //     }
-    //
-    //     // Retrieve the latest block height and cumulative weight of this ledger.
-    //     let latest_block_height = self.canon.latest_block_height();
-    //     let latest_cumulative_weight = self.canon.latest_cumulative_weight();
-    //
-    //     // Iterate through the peers to check if this node needs to catch up, and determine a peer to sync with.
-    //     // Prioritize the sync nodes before regular peers.
-    //     let mut maximum_block_height = latest_block_height;
-    //     let mut maximum_cumulative_weight = latest_cumulative_weight;
-    //
-    //     // Check if any of the peers are ahead and have a larger block height.
-    //     if let Some((peer_ip, maximal_peer_is_on_fork, maximum_block_locators)) = find_maximal_peer::<N, E>(
-    //         &*self.peers_state.read().await,
-    //         &mut maximum_block_height,
-    //         &mut maximum_cumulative_weight,
-    //     ) {
-    //         // Case 1 - Ensure the peer has a heavier canonical chain than this ledger.
-    //         // Note: this check is duplicated in `handle_block_requests`, as it is fast
-    //         // and allows us to skip acquiring `_block_requests_lock`.
-    //         if latest_cumulative_weight >= maximum_cumulative_weight {
-    //             return;
-    //         }
-    //
-    //         // Acquire the lock for block requests.
-    //         let _block_requests_lock = self.block_requests_lock.lock().await;
-    //
-    //         // Determine the common ancestor block height between this ledger and the peer
-    //         // and the first locator (smallest height) that does not exist in this ledger.
-    //         let (maximum_common_ancestor, first_deviating_locator) = match find_common_ancestor(&self.canon, &maximum_block_locators) {
-    //             Ok(ret) => ret,
-    //             Err(error) => {
-    //                 trace!("{}", error);
-    //                 self.add_failure(peer_ip, error).await;
-    //                 return;
-    //             }
-    //         };
-    //
-    //         // Case 2 - Prepare to send block requests, as the peer is ahead of this ledger.
-    //         let (start_block_height, end_block_height, ledger_is_on_fork) = match handle_block_requests::<N, E>(
-    //             latest_block_height,
-    //             latest_cumulative_weight,
-    //             peer_ip,
-    //             Some(maximal_peer_is_on_fork),
-    //             maximum_block_height,
-    //             maximum_cumulative_weight,
-    //             maximum_common_ancestor,
-    //             first_deviating_locator,
-    //         ) {
-    //             // Abort from the block request update.
-    //             BlockRequestHandler::Abort(_) => return,
-    //             // Disconnect from the peer if it is misbehaving and proceed to abort.
-    //             BlockRequestHandler::AbortAndDisconnect(_, reason) => {
-    //                 drop(_block_requests_lock);
-    //                 self.disconnect(peer_ip, reason).await;
-    //                 return;
-    //             }
-    //             // Proceed to send block requests to a connected peer, if the ledger is out of date.
-    //             BlockRequestHandler::Proceed(_, proceed) => {
-    //                 (proceed.start_block_height, proceed.end_block_height, proceed.ledger_is_on_fork)
-    //             }
-    //         };
-    //
-    //         // Revert the ledger, if it is on a fork.
-    //         if ledger_is_on_fork {
-    //             // If the revert operation fails, abort.
-    //             if !self.revert_to_block_height(maximum_common_ancestor).await {
-    //                 warn!("Ledger failed to revert to block {}", maximum_common_ancestor);
-    //                 return;
-    //             }
-    //         }
-    //
-    //         // Send a `BlockRequest` message to the peer.
-    //         debug!("Requesting blocks {} to {} from {}", start_block_height, end_block_height, peer_ip);
-    //         let request = PeersRequest::MessageSend(peer_ip, Message::BlockRequest(start_block_height, end_block_height));
-    //         if let Err(error) = self.state.peers().router().send(request).await {
-    //             warn!("[BlockRequest] {}", error);
-    //             return;
-    //         }
-    //
-    //         // Filter out any pre-existing block requests for the peer.
-    //         let mut missing_block_requests = false;
-    //         let mut new_block_heights = Vec::new();
-    //         if let Some(block_requests) = self.block_requests.read().await.get(&peer_ip) {
-    //             for block_height in start_block_height..=end_block_height {
-    //                 if !block_requests.contains_key(&block_height.into()) {
-    //                     new_block_heights.push(block_height);
-    //                 }
-    //             }
-    //         } else {
-    //             self.add_failure(peer_ip, format!("Missing block requests for {}", peer_ip)).await;
-    //             missing_block_requests = true;
-    //         }
-    //
-    //         if !missing_block_requests && !new_block_heights.is_empty() {
-    //             // Log each block request to ensure the peer responds with all requested blocks.
-    //             if let Some(locked_block_requests) = self.block_requests.write().await.get_mut(&peer_ip) {
-    //                 for block_height in new_block_heights {
-    //                     // If the ledger is on a fork and was reverted, include the expected new block hash for the fork.
-    //                     match ledger_is_on_fork {
-    //                         true => {
-    //                             self.add_block_request(
-    //                                 peer_ip,
-    //                                 block_height,
-    //                                 maximum_block_locators.get_block_hash(block_height),
-    //                                 locked_block_requests,
-    //                             )
-    //                             .await
-    //                         }
-    //                         false => self.add_block_request(peer_ip, block_height, None, locked_block_requests).await,
-    //                     };
-    //                 }
-    //             }
-    //         }
-    //     }
-    // }
+    ///
+    /// Advances the header chain to the canon chain, requests the next headers from the highest peer,
+    /// and requests the blocks along the header chain from the peers that are able to serve them.
+    ///
+    async fn update_header_sync(&self) {
+        // Switch to the fork of a peer that is ahead of this ledger, if it is within the reorg limit.
+        self.switch_to_fork().await;
+
+        // Advance the header chain past the blocks that were added to the canon chain, and remove their stored headers.
+        if let Some((start_block_height, end_block_height)) = self.header_chain.write().await.advance(&self.canon.latest_block()) {
+            if let Err(error) = self.canon.remove_header_links(start_block_height, end_block_height) {
+                warn!("Failed to remove the stored headers: {}", error);
+            }
+        }
+
+        // Determine the peers to sync from, which are the peers that are not on a fork.
+        let sync_peers = self
+            .peers_state
+            .read()
+            .await
+            .iter()
+            .filter_map(|(peer_ip, state)| match state {
+                Some((_, _, Some(false), block_height, _)) => Some((*peer_ip, *block_height)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Send a `HeadersRequest` to extend the header chain, if a peer is ahead of it.
+        let headers_request = self.header_chain.write().await.next_request(&sync_peers);
+        if let Some((peer_ip, start_block_height, end_block_height)) = headers_request {
+            debug!("Requesting headers {} to {} from {}", start_block_height, end_block_height, peer_ip);
+            let request = PeersRequest::MessageSend(peer_ip, Message::HeadersRequest(start_block_height, end_block_height));
+            if let Err(error) = self.state.peers().router().send(request).await {
+                warn!("[HeadersRequest] {}", error);
+            }
+        }
+
+        // Ensure the ledger is not awaiting responses for every block that fits in the pending queue.
+        if self.number_of_block_requests().await + self.unconfirmed_blocks.read().await.len() >= MAXIMUM_UNCONFIRMED_BLOCKS as usize {
+            return;
+        }
+
+        // Determine the blocks along the header chain, bounded by the capacity of the pending queue.
+        // The blocks in storage are never requested again, such as after a restart.
+        let missing_blocks = self.header_chain.read().await.missing_blocks();
+        let (start_block_height, end_block_height) =
+            match blocks_to_request(missing_blocks, self.canon.latest_block_height(), MAXIMUM_UNCONFIRMED_BLOCKS) {
+                Some(block_range) => block_range,
+                None => return,
+            };
+
+        // Acquire the lock for block requests.
+        let _block_requests_lock = self.block_requests_lock.lock().await;
+
+        // Determine the blocks that are already requested or pending, so that only missing blocks are requested.
+        let mut skipped_block_heights = self
+            .block_requests
+            .read()
+            .await
+            .values()
+            .flat_map(|requests| requests.keys().map(|request| request.block_height()))
+            .collect::<HashSet<_>>();
+        skipped_block_heights.extend(self.unconfirmed_blocks.read().await.values().map(|block| block.header().height()));
+
+        // Weigh the peers by their download performance and round-trip time, so that slow, flaky, or distant peers
+        // are assigned fewer and smaller chunks.
+        let weighted_sync_peers = {
+            let sync_peer_ips = sync_peers.iter().map(|(peer_ip, _)| *peer_ip).collect::<Vec<_>>();
+            let latency_weights = self.state.peers().latency_weights(&sync_peer_ips).await;
+            let peer_stats = self.peer_stats.read().await;
+            sync_peers
+                .iter()
+                .map(|(peer_ip, block_height)| {
+                    let latency_weight = latency_weights.get(peer_ip).copied().unwrap_or(1.0);
+                    (*peer_ip, *block_height, peer_stats.weight(peer_ip) * latency_weight)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Split the missing blocks into chunks, and send a `BlockRequest` to each assigned peer.
+        // Each chunk is at most the size of a batched block response, so that a chunk is served in a single message.
+        for (chunk_peer_ip, chunk_start, chunk_end) in assign_block_requests(
+            start_block_height,
+            end_block_height,
+            MAXIMUM_BLOCKS_PER_BATCH,
+            &weighted_sync_peers,
+            &skipped_block_heights,
+        ) {
+            debug!("Requesting blocks {} to {} from {}", chunk_start, chunk_end, chunk_peer_ip);
+            let request = PeersRequest::MessageSend(chunk_peer_ip, Message::BlockRequest(chunk_start, chunk_end));
+            if let Err(error) = self.state.peers().router().send(request).await {
+                warn!("[BlockRequest] {}", error);
+                continue;
+            }
+
+            // Log each block request with the block hash of the header chain, so that only blocks along it are accepted.
+            let header_chain = self.header_chain.read().await;
+            match self.block_requests.write().await.get_mut(&chunk_peer_ip) {
+                Some(locked_block_requests) => {
+                    for block_height in chunk_start..=chunk_end {
+                        let block_hash = header_chain.get_block_hash(block_height);
+                        self.add_block_request(chunk_peer_ip, block_height, block_hash, locked_block_requests).await;
+                    }
+                }
+                None => self.add_failure(chunk_peer_ip, format!("Missing block requests for {}", chunk_peer_ip)).await,
+            }
+        }
+    }
 
     ///
     /// Returns the number of outstanding block requests.
@@ -833,9 +1567,9 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         peer_ip: SocketAddr,
         block_height: u32,
         block_hash: Option<N::BlockHash>,
-        locked_block_requests: &mut HashMap<BlockRequest<N>, i64>,
+        locked_block_requests: &mut HashMap<BlockRequest<N>, Instant>,
     ) {
-        match locked_block_requests.insert((block_height, block_hash).into(), OffsetDateTime::now_utc().unix_timestamp()) {
+        match locked_block_requests.insert((block_height, block_hash).into(), Instant::now()) {
             None => debug!("Requesting block {} from {}", block_height, peer_ip),
             Some(_old_request) => self.add_failure(peer_ip, format!("Duplicate block request for {}", peer_ip)).await,
         }
@@ -853,37 +1587,58 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
     ///
     /// Removes a block request for the given block height to the specified peer.
-    /// On success, returns `true`, otherwise returns `false`.
+    /// On success, returns the time of the request, otherwise returns `None`.
     ///
-    async fn remove_block_request(&self, peer_ip: SocketAddr, block_height: u32) -> bool {
+    async fn remove_block_request(&self, peer_ip: SocketAddr, block_height: u32) -> Option<Instant> {
         // Ensure the block height corresponds to a requested block.
         if !self.contains_block_request(peer_ip, block_height).await {
             self.add_failure(peer_ip, "Received an invalid block response".to_string()).await;
-            false
+            None
         } else {
             if let Some(requests) = self.block_requests.write().await.get_mut(&peer_ip) {
-                let is_success = requests.remove(&block_height.into()).is_some();
-                match is_success {
-                    true => return true,
-                    false => {
+                match requests.remove(&block_height.into()) {
+                    Some(time_of_request) => return Some(time_of_request),
+                    None => {
                         self.add_failure(peer_ip, format!("Non-existent block request from {}", peer_ip))
                             .await
                     }
                 }
             }
-            false
+            None
+        }
+    }
+
+    ///
+    /// Returns the block hash requested from the specified peer for the given block height, if one was specified.
+    ///
+    async fn requested_block_hash(&self, peer_ip: SocketAddr, block_height: u32) -> Option<N::BlockHash> {
+        match self.block_requests.read().await.get(&peer_ip) {
+            Some(requests) => requests.get_key_value(&block_height.into()).and_then(|(request, _)| request.block_hash()),
+            None => None,
         }
     }
 
     ///
-    /// Removes block requests that have expired.
+    /// Removes block requests that have expired, penalizing the peers that did not respond in time.
     ///
     async fn remove_expired_block_requests(&self) {
-        // Clear all block requests that have lived longer than `E::RADIO_SILENCE_IN_SECS`.
-        let now = OffsetDateTime::now_utc().unix_timestamp();
-        self.block_requests.write().await.iter_mut().for_each(|(_peer, block_requests)| {
-            block_requests.retain(|_, time_of_request| now.saturating_sub(*time_of_request) < E::RADIO_SILENCE_IN_SECS as i64)
+        // Clear all block requests that have lived longer than `E::BLOCK_REQUEST_TIMEOUT_IN_SECS`,
+        // so that the blocks are requested from other peers.
+        let timeout = Duration::from_secs(E::BLOCK_REQUEST_TIMEOUT_IN_SECS);
+        let mut expired_block_requests = Vec::new();
+        self.block_requests.write().await.iter_mut().for_each(|(peer_ip, block_requests)| {
+            let number_of_block_requests = block_requests.len();
+            block_requests.retain(|_, time_of_request| time_of_request.elapsed() < timeout);
+            if block_requests.len() < number_of_block_requests {
+                expired_block_requests.push((*peer_ip, number_of_block_requests - block_requests.len()));
+            }
         });
+
+        for (peer_ip, number_of_expired_requests) in expired_block_requests {
+            self.peer_stats.write().await.record_failure(peer_ip);
+            self.add_failure(peer_ip, format!("{} block requests timed out", number_of_expired_requests))
+                .await;
+        }
     }
 
     ///
@@ -895,6 +1650,57 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             Some(failures) => failures.push((failure, OffsetDateTime::now_utc().unix_timestamp())),
             None => error!("Missing failure entry for {}", peer_ip),
         };
+        self.penalize(peer_ip, Misbehavior::Failure).await;
+    }
+
+    ///
+    /// Routes a `Penalize` to the peers, lowering the score of the given peer for the given misbehavior.
+    ///
+    async fn penalize(&self, peer_ip: SocketAddr, misbehavior: Misbehavior) {
+        if let Err(error) = self.state.peers().router().send(PeersRequest::Penalize(peer_ip, misbehavior)).await {
+            warn!("[Penalize] {}", error);
+        }
+    }
+
+    ///
+    /// Disconnects and temporarily bans the given peer for serving an invalid block, adding a strike to it.
+    /// The pending blocks served by the peer are dropped, so that its sync chunks are requested from other peers.
+    ///
+    /// This is reserved for blocks that fail validation, or do not match the requested block hash,
+    /// and not for blocks that are already known, or that do not connect to the canon chain yet.
+    /// A trusted peer is never struck or restricted, though its pending blocks are still dropped.
+    ///
+    async fn ban_invalid_block_peer(&self, peer_ip: SocketAddr, failure: String) {
+        self.peer_stats.write().await.record_failure(peer_ip);
+        let is_trusted = self.state.peers().is_trusted(&peer_ip).await;
+        match is_trusted {
+            true => warn!("{} (from trusted peer {})", failure, peer_ip),
+            false => {
+                let strikes = self.state.peers().add_invalid_block_strike(peer_ip).await;
+                warn!("{} ({:.2} invalid block strikes from {})", failure, strikes, peer_ip);
+                self.penalize(peer_ip, Misbehavior::InvalidBlock).await;
+            }
+        }
+
+        // Drop the pending blocks that were served by the peer.
+        {
+            let block_responders = self.block_responders.read().await;
+            let mut unconfirmed_blocks = self.unconfirmed_blocks.write().await;
+            let previous_block_hashes = unconfirmed_blocks
+                .values()
+                .filter(|block| block_responders.get(&block.hash()) == Some(&peer_ip))
+                .map(|block| block.previous_hash())
+                .collect::<Vec<_>>();
+            for previous_block_hash in previous_block_hashes {
+                unconfirmed_blocks.remove(&previous_block_hash);
+            }
+        }
+
+        // Disconnecting the peer removes its block requests, so that the blocks are requested from the remaining peers.
+        if !is_trusted {
+            self.disconnect_and_restrict(peer_ip, DisconnectReason::TooManyFailures).await;
+        }
+        self.update_header_sync().await;
     }
 
     ///
@@ -926,3 +1732,195 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         }
     }
 }
+
+/// Returns the maximum block height among the connected peers whose fork status is known.
+fn maximum_block_height_of_peers<N: Network>(peers_state: &PeersState<N>) -> Option<u32> {
+    peers_state
+        .values()
+        .filter_map(|peer_state| match peer_state {
+            Some((_, _, Some(_), block_height, _)) => Some(*block_height),
+            _ => None,
+        })
+        .max()
+}
+
+/// Returns `true` if the ledger is more than `MAXIMUM_LINEAR_BLOCK_LOCATORS` blocks behind the connected peers.
+fn is_out_of_date(latest_block_height: u32, maximum_block_height_of_peers: Option<u32>) -> bool {
+    match maximum_block_height_of_peers {
+        Some(block_height) => block_height.saturating_sub(latest_block_height) > MAXIMUM_LINEAR_BLOCK_LOCATORS,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::{peer, sample_canon_blocks, CurrentNetwork};
+    use snarkos_consensus::account::Account;
+    use snarkos_environment::TestEnvironment;
+    use snarkvm::Header;
+
+    use rand::{thread_rng, Rng};
+    use std::collections::BTreeMap;
+
+    type CurrentEnvironment = TestEnvironment<CurrentNetwork>;
+
+    /// Initializes the state of a node with a ledger in a temporary directory.
+    async fn sample_state() -> State<CurrentNetwork, CurrentEnvironment> {
+        sample_state_with_config(LedgerConfig::default()).await
+    }
+
+    /// Initializes the state of a node with a ledger of the given configuration in a temporary directory.
+    async fn sample_state_with_config(config: LedgerConfig) -> State<CurrentNetwork, CurrentEnvironment> {
+        let state = State::new("127.0.0.1:0".parse().unwrap(), Account::sample().unwrap()).await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("snarkos-test-ledger-{}", thread_rng().gen::<u64>()));
+        let (ledger, ledger_handler) = Ledger::open(&path, config, state.clone()).await.unwrap();
+        state.initialize_ledger(ledger, ledger_handler).await;
+        state
+    }
+
+    /// Initializes the state of a node with a ledger of `num_blocks` blocks, which reverts at most `maximum_reorg_depth` blocks.
+    async fn sample_state_with_blocks(num_blocks: u32, maximum_reorg_depth: u32) -> State<CurrentNetwork, CurrentEnvironment> {
+        let state = sample_state_with_config(LedgerConfig {
+            reorg_limit: ReorgLimit::new(maximum_reorg_depth, false),
+            ..Default::default()
+        })
+        .await;
+        for block in sample_canon_blocks(num_blocks) {
+            state.ledger().canon.add_next_block(&block).unwrap();
+        }
+        state
+    }
+
+    /// Sets the state of the given peer, as a peer at the given block height on a fork with the given block locators.
+    async fn set_fork_peer(
+        ledger: &Ledger<CurrentNetwork, CurrentEnvironment>,
+        peer_ip: SocketAddr,
+        block_height: u32,
+        block_locators: BTreeMap<u32, (<CurrentNetwork as Network>::BlockHash, Option<Header<CurrentNetwork>>)>,
+    ) {
+        let block_locators = BlockLocators::from(block_locators).unwrap();
+        ledger.initialize_peer(peer_ip).await;
+        ledger
+            .peers_state
+            .write()
+            .await
+            .insert(peer_ip, Some((NodeType::Client, Status::Ready, Some(true), block_height, block_locators)));
+    }
+
+    /// Returns a `Pong` from the given peer, as sent by a peer that is ready.
+    fn sample_pong(peer_ip: SocketAddr) -> LedgerRequest<CurrentNetwork> {
+        LedgerRequest::Pong(peer_ip, NodeType::Client, Status::Ready, Some(false), BlockLocators::default(), None)
+    }
+
+    #[tokio::test]
+    async fn test_invalid_block_bans_peer() {
+        let state = sample_state().await;
+        let ledger = state.ledger();
+
+        // The peer is struck and removed from the ledger for serving an invalid block.
+        ledger.initialize_peer(peer(4130)).await;
+        ledger.ban_invalid_block_peer(peer(4130), "Invalid block".to_string()).await;
+        assert!(!ledger.peers_state.read().await.contains_key(&peer(4130)));
+        assert!(!state.peers().is_struck_out(peer(4130)).await);
+
+        // A second invalid block keeps the peer banned.
+        ledger.ban_invalid_block_peer(peer(4130), "Invalid block".to_string()).await;
+        assert!(state.peers().is_struck_out(peer(4130)).await);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_block_does_not_ban_trusted_peer() {
+        let state = sample_state().await;
+        state.peers().add_trusted_peers(vec![peer(4130)]).await;
+        let ledger = state.ledger();
+
+        // The trusted peer is neither struck nor removed from the ledger, however many invalid blocks it serves.
+        ledger.initialize_peer(peer(4130)).await;
+        for _ in 0..3 {
+            ledger.ban_invalid_block_peer(peer(4130), "Invalid block".to_string()).await;
+        }
+        assert!(ledger.peers_state.read().await.contains_key(&peer(4130)));
+        assert!(!state.peers().is_struck_out(peer(4130)).await);
+    }
+
+    #[tokio::test]
+    async fn test_pong_from_struck_out_peer() {
+        let state = sample_state().await;
+        let ledger = state.ledger();
+
+        // Strike out both peers, as if they served invalid blocks before one of them was trusted.
+        for peer_ip in [peer(4130), peer(4131)] {
+            state.peers().add_invalid_block_strike(peer_ip).await;
+            state.peers().add_invalid_block_strike(peer_ip).await;
+        }
+        state.peers().add_trusted_peers(vec![peer(4131)]).await;
+
+        // The pong of the untrusted peer is refused, while the trusted peer is initialized in the ledger.
+        ledger.update(sample_pong(peer(4130))).await;
+        assert!(!ledger.peers_state.read().await.contains_key(&peer(4130)));
+        ledger.update(sample_pong(peer(4131))).await;
+        assert!(ledger.peers_state.read().await.contains_key(&peer(4131)));
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_fork_at_the_reorg_limit() {
+        let state = sample_state_with_blocks(3, 2).await;
+        let ledger = state.ledger();
+
+        // A fork that reverts the maximum number of blocks is switched to, and the peer is synced from.
+        let common_ancestor = ledger.canon.get_block(1).unwrap();
+        set_fork_peer(ledger, peer(4130), 5, BTreeMap::from([(1, (common_ancestor.hash(), None))])).await;
+        ledger.update_header_sync().await;
+        assert_eq!(ledger.canon.latest_block_height(), 1);
+        assert!(matches!(ledger.peers_state.read().await.get(&peer(4130)), Some(Some((_, _, Some(false), 5, _)))));
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_fork_beyond_the_reorg_limit() {
+        let state = sample_state_with_blocks(3, 2).await;
+        let ledger = state.ledger();
+
+        // A fork that reverts one block more than the maximum is refused, and the peer serving it is disconnected.
+        let common_ancestor = ledger.canon.get_block(0).unwrap();
+        set_fork_peer(ledger, peer(4130), 5, BTreeMap::from([(0, (common_ancestor.hash(), None))])).await;
+        ledger.update_header_sync().await;
+        assert_eq!(ledger.canon.latest_block_height(), 3);
+        assert!(!ledger.peers_state.read().await.contains_key(&peer(4130)));
+    }
+
+    #[tokio::test]
+    async fn test_memory_pool_follows_the_canon_chain() {
+        let state = sample_state_with_blocks(3, 2).await;
+        let ledger = state.ledger();
+
+        // The transactions of a block are removed from the memory pool once the block is added.
+        let block = sample_canon_blocks(4).pop().unwrap();
+        for (_, transaction) in block.transactions().iter() {
+            state.memory_pool().add_transaction(transaction).unwrap();
+        }
+        assert!(ledger.add_block(block.clone()).await);
+        assert!(state.memory_pool().is_empty());
+
+        // The transactions of the reverted blocks that remain valid are returned to the memory pool.
+        let removed_blocks = ledger.canon.get_blocks(3, 4).unwrap();
+        assert!(ledger.revert_to_block_height(2).await);
+        assert_eq!(state.memory_pool().transactions(), ledger.canon.get_disconnected_transactions(&removed_blocks));
+    }
+
+    #[tokio::test]
+    async fn test_blocks_unavailable_clears_block_requests() {
+        let state = sample_state().await;
+        let ledger = state.ledger();
+
+        // The block requests of a peer that answers with no blocks are forgotten, rather than left to time out.
+        ledger.initialize_peer(peer(4130)).await;
+        if let Some(requests) = ledger.block_requests.write().await.get_mut(&peer(4130)) {
+            requests.insert((1, None).into(), Instant::now());
+        }
+        assert_eq!(ledger.number_of_block_requests().await, 1);
+        ledger.update(LedgerRequest::BlocksUnavailable(peer(4130))).await;
+        assert_eq!(ledger.number_of_block_requests().await, 0);
+    }
+}
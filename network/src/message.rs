@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::helpers::{BlockBatch, HeaderLink, MAXIMUM_BLOCK_BATCH_SIZE};
 use snarkos_environment::{
     helpers::{NodeType, Status},
     Environment,
@@ -26,10 +27,20 @@ use snarkvm::{
     Transaction,
 };
 
+#[cfg(any(feature = "test", feature = "prometheus"))]
+use snarkos_metrics as metrics;
+#[cfg(any(feature = "test", feature = "prometheus"))]
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use ::bytes::{Buf, BufMut, Bytes, BytesMut};
 use anyhow::{bail, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{io::Write, marker::PhantomData, net::SocketAddr, time::Instant};
+use std::{
+    io::{self, Read, Write},
+    marker::PhantomData,
+    net::SocketAddr,
+    time::Instant,
+};
 use tokio::task;
 use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
 
@@ -118,8 +129,8 @@ pub enum Message<N: Network> {
     BlockRequest(u32, u32),
     /// BlockResponse := (block)
     BlockResponse(Data<Block<N>>),
-    /// ChallengeRequest := (version, fork_depth, node_type, status, listener_port)
-    ChallengeRequest(u32, u32, NodeType, Status, u16),
+    /// ChallengeRequest := (version, fork_depth, node_type, status, listener_port, features)
+    ChallengeRequest(u32, u32, NodeType, Status, u16, u32),
     /// ChallengeResponse := (block_header)
     ChallengeResponse(Data<Header<N>>),
     /// Disconnect := ()
@@ -128,17 +139,77 @@ pub enum Message<N: Network> {
     PeerRequest,
     /// PeerResponse := (\[peer_ip\])
     PeerResponse(Vec<SocketAddr>, Option<Instant>),
-    /// Ping := (version, fork_depth, node_type, status)
-    Ping(u32, u32, NodeType, Status),
-    /// Pong := (is_fork)
-    Pong(Option<bool>),
+    /// Ping := (version, fork_depth, node_type, status, nonce)
+    Ping(u32, u32, NodeType, Status, u64),
+    /// Pong := (is_fork, nonce)
+    Pong(Option<bool>, u64),
     /// UnconfirmedBlock := (block_height, block_hash, block)
     UnconfirmedBlock(u32, N::BlockHash, Data<Block<N>>),
     /// UnconfirmedTransaction := (transaction)
     UnconfirmedTransaction(Data<Transaction<N>>),
+    /// BlockBatchResponse := (blocks)
+    BlockBatchResponse(BlockBatch<N>),
+    /// HeadersRequest := (start_block_height, end_block_height (inclusive))
+    HeadersRequest(u32, u32),
+    /// HeadersResponse := (\[(previous_block_hash, block_hash, block_header)\])
+    HeadersResponse(Data<Vec<HeaderLink<N>>>),
 }
 
 impl<N: Network> Message<N> {
+    /// The maximum size of a serialized `BlockRequest`, including its message ID.
+    pub const MAXIMUM_BLOCK_REQUEST_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `BlockResponse`, which is a block that fits in a block batch.
+    pub const MAXIMUM_BLOCK_RESPONSE_SIZE: usize = MAXIMUM_BLOCK_BATCH_SIZE + 1024;
+    /// The maximum size of a serialized `ChallengeRequest`, including its message ID.
+    pub const MAXIMUM_CHALLENGE_REQUEST_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `ChallengeResponse`, which is a block header.
+    pub const MAXIMUM_CHALLENGE_RESPONSE_SIZE: usize = 64 * 1024; // 64 KiB
+    /// The maximum size of a serialized `Disconnect`, including its message ID.
+    pub const MAXIMUM_DISCONNECT_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `PeerRequest`, including its message ID.
+    pub const MAXIMUM_PEER_REQUEST_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `PeerResponse`, which is enough for thousands of peer IPs.
+    pub const MAXIMUM_PEER_RESPONSE_SIZE: usize = 64 * 1024; // 64 KiB
+    /// The maximum size of a serialized `Ping`, including its message ID.
+    pub const MAXIMUM_PING_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `Pong`, including its message ID.
+    pub const MAXIMUM_PONG_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `UnconfirmedBlock`, which is a block that fits in a block batch.
+    pub const MAXIMUM_UNCONFIRMED_BLOCK_SIZE: usize = MAXIMUM_BLOCK_BATCH_SIZE + 1024;
+    /// The maximum size of a serialized `UnconfirmedTransaction`, including its message ID.
+    pub const MAXIMUM_UNCONFIRMED_TRANSACTION_SIZE: usize = 4 * 1024 * 1024; // 4 MiB
+    /// The maximum size of a serialized `BlockBatchResponse`, with headroom for incompressible blocks.
+    pub const MAXIMUM_BLOCK_BATCH_RESPONSE_SIZE: usize = MAXIMUM_BLOCK_BATCH_SIZE + 1024 * 1024;
+    /// The maximum size of a serialized `HeadersRequest`, including its message ID.
+    pub const MAXIMUM_HEADERS_REQUEST_SIZE: usize = 1024; // 1 KiB
+    /// The maximum size of a serialized `HeadersResponse`, which is enough for `MAXIMUM_HEADERS_PER_RESPONSE` headers.
+    pub const MAXIMUM_HEADERS_RESPONSE_SIZE: usize = 8 * 1024 * 1024; // 8 MiB
+
+    ///
+    /// Returns the maximum size of a serialized message with the given message ID, including the message ID,
+    /// or `None` if the message ID is invalid.
+    ///
+    #[inline]
+    pub fn maximum_size(id: u16) -> Option<usize> {
+        match id {
+            0 => Some(Self::MAXIMUM_BLOCK_REQUEST_SIZE),
+            1 => Some(Self::MAXIMUM_BLOCK_RESPONSE_SIZE),
+            2 => Some(Self::MAXIMUM_CHALLENGE_REQUEST_SIZE),
+            3 => Some(Self::MAXIMUM_CHALLENGE_RESPONSE_SIZE),
+            4 => Some(Self::MAXIMUM_DISCONNECT_SIZE),
+            5 => Some(Self::MAXIMUM_PEER_REQUEST_SIZE),
+            6 => Some(Self::MAXIMUM_PEER_RESPONSE_SIZE),
+            7 => Some(Self::MAXIMUM_PING_SIZE),
+            8 => Some(Self::MAXIMUM_PONG_SIZE),
+            9 => Some(Self::MAXIMUM_UNCONFIRMED_BLOCK_SIZE),
+            10 => Some(Self::MAXIMUM_UNCONFIRMED_TRANSACTION_SIZE),
+            11 => Some(Self::MAXIMUM_BLOCK_BATCH_RESPONSE_SIZE),
+            12 => Some(Self::MAXIMUM_HEADERS_REQUEST_SIZE),
+            13 => Some(Self::MAXIMUM_HEADERS_RESPONSE_SIZE),
+            _ => None,
+        }
+    }
+
     /// Returns the message name.
     #[inline]
     pub fn name(&self) -> &str {
@@ -154,6 +225,9 @@ impl<N: Network> Message<N> {
             Self::Pong(..) => "Pong",
             Self::UnconfirmedBlock(..) => "UnconfirmedBlock",
             Self::UnconfirmedTransaction(..) => "UnconfirmedTransaction",
+            Self::BlockBatchResponse(..) => "BlockBatchResponse",
+            Self::HeadersRequest(..) => "HeadersRequest",
+            Self::HeadersResponse(..) => "HeadersResponse",
         }
     }
 
@@ -172,9 +246,18 @@ impl<N: Network> Message<N> {
             Self::Pong(..) => 8,
             Self::UnconfirmedBlock(..) => 9,
             Self::UnconfirmedTransaction(..) => 10,
+            Self::BlockBatchResponse(..) => 11,
+            Self::HeadersRequest(..) => 12,
+            Self::HeadersResponse(..) => 13,
         }
     }
 
+    /// Returns `true` if the message is worth compressing, as its data is not compressed already.
+    #[inline]
+    pub fn is_compressible(&self) -> bool {
+        !matches!(self, Self::BlockBatchResponse(..))
+    }
+
     /// Returns the message data as bytes.
     #[inline]
     pub fn serialize_data_into<W: Write>(&self, writer: &mut W) -> Result<()> {
@@ -184,18 +267,21 @@ impl<N: Network> Message<N> {
                 Ok(writer.write_all(&bytes)?)
             }
             Self::BlockResponse(block) => block.serialize_blocking_into(writer),
-            Self::ChallengeRequest(version, fork_depth, node_type, status, listener_port) => Ok(bincode::serialize_into(
-                writer,
-                &(version, fork_depth, node_type, status, listener_port),
-            )?),
+            Self::ChallengeRequest(version, fork_depth, node_type, status, listener_port, features) => {
+                bincode::serialize_into(&mut *writer, &(version, fork_depth, node_type, status, listener_port))?;
+                // The features are appended, so that the peers that predate them ignore them.
+                Ok(bincode::serialize_into(writer, features)?)
+            }
             Self::ChallengeResponse(block_header) => Ok(block_header.serialize_blocking_into(writer)?),
             Self::Disconnect(reason) => Ok(bincode::serialize_into(writer, reason)?),
             Self::PeerRequest => Ok(()),
             Self::PeerResponse(peer_ips, _) => Ok(bincode::serialize_into(writer, peer_ips)?),
-            Self::Ping(version, fork_depth, node_type, status) => {
-                Ok(bincode::serialize_into(&mut *writer, &(version, fork_depth, node_type, status))?)
+            Self::Ping(version, fork_depth, node_type, status, nonce) => {
+                bincode::serialize_into(&mut *writer, &(version, fork_depth, node_type, status))?;
+                // The nonce is appended, so that the peers that predate it ignore it.
+                Ok(bincode::serialize_into(writer, nonce)?)
             }
-            Self::Pong(is_fork) => {
+            Self::Pong(is_fork, nonce) => {
                 let serialized_is_fork: u8 = match is_fork {
                     None => 0,
                     Some(fork) => match fork {
@@ -204,7 +290,8 @@ impl<N: Network> Message<N> {
                     },
                 };
 
-                Ok(writer.write_all(&[serialized_is_fork])?)
+                writer.write_all(&[serialized_is_fork])?;
+                Ok(writer.write_all(&nonce.to_le_bytes())?)
             }
             Self::UnconfirmedBlock(block_height, block_hash, block) => {
                 writer.write_all(&block_height.to_le_bytes())?;
@@ -212,6 +299,12 @@ impl<N: Network> Message<N> {
                 block.serialize_blocking_into(writer)
             }
             Self::UnconfirmedTransaction(transaction) => Ok(transaction.serialize_blocking_into(writer)?),
+            Self::BlockBatchResponse(blocks) => blocks.serialize_blocking_into(writer),
+            Self::HeadersRequest(start_block_height, end_block_height) => {
+                let bytes = to_bytes_le![start_block_height, end_block_height]?;
+                Ok(writer.write_all(&bytes)?)
+            }
+            Self::HeadersResponse(headers) => headers.serialize_blocking_into(writer),
         }
     }
 
@@ -242,8 +335,14 @@ impl<N: Network> Message<N> {
             }
             1 => Self::BlockResponse(Data::Buffer(bytes.freeze())),
             2 => {
-                let (version, fork_depth, node_type, status, listener_port) = bincode::deserialize_from(&mut bytes.reader())?;
-                Self::ChallengeRequest(version, fork_depth, node_type, status, listener_port)
+                let mut reader = bytes.reader();
+                let (version, fork_depth, node_type, status, listener_port) = bincode::deserialize_from(&mut reader)?;
+                // The peers that predate the features do not send them.
+                let features = match reader.get_ref().has_remaining() {
+                    true => bincode::deserialize_from(&mut reader)?,
+                    false => 0,
+                };
+                Self::ChallengeRequest(version, fork_depth, node_type, status, listener_port, features)
             }
             3 => Self::ChallengeResponse(Data::Buffer(bytes.freeze())),
             4 => {
@@ -263,8 +362,13 @@ impl<N: Network> Message<N> {
             7 => {
                 let mut reader = bytes.reader();
                 let (version, fork_depth, node_type, status) = bincode::deserialize_from(&mut reader)?;
+                // The peers that predate the nonce do not send it.
+                let nonce = match reader.get_ref().has_remaining() {
+                    true => bincode::deserialize_from(&mut reader)?,
+                    false => 0,
+                };
 
-                Self::Ping(version, fork_depth, node_type, status)
+                Self::Ping(version, fork_depth, node_type, status, nonce)
             }
             8 => {
                 // Make sure a byte for the fork flag is available.
@@ -280,8 +384,13 @@ impl<N: Network> Message<N> {
                     2 => Some(false),
                     _ => bail!("Invalid 'Pong' message"),
                 };
+                // The peers that predate the nonce do not send it.
+                let nonce = match bytes.remaining() >= 8 {
+                    true => bytes.get_u64_le(),
+                    false => 0,
+                };
 
-                Self::Pong(is_fork)
+                Self::Pong(is_fork, nonce)
             }
             9 => {
                 let mut reader = bytes.reader();
@@ -292,6 +401,12 @@ impl<N: Network> Message<N> {
                 )
             }
             10 => Self::UnconfirmedTransaction(Data::Buffer(bytes.freeze())),
+            11 => Self::BlockBatchResponse(BlockBatch::Compressed(bytes.freeze())),
+            12 => {
+                let mut reader = bytes.reader();
+                Self::HeadersRequest(bincode::deserialize_from(&mut reader)?, bincode::deserialize_from(&mut reader)?)
+            }
+            13 => Self::HeadersResponse(Data::Buffer(bytes.freeze())),
             _ => bail!("Invalid message ID {}", id),
         };
 
@@ -302,12 +417,42 @@ impl<N: Network> Message<N> {
 /// The maximum size of a message that can be transmitted in the network.
 const MAXIMUM_MESSAGE_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
 
+/// The feature bit of a node that supports the zstd compression of large messages.
+pub const FEATURE_COMPRESSION: u32 = 1 << 0;
+/// The feature bits of this node, which are advertised to peers in the challenge request.
+pub const SUPPORTED_FEATURES: u32 = FEATURE_COMPRESSION;
+
+/// The minimum size of the data of a message to be compressed, when the peer supports compression.
+pub const COMPRESSION_THRESHOLD: usize = 4 * 1024; // 4 KiB
+/// The zstd compression level of a message.
+const COMPRESSION_LEVEL: i32 = 3;
+/// The bit of the message ID that flags a message whose data is zstd-compressed.
+const COMPRESSED_FLAG: u16 = 1 << 15;
+
+/// The total sizes of the compressed message data, before and after compression, for the compression ratio.
+#[cfg(any(feature = "test", feature = "prometheus"))]
+static COMPRESSION_TOTALS: [AtomicU64; 2] = [AtomicU64::new(0), AtomicU64::new(0)];
+
 /// The codec used to decode and encode network `Message`s.
 pub struct MessageCodec<N: Network> {
     codec: LengthDelimitedCodec,
+    /// If `true`, the peer supports compression, and large messages are compressed.
+    compression: bool,
     _phantom: PhantomData<N>,
 }
 
+impl<N: Network> MessageCodec<N> {
+    /// Enables the compression of large messages, once the peer has advertised that it supports compression.
+    pub fn enable_compression(&mut self) {
+        self.compression = true;
+    }
+
+    /// Returns `true` if the compression of large messages is enabled.
+    pub fn is_compression_enabled(&self) -> bool {
+        self.compression
+    }
+}
+
 impl<N: Network> Default for MessageCodec<N> {
     fn default() -> Self {
         Self {
@@ -315,6 +460,7 @@ impl<N: Network> Default for MessageCodec<N> {
                 .max_frame_length(MAXIMUM_MESSAGE_SIZE)
                 .little_endian()
                 .new_codec(),
+            compression: false,
             _phantom: Default::default(),
         }
     }
@@ -330,9 +476,16 @@ impl<N: Network> Encoder<Message<N>> for MessageCodec<N> {
             // This error should never happen, the conversion is for greater compatibility.
             .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "serialization error"))?;
 
-        let serialized_message = dst.split_to(dst.len()).freeze();
+        let mut serialized_message = dst.split_to(dst.len());
 
-        self.codec.encode(serialized_message, dst)
+        // Compress the data of a large message, if the peer supports compression, and the data shrinks.
+        if self.compression && message.is_compressible() && serialized_message.len() > 2 + COMPRESSION_THRESHOLD {
+            if let Some(compressed_message) = compress_message(&serialized_message) {
+                serialized_message = compressed_message;
+            }
+        }
+
+        self.codec.encode(serialized_message.freeze(), dst)
     }
 }
 
@@ -341,12 +494,31 @@ impl<N: Network> Decoder for MessageCodec<N> {
     type Item = Message<N>;
 
     fn decode(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Check the length of the next frame against the maximum size of its message, before the frame is buffered.
+        let maximum_size = match check_frame_length::<N>(source)? {
+            Some(maximum_size) => maximum_size,
+            None => return Ok(None),
+        };
+
         // Decode a frame containing bytes belonging to a message.
         let bytes = match self.codec.decode(source)? {
             Some(bytes) => bytes,
             None => return Ok(None),
         };
 
+        // Decompress the data of a compressed message, which is only sent once compression is enabled.
+        let bytes = match bytes.len() >= 2 && u16::from_le_bytes([bytes[0], bytes[1]]) & COMPRESSED_FLAG != 0 {
+            true if self.compression => decompress_message(&bytes, maximum_size - 2).map_err(|error| {
+                error!("Failed to decompress a message: {}", error);
+                error
+            })?,
+            true => {
+                error!("Received a compressed message from a peer that did not enable compression");
+                return Err(std::io::ErrorKind::InvalidData.into());
+            }
+            false => bytes,
+        };
+
         // Convert the bytes to a message, or fail if it is not valid.
         match Message::deserialize(bytes) {
             Ok(message) => Ok(Some(message)),
@@ -357,3 +529,277 @@ impl<N: Network> Decoder for MessageCodec<N> {
         }
     }
 }
+
+///
+/// Returns the maximum size of the message in the next frame of the given source, once the whole frame is buffered,
+/// or `None` if the frame is incomplete. The source is reserved for the remainder of the frame, only if its length
+/// is within the maximum size of the message with its declared message ID.
+///
+fn check_frame_length<N: Network>(source: &mut BytesMut) -> io::Result<Option<usize>> {
+    // Wait for the frame length and the message ID.
+    if source.len() < 4 {
+        return Ok(None);
+    }
+    let length = u32::from_le_bytes([source[0], source[1], source[2], source[3]]) as usize;
+    if length < 2 {
+        error!("Received a frame without a message ID");
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    if source.len() < 6 {
+        return Ok(None);
+    }
+    let id = u16::from_le_bytes([source[4], source[5]]) & !COMPRESSED_FLAG;
+
+    match Message::<N>::maximum_size(id) {
+        Some(maximum_size) if length <= maximum_size => {
+            // Wait for the whole frame, so that the frame is decoded in a single call.
+            match source.len() < 4 + length {
+                true => {
+                    source.reserve(4 + length - source.len());
+                    Ok(None)
+                }
+                false => Ok(Some(maximum_size)),
+            }
+        }
+        Some(maximum_size) => {
+            error!("Received a frame of {} bytes for message ID {}, which exceeds its maximum of {} bytes", length, id, maximum_size);
+            Err(io::ErrorKind::InvalidData.into())
+        }
+        None => {
+            error!("Received a frame with an invalid message ID {}", id);
+            Err(io::ErrorKind::InvalidData.into())
+        }
+    }
+}
+
+///
+/// Returns the given serialized message, with its data zstd-compressed and the compressed flag set in its ID,
+/// or `None` if the compressed data is no smaller than the data.
+///
+fn compress_message(serialized_message: &[u8]) -> Option<BytesMut> {
+    let (id, data) = serialized_message.split_at(2);
+    let compressed_data = zstd::bulk::compress(data, COMPRESSION_LEVEL).ok()?;
+    if compressed_data.len() >= data.len() {
+        return None;
+    }
+
+    #[cfg(any(feature = "test", feature = "prometheus"))]
+    {
+        let (data_size, compressed_size) = (data.len() as u64, compressed_data.len() as u64);
+        let total_data_size = COMPRESSION_TOTALS[0].fetch_add(data_size, Ordering::Relaxed) + data_size;
+        let total_compressed_size = COMPRESSION_TOTALS[1].fetch_add(compressed_size, Ordering::Relaxed) + compressed_size;
+        metrics::counter!(metrics::peers::COMPRESSION_UNCOMPRESSED_BYTES, data_size);
+        metrics::counter!(metrics::peers::COMPRESSION_COMPRESSED_BYTES, compressed_size);
+        metrics::counter!(metrics::peers::COMPRESSION_BYTES_SAVED, data_size - compressed_size);
+        metrics::gauge!(metrics::peers::COMPRESSION_RATIO, total_data_size as f64 / total_compressed_size as f64);
+    }
+
+    let mut compressed_message = BytesMut::with_capacity(2 + compressed_data.len());
+    compressed_message.put_u16_le(u16::from_le_bytes([id[0], id[1]]) | COMPRESSED_FLAG);
+    compressed_message.put_slice(&compressed_data);
+    Some(compressed_message)
+}
+
+///
+/// Returns the given compressed message, with its data decompressed and the compressed flag cleared in its ID.
+/// The decompressed data is limited to the given maximum size, so that a small message is unable to
+/// decompress into an arbitrarily large one.
+///
+fn decompress_message(compressed_message: &[u8], maximum_size: usize) -> io::Result<BytesMut> {
+    let id = u16::from_le_bytes([compressed_message[0], compressed_message[1]]) & !COMPRESSED_FLAG;
+
+    let mut data = Vec::new();
+    zstd::stream::read::Decoder::new(&compressed_message[2..])?
+        .take(maximum_size as u64 + 1)
+        .read_to_end(&mut data)?;
+    if data.len() > maximum_size {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the decompressed message exceeds the maximum size"));
+    }
+
+    let mut message = BytesMut::with_capacity(2 + data.len());
+    message.put_u16_le(id);
+    message.put_slice(&data);
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{
+        test_helpers::{sample_block, CurrentNetwork},
+        MAXIMUM_BLOCKS_PER_BATCH,
+        MAXIMUM_HEADERS_PER_RESPONSE,
+    };
+
+    #[test]
+    fn test_maximum_sizes() {
+        let block = sample_block();
+        let transaction = block.transactions().iter().next().unwrap().clone();
+        let link = (block.previous_hash(), block.hash(), block.header().clone());
+        let peer_ips = vec!["[2001:db8::1]:4133".parse().unwrap(); 1024];
+
+        let messages = vec![
+            Message::<CurrentNetwork>::BlockRequest(u32::MAX - 1, u32::MAX),
+            Message::BlockResponse(Data::Object(block.clone())),
+            Message::ChallengeRequest(u32::MAX, u32::MAX, NodeType::Beacon, Status::Peering, u16::MAX, u32::MAX),
+            Message::ChallengeResponse(Data::Object(block.header().clone())),
+            Message::Disconnect(DisconnectReason::YourPortIsClosed(u16::MAX)),
+            Message::PeerRequest,
+            Message::PeerResponse(peer_ips, None),
+            Message::Ping(u32::MAX, u32::MAX, NodeType::Beacon, Status::Peering, u64::MAX),
+            Message::Pong(Some(true), u64::MAX),
+            Message::UnconfirmedBlock(block.header().height(), block.hash(), Data::Object(block.clone())),
+            Message::UnconfirmedTransaction(Data::Object(transaction)),
+            Message::BlockBatchResponse(BlockBatch::Blocks(vec![block; MAXIMUM_BLOCKS_PER_BATCH as usize])),
+            Message::HeadersRequest(u32::MAX - 1, u32::MAX),
+            Message::HeadersResponse(Data::Object(vec![link; MAXIMUM_HEADERS_PER_RESPONSE as usize])),
+        ];
+
+        // Each message fits comfortably under the maximum size of its message ID.
+        for message in messages {
+            let mut bytes = BytesMut::new();
+            message.serialize_into(&mut (&mut bytes).writer()).unwrap();
+            let maximum_size = Message::<CurrentNetwork>::maximum_size(message.id()).unwrap();
+            assert!(bytes.len() * 2 <= maximum_size, "{} is {} bytes, of a maximum of {}", message.name(), bytes.len(), maximum_size);
+            assert!(maximum_size <= MAXIMUM_MESSAGE_SIZE);
+        }
+        assert_eq!(Message::<CurrentNetwork>::maximum_size(14), None);
+    }
+
+    #[test]
+    fn test_oversized_frame() {
+        let mut codec = MessageCodec::<CurrentNetwork>::default();
+
+        // A frame that declares a length beyond the maximum size of its message is rejected from its header alone.
+        let mut bytes = BytesMut::new();
+        bytes.put_u32_le(Message::<CurrentNetwork>::MAXIMUM_PEER_REQUEST_SIZE as u32 + 1);
+        bytes.put_u16_le(Message::<CurrentNetwork>::PeerRequest.id());
+        assert!(codec.decode(&mut bytes).is_err());
+
+        // A frame with an invalid message ID is rejected from its header alone.
+        let mut bytes = BytesMut::new();
+        bytes.put_u32_le(2);
+        bytes.put_u16_le(14);
+        assert!(MessageCodec::<CurrentNetwork>::default().decode(&mut bytes).is_err());
+
+        // A frame within the maximum size is decoded once it has fully arrived.
+        let mut message_bytes = BytesMut::new();
+        let peer_ips = vec!["127.0.0.1:4133".parse::<SocketAddr>().unwrap(); 100];
+        codec.encode(Message::PeerResponse(peer_ips.clone(), None), &mut message_bytes).unwrap();
+        let mut bytes = message_bytes.split_to(10);
+        assert!(codec.decode(&mut bytes).unwrap().is_none());
+        bytes.unsplit(message_bytes);
+        match codec.decode(&mut bytes).unwrap() {
+            Some(Message::PeerResponse(decoded_peer_ips, None)) => assert_eq!(decoded_peer_ips, peer_ips),
+            _ => panic!("Expected a 'PeerResponse' message"),
+        }
+    }
+
+    #[test]
+    fn test_peer_response_with_ipv6_addresses() {
+        let peer_ips: Vec<SocketAddr> = vec![
+            "127.0.0.1:4133".parse().unwrap(),
+            "[2001:db8::1]:4133".parse().unwrap(),
+            "[::1]:4134".parse().unwrap(),
+        ];
+
+        // Encode and decode the peer IPs across the wire.
+        let mut codec = MessageCodec::<CurrentNetwork>::default();
+        let mut bytes = BytesMut::new();
+        codec.encode(Message::PeerResponse(peer_ips.clone(), None), &mut bytes).unwrap();
+        match codec.decode(&mut bytes).unwrap() {
+            Some(Message::PeerResponse(decoded_peer_ips, None)) => assert_eq!(decoded_peer_ips, peer_ips),
+            _ => panic!("Expected a 'PeerResponse' message"),
+        }
+    }
+
+    #[test]
+    fn test_compression() {
+        let peer_ips = vec!["127.0.0.1:4133".parse::<SocketAddr>().unwrap(); 1000];
+        let message = || Message::<CurrentNetwork>::PeerResponse(peer_ips.clone(), None);
+
+        // A large message is sent uncompressed, unless compression is enabled.
+        let mut uncompressed_bytes = BytesMut::new();
+        MessageCodec::<CurrentNetwork>::default().encode(message(), &mut uncompressed_bytes).unwrap();
+
+        let mut codec = MessageCodec::<CurrentNetwork>::default();
+        codec.enable_compression();
+        let mut compressed_bytes = BytesMut::new();
+        codec.encode(message(), &mut compressed_bytes).unwrap();
+        assert!(compressed_bytes.len() < uncompressed_bytes.len() / 10);
+
+        // A compressed message decodes to the original message.
+        match codec.decode(&mut compressed_bytes.clone()).unwrap() {
+            Some(Message::PeerResponse(decoded_peer_ips, None)) => assert_eq!(decoded_peer_ips, peer_ips),
+            _ => panic!("Expected a 'PeerResponse' message"),
+        }
+
+        // A compressed message is rejected by a peer that did not enable compression.
+        assert!(MessageCodec::<CurrentNetwork>::default().decode(&mut compressed_bytes).is_err());
+
+        // A small message is sent uncompressed, even if compression is enabled.
+        let mut bytes = BytesMut::new();
+        codec.encode(Message::PeerRequest, &mut bytes).unwrap();
+        assert_eq!(&bytes[4..], &Message::<CurrentNetwork>::PeerRequest.id().to_le_bytes()[..]);
+    }
+
+    #[test]
+    fn test_decompression_limit() {
+        let mut serialized_message = 6u16.to_le_bytes().to_vec();
+        serialized_message.extend(vec![0u8; 64 * 1024]);
+        let compressed_message = compress_message(&serialized_message).unwrap();
+
+        // A message that decompresses beyond the maximum size is rejected.
+        assert_eq!(&decompress_message(&compressed_message, 64 * 1024).unwrap()[..], &serialized_message[..]);
+        assert!(decompress_message(&compressed_message, 64 * 1024 - 1).is_err());
+    }
+
+    #[test]
+    fn test_challenge_request_without_features() {
+        // A challenge request from a peer that predates the features has no feature bits.
+        let mut bytes = BytesMut::new();
+        bytes.put_u16_le(2);
+        bincode::serialize_into((&mut bytes).writer(), &(2u32, 4096u32, NodeType::Client, Status::Ready, 4133u16)).unwrap();
+        match Message::<CurrentNetwork>::deserialize(bytes).unwrap() {
+            Message::ChallengeRequest(_, _, _, _, listener_port, features) => assert_eq!((listener_port, features), (4133, 0)),
+            _ => panic!("Expected a 'ChallengeRequest' message"),
+        }
+
+        // The feature bits of this node are sent with its challenge request.
+        let mut bytes = BytesMut::new();
+        let message = Message::<CurrentNetwork>::ChallengeRequest(2, 4096, NodeType::Client, Status::Ready, 4133, SUPPORTED_FEATURES);
+        message.serialize_into(&mut (&mut bytes).writer()).unwrap();
+        match Message::<CurrentNetwork>::deserialize(bytes).unwrap() {
+            Message::ChallengeRequest(_, _, _, _, _, features) => assert_eq!(features, SUPPORTED_FEATURES),
+            _ => panic!("Expected a 'ChallengeRequest' message"),
+        }
+    }
+
+    #[test]
+    fn test_ping_and_pong_nonces() {
+        // The nonce of a ping is echoed in its pong.
+        let messages = vec![
+            Message::<CurrentNetwork>::Ping(3, 4096, NodeType::Client, Status::Ready, 42),
+            Message::<CurrentNetwork>::Pong(None, 42),
+        ];
+        for message in messages {
+            let mut bytes = BytesMut::new();
+            message.serialize_into(&mut (&mut bytes).writer()).unwrap();
+            match Message::<CurrentNetwork>::deserialize(bytes).unwrap() {
+                Message::Ping(_, _, _, _, nonce) | Message::Pong(_, nonce) => assert_eq!(nonce, 42),
+                message => panic!("Unexpected '{}' message", message.name()),
+            }
+        }
+
+        // A ping or a pong from a peer that predates the nonces has no nonce.
+        let mut bytes = BytesMut::new();
+        bytes.put_u16_le(7);
+        bincode::serialize_into((&mut bytes).writer(), &(2u32, 4096u32, NodeType::Client, Status::Ready)).unwrap();
+        assert!(matches!(Message::<CurrentNetwork>::deserialize(bytes).unwrap(), Message::Ping(2, _, _, _, 0)));
+
+        let mut bytes = BytesMut::new();
+        bytes.put_u16_le(8);
+        bytes.put_u8(1);
+        assert!(matches!(Message::<CurrentNetwork>::deserialize(bytes).unwrap(), Message::Pong(Some(true), 0)));
+    }
+}
@@ -28,6 +28,10 @@ impl<N: Network, E: Environment> Peer<N, E> {
             // Retrieve the peer IP.
             let peer_ip = *peer.ip();
             info!("Connected to {}", peer_ip);
+            // The messages of a trusted peer bypass the per-peer rate limits.
+            let is_trusted = peer.state.peers().is_trusted(&peer_ip).await;
+            // Initialize the count of blocks served to the peer, which limits the rate of its block requests.
+            let mut served_blocks = ServedBlocks::default();
 
             // Process incoming messages until this stream is disconnected.
             loop {
@@ -89,6 +93,22 @@ impl<N: Network, E: Environment> Peer<N, E> {
 
                                 is_ready_to_send
                             }
+                            Message::BlockBatchResponse(ref mut batch) => {
+                                // Perform non-blocking compression of the blocks (if they haven't been compressed yet).
+                                let compressed_batch = batch.clone().compress().await.expect("Block batch compression is bugged");
+                                let _ = std::mem::replace(batch, compressed_batch);
+
+                                true
+                            }
+                            Message::HeadersRequest(start_block_height, end_block_height) => {
+                                // Skip the request, if the peer does not support headers.
+                                let is_ready_to_send = *peer.version.read().await >= HEADERS_MESSAGE_VERSION;
+                                if !is_ready_to_send {
+                                    trace!("Skipping 'HeadersRequest {}-{}' to {}, as it does not support headers", start_block_height, end_block_height, peer_ip);
+                                }
+
+                                is_ready_to_send
+                            }
                             Message::PeerResponse(_, _rtt_start) => {
                                 // Stop the clock on internal RTT.
                                 #[cfg(any(feature = "test", feature = "prometheus"))]
@@ -134,35 +154,84 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     #[cfg(any(feature = "test", feature = "prometheus"))]
                                     metrics::increment_counter!(metrics::message_counts::BLOCK_REQUEST);
 
-                                    // // Ensure the request is within the accepted limits.
-                                    // let number_of_blocks = end_block_height.saturating_sub(start_block_height);
-                                    // if number_of_blocks > E::MAXIMUM_BLOCK_REQUEST {
-                                    //     // Route a `Failure` to the ledger.
-                                    //     let failure = format!("Attempted to request {} blocks", number_of_blocks);
-                                    //     if let Err(error) = state.ledger().router().send(LedgerRequest::Failure(peer_ip, failure)).await {
-                                    //         warn!("[Failure] {}", error);
-                                    //     }
-                                    //     continue;
-                                    // }
-                                    // // Retrieve the requested blocks.
-                                    // let blocks = match state.ledger().reader().get_blocks(start_block_height, end_block_height) {
-                                    //     Ok(blocks) => blocks,
-                                    //     Err(error) => {
-                                    //         // Route a `Failure` to the ledger.
-                                    //         if let Err(error) = state.ledger().router().send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
-                                    //             warn!("[Failure] {}", error);
-                                    //         }
-                                    //         continue;
-                                    //     }
-                                    // };
-                                    // // Send a `BlockResponse` message for each block to the peer.
-                                    // for block in blocks {
-                                    //     debug!("Sending 'BlockResponse {}' to {}", block.height(), peer_ip);
-                                    //     if let Err(error) = peer.outbound_socket.send(Message::BlockResponse(Data::Object(block))).await {
-                                    //         warn!("[BlockResponse] {}", error);
-                                    //         break;
-                                    //     }
-                                    // }
+                                    // Ensure the request is well-formed and within the accepted limits, truncating it to the latest block.
+                                    let latest_block_height = peer.state.ledger().reader().latest_block_height();
+                                    let block_range = match check_block_request(start_block_height, end_block_height, latest_block_height, E::MAXIMUM_BLOCK_REQUEST) {
+                                        Ok(block_range) => block_range,
+                                        Err(error) => {
+                                            // Route a `Failure` to the ledger, as a protocol violation.
+                                            if let Err(error) = peer.state.ledger().router().send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
+                                                warn!("[Failure] {}", error);
+                                            }
+                                            // Penalize the peer for the malformed request.
+                                            if let Err(error) = peers_router.send(PeersRequest::Penalize(peer_ip, Misbehavior::InvalidMessage)).await {
+                                                warn!("[Penalize] {}", error);
+                                            }
+                                            continue;
+                                        }
+                                    };
+                                    let blocks = match block_range {
+                                        Some((start_block_height, end_block_height)) => {
+                                            // Decline the request if the peer has been served too many blocks recently.
+                                            // The peer times out on the request, and requests the blocks from another peer.
+                                            if !is_trusted && !served_blocks.try_serve(end_block_height - start_block_height + 1) {
+                                                debug!("Declining 'BlockRequest {}-{}' from {}, as it exceeds the rate limit", start_block_height, end_block_height, peer_ip);
+                                                // Penalize the peer for spamming block requests.
+                                                if let Err(error) = peers_router.send(PeersRequest::Penalize(peer_ip, Misbehavior::Spam)).await {
+                                                    warn!("[Penalize] {}", error);
+                                                }
+                                                continue;
+                                            }
+                                            // Decline requests for pruned blocks, as their transactions are no longer available.
+                                            if let Ok(true) = peer.state.ledger().reader().is_block_pruned(start_block_height) {
+                                                debug!("Declining 'BlockRequest {}-{}' from {}, as the blocks are pruned", start_block_height, end_block_height, peer_ip);
+                                                continue;
+                                            }
+                                            // Retrieve the requested blocks.
+                                            match peer.state.ledger().reader().get_blocks(start_block_height, end_block_height) {
+                                                Ok(blocks) => blocks,
+                                                Err(error) => {
+                                                    // Route a `Failure` to the ledger.
+                                                    if let Err(error) = peer.state.ledger().router().send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
+                                                        warn!("[Failure] {}", error);
+                                                    }
+                                                    continue;
+                                                }
+                                            }
+                                        }
+                                        // Answer a request beyond the latest block with no blocks, as the peer may be ahead of this node.
+                                        None => {
+                                            debug!("Answering 'BlockRequest {}-{}' from {} with no blocks, as it starts beyond the latest block {}", start_block_height, end_block_height, peer_ip, latest_block_height);
+                                            Vec::new()
+                                        }
+                                    };
+
+                                    // Send the blocks in batched block responses, if the peer supports them.
+                                    let messages = match block_responses(blocks, *peer.version.read().await) {
+                                        Ok(messages) => messages,
+                                        Err(error) => {
+                                            warn!("[BlockBatchResponse] {}", error);
+                                            continue;
+                                        }
+                                    };
+                                    debug!("Sending {} block responses for 'BlockRequest {}-{}' to {}", messages.len(), start_block_height, end_block_height, peer_ip);
+                                    for message in messages {
+                                        // Perform non-blocking compression of the blocks.
+                                        let message = match message {
+                                            Message::BlockBatchResponse(batch) => match batch.compress().await {
+                                                Ok(batch) => Message::BlockBatchResponse(batch),
+                                                Err(error) => {
+                                                    warn!("[BlockBatchResponse] {}", error);
+                                                    break;
+                                                }
+                                            },
+                                            message => message,
+                                        };
+                                        if let Err(error) = outbound_socket.send(message).await {
+                                            warn!("[BlockResponse] {}", error);
+                                            break;
+                                        }
+                                    }
 
                                     // Stop the clock on internal RTT.
                                     #[cfg(any(feature = "test", feature = "prometheus"))]
@@ -172,33 +241,104 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     #[cfg(any(feature = "test", feature = "prometheus"))]
                                     metrics::increment_counter!(metrics::message_counts::BLOCK_RESPONSE);
 
-                                    // // Perform the deferred non-blocking deserialization of the block.
-                                    // match block.deserialize().await {
-                                    //     Ok(block) => {
-                                    //         // TODO (howardwu): TEMPORARY - Remove this after testnet2.
-                                    //         // Sanity check for a V12 ledger.
-                                    //         if N::ID == 3
-                                    //             && block.height() > snarkvm::dpc::testnet2::V12_UPGRADE_BLOCK_HEIGHT
-                                    //             && block.header().proof().is_hiding()
-                                    //         {
-                                    //             warn!("Peer {} is not V12-compliant, proceeding to disconnect", peer_ip);
-                                    //             break;
-                                    //         }
-                                    //
-                                    //         // Route the `BlockResponse` to the ledger.
-                                    //         if let Err(error) = state.ledger().router().send(LedgerRequest::BlockResponse(peer_ip, block)).await {
-                                    //             warn!("[BlockResponse] {}", error);
-                                    //         }
-                                    //     },
-                                    //     // Route the `Failure` to the ledger.
-                                    //     Err(error) => if let Err(error) = state.ledger().router().send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
-                                    //         warn!("[Failure] {}", error);
-                                    //     }
-                                    // }
+                                    // Perform the deferred non-blocking deserialization of the block.
+                                    let request = match block.deserialize().await {
+                                        // Route the `BlockResponse` to the ledger.
+                                        Ok(block) => LedgerRequest::BlockResponse(peer_ip, block),
+                                        // Route the `Failure` to the ledger.
+                                        Err(error) => LedgerRequest::Failure(peer_ip, format!("{}", error)),
+                                    };
+                                    if let Err(error) = peer.state.ledger().router().send(request).await {
+                                        warn!("[BlockResponse] {}", error);
+                                    }
+                                }
+                                Message::BlockBatchResponse(batch) => {
+                                    #[cfg(any(feature = "test", feature = "prometheus"))]
+                                    metrics::increment_counter!(metrics::message_counts::BLOCK_BATCH_RESPONSE);
+
+                                    // Perform the deferred non-blocking decompression of the blocks, and route them to the ledger in order.
+                                    let requests = match batch.deserialize().await {
+                                        // An empty batch answers a block request that starts beyond the latest block of the peer.
+                                        Ok(blocks) if blocks.is_empty() => vec![LedgerRequest::BlocksUnavailable(peer_ip)],
+                                        Ok(blocks) => blocks.into_iter().map(|block| LedgerRequest::BlockResponse(peer_ip, block)).collect(),
+                                        Err(error) => vec![LedgerRequest::Failure(peer_ip, format!("{}", error))],
+                                    };
+                                    for request in requests {
+                                        if let Err(error) = peer.state.ledger().router().send(request).await {
+                                            warn!("[BlockBatchResponse] {}", error);
+                                            break;
+                                        }
+                                    }
+                                }
+                                Message::HeadersRequest(start_block_height, end_block_height) => {
+                                    #[cfg(any(feature = "test", feature = "prometheus"))]
+                                    metrics::increment_counter!(metrics::message_counts::HEADERS_REQUEST);
+
+                                    // Ensure the request is within the accepted limits.
+                                    let number_of_headers = end_block_height.saturating_sub(start_block_height);
+                                    if start_block_height > end_block_height || start_block_height == 0 || number_of_headers >= MAXIMUM_HEADERS_PER_RESPONSE {
+                                        // Route a `Failure` to the ledger.
+                                        let failure = format!("Attempted to request headers {} to {}", start_block_height, end_block_height);
+                                        if let Err(error) = peer.state.ledger().router().send(LedgerRequest::Failure(peer_ip, failure)).await {
+                                            warn!("[Failure] {}", error);
+                                        }
+                                        continue;
+                                    }
+                                    // Serve the headers up to the latest block, as the peer may be ahead of this node.
+                                    let end_block_height = end_block_height.min(peer.state.ledger().reader().latest_block_height());
+                                    if start_block_height > end_block_height {
+                                        continue;
+                                    }
+
+                                    // Retrieve the requested headers, along with their previous block hashes and block hashes.
+                                    // The block headers are retained for pruned blocks, so they are served regardless.
+                                    let reader = peer.state.ledger().reader();
+                                    let links = match (
+                                        reader.get_previous_block_hash(start_block_height),
+                                        reader.get_block_hashes(start_block_height, end_block_height),
+                                        reader.get_block_headers(start_block_height, end_block_height),
+                                    ) {
+                                        (Ok(previous_block_hash), Ok(block_hashes), Ok(headers)) => {
+                                            let previous_block_hashes = std::iter::once(previous_block_hash).chain(block_hashes.iter().copied());
+                                            previous_block_hashes.zip(block_hashes.iter().copied()).zip(headers).map(|((previous_block_hash, block_hash), header)| (previous_block_hash, block_hash, header)).collect::<Vec<HeaderLink<N>>>()
+                                        }
+                                        (Err(error), ..) | (_, Err(error), _) | (.., Err(error)) => {
+                                            // Route a `Failure` to the ledger.
+                                            if let Err(error) = peer.state.ledger().router().send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
+                                                warn!("[Failure] {}", error);
+                                            }
+                                            continue;
+                                        }
+                                    };
+
+                                    // Send a `HeadersResponse` message to the peer.
+                                    debug!("Sending 'HeadersResponse {}-{}' to {}", start_block_height, end_block_height, peer_ip);
+                                    if let Err(error) = outbound_socket.send(Message::HeadersResponse(Data::Object(links))).await {
+                                        warn!("[HeadersResponse] {}", error);
+                                        break;
+                                    }
+                                }
+                                Message::HeadersResponse(links) => {
+                                    #[cfg(any(feature = "test", feature = "prometheus"))]
+                                    metrics::increment_counter!(metrics::message_counts::HEADERS_RESPONSE);
+
+                                    // Perform the deferred non-blocking deserialization of the headers.
+                                    let request = match links.deserialize().await {
+                                        // Route the `HeadersResponse` to the ledger.
+                                        Ok(links) => LedgerRequest::HeadersResponse(peer_ip, links),
+                                        // Route the `Failure` to the ledger.
+                                        Err(error) => LedgerRequest::Failure(peer_ip, format!("{}", error)),
+                                    };
+                                    if let Err(error) = peer.state.ledger().router().send(request).await {
+                                        warn!("[HeadersResponse] {}", error);
+                                    }
                                 }
                                 Message::ChallengeRequest(..) | Message::ChallengeResponse(..) => {
                                     // Peer is not following the protocol.
                                     warn!("Peer {} is not following the protocol", peer_ip);
+                                    if let Err(error) = peers_router.send(PeersRequest::Penalize(peer_ip, Misbehavior::InvalidMessage)).await {
+                                        warn!("[Penalize] {}", error);
+                                    }
                                     break;
                                 },
                                 Message::Disconnect(reason) => {
@@ -233,12 +373,12 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                         warn!("[PeerResponse] {}", error);
                                     }
                                 }
-                                Message::Ping(version, fork_depth, node_type, status) => {
+                                Message::Ping(version, fork_depth, node_type, status, nonce) => {
                                     #[cfg(any(feature = "test", feature = "prometheus"))]
                                     metrics::increment_counter!(metrics::message_counts::PING);
 
                                     // Ensure the message protocol version is not outdated.
-                                    if version < E::MESSAGE_VERSION {
+                                    if version < E::MINIMUM_MESSAGE_VERSION {
                                         warn!("Dropping {} on version {} (outdated)", peer_ip, version);
                                         break;
                                     }
@@ -293,15 +433,25 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     #[cfg(any(feature = "test", feature = "prometheus"))]
                                     metrics::histogram!(metrics::internal_rtt::PING, rtt_start.elapsed());
 
+                                    // Send a `Pong` message to the peer, which echoes the nonce of the `Ping`.
+                                    if let Err(error) = outbound_socket.send(Message::Pong(None, nonce)).await {
+                                        warn!("[Pong] {}", error);
+                                    }
+
                                     // // Send a `Pong` message to the peer.
                                     // if let Err(error) = peer.send(Message::Pong(is_fork, Data::Object(state.ledger().reader().latest_block_locators()))).await {
                                     //     warn!("[Pong] {}", error);
                                     // }
                                 },
-                                Message::Pong(is_fork) => {
+                                Message::Pong(_is_fork, nonce) => {
                                     #[cfg(any(feature = "test", feature = "prometheus"))]
                                     metrics::increment_counter!(metrics::message_counts::PONG);
 
+                                    // Record the round-trip time of the `Ping` that the `Pong` answers.
+                                    if let Some(rtt) = peer.state.peers().record_pong(peer_ip, nonce).await {
+                                        trace!("Received 'Pong' from {} in {:?}", peer_ip, rtt);
+                                    }
+
                                     // Unfortunately can't be feature-flagged because of the enum
                                     // it's passed around in.
                                     let _rtt_start_instant: Option<Instant> = None;
@@ -350,12 +500,16 @@ impl<N: Network, E: Environment> Peer<N, E> {
 
                                     // Drop the peer, if they have sent more than 5 unconfirmed blocks in the last 5 seconds.
                                     let frequency = peer.seen_inbound_blocks.read().await.values().filter(|t| t.elapsed().unwrap().as_secs() <= 5).count();
-                                    if frequency >= 10 {
+                                    if !is_trusted && frequency >= 10 {
                                         warn!("Dropping {} for spamming unconfirmed blocks (frequency = {})", peer_ip, frequency);
                                         // Send a `PeerRestricted` message.
                                         if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
                                             warn!("[PeerRestricted] {}", error);
                                         }
+                                        // Penalize the peer for the spam.
+                                        if let Err(error) = peers_router.send(PeersRequest::Penalize(peer_ip, Misbehavior::Spam)).await {
+                                            warn!("[Penalize] {}", error);
+                                        }
                                         break;
                                     }
 
@@ -371,6 +525,15 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     // Drop the lock on the seen inbound blocks.
                                     drop(seen_inbound_blocks);
 
+                                    // Skip the block if it was recently received from any peer, before it is deserialized.
+                                    if peer.state.seen_blocks().check_and_insert(block_hash) {
+                                        #[cfg(any(feature = "test", feature = "prometheus"))]
+                                        metrics::increment_counter!(metrics::seen_cache::BLOCK_HITS);
+
+                                        trace!("Skipping duplicate 'UnconfirmedBlock {}' from {}", block_height, peer_ip);
+                                        continue;
+                                    }
+
                                     // // Ensure the unconfirmed block is at least within 2 blocks of the latest block height,
                                     // // and no more that 2 blocks ahead of the latest block height.
                                     // // If it is stale, skip the routing of this unconfirmed block to the ledger.
@@ -384,7 +547,9 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     //
                                     // // If this node is a beacon or sync node, skip this message, after updating the timestamp.
                                     // if E::NODE_TYPE == NodeType::Beacon || E::NODE_TYPE == NodeType::Beacon || !is_router_ready || !is_within_range || !is_node_ready {
-                                    //     trace!("Skipping 'UnconfirmedBlock {}' from {}", block_height, peer_ip)
+                                    //     trace!("Skipping 'UnconfirmedBlock {}' from {}", block_height, peer_ip);
+                                    //     // Forget the skipped block, so that it is processed if it is received again.
+                                    //     peer.state.seen_blocks().remove(&block_hash);
                                     // } else {
                                     //     // Perform the deferred non-blocking deserialization of the block.
                                     //     let request = match block.deserialize().await {
@@ -411,15 +576,34 @@ impl<N: Network, E: Environment> Peer<N, E> {
 
                                     // Drop the peer, if they have sent more than 500 unconfirmed transactions in the last 5 seconds.
                                     let frequency = peer.seen_inbound_transactions.read().await.values().filter(|t| t.elapsed().unwrap().as_secs() <= 5).count();
-                                    if frequency >= 500 {
+                                    if !is_trusted && frequency >= 500 {
                                         warn!("Dropping {} for spamming unconfirmed transactions (frequency = {})", peer_ip, frequency);
                                         // Send a `PeerRestricted` message.
                                         if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
                                             warn!("[PeerRestricted] {}", error);
                                         }
+                                        // Penalize the peer for the spam.
+                                        if let Err(error) = peers_router.send(PeersRequest::Penalize(peer_ip, Misbehavior::Spam)).await {
+                                            warn!("[Penalize] {}", error);
+                                        }
                                         break;
                                     }
 
+                                    // Skip the transaction if it was recently received from any peer, before it is deserialized.
+                                    let digest = match &transaction {
+                                        Data::Buffer(bytes) => Some(TransactionDigest::new(bytes)),
+                                        Data::Object(..) => None,
+                                    };
+                                    if let Some(digest) = digest {
+                                        if peer.state.seen_transactions().check_and_insert(digest) {
+                                            #[cfg(any(feature = "test", feature = "prometheus"))]
+                                            metrics::increment_counter!(metrics::seen_cache::TRANSACTION_HITS);
+
+                                            trace!("Skipping a duplicate 'UnconfirmedTransaction' from {}", peer_ip);
+                                            continue;
+                                        }
+                                    }
+
                                     // Perform the deferred non-blocking deserialisation of the
                                     // transaction.
                                     match transaction.deserialize().await {
@@ -437,6 +621,10 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                             // // If this node is a beacon or sync node, skip this message, after updating the timestamp.
                                             // if E::NODE_TYPE == NodeType::Beacon || E::NODE_TYPE == NodeType::Beacon || !is_router_ready || !is_node_ready {
                                             //     trace!("Skipping 'UnconfirmedTransaction {}' from {}", transaction.id(), peer_ip);
+                                            //     // Forget the skipped transaction, so that it is processed if it is received again.
+                                            //     if let Some(digest) = digest {
+                                            //         peer.state.seen_transactions().remove(&digest);
+                                            //     }
                                             // } else {
                                             //     // // Route the `UnconfirmedTransaction` to the prover.
                                             //     // if let Err(error) = state.prover().router().send(ProverRequest::UnconfirmedTransaction(peer_ip, transaction)).await {
@@ -451,7 +639,13 @@ impl<N: Network, E: Environment> Peer<N, E> {
                             }
                         }
                         // An error occurred.
-                        Some(Err(error)) => error!("Failed to read message from {}: {}", peer_ip, error),
+                        Some(Err(error)) => {
+                            error!("Failed to read message from {}: {}", peer_ip, error);
+                            // Penalize the peer for the malformed message.
+                            if let Err(error) = peers_router.send(PeersRequest::Penalize(peer_ip, Misbehavior::InvalidMessage)).await {
+                                warn!("[Penalize] {}", error);
+                            }
+                        }
                         // The stream has been disconnected.
                         None => break,
                     },
@@ -469,3 +663,53 @@ impl<N: Network, E: Environment> Peer<N, E> {
         });
     }
 }
+
+///
+/// Returns the messages that answer a block request with the given blocks, for a peer of the given message version.
+///
+/// A peer that supports batched block responses is sent the blocks in batches, and is answered with an empty batch
+/// if there are no blocks to serve, so that it does not wait for the request to time out. Otherwise, the peer is sent
+/// a `BlockResponse` message for each block.
+///
+fn block_responses<N: Network>(blocks: Vec<Block<N>>, peer_version: u32) -> Result<Vec<Message<N>>> {
+    if peer_version < BLOCK_BATCH_MESSAGE_VERSION {
+        return Ok(blocks.into_iter().map(|block| Message::BlockResponse(Data::Object(block))).collect());
+    }
+    match blocks.is_empty() {
+        true if peer_version >= EMPTY_BLOCK_BATCH_MESSAGE_VERSION => Ok(vec![Message::BlockBatchResponse(BlockBatch::Blocks(blocks))]),
+        true => Ok(vec![]),
+        false => Ok(BlockBatch::split(blocks)?.into_iter().map(Message::BlockBatchResponse).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_helpers::{sample_block, CurrentNetwork};
+
+    #[test]
+    fn test_block_responses() {
+        let block = sample_block();
+
+        // The blocks are sent in a batch, or one at a time to a peer that does not support batches.
+        let messages = block_responses(vec![block.clone()], EMPTY_BLOCK_BATCH_MESSAGE_VERSION).unwrap();
+        assert!(matches!(&messages[..], [Message::BlockBatchResponse(BlockBatch::Blocks(blocks))] if *blocks == vec![block.clone()]));
+        let messages = block_responses(vec![block.clone()], BLOCK_BATCH_MESSAGE_VERSION - 1).unwrap();
+        assert!(matches!(&messages[..], [Message::BlockResponse(Data::Object(response))] if *response == block));
+    }
+
+    #[test]
+    fn test_block_responses_beyond_the_latest_block() {
+        // A request that starts above the latest block is answered with an empty batch.
+        let messages = block_responses::<CurrentNetwork>(vec![], EMPTY_BLOCK_BATCH_MESSAGE_VERSION).unwrap();
+        assert!(matches!(&messages[..], [Message::BlockBatchResponse(BlockBatch::Blocks(blocks))] if blocks.is_empty()));
+
+        // An empty batch round-trips as no blocks, rather than as an invalid batch.
+        let mut bytes = Vec::new();
+        BlockBatch::<CurrentNetwork>::Blocks(vec![]).serialize_blocking_into(&mut bytes).unwrap();
+        assert!(BlockBatch::<CurrentNetwork>::Compressed(bytes.into()).deserialize_blocking().unwrap().is_empty());
+
+        // A peer that does not support empty batches is sent nothing, and times out on the request.
+        assert!(block_responses::<CurrentNetwork>(vec![], EMPTY_BLOCK_BATCH_MESSAGE_VERSION - 1).unwrap().is_empty());
+    }
+}
@@ -22,6 +22,8 @@ impl<N: Network, E: Environment> Peer<N, E> {
     /// Initializes a handshake to connect with a peer.
     pub(crate) async fn handshake(state: State<N, E>, stream: TcpStream, connection_result: Option<ConnectionResult>) {
         spawn_task!(E::resources().procure_id(), {
+            // Retrieve the IP address of the peer, to report it if the handshake fails.
+            let peer_addr = stream.peer_addr().ok().map(canonical_addr);
             // Register our peer with state which internally sets up some channels.
             match Peer::initialize(&state, stream).await {
                 Ok(peer) => {
@@ -33,7 +35,21 @@ impl<N: Network, E: Environment> Peer<N, E> {
                     }
                 }
                 Err(error) => {
-                    trace!("{}", error);
+                    match peer_addr {
+                        Some(peer_addr) => match state.peers().is_trusted_ip(peer_addr.ip()).await {
+                            // Report a failed handshake with a trusted peer loudly, as it likely indicates a misconfiguration.
+                            true => error!("The handshake with trusted peer {} failed: {}", peer_addr, error),
+                            // Penalize the peer for the failed handshake.
+                            false => {
+                                trace!("{}", error);
+                                let request = PeersRequest::Penalize(peer_addr, Misbehavior::FailedHandshake);
+                                if let Err(error) = state.peers().router().send(request).await {
+                                    warn!("[Penalize] {}", error);
+                                }
+                            }
+                        },
+                        None => trace!("{}", error),
+                    }
                     // If the optional connection result router is given, report a failed connection result.
                     if let Some(router) = connection_result {
                         if router.send(Err(error)).is_err() {
@@ -48,7 +64,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
     /// Initializes a new instance of `Peer`.
     async fn initialize(state: &State<N, E>, stream: TcpStream) -> Result<Self> {
         // Perform the handshake before proceeding.
-        let (mut outbound_socket, peer_ip, node_type, status) = Self::perform_handshake(stream, *state.local_ip()).await?;
+        let (mut outbound_socket, peer_ip, version, node_type, status) = Self::perform_handshake(stream, state.listener_port()).await?;
 
         // Initialize an MPSC channel for sending requests to the `Peer` struct.
         let (peer_router, peer_handler) = mpsc::channel(1024);
@@ -58,7 +74,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
             state: state.clone(),
             peer_router,
             listener_ip: Arc::new(peer_ip),
-            version: Arc::new(RwLock::new(0)),
+            version: Arc::new(RwLock::new(version)),
             node_type: Arc::new(RwLock::new(node_type)),
             status: Arc::new(RwLock::new(status)),
             block_height: Arc::new(RwLock::new(0)),
@@ -85,13 +101,13 @@ impl<N: Network, E: Environment> Peer<N, E> {
     /// Performs the handshake protocol, returning the listener IP of the peer upon success.
     async fn perform_handshake(
         stream: TcpStream,
-        local_ip: SocketAddr,
-    ) -> Result<(Framed<TcpStream, MessageCodec<N>>, SocketAddr, NodeType, Status)> {
+        advertised_port: u16,
+    ) -> Result<(Framed<TcpStream, MessageCodec<N>>, SocketAddr, u32, NodeType, Status)> {
         // Construct the socket.
         let mut outbound_socket = Framed::<TcpStream, MessageCodec<N>>::new(stream, Default::default());
 
         // Get the IP address of the peer.
-        let mut peer_ip = outbound_socket.get_ref().peer_addr()?;
+        let mut peer_ip = canonical_addr(outbound_socket.get_ref().peer_addr()?);
 
         // Retrieve the genesis block header. // TODO (raychu86): Ensure that the genesis header has no transactions.
         let genesis_header = Header::<N>::genesis(&Transactions::from(&vec![])?)?;
@@ -102,20 +118,21 @@ impl<N: Network, E: Environment> Peer<N, E> {
             ALEO_MAXIMUM_FORK_DEPTH,
             E::NODE_TYPE,
             E::status().get(),
-            local_ip.port(),
+            advertised_port,
+            SUPPORTED_FEATURES,
         );
         trace!("Sending '{}-A' to {}", message.name(), peer_ip);
         outbound_socket.send(message).await?;
 
         // Wait for the counterparty challenge request to come in.
-        let (node_type, status) = match outbound_socket.next().await {
+        let (version, node_type, status) = match outbound_socket.next().await {
             Some(Ok(message)) => {
                 // Process the message.
                 trace!("Received '{}-B' from {}", message.name(), peer_ip);
                 match message {
-                    Message::ChallengeRequest(version, fork_depth, node_type, peer_status, listener_port) => {
+                    Message::ChallengeRequest(version, fork_depth, node_type, peer_status, listener_port, features) => {
                         // Ensure the message protocol version is not outdated.
-                        if version < E::MESSAGE_VERSION {
+                        if version < E::MINIMUM_MESSAGE_VERSION {
                             warn!("Dropping {peer_ip} on version {version} (outdated)");
 
                             // Send the disconnect message.
@@ -168,12 +185,16 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                 bail!("Unable to reach '{peer_ip}': '{:?}'", error);
                             }
                         }
+                        // Compress the large messages to the peer, if it supports compression.
+                        if features & FEATURE_COMPRESSION != 0 {
+                            outbound_socket.codec_mut().enable_compression();
+                        }
                         // Send the challenge response.
                         let message = Message::ChallengeResponse(Data::Object(genesis_header.clone()));
                         trace!("Sending '{}-B' to {peer_ip}", message.name());
                         outbound_socket.send(message).await?;
 
-                        (node_type, peer_status)
+                        (version, node_type, peer_status)
                     }
                     Message::Disconnect(reason) => {
                         bail!("Peer {peer_ip} disconnected for the following reason: {:?}", reason);
@@ -200,12 +221,12 @@ impl<N: Network, E: Environment> Peer<N, E> {
                         let block_header = block_header.deserialize().await?;
                         match block_header == genesis_header {
                             true => {
-                                // Send the first `Ping` message to the peer.
-                                let message = Message::Ping(E::MESSAGE_VERSION, ALEO_MAXIMUM_FORK_DEPTH, E::NODE_TYPE, E::status().get());
+                                // Send the first `Ping` message to the peer, whose round-trip time is not measured.
+                                let message = Message::Ping(E::MESSAGE_VERSION, ALEO_MAXIMUM_FORK_DEPTH, E::NODE_TYPE, E::status().get(), 0);
                                 trace!("Sending '{}' to {}", message.name(), peer_ip);
                                 outbound_socket.send(message).await?;
 
-                                Ok((outbound_socket, peer_ip, node_type, status))
+                                Ok((outbound_socket, peer_ip, version, node_type, status))
                             }
                             false => bail!("Challenge response from {peer_ip} failed, received '{block_header}'"),
                         }
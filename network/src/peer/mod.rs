@@ -18,7 +18,21 @@ mod handler;
 mod handshake;
 
 use crate::{
-    message::{Data, DisconnectReason, Message, MessageCodec},
+    helpers::{
+        canonical_addr,
+        check_block_request,
+        BlockBatch,
+        HeaderLink,
+        Misbehavior,
+        ServedBlocks,
+        TransactionDigest,
+        BLOCK_BATCH_MESSAGE_VERSION,
+        EMPTY_BLOCK_BATCH_MESSAGE_VERSION,
+        HEADERS_MESSAGE_VERSION,
+        MAXIMUM_HEADERS_PER_RESPONSE,
+    },
+    ledger::LedgerRequest,
+    message::{Data, DisconnectReason, Message, MessageCodec, FEATURE_COMPRESSION, SUPPORTED_FEATURES},
     peers::{ConnectionResult, PeersRequest},
     spawn_task,
     state::State,
@@ -28,7 +42,7 @@ use snarkos_environment::{
     helpers::{NodeType, Status},
     Environment,
 };
-use snarkvm::prelude::*;
+use snarkvm::{prelude::*, Block};
 
 use anyhow::Result;
 use futures::SinkExt;
@@ -92,6 +106,16 @@ impl<N: Network, E: Environment> Peer<N, E> {
         &self.listener_ip
     }
 
+    /// Returns the node type of the peer.
+    pub async fn node_type(&self) -> NodeType {
+        *self.node_type.read().await
+    }
+
+    /// Returns the message version of the peer.
+    pub async fn version(&self) -> u32 {
+        *self.version.read().await
+    }
+
     /// Sends the given message to this peer.
     pub async fn send(&self, message: Message<N>) -> Result<()> {
         trace!("Sending '{}' to {}", message.name(), self.ip());
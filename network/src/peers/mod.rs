@@ -17,12 +17,33 @@
 mod update;
 
 use crate::{
+    helpers::{
+        canonical_addr,
+        resolve_dns_seeds,
+        BannedPeer,
+        ConnectionLimits,
+        ConnectionRefusal,
+        KnownPeers,
+        Misbehavior,
+        PeerLatencies,
+        PeerLatency,
+        PeerScores,
+        PeerSource,
+        TrustedPeers,
+        DEFAULT_PONG_TIMEOUT,
+        DNS_SEED_INTERVAL,
+        INVALID_BLOCK_STRIKE_HALF_LIFE,
+        KNOWN_PEERS_SAVE_INTERVAL,
+        PING_NONCE_MESSAGE_VERSION,
+    },
+    ledger::LedgerRequest,
     message::{Data, DisconnectReason, Message},
     peer::{Peer, PeerRouter},
     spawn_task,
     state::State,
 };
 use snarkos_environment::Environment;
+use snarkos_storage::ALEO_MAXIMUM_FORK_DEPTH;
 use snarkvm::prelude::*;
 
 #[cfg(feature = "rpc")]
@@ -32,10 +53,13 @@ use snarkos_rpc::{initialize_rpc_node, RpcContext};
 use snarkos_metrics as metrics;
 
 use ::rand::{prelude::IteratorRandom, rngs::OsRng};
-use anyhow::Result;
+use ::time::OffsetDateTime;
+use anyhow::{anyhow, Result};
+use once_cell::sync::OnceCell;
 use std::{
     collections::{HashMap, HashSet},
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
     time::{Duration, Instant, SystemTime},
 };
 use tokio::{
@@ -74,6 +98,8 @@ pub enum PeersRequest<N: Network, E: Environment> {
     PeerDisconnected(SocketAddr),
     /// PeerRestricted := (peer_ip)
     PeerRestricted(SocketAddr),
+    /// Penalize := (peer_ip, misbehavior)
+    Penalize(SocketAddr, Misbehavior),
     /// SendPeerResponse := (peer_ip, rtt_start)
     /// Note: rtt_start is for the request/response cycle for sharing peers.
     SendPeerResponse(SocketAddr, Option<Instant>),
@@ -91,14 +117,36 @@ pub struct Peers<N: Network, E: Environment> {
     peers_router: PeersRouter<N, E>,
     /// The map connected peer IPs to their outbound message router.
     connected_peers: RwLock<HashMap<SocketAddr, Peer<N, E>>>,
-    /// The set of candidate peer IPs.
-    candidate_peers: RwLock<HashSet<SocketAddr>>,
+    /// The map of candidate peer IPs to the source they were learned from.
+    candidate_peers: RwLock<HashMap<SocketAddr, PeerSource>>,
     /// The set of restricted peer IPs.
     restricted_peers: RwLock<HashMap<SocketAddr, Instant>>,
     /// The map of peers to their first-seen port number, number of attempts, and timestamp of the last inbound connection request.
     seen_inbound_connections: RwLock<HashMap<SocketAddr, ((u16, u32), SystemTime)>>,
     /// The map of peers to the timestamp of their last outbound connection request.
     seen_outbound_connections: RwLock<HashMap<SocketAddr, SystemTime>>,
+    /// The peers known to the node, which are kept across restarts.
+    known_peers: RwLock<KnownPeers>,
+    /// The file that the known peers are written to, once it is initialized.
+    known_peers_path: OnceCell<PathBuf>,
+    /// The timestamp at which the known peers were last written to disk.
+    known_peers_saved_at: RwLock<Instant>,
+    /// The known peers from before the restart, in decreasing order of last seen, which are connected to before the sync nodes.
+    startup_peers: RwLock<Vec<SocketAddr>>,
+    /// The score of each peer IP, which is lowered by its misbehaviors, until it is banned.
+    peer_scores: RwLock<PeerScores>,
+    /// The maximum number of connections from a single IP address and from a single subnet.
+    connection_limits: RwLock<ConnectionLimits>,
+    /// The peers that the node keeps permanently connected to, which are exempt from eviction, bans, and rate limits.
+    trusted_peers: RwLock<TrustedPeers>,
+    /// The DNS seeds, which are resolved to candidate peers.
+    dns_seeds: RwLock<Vec<String>>,
+    /// The timestamp at which the DNS seeds were last resolved, if ever.
+    dns_seeds_resolved_at: RwLock<Option<Instant>>,
+    /// The round-trip times of the connected peers, as measured by their pings.
+    peer_latencies: RwLock<PeerLatencies>,
+    /// The maximum duration to wait for the `Pong` of a `Ping`, before the `Ping` counts as missed.
+    pong_timeout: RwLock<Duration>,
 }
 
 impl<N: Network, E: Environment> Peers<N, E> {
@@ -118,11 +166,145 @@ impl<N: Network, E: Environment> Peers<N, E> {
             restricted_peers: Default::default(),
             seen_inbound_connections: Default::default(),
             seen_outbound_connections: Default::default(),
+            known_peers: Default::default(),
+            known_peers_path: Default::default(),
+            known_peers_saved_at: RwLock::new(Instant::now()),
+            startup_peers: Default::default(),
+            peer_scores: RwLock::new(PeerScores::new(
+                Duration::from_secs(E::PEER_SCORE_HALF_LIFE_IN_SECS),
+                INVALID_BLOCK_STRIKE_HALF_LIFE,
+                E::PEER_BAN_THRESHOLD,
+            )),
+            connection_limits: Default::default(),
+            trusted_peers: RwLock::new(TrustedPeers::new(E::trusted_nodes().iter().copied())),
+            dns_seeds: RwLock::new(E::DNS_SEEDS.iter().map(|seed| seed.to_string()).collect()),
+            dns_seeds_resolved_at: Default::default(),
+            peer_latencies: Default::default(),
+            pong_timeout: RwLock::new(DEFAULT_PONG_TIMEOUT),
         };
 
         (peers, peers_handler)
     }
 
+    ///
+    /// Initializes the known peers, which are periodically written to the given file.
+    /// Unless `is_fresh` is set, the known peers seen within `maximum_age` are loaded from the file,
+    /// and the node connects to the most recently seen ones before falling back to the sync nodes.
+    /// The bans are loaded from the file regardless.
+    ///
+    pub async fn initialize_known_peers(&self, path: PathBuf, is_fresh: bool, maximum_age: Duration) -> Result<()> {
+        let mut known_peers = KnownPeers::load(&path, maximum_age)?;
+        if is_fresh {
+            known_peers.clear();
+        }
+        info!("Loaded {} known peers and {} bans from {}", known_peers.len(), known_peers.bans().len(), path.display());
+
+        let startup_peers = known_peers.most_recent();
+        self.add_candidate_peers(startup_peers.iter(), PeerSource::KnownPeer).await;
+        *self.startup_peers.write().await = startup_peers;
+        *self.known_peers.write().await = known_peers;
+
+        self.known_peers_path.set(path).map_err(|_| anyhow!("The known peers are already initialized"))?;
+        Ok(())
+    }
+
+    ///
+    /// Adds the given peers to the trusted peers, which the node connects to on the next heartbeat,
+    /// and keeps permanently connected to.
+    ///
+    pub async fn add_trusted_peers(&self, peer_ips: Vec<SocketAddr>) {
+        self.trusted_peers.write().await.extend(peer_ips);
+    }
+
+    ///
+    /// Adds the given hostnames to the DNS seeds, which are resolved to candidate peers on the next heartbeat,
+    /// and again whenever the node is low on candidate peers.
+    ///
+    pub async fn add_dns_seeds(&self, dns_seeds: Vec<String>) {
+        let mut seeds = self.dns_seeds.write().await;
+        for seed in dns_seeds {
+            if !seeds.contains(&seed) {
+                seeds.push(seed);
+            }
+        }
+    }
+
+    ///
+    /// Sets the maximum number of inbound connections from a single IP address and from a single subnet.
+    ///
+    pub async fn set_connection_limits(&self, connection_limits: ConnectionLimits) {
+        *self.connection_limits.write().await = connection_limits;
+    }
+
+    ///
+    /// Sets the maximum duration to wait for the `Pong` of a `Ping`, before the `Ping` counts as missed.
+    ///
+    pub async fn set_pong_timeout(&self, pong_timeout: Duration) {
+        *self.pong_timeout.write().await = pong_timeout;
+    }
+
+    ///
+    /// Returns `Ok(())` if an inbound connection from the given peer IP is within the connection limits.
+    /// The loopback addresses, sync nodes, and trusted nodes are exempt from the connection limits.
+    ///
+    async fn check_connection_limits(&self, peer_ip: SocketAddr) -> Result<(), ConnectionRefusal> {
+        let is_exempt = peer_ip.ip().is_loopback()
+            || E::beacon_nodes().iter().any(|node_ip| node_ip.ip() == peer_ip.ip())
+            || self.is_trusted_ip(peer_ip.ip()).await;
+        if is_exempt {
+            return Ok(());
+        }
+
+        let connected_ips = self.connected_peers.read().await.keys().map(|connected_ip| connected_ip.ip()).collect::<Vec<_>>();
+        self.connection_limits.read().await.check(peer_ip.ip(), connected_ips)
+    }
+
+    ///
+    /// Writes the known peers to disk, if `KNOWN_PEERS_SAVE_INTERVAL` has passed since they were last written.
+    ///
+    async fn save_known_peers(&self) {
+        if self.known_peers_path.get().is_none() {
+            return;
+        }
+
+        {
+            let mut known_peers_saved_at = self.known_peers_saved_at.write().await;
+            if known_peers_saved_at.elapsed() < KNOWN_PEERS_SAVE_INTERVAL {
+                return;
+            }
+            *known_peers_saved_at = Instant::now();
+        }
+
+        // Refresh the last seen timestamp of the connected peers.
+        let mut connected_peers = Vec::new();
+        for peer in self.connected_peers.read().await.values() {
+            connected_peers.push((*peer.ip(), peer.node_type().await));
+        }
+        let mut known_peers = self.known_peers.write().await;
+        for (peer_ip, node_type) in connected_peers {
+            known_peers.seen(peer_ip, Some(node_type));
+        }
+        drop(known_peers);
+
+        self.write_known_peers().await;
+    }
+
+    ///
+    /// Writes the known peers to disk immediately, if the file is initialized.
+    ///
+    async fn write_known_peers(&self) {
+        let path = match self.known_peers_path.get() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let known_peers = self.known_peers.read().await;
+        match known_peers.save(path) {
+            Ok(()) => trace!("Saved {} known peers to {}", known_peers.len(), path.display()),
+            Err(error) => warn!("Failed to save the known peers to {}: {}", path.display(), error),
+        }
+    }
+
     ///
     /// Returns the peers router.
     ///
@@ -138,15 +320,110 @@ impl<N: Network, E: Environment> Peers<N, E> {
     }
 
     ///
-    /// Returns `true` if the given IP is restricted.
+    /// Returns `true` if the given IP is a trusted peer.
+    ///
+    pub async fn is_trusted(&self, ip: &SocketAddr) -> bool {
+        self.trusted_peers.read().await.contains(ip)
+    }
+
+    ///
+    /// Returns `true` if the given IP address belongs to a trusted peer.
+    /// This is used before the handshake, when the listening port of the peer is not known yet.
+    ///
+    pub async fn is_trusted_ip(&self, ip: IpAddr) -> bool {
+        self.trusted_peers.read().await.contains_ip(&ip)
+    }
+
+    ///
+    /// Returns the list of trusted peers.
+    ///
+    pub async fn trusted_peers(&self) -> Vec<SocketAddr> {
+        self.trusted_peers.read().await.peers()
+    }
+
+    ///
+    /// Returns `true` if the given IP is restricted, or banned.
     ///
     pub async fn is_restricted(&self, ip: SocketAddr) -> bool {
+        if self.known_peers.read().await.is_banned(&ip.ip()) {
+            return true;
+        }
         match self.restricted_peers.read().await.get(&ip) {
             Some(timestamp) => timestamp.elapsed().as_secs() < E::RADIO_SILENCE_IN_SECS,
             None => false,
         }
     }
 
+    ///
+    /// Returns the banned peer IPs, with the timestamp at which each ban expires.
+    ///
+    pub async fn banned_peers(&self) -> Vec<BannedPeer> {
+        self.known_peers.read().await.bans()
+    }
+
+    ///
+    /// Bans the given peer IP for the given duration, disconnecting from its connected peers.
+    /// The ban is written to disk immediately, so that it survives a restart.
+    ///
+    pub async fn ban(&self, peer_ip: IpAddr, duration: Duration) {
+        let banned_until = OffsetDateTime::now_utc().unix_timestamp().saturating_add(duration.as_secs() as i64);
+        self.known_peers.write().await.ban(peer_ip, banned_until);
+        self.candidate_peers.write().await.retain(|candidate_ip, _| candidate_ip.ip() != peer_ip);
+        self.startup_peers.write().await.retain(|startup_ip| startup_ip.ip() != peer_ip);
+
+        // Route a `Disconnect` to the ledger for each connected peer of the banned IP.
+        let banned_peers = self
+            .connected_peers()
+            .await
+            .into_iter()
+            .filter(|connected_ip| connected_ip.ip() == peer_ip)
+            .collect::<Vec<_>>();
+        for banned_peer in banned_peers {
+            let request = LedgerRequest::Disconnect(banned_peer, DisconnectReason::TooManyFailures);
+            if let Err(error) = self.state.ledger().router().send(request).await {
+                warn!("[Disconnect] {}", error);
+            }
+        }
+
+        self.write_known_peers().await;
+    }
+
+    ///
+    /// Lifts the ban of the given peer IP, returning `true` if it was banned.
+    ///
+    pub async fn unban(&self, peer_ip: IpAddr) -> bool {
+        self.peer_scores.write().await.remove_strikes(&peer_ip);
+        let is_banned = self.known_peers.write().await.unban(&peer_ip);
+        if is_banned {
+            self.write_known_peers().await;
+        }
+        is_banned
+    }
+
+    ///
+    /// Lifts all bans, returning the number of lifted bans.
+    ///
+    pub async fn clear_bans(&self) -> usize {
+        self.peer_scores.write().await.clear_strikes();
+        let number_of_bans = self.known_peers.write().await.clear_bans();
+        self.write_known_peers().await;
+        number_of_bans
+    }
+
+    ///
+    /// Adds an invalid block strike to the IP address of the given peer, returning its decayed number of strikes.
+    ///
+    pub async fn add_invalid_block_strike(&self, peer_ip: SocketAddr) -> f64 {
+        self.peer_scores.write().await.add_strike(peer_ip.ip())
+    }
+
+    ///
+    /// Returns `true` if the IP address of the given peer has served too many invalid blocks recently, and remains banned.
+    ///
+    pub async fn is_struck_out(&self, peer_ip: SocketAddr) -> bool {
+        self.peer_scores.read().await.is_struck_out(&peer_ip.ip())
+    }
+
     ///
     /// Returns the list of connected peers.
     ///
@@ -158,6 +435,13 @@ impl<N: Network, E: Environment> Peers<N, E> {
     /// Returns the list of candidate peers.
     ///
     pub async fn candidate_peers(&self) -> HashSet<SocketAddr> {
+        self.candidate_peers.read().await.keys().copied().collect()
+    }
+
+    ///
+    /// Returns the map of candidate peers to the source they were learned from.
+    ///
+    pub async fn candidate_peer_sources(&self) -> HashMap<SocketAddr, PeerSource> {
         self.candidate_peers.read().await.clone()
     }
 
@@ -208,4 +492,69 @@ impl<N: Network, E: Environment> Peers<N, E> {
     pub async fn number_of_restricted_peers(&self) -> usize {
         self.restricted_peers.read().await.len()
     }
+
+    ///
+    /// Records the `Pong` of the given peer, returning the round-trip time if it answers the outstanding `Ping`.
+    ///
+    pub async fn record_pong(&self, peer_ip: SocketAddr, nonce: u64) -> Option<Duration> {
+        self.peer_latencies.write().await.record_pong(peer_ip, nonce)
+    }
+
+    ///
+    /// Returns the round-trip times of the connected peers, in increasing order of peer IP.
+    ///
+    pub async fn peer_latencies(&self) -> Vec<PeerLatency> {
+        let mut peer_ips = self.connected_peers().await;
+        peer_ips.sort();
+
+        let peer_latencies = self.peer_latencies.read().await;
+        peer_ips.into_iter().map(|peer_ip| peer_latencies.get(peer_ip)).collect()
+    }
+
+    ///
+    /// Returns the relative share of the sync work for each of the given peers, by their round-trip times,
+    /// where `1.0` is a peer with an average or lower round-trip time.
+    ///
+    pub async fn latency_weights(&self, peer_ips: &[SocketAddr]) -> HashMap<SocketAddr, f64> {
+        let peer_latencies = self.peer_latencies.read().await;
+        peer_ips.iter().map(|peer_ip| (*peer_ip, peer_latencies.weight(peer_ip))).collect()
+    }
+
+    ///
+    /// Sends a `Ping` to each connected peer that is due for one, and disconnects from the peers
+    /// that have missed `MAXIMUM_MISSED_PONGS` consecutive pongs. The peers that predate the nonces
+    /// do not answer a `Ping`, so they are not pinged.
+    ///
+    async fn ping_peers(&self) {
+        let mut connected_peers = Vec::new();
+        for (peer_ip, peer) in self.connected_peers.read().await.iter() {
+            connected_peers.push((*peer_ip, peer.version().await));
+        }
+
+        let ping_interval = Duration::from_secs(E::PING_SLEEP_IN_SECS);
+        let pong_timeout = *self.pong_timeout.read().await;
+        let (pings, unresponsive_peers) = {
+            let mut peer_latencies = self.peer_latencies.write().await;
+            let unresponsive_peers = peer_latencies.expire_pings(pong_timeout);
+            let pings = connected_peers
+                .into_iter()
+                .filter(|(_, version)| *version >= PING_NONCE_MESSAGE_VERSION)
+                .filter_map(|(peer_ip, _)| peer_latencies.next_ping(peer_ip, ping_interval).map(|nonce| (peer_ip, nonce)))
+                .collect::<Vec<_>>();
+            (pings, unresponsive_peers)
+        };
+
+        for peer_ip in unresponsive_peers {
+            info!("Disconnecting from {} (unresponsive to pings)", peer_ip);
+            let request = LedgerRequest::Disconnect(peer_ip, DisconnectReason::PeerHasDisconnected);
+            if let Err(error) = self.state.ledger().router().send(request).await {
+                warn!("[Disconnect] {}", error);
+            }
+        }
+
+        for (peer_ip, nonce) in pings {
+            let message = Message::Ping(E::MESSAGE_VERSION, ALEO_MAXIMUM_FORK_DEPTH, E::NODE_TYPE, E::status().get(), nonce);
+            self.send(peer_ip, message).await;
+        }
+    }
 }
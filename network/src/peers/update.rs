@@ -26,12 +26,16 @@ impl<N: Network, E: Environment> Peers<N, E> {
 
         match request {
             PeersRequest::Connect(peer_ip, connection_result) => {
+                let peer_ip = canonical_addr(peer_ip);
+                // The trusted peers are connected to regardless of the peer limits, as they are reconnected to with their own backoff.
+                let is_trusted = self.is_trusted(&peer_ip).await;
+
                 // Ensure the peer IP is not this node.
                 if self.state.is_local_ip(&peer_ip) {
                     debug!("Skipping connection request to {} (attempted to self-connect)", peer_ip);
                 }
                 // Ensure the node does not surpass the maximum number of peer connections.
-                else if self.number_of_connected_peers().await >= E::MAXIMUM_NUMBER_OF_PEERS {
+                else if !is_trusted && self.number_of_connected_peers().await >= E::MAXIMUM_NUMBER_OF_PEERS {
                     debug!("Skipping connection request to {} (maximum peers reached)", peer_ip);
                 }
                 // Ensure the peer is a new connection.
@@ -39,7 +43,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     debug!("Skipping connection request to {} (already connected)", peer_ip);
                 }
                 // Ensure the peer is not restricted.
-                else if self.is_restricted(peer_ip).await {
+                else if !is_trusted && self.is_restricted(peer_ip).await {
                     debug!("Skipping connection request to {} (restricted)", peer_ip);
                 }
                 // Attempt to open a TCP stream.
@@ -50,7 +54,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     // Ensure the node respects the connection frequency limit.
                     let last_seen = seen_outbound_connections.entry(peer_ip).or_insert(SystemTime::UNIX_EPOCH);
                     let elapsed = last_seen.elapsed().unwrap_or(Duration::MAX).as_secs();
-                    if elapsed < E::RADIO_SILENCE_IN_SECS {
+                    if !is_trusted && elapsed < E::RADIO_SILENCE_IN_SECS {
                         trace!("Skipping connection request to {} (tried {} secs ago)", peer_ip, elapsed);
                     } else {
                         debug!("Connecting to {}...", peer_ip);
@@ -65,7 +69,10 @@ impl<N: Network, E: Environment> Peers<N, E> {
                             Ok(stream) => match stream {
                                 Ok(stream) => Peer::handshake(self.state.clone(), stream, Some(connection_result)).await,
                                 Err(error) => {
-                                    trace!("Failed to connect to '{}': '{:?}'", peer_ip, error);
+                                    match is_trusted {
+                                        true => warn!("Failed to connect to trusted peer '{}': '{:?}'", peer_ip, error),
+                                        false => trace!("Failed to connect to '{}': '{:?}'", peer_ip, error),
+                                    }
                                     self.candidate_peers.write().await.remove(&peer_ip);
                                 }
                             },
@@ -78,23 +85,36 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 }
             }
             PeersRequest::Heartbeat => {
+                // Periodically write the known peers to disk.
+                self.save_known_peers().await;
+                // Forget the scores of the peers that have decayed back to neutral.
+                self.peer_scores.write().await.remove_expired();
+                // Ping the connected peers, and disconnect from the unresponsive ones.
+                self.ping_peers().await;
+
                 // Obtain the number of connected peers.
                 let number_of_connected_peers = self.number_of_connected_peers().await;
                 // Ensure the number of connected peers is below the maximum threshold.
                 if number_of_connected_peers > E::MAXIMUM_NUMBER_OF_PEERS {
                     debug!("Exceeded maximum number of connected peers");
 
-                    // Determine the peers to disconnect from.
+                    // Determine the peers to disconnect from, preferring the unresponsive, low-value, and high-latency peers.
                     let num_excess_peers = number_of_connected_peers.saturating_sub(E::MAXIMUM_NUMBER_OF_PEERS);
-                    let peer_ips_to_disconnect = self
+                    let trusted_peers = self.trusted_peers().await;
+                    let evictable_peers = self
                         .connected_peers
                         .read()
                         .await
                         .keys()
-                        .filter(|peer_ip| !E::beacon_nodes().contains(peer_ip) && !E::trusted_nodes().contains(peer_ip))
-                        .take(num_excess_peers)
+                        .filter(|peer_ip| !E::beacon_nodes().contains(peer_ip) && !trusted_peers.contains(peer_ip))
                         .copied()
                         .collect::<Vec<SocketAddr>>();
+                    let peer_ips_to_disconnect = {
+                        let peer_scores = self.peer_scores.read().await;
+                        let eviction_order =
+                            self.peer_latencies.read().await.eviction_order(evictable_peers, |peer_ip| peer_scores.get(&peer_ip.ip()));
+                        eviction_order.into_iter().take(num_excess_peers).collect::<Vec<SocketAddr>>()
+                    };
 
                     // Proceed to send disconnect requests to these peers.
                     for peer_ip in peer_ips_to_disconnect {
@@ -126,31 +146,32 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     }
                 }
 
-                // Ensure that the trusted nodes are connected.
-                if !E::trusted_nodes().is_empty() {
-                    let connected_peers = self.connected_peers().await.into_iter().collect::<HashSet<_>>();
-                    let trusted_nodes = E::trusted_nodes();
-                    let disconnected_trusted_nodes = trusted_nodes.difference(&connected_peers).copied();
-                    for peer_ip in disconnected_trusted_nodes {
-                        // Initialize the connection process.
-                        let (router, handler) = oneshot::channel();
-                        let request = PeersRequest::Connect(peer_ip, router);
-                        if let Err(error) = self.peers_router.send(request).await {
-                            warn!("Failed to transmit the request: '{}'", error);
-                        }
+                // Ensure that the trusted peers are connected, reconnecting to the lost ones with backoff.
+                let connected_peers = self.connected_peers().await.into_iter().collect::<HashSet<_>>();
+                let trusted_peers_to_connect = self.trusted_peers.write().await.next_attempts(&connected_peers);
+                for peer_ip in trusted_peers_to_connect {
+                    let backoff = self.trusted_peers.read().await.backoff_of(&peer_ip).unwrap_or_default();
+
+                    // Initialize the connection process.
+                    let (router, handler) = oneshot::channel();
+                    let request = PeersRequest::Connect(peer_ip, router);
+                    if let Err(error) = self.peers_router.send(request).await {
+                        warn!("Failed to transmit the request: '{}'", error);
+                    }
 
-                        // Do not wait for the result of each connection.
-                        // Procure a resource id to register the task with, as it might be terminated at any point in time.
-                        let resource_id = E::resources().procure_id();
-                        E::resources().register_task(
-                            Some(resource_id),
-                            task::spawn(async move {
-                                let _ = handler.await;
+                    // Do not wait for the result of each connection.
+                    // Procure a resource id to register the task with, as it might be terminated at any point in time.
+                    let resource_id = E::resources().procure_id();
+                    E::resources().register_task(
+                        Some(resource_id),
+                        task::spawn(async move {
+                            if let Ok(Err(error)) = handler.await {
+                                warn!("Failed to connect to trusted peer {} (retrying in {:?}): {}", peer_ip, backoff, error);
+                            }
 
-                                E::resources().deregister(resource_id);
-                            }),
-                        );
-                    }
+                            E::resources().deregister(resource_id);
+                        }),
+                    );
                 }
 
                 // Skip if the number of connected peers is above the minimum threshold.
@@ -167,21 +188,36 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     false => return,
                 };
 
-                // Add the sync nodes to the list of candidate peers.
-                if number_of_connected_beacon_nodes == 0 {
-                    self.add_candidate_peers(E::beacon_nodes().iter()).await;
-                }
-
                 // Attempt to connect to more peers if the number of connected peers is below the minimum threshold.
-                // Select the peers randomly from the list of candidate peers.
                 let midpoint_number_of_peers = E::MINIMUM_NUMBER_OF_PEERS.saturating_add(E::MAXIMUM_NUMBER_OF_PEERS) / 2;
-                for peer_ip in self
-                    .candidate_peers()
-                    .await
-                    .iter()
-                    .copied()
-                    .choose_multiple(&mut OsRng::default(), midpoint_number_of_peers)
-                {
+
+                // Resolve the DNS seeds at startup, and again whenever the node is low on candidate peers.
+                self.refresh_dns_seeds(midpoint_number_of_peers).await;
+
+                // Select the most recently seen peers from before the restart first, before falling back to the sync nodes.
+                let startup_peers = {
+                    let mut startup_peers = self.startup_peers.write().await;
+                    let number_of_startup_peers = std::cmp::min(midpoint_number_of_peers, startup_peers.len());
+                    startup_peers.drain(..number_of_startup_peers).collect::<Vec<_>>()
+                };
+                let peer_ips = match startup_peers.is_empty() {
+                    false => startup_peers,
+                    true => {
+                        // Add the sync nodes to the list of candidate peers.
+                        if number_of_connected_beacon_nodes == 0 {
+                            self.add_candidate_peers(E::beacon_nodes().iter(), PeerSource::Bootnode).await;
+                        }
+
+                        // Select the peers randomly from the list of candidate peers.
+                        self.candidate_peers()
+                            .await
+                            .iter()
+                            .copied()
+                            .choose_multiple(&mut OsRng::default(), midpoint_number_of_peers)
+                    }
+                };
+
+                for peer_ip in peer_ips {
                     // Ensure this node is not connected to more than the permitted number of sync nodes.
                     if E::beacon_nodes().contains(&peer_ip) && number_of_connected_beacon_nodes >= 1 {
                         continue;
@@ -213,12 +249,16 @@ impl<N: Network, E: Environment> Peers<N, E> {
             PeersRequest::MessagePropagate(sender, message) => self.propagate(sender, message).await,
             PeersRequest::MessageSend(sender, message) => self.send(sender, message).await,
             PeersRequest::PeerConnecting(stream, peer_ip) => {
+                // The trusted peers are accepted regardless of the peer limits and restrictions.
+                // Note: The listening port of the peer is not known before the handshake, so it is matched by IP address.
+                let is_trusted = self.is_trusted_ip(peer_ip.ip()).await;
+
                 // Ensure the peer IP is not this node.
                 if self.state.is_local_ip(&peer_ip) {
                     debug!("Skipping connection request to {} (attempted to self-connect)", peer_ip);
                 }
                 // Ensure the node does not surpass the maximum number of peer connections.
-                else if self.number_of_connected_peers().await >= E::MAXIMUM_NUMBER_OF_PEERS {
+                else if !is_trusted && self.number_of_connected_peers().await >= E::MAXIMUM_NUMBER_OF_PEERS {
                     debug!("Dropping connection request from {} (maximum peers reached)", peer_ip);
                 }
                 // Ensure the node is not already connected to this peer.
@@ -226,9 +266,21 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     debug!("Dropping connection request from {} (already connected)", peer_ip);
                 }
                 // Ensure the peer is not restricted.
-                else if self.is_restricted(peer_ip).await {
+                else if !is_trusted && self.is_restricted(peer_ip).await {
                     debug!("Dropping connection request from {} (restricted)", peer_ip);
                 }
+                // Ensure the peer IP and its subnet are within the connection limits.
+                else if let Err(refusal) = self.check_connection_limits(peer_ip).await {
+                    debug!("Dropping connection request from {} ({:?})", peer_ip, refusal);
+
+                    #[cfg(any(feature = "test", feature = "prometheus"))]
+                    {
+                        match refusal {
+                            ConnectionRefusal::IpLimit => metrics::increment_counter!(metrics::peers::REFUSED_IP_LIMIT),
+                            ConnectionRefusal::SubnetLimit => metrics::increment_counter!(metrics::peers::REFUSED_SUBNET_LIMIT),
+                        }
+                    }
+                }
                 // Spawn a handler to be run asynchronously.
                 else {
                     // Sanitize the port from the peer, if it is a remote IP address.
@@ -259,7 +311,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     }
 
                     // Ensure the connecting peer has not surpassed the connection attempt limit.
-                    if *num_attempts > E::MAXIMUM_CONNECTION_FAILURES {
+                    if !is_trusted && *num_attempts > E::MAXIMUM_CONNECTION_FAILURES {
                         trace!("Dropping connection request from {} (tried {} secs ago)", peer_ip, elapsed);
                         // Add an entry for this `Peer` in the restricted peers.
                         self.restricted_peers.write().await.insert(peer_ip, Instant::now());
@@ -277,6 +329,10 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 }
             }
             PeersRequest::PeerConnected(peer_ip, peer) => {
+                // Reset the reconnection backoff of the peer, if it is trusted.
+                self.trusted_peers.write().await.reset(&peer_ip);
+                // Record the peer in the known peers.
+                self.known_peers.write().await.seen(peer_ip, Some(peer.node_type().await));
                 // Add an entry for this `Peer` in the connected peers.
                 self.connected_peers.write().await.insert(peer_ip, peer);
                 // Remove an entry for this `Peer` in the candidate peers, if it exists.
@@ -292,9 +348,14 @@ impl<N: Network, E: Environment> Peers<N, E> {
             }
             PeersRequest::PeerDisconnected(peer_ip) => {
                 // Remove an entry for this `Peer` in the connected peers, if it exists.
-                self.connected_peers.write().await.remove(&peer_ip);
+                if self.connected_peers.write().await.remove(&peer_ip).is_some() {
+                    // Record the time the peer was last seen in the known peers.
+                    self.known_peers.write().await.seen(peer_ip, None);
+                }
+                // Forget the round-trip time of the peer.
+                self.peer_latencies.write().await.remove(&peer_ip);
                 // Add an entry for this `Peer` in the candidate peers.
-                self.candidate_peers.write().await.insert(peer_ip);
+                self.candidate_peers.write().await.insert(peer_ip, PeerSource::Disconnected);
 
                 #[cfg(any(feature = "test", feature = "prometheus"))]
                 {
@@ -307,6 +368,10 @@ impl<N: Network, E: Environment> Peers<N, E> {
             PeersRequest::PeerRestricted(peer_ip) => {
                 // Remove an entry for this `Peer` in the connected peers, if it exists.
                 self.connected_peers.write().await.remove(&peer_ip);
+                // Remove an entry for this `Peer` in the known peers, so that it is not reconnected to after a restart.
+                self.known_peers.write().await.remove(&peer_ip);
+                // Forget the round-trip time of the peer.
+                self.peer_latencies.write().await.remove(&peer_ip);
                 // Add an entry for this `Peer` in the restricted peers.
                 self.restricted_peers.write().await.insert(peer_ip, Instant::now());
 
@@ -318,13 +383,39 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     metrics::gauge!(metrics::peers::RESTRICTED, number_of_restricted_peers as f64);
                 }
             }
+            PeersRequest::Penalize(peer_ip, misbehavior) => {
+                // Ensure the sync nodes and trusted peers are never banned.
+                if E::beacon_nodes().contains(&peer_ip) || self.is_trusted_ip(peer_ip.ip()).await {
+                    trace!("Skipping the penalty of {} for {:?} (sync node or trusted peer)", peer_ip, misbehavior);
+                    return;
+                }
+
+                let penalty = misbehavior.penalty::<E>();
+                if self.peer_scores.write().await.penalize(peer_ip.ip(), penalty) {
+                    let ban_duration = Duration::from_secs(E::PEER_BAN_DURATION_IN_SECS);
+                    info!("Banning {} for {:?} (reached the ban threshold on {:?})", peer_ip.ip(), ban_duration, misbehavior);
+                    self.ban(peer_ip.ip(), ban_duration).await;
+                } else {
+                    debug!("Penalized {} by {} for {:?}", peer_ip, penalty, misbehavior);
+                }
+            }
             PeersRequest::SendPeerResponse(recipient, rtt_start) => {
                 // Send a `PeerResponse` message.
                 let connected_peers = self.connected_peers().await;
                 self.send(recipient, Message::PeerResponse(connected_peers, rtt_start)).await;
             }
             PeersRequest::ReceivePeerResponse(peer_ips) => {
-                self.add_candidate_peers(peer_ips.iter()).await;
+                let peer_ips = peer_ips.into_iter().map(canonical_addr).collect::<Vec<_>>();
+                self.add_candidate_peers(peer_ips.iter(), PeerSource::Gossip).await;
+
+                // Record the new candidate peers in the known peers, as they were just seen by a connected peer.
+                let mut known_peers = self.known_peers.write().await;
+                for peer_ip in peer_ips.iter().filter(|peer_ip| !self.state.is_local_ip(peer_ip)) {
+                    if !known_peers.contains(peer_ip) && !known_peers.is_banned(&peer_ip.ip()) {
+                        known_peers.seen(*peer_ip, None);
+                    }
+                }
+                drop(known_peers);
 
                 #[cfg(any(feature = "test", feature = "prometheus"))]
                 {
@@ -335,23 +426,61 @@ impl<N: Network, E: Environment> Peers<N, E> {
         }
     }
 
-    /// Adds the given peer IPs to the set of candidate peers.
+    /// Adds the given peer IPs to the set of candidate peers, from the given source.
     ///
     /// This method skips adding any given peers if the combined size exceeds the threshold,
     /// as the peer providing this list could be subverting the protocol.
-    async fn add_candidate_peers<'a, T: ExactSizeIterator<Item = &'a SocketAddr> + IntoIterator>(&self, peers: T) {
+    /// A peer that is already a candidate keeps the source it was first learned from.
+    async fn add_candidate_peers<'a, T: ExactSizeIterator<Item = &'a SocketAddr> + IntoIterator>(&self, peers: T, source: PeerSource) {
         // Acquire the candidate peers write lock.
         let mut candidate_peers = self.candidate_peers.write().await;
         // Ensure the combined number of peers does not surpass the threshold.
         for peer_ip in peers.take(E::MAXIMUM_CANDIDATE_PEERS.saturating_sub(candidate_peers.len())) {
+            let peer_ip = canonical_addr(*peer_ip);
             // Ensure the peer is not itself and is a new candidate peer.
-            if !self.state.is_local_ip(peer_ip) && !self.is_connected_to(*peer_ip).await {
+            if !self.state.is_local_ip(&peer_ip) && !self.is_connected_to(peer_ip).await {
                 // Proceed to insert each new candidate peer IP.
-                candidate_peers.insert(*peer_ip);
+                candidate_peers.entry(peer_ip).or_insert(source);
             }
         }
     }
 
+    ///
+    /// Resolves the DNS seeds in the background, and adds the resolved peers to the candidate peers.
+    /// The DNS seeds are resolved on the first call, and afterwards at most once per `DNS_SEED_INTERVAL`,
+    /// if the number of candidate peers is below the given threshold.
+    ///
+    async fn refresh_dns_seeds(&self, minimum_number_of_candidate_peers: usize) {
+        let dns_seeds = self.dns_seeds.read().await.clone();
+        if dns_seeds.is_empty() {
+            return;
+        }
+
+        // Ensure the DNS seeds are due for a resolution.
+        {
+            let number_of_candidate_peers = self.number_of_candidate_peers().await;
+            let mut resolved_at = self.dns_seeds_resolved_at.write().await;
+            let is_due = match *resolved_at {
+                Some(resolved_at) => {
+                    resolved_at.elapsed() >= DNS_SEED_INTERVAL && number_of_candidate_peers < minimum_number_of_candidate_peers
+                }
+                None => true,
+            };
+            if !is_due {
+                return;
+            }
+            *resolved_at = Some(Instant::now());
+        }
+
+        // Resolve the DNS seeds without blocking the heartbeat, as a resolver may be slow or unresponsive.
+        let state = self.state.clone();
+        spawn_task!(E::resources().procure_id(), {
+            let peer_ips = resolve_dns_seeds(&dns_seeds, E::DEFAULT_NODE_PORT).await;
+            debug!("Resolved {} candidate peers from {} DNS seeds", peer_ips.len(), dns_seeds.len());
+            state.peers().add_candidate_peers(peer_ips.iter(), PeerSource::DnsSeed).await;
+        });
+    }
+
     /// Sends the given message to specified peer.
     async fn send(&self, peer_ip: SocketAddr, message: Message<N>) {
         let target_peer = self.connected_peers.read().await.get(&peer_ip).cloned();
@@ -398,5 +527,8 @@ impl<N: Network, E: Environment> Peers<N, E> {
         self.restricted_peers.write().await.clear();
         self.seen_inbound_connections.write().await.clear();
         self.seen_outbound_connections.write().await.clear();
+        self.known_peers.write().await.clear();
+        self.known_peers.write().await.clear_bans();
+        self.startup_peers.write().await.clear();
     }
 }
@@ -15,6 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+    helpers::{canonical_addr, LedgerEvent, MemoryPool, PortMapping, SeenCache, TransactionDigest},
     ledger::{Ledger, LedgerHandler},
     peers::{Peers, PeersHandler, PeersRequest},
 };
@@ -25,8 +26,13 @@ use snarkvm::prelude::*;
 
 use anyhow::Result;
 use once_cell::race::OnceBox;
-use std::{net::SocketAddr, sync::Arc, time::Duration};
-use tokio::{net::TcpListener, sync::oneshot};
+use parking_lot::RwLock;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, oneshot, watch, Mutex},
+};
 
 #[macro_export]
 macro_rules! spawn_task {
@@ -75,19 +81,29 @@ macro_rules! spawn_task {
 pub struct State<N: Network, E: Environment> {
     /// The local IP of the node.
     local_ip: Arc<SocketAddr>,
+    /// The external IP of the node, if its listener port is mapped on the gateway.
+    external_ip: Arc<RwLock<Option<SocketAddr>>>,
+    /// The mapping of the listener port on the gateway, if any.
+    port_mapping: Arc<Mutex<Option<PortMapping>>>,
     /// The Aleo account of the node.
     account: Arc<Account<N>>,
     /// The list of peers for the node.
     peers: Arc<OnceBox<Peers<N, E>>>,
     /// The ledger for the node.
     ledger: Arc<OnceBox<Ledger<N, E>>>,
+    /// The unconfirmed blocks recently received from any peer.
+    seen_blocks: Arc<SeenCache<N::BlockHash>>,
+    /// The unconfirmed transactions recently received from any peer.
+    seen_transactions: Arc<SeenCache<TransactionDigest>>,
+    /// The memory pool of unconfirmed transactions.
+    memory_pool: Arc<MemoryPool<N>>,
 }
 
 impl<N: Network, E: Environment> State<N, E> {
     /// Initializes a new `State` instance.
     pub async fn new(node_ip: SocketAddr, account: Account<N>) -> Result<Self> {
         // Initialize a new TCP listener at the given IP.
-        let (local_ip, listener) = match TcpListener::bind(node_ip).await {
+        let (local_ip, listener) = match bind_listener(node_ip) {
             Ok(listener) => (listener.local_addr().expect("Failed to fetch the local IP"), listener),
             Err(error) => panic!("Failed to bind listener: {:?}. Check if another Aleo node is running", error),
         };
@@ -95,9 +111,14 @@ impl<N: Network, E: Environment> State<N, E> {
         // Construct the state.
         let state = Self {
             local_ip: Arc::new(local_ip),
+            external_ip: Arc::new(Default::default()),
+            port_mapping: Arc::new(Default::default()),
             account: Arc::new(account),
             peers: Arc::new(Default::default()),
             ledger: Arc::new(Default::default()),
+            seen_blocks: Arc::new(Default::default()),
+            seen_transactions: Arc::new(Default::default()),
+            memory_pool: Arc::new(Default::default()),
         };
 
         // Initialize a new peers module.
@@ -123,6 +144,16 @@ impl<N: Network, E: Environment> State<N, E> {
         &self.local_ip
     }
 
+    /// Returns the external IP address of this node, if its listener port is mapped on the gateway.
+    pub fn external_ip(&self) -> Option<SocketAddr> {
+        *self.external_ip.read()
+    }
+
+    /// Returns the listener port that this node advertises to its peers, which is the external port if it is mapped.
+    pub fn listener_port(&self) -> u16 {
+        self.external_ip().map_or(self.local_ip.port(), |external_ip| external_ip.port())
+    }
+
     /// Returns the Aleo address of this node.
     pub fn address(&self) -> &Address<N> {
         self.account.address()
@@ -137,9 +168,51 @@ impl<N: Network, E: Environment> State<N, E> {
         self.ledger.get().unwrap()
     }
 
+    /// Returns a new subscription to the events of the ledger of this node.
+    pub fn ledger_events(&self) -> broadcast::Receiver<LedgerEvent<N>> {
+        self.ledger().subscribe()
+    }
+
+    /// Returns a new subscription to the latest block height and block hash of the ledger of this node.
+    pub fn canon_tip(&self) -> watch::Receiver<(u32, N::BlockHash)> {
+        self.ledger().subscribe_canon_tip()
+    }
+
+    /// Returns the cache of unconfirmed blocks recently received from any peer.
+    pub fn seen_blocks(&self) -> &SeenCache<N::BlockHash> {
+        &self.seen_blocks
+    }
+
+    /// Returns the cache of unconfirmed transactions recently received from any peer.
+    pub fn seen_transactions(&self) -> &SeenCache<TransactionDigest> {
+        &self.seen_transactions
+    }
+
+    /// Returns the memory pool of unconfirmed transactions of this node.
+    pub fn memory_pool(&self) -> &MemoryPool<N> {
+        &self.memory_pool
+    }
+
     /// Returns `true` if the given IP is this node.
     pub fn is_local_ip(&self, ip: &SocketAddr) -> bool {
-        *ip == *self.local_ip || (ip.ip().is_unspecified() || ip.ip().is_loopback()) && ip.port() == self.local_ip.port()
+        let ip = &canonical_addr(*ip);
+        *ip == *self.local_ip
+            || (ip.ip().is_unspecified() || ip.ip().is_loopback()) && ip.port() == self.local_ip.port()
+            || Some(*ip) == self.external_ip()
+    }
+
+    ///
+    /// Removes the mapping of the listener port from the gateway, if any.
+    ///
+    pub async fn remove_port_mapping(&self) {
+        if let Some(port_mapping) = self.port_mapping.lock().await.take() {
+            *self.external_ip.write() = None;
+            let kind = port_mapping.kind();
+            match port_mapping.remove().await {
+                Ok(()) => debug!("Removed the {} port mapping from the gateway", kind),
+                Err(error) => warn!("Failed to remove the port mapping from the gateway: {}", error),
+            }
+        }
     }
 }
 
@@ -183,7 +256,7 @@ impl<N: Network, E: Environment> State<N, E> {
                     match listener.accept().await {
                         // Process the inbound connection request.
                         Ok((stream, peer_ip)) => {
-                            let request = PeersRequest::PeerConnecting(stream, peer_ip);
+                            let request = PeersRequest::PeerConnecting(stream, canonical_addr(peer_ip));
                             if let Err(error) = state.peers().router().send(request).await {
                                 error!("Failed to send request to peers: {}", error)
                             }
@@ -200,6 +273,49 @@ impl<N: Network, E: Environment> State<N, E> {
         });
     }
 
+    ///
+    /// Initialize the mapping of the listener port on the gateway, so that peers behind NAT are able to connect to this node.
+    /// The mapping is renewed periodically. If no gateway supports UPnP or NAT-PMP, the node proceeds without the mapping.
+    ///
+    pub async fn initialize_port_mapping(&self) {
+        let state = self.clone();
+        spawn_task!({
+            let port_mapping = match PortMapping::new(state.local_ip.port()).await {
+                Ok(port_mapping) => port_mapping,
+                Err(error) => {
+                    warn!("Unable to map the listener port on the gateway, so peers behind NAT may be unable to connect: {}", error);
+                    return;
+                }
+            };
+            info!("Mapped the listener port on the gateway with {}, at {}", port_mapping.kind(), port_mapping.external_ip());
+            // Advertise the external IP to peers.
+            *state.external_ip.write() = Some(port_mapping.external_ip());
+            let mut renewal_interval = port_mapping.renewal_interval();
+            *state.port_mapping.lock().await = Some(port_mapping);
+
+            loop {
+                tokio::time::sleep(renewal_interval).await;
+                let mut port_mapping = state.port_mapping.lock().await;
+                // Stop renewing once the mapping has been removed on shutdown.
+                let port_mapping = match port_mapping.as_mut() {
+                    Some(port_mapping) => port_mapping,
+                    None => break,
+                };
+                match port_mapping.renew().await {
+                    Ok(()) => {
+                        *state.external_ip.write() = Some(port_mapping.external_ip());
+                        renewal_interval = port_mapping.renewal_interval();
+                    }
+                    Err(error) => {
+                        // Retry sooner than the next renewal, so that the lease does not expire in the meantime.
+                        renewal_interval = port_mapping.renewal_retry_interval();
+                        warn!("Failed to renew the port mapping on the gateway (retrying in {:?}): {}", renewal_interval, error);
+                    }
+                }
+            }
+        });
+    }
+
     ///
     /// Initialize a new instance of the ledger.
     ///
@@ -213,11 +329,11 @@ impl<N: Network, E: Environment> State<N, E> {
             tokio::spawn(async move {
                 // Notify the outer function that the task is ready.
                 let _ = router.send(());
-                // Asynchronously wait for a ledger request.
+                // Asynchronously wait for a ledger request, favouring the requests relevant to the network tip.
                 while let Some(request) = ledger_handler.recv().await {
                     // Update the state of the ledger.
                     // Note: Do not wrap this call in a `tokio::spawn` as `BlockResponse` messages
-                    // will end up being processed out of order.
+                    // will end up being processed out of order. They share the bulk lane, which preserves their order.
 
                     state.ledger().update(request).await;
                 }
@@ -249,3 +365,23 @@ impl<N: Network, E: Environment> State<N, E> {
         });
     }
 }
+
+///
+/// Binds a new TCP listener at the given IP. A listener at an IPv6 address accepts IPv4 connections as well,
+/// where dual-stack sockets are supported, so that `[::]` listens on all interfaces of both protocols.
+///
+fn bind_listener(node_ip: SocketAddr) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(node_ip), Type::STREAM, Some(Protocol::TCP))?;
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    if node_ip.is_ipv6() {
+        // Fall back to an IPv6-only listener, if dual-stack sockets are not supported.
+        if let Err(error) = socket.set_only_v6(false) {
+            debug!("Listening for IPv6 peers only, as dual-stack sockets are not supported: {}", error);
+        }
+    }
+    socket.bind(&node_ip.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    TcpListener::from_std(socket.into())
+}
@@ -0,0 +1,177 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+///
+/// A helper struct to maintain a bounded number of elements in a set, with a capacity chosen at runtime.
+/// Once the set is full, the oldest element is evicted to make room for a new one.
+///
+/// Each element is tagged with the generation in which it was inserted, so that removing an element only
+/// leaves a stale entry in the queue, which is skipped on eviction, instead of searching the queue for it.
+///
+#[derive(Clone, Debug)]
+pub struct CircularSet<K: Clone + PartialEq + Eq + Hash> {
+    set: HashMap<K, u64>,
+    queue: VecDeque<(K, u64)>,
+    next_generation: u64,
+    capacity: usize,
+}
+
+impl<K: Clone + PartialEq + Eq + Hash> CircularSet<K> {
+    ///
+    /// Initializes a new instance of a circular set, of the given capacity.
+    ///
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            set: Default::default(),
+            queue: Default::default(),
+            next_generation: 0,
+            capacity,
+        }
+    }
+
+    ///
+    /// Returns `true` if the circular set is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    ///
+    /// Returns the number of elements in the circular set.
+    ///
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    ///
+    /// Returns `true` if the circular set has reached its capacity.
+    ///
+    pub fn is_full(&self) -> bool {
+        self.set.len() >= self.capacity
+    }
+
+    ///
+    /// Returns `true` if the given element exists in the circular set.
+    ///
+    pub fn contains(&self, element: &K) -> bool {
+        self.set.contains_key(element)
+    }
+
+    ///
+    /// Inserts the given element into the circular set, returning a `bool`
+    /// indicating whether the insertion took place.
+    ///
+    pub fn insert(&mut self, element: K) -> bool {
+        if self.capacity == 0 || self.contains(&element) {
+            return false;
+        }
+
+        if self.is_full() {
+            // Evict the oldest element, skipping the stale entries of removed elements.
+            while let Some((popped, generation)) = self.queue.pop_front() {
+                if self.set.get(&popped) == Some(&generation) {
+                    self.set.remove(&popped);
+                    break;
+                }
+            }
+        }
+
+        // Drop the stale entries once they outnumber the elements, which takes amortized constant time.
+        if self.queue.len() >= self.capacity.saturating_mul(2) {
+            let set = &self.set;
+            self.queue.retain(|(queued, generation)| set.get(queued) == Some(generation));
+        }
+
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        self.queue.push_back((element.clone(), generation));
+        self.set.insert(element, generation).is_none()
+    }
+
+    ///
+    /// Removes the given element from the circular set, returning a `bool`
+    /// indicating whether the element was present.
+    ///
+    pub fn remove(&mut self, element: &K) -> bool {
+        // The entry of the element in the queue is left behind, and is skipped once it is reached.
+        self.set.remove(element).is_some()
+    }
+
+    ///
+    /// Removes all the elements from the circular set.
+    ///
+    pub fn clear(&mut self) {
+        self.set.clear();
+        self.queue.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction() {
+        let mut set = CircularSet::with_capacity(3);
+        for element in 0..5 {
+            assert!(set.insert(element));
+        }
+        // The oldest elements are evicted.
+        assert_eq!(3, set.len());
+        assert!(!set.contains(&0));
+        assert!(!set.contains(&1));
+        assert!((2..5).all(|element| set.contains(&element)));
+        assert!(!set.insert(4));
+    }
+
+    #[test]
+    fn test_remove_and_reinsert() {
+        let mut set = CircularSet::with_capacity(3);
+        for element in 0..3 {
+            set.insert(element);
+        }
+        assert!(set.remove(&0));
+        assert!(!set.remove(&0));
+        assert!(set.remove(&1));
+
+        // Reinsert a removed element, and ensure its stale entry does not evict the new one.
+        assert!(set.insert(0));
+        assert!(set.insert(3));
+        assert_eq!(3, set.len());
+        assert!(set.is_full());
+
+        // The next insertion evicts the oldest live element, which is 2.
+        assert!(set.insert(4));
+        assert!(!set.contains(&2));
+        assert!([0, 3, 4].iter().all(|element| set.contains(element)));
+    }
+
+    #[test]
+    fn test_stale_entries_are_dropped() {
+        let mut set = CircularSet::with_capacity(4);
+        for element in 0..1000 {
+            set.insert(element);
+            set.remove(&element);
+        }
+        assert!(set.is_empty());
+        assert!(set.queue.len() <= 8);
+    }
+}
@@ -25,3 +25,6 @@
 
 mod circular_map;
 pub use circular_map::*;
+
+mod circular_set;
+pub use circular_set::*;
@@ -145,6 +145,11 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         &self.ledger_router
     }
 
+    /// Returns the highest latest block height of the connected peers, if any peer has sent its ledger state.
+    pub async fn maximum_block_height_of_peers(&self) -> Option<u32> {
+        self.peers_state.read().await.values().flatten().map(|(_, _, _, block_height, _)| *block_height).max()
+    }
+
     pub async fn shut_down(&self) {
         debug!("Ledger is shutting down...");
 
@@ -19,10 +19,13 @@ use snarkos_environment::{
     helpers::{NodeType, Status},
     Environment,
 };
-use snarkvm::prelude::{to_bytes_le, Network, ToBytes};
+use snarkvm::{
+    dpc::{posw::PoSWProof, BlockTemplate},
+    prelude::{to_bytes_le, Address, Network, ToBytes},
+};
 
 use ::bytes::{Buf, BufMut, Bytes, BytesMut};
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{io::Write, marker::PhantomData, net::SocketAddr, time::Instant};
 use tokio::task;
@@ -78,6 +81,13 @@ impl<T: 'static + Serialize + DeserializeOwned + Send> Data<T> {
     }
 }
 
+/// The maximum length in bytes of a worker name in a `PoolRegister` message.
+pub const MAXIMUM_WORKER_NAME_LENGTH: usize = 64;
+/// The worker name of a prover that registers without one, which a `PoolRegister` message may not name explicitly.
+pub const DEFAULT_WORKER_NAME: &str = "default";
+/// The maximum number of shares in a `PoolResponse` message.
+pub const MAXIMUM_SHARES_PER_POOL_RESPONSE: usize = 16;
+
 /// The reason behind the node disconnecting from a peer.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum DisconnectReason {
@@ -127,6 +137,13 @@ pub enum Message<N: Network> {
     Ping(u32, u32, NodeType, Status),
     /// Pong := (is_fork)
     Pong(Option<bool>),
+    /// PoolRegister := (address, worker)
+    PoolRegister(Address<N>, Option<String>),
+    /// PoolRequest := (share_difficulty, block_template)
+    PoolRequest(u64, Data<BlockTemplate<N>>),
+    /// PoolResponse := (address, block_height, [(nonce, proof)])
+    /// The block height of the block template of the shares is omitted by older provers.
+    PoolResponse(Address<N>, Option<u32>, Vec<(N::PoSWNonce, Data<PoSWProof<N>>)>),
     /// UnconfirmedBlock := (block_height, block_hash, block)
     UnconfirmedBlock(u32, N::BlockHash, Data<Block<N>>),
     /// UnconfirmedTransaction := (transaction)
@@ -147,6 +164,9 @@ impl<N: Network> Message<N> {
             Self::PeerResponse(..) => "PeerResponse",
             Self::Ping(..) => "Ping",
             Self::Pong(..) => "Pong",
+            Self::PoolRegister(..) => "PoolRegister",
+            Self::PoolRequest(..) => "PoolRequest",
+            Self::PoolResponse(..) => "PoolResponse",
             Self::UnconfirmedBlock(..) => "UnconfirmedBlock",
             Self::UnconfirmedTransaction(..) => "UnconfirmedTransaction",
         }
@@ -167,6 +187,15 @@ impl<N: Network> Message<N> {
             Self::Pong(..) => 8,
             Self::UnconfirmedBlock(..) => 9,
             Self::UnconfirmedTransaction(..) => 10,
+            Self::PoolRegister(..) => 11,
+            Self::PoolRequest(..) => 12,
+            // A single share is sent with the ID that predates batched shares, so that older operators understand it.
+            // Shares are only sent with their block height under a new ID, as older operators reject it.
+            Self::PoolResponse(_, block_height, shares) => match (block_height, shares.len()) {
+                (None, 1) => 13,
+                (None, _) => 14,
+                (Some(_), _) => 15,
+            },
         }
     }
 
@@ -207,6 +236,38 @@ impl<N: Network> Message<N> {
                 block.serialize_blocking_into(writer)
             }
             Self::UnconfirmedTransaction(transaction) => Ok(transaction.serialize_blocking_into(writer)?),
+            Self::PoolRegister(address, worker) => {
+                bincode::serialize_into(&mut *writer, address)?;
+                // The worker name is only appended if present, so that the default worker is understood by older operators.
+                match worker {
+                    Some(worker) => Ok(bincode::serialize_into(writer, worker)?),
+                    None => Ok(()),
+                }
+            }
+            Self::PoolRequest(share_difficulty, block_template) => {
+                bincode::serialize_into(&mut *writer, share_difficulty)?;
+                block_template.serialize_blocking_into(writer)
+            }
+            Self::PoolResponse(address, block_height, shares) => match (block_height, &shares[..]) {
+                (None, [(nonce, proof)]) => {
+                    bincode::serialize_into(&mut *writer, &(address, nonce))?;
+                    proof.serialize_blocking_into(writer)
+                }
+                _ => {
+                    match block_height {
+                        Some(block_height) => bincode::serialize_into(&mut *writer, &(address, block_height, shares.len() as u16))?,
+                        None => bincode::serialize_into(&mut *writer, &(address, shares.len() as u16))?,
+                    }
+                    // Each proof is prefixed with its length, as its deserialization is deferred.
+                    for (nonce, proof) in shares {
+                        let mut proof_bytes = Vec::new();
+                        proof.serialize_blocking_into(&mut proof_bytes)?;
+                        bincode::serialize_into(&mut *writer, &(nonce, proof_bytes.len() as u32))?;
+                        writer.write_all(&proof_bytes)?;
+                    }
+                    Ok(())
+                }
+            },
         }
     }
 
@@ -287,6 +348,58 @@ impl<N: Network> Message<N> {
                 )
             }
             10 => Self::UnconfirmedTransaction(Data::Buffer(bytes.freeze())),
+            11 => {
+                let mut reader = bytes.reader();
+                let address = bincode::deserialize_from(&mut reader)?;
+                // Provers that predate worker names only send their address.
+                let worker: Option<String> = match reader.get_ref().has_remaining() {
+                    true => Some(bincode::deserialize_from(&mut reader)?),
+                    false => None,
+                };
+                if let Some(worker) = &worker {
+                    ensure!(worker.len() <= MAXIMUM_WORKER_NAME_LENGTH, "Invalid worker name in a 'PoolRegister'");
+                    ensure!(worker != DEFAULT_WORKER_NAME, "Reserved worker name in a 'PoolRegister'");
+                }
+
+                Self::PoolRegister(address, worker)
+            }
+            12 => {
+                let mut reader = bytes.reader();
+                let share_difficulty = bincode::deserialize_from(&mut reader)?;
+                Self::PoolRequest(share_difficulty, Data::Buffer(reader.into_inner().freeze()))
+            }
+            13 => {
+                let mut reader = bytes.reader();
+                let (address, nonce) = bincode::deserialize_from(&mut reader)?;
+                Self::PoolResponse(address, None, vec![(nonce, Data::Buffer(reader.into_inner().freeze()))])
+            }
+            14 | 15 => {
+                let mut reader = bytes.reader();
+                let (address, block_height, num_shares): (Address<N>, Option<u32>, u16) = match id {
+                    14 => {
+                        let (address, num_shares) = bincode::deserialize_from(&mut reader)?;
+                        (address, None, num_shares)
+                    }
+                    _ => {
+                        let (address, block_height, num_shares) = bincode::deserialize_from(&mut reader)?;
+                        (address, Some(block_height), num_shares)
+                    }
+                };
+                ensure!(
+                    (1..=MAXIMUM_SHARES_PER_POOL_RESPONSE).contains(&(num_shares as usize)),
+                    "Invalid number of shares in a 'PoolResponse'"
+                );
+
+                let mut shares = Vec::with_capacity(num_shares as usize);
+                for _ in 0..num_shares {
+                    let (nonce, proof_length): (N::PoSWNonce, u32) = bincode::deserialize_from(&mut reader)?;
+                    ensure!(reader.get_ref().remaining() >= proof_length as usize, "Missing a proof in a 'PoolResponse'");
+                    shares.push((nonce, Data::Buffer(reader.get_mut().split_to(proof_length as usize).freeze())));
+                }
+                ensure!(!reader.get_ref().has_remaining(), "Invalid 'PoolResponse' message");
+
+                Self::PoolResponse(address, block_height, shares)
+            }
             _ => bail!("Invalid message ID {}", id),
         };
 
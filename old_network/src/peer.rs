@@ -726,6 +726,65 @@ impl<N: Network> Peer<N> {
                                         Err(error) => warn!("[UnconfirmedTransaction] {}", error)
                                     }
                                 }
+                                Message::PoolRegister(_address, _worker) => {
+                                    if E::NODE_TYPE != NodeType::Validator {
+                                        trace!("Skipping 'PoolRegister' from {}", peer_ip);
+                                    } else {
+                                        // // Route the `PoolRegister` to the operator.
+                                        // let request = OperatorRequest::PoolRegister(peer_ip, _address, _worker);
+                                        // if let Err(error) = state.operator().router().send(request).await {
+                                        //     warn!("[PoolRegister] {}", error);
+                                        // }
+                                    }
+                                }
+                                Message::PoolRequest(_share_difficulty, block_template) => {
+                                    if E::NODE_TYPE != NodeType::Prover {
+                                        trace!("Skipping 'PoolRequest' from {}", peer_ip);
+                                    } else {
+                                        match block_template.deserialize().await {
+                                            Ok(_block_template) => {
+                                                // // Route the `PoolRequest` to the prover.
+                                                // let request = ProverRequest::PoolRequest(peer_ip, _share_difficulty, _block_template);
+                                                // if let Err(error) = state.prover().router().send(request).await {
+                                                //     warn!("[PoolRequest] {}", error);
+                                                // }
+                                            }
+                                            Err(error) => warn!("[PoolRequest] {}", error)
+                                        }
+                                    }
+                                }
+                                Message::PoolResponse(_address, _block_height, shares) => {
+                                    if E::NODE_TYPE != NodeType::Validator {
+                                        trace!("Skipping 'PoolResponse' from {}", peer_ip);
+                                    } else {
+                                        // // Ensure the peer is within its limit of in-flight operator requests.
+                                        // if !state.operator().reserve_request(peer_ip).await {
+                                        //     continue;
+                                        // }
+
+                                        let mut _shares = Vec::with_capacity(shares.len());
+                                        for (nonce, proof) in shares {
+                                            // // Skip the shares the operator would turn away, before deserializing their proofs.
+                                            // if !state.operator().precheck_share(&_address, &nonce).await {
+                                            //     continue;
+                                            // }
+                                            match proof.deserialize().await {
+                                                Ok(proof) => _shares.push((nonce, proof)),
+                                                Err(error) => warn!("[PoolResponse] {}", error),
+                                            }
+                                        }
+                                        // // Route the `PoolResponse` to the operator, which releases the request once processed.
+                                        // if _shares.is_empty() {
+                                        //     state.operator().release_request(peer_ip).await;
+                                        // } else {
+                                        //     let request = OperatorRequest::PoolResponse(peer_ip, _address, _block_height, _shares);
+                                        //     if let Err(error) = state.operator().router().send(request).await {
+                                        //         warn!("[PoolResponse] {}", error);
+                                        //         state.operator().release_request(peer_ip).await;
+                                        //     }
+                                        // }
+                                    }
+                                }
                             }
                         }
                         // An error occurred.
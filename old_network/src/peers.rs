@@ -135,6 +135,13 @@ impl<N: Network, E: Environment> Peers<N, E> {
         self.connected_peers.read().await.contains_key(&ip)
     }
 
+    ///
+    /// Returns the number of messages that wait to be sent to the given peer, if it is connected.
+    ///
+    pub async fn number_of_pending_messages(&self, ip: SocketAddr) -> Option<usize> {
+        self.connected_peers.read().await.get(&ip).map(|(_, outbound)| outbound.max_capacity() - outbound.capacity())
+    }
+
     ///
     /// Returns `true` if the given IP is restricted.
     ///
@@ -14,28 +14,49 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{LedgerRequest, PeersRequest, State};
+use crate::{
+    LedgerReader,
+    LedgerRequest,
+    PeersRequest,
+    State,
+    DEFAULT_WORKER_NAME,
+    MAXIMUM_SHARES_PER_POOL_RESPONSE,
+    MAXIMUM_WORKER_NAME_LENGTH,
+};
 use snarkos_environment::{
     helpers::{NodeType, Status},
-    network::{Data, Message},
+    network::{Data, DisconnectReason, Message},
     Environment,
 };
 use snarkos_storage::{
     storage::{rocksdb::RocksDB, ReadWrite},
+    ProverLifetimeStats,
     ProverState,
 };
 use snarkvm::dpc::{posw::PoSWProof, prelude::*};
 
-use anyhow::{anyhow, Result};
-use rand::thread_rng;
+#[cfg(any(feature = "test", feature = "prometheus"))]
+use snarkos_metrics as metrics;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use rand::{thread_rng, CryptoRng, Rng};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use serde::Serialize;
 use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
     net::SocketAddr,
     path::Path,
-    sync::{atomic::Ordering, Arc},
-    time::Duration,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
+        Arc,
+        Mutex,
+    },
+    time::{Duration, Instant},
 };
 use tokio::{
-    sync::{mpsc, oneshot, RwLock},
+    sync::{mpsc, oneshot, Notify, RwLock},
     task,
 };
 
@@ -46,6 +67,28 @@ pub type ProverHandler<N> = mpsc::Receiver<ProverRequest<N>>;
 
 /// The miner heartbeat in seconds.
 const MINER_HEARTBEAT_IN_SECONDS: Duration = Duration::from_secs(2);
+/// The duration in between checks of the connection to the current pool.
+const POOL_CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// The initial duration in between attempts to reconnect to the current pool, which doubles with each attempt.
+const MINIMUM_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+/// The duration in between samples of the number of proof attempts, from which the proof rate is computed.
+const PROOF_RATE_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// The windows over which the proof rate is reported: 1, 5, and 15 minutes.
+const PROOF_RATE_WINDOWS: [Duration; 3] = [Duration::from_secs(60), Duration::from_secs(300), Duration::from_secs(900)];
+/// The duration in between logs of the prover statistics.
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+/// The duration in between flushes of the prover statistics to storage.
+const STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+/// The duration in between prunes of the coinbase records of orphaned blocks.
+const COINBASE_PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+/// The maximum number of found shares that wait to be sent to the pool, beyond which the oldest share is discarded.
+const SHARE_QUEUE_CAPACITY: usize = 4;
+/// The duration in between checks of whether the previous messages to the pool have been sent.
+const SHARE_SUBMISSION_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+/// The longest sleep of a proving thread in between checks of whether its proof attempts have been aborted.
+const DUTY_CYCLE_SLEEP_SLICE: Duration = Duration::from_millis(100);
+/// The longest duration of the startup self-test of the prover, beyond which proving is deemed to be broken.
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(300);
 
 ///
 /// An enum of requests that the `Prover` struct processes.
@@ -56,8 +99,527 @@ pub enum ProverRequest<N: Network> {
     PoolRequest(SocketAddr, u64, BlockTemplate<N>),
     /// MemoryPoolClear := (block)
     MemoryPoolClear(Option<Block<N>>),
+    /// MemoryPoolRestore := (removed_blocks)
+    MemoryPoolRestore(Vec<Block<N>>),
     /// UnconfirmedTransaction := (peer_ip, transaction)
     UnconfirmedTransaction(SocketAddr, Transaction<N>),
+    /// SetThreads := (number_of_threads)
+    SetThreads(usize),
+    /// SetPaused := (is_paused)
+    SetPaused(bool),
+    /// SetMiningMode := (mining_mode, pool_ip)
+    SetMiningMode(MiningMode, Option<SocketAddr>),
+    /// SetIntensity := (intensity)
+    SetIntensity(u8),
+}
+
+///
+/// Whether the prover mines blocks on its own, or shares for a pool.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MiningMode {
+    /// The prover mines blocks on its own, and keeps their coinbase records.
+    Solo,
+    /// The prover mines shares for the block templates of its current pool.
+    Pool,
+}
+
+impl FromStr for MiningMode {
+    type Err = anyhow::Error;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "solo" => Ok(Self::Solo),
+            "pool" => Ok(Self::Pool),
+            _ => Err(anyhow!("The mining mode must be either 'solo' or 'pool', found '{}'", mode)),
+        }
+    }
+}
+
+impl fmt::Display for MiningMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Solo => write!(f, "solo"),
+            Self::Pool => write!(f, "pool"),
+        }
+    }
+}
+
+///
+/// The pool that the prover is currently mining for.
+///
+#[derive(Clone, Debug)]
+struct CurrentPool {
+    /// The index of the pool in the list of pools.
+    index: usize,
+    /// The timestamp at which the pool was selected.
+    selected_at: Instant,
+    /// The timestamp of the last `PoolRequest` from the pool, if one has been received.
+    last_pool_request: Option<Instant>,
+    /// The share difficulty assigned in the last `PoolRequest` from the pool, if one has been received.
+    share_difficulty: Option<u64>,
+}
+
+impl CurrentPool {
+    /// Selects the pool at the given index in the list of pools.
+    fn new(index: usize) -> Self {
+        Self { index, selected_at: Instant::now(), last_pool_request: None, share_difficulty: None }
+    }
+
+    /// Returns the duration since the pool was selected or last sent a `PoolRequest`, whichever is more recent.
+    fn idle_time(&self) -> Duration {
+        self.last_pool_request.unwrap_or(self.selected_at).elapsed()
+    }
+}
+
+///
+/// A duty cycle that limits the share of wall-clock time that each proving thread spends proving.
+///
+/// After each proof attempt, the proving thread sleeps for as long as keeps its time spent proving at the intensity.
+/// The sleep is taken in short slices, and ends early once the proof attempts are aborted for new work or a pause.
+///
+#[derive(Debug)]
+pub struct DutyCycle {
+    /// The percentage of wall-clock time to spend proving, from 1 to 100.
+    intensity: AtomicU8,
+    /// The total time spent proving in microseconds, summed over the proving threads.
+    proving_micros: AtomicU64,
+    /// The total time spent sleeping in microseconds, summed over the proving threads.
+    sleeping_micros: AtomicU64,
+}
+
+impl DutyCycle {
+    /// Initializes a new duty cycle with the given intensity.
+    pub fn new(intensity: u8) -> Result<Self> {
+        let duty_cycle = Self { intensity: AtomicU8::new(100), proving_micros: Default::default(), sleeping_micros: Default::default() };
+        duty_cycle.set_intensity(intensity)?;
+        Ok(duty_cycle)
+    }
+
+    /// Returns the percentage of wall-clock time to spend proving.
+    pub fn intensity(&self) -> u8 {
+        self.intensity.load(Ordering::SeqCst)
+    }
+
+    /// Sets the percentage of wall-clock time to spend proving, which takes effect from the next proof attempt.
+    pub fn set_intensity(&self, intensity: u8) -> Result<()> {
+        ensure!((1..=100).contains(&intensity), "The prover intensity must be between 1 and 100, found {}", intensity);
+        self.intensity.store(intensity, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns the percentage of time spent proving since the last reset, or the intensity if nothing has been proven.
+    pub fn effective_intensity(&self) -> f64 {
+        let proving_micros = self.proving_micros.load(Ordering::SeqCst);
+        let total_micros = proving_micros + self.sleeping_micros.load(Ordering::SeqCst);
+        match total_micros {
+            0 => self.intensity() as f64,
+            _ => proving_micros as f64 * 100.0 / total_micros as f64,
+        }
+    }
+
+    /// Resets the time spent proving and sleeping.
+    fn reset(&self) {
+        self.proving_micros.store(0, Ordering::SeqCst);
+        self.sleeping_micros.store(0, Ordering::SeqCst);
+    }
+
+    /// Returns the duration to sleep for after proving for the given duration, to keep to the intensity.
+    fn sleep_time(&self, proving_time: Duration) -> Duration {
+        let intensity = self.intensity() as u32;
+        proving_time * (100 - intensity) / intensity
+    }
+
+    ///
+    /// Records a proof attempt of the given duration, and sleeps for the rest of the duty cycle,
+    /// unless the proof attempts are aborted or terminated in the meantime.
+    ///
+    fn throttle<E: Environment>(&self, proving_time: Duration, abort_proof: &AtomicBool) {
+        self.proving_micros.fetch_add(proving_time.as_micros() as u64, Ordering::SeqCst);
+
+        let sleep_until = Instant::now() + self.sleep_time(proving_time);
+        let started_at = Instant::now();
+        while !abort_proof.load(Ordering::SeqCst) && !E::terminator().load(Ordering::SeqCst) {
+            let now = Instant::now();
+            if now >= sleep_until {
+                break;
+            }
+            std::thread::sleep((sleep_until - now).min(DUTY_CYCLE_SLEEP_SLICE));
+        }
+        self.sleeping_micros.fetch_add(started_at.elapsed().as_micros() as u64, Ordering::SeqCst);
+    }
+}
+
+///
+/// The time that a prover spends proving on stale block templates from its pool, whose block height is already
+/// in the ledger of the prover, and the delay in between receiving each block template and starting to mine it.
+///
+#[derive(Debug, Default)]
+struct StaleWork {
+    /// The time spent proving in microseconds.
+    proving_micros: AtomicU64,
+    /// The time spent proving on stale block templates in microseconds.
+    stale_proving_micros: AtomicU64,
+    /// The number of block templates that the prover started mining.
+    templates_started: AtomicU64,
+    /// The total delay in between receiving and starting to mine each block template in microseconds.
+    start_delay_micros: AtomicU64,
+}
+
+impl StaleWork {
+    /// Records that a block template was started, the given duration after it was received.
+    fn record_start(&self, start_delay: Duration) {
+        self.templates_started.fetch_add(1, Ordering::SeqCst);
+        self.start_delay_micros.fetch_add(start_delay.as_micros() as u64, Ordering::SeqCst);
+    }
+
+    /// Records a proof attempt of the given duration, on a stale block template or not.
+    fn record_attempt(&self, proving_time: Duration, is_stale: bool) {
+        let proving_micros = proving_time.as_micros() as u64;
+        self.proving_micros.fetch_add(proving_micros, Ordering::SeqCst);
+        if is_stale {
+            self.stale_proving_micros.fetch_add(proving_micros, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns the percentage of time spent proving on stale block templates since the last reset.
+    fn stale_percentage(&self) -> f64 {
+        match self.proving_micros.load(Ordering::SeqCst) {
+            0 => 0.0,
+            proving_micros => self.stale_proving_micros.load(Ordering::SeqCst) as f64 * 100.0 / proving_micros as f64,
+        }
+    }
+
+    /// Returns the mean delay in between receiving and starting to mine a block template since the last reset.
+    fn mean_start_delay(&self) -> Duration {
+        match self.templates_started.load(Ordering::SeqCst) {
+            0 => Duration::ZERO,
+            templates_started => Duration::from_micros(self.start_delay_micros.load(Ordering::SeqCst) / templates_started),
+        }
+    }
+
+    /// Resets the recorded proving time and start delays.
+    fn reset(&self) {
+        self.proving_micros.store(0, Ordering::SeqCst);
+        self.stale_proving_micros.store(0, Ordering::SeqCst);
+        self.templates_started.store(0, Ordering::SeqCst);
+        self.start_delay_micros.store(0, Ordering::SeqCst);
+    }
+}
+
+///
+/// A bounded queue of the shares found for the latest block template of the pool, which have yet to be sent.
+///
+#[derive(Debug)]
+struct ShareQueue<T: PartialEq, S> {
+    /// The latest block template, which the queued shares are for.
+    template: Option<T>,
+    /// The queued shares, oldest first.
+    shares: VecDeque<S>,
+    /// The maximum number of queued shares.
+    capacity: usize,
+}
+
+impl<T: PartialEq, S> ShareQueue<T, S> {
+    /// Initializes an empty queue, holding up to the given number of shares.
+    fn new(capacity: usize) -> Self {
+        Self { template: None, shares: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    ///
+    /// Sets the latest block template, and discards the queued shares if it has changed, as they are stale.
+    /// Returns the number of discarded shares.
+    ///
+    fn set_template(&mut self, template: T) -> usize {
+        if self.template.as_ref() == Some(&template) {
+            return 0;
+        }
+        self.template = Some(template);
+        self.clear()
+    }
+
+    ///
+    /// Queues a share for the given block template. If the block template is no longer the latest one, the share
+    /// is discarded, and otherwise the oldest share is discarded if the queue is full. Returns the number of discarded shares.
+    ///
+    fn push(&mut self, template: &T, share: S) -> usize {
+        if self.template.as_ref() != Some(template) {
+            return 1;
+        }
+        let num_discarded = match self.shares.len() >= self.capacity {
+            true => self.shares.pop_front().map_or(0, |_| 1),
+            false => 0,
+        };
+        self.shares.push_back(share);
+        num_discarded
+    }
+
+    /// Takes up to the given number of queued shares, oldest first, along with their block template.
+    fn take(&mut self, maximum: usize) -> Option<(&T, Vec<S>)> {
+        let num_shares = self.shares.len().min(maximum);
+        match (&self.template, num_shares) {
+            (Some(template), 1..) => Some((template, self.shares.drain(..num_shares).collect())),
+            _ => None,
+        }
+    }
+
+    /// Discards the queued shares, and returns the number of discarded shares.
+    fn clear(&mut self) -> usize {
+        let num_discarded = self.shares.len();
+        self.shares.clear();
+        num_discarded
+    }
+}
+
+///
+/// The kind of template that a solo miner mines a block on.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TemplateKind {
+    /// A template with only the coinbase transaction, which is mined first on a new tip to minimize latency.
+    Empty,
+    /// A template with the unconfirmed transactions of the memory pool.
+    Full,
+}
+
+///
+/// The conditions that a solo miner waits for before it mines, so that it does not mine on a stale ledger.
+///
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct ReadinessGate {
+    /// The number of blocks that the ledger may be behind the highest connected peer.
+    pub sync_tolerance: u32,
+    /// The minimum number of connected peers.
+    pub minimum_peers: usize,
+}
+
+///
+/// The readiness of a solo miner to mine, with the current values of the conditions of its readiness gate.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct MiningReadiness {
+    /// `true` if the node status is ready, the ledger is synced, and enough peers are connected.
+    pub is_ready: bool,
+    /// The status of the node, which must be `Ready`, or `Mining` while a block is being mined.
+    pub status: Status,
+    /// The latest block height of the ledger.
+    pub latest_block_height: u32,
+    /// The highest latest block height of the connected peers, if any peer has sent its ledger state.
+    pub maximum_block_height_of_peers: Option<u32>,
+    /// The number of connected peers.
+    pub connected_peers: usize,
+    /// The conditions that the miner waits for.
+    pub gate: ReadinessGate,
+}
+
+///
+/// The statistics of a prover that are flushed to storage in batches, rather than on every proof attempt.
+///
+#[derive(Debug, Default)]
+struct PersistedStats {
+    /// The proving statistics as of the last flush, from which the unflushed statistics are counted.
+    flushed: ProverLifetimeStats,
+    /// The statistics since the prover started, as of the last flush.
+    since_restart: ProverLifetimeStats,
+    /// The lifetime statistics of the prover, as of the last flush.
+    lifetime: ProverLifetimeStats,
+    /// The lifetime number of shares sent to each pool, including the shares that have yet to be flushed.
+    lifetime_pool_shares: HashMap<SocketAddr, u64>,
+    /// The number of shares sent to each pool since the last flush.
+    unflushed_pool_shares: HashMap<SocketAddr, u64>,
+}
+
+///
+/// The proving statistics of a prover, since it started mining for its current pool.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(bound = "")]
+pub struct ProvingStats<N: Network> {
+    /// Whether the prover mines blocks on its own, or shares for a pool.
+    pub mode: MiningMode,
+    /// The IP address of the pool that the prover is currently mining for, if it mines for a pool.
+    pub pool: Option<SocketAddr>,
+    /// The address that the current work pays: the prover address in pool mode, or the coinbase recipient of the
+    /// block being mined in solo mode, if any.
+    pub recipient: Option<Address<N>>,
+    /// The number of completed proof attempts, whether or not they met the share difficulty.
+    pub proof_attempts: u64,
+    /// The proofs per second over the last 1, 5, and 15 minutes.
+    pub proof_rates: [f64; 3],
+    /// The number of shares sent to the pool. The pool does not acknowledge shares, so some may have been rejected.
+    pub submitted_shares: u64,
+    /// The number of shares that could not be sent to the pool.
+    pub failed_submissions: u64,
+    /// The number of shares that were not sent, for failing to meet the share difficulty most recently assigned by the pool.
+    pub dropped_shares: u64,
+    /// The number of shares that were not sent, as their block template was replaced, or the share queue was full.
+    pub stale_shares: u64,
+    /// The number of block templates from the pool that were ignored, for duplicating the latest block template.
+    pub duplicate_templates: u64,
+    /// The number of block templates from the pool that were rejected, for being below the latest block template.
+    pub regressive_templates: u64,
+    /// The number of unconfirmed transactions that were dropped from the memory pool, for failing verification before being mined.
+    pub dropped_transactions: u64,
+    /// The percentage of wall-clock time that the prover is set to spend proving.
+    pub intensity: u8,
+    /// The percentage of wall-clock time that the prover has spent proving.
+    pub effective_intensity: f64,
+    /// The percentage of proving time spent on block templates for a block height that the ledger of the prover already has.
+    pub stale_work_percentage: f64,
+    /// The mean delay in between receiving a block template from the pool and starting to mine it, in milliseconds.
+    pub mean_template_start_delay_ms: f64,
+    /// The number of blocks found in solo mode.
+    pub blocks_found: u64,
+    /// The number of blocks found on an empty template in solo mode, when mining empty templates first.
+    pub empty_template_blocks: u64,
+    /// The number of blocks found on a full template in solo mode.
+    pub full_template_blocks: u64,
+    /// The readiness of the miner to mine in solo mode, if it waits for its readiness gate.
+    pub readiness: Option<MiningReadiness>,
+    /// The statistics since the prover started, across all pools and mining modes.
+    pub since_restart: ProverLifetimeStats,
+    /// The statistics since the prover was first started, which persist across restarts.
+    pub lifetime: ProverLifetimeStats,
+    /// The lifetime number of shares sent to each pool.
+    pub lifetime_pool_shares: HashMap<SocketAddr, u64>,
+    /// The result of the startup self-test, if it was run.
+    pub self_test: Option<SelfTestReport>,
+}
+
+///
+/// The results of a benchmark of the proving loop of the prover.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchmarkReport {
+    /// The name of the proving backend.
+    pub backend: String,
+    /// The duration of the benchmark in seconds.
+    pub duration_secs: f64,
+    /// The number of threads of the proving pool.
+    pub threads: usize,
+    /// The number of independent proof attempts that ran concurrently.
+    pub parallel_proofs: usize,
+    /// The number of completed proof attempts.
+    pub proofs: u64,
+    /// The number of completed proof attempts per second.
+    pub proofs_per_second: f64,
+    /// The mean duration of a proof attempt in milliseconds.
+    pub mean_latency_ms: f64,
+    /// The median duration of a proof attempt in milliseconds.
+    pub median_latency_ms: f64,
+    /// The peak resident memory of the process in bytes, if the platform reports it.
+    pub peak_memory_bytes: Option<u64>,
+}
+
+///
+/// The result of the startup self-test of the prover, which proves and verifies a share on a synthetic block template.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct SelfTestReport {
+    /// The name of the proving backend.
+    pub backend: String,
+    /// `true` if the backend produced a proof that passed verification.
+    pub passed: bool,
+    /// The duration of the self-test in milliseconds.
+    pub duration_ms: f64,
+    /// The reason the self-test failed, if it failed.
+    pub error: Option<String>,
+}
+
+///
+/// The status of a coinbase record of the prover.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoinbaseStatus {
+    /// The block of the record is on the canonical chain.
+    Canon,
+    /// The canonical block at the height of the record does not contain it.
+    Orphaned,
+    /// The ledger has yet to reach the height of the record.
+    Pending,
+}
+
+///
+/// A coinbase record of the prover, and its status.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(bound = "")]
+pub struct CoinbaseRecordInfo<N: Network> {
+    /// The height of the block that the record was mined in.
+    pub block_height: u32,
+    /// The commitment of the record.
+    pub commitment: N::Commitment,
+    /// The owner of the record, which is the coinbase recipient that the block was mined for.
+    pub owner: Address<N>,
+    /// The value of the record.
+    pub value: i64,
+    /// The status of the record.
+    pub status: CoinbaseStatus,
+}
+
+///
+/// The configuration of a prover.
+///
+#[derive(Clone)]
+pub struct ProverConfig<N: Network> {
+    /// The IP addresses of the pools that the prover mines for, in order of preference, or none to mine in solo mode.
+    pub pools: Vec<SocketAddr>,
+    /// The duration without a `PoolRequest` from the current pool after which the prover fails over to the next pool.
+    pub pool_timeout: Duration,
+    /// The duration after failing over from the primary pool after which the prover returns to it.
+    pub primary_pool_retry: Duration,
+    /// The maximum duration in between attempts to reconnect to the current pool.
+    pub maximum_reconnect_backoff: Duration,
+    /// The number of blocks after which a coinbase record that is not on the canonical chain is pruned.
+    pub coinbase_confirmations: u32,
+    /// The worker name that the prover registers with the pool, if one is set.
+    pub worker: Option<String>,
+    /// The addresses that the miner rotates the coinbase of each block through in solo mode, or the prover's address if empty.
+    pub miner_recipients: Vec<Address<N>>,
+    /// If `true`, the unconfirmed transactions are verified against the canonical chain before they are mined.
+    pub verify_transactions: bool,
+    /// The conditions that the miner waits for before it mines in solo mode, if any.
+    pub readiness_gate: Option<ReadinessGate>,
+    /// If `true`, the miner mines an empty template first on each new tip, before the full template.
+    pub mine_empty_first: bool,
+    /// The number of threads of the proving pool, or `None` for the default.
+    pub threads: Option<usize>,
+    /// The number of independent proof attempts that run concurrently on the proving pool.
+    pub parallel_proofs: usize,
+    /// The percentage of wall-clock time spent proving.
+    pub intensity: u8,
+    /// The backend that runs the proof attempts.
+    pub backend: Arc<dyn ProvingBackend<N>>,
+    /// If `true`, the backend is checked to produce valid proofs before the prover starts mining with it.
+    pub self_test: bool,
+    /// The percentage of proving time on stale block templates above which the prover logs a warning.
+    pub stale_work_threshold: f64,
+}
+
+impl<N: Network> Default for ProverConfig<N> {
+    /// Returns the default configuration of a prover, which matches the defaults of the command line.
+    fn default() -> Self {
+        Self {
+            pools: Vec::new(),
+            pool_timeout: Duration::from_secs(60),
+            primary_pool_retry: Duration::from_secs(600),
+            maximum_reconnect_backoff: Duration::from_secs(60),
+            coinbase_confirmations: 100,
+            worker: None,
+            miner_recipients: Vec::new(),
+            verify_transactions: true,
+            readiness_gate: None,
+            mine_empty_first: false,
+            threads: None,
+            parallel_proofs: 1,
+            intensity: 100,
+            backend: Arc::new(CpuBackend),
+            self_test: true,
+            stale_work_threshold: 10.0,
+        }
+    }
 }
 
 ///
@@ -66,8 +628,93 @@ pub enum ProverRequest<N: Network> {
 pub struct Prover<N: Network, E: Environment> {
     /// The state storage of the prover.
     prover_state: Arc<ProverState<N, ReadWrite>>,
-    /// The IP address of the connected pool.
-    pool: Option<SocketAddr>,
+    /// The IP addresses of the pools that the prover mines for, in order of preference.
+    pools: RwLock<Vec<SocketAddr>>,
+    /// Whether the prover currently mines blocks on its own, or shares for a pool.
+    mining_mode: RwLock<MiningMode>,
+    /// The pool that the prover is currently registered with.
+    current_pool: RwLock<CurrentPool>,
+    /// The duration without a `PoolRequest` from the current pool after which the prover fails over to the next pool.
+    pool_timeout: Duration,
+    /// The duration after failing over from the primary pool after which the prover returns to it.
+    primary_pool_retry: Duration,
+    /// The maximum duration in between attempts to reconnect to the current pool.
+    maximum_reconnect_backoff: Duration,
+    /// The number of blocks after which a coinbase record that is not on the canonical chain is pruned.
+    coinbase_confirmations: u32,
+    /// The worker name that the prover registers with the pool, if one is set.
+    worker: Option<String>,
+    /// The addresses that the miner rotates the coinbase of each block through, in solo mode.
+    miner_recipients: Vec<Address<N>>,
+    /// The index of the next coinbase recipient, in solo mode.
+    next_miner_recipient: AtomicUsize,
+    /// The coinbase recipient of the block being mined, in solo mode.
+    current_miner_recipient: RwLock<Option<Address<N>>>,
+    /// A flag indicating that the unconfirmed transactions are verified against the canonical chain before they are mined.
+    verify_transactions: bool,
+    /// The number of unconfirmed transactions that were dropped from the memory pool, for failing verification before being mined.
+    dropped_transactions: AtomicU64,
+    /// The conditions that the miner waits for before it mines in solo mode, if any.
+    readiness_gate: Option<ReadinessGate>,
+    /// A flag indicating that the miner is waiting for its readiness gate, so that the wait is only logged once.
+    is_awaiting_readiness: AtomicBool,
+    /// A flag indicating that the miner mines an empty template first on each new tip, before the full template.
+    mine_empty_first: bool,
+    /// The tip that the miner last mined on in solo mode, and the kind of template it mined.
+    solo_template: RwLock<Option<(N::BlockHash, TemplateKind)>>,
+    /// The unconfirmed transactions of the full template for a tip, once they are prepared.
+    full_template: RwLock<Option<(N::BlockHash, Vec<Transaction<N>>)>>,
+    /// The number of blocks found on an empty template in solo mode.
+    empty_template_blocks: AtomicU64,
+    /// The number of blocks found on a full template in solo mode.
+    full_template_blocks: AtomicU64,
+    /// The thread pool dedicated to PoSW proving, separate from the thread pool of the node. It may be resized at runtime.
+    proving_pool: RwLock<Arc<ThreadPool>>,
+    /// The number of independent proof attempts that run concurrently on the proving pool.
+    parallel_proofs: usize,
+    /// The backend that runs the proof attempts.
+    backend: Arc<dyn ProvingBackend<N>>,
+    /// The result of the startup self-test, if it was run.
+    self_test: Option<SelfTestReport>,
+    /// The duty cycle that limits the share of wall-clock time spent proving. It may be adjusted at runtime.
+    duty_cycle: Arc<DutyCycle>,
+    /// The latest work from the pool that has yet to be started: the pool IP address, share difficulty, block template,
+    /// and the time at which it was received.
+    pool_work: RwLock<Option<(SocketAddr, u64, BlockTemplate<N>, Instant)>>,
+    /// The time spent proving on stale block templates from the pool, and the delay in starting each block template.
+    stale_work: Arc<StaleWork>,
+    /// The percentage of proving time on stale block templates above which the prover logs a warning.
+    stale_work_threshold: f64,
+    /// A flag that aborts the proof in progress, which is set when new work arrives from the pool.
+    abort_proof: Arc<AtomicBool>,
+    /// A flag indicating that the prover has been paused, and does not start any proofs until it is resumed.
+    is_paused: AtomicBool,
+    /// The number of completed proof attempts since the prover started mining for its current pool.
+    proof_attempts: Arc<AtomicU64>,
+    /// The samples of the number of proof attempts, taken at the proof rate sample interval, within the longest proof rate window.
+    proof_attempt_samples: RwLock<VecDeque<(Instant, u64)>>,
+    /// The number of shares sent to the current pool.
+    submitted_shares: AtomicU64,
+    /// The number of shares that could not be sent to the current pool.
+    failed_submissions: AtomicU64,
+    /// The number of shares that were not sent, for failing to meet the share difficulty most recently assigned by the pool.
+    dropped_shares: AtomicU64,
+    /// The latest block template from the pool, along with the pool IP address and its header root.
+    latest_template: RwLock<Option<(SocketAddr, N::BlockHeaderRoot, BlockTemplate<N>)>>,
+    /// The number of block templates from the pool that were ignored, for duplicating the latest block template.
+    duplicate_templates: AtomicU64,
+    /// The number of block templates from the pool that were rejected, for being below the latest block template.
+    regressive_templates: AtomicU64,
+    /// The shares that wait to be sent to the pool, for the latest block template from the pool.
+    share_queue: RwLock<ShareQueue<(SocketAddr, BlockTemplate<N>), (N::PoSWNonce, PoSWProof<N>)>>,
+    /// A notification for the share submission task, which is sent once a share has been queued.
+    share_queued: Notify,
+    /// The number of shares that were not sent, as their block template was replaced, or the share queue was full.
+    stale_shares: AtomicU64,
+    /// The number of blocks found in solo mode.
+    blocks_found: AtomicU64,
+    /// The statistics that are flushed to storage, so that they persist across restarts.
+    persisted_stats: Mutex<PersistedStats>,
     /// The prover router of the node.
     prover_router: ProverRouter<N>,
     /// The pool of unconfirmed transactions.
@@ -77,17 +724,127 @@ pub struct Prover<N: Network, E: Environment> {
 }
 
 impl<N: Network, E: Environment> Prover<N, E> {
-    /// Initializes a new instance of the prover, paired with its handler.
+    /// Initializes a new instance of the prover with the given configuration, paired with its handler.
     pub async fn open<P: AsRef<Path> + Copy>(
         path: P,
+        config: ProverConfig<N>,
         state: Arc<State<N, E>>,
     ) -> Result<(Self, mpsc::Receiver<ProverRequest<N>>)> {
+        let ProverConfig {
+            pools,
+            pool_timeout,
+            primary_pool_retry,
+            maximum_reconnect_backoff,
+            coinbase_confirmations,
+            worker,
+            miner_recipients,
+            verify_transactions,
+            readiness_gate,
+            mine_empty_first,
+            threads,
+            parallel_proofs,
+            intensity,
+            backend,
+            self_test,
+            stale_work_threshold,
+        } = config;
+
+        ensure!(!pool_timeout.is_zero(), "The pool timeout must be greater than 0");
+        ensure!(
+            (0.0..=100.0).contains(&stale_work_threshold),
+            "The stale work threshold must be between 0 and 100, found {}",
+            stale_work_threshold
+        );
+        ensure!(parallel_proofs > 0, "The number of parallel proofs must be greater than 0");
+        // Ensure the worker name fits in a `PoolRegister` message, and does not collide with the default worker.
+        if let Some(worker) = &worker {
+            ensure!(
+                worker.len() <= MAXIMUM_WORKER_NAME_LENGTH,
+                "The worker name must be at most {} bytes",
+                MAXIMUM_WORKER_NAME_LENGTH
+            );
+            ensure!(worker != DEFAULT_WORKER_NAME, "The worker name '{}' is reserved for unnamed workers", DEFAULT_WORKER_NAME);
+        }
+
+        // Ensure the proving backend produces valid proofs, before the prover starts mining with it.
+        let proving_pool = Arc::new(build_proving_pool(threads)?);
+        let self_test = match self_test {
+            true => {
+                let (backend, proving_pool) = (backend.clone(), proving_pool.clone());
+                let report = task::spawn_blocking(move || run_self_test::<N, E>(&*backend, &proving_pool)).await?;
+                match &report.error {
+                    None => info!("Prover passed its self-test on the {} backend in {:.1} ms", report.backend, report.duration_ms),
+                    Some(error) => bail!("Prover failed its self-test on the {} backend - {}", report.backend, error),
+                }
+                Some(report)
+            }
+            false => None,
+        };
+
+        // Initialize the statistics of the prover from storage.
+        let prover_state = ProverState::open::<RocksDB, P>(path)?;
+        let persisted_stats = PersistedStats {
+            lifetime: prover_state.get_lifetime_stats()?,
+            lifetime_pool_shares: prover_state.to_pool_shares().into_iter().collect(),
+            ..Default::default()
+        };
+
         // Initialize an mpsc channel for sending requests to the `Prover` struct.
         let (prover_router, prover_handler) = mpsc::channel(1024);
         // Initialize the prover.
         let prover = Self {
-            prover_state: Arc::new(ProverState::open::<RocksDB, P>(path)?),
-            pool: pool_ip,
+            prover_state: Arc::new(prover_state),
+            mining_mode: RwLock::new(if pools.is_empty() { MiningMode::Solo } else { MiningMode::Pool }),
+            pools: RwLock::new(pools),
+            current_pool: RwLock::new(CurrentPool::new(0)),
+            pool_timeout,
+            primary_pool_retry,
+            maximum_reconnect_backoff: maximum_reconnect_backoff.max(MINIMUM_RECONNECT_BACKOFF),
+            coinbase_confirmations,
+            worker,
+            // Without a list of recipients, the coinbase of every block is sent to the prover's address.
+            miner_recipients: match miner_recipients.is_empty() {
+                true => state.address.into_iter().collect(),
+                false => miner_recipients,
+            },
+            next_miner_recipient: Default::default(),
+            current_miner_recipient: Default::default(),
+            verify_transactions,
+            dropped_transactions: Default::default(),
+            readiness_gate,
+            is_awaiting_readiness: Default::default(),
+            mine_empty_first,
+            solo_template: Default::default(),
+            full_template: Default::default(),
+            empty_template_blocks: Default::default(),
+            full_template_blocks: Default::default(),
+            proving_pool: RwLock::new(proving_pool),
+            // Run no more proof attempts concurrently than the backend supports.
+            parallel_proofs: match backend.capabilities().max_parallel_proofs {
+                Some(max_parallel_proofs) => parallel_proofs.min(max_parallel_proofs.max(1)),
+                None => parallel_proofs,
+            },
+            backend,
+            self_test,
+            duty_cycle: Arc::new(DutyCycle::new(intensity)?),
+            pool_work: Default::default(),
+            stale_work: Default::default(),
+            stale_work_threshold,
+            abort_proof: Default::default(),
+            is_paused: Default::default(),
+            proof_attempts: Default::default(),
+            proof_attempt_samples: Default::default(),
+            submitted_shares: Default::default(),
+            failed_submissions: Default::default(),
+            dropped_shares: Default::default(),
+            latest_template: Default::default(),
+            duplicate_templates: Default::default(),
+            regressive_templates: Default::default(),
+            share_queue: RwLock::new(ShareQueue::new(SHARE_QUEUE_CAPACITY)),
+            share_queued: Default::default(),
+            stale_shares: Default::default(),
+            blocks_found: Default::default(),
+            persisted_stats: Mutex::new(persisted_stats),
             prover_router,
             memory_pool: Arc::new(RwLock::new(MemoryPool::new())),
             state,
@@ -97,15 +854,49 @@ impl<N: Network, E: Environment> Prover<N, E> {
     }
 
     pub async fn initialize_miner(&self) {
-        // Initialize the miner, if the node type is a miner.
-        if E::NODE_TYPE == NodeType::Miner && self.pool.is_none() {
+        // Initialize the miner, if the node type is a miner or a prover, as either may switch to solo mining at runtime.
+        if E::NODE_TYPE == NodeType::Miner || E::NODE_TYPE == NodeType::Prover {
+            self.initialize_stats().await;
+            self.initialize_coinbase_pruning().await;
             self.state.prover().start_miner().await;
         }
     }
 
+    ///
+    /// Initializes a task that prunes the coinbase records of orphaned blocks, once they are beyond the coinbase confirmations.
+    ///
+    async fn initialize_coinbase_pruning(&self) {
+        let state = self.state.clone();
+        let (router, handler) = oneshot::channel();
+        E::resources().register_task(
+            None, // No need to provide an id, as the task will run indefinitely.
+            task::spawn(async move {
+                // Notify the outer function that the task is ready.
+                let _ = router.send(());
+                loop {
+                    tokio::time::sleep(COINBASE_PRUNE_INTERVAL).await;
+
+                    let prover = state.prover();
+                    let latest_block_height = state.ledger().reader().latest_block_height();
+                    let block_height = latest_block_height.saturating_sub(prover.coinbase_confirmations);
+                    match prover.prover_state.prune_orphaned_coinbase_records(block_height, |block_height, record| {
+                        Ok(prover.get_coinbase_status(block_height, record)? == CoinbaseStatus::Canon)
+                    }) {
+                        Ok(0) => {}
+                        Ok(num_pruned) => info!("Pruned {} coinbase records of orphaned blocks below block {}", num_pruned, block_height),
+                        Err(error) => warn!("Failed to prune the coinbase records - {}", error),
+                    }
+                }
+            }),
+        );
+
+        // Wait until the prune task is ready.
+        let _ = handler.await;
+    }
+
     pub async fn initialize_pooling(&self) {
-        // Initialize the prover, if the node type is a prover.
-        if E::NODE_TYPE == NodeType::Prover && self.pool.is_some() {
+        // Initialize the prover, if the node type is a miner or a prover, as either may switch to pool mining at runtime.
+        if E::NODE_TYPE == NodeType::Miner || E::NODE_TYPE == NodeType::Prover {
             let state = self.state.clone();
             let (router, handler) = oneshot::channel();
             E::resources().register_task(
@@ -117,6 +908,14 @@ impl<N: Network, E: Environment> Prover<N, E> {
                         // Sleep for `1` second.
                         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
 
+                        // A paused or solo prover neither registers for work, nor fails over for a lack of it.
+                        if state.prover().is_paused() || state.prover().mining_mode().await != MiningMode::Pool {
+                            continue;
+                        }
+
+                        // Fail over to the next pool if the current pool has stopped sending work.
+                        state.prover().check_pool_failover().await;
+
                         // TODO (howardwu): Check that the prover is connected to the pool before proceeding.
                         //  Currently we use a sleep function to probabilistically ensure the peer is connected.
                         if !E::terminator().load(Ordering::SeqCst) && !E::status().is_peering() && !E::status().is_mining() {
@@ -128,32 +927,205 @@ impl<N: Network, E: Environment> Prover<N, E> {
 
             // Wait until the operator handler is ready.
             let _ = handler.await;
+
+            self.initialize_share_submission().await;
         }
     }
 
-    pub async fn initialize_pool_connection_loop(&self, pool_ip: Option<SocketAddr>) {
-        // TODO (howardwu): This is a hack for the prover.
-        // Check that the prover is connected to the pool before sending a PoolRegister message.
-        if let Some(pool_ip) = pool_ip {
-            let peers_router = self.state.peers().router().clone();
+    ///
+    /// Initializes a task that sends the queued shares to the pool. The task waits for the previous messages
+    /// to the pool to be sent before taking the next shares, so that shares wait in the bounded share queue,
+    /// where they are discarded once stale, rather than in the outbound channel to the pool.
+    ///
+    async fn initialize_share_submission(&self) {
+        let state = self.state.clone();
+        let (router, handler) = oneshot::channel();
+        E::resources().register_task(
+            None, // No need to provide an id, as the task will run indefinitely.
+            task::spawn(async move {
+                // Notify the outer function that the task is ready.
+                let _ = router.send(());
+                // Only a prover with an address finds any shares to send.
+                let recipient = match state.address {
+                    Some(recipient) => recipient,
+                    None => return,
+                };
+                loop {
+                    let prover = state.prover();
+                    prover.share_queued.notified().await;
+
+                    loop {
+                        // Wait until the previous messages to the pool have been sent.
+                        let pool_ip = prover.share_queue.read().await.template.as_ref().map(|(pool_ip, _)| *pool_ip);
+                        if let Some(pool_ip) = pool_ip {
+                            while state.peers().number_of_pending_messages(pool_ip).await.unwrap_or(0) > 0 {
+                                tokio::time::sleep(SHARE_SUBMISSION_CHECK_INTERVAL).await;
+                            }
+                        }
+
+                        // Take the queued shares, which may have been discarded for a new block template in the meantime.
+                        let (operator_ip, block_height, shares) = {
+                            let mut share_queue = prover.share_queue.write().await;
+                            match share_queue.take(MAXIMUM_SHARES_PER_POOL_RESPONSE) {
+                                Some(((operator_ip, block_template), shares)) => (*operator_ip, block_template.block_height(), shares),
+                                None => break,
+                            }
+                        };
+
+                        // Send a `PoolResponse` to the operator that sent the block template, even if the prover has
+                        // since failed over, as the shares are only valid for their template.
+                        let num_shares = shares.len() as u64;
+                        let shares = shares.into_iter().map(|(nonce, proof)| (nonce, Data::Object(proof))).collect();
+                        let message = Message::PoolResponse(recipient, Some(block_height), shares);
+                        match state.peers().router().send(PeersRequest::MessageSend(operator_ip, message)).await {
+                            Ok(()) => {
+                                let mut persisted_stats = prover.persisted_stats.lock().unwrap();
+                                *persisted_stats.unflushed_pool_shares.entry(operator_ip).or_default() += num_shares;
+                                *persisted_stats.lifetime_pool_shares.entry(operator_ip).or_default() += num_shares;
+                                prover.submitted_shares.fetch_add(num_shares, Ordering::SeqCst)
+                            }
+                            Err(error) => {
+                                warn!("[PoolResponse] {}", error);
+                                prover.failed_submissions.fetch_add(num_shares, Ordering::SeqCst)
+                            }
+                        };
+                    }
+                }
+            }),
+        );
+
+        // Wait until the share submission task is ready.
+        let _ = handler.await;
+    }
+
+    ///
+    /// Initializes a task that samples the number of proof attempts, and logs the prover statistics every minute.
+    ///
+    async fn initialize_stats(&self) {
+        let state = self.state.clone();
+        let (router, handler) = oneshot::channel();
+        E::resources().register_task(
+            None, // No need to provide an id, as the task will run indefinitely.
+            task::spawn(async move {
+                // Notify the outer function that the task is ready.
+                let _ = router.send(());
+                let mut last_stats_log = Instant::now();
+                let mut last_stats_flush = Instant::now();
+                loop {
+                    tokio::time::sleep(PROOF_RATE_SAMPLE_INTERVAL).await;
+
+                    let prover = state.prover();
+                    {
+                        let mut samples = prover.proof_attempt_samples.write().await;
+                        samples.push_back((Instant::now(), prover.proof_attempts.load(Ordering::SeqCst)));
+                        // Forget the samples that have fallen out of the longest proof rate window.
+                        let longest_window = PROOF_RATE_WINDOWS[PROOF_RATE_WINDOWS.len() - 1];
+                        while samples.front().map_or(false, |(sampled_at, _)| sampled_at.elapsed() > longest_window) {
+                            samples.pop_front();
+                        }
+                    }
+
+                    if last_stats_log.elapsed() >= STATS_LOG_INTERVAL {
+                        let stats = prover.get_stats().await;
+                        info!(
+                            "Prover computed {:.2}/{:.2}/{:.2} proofs/s over 1/5/15 minutes, submitted {} shares \
+                             ({} failed, {} dropped, {} stale)",
+                            stats.proof_rates[0],
+                            stats.proof_rates[1],
+                            stats.proof_rates[2],
+                            stats.submitted_shares,
+                            stats.failed_submissions,
+                            stats.dropped_shares,
+                            stats.stale_shares
+                        );
+                        if stats.stale_work_percentage > prover.stale_work_threshold {
+                            warn!(
+                                "Prover spent {:.1}% of its proving time on stale block templates, with a mean delay of {:.0} ms \
+                                 in starting each template. Please check the connection to the pool",
+                                stats.stale_work_percentage,
+                                stats.mean_template_start_delay_ms
+                            );
+                        }
+                        last_stats_log = Instant::now();
+                    }
+
+                    if last_stats_flush.elapsed() >= STATS_FLUSH_INTERVAL {
+                        prover.flush_stats();
+                        last_stats_flush = Instant::now();
+                    }
+                }
+            }),
+        );
+
+        // Wait until the stats task is ready.
+        let _ = handler.await;
+    }
+
+    ///
+    /// Initializes a task that keeps the prover connected to its current pool. Once the connection drops,
+    /// the prover stops mining the work from the pool, and reconnects with an exponential backoff.
+    /// Upon reconnecting, the prover registers with the pool right away, to receive its current work.
+    ///
+    pub async fn initialize_pool_connection_loop(&self) {
+        if E::NODE_TYPE == NodeType::Miner || E::NODE_TYPE == NodeType::Prover {
+            let state = self.state.clone();
             let (router, handler) = oneshot::channel();
             E::resources().register_task(
                 None, // No need to provide an id, as the task will run indefinitely.
                 task::spawn(async move {
                     // Notify the outer function that the task is ready.
                     let _ = router.send(());
+
+                    // Initialize the connection state for the current pool.
+                    let mut connected_pool = None;
+                    let mut is_connected = false;
+                    let mut backoff = MINIMUM_RECONNECT_BACKOFF;
+                    let mut next_attempt = Instant::now();
                     loop {
-                        // Initialize the connection process.
-                        let (router, handler) = oneshot::channel();
-                        // Route a `Connect` request to the pool.
-                        if let Err(error) = peers_router.send(PeersRequest::Connect(pool_ip, router)).await {
-                            trace!("[Connect] {}", error);
+                        let prover = state.prover();
+                        // Retrieve the pool that the prover is currently mining for, which changes upon a failover.
+                        // Forget the connection state in solo mode, so that the prover registers upon returning to pool mode.
+                        let current_pool = prover.current_pool().await;
+                        if current_pool.is_none() {
+                            connected_pool = None;
+                        }
+                        if let Some(pool_ip) = current_pool {
+                            // Reset the connection state upon a failover.
+                            if connected_pool != Some(pool_ip) {
+                                connected_pool = Some(pool_ip);
+                                is_connected = false;
+                                backoff = MINIMUM_RECONNECT_BACKOFF;
+                                next_attempt = Instant::now();
+                            }
+
+                            if state.peers().is_connected_to(pool_ip).await {
+                                // Reset the backoff upon reconnecting, and register for the current work of the pool.
+                                if !is_connected {
+                                    info!("Connected to pool {}", pool_ip);
+                                    is_connected = true;
+                                    backoff = MINIMUM_RECONNECT_BACKOFF;
+                                    if !prover.is_paused() {
+                                        prover.send_pool_register().await;
+                                    }
+                                }
+                            } else {
+                                // Stop mining the work from the pool, as it would not receive the shares.
+                                if is_connected {
+                                    warn!("Lost the connection to pool {}", pool_ip);
+                                    is_connected = false;
+                                    prover.discard_pool_work().await;
+                                }
+
+                                if Instant::now() >= next_attempt {
+                                    debug!("Connecting to pool {} (next attempt in {} seconds)", pool_ip, backoff.as_secs());
+                                    prover.connect_to_pool(pool_ip).await;
+                                    next_attempt = Instant::now() + backoff;
+                                    backoff = (backoff * 2).min(prover.maximum_reconnect_backoff);
+                                }
+                            }
                         }
-                        // Wait until the connection task is initialized.
-                        let _ = handler.await;
 
-                        // Sleep for `30` seconds.
-                        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                        tokio::time::sleep(POOL_CONNECTION_CHECK_INTERVAL).await;
                     }
                 }),
             );
@@ -179,116 +1151,465 @@ impl<N: Network, E: Environment> Prover<N, E> {
     }
 
     ///
-    /// Performs the given `request` to the prover.
-    /// All requests must go through this `update`, so that a unified view is preserved.
+    /// Returns the coinbase records in storage, in order of block height, with the status of each.
+    /// A record whose status cannot be determined is omitted.
     ///
-    pub(super) async fn update(&self, request: ProverRequest<N>) {
-        match request {
-            ProverRequest::PoolRequest(operator_ip, share_difficulty, block_template) => {
-                // Process the pool request message.
-                self.process_pool_request(operator_ip, share_difficulty, block_template).await;
-            }
-            ProverRequest::MemoryPoolClear(block) => match block {
-                Some(block) => self.memory_pool.write().await.remove_transactions(block.transactions()),
-                None => *self.memory_pool.write().await = MemoryPool::new(),
-            },
-            ProverRequest::UnconfirmedTransaction(peer_ip, transaction) => {
-                // Ensure the node is not peering.
-                if !E::status().is_peering() {
-                    // Process the unconfirmed transaction.
-                    self.add_unconfirmed_transaction(peer_ip, transaction).await
+    pub fn get_coinbase_records(&self) -> Vec<CoinbaseRecordInfo<N>> {
+        let mut records: Vec<_> = self
+            .prover_state
+            .to_coinbase_records()
+            .into_iter()
+            .filter_map(|(block_height, record)| match self.get_coinbase_status(block_height, &record) {
+                Ok(status) => Some(CoinbaseRecordInfo {
+                    block_height,
+                    commitment: record.commitment(),
+                    owner: record.owner(),
+                    value: record.value().0,
+                    status,
+                }),
+                Err(error) => {
+                    warn!("Failed to determine the status of the coinbase record for block {} - {}", block_height, error);
+                    None
                 }
-            }
+            })
+            .collect();
+        records.sort_by_key(|record| record.block_height);
+        records
+    }
+
+    /// Returns the status of the given coinbase record, from the canonical block at its block height.
+    fn get_coinbase_status(&self, block_height: u32, record: &Record<N>) -> Result<CoinbaseStatus> {
+        let ledger = self.state.ledger().reader();
+        if block_height > ledger.latest_block_height() {
+            return Ok(CoinbaseStatus::Pending);
+        }
+
+        let commitment = record.commitment();
+        match ledger.get_block(block_height)?.transactions().commitments().any(|candidate| *candidate == commitment) {
+            true => Ok(CoinbaseStatus::Canon),
+            false => Ok(CoinbaseStatus::Orphaned),
         }
     }
 
+    /// Returns `true` if the prover has been paused.
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::SeqCst)
+    }
+
     ///
-    /// Sends a `PoolRegister` message to the pool IP address.
+    /// Shuts down the prover, flushing its statistics to storage.
     ///
-    async fn send_pool_register(&self) {
-        if E::NODE_TYPE == NodeType::Prover {
-            if let Some(recipient) = self.state.address {
-                if let Some(pool_ip) = self.pool {
-                    // Proceed to register the prover to receive a block template.
-                    let request = PeersRequest::MessageSend(pool_ip, Message::PoolRegister(recipient));
-                    if let Err(error) = self.state.peers().router().send(request).await {
-                        warn!("[PoolRegister] {}", error);
-                    }
-                } else {
-                    error!("Missing pool IP address. Please specify a pool IP address in order to run the prover");
-                }
-            } else {
-                error!("Missing prover address. Please specify an Aleo address in order to prove");
-            }
+    pub fn shut_down(&self) {
+        debug!("Prover is shutting down...");
+        self.flush_stats();
+    }
+
+    /// Returns whether the prover currently mines blocks on its own, or shares for a pool.
+    pub async fn mining_mode(&self) -> MiningMode {
+        *self.mining_mode.read().await
+    }
+
+    /// Returns the proving statistics of the prover, since it started mining for its current pool.
+    pub async fn get_stats(&self) -> ProvingStats<N> {
+        let samples = self.proof_attempt_samples.read().await;
+        let mode = self.mining_mode().await;
+        let (since_restart, lifetime, lifetime_pool_shares) = {
+            let persisted_stats = self.persisted_stats.lock().unwrap();
+            let unflushed = self.counters().since(&persisted_stats.flushed);
+            let (mut since_restart, mut lifetime) = (persisted_stats.since_restart, persisted_stats.lifetime);
+            since_restart.add(&unflushed);
+            lifetime.add(&unflushed);
+            (since_restart, lifetime, persisted_stats.lifetime_pool_shares.clone())
+        };
+        ProvingStats {
+            mode,
+            pool: self.current_pool().await,
+            recipient: match mode {
+                MiningMode::Solo => *self.current_miner_recipient.read().await,
+                MiningMode::Pool => self.state.address,
+            },
+            proof_attempts: self.proof_attempts.load(Ordering::SeqCst),
+            proof_rates: PROOF_RATE_WINDOWS.map(|window| proof_rate(&samples, window)),
+            submitted_shares: self.submitted_shares.load(Ordering::SeqCst),
+            failed_submissions: self.failed_submissions.load(Ordering::SeqCst),
+            dropped_shares: self.dropped_shares.load(Ordering::SeqCst),
+            stale_shares: self.stale_shares.load(Ordering::SeqCst),
+            duplicate_templates: self.duplicate_templates.load(Ordering::SeqCst),
+            regressive_templates: self.regressive_templates.load(Ordering::SeqCst),
+            dropped_transactions: self.dropped_transactions.load(Ordering::SeqCst),
+            intensity: self.duty_cycle.intensity(),
+            effective_intensity: self.duty_cycle.effective_intensity(),
+            stale_work_percentage: self.stale_work.stale_percentage(),
+            mean_template_start_delay_ms: self.stale_work.mean_start_delay().as_secs_f64() * 1000.0,
+            blocks_found: self.blocks_found.load(Ordering::SeqCst),
+            empty_template_blocks: self.empty_template_blocks.load(Ordering::SeqCst),
+            full_template_blocks: self.full_template_blocks.load(Ordering::SeqCst),
+            readiness: match mode {
+                MiningMode::Solo => self.mining_readiness().await,
+                MiningMode::Pool => None,
+            },
+            since_restart,
+            lifetime,
+            lifetime_pool_shares,
+            self_test: self.self_test.clone(),
+        }
+    }
+
+    /// Returns the current values of the statistics counters, which are reset with the proving statistics.
+    fn counters(&self) -> ProverLifetimeStats {
+        ProverLifetimeStats {
+            proof_attempts: self.proof_attempts.load(Ordering::SeqCst),
+            submitted_shares: self.submitted_shares.load(Ordering::SeqCst),
+            failed_submissions: self.failed_submissions.load(Ordering::SeqCst),
+            dropped_shares: self.dropped_shares.load(Ordering::SeqCst),
+            stale_shares: self.stale_shares.load(Ordering::SeqCst),
+            blocks_found: self.blocks_found.load(Ordering::SeqCst),
         }
     }
 
     ///
-    /// Processes a `PoolRequest` message from a pool operator.
+    /// Adds the statistics counted since the last flush to the lifetime statistics in storage.
+    /// If the flush fails, the statistics are retained for the next flush, unless they are reset in the meantime.
     ///
-    async fn process_pool_request(&self, operator_ip: SocketAddr, share_difficulty: u64, block_template: BlockTemplate<N>) {
-        if E::NODE_TYPE == NodeType::Prover {
-            if let Some(recipient) = self.state.address {
-                if let Some(pool_ip) = self.pool {
+    fn flush_stats(&self) {
+        let mut persisted_stats = self.persisted_stats.lock().unwrap();
+        let counters = self.counters();
+        let unflushed = counters.since(&persisted_stats.flushed);
+        if unflushed == ProverLifetimeStats::default() && persisted_stats.unflushed_pool_shares.is_empty() {
+            return;
+        }
+
+        let pool_shares: Vec<_> = persisted_stats.unflushed_pool_shares.iter().map(|(ip, num_shares)| (*ip, *num_shares)).collect();
+        match self.prover_state.add_lifetime_stats(&unflushed, &pool_shares) {
+            Ok(()) => {
+                persisted_stats.flushed = counters;
+                persisted_stats.since_restart.add(&unflushed);
+                persisted_stats.lifetime.add(&unflushed);
+                persisted_stats.unflushed_pool_shares.clear();
+            }
+            Err(error) => warn!("Failed to store the prover statistics - {}", error),
+        }
+    }
+
+    ///
+    /// Resets the proving statistics, so that they only reflect the pool that the prover is currently mining for.
+    ///
+    async fn reset_stats(&self) {
+        // Flush the statistics before they are reset, so that they are counted in the lifetime statistics.
+        self.flush_stats();
+        self.persisted_stats.lock().unwrap().flushed = Default::default();
+        self.proof_attempts.store(0, Ordering::SeqCst);
+        self.proof_attempt_samples.write().await.clear();
+        self.submitted_shares.store(0, Ordering::SeqCst);
+        self.failed_submissions.store(0, Ordering::SeqCst);
+        self.dropped_shares.store(0, Ordering::SeqCst);
+        self.stale_shares.store(0, Ordering::SeqCst);
+        self.duplicate_templates.store(0, Ordering::SeqCst);
+        self.regressive_templates.store(0, Ordering::SeqCst);
+        self.dropped_transactions.store(0, Ordering::SeqCst);
+        self.blocks_found.store(0, Ordering::SeqCst);
+        self.empty_template_blocks.store(0, Ordering::SeqCst);
+        self.full_template_blocks.store(0, Ordering::SeqCst);
+        self.duty_cycle.reset();
+        self.stale_work.reset();
+    }
+
+    /// Returns the IP address of the pool that the prover is currently mining for, if the prover mines for a pool.
+    pub async fn current_pool(&self) -> Option<SocketAddr> {
+        match self.mining_mode().await {
+            MiningMode::Solo => None,
+            MiningMode::Pool => {
+                let index = self.current_pool.read().await.index;
+                self.pools.read().await.get(index).copied()
+            }
+        }
+    }
+
+    ///
+    /// Fails over to the next pool if the current pool has not sent a `PoolRequest` within the pool timeout,
+    /// cycling through the pools in order. Once the primary pool retry has elapsed since failing over from
+    /// the primary pool, the prover returns to it, and fails over again if it is still unresponsive.
+    ///
+    async fn check_pool_failover(&self) {
+        let pools = self.pools.read().await.clone();
+        // A single pool has nothing to fail over to, so the prover keeps reconnecting to it instead.
+        if pools.len() < 2 {
+            return;
+        }
+
+        let (index, reason) = {
+            let current_pool = self.current_pool.read().await;
+            let idle_time = current_pool.idle_time();
+            if idle_time >= self.pool_timeout {
+                ((current_pool.index + 1) % pools.len(), format!("no work received in {} seconds", idle_time.as_secs()))
+            } else if current_pool.index != 0 && current_pool.selected_at.elapsed() >= self.primary_pool_retry {
+                (0, "retrying the primary pool".to_string())
+            } else {
+                return;
+            }
+        };
+
+        // Select the next pool, before disconnecting from the previous one.
+        let previous_pool_ip = {
+            let mut current_pool = self.current_pool.write().await;
+            let previous_pool_ip = pools[current_pool.index];
+            *current_pool = CurrentPool::new(index);
+            previous_pool_ip
+        };
+        let pool_ip = pools[index];
+        warn!("Failing over from pool {} to pool {} - {}", previous_pool_ip, pool_ip, reason);
+        self.reset_stats().await;
+
+        // Disconnect from the previous pool, and connect to the next pool.
+        self.disconnect_from_pool(previous_pool_ip).await;
+        self.connect_to_pool(pool_ip).await;
+    }
+
+    ///
+    /// Disconnects from the given pool, which forgets the prover once it is disconnected.
+    ///
+    async fn disconnect_from_pool(&self, pool_ip: SocketAddr) {
+        let message = Message::Disconnect(DisconnectReason::NoReasonGiven);
+        if let Err(error) = self.state.peers().router().send(PeersRequest::MessageSend(pool_ip, message)).await {
+            warn!("[Disconnect] {}", error);
+        }
+    }
+
+    ///
+    /// Connects to the given pool, and waits until the connection task is initialized.
+    ///
+    async fn connect_to_pool(&self, pool_ip: SocketAddr) {
+        let (router, handler) = oneshot::channel();
+        // Route a `Connect` request to the pool.
+        if let Err(error) = self.state.peers().router().send(PeersRequest::Connect(pool_ip, router)).await {
+            trace!("[Connect] {}", error);
+        }
+        // Wait until the connection task is initialized.
+        let _ = handler.await;
+    }
+
+    ///
+    /// Performs the given `request` to the prover.
+    /// All requests must go through this `update`, so that a unified view is preserved.
+    ///
+    pub(super) async fn update(&self, request: ProverRequest<N>) {
+        match request {
+            ProverRequest::PoolRequest(operator_ip, share_difficulty, block_template) => {
+                // Process the pool request message.
+                self.process_pool_request(operator_ip, share_difficulty, block_template).await;
+            }
+            ProverRequest::MemoryPoolClear(block) => match block {
+                Some(block) => self.memory_pool.write().await.remove_transactions(block.transactions()),
+                None => *self.memory_pool.write().await = MemoryPool::new(),
+            },
+            ProverRequest::MemoryPoolRestore(blocks) => {
+                // Return the transactions of the blocks removed by a reorg to the memory pool.
+                self.restore_transactions(&blocks).await;
+            }
+            ProverRequest::UnconfirmedTransaction(peer_ip, transaction) => {
+                // Ensure the node is not peering.
+                if !E::status().is_peering() {
+                    // Process the unconfirmed transaction.
+                    self.add_unconfirmed_transaction(peer_ip, transaction).await
+                }
+            }
+            ProverRequest::SetThreads(number_of_threads) => match build_proving_pool(Some(number_of_threads)) {
+                // The proof attempt in progress, if any, completes on the previous proving pool.
+                Ok(proving_pool) => {
+                    *self.proving_pool.write().await = Arc::new(proving_pool);
+                    info!("Resized the proving pool to {} threads", number_of_threads);
+                }
+                Err(error) => warn!("[SetThreads] {}", error),
+            },
+            ProverRequest::SetPaused(is_paused) => match is_paused {
+                true => self.pause().await,
+                false => self.resume().await,
+            },
+            ProverRequest::SetMiningMode(mining_mode, pool_ip) => {
+                if let Err(error) = self.set_mining_mode(mining_mode, pool_ip).await {
+                    warn!("[SetMiningMode] {}", error);
+                }
+            }
+            ProverRequest::SetIntensity(intensity) => match self.duty_cycle.set_intensity(intensity) {
+                Ok(()) => info!("Set the prover intensity to {}%", intensity),
+                Err(error) => warn!("[SetIntensity] {}", error),
+            },
+        }
+    }
+
+    ///
+    /// Switches the prover between solo mining and pool mining. The prover aborts the proof in progress, if any,
+    /// and leaves the previous mode before entering the next one. If a pool is given, the prover switches to it,
+    /// in preference to the configured pools, and otherwise mines for its primary pool.
+    ///
+    /// Upon entering pool mode, the prover connects to the pool and registers with it, and only starts proving
+    /// once the pool sends it work. Upon entering solo mode, the miner starts on its next heartbeat.
+    ///
+    async fn set_mining_mode(&self, mining_mode: MiningMode, pool_ip: Option<SocketAddr>) -> Result<()> {
+        let has_address = match mining_mode {
+            MiningMode::Solo => !self.miner_recipients.is_empty(),
+            MiningMode::Pool => self.state.address.is_some(),
+        };
+        ensure!(has_address, "Missing {} mining address. Please specify an Aleo address in order to mine", mining_mode);
+        ensure!(mining_mode == MiningMode::Pool || pool_ip.is_none(), "A pool may only be given for pool mining");
+        ensure!(
+            pool_ip.is_some() || !self.pools.read().await.is_empty(),
+            "Missing pool IP address. Please specify a pool IP address in order to mine for a pool"
+        );
+
+        let previous_mode = self.mining_mode().await;
+        let previous_pool_ip = self.current_pool().await;
+        if mining_mode == previous_mode && (pool_ip.is_none() || pool_ip == previous_pool_ip) {
+            return Ok(());
+        }
+
+        // Leave the previous mode, aborting the proof in progress.
+        match previous_mode {
+            // Set the terminator bit to `true` to ensure the miner stops mining.
+            MiningMode::Solo => E::terminator().store(true, Ordering::SeqCst),
+            MiningMode::Pool => self.discard_pool_work().await,
+        }
+        if let Some(previous_pool_ip) = previous_pool_ip {
+            self.disconnect_from_pool(previous_pool_ip).await;
+        }
+
+        // Enter the next mode, with the given pool as the primary pool.
+        if let Some(pool_ip) = pool_ip {
+            let mut pools = self.pools.write().await;
+            pools.retain(|candidate| *candidate != pool_ip);
+            pools.insert(0, pool_ip);
+        }
+        *self.current_pool.write().await = CurrentPool::new(0);
+        *self.mining_mode.write().await = mining_mode;
+        self.reset_stats().await;
+
+        // Connect to the pool, upon which the pool connection loop registers the prover.
+        if let Some(pool_ip) = self.current_pool().await {
+            info!("Switched from {} mining to mining for pool {}", previous_mode, pool_ip);
+            self.connect_to_pool(pool_ip).await;
+        } else {
+            info!("Switched from {} mining to solo mining", previous_mode);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Pauses the prover, aborting the proof in progress, if any. The node continues to sync and relay as usual.
+    ///
+    async fn pause(&self) {
+        if self.is_paused.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        // Abort the proof in progress. The work from the pool is kept, so that it is resumed if it is still current.
+        match self.mining_mode().await {
+            // Set the terminator bit to `true` to ensure the miner stops mining.
+            MiningMode::Solo => E::terminator().store(true, Ordering::SeqCst),
+            MiningMode::Pool => self.abort_proof.store(true, Ordering::SeqCst),
+        }
+        info!("Paused the prover");
+    }
+
+    ///
+    /// Resumes the prover. In pool mode, the prover registers with its pool again,
+    /// and resumes mining the latest work from the pool, if any.
+    ///
+    async fn resume(&self) {
+        if !self.is_paused.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        info!("Resumed the prover");
+
+        if self.state.address.is_some() {
+            if self.mining_mode().await == MiningMode::Pool {
+                // Restart the failover timer, as the pool was not expected to send work while the prover was paused.
+                {
+                    let mut current_pool = self.current_pool.write().await;
+                    *current_pool = CurrentPool::new(current_pool.index);
+                }
+
+                // Resume mining the latest work, if the mining task has stopped.
+                let pool_work = self.pool_work.write().await;
+                if pool_work.is_some() && !E::status().is_mining() && !E::status().is_peering() {
+                    E::status().update(Status::Mining);
+                    self.start_pool_mining();
+                }
+                drop(pool_work);
+
+                self.send_pool_register().await;
+            }
+        }
+    }
+
+    ///
+    /// Sends a `PoolRegister` message to the pool IP address.
+    ///
+    async fn send_pool_register(&self) {
+        if self.mining_mode().await == MiningMode::Pool {
+            if let Some(recipient) = self.state.address {
+                if let Some(pool_ip) = self.current_pool().await {
+                    // Proceed to register the prover to receive a block template.
+                    let request = PeersRequest::MessageSend(pool_ip, Message::PoolRegister(recipient, self.worker.clone()));
+                    if let Err(error) = self.state.peers().router().send(request).await {
+                        warn!("[PoolRegister] {}", error);
+                    }
+                } else {
+                    error!("Missing pool IP address. Please specify a pool IP address in order to run the prover");
+                }
+            } else {
+                error!("Missing prover address. Please specify an Aleo address in order to prove");
+            }
+        }
+    }
+
+    ///
+    /// Processes a `PoolRequest` message from a pool operator.
+    ///
+    /// The work from the pool replaces any work that has yet to be started. If a proof is in progress,
+    /// it is aborted, so that the prover switches to the new block template without finishing a proof
+    /// for the previous one.
+    ///
+    async fn process_pool_request(&self, operator_ip: SocketAddr, share_difficulty: u64, block_template: BlockTemplate<N>) {
+        if self.mining_mode().await == MiningMode::Pool {
+            if self.state.address.is_some() {
+                if let Some(pool_ip) = self.current_pool().await {
                     // Refuse work from any pool other than the registered one.
                     if pool_ip == operator_ip {
-                        // If `terminator` is `false` and the status is not `Peering` or `Mining`
-                        // already, mine the next block.
-                        if !E::terminator().load(Ordering::SeqCst) && !E::status().is_peering() && !E::status().is_mining() {
-                            // Set the status to `Mining`.
-                            E::status().update(Status::Mining);
+                        // Record the request from the pool, so that the prover does not fail over from it.
+                        self.current_pool.write().await.last_pool_request = Some(Instant::now());
 
-                            let block_height = block_template.block_height();
-                            let block_template = block_template.clone();
-
-                            let result = task::spawn_blocking(move || {
-                                E::thread_pool().install(move || {
-                                    loop {
-                                        let block_header =
-                                            BlockHeader::mine_once_unchecked(&block_template, E::terminator(), &mut thread_rng())?;
-
-                                        // Ensure the share difficulty target is met.
-                                        if N::posw().verify(
-                                            block_header.height(),
-                                            share_difficulty,
-                                            &[*block_header.to_header_root().unwrap(), *block_header.nonce()],
-                                            block_header.proof(),
-                                        ) {
-                                            return Ok::<(N::PoSWNonce, PoSWProof<N>, u64), anyhow::Error>((
-                                                block_header.nonce(),
-                                                block_header.proof().clone(),
-                                                block_header.proof().to_proof_difficulty()?,
-                                            ));
-                                        }
-                                    }
-                                })
-                            })
-                            .await;
+                        // Ensure the block template is not a duplicate or a regression of the latest block template.
+                        let is_duplicate = match self.check_block_template(operator_ip, &block_template).await {
+                            Ok(is_duplicate) => is_duplicate,
+                            Err(error) => {
+                                warn!("[PoolRequest] {}", error);
+                                return;
+                            }
+                        };
 
-                            E::status().update(Status::Ready);
+                        // Record the share difficulty that the pool assigned.
+                        self.current_pool.write().await.share_difficulty = Some(share_difficulty);
 
-                            match result {
-                                Ok(Ok((nonce, proof, proof_difficulty))) => {
-                                    info!(
-                                        "Prover successfully mined a share for unconfirmed block {} with proof difficulty of {}",
-                                        block_height, proof_difficulty
-                                    );
-
-                                    // Send a `PoolResponse` to the operator.
-                                    let message = Message::PoolResponse(recipient, nonce, Data::Object(proof));
-                                    if let Err(error) = self
-                                        .state
-                                        .peers()
-                                        .router()
-                                        .send(PeersRequest::MessageSend(operator_ip, message))
-                                        .await
-                                    {
-                                        warn!("[PoolResponse] {}", error);
-                                    }
-                                }
-                                Ok(Err(error)) => trace!("{}", error),
-                                Err(error) => trace!("{}", anyhow!("Failed to mine the next block {}", error)),
+                        // Discard the shares that wait to be sent for the previous block template, as they are stale.
+                        let num_stale = self.share_queue.write().await.set_template((operator_ip, block_template.clone()));
+                        self.stale_shares.fetch_add(num_stale as u64, Ordering::SeqCst);
+
+                        // Hold the lock on the work until the mining status is settled, as the mining task
+                        // only stops once it finds no work under this lock.
+                        let mut pool_work = self.pool_work.write().await;
+                        *pool_work = Some((operator_ip, share_difficulty, block_template, Instant::now()));
+
+                        if E::status().is_mining() {
+                            // Abort the proof in progress, and let the mining task pick up the new work.
+                            // A duplicate block template is picked up once the proof in progress completes.
+                            if !is_duplicate {
+                                self.abort_proof.store(true, Ordering::SeqCst);
                             }
+                        } else if !self.is_paused() && !E::terminator().load(Ordering::SeqCst) && !E::status().is_peering() {
+                            // If `terminator` is `false` and the status is not `Peering` or `Mining`
+                            // already, mine the next block.
+                            E::status().update(Status::Mining);
+                            self.start_pool_mining();
                         }
                     }
                 } else {
@@ -300,6 +1621,218 @@ impl<N: Network, E: Environment> Prover<N, E> {
         }
     }
 
+    ///
+    /// Checks the given block template against the latest block template from the pool, and records it as the latest.
+    /// Returns `true` if it is a duplicate of the latest block template, whose header root is the same.
+    ///
+    /// Returns an error if the block template is for a lower block height than the latest block template, unless
+    /// the ledger of the prover confirms a reorg, with the previous block of the given block template on its canonical
+    /// chain, and that of the latest block template off of it.
+    ///
+    async fn check_block_template(&self, operator_ip: SocketAddr, block_template: &BlockTemplate<N>) -> Result<bool> {
+        let header_root = block_template.to_header_root()?;
+
+        let mut latest_template = self.latest_template.write().await;
+        if let Some((latest_ip, latest_header_root, latest_block_template)) = &*latest_template {
+            if *latest_ip == operator_ip {
+                if *latest_header_root == header_root {
+                    trace!("Ignoring a duplicate block template for block {} from {}", block_template.block_height(), operator_ip);
+                    self.duplicate_templates.fetch_add(1, Ordering::SeqCst);
+                    return Ok(true);
+                }
+
+                if block_template.block_height() < latest_block_template.block_height() {
+                    let ledger = self.state.ledger().reader();
+                    let is_canonical = |template: &BlockTemplate<N>| match template.block_height().checked_sub(1) {
+                        Some(height) => ledger.get_block_hash(height).map_or(false, |hash| hash == template.previous_block_hash()),
+                        None => false,
+                    };
+                    if !is_canonical(block_template) || is_canonical(latest_block_template) {
+                        self.regressive_templates.fetch_add(1, Ordering::SeqCst);
+                        return Err(anyhow!(
+                            "Rejecting a block template for block {} from {}, below the latest block template for block {}",
+                            block_template.block_height(),
+                            operator_ip,
+                            latest_block_template.block_height()
+                        ));
+                    }
+                    debug!("Accepting a block template for block {} from {} upon a reorg", block_template.block_height(), operator_ip);
+                }
+            }
+        }
+
+        *latest_template = Some((operator_ip, header_root, block_template.clone()));
+        Ok(false)
+    }
+
+    ///
+    /// Starts a task that mines the work from the pool, until there is no new work left.
+    /// The mining status must be set to `Mining` before this is called.
+    ///
+    fn start_pool_mining(&self) {
+        let state = self.state.clone();
+        // Procure a resource id to register the task with, as it might be terminated at any point in time.
+        let mining_task_id = E::resources().procure_id();
+        E::resources().register_task(
+            Some(mining_task_id),
+            task::spawn(async move {
+                let prover = state.prover();
+                loop {
+                    // Take the latest work from the pool, or stop mining if there is none, or the prover has been paused.
+                    let (operator_ip, share_difficulty, block_template, received_at) = {
+                        let mut pool_work = prover.pool_work.write().await;
+                        if prover.is_paused() {
+                            E::status().update(Status::Ready);
+                            break;
+                        }
+                        match pool_work.take() {
+                            Some(work) => {
+                                prover.abort_proof.store(false, Ordering::SeqCst);
+                                work
+                            }
+                            None => {
+                                E::status().update(Status::Ready);
+                                break;
+                            }
+                        }
+                    };
+
+                    prover.stale_work.record_start(received_at.elapsed());
+                    prover.mine_pool_share(operator_ip, share_difficulty, block_template.clone()).await;
+
+                    // Keep the work of a proof aborted by a pause for when the prover resumes, unless newer work has arrived.
+                    if prover.is_paused() {
+                        prover.pool_work.write().await.get_or_insert((operator_ip, share_difficulty, block_template, received_at));
+                    }
+                }
+
+                E::resources().deregister(mining_task_id);
+            }),
+        );
+    }
+
+    ///
+    /// Discards the work from the pool, and aborts the proof in progress, if any.
+    ///
+    async fn discard_pool_work(&self) {
+        // Forget the latest block template, so that the pool may send it again upon reconnecting.
+        *self.latest_template.write().await = None;
+        let num_stale = self.share_queue.write().await.clear();
+        self.stale_shares.fetch_add(num_stale as u64, Ordering::SeqCst);
+
+        let mut pool_work = self.pool_work.write().await;
+        *pool_work = None;
+        if E::status().is_mining() {
+            self.abort_proof.store(true, Ordering::SeqCst);
+        }
+    }
+
+    ///
+    /// Returns the share difficulty most recently assigned by the given pool, if it is the current pool and has assigned one.
+    ///
+    async fn assigned_share_difficulty(&self, pool_ip: SocketAddr) -> Option<u64> {
+        let (index, share_difficulty) = {
+            let current_pool = self.current_pool.read().await;
+            (current_pool.index, current_pool.share_difficulty)
+        };
+        match self.pools.read().await.get(index) == Some(&pool_ip) {
+            true => share_difficulty,
+            false => None,
+        }
+    }
+
+    ///
+    /// Mines a share for the given block template, and sends it to the operator that sent the block template.
+    /// The proof attempts return early if they are aborted for new work. A proof that has already met
+    /// the share difficulty is submitted regardless, as it remains valid for the stale template grace period.
+    ///
+    async fn mine_pool_share(&self, operator_ip: SocketAddr, share_difficulty: u64, block_template: BlockTemplate<N>) {
+        let block_height = block_template.block_height();
+        let difficulty_target = block_template.difficulty_target();
+        let share_template = (operator_ip, block_template.clone());
+        let proving_pool = self.proving_pool.read().await.clone();
+        let parallel_proofs = self.parallel_proofs;
+        let backend = self.backend.clone();
+        let abort_proof = self.abort_proof.clone();
+        let duty_cycle = self.duty_cycle.clone();
+        let proof_attempts = self.proof_attempts.clone();
+        let stale_work = self.stale_work.clone();
+        let canon = self.state.ledger().reader().clone();
+
+        let result = task::spawn_blocking(move || {
+            let on_attempt = |proving_time: Duration| {
+                proof_attempts.fetch_add(1, Ordering::SeqCst);
+                // The block template is stale once the ledger of the prover has a block at its height.
+                stale_work.record_attempt(proving_time, block_height <= canon.latest_block_height());
+            };
+            prove_share::<N, E>(
+                &*backend,
+                &block_template,
+                share_difficulty,
+                &proving_pool,
+                parallel_proofs,
+                &abort_proof,
+                &duty_cycle,
+                &on_attempt,
+            )
+        })
+        .await;
+
+        match result {
+            Ok(Ok((nonce, proof, proof_difficulty))) => {
+                if proof_difficulty <= difficulty_target {
+                    info!(
+                        "Prover found a proof that meets the network difficulty for unconfirmed block {} ({} <= {})",
+                        block_height, proof_difficulty, difficulty_target
+                    );
+                }
+
+                // Ensure the share meets the share difficulty most recently assigned by the pool, which may have
+                // changed while the proof was in progress, as the operator rejects the share otherwise.
+                let share_difficulty = self.assigned_share_difficulty(operator_ip).await.unwrap_or(share_difficulty);
+                if proof_difficulty > share_difficulty {
+                    debug!(
+                        "Dropping a share for unconfirmed block {} with proof difficulty of {}, above the share difficulty of {}",
+                        block_height, proof_difficulty, share_difficulty
+                    );
+                    self.dropped_shares.fetch_add(1, Ordering::SeqCst);
+                    return;
+                }
+
+                info!(
+                    "Prover successfully mined a share for unconfirmed block {} with proof difficulty of {}",
+                    block_height, proof_difficulty
+                );
+
+                // Queue the share to be sent, unless its block template has been replaced in the meantime.
+                let num_stale = self.share_queue.write().await.push(&share_template, (nonce, proof));
+                self.stale_shares.fetch_add(num_stale as u64, Ordering::SeqCst);
+                self.share_queued.notify_one();
+            }
+            Ok(Err(error)) => match self.abort_proof.load(Ordering::SeqCst) {
+                true => debug!("Aborted the proof for unconfirmed block {}", block_height),
+                false => trace!("{}", error),
+            },
+            Err(error) => trace!("{}", anyhow!("Failed to mine the next block {}", error)),
+        }
+    }
+
+    ///
+    /// Adds the transactions of the given blocks, which were removed from the canon chain, back to the memory pool.
+    ///
+    async fn restore_transactions(&self, blocks: &[Block<N>]) {
+        // Re-validate the transactions against the new canon chain, excluding the coinbase transactions.
+        let transactions = self.state.ledger().reader().get_disconnected_transactions(blocks);
+
+        let mut memory_pool = self.memory_pool.write().await;
+        for transaction in &transactions {
+            match memory_pool.add_transaction(transaction) {
+                Ok(()) => debug!("Returned transaction {} of a disconnected block to the memory pool", transaction.transaction_id()),
+                Err(error) => trace!("Skipping transaction {} of a disconnected block: {}", transaction.transaction_id(), error),
+            }
+        }
+    }
+
     ///
     /// Adds the given unconfirmed transaction to the memory pool.
     ///
@@ -323,13 +1856,182 @@ impl<N: Network, E: Environment> Prover<N, E> {
         }
     }
 
+    ///
+    /// Returns the unconfirmed transactions to mine the next block with in solo mode, and the kind of its template.
+    ///
+    /// When mining empty templates first, the first block on a new tip is mined without any unconfirmed transactions,
+    /// while the full template is prepared in the background. The blocks after it are mined on the full template.
+    ///
+    async fn select_unconfirmed_transactions(
+        &self,
+        canon: &LedgerReader<N>,
+        proving_pool: &Arc<ThreadPool>,
+    ) -> (Vec<Transaction<N>>, TemplateKind) {
+        let latest_block_hash = canon.latest_block_hash();
+        if self.mine_empty_first {
+            let mut solo_template = self.solo_template.write().await;
+            if solo_template.map_or(true, |(block_hash, _)| block_hash != latest_block_hash) {
+                *solo_template = Some((latest_block_hash, TemplateKind::Empty));
+                self.prepare_full_template(latest_block_hash, canon.clone(), proving_pool.clone());
+                return (vec![], TemplateKind::Empty);
+            }
+            *solo_template = Some((latest_block_hash, TemplateKind::Full));
+
+            // Use the full template that was prepared for this tip, if it is ready.
+            if let Some((block_hash, transactions)) = self.full_template.write().await.take() {
+                if block_hash == latest_block_hash {
+                    return (transactions, TemplateKind::Full);
+                }
+            }
+        }
+        (self.unconfirmed_transactions(canon, proving_pool).await, TemplateKind::Full)
+    }
+
+    ///
+    /// Initializes a task that prepares the unconfirmed transactions of the full template for the given tip,
+    /// and swaps the full template in, by setting the terminator bit, if the miner is mining the empty template.
+    ///
+    fn prepare_full_template(&self, tip: N::BlockHash, canon: LedgerReader<N>, proving_pool: Arc<ThreadPool>) {
+        let state = self.state.clone();
+        // Procure a resource id to register the task with, as it might be terminated at any point in time.
+        let task_id = E::resources().procure_id();
+        E::resources().register_task(
+            Some(task_id),
+            task::spawn(async move {
+                let prover = state.prover();
+                let transactions = prover.unconfirmed_transactions(&canon, &proving_pool).await;
+                // Without any unconfirmed transactions, the empty template is the full template.
+                if !transactions.is_empty() {
+                    let num_transactions = transactions.len();
+                    *prover.full_template.write().await = Some((tip, transactions));
+
+                    // Set the terminator bit to `true` to ensure the miner stops mining the empty template.
+                    if E::status().is_mining() && *prover.solo_template.read().await == Some((tip, TemplateKind::Empty)) {
+                        debug!("Swapping in the full template with {} transactions", num_transactions);
+                        E::terminator().store(true, Ordering::SeqCst);
+                    }
+                }
+
+                E::resources().deregister(task_id);
+            }),
+        );
+    }
+
+    ///
+    /// Returns the unconfirmed transactions in the memory pool, without those that would make the next block invalid,
+    /// if the unconfirmed transactions are verified.
+    ///
+    async fn unconfirmed_transactions(&self, canon: &LedgerReader<N>, proving_pool: &Arc<ThreadPool>) -> Vec<Transaction<N>> {
+        let unconfirmed_transactions = self.memory_pool.read().await.transactions();
+        match self.verify_transactions {
+            true => self.verify_unconfirmed_transactions(canon, unconfirmed_transactions, proving_pool).await.unwrap_or_else(|error| {
+                warn!("[Miner] Failed to verify the unconfirmed transactions - {}", error);
+                vec![]
+            }),
+            false => unconfirmed_transactions,
+        }
+    }
+
+    ///
+    /// Verifies the given unconfirmed transactions against the canonical chain concurrently on the proving pool,
+    /// and returns the valid transactions. The invalid transactions are evicted from the memory pool.
+    ///
+    async fn verify_unconfirmed_transactions(
+        &self,
+        canon: &LedgerReader<N>,
+        transactions: Vec<Transaction<N>>,
+        proving_pool: &Arc<ThreadPool>,
+    ) -> Result<Vec<Transaction<N>>> {
+        let (canon, proving_pool) = (canon.clone(), proving_pool.clone());
+        let (valid_transactions, invalid_transactions): (Vec<_>, Vec<_>) = task::spawn_blocking(move || {
+            proving_pool.install(|| {
+                transactions.into_par_iter().partition(|transaction| match verify_unconfirmed_transaction(&canon, transaction) {
+                    Ok(()) => true,
+                    Err(error) => {
+                        debug!("Dropping unconfirmed transaction {} - {}", transaction.transaction_id(), error);
+                        false
+                    }
+                })
+            })
+        })
+        .await?;
+
+        if !invalid_transactions.is_empty() {
+            self.memory_pool.write().await.remove_transactions(&invalid_transactions);
+            self.dropped_transactions.fetch_add(invalid_transactions.len() as u64, Ordering::SeqCst);
+
+            #[cfg(any(feature = "test", feature = "prometheus"))]
+            metrics::counter!(metrics::prover::DROPPED_TRANSACTIONS, invalid_transactions.len() as u64);
+
+            info!("Dropped {} invalid unconfirmed transactions from the memory pool", invalid_transactions.len());
+        }
+
+        Ok(valid_transactions)
+    }
+
+    ///
+    /// Returns the readiness of the miner to mine in solo mode, if it waits for its readiness gate.
+    ///
+    async fn mining_readiness(&self) -> Option<MiningReadiness> {
+        let gate = self.readiness_gate?;
+        let status = E::status().get();
+        let latest_block_height = self.state.ledger().reader().latest_block_height();
+        let maximum_block_height_of_peers = self.state.ledger().maximum_block_height_of_peers().await;
+        let connected_peers = self.state.peers().number_of_connected_peers().await;
+
+        let is_synced = maximum_block_height_of_peers
+            .map_or(false, |maximum_block_height| maximum_block_height.saturating_sub(latest_block_height) <= gate.sync_tolerance);
+        Some(MiningReadiness {
+            is_ready: matches!(status, Status::Ready | Status::Mining) && is_synced && connected_peers >= gate.minimum_peers,
+            status,
+            latest_block_height,
+            maximum_block_height_of_peers,
+            connected_peers,
+            gate,
+        })
+    }
+
+    ///
+    /// Returns `true` if the miner may start mining the next block in solo mode, logging whenever it starts
+    /// or stops waiting for its readiness gate.
+    ///
+    async fn is_ready_to_mine(&self) -> bool {
+        let readiness = match self.mining_readiness().await {
+            Some(readiness) => readiness,
+            None => return true,
+        };
+
+        let was_awaiting_readiness = self.is_awaiting_readiness.swap(!readiness.is_ready, Ordering::SeqCst);
+        if !readiness.is_ready && !was_awaiting_readiness {
+            info!(
+                "Miner is waiting for the node to be ready and synced with at least {} peers (status {}, block {} of {}, {} peers)",
+                readiness.gate.minimum_peers,
+                readiness.status,
+                readiness.latest_block_height,
+                readiness.maximum_block_height_of_peers.map_or_else(|| "unknown".to_string(), |height| height.to_string()),
+                readiness.connected_peers
+            );
+        } else if readiness.is_ready && was_awaiting_readiness {
+            info!("Miner is ready, and resumes mining at block {}", readiness.latest_block_height + 1);
+        }
+        readiness.is_ready
+    }
+
+    ///
+    /// Returns the coinbase recipient of the next block, rotating through the miner recipients in order.
+    ///
+    fn next_miner_recipient(&self) -> Address<N> {
+        let index = self.next_miner_recipient.fetch_add(1, Ordering::SeqCst);
+        self.miner_recipients[index % self.miner_recipients.len()]
+    }
+
     ///
     /// Initialize the miner, if the node type is a miner.
     ///
     async fn start_miner(&self) {
-        // Initialize a new instance of the miner.
-        if E::NODE_TYPE == NodeType::Miner && self.pool.is_none() {
-            if let Some(recipient) = self.state.address {
+        // Initialize a new instance of the miner, which only mines while the prover is in solo mode.
+        if E::NODE_TYPE == NodeType::Miner || E::NODE_TYPE == NodeType::Prover {
+            if !self.miner_recipients.is_empty() {
                 // Initialize the prover process.
                 let (router, handler) = oneshot::channel();
                 let state = self.state.clone();
@@ -342,24 +2044,40 @@ impl<N: Network, E: Environment> Prover<N, E> {
                         let _ = router.send(());
                         loop {
                             // If `terminator` is `false` and the status is not `Peering` or `Mining` already, mine the next block.
-                            if !E::terminator().load(Ordering::SeqCst) && !E::status().is_peering() && !E::status().is_mining() {
+                            if !state.prover().is_paused()
+                                && state.prover().mining_mode().await == MiningMode::Solo
+                                && !E::terminator().load(Ordering::SeqCst)
+                                && !E::status().is_peering()
+                                && !E::status().is_mining()
+                                && state.prover().is_ready_to_mine().await
+                            {
                                 // Set the status to `Mining`.
                                 E::status().update(Status::Mining);
 
-                                // Prepare the unconfirmed transactions and dependent objects.
+                                // Select the coinbase recipient of the next block.
+                                let recipient = state.prover().next_miner_recipient();
+                                *state.prover().current_miner_recipient.write().await = Some(recipient);
+
+                                // Prepare the dependent objects.
                                 let prover_state = prover_state.clone();
                                 let canon = state.ledger().reader().clone(); // This is *safe* as the ledger only reads.
-                                let unconfirmed_transactions = state.prover().memory_pool.read().await.transactions();
                                 let ledger_router = state.ledger().router().clone();
+                                let proving_pool = state.prover().proving_pool.read().await.clone();
+
+                                let state = state.clone();
 
                                 // Procure a resource id to register the task with, as it might be terminated at any point in time.
                                 let mining_task_id = E::resources().procure_id();
                                 E::resources().register_task(
                                     Some(mining_task_id),
                                     task::spawn(async move {
+                                        // Prepare the unconfirmed transactions of the next block.
+                                        let (unconfirmed_transactions, template_kind) =
+                                            state.prover().select_unconfirmed_transactions(&canon, &proving_pool).await;
+
                                         // Mine the next block.
                                         let result = task::spawn_blocking(move || {
-                                            E::thread_pool().install(move || {
+                                            proving_pool.install(move || {
                                                 canon.mine_next_block(
                                                     recipient,
                                                     E::COINBASE_IS_PUBLIC,
@@ -377,7 +2095,14 @@ impl<N: Network, E: Environment> Prover<N, E> {
 
                                         match result {
                                             Ok(Ok((block, coinbase_record))) => {
-                                                debug!("Miner has found unconfirmed block {} ({})", block.height(), block.hash());
+                                                let (height, hash) = (block.height(), block.hash());
+                                                debug!("Miner has found unconfirmed block {} ({}) for {}", height, hash, recipient);
+                                                state.prover().blocks_found.fetch_add(1, Ordering::SeqCst);
+                                                let template_blocks = match template_kind {
+                                                    TemplateKind::Empty => &state.prover().empty_template_blocks,
+                                                    TemplateKind::Full => &state.prover().full_template_blocks,
+                                                };
+                                                template_blocks.fetch_add(1, Ordering::SeqCst);
                                                 // Store the coinbase record.
                                                 if let Err(error) = prover_state.add_coinbase_record(block.height(), coinbase_record) {
                                                     warn!("[Miner] Failed to store coinbase record - {}", error);
@@ -410,3 +2135,524 @@ impl<N: Network, E: Environment> Prover<N, E> {
         }
     }
 }
+
+///
+/// Ensures the given unconfirmed transaction may be included in the next block of the canonical chain:
+/// it is new, references a known ledger root, neither double-spends a serial number nor reuses a commitment,
+/// and passes full verification.
+///
+fn verify_unconfirmed_transaction<N: Network>(canon: &LedgerReader<N>, transaction: &Transaction<N>) -> Result<()> {
+    ensure!(!canon.contains_transaction(&transaction.transaction_id())?, "The transaction already exists in the ledger");
+    ensure!(
+        canon.contains_ledger_root(&transaction.ledger_root())?,
+        "The transaction references non-existent ledger root {}",
+        transaction.ledger_root()
+    );
+    for serial_number in transaction.serial_numbers() {
+        ensure!(!canon.contains_serial_number(serial_number)?, "Serial number {} already exists in the ledger", serial_number);
+    }
+    for commitment in transaction.commitments() {
+        ensure!(!canon.contains_commitment(commitment)?, "Commitment {} already exists in the ledger", commitment);
+    }
+    ensure!(transaction.is_valid(), "The transaction failed verification");
+    Ok(())
+}
+
+///
+/// The capabilities of a proving backend.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ProvingCapabilities {
+    /// The name of the proving backend.
+    pub name: String,
+    /// The maximum number of proof attempts that the backend can run concurrently, if it is limited.
+    pub max_parallel_proofs: Option<usize>,
+}
+
+///
+/// A PoSW proving backend, which runs the proof attempts of the prover. The proving loop of the prover,
+/// including its handling of block templates, share submission, and statistics, does not depend on the backend,
+/// so that alternative implementations, such as GPU or FPGA provers, may be plugged in.
+///
+pub trait ProvingBackend<N: Network>: Send + Sync {
+    /// Returns the capabilities of the backend.
+    fn capabilities(&self) -> ProvingCapabilities;
+
+    ///
+    /// Runs a single proof attempt for the given block template, and returns the nonce and proof if they meet
+    /// the share difficulty. The proof attempt returns early with an error once `abort_proof` is set.
+    ///
+    fn prove(
+        &self,
+        block_template: &BlockTemplate<N>,
+        share_difficulty: u64,
+        abort_proof: &AtomicBool,
+    ) -> Result<Option<(N::PoSWNonce, PoSWProof<N>)>>;
+}
+
+///
+/// The default proving backend, which runs PoSW proofs on the CPU, on the proving pool it is called from.
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CpuBackend;
+
+impl<N: Network> ProvingBackend<N> for CpuBackend {
+    fn capabilities(&self) -> ProvingCapabilities {
+        ProvingCapabilities { name: "cpu".to_string(), max_parallel_proofs: None }
+    }
+
+    fn prove(
+        &self,
+        block_template: &BlockTemplate<N>,
+        share_difficulty: u64,
+        abort_proof: &AtomicBool,
+    ) -> Result<Option<(N::PoSWNonce, PoSWProof<N>)>> {
+        let block_header = BlockHeader::mine_once_unchecked(block_template, abort_proof, &mut thread_rng())?;
+
+        // Ensure the share difficulty target is met.
+        match N::posw().verify(
+            block_header.height(),
+            share_difficulty,
+            &[*block_header.to_header_root()?, *block_header.nonce()],
+            block_header.proof(),
+        ) {
+            true => Ok(Some((block_header.nonce(), block_header.proof().clone()))),
+            false => Ok(None),
+        }
+    }
+}
+
+/// Returns the proving backend with the given name.
+pub fn proving_backend<N: Network>(name: &str) -> Result<Arc<dyn ProvingBackend<N>>> {
+    match name {
+        "cpu" => Ok(Arc::new(CpuBackend)),
+        _ => Err(anyhow!("Unknown proving backend '{}'", name)),
+    }
+}
+
+///
+/// Runs independent proof attempts for the given block template concurrently on the given proving pool,
+/// with the given proving backend, until one of them meets the share difficulty, or the proof attempts
+/// are aborted or terminated. The duration of each completed proof attempt is passed to `on_attempt`,
+/// and the proving thread then sleeps for the rest of its duty cycle.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn prove_share<N: Network, E: Environment>(
+    backend: &dyn ProvingBackend<N>,
+    block_template: &BlockTemplate<N>,
+    share_difficulty: u64,
+    proving_pool: &ThreadPool,
+    parallel_proofs: usize,
+    abort_proof: &AtomicBool,
+    duty_cycle: &DutyCycle,
+    on_attempt: &(dyn Fn(Duration) + Sync),
+) -> Result<(N::PoSWNonce, PoSWProof<N>, u64)> {
+    proving_pool.install(|| {
+        // Run independent proof attempts concurrently, until one of them meets the share difficulty.
+        let is_done = AtomicBool::new(false);
+        let attempt = || -> Result<Option<(N::PoSWNonce, PoSWProof<N>, u64)>> {
+            while !is_done.load(Ordering::SeqCst) && !E::terminator().load(Ordering::SeqCst) {
+                let started_at = Instant::now();
+                let result = backend.prove(block_template, share_difficulty, abort_proof)?;
+                let proving_time = started_at.elapsed();
+                on_attempt(proving_time);
+
+                if let Some((nonce, proof)) = result {
+                    let proof_difficulty = proof.to_proof_difficulty()?;
+                    return Ok(Some((nonce, proof, proof_difficulty)));
+                }
+
+                // Sleep for the rest of the duty cycle, which ends early if new work arrives.
+                duty_cycle.throttle::<E>(proving_time, abort_proof);
+            }
+            Ok(None)
+        };
+
+        (0..parallel_proofs)
+            .into_par_iter()
+            .find_map_any(|_| {
+                // Stop the other proof attempts once this one has a result.
+                let result = attempt().transpose();
+                is_done.store(true, Ordering::SeqCst);
+                result
+            })
+            .unwrap_or_else(|| Err(anyhow!("The proof attempts ended without a share")))
+    })
+}
+
+///
+/// Benchmarks the proving loop of the prover for the given duration, on a synthetic block template,
+/// without connecting to any network. The proof attempts run through `prove_share`, exactly as they do
+/// while mining for a pool, against a share difficulty that is never met, until the duration has elapsed.
+///
+pub fn benchmark_proving<N: Network, E: Environment>(
+    backend: &dyn ProvingBackend<N>,
+    duration: Duration,
+    threads: Option<usize>,
+    parallel_proofs: usize,
+) -> Result<BenchmarkReport> {
+    ensure!(!duration.is_zero(), "The benchmark duration must be greater than 0");
+    ensure!(parallel_proofs > 0, "The number of parallel proofs must be greater than 0");
+
+    let block_template = benchmark_block_template::<N, _>(&mut thread_rng())?;
+    let proving_pool = build_proving_pool(threads)?;
+    let duty_cycle = DutyCycle::new(100)?;
+
+    // Abort the proof attempts in progress once the duration has elapsed.
+    let abort_proof = Arc::new(AtomicBool::new(false));
+    let timer = {
+        let abort_proof = abort_proof.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            abort_proof.store(true, Ordering::SeqCst);
+        })
+    };
+
+    let latencies = Mutex::new(Vec::new());
+    let on_attempt = |latency: Duration| latencies.lock().expect("The latencies lock is poisoned").push(latency);
+    let started_at = Instant::now();
+    while !abort_proof.load(Ordering::SeqCst) {
+        // A share difficulty of 0 is never met, so the proof attempts only end once they are aborted.
+        let result =
+            prove_share::<N, E>(backend, &block_template, 0, &proving_pool, parallel_proofs, &abort_proof, &duty_cycle, &on_attempt);
+        if let Err(error) = result {
+            if !abort_proof.load(Ordering::SeqCst) {
+                return Err(error);
+            }
+        }
+    }
+    let elapsed = started_at.elapsed();
+    let _ = timer.join();
+
+    let mut latencies = latencies.into_inner().map_err(|_| anyhow!("The latencies lock is poisoned"))?;
+    latencies.sort();
+    let proofs = latencies.len() as u64;
+    let mean_latency = match proofs {
+        0 => Duration::ZERO,
+        _ => latencies.iter().sum::<Duration>() / proofs as u32,
+    };
+    let median_latency = latencies.get(latencies.len() / 2).copied().unwrap_or_default();
+
+    Ok(BenchmarkReport {
+        backend: backend.capabilities().name,
+        duration_secs: elapsed.as_secs_f64(),
+        threads: proving_pool.current_num_threads(),
+        parallel_proofs,
+        proofs,
+        proofs_per_second: proofs as f64 / elapsed.as_secs_f64(),
+        mean_latency_ms: mean_latency.as_secs_f64() * 1000.0,
+        median_latency_ms: median_latency.as_secs_f64() * 1000.0,
+        peak_memory_bytes: std::fs::read_to_string("/proc/self/status").ok().as_deref().and_then(parse_peak_memory),
+    })
+}
+
+///
+/// Returns a synthetic block template on top of the genesis block, with a coinbase for a random address.
+///
+fn benchmark_block_template<N: Network, R: Rng + CryptoRng>(rng: &mut R) -> Result<BlockTemplate<N>> {
+    let genesis_block = N::genesis_block();
+    let block_height = genesis_block.height() + 1;
+    let recipient = Address::from_private_key(&PrivateKey::new(rng));
+    let (coinbase_transaction, coinbase_record) =
+        Transaction::<N>::new_coinbase(recipient, Block::<N>::block_reward(block_height), true, rng)?;
+
+    Ok(BlockTemplate::new(
+        genesis_block.hash(),
+        block_height,
+        genesis_block.timestamp(),
+        genesis_block.difficulty_target(),
+        genesis_block.cumulative_weight(),
+        genesis_block.previous_ledger_root(),
+        Transactions::from(&[coinbase_transaction])?,
+        coinbase_record,
+    ))
+}
+
+///
+/// Runs the startup self-test of the prover, which proves a share against the lowest share difficulty
+/// on a synthetic block template with the given proving backend, and verifies it. The self-test fails
+/// if the proof fails verification, or if proving takes longer than the self-test timeout.
+///
+pub fn run_self_test<N: Network, E: Environment>(backend: &dyn ProvingBackend<N>, proving_pool: &ThreadPool) -> SelfTestReport {
+    let started_at = Instant::now();
+    let result = prove_and_verify_share::<N, E>(backend, proving_pool);
+    SelfTestReport {
+        backend: backend.capabilities().name,
+        passed: result.is_ok(),
+        duration_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+        error: result.err().map(|error| error.to_string()),
+    }
+}
+
+/// Proves a share on a synthetic block template with the given proving backend, and verifies it.
+fn prove_and_verify_share<N: Network, E: Environment>(backend: &dyn ProvingBackend<N>, proving_pool: &ThreadPool) -> Result<()> {
+    let block_template = benchmark_block_template::<N, _>(&mut thread_rng())?;
+    let duty_cycle = DutyCycle::new(100)?;
+
+    // Abort the proof attempt once the self-test timeout has elapsed.
+    let abort_proof = Arc::new(AtomicBool::new(false));
+    let is_done = Arc::new(AtomicBool::new(false));
+    {
+        let (abort_proof, is_done) = (abort_proof.clone(), is_done.clone());
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + SELF_TEST_TIMEOUT;
+            while !is_done.load(Ordering::SeqCst) && Instant::now() < deadline {
+                std::thread::sleep(DUTY_CYCLE_SLEEP_SLICE);
+            }
+            abort_proof.store(true, Ordering::SeqCst);
+        });
+    }
+
+    // A share difficulty of `u64::MAX` is met by any valid proof.
+    let started_at = Instant::now();
+    let result = prove_share::<N, E>(backend, &block_template, u64::MAX, proving_pool, 1, &abort_proof, &duty_cycle, &|_: Duration| {});
+    is_done.store(true, Ordering::SeqCst);
+    let (nonce, proof, _) = match result {
+        Ok(share) => share,
+        Err(_) if started_at.elapsed() >= SELF_TEST_TIMEOUT => bail!("Proving took longer than {} seconds", SELF_TEST_TIMEOUT.as_secs()),
+        Err(error) => bail!("Proving failed - {}", error),
+    };
+
+    let header_root = block_template.to_header_root()?;
+    ensure!(
+        N::posw().verify(block_template.block_height(), u64::MAX, &[*header_root, *nonce], &proof),
+        "The proof failed verification. Please ensure the binary and its parameters are intact"
+    );
+    Ok(())
+}
+
+///
+/// Returns the peak resident memory in bytes, from the contents of `/proc/self/status`.
+///
+fn parse_peak_memory(status: &str) -> Option<u64> {
+    let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+    let kilobytes: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+///
+/// Returns the proofs per second over the given window, from the samples of the number of proof attempts.
+///
+fn proof_rate(samples: &VecDeque<(Instant, u64)>, window: Duration) -> f64 {
+    let latest = match samples.back() {
+        Some(latest) => latest,
+        None => return 0.0,
+    };
+    let earliest = match samples.iter().find(|(sampled_at, _)| latest.0.duration_since(*sampled_at) <= window) {
+        Some(earliest) => earliest,
+        None => return 0.0,
+    };
+
+    let elapsed = latest.0.duration_since(earliest.0).as_secs_f64();
+    match elapsed > 0.0 {
+        true => latest.1.saturating_sub(earliest.1) as f64 / elapsed,
+        false => 0.0,
+    }
+}
+
+///
+/// Initializes a thread pool for PoSW proving with the given number of threads, or one thread per core if omitted.
+///
+fn build_proving_pool(threads: Option<usize>) -> Result<ThreadPool> {
+    ThreadPoolBuilder::new()
+        .stack_size(8 * 1024 * 1024)
+        .num_threads(threads.unwrap_or(0))
+        .thread_name(|index| format!("prover-{}", index))
+        .build()
+        .map_err(|error| anyhow!("Failed to initialize the proving pool - {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_environment::TestEnvironment;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    type CurrentNetwork = Testnet2;
+
+    /// A proving backend that returns the nonce and proof of the genesis block as a share on every n-th proof attempt,
+    /// so that the proving loop may be tested without proving.
+    struct MockBackend {
+        attempts_per_share: usize,
+        attempts: AtomicUsize,
+    }
+
+    impl<N: Network> ProvingBackend<N> for MockBackend {
+        fn capabilities(&self) -> ProvingCapabilities {
+            ProvingCapabilities { name: "mock".to_string(), max_parallel_proofs: Some(1) }
+        }
+
+        fn prove(&self, _: &BlockTemplate<N>, _: u64, abort_proof: &AtomicBool) -> Result<Option<(N::PoSWNonce, PoSWProof<N>)>> {
+            ensure!(!abort_proof.load(Ordering::SeqCst), "The proof attempt was aborted");
+            match (self.attempts.fetch_add(1, Ordering::SeqCst) + 1) % self.attempts_per_share {
+                0 => {
+                    let genesis_header = N::genesis_block().header();
+                    Ok(Some((genesis_header.nonce(), genesis_header.proof().clone())))
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_rate() {
+        assert_eq!(0.0, proof_rate(&VecDeque::new(), Duration::from_secs(60)));
+
+        // A sample every 10 seconds, at 2 proofs per second for the first 100 seconds, and 4 proofs per second since.
+        let start = Instant::now().checked_sub(Duration::from_secs(200)).expect("Failed to compute an instant in the past");
+        let samples: VecDeque<_> = (0..=20u64)
+            .map(|index| {
+                let sampled_at = start + Duration::from_secs(index * 10);
+                let proof_attempts = if index <= 10 { index * 20 } else { 200 + (index - 10) * 40 };
+                (sampled_at, proof_attempts)
+            })
+            .collect();
+
+        assert_eq!(4.0, proof_rate(&samples, Duration::from_secs(60)));
+        assert_eq!(3.0, proof_rate(&samples, Duration::from_secs(200)));
+        assert_eq!(3.0, proof_rate(&samples, Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn test_duty_cycle() {
+        assert!(DutyCycle::new(0).is_err());
+        assert!(DutyCycle::new(101).is_err());
+
+        let duty_cycle = DutyCycle::new(100).unwrap();
+        assert_eq!(Duration::ZERO, duty_cycle.sleep_time(Duration::from_millis(300)));
+        assert_eq!(100.0, duty_cycle.effective_intensity());
+
+        duty_cycle.set_intensity(60).unwrap();
+        assert_eq!(Duration::from_millis(200), duty_cycle.sleep_time(Duration::from_millis(300)));
+        assert!(duty_cycle.set_intensity(0).is_err());
+        assert_eq!(60, duty_cycle.intensity());
+
+        duty_cycle.proving_micros.store(600, Ordering::SeqCst);
+        duty_cycle.sleeping_micros.store(400, Ordering::SeqCst);
+        assert_eq!(60.0, duty_cycle.effective_intensity());
+        duty_cycle.reset();
+        assert_eq!(60.0, duty_cycle.effective_intensity());
+    }
+
+    #[test]
+    fn test_stale_work() {
+        let stale_work = StaleWork::default();
+        assert_eq!(0.0, stale_work.stale_percentage());
+        assert_eq!(Duration::ZERO, stale_work.mean_start_delay());
+
+        stale_work.record_start(Duration::from_millis(10));
+        stale_work.record_start(Duration::from_millis(30));
+        stale_work.record_attempt(Duration::from_secs(3), false);
+        stale_work.record_attempt(Duration::from_secs(1), true);
+        assert_eq!(25.0, stale_work.stale_percentage());
+        assert_eq!(Duration::from_millis(20), stale_work.mean_start_delay());
+
+        stale_work.reset();
+        assert_eq!(0.0, stale_work.stale_percentage());
+        assert_eq!(Duration::ZERO, stale_work.mean_start_delay());
+    }
+
+    #[test]
+    fn test_share_queue() {
+        let mut queue = ShareQueue::new(2);
+        // A share is discarded until its block template is the latest one.
+        assert_eq!(1, queue.push(&1u32, 'a'));
+        assert!(queue.take(16).is_none());
+
+        assert_eq!(0, queue.set_template(1));
+        assert_eq!(0, queue.push(&1, 'a'));
+        assert_eq!(0, queue.push(&1, 'b'));
+        // The oldest share is discarded once the queue is full.
+        assert_eq!(1, queue.push(&1, 'c'));
+        assert_eq!(Some((&1, vec!['b'])), queue.take(1));
+        assert_eq!(Some((&1, vec!['c'])), queue.take(16));
+        assert!(queue.take(16).is_none());
+
+        // Setting the same block template keeps the queued shares, and a new block template discards them.
+        queue.push(&1, 'd');
+        assert_eq!(0, queue.set_template(1));
+        assert_eq!(1, queue.set_template(2));
+        assert_eq!(1, queue.push(&1, 'e'));
+        assert!(queue.take(16).is_none());
+    }
+
+    #[test]
+    fn test_mining_mode_from_str() {
+        for mining_mode in [MiningMode::Solo, MiningMode::Pool] {
+            assert_eq!(mining_mode, MiningMode::from_str(&mining_mode.to_string()).unwrap());
+        }
+        assert!(MiningMode::from_str("Solo").is_err());
+        assert!(MiningMode::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_parse_peak_memory() {
+        let status = "Name:\tsnarkos\nVmPeak:\t 2048000 kB\nVmHWM:\t  524288 kB\nVmRSS:\t  262144 kB\n";
+        assert_eq!(Some(524288 * 1024), parse_peak_memory(status));
+        assert_eq!(None, parse_peak_memory("Name:\tsnarkos\n"));
+    }
+
+    #[test]
+    fn test_proving_backend() {
+        assert_eq!("cpu", proving_backend::<CurrentNetwork>("cpu").unwrap().capabilities().name);
+        assert!(proving_backend::<CurrentNetwork>("gpu").is_err());
+    }
+
+    #[test]
+    fn test_prove_share_with_mock_backend() {
+        let block_template = benchmark_block_template::<CurrentNetwork, _>(&mut thread_rng()).unwrap();
+        let proving_pool = build_proving_pool(Some(1)).unwrap();
+        let duty_cycle = DutyCycle::new(100).unwrap();
+        let abort_proof = AtomicBool::new(false);
+        let backend = MockBackend { attempts_per_share: 3, attempts: Default::default() };
+        let proof_attempts = AtomicUsize::new(0);
+        let on_attempt = |_: Duration| {
+            proof_attempts.fetch_add(1, Ordering::SeqCst);
+        };
+
+        // The proving loop runs proof attempts until the backend returns a share.
+        let (nonce, proof, proof_difficulty) = prove_share::<CurrentNetwork, TestEnvironment<CurrentNetwork>>(
+            &backend,
+            &block_template,
+            u64::MAX,
+            &proving_pool,
+            1,
+            &abort_proof,
+            &duty_cycle,
+            &on_attempt,
+        )
+        .unwrap();
+        let genesis_header = CurrentNetwork::genesis_block().header();
+        assert_eq!(genesis_header.nonce(), nonce);
+        assert_eq!(genesis_header.proof().to_proof_difficulty().unwrap(), proof_difficulty);
+        assert_eq!(proof, *genesis_header.proof());
+        assert_eq!(3, proof_attempts.load(Ordering::SeqCst));
+
+        // Aborted proof attempts end the proving loop without a share.
+        abort_proof.store(true, Ordering::SeqCst);
+        let result = prove_share::<CurrentNetwork, TestEnvironment<CurrentNetwork>>(
+            &backend,
+            &block_template,
+            u64::MAX,
+            &proving_pool,
+            1,
+            &abort_proof,
+            &duty_cycle,
+            &on_attempt,
+        );
+        assert!(result.is_err());
+        assert_eq!(3, proof_attempts.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_self_test_rejects_invalid_proof() {
+        let proving_pool = build_proving_pool(Some(1)).unwrap();
+        // The genesis proof of the mock backend does not match the synthetic block template of the self-test.
+        let backend = MockBackend { attempts_per_share: 1, attempts: Default::default() };
+
+        let report = run_self_test::<CurrentNetwork, TestEnvironment<CurrentNetwork>>(&backend, &proving_pool);
+        assert_eq!("mock", report.backend);
+        assert!(!report.passed);
+        assert!(report.error.unwrap().contains("failed verification"));
+    }
+}
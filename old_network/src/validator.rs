@@ -14,27 +14,44 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{LedgerRequest, PeersRequest, State};
-use crate::{Data, Message};
+use crate::{LedgerRequest, PeersRequest, ProverRequest, State};
+use crate::{
+    helpers::{CircularSet, LedgerEvent},
+    Data,
+    DisconnectReason,
+    Message,
+    DEFAULT_WORKER_NAME,
+    MAXIMUM_SHARES_PER_POOL_RESPONSE,
+};
 use snarkos_environment::{
     helpers::NodeType,
     Environment,
 };
 use snarkos_storage::{
     storage::{rocksdb::RocksDB, ReadWrite},
+    BlockFound,
     OperatorState,
+    ProverStats,
+    ShareAccounting,
+    ShareOutcome,
 };
 use snarkvm::dpc::{prelude::*, PoSWProof};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, ensure, Result};
+use rand::thread_rng;
+use serde::Serialize;
 use std::{
-    collections::{HashMap, HashSet},
-    net::SocketAddr,
+    collections::{HashMap, HashSet, VecDeque},
+    net::{IpAddr, SocketAddr},
     path::Path,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
-use tokio::sync::{mpsc, oneshot, RwLock};
+use time::OffsetDateTime;
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock, Semaphore};
 
 /// Shorthand for the parent half of the `Operator` message channel.
 pub type OperatorRouter<N> = mpsc::Sender<OperatorRequest<N>>;
@@ -46,16 +63,512 @@ pub type OperatorHandler<N> = mpsc::Receiver<OperatorRequest<N>>;
 ///
 #[derive(Debug)]
 pub enum OperatorRequest<N: Network> {
-    /// PoolRegister := (peer_ip, prover_address)
-    PoolRegister(SocketAddr, Address<N>),
-    /// PoolResponse := (peer_ip, prover_address, nonce, proof)
-    PoolResponse(SocketAddr, Address<N>, N::PoSWNonce, PoSWProof<N>),
+    /// PoolRegister := (peer_ip, prover_address, worker)
+    PoolRegister(SocketAddr, Address<N>, Option<String>),
+    /// PoolResponse := (peer_ip, prover_address, block_height, [(nonce, proof)])
+    PoolResponse(SocketAddr, Address<N>, Option<u32>, Vec<(N::PoSWNonce, PoSWProof<N>)>),
+    /// ProcessPayouts := (block_height)
+    ProcessPayouts(u32),
+    /// GetBlockTemplate := (prover_address, worker, response_sender)
+    GetBlockTemplate(Option<Address<N>>, Option<String>, oneshot::Sender<Option<(BlockTemplate<N>, Option<u64>)>>),
+    /// SetShareDifficulty := (prover_address, share_difficulty)
+    SetShareDifficulty(Address<N>, u64),
+    /// RefreshTemplate := (response_sender)
+    RefreshTemplate(oneshot::Sender<Result<(u32, usize), String>>),
+    /// Shutdown := (response_sender)
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// The maximum number of requests from a single peer that may be in flight to the operator at once.
+pub const MAXIMUM_IN_FLIGHT_REQUESTS_PER_PEER: usize = 8;
+/// The duration in between share difficulty retargets.
+const RETARGET_INTERVAL: Duration = Duration::from_secs(120);
+/// The desired duration in between shares submitted by a single prover.
+const TARGET_SHARE_INTERVAL: Duration = Duration::from_secs(25);
+/// The maximum factor by which a share difficulty may change in a single retarget.
+const MAXIMUM_RETARGET_FACTOR: u128 = 4;
+/// The number of target share intervals without a share after which the share difficulty of a prover decays.
+const DECAY_SHARE_INTERVALS: u32 = 10;
+/// The minimum duration in between rebuilds of the block template for new memory pool transactions.
+const TEMPLATE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// The maximum duration of a single attempt to notify the block hook.
+const BLOCK_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+/// The number of attempts to notify the block hook of a found block.
+const BLOCK_HOOK_ATTEMPTS: u32 = 3;
+/// The duration in between attempts to notify the block hook.
+const BLOCK_HOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+/// The duration in between prunes of the rounds that fall outside the round retention depth.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+/// The duration of the window of accepted shares from which the hashrate of a prover is estimated.
+const HASHRATE_WINDOW: Duration = Duration::from_secs(600);
+/// The duration in between logs of the estimated pool hashrate, round progress, and luck.
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+/// The number of most recent rounds that found a block, over which the luck of the pool is averaged.
+const LUCK_WINDOW: usize = 10;
+/// The duration in between sweeps for inactive provers.
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+/// The factor by which the prover timeout is extended for provers that have yet to submit a share.
+const FIRST_SHARE_GRACE_FACTOR: u32 = 3;
+/// The minimum number of shares from an IP address before its reject rate is considered.
+const MINIMUM_REJECT_RATE_SHARES: u64 = 20;
+
+/// The result of verifying a share against the current and stale block templates.
+enum ShareVerification<N: Network> {
+    /// The share is valid for the current block template.
+    Current(BlockTemplate<N>),
+    /// The share is valid for the stale block template, within the stale template grace period.
+    Stale(BlockTemplate<N>),
+    /// The share is valid for the stale block template, after the stale template grace period.
+    Expired,
+    /// The share is not valid for any block template.
+    Invalid,
+}
+
+///
+/// The operator's view of a prover that has registered with it.
+///
+#[derive(Clone, Debug)]
+struct ProverInfo {
+    /// The timestamp of the last registration by the prover.
+    last_registered: Instant,
+    /// The timestamp of the last share submitted by the prover, if one has been submitted.
+    last_submitted: Option<Instant>,
+    /// The share difficulty that the prover is currently assigned.
+    share_difficulty: u64,
+    /// The share difficulty before the last retarget, kept until the prover has been sent its new one.
+    previous_share_difficulty: Option<u64>,
+    /// The timestamp of the last retarget for the prover.
+    last_retarget: Instant,
+    /// The number of shares submitted by the prover since the last retarget.
+    shares_since_retarget: u64,
+    /// The timestamp of the last decay of the share difficulty of the prover, if it has decayed.
+    last_decayed: Option<Instant>,
+}
+
+impl ProverInfo {
+    /// Initializes a new prover entry with the given share difficulty.
+    fn new(share_difficulty: u64) -> Self {
+        Self {
+            last_registered: Instant::now(),
+            last_submitted: None,
+            share_difficulty,
+            previous_share_difficulty: None,
+            last_retarget: Instant::now(),
+            shares_since_retarget: 0,
+            last_decayed: None,
+        }
+    }
+
+    ///
+    /// Returns the share difficulty that a submitted share must meet.
+    ///
+    /// While the prover has not yet been sent its retargeted share difficulty, shares that
+    /// meet the previous (easier) share difficulty are still accepted.
+    ///
+    fn accepted_share_difficulty(&self) -> u64 {
+        match self.previous_share_difficulty {
+            Some(previous_share_difficulty) => previous_share_difficulty.max(self.share_difficulty),
+            None => self.share_difficulty,
+        }
+    }
+
+    ///
+    /// Sets the share difficulty of the prover to the given share difficulty.
+    /// Shares that meet the previous share difficulty are still accepted until the prover has been sent the new one.
+    ///
+    fn pin(&mut self, share_difficulty: u64) {
+        if share_difficulty != self.share_difficulty {
+            self.previous_share_difficulty = Some(self.accepted_share_difficulty());
+            self.share_difficulty = share_difficulty;
+        }
+    }
+
+    ///
+    /// Returns `true` if the prover has not registered or submitted a share within the given timeout.
+    /// A prover that has yet to submit its first share is given a longer grace period to compute it.
+    ///
+    fn is_inactive(&self, timeout: Duration) -> bool {
+        let has_registered_recently = self.last_registered.elapsed() <= timeout;
+        match self.last_submitted {
+            Some(last_submitted) => !has_registered_recently && last_submitted.elapsed() > timeout,
+            None => self.last_registered.elapsed() > timeout.saturating_mul(FIRST_SHARE_GRACE_FACTOR),
+        }
+    }
+
+    ///
+    /// Halves the difficulty of the prover, by doubling its share difficulty up to the given base share difficulty,
+    /// if the prover has not submitted a share, or had its share difficulty decayed, within the given threshold.
+    /// Returns `true` if the share difficulty has decayed.
+    ///
+    fn decay(&mut self, base_share_difficulty: u64, threshold: Duration) -> bool {
+        let is_stalled = match self.last_submitted {
+            Some(last_submitted) => last_submitted.elapsed() > threshold,
+            None => false,
+        };
+        if !is_stalled
+            || self.last_decayed.map_or(false, |last_decayed| last_decayed.elapsed() <= threshold)
+            || self.share_difficulty >= base_share_difficulty
+        {
+            return false;
+        }
+
+        // Note: A share difficulty is a target, so a larger value is easier to meet.
+        self.previous_share_difficulty = Some(self.accepted_share_difficulty());
+        self.share_difficulty = self.share_difficulty.saturating_mul(2).min(base_share_difficulty);
+        self.last_decayed = Some(Instant::now());
+        true
+    }
+
+    ///
+    /// Retargets the share difficulty of the prover, so that it submits roughly one share every
+    /// `TARGET_SHARE_INTERVAL`, bounded by the given minimum share difficulty.
+    ///
+    fn retarget(&mut self, minimum_share_difficulty: u64) {
+        let current = self.share_difficulty as u128;
+
+        // Note: A share difficulty is a target, so a larger value is easier to meet.
+        let retargeted = match self.shares_since_retarget {
+            // If the prover submitted no shares, ease the share difficulty as much as permitted.
+            0 => current.saturating_mul(MAXIMUM_RETARGET_FACTOR),
+            // Otherwise, scale the share difficulty by the ratio of the observed to the desired share interval.
+            num_shares => {
+                let elapsed = self.last_retarget.elapsed().as_millis().max(1);
+                let expected = (num_shares as u128).saturating_mul(TARGET_SHARE_INTERVAL.as_millis());
+                current.saturating_mul(elapsed) / expected
+            }
+        };
+
+        // Bound the change in share difficulty, and clamp it within the permitted range.
+        let retargeted = retargeted
+            .clamp(current / MAXIMUM_RETARGET_FACTOR, current.saturating_mul(MAXIMUM_RETARGET_FACTOR))
+            .clamp(minimum_share_difficulty as u128, u64::MAX as u128) as u64;
+
+        if retargeted != self.share_difficulty {
+            // Retain the easiest share difficulty the prover may still be mining at.
+            self.previous_share_difficulty = Some(self.accepted_share_difficulty());
+            self.share_difficulty = retargeted;
+        }
+
+        self.last_retarget = Instant::now();
+        self.shares_since_retarget = 0;
+    }
+
+    ///
+    /// Returns the estimated number of proofs per second computed by the prover since the last retarget.
+    /// Meeting a share difficulty of `d` takes `u64::MAX / d` proofs on average.
+    ///
+    fn hashrate(&self) -> f64 {
+        let proofs_per_share = (u64::MAX / self.share_difficulty.max(1)) as f64;
+        self.shares_since_retarget as f64 * proofs_per_share / self.last_retarget.elapsed().as_secs_f64().max(1.0)
+    }
+
+    /// Returns the UNIX timestamp of the last registration or share from the prover.
+    fn last_seen(&self) -> i64 {
+        let last_seen = match self.last_submitted {
+            Some(last_submitted) => last_submitted.max(self.last_registered),
+            None => self.last_registered,
+        };
+        OffsetDateTime::now_utc().unix_timestamp() - last_seen.elapsed().as_secs() as i64
+    }
+}
+
+///
+/// The operator's view of an IP address that provers have connected from.
+///
+#[derive(Clone, Debug)]
+struct PeerInfo<N: Network> {
+    /// The number of registrations from the IP address.
+    registrations: u64,
+    /// The number of shares from the IP address that were accepted.
+    accepted_shares: u64,
+    /// The number of shares from the IP address that were rejected as invalid.
+    rejected_shares: u64,
+    /// The prover addresses seen from the IP address, and the time each was last seen.
+    addresses: HashMap<Address<N>, Instant>,
+    /// The timestamp of the last registration or share from the IP address.
+    last_seen: Instant,
+}
+
+impl<N: Network> PeerInfo<N> {
+    /// Initializes a new entry for an IP address.
+    fn new() -> Self {
+        Self {
+            registrations: 0,
+            accepted_shares: 0,
+            rejected_shares: 0,
+            addresses: Default::default(),
+            last_seen: Instant::now(),
+        }
+    }
+
+    /// Records activity from the given prover address, and forgets the addresses not seen within the given timeout.
+    fn record(&mut self, address: Address<N>, timeout: Duration) {
+        self.last_seen = Instant::now();
+        self.addresses.insert(address, self.last_seen);
+        self.addresses.retain(|_, last_seen| last_seen.elapsed() <= timeout);
+    }
+
+    /// Returns the fraction of shares that were rejected, once enough shares have been received to consider it.
+    fn reject_rate(&self) -> Option<f64> {
+        let number_of_shares = self.accepted_shares + self.rejected_shares;
+        match number_of_shares >= MINIMUM_REJECT_RATE_SHARES {
+            true => Some(self.rejected_shares as f64 / number_of_shares as f64),
+            false => None,
+        }
+    }
+}
+
+///
+/// The statistics of a single IP address that provers have connected from.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(bound = "")]
+pub struct PeerStats<N: Network> {
+    /// The number of registrations from the IP address.
+    pub registrations: u64,
+    /// The number of shares from the IP address that were accepted.
+    pub accepted_shares: u64,
+    /// The number of shares from the IP address that were rejected as invalid.
+    pub rejected_shares: u64,
+    /// The prover addresses recently seen from the IP address.
+    pub addresses: Vec<Address<N>>,
+    /// `true` if the IP address has been banned.
+    pub is_banned: bool,
+}
+
+///
+/// The statistics of a single worker of a prover.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(bound = "")]
+pub struct WorkerStats<N: Network> {
+    /// The address of the prover.
+    pub address: Address<N>,
+    /// The name of the worker.
+    pub worker: String,
+    /// The share difficulty that the worker is currently assigned.
+    pub share_difficulty: u64,
+    /// The estimated number of proofs per second computed by the worker.
+    pub hashrate: f64,
+    /// The UNIX timestamp of the last registration or share from the worker.
+    pub last_seen: i64,
+}
+
+///
+/// The payload sent to the block hook when the operator finds a block.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(bound = "")]
+struct BlockNotification<N: Network> {
+    /// The height of the block.
+    block_height: u32,
+    /// The hash of the block.
+    block_hash: N::BlockHash,
+    /// The address of the prover whose share produced the block.
+    prover: Address<N>,
+    /// The total weighted shares of the round.
+    round_shares: u64,
+    /// The UNIX timestamp at which the block was found.
+    timestamp: i64,
+}
+
+///
+/// Returns the number of proofs that are expected to find a block at the difficulty target of the given block template.
+///
+fn expected_work<N: Network>(block_template: &BlockTemplate<N>) -> u64 {
+    u64::MAX / block_template.difficulty_target().max(1)
+}
+
+///
+/// Returns the IDs of the transactions of the given block template other than its coinbase transaction, in order.
+///
+fn non_coinbase_transaction_ids<N: Network>(block_template: &BlockTemplate<N>) -> Vec<N::TransactionID> {
+    let coinbase_commitment = block_template.coinbase_record().commitment();
+    block_template
+        .transactions()
+        .iter()
+        .filter(|transaction| !transaction.commitments().any(|commitment| *commitment == coinbase_commitment))
+        .map(|transaction| transaction.transaction_id())
+        .collect()
+}
+
+///
+/// Returns `true` if the given block templates build on the same block with the same transactions. A coinbase transaction
+/// is crafted afresh for every block template, so it is excluded from the comparison.
+///
+fn is_same_block_template<N: Network>(current: &BlockTemplate<N>, proposed: &BlockTemplate<N>) -> bool {
+    current.previous_block_hash() == proposed.previous_block_hash()
+        && non_coinbase_transaction_ids(current) == non_coinbase_transaction_ids(proposed)
+}
+
+///
+/// Returns `true` if a block template for the given block height and previous block hash extends the given canon tip.
+/// The previous block hash is compared as well, as a reorg may replace the latest block without changing the block height.
+///
+fn extends_canon_tip<H: PartialEq>(block_height: u32, previous_block_hash: H, (latest_block_height, latest_block_hash): (u32, H)) -> bool {
+    latest_block_height.saturating_add(1) == block_height && previous_block_hash == latest_block_hash
+}
+
+///
+/// Returns the estimated number of proofs per second from the given accepted shares within the hashrate window.
+/// Meeting a share difficulty of `d` takes `u64::MAX / d` proofs on average.
+///
+fn estimate_hashrate(shares: &VecDeque<(Instant, u64)>) -> f64 {
+    let proofs: f64 = shares
+        .iter()
+        .filter(|(accepted_at, _)| accepted_at.elapsed() <= HASHRATE_WINDOW)
+        .map(|(_, share_difficulty)| (u64::MAX / (*share_difficulty).max(1)) as f64)
+        .sum();
+    proofs / HASHRATE_WINDOW.as_secs_f64()
+}
+
+///
+/// Sends the given payload to the given block hook, which is either a URL or a command.
+///
+async fn run_block_hook(block_hook: &str, payload: &str) -> Result<()> {
+    if block_hook.starts_with("http://") || block_hook.starts_with("https://") {
+        let response = reqwest::Client::new()
+            .post(block_hook)
+            .header("content-type", "application/json")
+            .body(payload.to_string())
+            .send()
+            .await?;
+        ensure!(response.status().is_success(), "Received status {}", response.status());
+    } else {
+        let status = tokio::process::Command::new(block_hook).arg(payload).kill_on_drop(true).status().await?;
+        ensure!(status.success(), "Exited with {}", status);
+    }
+    Ok(())
+}
+
+///
+/// A prover that has been banned by the operator.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct BannedProver {
+    /// The IP address of the peer that the banned shares were received from.
+    pub peer_ip: SocketAddr,
+    /// The reason for the ban.
+    pub reason: String,
+    /// The UNIX timestamp of the ban.
+    pub timestamp: i64,
+    /// The UNIX timestamp at which the ban expires.
+    pub banned_until: i64,
+}
+
+///
+/// The configuration of an operator.
+///
+#[derive(Clone, Debug)]
+pub struct OperatorConfig<N: Network> {
+    /// The share difficulty that a prover is assigned when it first registers, at which a share weighs 1.
+    pub base_share_difficulty: u64,
+    /// The minimum share difficulty that a prover may be retargeted to.
+    pub minimum_share_difficulty: u64,
+    /// The duration in between iterations of the operator update loop.
+    pub heartbeat: Duration,
+    /// The duration of inactivity after which a prover is evicted from the list of provers.
+    pub prover_timeout: Duration,
+    /// The duration after a block template is replaced during which shares for it are still accepted.
+    pub stale_template_grace: Duration,
+    /// The minimum duration in between updates to the block template for the same block height.
+    pub minimum_template_interval: Duration,
+    /// The number of memory pool transactions missing from the block template at which it is rebuilt, or 0 to disable.
+    pub template_refresh_threshold: usize,
+    /// The age at which the block template is rebuilt if any memory pool transaction is missing from it, or 0 to disable.
+    pub template_refresh_age: Duration,
+    /// The maximum number of transactions in a block template, or 0 for no limit.
+    pub maximum_template_transactions: usize,
+    /// The maximum combined serialized size in bytes of the transactions in a block template, or 0 for no limit.
+    pub maximum_template_size: usize,
+    /// The minimum fee of a transaction to be included in a block template.
+    pub minimum_transaction_fee: u64,
+    /// The minimum duration in between `PoolRequest`s to a peer, unless its template or share difficulty has changed.
+    pub pool_register_interval: Duration,
+    /// The maximum number of distinct prover addresses that may be registered at once.
+    pub maximum_provers: usize,
+    /// If `true`, a peer is disconnected when its prover is turned away for exceeding the maximum number of provers.
+    pub disconnect_excess_provers: bool,
+    /// If `true`, shares are rejected from provers that have not registered with a `PoolRegister`.
+    pub require_registration: bool,
+    /// The method by which the shares of a round are counted towards its payouts.
+    pub share_accounting: ShareAccounting,
+    /// The addresses that the coinbase of each block template is rotated through, or the operator's address if empty.
+    pub recipients: Vec<Address<N>>,
+    /// The private key of the operator, used to sign payout transactions.
+    pub private_key: Option<PrivateKey<N>>,
+    /// The percentage of each block reward that is kept by the operator.
+    pub pool_fee_percent: f64,
+    /// The number of confirmations a block requires before its round is paid out.
+    pub payout_confirmations: u32,
+    /// If `true`, payouts are computed and logged, but no transactions are created.
+    pub payout_dry_run: bool,
+    /// If `true`, the share counters of the provers are reset whenever a block is found.
+    pub reset_stats_each_round: bool,
+    /// The URL or command that is notified whenever the operator finds a block.
+    pub block_hook: Option<String>,
+    /// The number of blocks after a found block at which it is checked to be on the canonical chain.
+    pub orphan_confirmations: u32,
+    /// If `true`, the shares of an orphaned round are added to the current round, instead of being removed.
+    pub roll_over_orphaned_shares: bool,
+    /// The number of blocks for which paid and orphaned rounds are retained, or `None` to retain them all.
+    pub round_retention_depth: Option<u32>,
+    /// The number of invalid shares within the invalid share window after which a prover is banned.
+    pub invalid_share_limit: usize,
+    /// The duration for which an invalid share counts towards the invalid share limit.
+    pub invalid_share_window: Duration,
+    /// The duration for which a prover or IP address remains banned.
+    pub ban_duration: Duration,
+    /// The maximum number of distinct prover addresses recently seen from an IP address before it is banned, or 0 for no limit.
+    pub maximum_addresses_per_ip: usize,
+    /// The maximum fraction of shares from an IP address that may be rejected before it is banned.
+    pub maximum_reject_rate: f64,
+    /// The maximum number of known nonces that are cached for a block template.
+    pub maximum_known_nonces: usize,
+    /// The maximum number of shares pending verification.
+    pub maximum_pending_verifications: usize,
 }
 
-/// The predefined base share difficulty.
-const BASE_SHARE_DIFFICULTY: u64 = u64::MAX;
-/// The operator heartbeat in seconds.
-const HEARTBEAT_IN_SECONDS: Duration = Duration::from_secs(1);
+impl<N: Network> Default for OperatorConfig<N> {
+    /// Returns the default configuration of an operator, which matches the defaults of the command line.
+    fn default() -> Self {
+        Self {
+            base_share_difficulty: u64::MAX,
+            minimum_share_difficulty: u64::MAX / 1_000_000,
+            heartbeat: Duration::from_secs(1),
+            prover_timeout: Duration::from_secs(600),
+            stale_template_grace: Duration::from_secs(10),
+            minimum_template_interval: Duration::ZERO,
+            template_refresh_threshold: 10,
+            template_refresh_age: Duration::from_secs(30),
+            maximum_template_transactions: 0,
+            maximum_template_size: 0,
+            minimum_transaction_fee: 0,
+            pool_register_interval: Duration::from_secs(10),
+            maximum_provers: 512,
+            disconnect_excess_provers: false,
+            require_registration: false,
+            share_accounting: ShareAccounting::Proportional,
+            recipients: Vec::new(),
+            private_key: None,
+            pool_fee_percent: 0.0,
+            payout_confirmations: 10,
+            payout_dry_run: false,
+            reset_stats_each_round: false,
+            block_hook: None,
+            orphan_confirmations: 10,
+            roll_over_orphaned_shares: false,
+            round_retention_depth: None,
+            invalid_share_limit: 10,
+            invalid_share_window: Duration::from_secs(600),
+            ban_duration: Duration::from_secs(86400),
+            maximum_addresses_per_ip: 32,
+            maximum_reject_rate: 0.5,
+            maximum_known_nonces: 1_000_000,
+            maximum_pending_verifications: 256,
+        }
+    }
+}
 
 ///
 /// An operator for a program on a specific network in the node server.
@@ -63,12 +576,124 @@ const HEARTBEAT_IN_SECONDS: Duration = Duration::from_secs(1);
 pub struct Operator<N: Network, E: Environment> {
     /// The state storage of the operator.
     operator_state: Arc<OperatorState<N, ReadWrite>>,
-    /// The current block template that is being mined on by the operator.
-    block_template: RwLock<Option<BlockTemplate<N>>>,
-    /// A list of provers and their associated state := (last_submitted, share_difficulty)
-    provers: RwLock<HashMap<Address<N>, (Instant, u64)>>,
-    /// A list of the known nonces for the current round.
-    known_nonces: RwLock<HashSet<N::PoSWNonce>>,
+    /// The current block template that is being mined on by the operator, and its header root.
+    block_template: RwLock<Option<(BlockTemplate<N>, N::BlockHeaderRoot)>>,
+    /// The previous block template, its header root, its known nonces, and the time it was replaced,
+    /// kept for the stale template grace period.
+    #[allow(clippy::type_complexity)]
+    stale_block_template: RwLock<Option<(BlockTemplate<N>, N::BlockHeaderRoot, CircularSet<N::PoSWNonce>, Instant)>>,
+    /// The duration after a block template is replaced during which shares for it are still accepted.
+    stale_template_grace: Duration,
+    /// A block template for the current block height that is deferred until the minimum template interval has elapsed.
+    pending_block_template: RwLock<Option<BlockTemplate<N>>>,
+    /// The minimum duration in between updates to the block template for the same block height.
+    minimum_template_interval: Duration,
+    /// The number of memory pool transactions missing from the block template at which it is rebuilt, or 0 to disable.
+    template_refresh_threshold: usize,
+    /// The age at which the block template is rebuilt if any memory pool transaction is missing from it, or 0 to disable.
+    template_refresh_age: Duration,
+    /// The maximum number of transactions in a block template, or 0 for no limit.
+    maximum_template_transactions: usize,
+    /// The maximum combined serialized size in bytes of the transactions in a block template, or 0 for no limit.
+    maximum_template_size: usize,
+    /// The minimum fee of a transaction to be included in a block template.
+    minimum_transaction_fee: u64,
+    /// The time of the last rebuild of the block template for new memory pool transactions.
+    last_template_refresh: RwLock<Option<Instant>>,
+    /// A list of provers, keyed by their address and worker name, and their associated state.
+    provers: RwLock<HashMap<(Address<N>, String), ProverInfo>>,
+    /// The time, block template header root, and share difficulty of the last `PoolRequest` sent to each peer.
+    pool_requests: RwLock<HashMap<SocketAddr, (Instant, N::BlockHeaderRoot, u64)>>,
+    /// The block template header root of the last share difficulty update pushed to each peer.
+    share_difficulty_pushes: RwLock<HashMap<SocketAddr, N::BlockHeaderRoot>>,
+    /// The minimum duration in between `PoolRequest`s to a peer, unless its template or share difficulty has changed.
+    pool_register_interval: Duration,
+    /// The number of registrations that were ignored for arriving within the pool register interval.
+    throttled_registrations: AtomicU64,
+    /// The number of times the share difficulty of a stalled prover has decayed.
+    share_difficulty_decays: AtomicU64,
+    /// The number of requests from each peer that have been forwarded to the operator, and not yet processed.
+    in_flight_requests: RwLock<HashMap<SocketAddr, usize>>,
+    /// The number of requests that were dropped for exceeding the in-flight limit of their peer.
+    dropped_requests: AtomicU64,
+    /// The address and worker name that each peer registered with.
+    workers: RwLock<HashMap<SocketAddr, (Address<N>, String)>>,
+    /// The maximum number of distinct prover addresses that may be registered at once.
+    maximum_provers: usize,
+    /// If `true`, a peer is disconnected when its prover is turned away for exceeding the maximum number of provers.
+    disconnect_excess_provers: bool,
+    /// If `true`, shares are rejected from provers that have not registered with a `PoolRegister`.
+    require_registration: bool,
+    /// The share difficulty that a prover is assigned when it first registers.
+    base_share_difficulty: u64,
+    /// The share difficulties that have been pinned for provers, which are exempt from retargeting.
+    pinned_share_difficulties: RwLock<HashMap<Address<N>, u64>>,
+    /// The minimum share difficulty that a prover may be retargeted to.
+    minimum_share_difficulty: u64,
+    /// The duration in between iterations of the operator update loop.
+    heartbeat: Duration,
+    /// The duration of inactivity after which a prover is evicted from the list of provers.
+    prover_timeout: Duration,
+    /// The addresses that the coinbase of each block template is rotated through.
+    recipients: Vec<Address<N>>,
+    /// The index of the recipient of the next block template.
+    next_recipient: AtomicUsize,
+    /// The private key of the operator, used to sign payout transactions.
+    private_key: Option<PrivateKey<N>>,
+    /// The percentage of each block reward that is kept by the operator.
+    pool_fee_percent: f64,
+    /// The number of confirmations a block requires before its round is paid out.
+    payout_confirmations: u32,
+    /// If `true`, payouts are computed and logged, but no transactions are created.
+    payout_dry_run: bool,
+    /// If `true`, the share counters of the provers are reset whenever a block is found.
+    reset_stats_each_round: bool,
+    /// The URL or command that is notified whenever the operator finds a block.
+    block_hook: Option<String>,
+    /// The number of blocks after a found block at which it is checked to be on the canonical chain.
+    orphan_confirmations: u32,
+    /// If `true`, the shares of an orphaned round are added to the current round, instead of being removed.
+    roll_over_orphaned_shares: bool,
+    /// The number of blocks for which paid and orphaned rounds are retained, if they are to be pruned.
+    round_retention_depth: Option<u32>,
+    /// The number of invalid shares within the invalid share window after which a prover is banned.
+    invalid_share_limit: usize,
+    /// The duration for which an invalid share counts towards the invalid share limit.
+    invalid_share_window: Duration,
+    /// The timestamps of the recent invalid shares submitted by each prover.
+    invalid_shares_by_prover: RwLock<HashMap<Address<N>, VecDeque<Instant>>>,
+    /// The timestamps of the recent invalid shares submitted from each IP address.
+    invalid_shares_by_ip: RwLock<HashMap<IpAddr, VecDeque<Instant>>>,
+    /// The provers that have been banned, and the reason for each ban.
+    banned_provers: RwLock<HashMap<Address<N>, BannedProver>>,
+    /// The IP addresses that have been banned, and the UNIX timestamp at which each ban expires.
+    banned_ips: RwLock<HashMap<IpAddr, i64>>,
+    /// The duration for which a prover or IP address remains banned.
+    ban_duration: Duration,
+    /// The registrations, shares, and prover addresses seen from each IP address.
+    peer_stats: RwLock<HashMap<IpAddr, PeerInfo<N>>>,
+    /// The maximum number of distinct prover addresses recently seen from an IP address before it is banned, or 0 for no limit.
+    maximum_addresses_per_ip: usize,
+    /// The maximum fraction of shares from an IP address that may be rejected before it is banned.
+    maximum_reject_rate: f64,
+    /// The timestamp of the last share accepted by the operator.
+    last_share_timestamp: RwLock<Option<i64>>,
+    /// The difficulty-weighted work of the shares accepted since the operator last found a block.
+    round_work: AtomicU64,
+    /// The time and share difficulty of each share accepted from each prover within the hashrate window.
+    accepted_shares: RwLock<HashMap<Address<N>, VecDeque<(Instant, u64)>>>,
+    /// A cache of the known nonces for the current round, including those of the shares pending verification.
+    /// Once the maximum number of known nonces is reached, the oldest nonces are forgotten from the cache,
+    /// but the nonces of accepted shares remain persisted for the round until the round changes.
+    known_nonces: RwLock<CircularSet<N::PoSWNonce>>,
+    /// The maximum number of known nonces that are cached for a block template.
+    maximum_known_nonces: usize,
+    /// The permits for shares pending verification, bounding the number of shares verified or queued at once.
+    verification_permits: Arc<Semaphore>,
+    /// The maximum number of shares pending verification.
+    maximum_pending_verifications: usize,
+    /// A flag indicating that the operator has shut down, and no longer processes requests.
+    is_shut_down: AtomicBool,
     /// The operator router of the node.
     operator_router: OperatorRouter<N>,
     /// The shared state of the owning node.
@@ -76,17 +701,150 @@ pub struct Operator<N: Network, E: Environment> {
 }
 
 impl<N: Network, E: Environment> Operator<N, E> {
-    /// Initializes a new instance of the operator, paired with its handler.
-    #[allow(clippy::too_many_arguments)]
-    pub async fn open<P: AsRef<Path> + Copy>(path: P, state: Arc<State<N, E>>) -> Result<(Self, mpsc::Receiver<OperatorRequest<N>>)> {
+    /// Initializes a new instance of the operator with the given configuration, paired with its handler.
+    pub async fn open<P: AsRef<Path> + Copy>(
+        path: P,
+        config: OperatorConfig<N>,
+        state: Arc<State<N, E>>,
+    ) -> Result<(Self, mpsc::Receiver<OperatorRequest<N>>)> {
+        let OperatorConfig {
+            base_share_difficulty,
+            minimum_share_difficulty,
+            heartbeat,
+            prover_timeout,
+            stale_template_grace,
+            minimum_template_interval,
+            template_refresh_threshold,
+            template_refresh_age,
+            maximum_template_transactions,
+            maximum_template_size,
+            minimum_transaction_fee,
+            pool_register_interval,
+            maximum_provers,
+            disconnect_excess_provers,
+            require_registration,
+            share_accounting,
+            recipients,
+            private_key,
+            pool_fee_percent,
+            payout_confirmations,
+            payout_dry_run,
+            reset_stats_each_round,
+            block_hook,
+            orphan_confirmations,
+            roll_over_orphaned_shares,
+            round_retention_depth,
+            invalid_share_limit,
+            invalid_share_window,
+            ban_duration,
+            maximum_addresses_per_ip,
+            maximum_reject_rate,
+            maximum_known_nonces,
+            maximum_pending_verifications,
+        } = config;
+
+        // Ensure the configuration is valid.
+        ensure!(base_share_difficulty > 0, "The base share difficulty must be greater than 0");
+        ensure!(
+            minimum_share_difficulty <= base_share_difficulty,
+            "The minimum share difficulty ({}) must not exceed the base share difficulty ({})",
+            minimum_share_difficulty,
+            base_share_difficulty
+        );
+        ensure!(!heartbeat.is_zero(), "The operator heartbeat must be greater than 0");
+        ensure!(maximum_provers > 0, "The maximum number of provers must be greater than 0");
+        if let Some(round_retention_depth) = round_retention_depth {
+            ensure!(
+                round_retention_depth >= orphan_confirmations,
+                "The round retention depth ({}) must not be less than the orphan confirmations ({})",
+                round_retention_depth,
+                orphan_confirmations
+            );
+        }
+        ensure!(
+            (0.0..=100.0).contains(&pool_fee_percent),
+            "The pool fee must be a percentage between 0 and 100, found {}",
+            pool_fee_percent
+        );
+        ensure!(
+            (0.0..=1.0).contains(&maximum_reject_rate),
+            "The maximum reject rate must be a fraction between 0 and 1, found {}",
+            maximum_reject_rate
+        );
+        ensure!(maximum_pending_verifications > 0, "The maximum number of pending verifications must be greater than 0");
+
+        // Open the operator state, and determine if the previous run of the operator shut down cleanly.
+        let operator_state = OperatorState::open::<RocksDB, P>(path, share_accounting, base_share_difficulty)?;
+        if operator_state.mark_running()? {
+            warn!("The operator did not shut down cleanly on its last run, so its most recent shares may have been lost");
+        }
+
         // Initialize an mpsc channel for sending requests to the `Operator` struct.
         let (operator_router, operator_handler) = mpsc::channel(1024);
         // Initialize the operator.
         let operator = Self {
-            operator_state: Arc::new(OperatorState::open::<RocksDB, P>(path)?),
+            operator_state: Arc::new(operator_state),
             block_template: RwLock::new(None),
+            stale_block_template: RwLock::new(None),
+            stale_template_grace,
+            pending_block_template: RwLock::new(None),
+            minimum_template_interval,
+            template_refresh_threshold,
+            template_refresh_age,
+            maximum_template_transactions,
+            maximum_template_size,
+            minimum_transaction_fee,
+            last_template_refresh: Default::default(),
             provers: Default::default(),
-            known_nonces: Default::default(),
+            pool_requests: Default::default(),
+            share_difficulty_pushes: Default::default(),
+            pool_register_interval,
+            throttled_registrations: Default::default(),
+            share_difficulty_decays: Default::default(),
+            in_flight_requests: Default::default(),
+            dropped_requests: Default::default(),
+            workers: Default::default(),
+            maximum_provers,
+            disconnect_excess_provers,
+            require_registration,
+            base_share_difficulty,
+            pinned_share_difficulties: Default::default(),
+            minimum_share_difficulty,
+            heartbeat,
+            prover_timeout,
+            // Without a list of recipients, the coinbase of every block template is sent to the operator's address.
+            recipients: match recipients.is_empty() {
+                true => state.address.into_iter().collect(),
+                false => recipients,
+            },
+            next_recipient: Default::default(),
+            private_key,
+            pool_fee_percent,
+            payout_confirmations,
+            payout_dry_run,
+            reset_stats_each_round,
+            block_hook,
+            orphan_confirmations,
+            roll_over_orphaned_shares,
+            round_retention_depth,
+            invalid_share_limit,
+            invalid_share_window,
+            invalid_shares_by_prover: Default::default(),
+            invalid_shares_by_ip: Default::default(),
+            banned_provers: Default::default(),
+            banned_ips: Default::default(),
+            ban_duration,
+            peer_stats: Default::default(),
+            maximum_addresses_per_ip,
+            maximum_reject_rate,
+            last_share_timestamp: Default::default(),
+            round_work: Default::default(),
+            accepted_shares: Default::default(),
+            known_nonces: RwLock::new(CircularSet::with_capacity(maximum_known_nonces)),
+            maximum_known_nonces,
+            verification_permits: Arc::new(Semaphore::new(maximum_pending_verifications)),
+            maximum_pending_verifications,
+            is_shut_down: AtomicBool::new(false),
             operator_router,
             state,
         };
@@ -96,7 +854,7 @@ impl<N: Network, E: Environment> Operator<N, E> {
 
     pub async fn initialize(&self) {
         if E::NODE_TYPE == NodeType::Validator {
-            if let Some(recipient) = self.state.address {
+            if !self.recipients.is_empty() {
                 // Initialize an update loop for the block template.
                 let state = self.state.clone();
                 let (router, handler) = oneshot::channel();
@@ -106,51 +864,116 @@ impl<N: Network, E: Environment> Operator<N, E> {
                         let operator = &state.operator();
                         // Notify the outer function that the task is ready.
                         let _ = router.send(());
-                        // TODO (julesdesmit): Add logic to the loop to retarget share difficulty.
-                        loop {
-                            // Determine if the current block template is stale.
+                        // Initialize the timestamps of the last retarget, eviction sweep, prune, and hashrate log.
+                        let (mut last_retarget, mut last_eviction, mut last_prune) = (Instant::now(), Instant::now(), Instant::now());
+                        let mut last_stats_log = Instant::now();
+                        // Initialize the block height at which the found blocks were last checked.
+                        let mut last_checked_block_height = None;
+                        // Subscribe to the ledger events, to detect the found blocks that are orphaned by a reorg.
+                        let mut ledger_events = operator.state.ledger_events();
+                        // Subscribe to the canon tip, to update the block template as soon as the ledger advances.
+                        let mut canon_tip = operator.state.canon_tip();
+                        // Stop updating the block template once the operator has shut down.
+                        while !operator.is_shut_down.load(Ordering::SeqCst) {
+                            // Apply the deferred block template, if the minimum template interval has elapsed.
+                            operator.apply_pending_block_template().await;
+
+                            // Determine if the current block template is stale. The canon tip changes whenever the ledger
+                            // adds a block, from the network or the operator, or reverts, so the block template is rebuilt
+                            // on the new tip without the block-found paths having to invalidate it. The change is marked as seen,
+                            // and the latest block height and block hash are read as one snapshot, so that they are never torn.
+                            canon_tip.borrow_and_update();
+                            let (latest_block_height, latest_block_hash, _) = operator.state.ledger().latest_tip();
+                            let latest_block = (latest_block_height, latest_block_hash);
                             let is_block_template_stale = match &*operator.block_template.read().await {
-                                Some(template) => {
-                                    operator.state.ledger().reader().latest_block_height().saturating_add(1) != template.block_height()
+                                Some((template, _)) => {
+                                    !extends_canon_tip(template.block_height(), template.previous_block_hash(), latest_block)
                                 }
                                 None => true,
                             };
 
-                            // Update the block template if it is stale.
-                            if is_block_template_stale {
-                                // Construct a new block template.
-                                let transactions = operator.state.prover().memory_pool().read().await.transactions();
-                                let ledger_reader = operator.state.ledger().reader().clone();
-                                let result = tokio::task::spawn_blocking(move || {
-                                    E::thread_pool().install(move || {
-                                        match ledger_reader.get_block_template(
-                                            recipient,
-                                            E::COINBASE_IS_PUBLIC,
-                                            &transactions,
-                                            &mut rand::thread_rng(),
-                                        ) {
-                                            Ok(block_template) => Ok(block_template),
-                                            Err(error) => Err(format!("Failed to produce a new block template: {}", error)),
-                                        }
-                                    })
-                                })
-                                .await;
-
-                                // Update the block template.
-                                match result {
-                                    Ok(Ok(block_template)) => {
-                                        // Acquire the write lock to update the block template.
-                                        *operator.block_template.write().await = Some(block_template);
-                                        // Clear the set of known nonces.
-                                        operator.known_nonces.write().await.clear();
-                                    }
-                                    Ok(Err(error_message)) => error!("{}", error_message),
+                            // Update the block template if it is stale, or if the memory pool has new transactions for it.
+                            if is_block_template_stale || operator.has_new_transactions().await {
+                                match operator.build_block_template().await {
+                                    Ok(block_template) => operator.propose_block_template(block_template).await,
                                     Err(error) => error!("{}", error),
                                 };
                             }
 
-                            // Proceed to sleep for a preset amount of time.
-                            tokio::time::sleep(HEARTBEAT_IN_SECONDS).await;
+                            // Retarget the share difficulty of the provers, if the retarget interval has elapsed.
+                            if last_retarget.elapsed() >= RETARGET_INTERVAL {
+                                operator.retarget_share_difficulties().await;
+                                last_retarget = Instant::now();
+                            }
+
+                            // Decay the share difficulty of the provers that have stopped submitting shares.
+                            operator.decay_share_difficulties().await;
+
+                            // Evict the inactive provers, if the eviction interval has elapsed.
+                            if last_eviction.elapsed() >= EVICTION_INTERVAL {
+                                operator.evict_inactive_provers().await;
+                                last_eviction = Instant::now();
+                            }
+
+                            // Log the estimated pool hashrate, round progress, and luck, if the stats log interval has elapsed.
+                            if last_stats_log.elapsed() >= STATS_LOG_INTERVAL {
+                                let number_of_provers = operator.accepted_shares.read().await.len();
+                                info!(
+                                    "Estimated pool hashrate is {:.2} proofs/s across {} provers",
+                                    operator.get_estimated_pool_hashrate().await,
+                                    number_of_provers
+                                );
+                                let luck = match operator.get_average_luck() {
+                                    Some(luck) => format!("{:.1}%", luck * 100.0),
+                                    None => "unknown".to_string(),
+                                };
+                                info!(
+                                    "Round progress is {:.1}%, and luck over the last {} blocks is {}",
+                                    operator.get_round_progress().await * 100.0,
+                                    LUCK_WINDOW,
+                                    luck
+                                );
+                                last_stats_log = Instant::now();
+                            }
+
+                            // Prune the rounds outside the retention depth, if the prune interval has elapsed.
+                            if last_prune.elapsed() >= PRUNE_INTERVAL {
+                                operator.prune_rounds().await;
+                                last_prune = Instant::now();
+                            }
+
+                            // Determine if the ledger has reorganized, as a reorg may orphan found blocks without changing the height.
+                            let mut is_reorg = false;
+                            loop {
+                                match ledger_events.try_recv() {
+                                    Ok(LedgerEvent::Reorg { common_ancestor, disconnected, .. }) => {
+                                        debug!("Ledger reorganized {} blocks from block {}", disconnected.len(), common_ancestor.0);
+                                        is_reorg = true;
+                                    }
+                                    Ok(LedgerEvent::BlockAdded { .. }) => (),
+                                    // Conservatively assume a reorg if ledger events were missed.
+                                    Err(broadcast::error::TryRecvError::Lagged(_)) => is_reorg = true,
+                                    Err(_) => break,
+                                }
+                            }
+
+                            // Check the found blocks for orphans, if the ledger has advanced or reorganized.
+                            let (latest_block_height, ..) = operator.state.ledger().latest_tip();
+                            if is_reorg || last_checked_block_height != Some(latest_block_height) {
+                                operator.check_blocks_found(latest_block_height).await;
+                                last_checked_block_height = Some(latest_block_height);
+                            }
+
+                            // Proceed to sleep for a preset amount of time, or until the canon chain advances or reverts.
+                            tokio::select! {
+                                result = canon_tip.changed() => {
+                                    // If the ledger has shut down, fall back to the heartbeat.
+                                    if result.is_err() {
+                                        tokio::time::sleep(operator.heartbeat).await;
+                                    }
+                                }
+                                _ = tokio::time::sleep(operator.heartbeat) => (),
+                            }
                         }
                     }),
                 );
@@ -168,6 +991,21 @@ impl<N: Network, E: Environment> Operator<N, E> {
         &self.operator_router
     }
 
+    ///
+    /// Shuts down the operator, once the requests queued before this call have been processed.
+    ///
+    pub async fn shut_down(&self) {
+        debug!("Operator is shutting down...");
+
+        let (router, handler) = oneshot::channel();
+        if let Err(error) = self.operator_router.send(OperatorRequest::Shutdown(router)).await {
+            warn!("[ShuttingDown] {}", error);
+            return;
+        }
+        // Wait until the operator has processed the shutdown request.
+        let _ = handler.await;
+    }
+
     /// Returns all the shares in storage.
     pub fn to_shares(&self) -> Vec<((u32, Record<N>), HashMap<Address<N>, u64>)> {
         self.operator_state.to_shares()
@@ -190,108 +1028,1958 @@ impl<N: Network, E: Environment> Operator<N, E> {
         self.operator_state.get_provers()
     }
 
+    /// Returns the addresses that the coinbase of each block template is rotated through.
+    pub fn recipients(&self) -> &[Address<N>] {
+        &self.recipients
+    }
+
+    /// Returns the recipient of the coinbase of each round, by block height.
+    pub fn get_round_recipients(&self) -> Vec<(u32, Address<N>)> {
+        self.operator_state.to_round_recipients()
+    }
+
+    /// Returns the block height of the current block template, if one exists.
+    pub async fn block_template_height(&self) -> Option<u32> {
+        self.block_template.read().await.as_ref().map(|(template, _)| template.block_height())
+    }
+
+    /// Returns the number of provers that are currently registered with the operator.
+    pub async fn number_of_provers(&self) -> usize {
+        self.provers.read().await.keys().map(|(address, _)| address).collect::<HashSet<_>>().len()
+    }
+
+    /// Returns the shares of each prover for the round of the current block template.
+    pub async fn get_current_round_shares(&self) -> HashMap<Address<N>, u64> {
+        match &*self.block_template.read().await {
+            Some((template, _)) => self
+                .operator_state
+                .get_shares_for_block(template.block_height(), template.coinbase_record().clone())
+                .unwrap_or_default(),
+            None => Default::default(),
+        }
+    }
+
+    /// Returns the statistics of each registered worker.
+    pub async fn get_workers(&self) -> Vec<WorkerStats<N>> {
+        self.provers
+            .read()
+            .await
+            .iter()
+            .map(|((address, worker), prover)| WorkerStats {
+                address: *address,
+                worker: worker.clone(),
+                share_difficulty: prover.share_difficulty,
+                hashrate: prover.hashrate(),
+                last_seen: prover.last_seen(),
+            })
+            .collect()
+    }
+
+    /// Returns the percentage of each block reward that is kept by the operator.
+    pub fn pool_fee_percent(&self) -> f64 {
+        self.pool_fee_percent
+    }
+
+    /// Returns the timestamp of the last share accepted by the operator, if one has been accepted.
+    pub async fn last_share_timestamp(&self) -> Option<i64> {
+        *self.last_share_timestamp.read().await
+    }
+
+    /// Returns the number of registrations that were ignored for arriving within the pool register interval.
+    pub fn throttled_registrations(&self) -> u64 {
+        self.throttled_registrations.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of times the share difficulty of a stalled prover has decayed.
+    pub fn share_difficulty_decays(&self) -> u64 {
+        self.share_difficulty_decays.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of requests that were dropped for exceeding the in-flight limit of their peer.
+    pub fn dropped_requests(&self) -> u64 {
+        self.dropped_requests.load(Ordering::SeqCst)
+    }
+
     ///
-    /// Performs the given `request` to the operator.
-    /// All requests must go through this `update`, so that a unified view is preserved.
+    /// Reserves an in-flight request for the given peer, returning `false` if the peer has reached
+    /// `MAXIMUM_IN_FLIGHT_REQUESTS_PER_PEER`, in which case the request must be dropped by the caller.
+    /// Each reservation is released once the operator has processed the request, or by the caller if it is not sent.
     ///
-    pub(super) async fn update(&self, request: OperatorRequest<N>) {
-        match request {
-            OperatorRequest::PoolRegister(peer_ip, address) => {
-                if let Some(block_template) = self.block_template.read().await.clone() {
-                    // Ensure this prover exists in the list first, and retrieve their share difficulty.
-                    let share_difficulty = self
-                        .provers
-                        .write()
-                        .await
-                        .entry(address)
-                        .or_insert((Instant::now(), BASE_SHARE_DIFFICULTY))
-                        .1;
-
-                    // Route a `PoolRequest` to the peer.
-                    let message = Message::PoolRequest(share_difficulty, Data::Object(block_template));
-                    if let Err(error) = self.state.peers().router().send(PeersRequest::MessageSend(peer_ip, message)).await {
-                        warn!("[PoolRequest] {}", error);
-                    }
-                } else {
-                    warn!("[PoolRegister] No current block template exists");
-                }
+    pub async fn reserve_request(&self, peer_ip: SocketAddr) -> bool {
+        let mut in_flight_requests = self.in_flight_requests.write().await;
+        let in_flight = in_flight_requests.entry(peer_ip).or_default();
+        if *in_flight >= MAXIMUM_IN_FLIGHT_REQUESTS_PER_PEER {
+            let num_dropped = self.dropped_requests.fetch_add(1, Ordering::SeqCst) + 1;
+            warn!(
+                "Dropping a request from {} beyond its limit of {} in-flight requests ({} dropped in total)",
+                peer_ip, MAXIMUM_IN_FLIGHT_REQUESTS_PER_PEER, num_dropped
+            );
+            return false;
+        }
+        *in_flight += 1;
+        true
+    }
+
+    /// Releases an in-flight request for the given peer.
+    pub async fn release_request(&self, peer_ip: SocketAddr) {
+        let mut in_flight_requests = self.in_flight_requests.write().await;
+        if let Some(in_flight) = in_flight_requests.get_mut(&peer_ip) {
+            *in_flight = in_flight.saturating_sub(1);
+            if *in_flight == 0 {
+                in_flight_requests.remove(&peer_ip);
             }
-            OperatorRequest::PoolResponse(peer_ip, prover, nonce, proof) => {
-                if let Some(block_template) = self.block_template.read().await.clone() {
-                    // Ensure the given nonce from the prover is new.
-                    if self.known_nonces.read().await.contains(&nonce) {
-                        warn!("[PoolResponse] Peer {} sent a duplicate share", peer_ip);
-                        // TODO (julesdesmit): punish?
-                        return;
-                    }
+        }
+    }
+
+    ///
+    /// Returns `true` if a share with the given nonce may be accepted, which is checked before its proof is deserialized.
+    /// A share is turned away if there is no block template, or if its nonce is a known duplicate.
+    ///
+    pub async fn precheck_share(&self, prover: &Address<N>, nonce: &N::PoSWNonce) -> bool {
+        let round = match &*self.block_template.read().await {
+            Some((block_template, _)) => (block_template.block_height(), block_template.coinbase_record().commitment()),
+            None => return false,
+        };
+        let is_stale_nonce = match &*self.stale_block_template.read().await {
+            Some((template, _, known_nonces, _)) => self.is_known_nonce(template, known_nonces, nonce),
+            None => false,
+        };
+        if is_stale_nonce || self.is_known_nonce_in_round(round, &*self.known_nonces.read().await, nonce) {
+            self.record_share_outcome(prover, ShareOutcome::Duplicate);
+            return false;
+        }
+        true
+    }
 
-                    // Update known nonces.
-                    self.known_nonces.write().await.insert(nonce);
+    ///
+    /// Returns `true` if a share with the given nonce was already received for the given block template.
+    ///
+    fn is_known_nonce(&self, block_template: &BlockTemplate<N>, known_nonces: &CircularSet<N::PoSWNonce>, nonce: &N::PoSWNonce) -> bool {
+        let round = (block_template.block_height(), block_template.coinbase_record().commitment());
+        self.is_known_nonce_in_round(round, known_nonces, nonce)
+    }
 
-                    // Retrieve the share difficulty for the given prover.
-                    let share_difficulty = {
-                        let provers = self.provers.read().await.clone();
-                        match provers.get(&prover) {
-                            Some((_, share_difficulty)) => *share_difficulty,
-                            None => {
-                                self.provers.write().await.insert(prover, (Instant::now(), BASE_SHARE_DIFFICULTY));
-                                BASE_SHARE_DIFFICULTY
-                            }
-                        }
-                    };
+    ///
+    /// Returns `true` if a share with the given nonce was already received for the round of the given block height and
+    /// coinbase commitment. As the cache of known nonces is bounded, the nonces persisted for the round are checked as well,
+    /// so that a share is never accepted twice within a round.
+    ///
+    fn is_known_nonce_in_round(&self, round: (u32, N::Commitment), known_nonces: &CircularSet<N::PoSWNonce>, nonce: &N::PoSWNonce) -> bool {
+        if known_nonces.contains(nonce) {
+            return true;
+        }
+        let (block_height, coinbase_commitment) = round;
+        match self.operator_state.contains_round_nonce(block_height, coinbase_commitment, *nonce) {
+            Ok(is_known) => is_known,
+            Err(error) => {
+                warn!("Failed to check the persisted nonces for block {} - {}", block_height, error);
+                false
+            }
+        }
+    }
 
-                    // Ensure the share difficulty target is met, and the PoSW proof is valid.
-                    let block_height = block_template.block_height();
-                    if !N::posw().verify(
-                        block_height,
-                        share_difficulty,
-                        &[*block_template.to_header_root().unwrap(), *nonce],
-                        &proof,
-                    ) {
-                        warn!("[PoolResponse] PoSW proof verification failed");
-                        return;
-                    }
+    ///
+    /// Returns the estimated number of proofs per second computed by the given prover, from the shares accepted
+    /// within the hashrate window. Each share is weighted by the share difficulty it was solved at.
+    ///
+    pub async fn get_estimated_hashrate(&self, prover: &Address<N>) -> f64 {
+        match self.accepted_shares.read().await.get(prover) {
+            Some(shares) => estimate_hashrate(shares),
+            None => 0.0,
+        }
+    }
 
-                    // Update the internal state for this prover.
-                    if let Some(ref mut prover) = self.provers.write().await.get_mut(&prover) {
-                        prover.0 = Instant::now();
-                    } else {
-                        error!("Prover should have existing info");
-                        return;
-                    }
+    /// Returns the estimated number of proofs per second computed by all provers.
+    pub async fn get_estimated_pool_hashrate(&self) -> f64 {
+        self.accepted_shares.read().await.values().map(estimate_hashrate).sum()
+    }
 
-                    // Increment the share count for the prover.
-                    let coinbase_record = block_template.coinbase_record().clone();
-                    match self.operator_state.increment_share(block_height, coinbase_record, &prover) {
-                        Ok(..) => info!(
-                            "Operator has received a valid share from {} ({}) for block {}",
-                            prover, peer_ip, block_height,
-                        ),
-                        Err(error) => error!("{}", error),
-                    }
+    /// Returns the estimated number of proofs per second computed by each prover.
+    pub async fn get_estimated_hashrates(&self) -> HashMap<Address<N>, f64> {
+        self.accepted_shares.read().await.iter().map(|(prover, shares)| (*prover, estimate_hashrate(shares))).collect()
+    }
 
-                    // If the block has satisfactory difficulty and is valid, proceed to broadcast it.
-                    let previous_block_hash = block_template.previous_block_hash();
-                    let transactions = block_template.transactions().clone();
-                    if let Ok(block_header) = BlockHeader::<N>::from(
-                        block_template.previous_ledger_root(),
-                        block_template.transactions().transactions_root(),
-                        BlockHeaderMetadata::new(&block_template),
-                        nonce,
-                        proof,
-                    ) {
-                        if let Ok(block) = Block::from(previous_block_hash, block_header, transactions) {
-                            info!("Operator has found unconfirmed block {} ({})", block.height(), block.hash());
-                            let request = LedgerRequest::UnconfirmedBlock(self.state.local_ip, block);
-                            if let Err(error) = self.state.ledger().router().send(request).await {
-                                warn!("Failed to broadcast mined block - {}", error);
-                            }
-                        }
-                    }
-                } else {
-                    warn!("[PoolResponse] No current block template exists");
-                }
+    ///
+    /// Returns the work of the shares accepted since the operator last found a block, as a fraction of the work
+    /// that is expected to find a block at the difficulty target of the current block template.
+    ///
+    pub async fn get_round_progress(&self) -> f64 {
+        match &*self.block_template.read().await {
+            Some((template, _)) => self.round_work.load(Ordering::SeqCst) as f64 / expected_work(template) as f64,
+            None => 0.0,
+        }
+    }
+
+    ///
+    /// Returns the average luck of the most recent rounds that found a block, up to `LUCK_WINDOW` rounds,
+    /// where the luck of a round is its expected work over its actual work. A luck above 1 is better than expected.
+    ///
+    pub fn get_average_luck(&self) -> Option<f64> {
+        let luck: Vec<f64> = self
+            .operator_state
+            .get_recent_round_work(LUCK_WINDOW)
+            .into_iter()
+            .filter(|(_, (work, _))| *work > 0)
+            .map(|(_, (work, expected_work))| expected_work as f64 / work as f64)
+            .collect();
+        match luck.is_empty() {
+            true => None,
+            false => Some(luck.iter().sum::<f64>() / luck.len() as f64),
+        }
+    }
+
+    /// Returns the blocks found by the operator, in order of block height.
+    pub fn get_blocks_found(&self) -> Vec<(u32, BlockFound<N>)> {
+        self.operator_state.get_blocks_found()
+    }
+
+    /// Returns the blocks found by the given prover, in order of block height.
+    pub fn get_blocks_found_by_prover(&self, prover: &Address<N>) -> Vec<(u32, BlockFound<N>)> {
+        self.get_blocks_found().into_iter().filter(|(_, block_found)| block_found.prover == *prover).collect()
+    }
+
+    /// Returns the number of shares submitted by the given prover, by outcome.
+    pub fn get_stats_for_prover(&self, prover: &Address<N>) -> Result<ProverStats> {
+        self.operator_state.get_stats_for_prover(prover)
+    }
+
+    /// Returns the number of shares submitted by each prover, by outcome.
+    pub fn get_prover_stats(&self) -> HashMap<Address<N>, ProverStats> {
+        self.operator_state.to_prover_stats().into_iter().collect()
+    }
+
+    /// Returns the provers that have been banned by the operator.
+    pub async fn get_banned_provers(&self) -> HashMap<Address<N>, BannedProver> {
+        self.banned_provers.read().await.clone()
+    }
+
+    /// Returns the registrations, shares, and prover addresses seen from each IP address.
+    pub async fn get_peer_stats(&self) -> HashMap<IpAddr, PeerStats<N>> {
+        let banned_ips = self.banned_ips.read().await;
+        self.peer_stats
+            .read()
+            .await
+            .iter()
+            .map(|(ip, peer)| {
+                (*ip, PeerStats {
+                    registrations: peer.registrations,
+                    accepted_shares: peer.accepted_shares,
+                    rejected_shares: peer.rejected_shares,
+                    addresses: peer.addresses.keys().copied().collect(),
+                    is_banned: banned_ips.contains_key(ip),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the share difficulty that a new worker of the given prover is assigned.
+    async fn initial_share_difficulty(&self, prover: &Address<N>) -> u64 {
+        self.pinned_share_difficulties.read().await.get(prover).copied().unwrap_or(self.base_share_difficulty)
+    }
+
+    ///
+    /// Returns `true` if the given prover is already registered, or there is room for it to register.
+    /// Otherwise, the prover is turned away, and its peer is disconnected if configured to.
+    ///
+    async fn has_capacity_for(&self, peer_ip: SocketAddr, prover: &Address<N>) -> bool {
+        {
+            let provers = self.provers.read().await;
+            if provers.keys().any(|(address, _)| address == prover) {
+                return true;
+            }
+            let number_of_provers = provers.keys().map(|(address, _)| address).collect::<HashSet<_>>().len();
+            if number_of_provers < self.maximum_provers {
+                return true;
             }
         }
+
+        warn!("Turning away prover {} ({}), as the pool has reached its maximum of {} provers", prover, peer_ip, self.maximum_provers);
+        if self.disconnect_excess_provers {
+            let request = PeersRequest::MessageSend(peer_ip, Message::Disconnect(DisconnectReason::TooManyPeers));
+            if let Err(error) = self.state.peers().router().send(request).await {
+                warn!("[Disconnect] {}", error);
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if the given prover or the IP address it connects from is currently banned.
+    async fn is_banned(&self, peer_ip: SocketAddr, prover: &Address<N>) -> bool {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let is_prover_banned = self.banned_provers.read().await.get(prover).map_or(false, |ban| ban.banned_until > now);
+        is_prover_banned || self.banned_ips.read().await.get(&peer_ip.ip()).map_or(false, |banned_until| *banned_until > now)
+    }
+
+    /// Returns the UNIX timestamp at which a ban issued now expires.
+    fn ban_expiry(&self) -> i64 {
+        OffsetDateTime::now_utc().unix_timestamp().saturating_add(self.ban_duration.as_secs() as i64)
+    }
+
+    ///
+    /// Lifts the ban on the given prover, and on the IP address it was banned from.
+    /// Returns `true` if the prover was banned.
+    ///
+    pub async fn unban_prover(&self, prover: &Address<N>) -> bool {
+        match self.banned_provers.write().await.remove(prover) {
+            Some(ban) => {
+                self.unban_ip(ban.peer_ip.ip()).await;
+                self.invalid_shares_by_prover.write().await.remove(prover);
+                info!("Lifted the ban on prover {}", prover);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Lifts the ban on the given IP address. Returns `true` if the IP address was banned.
+    pub async fn unban_ip(&self, ip: IpAddr) -> bool {
+        let is_banned = self.banned_ips.write().await.remove(&ip).is_some();
+        if is_banned {
+            // Reset the statistics of the IP address, so that it is not immediately banned again.
+            self.invalid_shares_by_ip.write().await.remove(&ip);
+            self.peer_stats.write().await.remove(&ip);
+            info!("Lifted the ban on IP address {}", ip);
+        }
+        is_banned
+    }
+
+    ///
+    /// Records activity from the given prover at the IP address of the given peer, applying the given update to its statistics.
+    /// The IP address is banned if too many distinct prover addresses have recently been seen from it, or if too many of its
+    /// shares have been rejected. The thresholds leave room for provers that legitimately share an IP address behind a NAT.
+    ///
+    async fn record_peer_activity(&self, peer_ip: SocketAddr, prover: Address<N>, update: impl FnOnce(&mut PeerInfo<N>)) {
+        let reason = {
+            let mut peer_stats = self.peer_stats.write().await;
+            let peer = peer_stats.entry(peer_ip.ip()).or_insert_with(PeerInfo::new);
+            peer.record(prover, self.prover_timeout);
+            update(peer);
+
+            let number_of_addresses = peer.addresses.len();
+            if self.maximum_addresses_per_ip > 0 && number_of_addresses > self.maximum_addresses_per_ip {
+                Some(format!("{} distinct prover addresses", number_of_addresses))
+            } else {
+                match peer.reject_rate() {
+                    Some(reject_rate) if reject_rate > self.maximum_reject_rate => {
+                        Some(format!("{:.1}% of {} shares rejected", reject_rate * 100.0, peer.accepted_shares + peer.rejected_shares))
+                    }
+                    _ => None,
+                }
+            }
+        };
+
+        if let Some(reason) = reason {
+            // Ensure the IP address is only banned once.
+            if self.banned_ips.write().await.insert(peer_ip.ip(), self.ban_expiry()).is_none() {
+                warn!("Banning IP address {} - {}", peer_ip.ip(), reason);
+                self.workers.write().await.retain(|worker_ip, _| worker_ip.ip() != peer_ip.ip());
+
+                // Disconnect and restrict the peer.
+                if let Err(error) = self.state.peers().router().send(PeersRequest::PeerRestricted(peer_ip)).await {
+                    warn!("[PeerRestricted] {}", error);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Records an invalid share from the given prover, and bans the prover and its IP address
+    /// once either has submitted `invalid_share_limit` invalid shares within the invalid share window.
+    ///
+    async fn record_invalid_share(&self, peer_ip: SocketAddr, prover: Address<N>, reason: &str) {
+        self.record_peer_activity(peer_ip, prover, |peer| peer.rejected_shares += 1).await;
+
+        let now = Instant::now();
+        let window = self.invalid_share_window;
+        let record = |timestamps: &mut VecDeque<Instant>| {
+            timestamps.push_back(now);
+            // Forget the invalid shares that have fallen out of the window.
+            while timestamps.front().map_or(false, |timestamp| now.duration_since(*timestamp) > window) {
+                timestamps.pop_front();
+            }
+            timestamps.len()
+        };
+
+        let num_by_prover = record(self.invalid_shares_by_prover.write().await.entry(prover).or_default());
+        let num_by_ip = record(self.invalid_shares_by_ip.write().await.entry(peer_ip.ip()).or_default());
+
+        if num_by_prover.max(num_by_ip) >= self.invalid_share_limit {
+            let reason = format!("{} ({} invalid shares in {} seconds)", reason, num_by_prover.max(num_by_ip), window.as_secs());
+            warn!("Banning prover {} ({}) - {}", prover, peer_ip, reason);
+
+            self.banned_provers.write().await.insert(prover, BannedProver {
+                peer_ip,
+                reason,
+                timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+                banned_until: self.ban_expiry(),
+            });
+            self.banned_ips.write().await.insert(peer_ip.ip(), self.ban_expiry());
+            self.provers.write().await.retain(|(address, _), _| *address != prover);
+
+            // Disconnect and restrict the peer.
+            if let Err(error) = self.state.peers().router().send(PeersRequest::PeerRestricted(peer_ip)).await {
+                warn!("[PeerRestricted] {}", error);
+            }
+        }
+    }
+
+    ///
+    /// Sends a `PoolRequest` with the given share difficulty and block template to the given peer,
+    /// and records the send, so that redundant registrations from the peer can be throttled.
+    ///
+    async fn send_pool_request(
+        &self,
+        peer_ip: SocketAddr,
+        share_difficulty: u64,
+        block_template: BlockTemplate<N>,
+        header_root: N::BlockHeaderRoot,
+    ) {
+        self.pool_requests.write().await.insert(peer_ip, (Instant::now(), header_root, share_difficulty));
+
+        // Route a `PoolRequest` to the peer.
+        let message = Message::PoolRequest(share_difficulty, Data::Object(block_template));
+        if let Err(error) = self.state.peers().router().send(PeersRequest::MessageSend(peer_ip, message)).await {
+            warn!("[PoolRequest] {}", error);
+        }
+    }
+
+    ///
+    /// Constructs a new block template on the latest block, for the next recipient, from the memory pool
+    /// transactions that the template policy permits. The block template is constructed off the async runtime.
+    ///
+    async fn build_block_template(&self) -> Result<BlockTemplate<N>> {
+        let recipient = self.next_recipient();
+        let transactions = self.state.prover().memory_pool().read().await.transactions();
+        let (transactions, num_low_fee, num_over_capacity) = self.select_template_transactions(transactions);
+        if num_low_fee > 0 || num_over_capacity > 0 {
+            debug!(
+                "Excluded {} transactions below the minimum fee and {} beyond the template capacity ({} included)",
+                num_low_fee,
+                num_over_capacity,
+                transactions.len()
+            );
+        }
+
+        let ledger_reader = self.state.ledger().reader().clone();
+        tokio::task::spawn_blocking(move || {
+            E::thread_pool().install(move || {
+                ledger_reader
+                    .get_block_template(recipient, E::COINBASE_IS_PUBLIC, &transactions, &mut thread_rng())
+                    .map_err(|error| anyhow!("Failed to produce a new block template: {}", error))
+            })
+        })
+        .await?
+    }
+
+    ///
+    /// Sends the current block template to every registered worker, at its current share difficulty, so that
+    /// the provers switch to it without waiting to register again. Peers that have disconnected are forgotten.
+    ///
+    async fn broadcast_block_template(&self) {
+        let (block_template, header_root) = match self.block_template.read().await.clone() {
+            Some(block_template) => block_template,
+            None => return,
+        };
+
+        let workers: Vec<_> = self.workers.read().await.iter().map(|(peer_ip, key)| (*peer_ip, key.clone())).collect();
+        for (peer_ip, key) in workers {
+            // Forget the peers that have disconnected since they registered.
+            if !self.state.peers().is_connected_to(peer_ip).await {
+                trace!("[PoolRequest] Forgetting disconnected prover {} ({})", key.0, peer_ip);
+                self.workers.write().await.remove(&peer_ip);
+                self.pool_requests.write().await.remove(&peer_ip);
+                self.share_difficulty_pushes.write().await.remove(&peer_ip);
+                continue;
+            }
+
+            let share_difficulty = match self.provers.read().await.get(&key) {
+                Some(prover) => prover.share_difficulty,
+                None => continue,
+            };
+            self.send_pool_request(peer_ip, share_difficulty, block_template.clone(), header_root).await;
+        }
+    }
+
+    ///
+    /// Proposes the given block template to replace the current one. The block template is discarded if it
+    /// has the same block height, previous block hash, and transactions other than its coinbase as the current one. A block template
+    /// for the same block height is deferred until the minimum template interval has elapsed since the last
+    /// update, so that changes to the memory pool do not constantly restart the provers.
+    ///
+    async fn propose_block_template(&self, block_template: BlockTemplate<N>) {
+        if let Some((current, _)) = &*self.block_template.read().await {
+            let is_same_block_height = current.block_height() == block_template.block_height();
+            if is_same_block_height && is_same_block_template(current, &block_template) {
+                trace!("Skipping an unchanged block template for block {}", block_template.block_height());
+                return;
+            }
+
+            let last_update = self.stale_block_template.read().await.as_ref().map(|(_, _, _, replaced_at)| replaced_at.elapsed());
+            if is_same_block_height && last_update.map_or(false, |elapsed| elapsed < self.minimum_template_interval) {
+                trace!("Deferring the block template for block {}", block_template.block_height());
+                *self.pending_block_template.write().await = Some(block_template);
+                return;
+            }
+        }
+
+        *self.pending_block_template.write().await = None;
+        self.set_block_template(block_template).await;
+    }
+
+    ///
+    /// Returns the given memory pool transactions that the template policy permits in a block template, in order,
+    /// along with the number of transactions excluded for a fee below the minimum, and for exceeding the maximum
+    /// number of transactions or the maximum template size. Excluded transactions remain in the memory pool.
+    ///
+    fn select_template_transactions(&self, transactions: Vec<Transaction<N>>) -> (Vec<Transaction<N>>, usize, usize) {
+        let (mut num_low_fee, mut num_over_capacity) = (0, 0);
+        let mut template_size = 0usize;
+
+        let mut selected = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            if transaction.value_balance().0 < self.minimum_transaction_fee as i64 {
+                num_low_fee += 1;
+                continue;
+            }
+
+            let size = match transaction.to_bytes_le() {
+                Ok(bytes) => bytes.len(),
+                Err(error) => {
+                    warn!("Failed to serialize transaction {} - {}", transaction.transaction_id(), error);
+                    continue;
+                }
+            };
+            let is_full = self.maximum_template_transactions > 0 && selected.len() >= self.maximum_template_transactions;
+            // A transaction that does not fit is skipped, as a smaller one behind it may still fit.
+            let is_oversized = self.maximum_template_size > 0 && template_size.saturating_add(size) > self.maximum_template_size;
+            if is_full || is_oversized {
+                num_over_capacity += 1;
+                continue;
+            }
+
+            template_size += size;
+            selected.push(transaction);
+        }
+        (selected, num_low_fee, num_over_capacity)
+    }
+
+    ///
+    /// Returns `true` if enough transactions in the memory pool are missing from the current block template
+    /// to rebuild it, either by their number or by the age of the block template.
+    ///
+    async fn has_new_transactions(&self) -> bool {
+        if self.template_refresh_threshold == 0 && self.template_refresh_age.is_zero() {
+            return false;
+        }
+        // Rebuilds may not change the block template, so they are rate limited regardless.
+        if self.last_template_refresh.read().await.map_or(false, |refreshed_at| refreshed_at.elapsed() < TEMPLATE_REFRESH_INTERVAL) {
+            return false;
+        }
+
+        let template_transaction_ids = match &*self.block_template.read().await {
+            Some((template, _)) => template.transactions().transaction_ids().collect::<HashSet<_>>(),
+            None => return false,
+        };
+        // Only the transactions that the template policy would include are considered, so that excluded
+        // transactions do not cause the block template to be rebuilt over and over.
+        let transactions = self.state.prover().memory_pool().read().await.transactions();
+        let number_of_new_transactions = self
+            .select_template_transactions(transactions)
+            .0
+            .iter()
+            .filter(|transaction| !template_transaction_ids.contains(&transaction.transaction_id()))
+            .count();
+        if number_of_new_transactions == 0 {
+            return false;
+        }
+
+        // The first block template has no recorded update, so it is considered old enough to rebuild.
+        let template_age = match &*self.stale_block_template.read().await {
+            Some((_, _, _, replaced_at)) => replaced_at.elapsed(),
+            None => Duration::MAX,
+        };
+
+        let exceeds_threshold = self.template_refresh_threshold > 0 && number_of_new_transactions >= self.template_refresh_threshold;
+        let exceeds_age = !self.template_refresh_age.is_zero() && template_age >= self.template_refresh_age;
+        if exceeds_threshold || exceeds_age {
+            trace!("Rebuilding the block template for {} new transactions", number_of_new_transactions);
+            *self.last_template_refresh.write().await = Some(Instant::now());
+        }
+        exceeds_threshold || exceeds_age
+    }
+
+    ///
+    /// Applies the deferred block template, once the minimum template interval has elapsed since the last update.
+    /// The deferred block template is discarded if the ledger has advanced past it.
+    ///
+    async fn apply_pending_block_template(&self) {
+        let is_ready = match &*self.stale_block_template.read().await {
+            Some((_, _, _, replaced_at)) => replaced_at.elapsed() >= self.minimum_template_interval,
+            None => true,
+        };
+        if !is_ready {
+            return;
+        }
+
+        if let Some(block_template) = self.pending_block_template.write().await.take() {
+            let latest_block = *self.state.canon_tip().borrow();
+            if extends_canon_tip(block_template.block_height(), block_template.previous_block_hash(), latest_block) {
+                self.set_block_template(block_template).await;
+            }
+        }
+    }
+
+    ///
+    /// Replaces the current block template with the given one, and starts a new set of known nonces for it.
+    /// The previous block template and its known nonces are retained for the stale template grace period.
+    /// All updates to the block template must go through this method, by way of `propose_block_template`,
+    /// except for a refresh forced by the `RefreshTemplate` request.
+    ///
+    /// The nonces of the shares accepted for the round of the block template are persisted, and restored here.
+    ///
+    /// The header root of the block template is computed once here, so that shares are verified against it directly.
+    /// A block template whose header root cannot be computed is discarded, in which case `false` is returned.
+    ///
+    async fn set_block_template(&self, block_template: BlockTemplate<N>) -> bool {
+        let header_root = match block_template.to_header_root() {
+            Ok(header_root) => header_root,
+            Err(error) => {
+                error!("Discarding the block template for block {} - {}", block_template.block_height(), error);
+                return false;
+            }
+        };
+
+        // Forget the persisted nonces of the previous rounds. Those of this round are kept, in case the operator is resuming
+        // a round from before a restart, as are those of the replaced template, which is kept for the stale template grace period.
+        let round = (block_template.block_height(), block_template.coinbase_record().commitment());
+        let previous_round = match &*self.block_template.read().await {
+            Some((template, _)) => Some((template.block_height(), template.coinbase_record().commitment())),
+            None => None,
+        };
+        let rounds: Vec<_> = std::iter::once(round).chain(previous_round).collect();
+        if let Err(error) = self.operator_state.clear_round_nonces_except(&rounds) {
+            warn!("Failed to clear the nonces of the previous rounds - {}", error);
+        }
+        let round_known_nonces = CircularSet::with_capacity(self.maximum_known_nonces);
+
+        // Acquire the write locks, so that no share is checked against a mismatched template and set of nonces.
+        let mut current_block_template = self.block_template.write().await;
+        let mut known_nonces = self.known_nonces.write().await;
+
+        let previous_known_nonces = std::mem::replace(&mut *known_nonces, round_known_nonces);
+        let previous_block_template = current_block_template.replace((block_template, header_root));
+        *self.stale_block_template.write().await =
+            previous_block_template.map(|(template, header_root)| (template, header_root, previous_known_nonces, Instant::now()));
+        true
+    }
+
+    ///
+    /// Returns the recipient of the coinbase of the next block template, rotating through the recipients in order.
+    ///
+    fn next_recipient(&self) -> Address<N> {
+        let index = self.next_recipient.fetch_add(1, Ordering::SeqCst);
+        self.recipients[index % self.recipients.len()]
+    }
+
+    ///
+    /// Returns the coinbase record and the amount owed to each prover for the round of the given block height.
+    /// The coinbase record is split in proportion to the shares of each prover, and any remainder is kept by the operator.
+    ///
+    pub fn compute_payouts(&self, block_height: u32) -> Result<(Record<N>, Vec<(Address<N>, AleoAmount)>)> {
+        // Retrieve the coinbase record of the round that was confirmed on the canonical chain.
+        let ledger = self.state.ledger().reader();
+        let coinbase_record = self
+            .operator_state
+            .to_coinbase_records()
+            .into_iter()
+            .filter(|(height, _)| *height == block_height)
+            .map(|(_, record)| record)
+            .find(|record| ledger.contains_commitment(&record.commitment()).unwrap_or(false))
+            .ok_or_else(|| anyhow!("Block {} is not a canonical block found by this operator", block_height))?;
+
+        let payouts = self.get_payouts_for_block(block_height, coinbase_record.clone())?;
+        Ok((coinbase_record, payouts))
+    }
+
+    ///
+    /// Returns the amount owed to each recipient for the given block height and coinbase record.
+    /// The pool fee is attributed to the recipient of the coinbase record, and the remainder of the coinbase record
+    /// is split in proportion to the shares of each prover. Any rounding remainder is kept by the operator.
+    ///
+    pub fn get_payouts_for_block(&self, block_height: u32, coinbase_record: Record<N>) -> Result<Vec<(Address<N>, AleoAmount)>> {
+        let shares = self.operator_state.get_shares_for_block(block_height, coinbase_record.clone())?;
+        let total_shares = shares.values().map(|shares| *shares as u128).sum::<u128>();
+        if total_shares == 0 {
+            bail!("Block {} has no shares to pay out", block_height);
+        }
+
+        // Note: The pool fee is applied in basis points, so that fractional percentages are exact.
+        let reward = coinbase_record.value().0.max(0) as u128;
+        let fee = reward * (self.pool_fee_percent * 100.0).round() as u128 / 10_000;
+
+        let mut payouts: HashMap<Address<N>, u128> = HashMap::with_capacity(shares.len() + 1);
+        for (prover, shares) in shares {
+            *payouts.entry(prover).or_default() += (reward - fee) * shares as u128 / total_shares;
+        }
+        if fee > 0 {
+            *payouts.entry(coinbase_record.owner()).or_default() += fee;
+        }
+
+        Ok(payouts
+            .into_iter()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(recipient, amount)| (recipient, AleoAmount(amount as i64)))
+            .collect())
+    }
+
+    ///
+    /// Pays out the round of the given block height to its provers, once the block has enough confirmations.
+    /// The round is marked as paid before the transaction is broadcast, so that it is never paid twice.
+    ///
+    async fn process_payouts(&self, block_height: u32) -> Result<()> {
+        // Ensure the block is deep enough in the canonical chain.
+        let latest_block_height = self.state.ledger().reader().latest_block_height();
+        let confirmations = latest_block_height.saturating_sub(block_height);
+        if block_height > latest_block_height || confirmations < self.payout_confirmations {
+            bail!(
+                "Block {} has {} confirmations, but {} are required for a payout",
+                block_height,
+                confirmations,
+                self.payout_confirmations
+            );
+        }
+
+        let (coinbase_record, payouts) = self.compute_payouts(block_height)?;
+
+        // Ensure the round has not already been paid out.
+        if let Some(transaction_ids) = self.operator_state.get_payout(block_height, coinbase_record.clone())? {
+            bail!("Block {} has already been paid out in {:?}", block_height, transaction_ids);
+        }
+
+        if self.payout_dry_run {
+            for (prover, amount) in &payouts {
+                info!("[Dry run] Operator would pay {} to {} for block {}", amount, prover, block_height);
+            }
+            return Ok(());
+        }
+
+        let transaction = self.create_payout_transaction(coinbase_record.clone(), &payouts)?;
+        let transaction_id = transaction.transaction_id();
+
+        // Mark the round as paid, before the transaction leaves the operator.
+        self.operator_state.mark_paid(block_height, coinbase_record, &[transaction_id])?;
+        info!("Operator has paid out block {} to {} recipients in transaction {}", block_height, payouts.len(), transaction_id);
+
+        // Route the payout transaction to the memory pool, which propagates it to the network.
+        let request = ProverRequest::UnconfirmedTransaction(self.state.local_ip, transaction);
+        if let Err(error) = self.state.prover().router().send(request).await {
+            warn!("[ProcessPayouts] {}", error);
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Returns a transaction that transfers the given amounts out of the given coinbase record.
+    /// Each transfer spends the change of the previous transfer, which is proven locally in the same transaction.
+    ///
+    fn create_payout_transaction(&self, coinbase_record: Record<N>, payouts: &[(Address<N>, AleoAmount)]) -> Result<Transaction<N>> {
+        let private_key = self
+            .private_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("Payouts require the operator to be started with its private key"))?;
+        let view_key = ViewKey::from_private_key(private_key);
+        let address = Address::from_private_key(private_key);
+        ensure!(
+            coinbase_record.owner() == address,
+            "Payouts of coinbase records sent to {} require its private key",
+            coinbase_record.owner()
+        );
+
+        let rng = &mut thread_rng();
+        let ledger = self.state.ledger().reader();
+        let mut ledger_proof = ledger.get_ledger_inclusion_proof(coinbase_record.commitment())?;
+        let mut virtual_machine = VirtualMachine::<N>::new(ledger.latest_ledger_root())?;
+        let mut input_record = coinbase_record;
+
+        // The operator's own payout, from the pool fee, remains in the change record.
+        for (prover, amount) in payouts.iter().filter(|(recipient, _)| *recipient != address) {
+            let change = input_record.value().sub(*amount);
+            let request = Request::new_transfer(
+                private_key,
+                vec![input_record],
+                vec![ledger_proof],
+                *prover,
+                *amount,
+                AleoAmount::ZERO,
+                false,
+                rng,
+            )?;
+            virtual_machine = virtual_machine.execute(&request, rng)?;
+
+            // Retrieve the change record of this transfer, to fund the next one.
+            input_record = virtual_machine
+                .clone()
+                .finalize()?
+                .to_decrypted_records(&view_key.clone().into())
+                .filter(|record| record.owner() == address && record.value() == change)
+                .last()
+                .ok_or_else(|| anyhow!("Missing the change record of the payout to {}", prover))?;
+            ledger_proof = LedgerProof::default();
+        }
+
+        virtual_machine.finalize()
+    }
+
+    ///
+    /// Retargets the share difficulty of every prover, based on their recent share submissions.
+    /// The new share difficulty is sent to each prover in its next `PoolRequest`.
+    ///
+    async fn retarget_share_difficulties(&self) {
+        let mut retargeted = Vec::new();
+        {
+            let pinned_share_difficulties = self.pinned_share_difficulties.read().await;
+            for ((address, worker), prover) in self.provers.write().await.iter_mut() {
+                // Skip the provers with a pinned share difficulty.
+                if pinned_share_difficulties.contains_key(address) {
+                    continue;
+                }
+
+                let previous_share_difficulty = prover.share_difficulty;
+                prover.retarget(self.minimum_share_difficulty);
+                if prover.share_difficulty != previous_share_difficulty {
+                    debug!(
+                        "Retargeted the share difficulty of {} ({}) from {} to {}",
+                        address, worker, previous_share_difficulty, prover.share_difficulty
+                    );
+                    retargeted.push(((*address, worker.clone()), prover.share_difficulty));
+                }
+            }
+        }
+        self.push_share_difficulties(retargeted).await;
+    }
+
+    ///
+    /// Decays the share difficulty of the provers that have not submitted a share within `DECAY_SHARE_INTERVALS`
+    /// target share intervals, so that a prover that can no longer meet its share difficulty recovers.
+    /// Each of their peers is sent a `PoolRequest` with the decayed share difficulty.
+    ///
+    async fn decay_share_difficulties(&self) {
+        let threshold = TARGET_SHARE_INTERVAL.saturating_mul(DECAY_SHARE_INTERVALS);
+
+        let mut decayed = Vec::new();
+        {
+            let pinned_share_difficulties = self.pinned_share_difficulties.read().await;
+            for ((address, worker), prover) in self.provers.write().await.iter_mut() {
+                // Skip the provers with a pinned share difficulty.
+                if pinned_share_difficulties.contains_key(address) {
+                    continue;
+                }
+
+                let previous_share_difficulty = prover.share_difficulty;
+                if prover.decay(self.base_share_difficulty, threshold) {
+                    info!(
+                        "Decayed the share difficulty of stalled prover {} ({}) from {} to {}",
+                        address, worker, previous_share_difficulty, prover.share_difficulty
+                    );
+                    self.share_difficulty_decays.fetch_add(1, Ordering::SeqCst);
+                    decayed.push(((*address, worker.clone()), prover.share_difficulty));
+                }
+            }
+        }
+        self.push_share_difficulties(decayed).await;
+    }
+
+    ///
+    /// Sends each of the given workers its updated share difficulty in a `PoolRequest`, to the peers it registered from,
+    /// so that it does not keep mining at its previous share difficulty until it registers again. Peers that have
+    /// disconnected are forgotten, and each peer is sent at most one update per block template. A worker that could not
+    /// be sent its update receives it with its next registration, and its previous share difficulty is accepted until then.
+    ///
+    async fn push_share_difficulties(&self, updates: Vec<((Address<N>, String), u64)>) {
+        if updates.is_empty() {
+            return;
+        }
+
+        let (block_template, header_root) = match self.block_template.read().await.clone() {
+            Some(block_template) => block_template,
+            None => return,
+        };
+
+        for (key, share_difficulty) in updates {
+            let peer_ips: Vec<_> =
+                self.workers.read().await.iter().filter(|(_, worker)| **worker == key).map(|(peer_ip, _)| *peer_ip).collect();
+
+            let mut is_sent_to_all = !peer_ips.is_empty();
+            for peer_ip in peer_ips {
+                // Forget the peers that have disconnected since they registered.
+                if !self.state.peers().is_connected_to(peer_ip).await {
+                    trace!("[PoolRequest] Forgetting disconnected prover {} ({})", key.0, peer_ip);
+                    self.workers.write().await.remove(&peer_ip);
+                    self.pool_requests.write().await.remove(&peer_ip);
+                    self.share_difficulty_pushes.write().await.remove(&peer_ip);
+                    continue;
+                }
+
+                // Ensure the peer has not already been pushed an update for the current block template.
+                if self.share_difficulty_pushes.read().await.get(&peer_ip) == Some(&header_root) {
+                    trace!("[PoolRequest] Deferring the share difficulty update for {} to its next registration", peer_ip);
+                    is_sent_to_all = false;
+                    continue;
+                }
+
+                self.share_difficulty_pushes.write().await.insert(peer_ip, header_root);
+                self.send_pool_request(peer_ip, share_difficulty, block_template.clone(), header_root).await;
+            }
+
+            // The prover has been sent its updated share difficulty, so the previous one is retired.
+            if is_sent_to_all {
+                if let Some(prover) = self.provers.write().await.get_mut(&key) {
+                    prover.previous_share_difficulty = None;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Checks the found blocks that have reached the orphan confirmations, and confirms the ones on the canonical chain.
+    /// The rounds of the orphaned blocks are removed, so that they are excluded from payouts, or rolled into the current
+    /// round if configured to. Rolling over only applies to proportional share accounting, as PPLNS rounds are windowed.
+    ///
+    async fn check_blocks_found(&self, latest_block_height: u32) {
+        let ledger = self.state.ledger().reader();
+        for (block_height, block_found) in self.operator_state.get_blocks_found() {
+            if block_found.is_confirmed
+                || block_found.is_orphaned
+                || latest_block_height < block_height.saturating_add(self.orphan_confirmations)
+            {
+                continue;
+            }
+
+            // Ensure the canonical block at this height contains the coinbase commitment of the round.
+            let is_canonical = match ledger.get_block(block_height) {
+                Ok(block) => block.transactions().commitments().any(|commitment| *commitment == block_found.coinbase_commitment),
+                Err(error) => {
+                    warn!("Failed to retrieve block {} to check for an orphan - {}", block_height, error);
+                    continue;
+                }
+            };
+            if is_canonical {
+                if let Err(error) = self.operator_state.mark_block_confirmed(block_height) {
+                    error!("Failed to confirm the found block {} - {}", block_height, error);
+                }
+                continue;
+            }
+
+            warn!("Block {} ({}) found by the operator has been orphaned", block_height, block_found.block_hash);
+            let coinbase_record = self
+                .operator_state
+                .to_coinbase_records()
+                .into_iter()
+                .find(|(height, record)| *height == block_height && record.commitment() == block_found.coinbase_commitment)
+                .map(|(_, record)| record);
+            if let Some(coinbase_record) = coinbase_record {
+                let roll_into = match self.roll_over_orphaned_shares && self.operator_state.accounting() == ShareAccounting::Proportional {
+                    true => self
+                        .block_template
+                        .read()
+                        .await
+                        .as_ref()
+                        .map(|(template, _)| (template.block_height(), template.coinbase_record().clone())),
+                    false => None,
+                };
+                if let Err(error) = self.operator_state.remove_orphaned_round(block_height, coinbase_record, roll_into) {
+                    error!("Failed to remove the orphaned round for block {} - {}", block_height, error);
+                }
+            }
+            if let Err(error) = self.operator_state.mark_block_orphaned(block_height) {
+                error!("Failed to mark the found block {} as orphaned - {}", block_height, error);
+            }
+        }
+    }
+
+    ///
+    /// Removes the paid and orphaned rounds that are deeper than the round retention depth, if one is configured.
+    /// A round whose coinbase record is not on the canonical chain at this depth can never be paid out,
+    /// so it is treated as orphaned.
+    ///
+    async fn prune_rounds(&self) {
+        let round_retention_depth = match self.round_retention_depth {
+            Some(round_retention_depth) => round_retention_depth,
+            None => return,
+        };
+        let ledger = self.state.ledger().reader();
+        let prune_height = ledger.latest_block_height().saturating_sub(round_retention_depth);
+
+        // Remove the rounds whose coinbase record did not make it onto the canonical chain.
+        let mut num_orphaned_rounds = 0;
+        for (block_height, coinbase_record) in self.operator_state.to_coinbase_records() {
+            if block_height >= prune_height || ledger.contains_commitment(&coinbase_record.commitment()).unwrap_or(true) {
+                continue;
+            }
+            match self.operator_state.remove_orphaned_round(block_height, coinbase_record, None) {
+                Ok(()) => num_orphaned_rounds += 1,
+                Err(error) => warn!("Failed to remove the orphaned round for block {} - {}", block_height, error),
+            }
+        }
+
+        // Remove the rounds that have been paid out.
+        match self.operator_state.prune_rounds_below(prune_height) {
+            Ok((num_rounds, num_keys)) => {
+                if num_orphaned_rounds + num_rounds > 0 {
+                    info!(
+                        "Pruned {} paid rounds ({} keys) and {} orphaned rounds below block {}",
+                        num_rounds, num_keys, num_orphaned_rounds, prune_height
+                    );
+                }
+            }
+            Err(error) => warn!("Failed to prune the rounds below block {} - {}", prune_height, error),
+        }
+    }
+
+    ///
+    /// Removes the provers that have been inactive for longer than the prover timeout.
+    ///
+    async fn evict_inactive_provers(&self) {
+        let mut provers = self.provers.write().await;
+        let num_provers = provers.len();
+        provers.retain(|_, prover| !prover.is_inactive(self.prover_timeout));
+
+        let num_evicted = num_provers - provers.len();
+        if num_evicted > 0 {
+            debug!("Evicted {} inactive workers ({} remaining)", num_evicted, provers.len());
+        }
+        self.workers.write().await.retain(|_, key| provers.contains_key(key));
+        drop(provers);
+        let workers = self.workers.read().await;
+        self.share_difficulty_pushes.write().await.retain(|peer_ip, _| workers.contains_key(peer_ip));
+        drop(workers);
+
+        // Forget the accepted shares that have fallen out of the hashrate window.
+        self.accepted_shares.write().await.retain(|_, shares| {
+            shares.retain(|(accepted_at, _)| accepted_at.elapsed() <= HASHRATE_WINDOW);
+            !shares.is_empty()
+        });
+        self.pool_requests.write().await.retain(|_, (sent_at, ..)| sent_at.elapsed() <= self.prover_timeout);
+
+        // Forget the invalid shares that have fallen out of the window.
+        let window = self.invalid_share_window;
+        let is_recent = |timestamps: &VecDeque<Instant>| timestamps.back().map_or(false, |timestamp| timestamp.elapsed() <= window);
+        self.invalid_shares_by_prover.write().await.retain(|_, timestamps| is_recent(timestamps));
+        self.invalid_shares_by_ip.write().await.retain(|_, timestamps| is_recent(timestamps));
+
+        // Forget the IP addresses that have been idle for longer than the prover timeout.
+        self.peer_stats.write().await.retain(|_, peer| peer.last_seen.elapsed() <= self.prover_timeout);
+
+        // Lift the bans that have expired.
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.banned_provers.write().await.retain(|_, ban| ban.banned_until > now);
+        self.banned_ips.write().await.retain(|_, banned_until| *banned_until > now);
+    }
+
+    ///
+    /// Records a verified share from the given worker, and proceeds to broadcast the block
+    /// if the share also meets the difficulty target of the block template.
+    ///
+    async fn accept_share(
+        &self,
+        peer_ip: SocketAddr,
+        key: (Address<N>, String),
+        share_difficulty: u64,
+        nonce: N::PoSWNonce,
+        proof: PoSWProof<N>,
+        block_template: BlockTemplate<N>,
+    ) {
+        let prover = key.0;
+        let block_height = block_template.block_height();
+
+        // Update the internal state for this prover.
+        if let Some(prover) = self.provers.write().await.get_mut(&key) {
+            prover.last_submitted = Some(Instant::now());
+            prover.shares_since_retarget = prover.shares_since_retarget.saturating_add(1);
+        } else {
+            error!("Prover should have existing info");
+            return;
+        }
+        *self.last_share_timestamp.write().await = Some(OffsetDateTime::now_utc().unix_timestamp());
+        {
+            let mut accepted_shares = self.accepted_shares.write().await;
+            let shares = accepted_shares.entry(prover).or_default();
+            shares.push_back((Instant::now(), share_difficulty));
+            while shares.front().map_or(false, |(accepted_at, _)| accepted_at.elapsed() > HASHRATE_WINDOW) {
+                shares.pop_front();
+            }
+        }
+
+        // Record the share for the prover.
+        let coinbase_record = block_template.coinbase_record().clone();
+        match self.operator_state.increment_share_with_nonce(block_height, coinbase_record, &prover, share_difficulty, nonce) {
+            Ok(..) => info!("Operator has received a valid share from {} ({}) for block {}", prover, peer_ip, block_height),
+            Err(error) => error!("{}", error),
+        }
+        self.record_share_outcome(&prover, ShareOutcome::Accepted);
+        self.record_peer_activity(peer_ip, prover, |peer| peer.accepted_shares += 1).await;
+        self.round_work.fetch_add(u64::MAX / share_difficulty.max(1), Ordering::SeqCst);
+
+        // Ensure the block template still extends the canon tip, as the ledger may have advanced or reverted since.
+        let latest_block = *self.state.canon_tip().borrow();
+        if !extends_canon_tip(block_height, block_template.previous_block_hash(), latest_block) {
+            return;
+        }
+
+        // If the block has satisfactory difficulty and is valid, proceed to broadcast it.
+        let previous_block_hash = block_template.previous_block_hash();
+        let transactions = block_template.transactions().clone();
+        if let Ok(block_header) = BlockHeader::<N>::from(
+            block_template.previous_ledger_root(),
+            block_template.transactions().transactions_root(),
+            BlockHeaderMetadata::new(&block_template),
+            nonce,
+            proof,
+        ) {
+            if let Ok(block) = Block::from(previous_block_hash, block_header, transactions) {
+                info!("Operator has found unconfirmed block {} ({})", block.height(), block.hash());
+                // Record the prover whose share found the block.
+                let block_found = BlockFound {
+                    block_hash: block.hash(),
+                    prover,
+                    timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+                    coinbase_commitment: block_template.coinbase_record().commitment(),
+                    is_confirmed: false,
+                    is_orphaned: false,
+                };
+                if let Err(error) = self.operator_state.add_block_found(block_height, &block_found) {
+                    error!("Failed to record the found block {} - {}", block_height, error);
+                }
+                // Record the work of the round, and start the next round.
+                let work = self.round_work.swap(0, Ordering::SeqCst);
+                if let Err(error) = self.operator_state.add_round_work(block_height, work, expected_work(&block_template)) {
+                    error!("Failed to record the work of the round for block {} - {}", block_height, error);
+                }
+                // Attribute the shares for this round, now that the block has been found.
+                let coinbase_record = block_template.coinbase_record().clone();
+                if let Err(error) = self.operator_state.close_round(block_height, coinbase_record) {
+                    error!("Failed to close the round for block {} - {}", block_height, error);
+                }
+                // Start the share counters afresh for the next round, if configured to.
+                if self.reset_stats_each_round {
+                    if let Err(error) = self.operator_state.reset_prover_stats() {
+                        error!("Failed to reset the prover stats - {}", error);
+                    }
+                }
+                let block_hash = block.hash();
+                let request = LedgerRequest::UnconfirmedBlock(self.state.local_ip, block);
+                if let Err(error) = self.state.ledger().router().send(request).await {
+                    warn!("Failed to broadcast mined block - {}", error);
+                }
+
+                // Notify the block hook, if one is configured.
+                if let Some(block_hook) = &self.block_hook {
+                    let round_shares = self
+                        .operator_state
+                        .get_shares_for_block(block_height, block_template.coinbase_record().clone())
+                        .map(|shares| shares.values().fold(0u64, |total, shares| total.saturating_add(*shares)))
+                        .unwrap_or_default();
+                    let notification = BlockNotification {
+                        block_height,
+                        block_hash,
+                        prover,
+                        round_shares,
+                        timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+                    };
+                    match serde_json::to_string(&notification) {
+                        Ok(payload) => Self::notify_block_hook(block_hook.clone(), block_height, payload),
+                        Err(error) => warn!("Failed to serialize the block hook payload for block {} - {}", block_height, error),
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Sends the given payload to the block hook on a dedicated task, so that a slow hook does not hold up the operator.
+    /// A URL is sent the payload in a POST request, and a command is run with the payload as its argument.
+    ///
+    fn notify_block_hook(block_hook: String, block_height: u32, payload: String) {
+        let resource_id = E::resources().procure_id();
+        E::resources().register_task(
+            Some(resource_id),
+            tokio::spawn(async move {
+                for attempt in 1..=BLOCK_HOOK_ATTEMPTS {
+                    match tokio::time::timeout(BLOCK_HOOK_TIMEOUT, run_block_hook(&block_hook, &payload)).await {
+                        Ok(Ok(())) => {
+                            debug!("Notified the block hook of block {}", block_height);
+                            break;
+                        }
+                        Ok(Err(error)) => warn!("Block hook failed for block {} (attempt {}) - {}", block_height, attempt, error),
+                        Err(_) => warn!("Block hook timed out for block {} (attempt {})", block_height, attempt),
+                    }
+                    if attempt < BLOCK_HOOK_ATTEMPTS {
+                        tokio::time::sleep(BLOCK_HOOK_RETRY_DELAY).await;
+                    }
+                }
+                E::resources().deregister(resource_id);
+            }),
+        );
+    }
+
+    /// Increments the share counter of the given prover for the given share outcome.
+    fn record_share_outcome(&self, prover: &Address<N>, outcome: ShareOutcome) {
+        if let Err(error) = self.operator_state.record_share_outcome(prover, outcome) {
+            error!("Failed to record a share outcome for {} - {}", prover, error);
+        }
+    }
+
+    ///
+    /// Forgets the given nonce, so that a share for a rejected nonce may be resubmitted.
+    ///
+    async fn release_nonce(&self, nonce: &N::PoSWNonce) {
+        self.known_nonces.write().await.remove(nonce);
+        if let Some((_, _, known_nonces, _)) = &mut *self.stale_block_template.write().await {
+            known_nonces.remove(nonce);
+        }
+    }
+
+    ///
+    /// Checks the given shares from the given prover, and proceeds to process each share.
+    ///
+    async fn process_pool_response(
+        &self,
+        peer_ip: SocketAddr,
+        prover: Address<N>,
+        block_height: Option<u32>,
+        shares: Vec<(N::PoSWNonce, PoSWProof<N>)>,
+    ) {
+        // Ensure the prover is not banned, before spending any time on verification.
+        if self.is_banned(peer_ip, &prover).await {
+            trace!("[PoolResponse] Ignoring banned prover {} ({})", prover, peer_ip);
+            return;
+        }
+
+        // Ensure the prover has registered, if registration is required. This is checked before verification,
+        // as otherwise anyone observing a block template could submit shares while bypassing registration.
+        if self.require_registration && !self.provers.read().await.keys().any(|(address, _)| *address == prover) {
+            warn!("[PoolResponse] Peer {} sent shares for unregistered prover {}", peer_ip, prover);
+            self.record_invalid_share(peer_ip, prover, "Shares from an unregistered prover").await;
+            return;
+        }
+
+        // Ensure the pool has room for the prover, as an unregistered prover is registered by its first share.
+        if !self.has_capacity_for(peer_ip, &prover).await {
+            return;
+        }
+
+        // Ensure the number of shares is within the permitted bounds.
+        if shares.is_empty() || shares.len() > MAXIMUM_SHARES_PER_POOL_RESPONSE {
+            warn!("[PoolResponse] Peer {} sent an invalid number of shares ({})", peer_ip, shares.len());
+            return;
+        }
+
+        // Process each share independently. The nonce of each share is reserved before the next share
+        // is checked, so a duplicate nonce within the batch is rejected as well.
+        for (nonce, proof) in shares {
+            self.process_pool_share(peer_ip, prover, block_height, nonce, proof).await;
+        }
+    }
+
+    ///
+    /// Checks the given share for a duplicate nonce, and proceeds to verify it on the thread pool.
+    /// The share is credited to the prover once the verification has succeeded.
+    ///
+    /// If the prover sent the block height of the block template of the share, the share is only verified
+    /// against the block templates at that height, and a share for an older block template is expired.
+    ///
+    async fn process_pool_share(
+        &self,
+        peer_ip: SocketAddr,
+        prover: Address<N>,
+        block_height: Option<u32>,
+        nonce: N::PoSWNonce,
+        proof: PoSWProof<N>,
+    ) {
+        // Wait for a verification permit. Once the maximum number of pending verifications is reached,
+        // this holds up the operator handler, which in turn applies backpressure to the peers submitting shares.
+        let permit = match self.verification_permits.clone().acquire_owned().await {
+            Ok(permit) => permit,
+            Err(error) => {
+                warn!("[PoolResponse] {}", error);
+                return;
+            }
+        };
+
+        if let Some((current_block_template, current_header_root)) = self.block_template.read().await.clone() {
+            // Retrieve the previous block template, and whether it is still within the grace period.
+            let (stale_block_template, is_stale_nonce) = match &*self.stale_block_template.read().await {
+                Some((template, header_root, known_nonces, replaced_at)) => {
+                    let is_within_grace = replaced_at.elapsed() <= self.stale_template_grace;
+                    (Some((template.clone(), *header_root, is_within_grace)), self.is_known_nonce(template, known_nonces, &nonce))
+                }
+                None => (None, false),
+            };
+
+            // Ensure the given nonce from the prover is new within the round.
+            if is_stale_nonce || self.is_known_nonce(&current_block_template, &*self.known_nonces.read().await, &nonce) {
+                warn!("[PoolResponse] Peer {} sent a duplicate share", peer_ip);
+                self.record_share_outcome(&prover, ShareOutcome::Duplicate);
+                // TODO (julesdesmit): punish?
+                return;
+            }
+
+            // Reserve the nonce before verification, so that a duplicate share received in the meantime is rejected.
+            // If the cache is full, the oldest nonce is forgotten from it, but remains persisted for the round once accepted.
+            {
+                let mut known_nonces = self.known_nonces.write().await;
+                if known_nonces.is_full() {
+                    trace!("[PoolResponse] Reached the maximum of {} known nonces", self.maximum_known_nonces);
+                }
+                known_nonces.insert(nonce);
+            }
+
+            // Retrieve the worker that the peer registered as, for the given prover.
+            let key = match self.workers.read().await.get(&peer_ip) {
+                Some((address, worker)) if *address == prover => (prover, worker.clone()),
+                _ => (prover, DEFAULT_WORKER_NAME.to_string()),
+            };
+
+            // Retrieve the share difficulty for the given worker.
+            let initial_share_difficulty = self.initial_share_difficulty(&prover).await;
+            let share_difficulty = self
+                .provers
+                .write()
+                .await
+                .entry(key.clone())
+                .or_insert_with(|| ProverInfo::new(initial_share_difficulty))
+                .accepted_share_difficulty();
+
+            // Verify the share on the thread pool, so that the operator handler may proceed in the meantime.
+            let state = self.state.clone();
+            let resource_id = E::resources().procure_id();
+            E::resources().register_task(
+                Some(resource_id),
+                tokio::spawn(async move {
+                    let operator = state.operator();
+
+                    // Ensure the share difficulty target is met, and the PoSW proof is valid,
+                    // for the current block template or, failing that, the stale block template.
+                    let result = tokio::task::spawn_blocking({
+                        let proof = proof.clone();
+                        move || {
+                            E::thread_pool().install(move || {
+                                let is_for =
+                                    |template: &BlockTemplate<N>| block_height.map_or(true, |height| height == template.block_height());
+                                let verify = |template: &BlockTemplate<N>, header_root: N::BlockHeaderRoot| {
+                                    is_for(template)
+                                        && N::posw().verify(template.block_height(), share_difficulty, &[*header_root, *nonce], &proof)
+                                };
+                                if verify(&current_block_template, current_header_root) {
+                                    ShareVerification::Current(current_block_template)
+                                } else {
+                                    match stale_block_template {
+                                        Some((template, header_root, is_within_grace)) if verify(&template, header_root) => {
+                                            match is_within_grace {
+                                                true => ShareVerification::Stale(template),
+                                                false => ShareVerification::Expired,
+                                            }
+                                        }
+                                        // A share for a block template older than the stale block template has expired.
+                                        Some((template, ..)) if !is_for(&current_block_template) && !is_for(&template) => {
+                                            ShareVerification::Expired
+                                        }
+                                        None if !is_for(&current_block_template) => ShareVerification::Expired,
+                                        _ => ShareVerification::Invalid,
+                                    }
+                                }
+                            })
+                        }
+                    })
+                    .await;
+
+                    match result {
+                        Ok(ShareVerification::Current(block_template)) => {
+                            operator.accept_share(peer_ip, key, share_difficulty, nonce, proof, block_template).await;
+                        }
+                        Ok(ShareVerification::Stale(block_template)) => {
+                            debug!("[PoolResponse] Accepting a share for the stale block template from {}", peer_ip);
+                            operator.accept_share(peer_ip, key, share_difficulty, nonce, proof, block_template).await;
+                        }
+                        Ok(ShareVerification::Expired) => {
+                            debug!("[PoolResponse] Rejecting a share for an expired block template from {}", peer_ip);
+                            operator.release_nonce(&nonce).await;
+                            operator.record_share_outcome(&prover, ShareOutcome::Stale);
+                        }
+                        Ok(ShareVerification::Invalid) => {
+                            warn!("[PoolResponse] PoSW proof verification failed");
+                            operator.release_nonce(&nonce).await;
+                            operator.record_share_outcome(&prover, ShareOutcome::Rejected);
+                            operator.record_invalid_share(peer_ip, prover, "Invalid PoSW proof").await;
+                        }
+                        Err(error) => {
+                            error!("[PoolResponse] {}", error);
+                            operator.release_nonce(&nonce).await;
+                        }
+                    }
+
+                    drop(permit);
+                    E::resources().deregister(resource_id);
+                }),
+            );
+        } else {
+            warn!("[PoolResponse] No current block template exists");
+        }
+    }
+
+    ///
+    /// Performs the given `request` to the operator.
+    /// All requests must go through this `update`, so that a unified view is preserved.
+    ///
+    pub(super) async fn update(&self, request: OperatorRequest<N>) {
+        // Ignore all requests once the operator has shut down.
+        if self.is_shut_down.load(Ordering::SeqCst) {
+            if let OperatorRequest::Shutdown(sender) = request {
+                let _ = sender.send(());
+            }
+            return;
+        }
+
+        match request {
+            OperatorRequest::PoolRegister(peer_ip, address, worker) => {
+                // Ensure the prover is not banned.
+                if self.is_banned(peer_ip, &address).await {
+                    trace!("[PoolRegister] Ignoring banned prover {} ({})", address, peer_ip);
+                    return;
+                }
+
+                // Record the registration for the IP address, and ensure it has not been banned as a result.
+                self.record_peer_activity(peer_ip, address, |peer| peer.registrations += 1).await;
+                if self.is_banned(peer_ip, &address).await {
+                    return;
+                }
+
+                // Ensure the pool has room for the prover.
+                if !self.has_capacity_for(peer_ip, &address).await {
+                    return;
+                }
+
+                if let Some((block_template, header_root)) = self.block_template.read().await.clone() {
+                    // Provers that predate worker names are treated as the default worker.
+                    let key = (address, worker.unwrap_or_else(|| DEFAULT_WORKER_NAME.to_string()));
+                    self.workers.write().await.insert(peer_ip, key.clone());
+
+                    // Ensure this prover exists in the list first, and retrieve their share difficulty.
+                    let initial_share_difficulty = self.initial_share_difficulty(&address).await;
+                    let share_difficulty = {
+                        let mut provers = self.provers.write().await;
+                        let prover = provers.entry(key.clone()).or_insert_with(|| ProverInfo::new(initial_share_difficulty));
+                        prover.last_registered = Instant::now();
+                        prover.share_difficulty
+                    };
+
+                    // Ignore the registration if the peer was recently sent the same template and share difficulty.
+                    if let Some((sent_at, sent_header_root, sent_share_difficulty)) = self.pool_requests.read().await.get(&peer_ip) {
+                        if sent_at.elapsed() < self.pool_register_interval
+                            && *sent_header_root == header_root
+                            && *sent_share_difficulty == share_difficulty
+                        {
+                            self.throttled_registrations.fetch_add(1, Ordering::SeqCst);
+                            trace!("[PoolRegister] Throttling the registration from {}", peer_ip);
+                            return;
+                        }
+                    }
+
+                    // The prover is about to be sent its current share difficulty, so the previous one is retired.
+                    if let Some(prover) = self.provers.write().await.get_mut(&key) {
+                        prover.previous_share_difficulty = None;
+                    }
+
+                    self.send_pool_request(peer_ip, share_difficulty, block_template, header_root).await;
+                } else {
+                    warn!("[PoolRegister] No current block template exists");
+                }
+            }
+            OperatorRequest::GetBlockTemplate(prover, worker, sender) => {
+                let response = match self.block_template.read().await.clone() {
+                    Some((block_template, _)) => {
+                        // Retrieve the share difficulty the worker of the prover mines at, or would be assigned upon registering.
+                        let share_difficulty = match prover {
+                            Some(address) => {
+                                let key = (address, worker.unwrap_or_else(|| DEFAULT_WORKER_NAME.to_string()));
+                                match self.provers.read().await.get(&key) {
+                                    Some(prover) => Some(prover.share_difficulty),
+                                    None => Some(self.initial_share_difficulty(&address).await),
+                                }
+                            }
+                            None => None,
+                        };
+                        Some((block_template, share_difficulty))
+                    }
+                    None => None,
+                };
+                // The requester may have stopped waiting, in which case the response is discarded.
+                let _ = sender.send(response);
+            }
+            OperatorRequest::ProcessPayouts(block_height) => {
+                if let Err(error) = self.process_payouts(block_height).await {
+                    warn!("[ProcessPayouts] {}", error);
+                }
+            }
+            OperatorRequest::SetShareDifficulty(address, share_difficulty) => {
+                // A share difficulty of 0 unpins the prover, and returns it to retargeting from its current share difficulty.
+                if share_difficulty == 0 {
+                    if self.pinned_share_difficulties.write().await.remove(&address).is_some() {
+                        info!("Unpinned the share difficulty of {}", address);
+                    }
+                    return;
+                }
+
+                self.pinned_share_difficulties.write().await.insert(address, share_difficulty);
+
+                // Update the workers of the prover, creating its default worker if the prover is unknown.
+                let updates = {
+                    let mut provers = self.provers.write().await;
+                    if !provers.keys().any(|(prover, _)| *prover == address) {
+                        provers.insert((address, DEFAULT_WORKER_NAME.to_string()), ProverInfo::new(share_difficulty));
+                    }
+                    provers
+                        .iter_mut()
+                        .filter(|((prover, _), _)| *prover == address)
+                        .map(|(key, prover)| {
+                            prover.pin(share_difficulty);
+                            (key.clone(), share_difficulty)
+                        })
+                        .collect()
+                };
+                info!("Pinned the share difficulty of {} to {}", address, share_difficulty);
+
+                // Send the pinned share difficulty to the workers of the prover.
+                self.push_share_difficulties(updates).await;
+            }
+            OperatorRequest::RefreshTemplate(sender) => {
+                let response = match self.build_block_template().await {
+                    Ok(block_template) => {
+                        let (block_height, number_of_transactions) = (block_template.block_height(), block_template.transactions().len());
+
+                        // Install the block template immediately, superseding any deferred block template.
+                        *self.pending_block_template.write().await = None;
+                        if self.set_block_template(block_template).await {
+                            info!("Refreshed the block template for block {} ({} transactions)", block_height, number_of_transactions);
+                            self.broadcast_block_template().await;
+                            Ok((block_height, number_of_transactions))
+                        } else {
+                            Err(format!("Failed to compute the header root of the block template for block {}", block_height))
+                        }
+                    }
+                    Err(error) => {
+                        warn!("[RefreshTemplate] {}", error);
+                        Err(error.to_string())
+                    }
+                };
+                // The requester may have stopped waiting, in which case the response is discarded.
+                let _ = sender.send(response);
+            }
+            OperatorRequest::Shutdown(sender) => {
+                self.is_shut_down.store(true, Ordering::SeqCst);
+                trace!("[ShuttingDown] Operator heartbeat has been stopped");
+
+                // Wait for the shares that are pending verification to be recorded, by acquiring every verification permit.
+                if let Err(error) = self.verification_permits.acquire_many(self.maximum_pending_verifications as u32).await {
+                    warn!("[ShuttingDown] {}", error);
+                }
+                trace!("[ShuttingDown] Pending shares have been processed");
+
+                // Flush the operator state to disk.
+                match self.operator_state.shut_down() {
+                    Ok(()) => trace!("[ShuttingDown] Operator state has been flushed"),
+                    Err(error) => error!("[ShuttingDown] Failed to flush the operator state - {}", error),
+                }
+                let _ = sender.send(());
+            }
+            OperatorRequest::PoolResponse(peer_ip, prover, block_height, shares) => {
+                self.process_pool_response(peer_ip, prover, block_height, shares).await;
+                // The request is no longer in flight, so the peer may send another.
+                self.release_request(peer_ip).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ledger::Ledger, CpuBackend, Peers, Prover, ProverConfig, ProvingBackend};
+    use snarkos_environment::TestEnvironment;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    type CurrentEnvironment = TestEnvironment<Testnet2>;
+
+    /// Returns the instant the given duration ago.
+    fn ago(duration: Duration) -> Instant {
+        Instant::now().checked_sub(duration).expect("Failed to compute an instant in the past")
+    }
+
+    #[test]
+    fn test_block_template_follows_canon_tip() {
+        // A block template is built on the latest block 10.
+        let template = (11, "block 10");
+        assert!(extends_canon_tip(template.0, template.1, (10, "block 10")));
+
+        // A block arrives from the network between two template constructions, so the first template is stale,
+        // and the second template is built against the new tip.
+        assert!(!extends_canon_tip(template.0, template.1, (11, "block 11")));
+        let template = (12, "block 11");
+        assert!(extends_canon_tip(template.0, template.1, (11, "block 11")));
+
+        // A reorg replaces the latest block at the same block height, which also makes the template stale.
+        assert!(!extends_canon_tip(template.0, template.1, (11, "fork block 11")));
+        // A revert to an earlier block makes the template stale as well.
+        assert!(!extends_canon_tip(template.0, template.1, (10, "block 10")));
+    }
+
+    /// Returns a block template on top of the given block with the given transactions, and a coinbase for a random address.
+    fn sample_block_template(
+        previous_block_hash: <Testnet2 as Network>::BlockHash,
+        transactions: &[Transaction<Testnet2>],
+    ) -> BlockTemplate<Testnet2> {
+        sample_block_template_with_difficulty(previous_block_hash, transactions, Testnet2::genesis_block().difficulty_target())
+    }
+
+    ///
+    /// Returns a block template on top of the given block with the given transactions and difficulty target,
+    /// and a coinbase for a random address.
+    ///
+    fn sample_block_template_with_difficulty(
+        previous_block_hash: <Testnet2 as Network>::BlockHash,
+        transactions: &[Transaction<Testnet2>],
+        difficulty_target: u64,
+    ) -> BlockTemplate<Testnet2> {
+        let rng = &mut thread_rng();
+        let genesis_block = Testnet2::genesis_block();
+        let block_height = genesis_block.height() + 1;
+        let recipient = Address::from_private_key(&PrivateKey::new(rng));
+        let (coinbase_transaction, coinbase_record) =
+            Transaction::<Testnet2>::new_coinbase(recipient, Block::<Testnet2>::block_reward(block_height), true, rng)
+                .expect("Failed to create a coinbase transaction");
+        let transactions = [transactions, &[coinbase_transaction]].concat();
+
+        BlockTemplate::new(
+            previous_block_hash,
+            block_height,
+            genesis_block.timestamp() + 1,
+            difficulty_target,
+            genesis_block.cumulative_weight() + (u64::MAX / difficulty_target.max(1)) as u128,
+            genesis_block.previous_ledger_root(),
+            Transactions::from(&transactions).expect("Failed to create the block transactions"),
+            coinbase_record,
+        )
+    }
+
+    #[test]
+    fn test_same_mempool_is_same_block_template() {
+        let genesis_block = Testnet2::genesis_block();
+        // A transaction from another block stands in for a memory pool transaction.
+        let transaction = genesis_block.transactions().iter().next().cloned().expect("The genesis block has a transaction");
+
+        // Proposing the same memory pool twice yields the same block template, despite the fresh coinbase of each.
+        let first = sample_block_template(genesis_block.hash(), &[transaction.clone()]);
+        let second = sample_block_template(genesis_block.hash(), &[transaction.clone()]);
+        assert_ne!(first.coinbase_record(), second.coinbase_record());
+        assert!(is_same_block_template(&first, &second));
+
+        // A change to the memory pool or the previous block yields a new block template.
+        assert!(!is_same_block_template(&first, &sample_block_template(genesis_block.hash(), &[])));
+        assert!(!is_same_block_template(&first, &sample_block_template(genesis_block.previous_block_hash(), &[transaction])));
+    }
+
+    #[test]
+    fn test_prover_info_retarget() {
+        let mut prover = ProverInfo::new(1_000_000);
+        assert_eq!(1_000_000, prover.accepted_share_difficulty());
+
+        // Submitting far more shares than targeted makes the share difficulty as much harder as permitted.
+        prover.shares_since_retarget = 1000;
+        prover.retarget(1);
+        assert_eq!(250_000, prover.share_difficulty);
+        assert_eq!(0, prover.shares_since_retarget);
+        // Shares at the previous share difficulty are accepted until the prover has been sent the new one.
+        assert_eq!(1_000_000, prover.accepted_share_difficulty());
+
+        // Submitting no shares makes the share difficulty as much easier as permitted, bounded by the minimum.
+        prover.previous_share_difficulty = None;
+        prover.retarget(1);
+        assert_eq!(1_000_000, prover.share_difficulty);
+        assert_eq!(1_000_000, prover.accepted_share_difficulty());
+    }
+
+    #[test]
+    fn test_prover_info_pin() {
+        let mut prover = ProverInfo::new(1_000_000);
+        prover.pin(500_000);
+        assert_eq!(500_000, prover.share_difficulty);
+        assert_eq!(1_000_000, prover.accepted_share_difficulty());
+
+        prover.previous_share_difficulty = None;
+        assert_eq!(500_000, prover.accepted_share_difficulty());
+    }
+
+    #[test]
+    fn test_prover_info_decay() {
+        let threshold = Duration::from_secs(1);
+        let mut prover = ProverInfo::new(1000);
+
+        // A prover that has yet to submit a share does not decay.
+        assert!(!prover.decay(4000, threshold));
+
+        // A stalled prover decays once per threshold, up to the base share difficulty.
+        prover.last_submitted = Some(ago(threshold * 2));
+        assert!(prover.decay(3000, threshold));
+        assert_eq!(2000, prover.share_difficulty);
+        assert!(!prover.decay(3000, threshold));
+
+        prover.last_decayed = Some(ago(threshold * 2));
+        assert!(prover.decay(3000, threshold));
+        assert_eq!(3000, prover.share_difficulty);
+
+        prover.last_decayed = Some(ago(threshold * 2));
+        assert!(!prover.decay(3000, threshold));
+    }
+
+    #[test]
+    fn test_prover_info_is_inactive() {
+        let timeout = Duration::from_secs(10);
+        let mut prover = ProverInfo::new(1000);
+        assert!(!prover.is_inactive(timeout));
+
+        // A prover that has yet to submit a share is given a longer grace period.
+        prover.last_registered = ago(timeout * 2);
+        assert!(!prover.is_inactive(timeout));
+        prover.last_registered = ago(timeout * (FIRST_SHARE_GRACE_FACTOR + 1));
+        assert!(prover.is_inactive(timeout));
+
+        prover.last_submitted = Some(Instant::now());
+        assert!(!prover.is_inactive(timeout));
+        prover.last_submitted = Some(ago(timeout * 2));
+        assert!(prover.is_inactive(timeout));
+    }
+
+    #[test]
+    fn test_estimate_hashrate() {
+        assert_eq!(0.0, estimate_hashrate(&VecDeque::new()));
+
+        // A share at the base share difficulty is worth one proof, and a share at half of it is worth two.
+        let window = HASHRATE_WINDOW.as_secs();
+        let shares: VecDeque<_> = (0..window).map(|_| (Instant::now(), u64::MAX)).collect();
+        assert_eq!(1.0, estimate_hashrate(&shares));
+
+        let shares: VecDeque<_> = (0..window).map(|_| (Instant::now(), u64::MAX / 2)).collect();
+        assert_eq!(2.0, estimate_hashrate(&shares));
+    }
+
+    #[test]
+    fn test_peer_info_addresses() {
+        let rng = &mut thread_rng();
+        let mut peer = PeerInfo::<Testnet2>::new();
+        let first = Address::from_private_key(&PrivateKey::new(rng));
+        let second = Address::from_private_key(&PrivateKey::new(rng));
+
+        peer.record(first, Duration::from_secs(60));
+        peer.record(first, Duration::from_secs(60));
+        peer.record(second, Duration::from_secs(60));
+        assert_eq!(2, peer.addresses.len());
+
+        // An address that has not been seen within the timeout is forgotten.
+        peer.addresses.insert(first, ago(Duration::from_secs(120)));
+        peer.record(second, Duration::from_secs(60));
+        assert_eq!(vec![second], peer.addresses.keys().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_peer_info_reject_rate() {
+        let mut peer = PeerInfo::<Testnet2>::new();
+
+        // The reject rate is not considered until enough shares have been received.
+        peer.rejected_shares = MINIMUM_REJECT_RATE_SHARES - 1;
+        assert_eq!(None, peer.reject_rate());
+
+        peer.accepted_shares = (MINIMUM_REJECT_RATE_SHARES - 1) * 3;
+        assert_eq!(Some(0.25), peer.reject_rate());
+    }
+
+    /// A difficulty target that no share meets, so that the tests of shares do not find a block.
+    const UNREACHABLE_DIFFICULTY_TARGET: u64 = 1;
+
+    ///
+    /// A pool operator on a fresh ledger, which is driven through the `OperatorRequest`s that the peers of a pool send.
+    /// Each request is processed to completion, including the verification of the shares it carries.
+    ///
+    struct PoolHarness {
+        state: Arc<State<Testnet2, CurrentEnvironment>>,
+    }
+
+    impl PoolHarness {
+        /// Initializes an operator with the given configuration, along with the ledger and prover of its node.
+        async fn new(config: OperatorConfig<Testnet2>) -> Self {
+            let temp_dir = || tempfile::tempdir().expect("Failed to open a temporary directory").into_path();
+            let address = Address::from_private_key(&PrivateKey::new(&mut thread_rng()));
+            let state = Arc::new(State::new("127.0.0.1:4130".parse().unwrap(), Some(address)));
+
+            let (peers, peers_handler) = Peers::new(None, state.clone()).await;
+            let (ledger, ledger_handler) = Ledger::open::<_>(&temp_dir(), state.clone()).await.expect("Failed to initialize the ledger");
+            let prover_config = ProverConfig { self_test: false, ..Default::default() };
+            let (prover, prover_handler) =
+                Prover::open::<_>(&temp_dir(), prover_config, state.clone()).await.expect("Failed to initialize the prover");
+            let (operator, operator_handler) =
+                Operator::open::<_>(&temp_dir(), config, state.clone()).await.expect("Failed to initialize the operator");
+
+            state.initialize_peers(peers, peers_handler).await;
+            state.initialize_ledger(ledger, ledger_handler).await;
+            state.initialize_prover(prover, prover_handler).await;
+            state.initialize_operator(operator, operator_handler).await;
+
+            Self { state }
+        }
+
+        fn operator(&self) -> &Operator<Testnet2, CurrentEnvironment> {
+            self.state.operator()
+        }
+
+        /// Replaces the block template of the operator, as its update loop does once the ledger advances.
+        async fn set_block_template(&self, block_template: BlockTemplate<Testnet2>) {
+            assert!(self.operator().set_block_template(block_template).await);
+        }
+
+        /// Performs the given request, and waits until the shares it carries, if any, have been verified and processed.
+        async fn update(&self, request: OperatorRequest<Testnet2>) {
+            let operator = self.operator();
+            operator.update(request).await;
+
+            // Each share holds a verification permit until it is processed, so acquiring every permit awaits all of them.
+            let permits = operator.maximum_pending_verifications as u32;
+            let _permits = operator.verification_permits.acquire_many(permits).await.expect("Failed to await the pending shares");
+        }
+
+        /// Registers the given worker of the given prover, from the given peer.
+        async fn register(&self, peer_ip: SocketAddr, prover: Address<Testnet2>, worker: Option<&str>) {
+            self.update(OperatorRequest::PoolRegister(peer_ip, prover, worker.map(str::to_string))).await;
+        }
+
+        /// Submits the given share for the given block template, from the given peer.
+        async fn submit(
+            &self,
+            peer_ip: SocketAddr,
+            prover: Address<Testnet2>,
+            block_template: &BlockTemplate<Testnet2>,
+            share: (<Testnet2 as Network>::PoSWNonce, PoSWProof<Testnet2>),
+        ) {
+            self.update(OperatorRequest::PoolResponse(peer_ip, prover, Some(block_template.block_height()), vec![share])).await;
+        }
+
+        /// Returns the share counters of the given prover.
+        fn stats(&self, prover: &Address<Testnet2>) -> ProverStats {
+            self.operator().get_stats_for_prover(prover).expect("Failed to retrieve the prover stats")
+        }
+    }
+
+    /// Returns a share for the given block template, which meets the maximum share difficulty, as any valid proof does.
+    fn mine_share(block_template: &BlockTemplate<Testnet2>) -> (<Testnet2 as Network>::PoSWNonce, PoSWProof<Testnet2>) {
+        CpuBackend
+            .prove(block_template, u64::MAX, &AtomicBool::new(false))
+            .expect("Failed to prove a share")
+            .expect("Any valid proof meets the maximum share difficulty")
+    }
+
+    /// Returns a new prover address, and the peer IP address it connects from.
+    fn sample_prover(port: u16) -> (Address<Testnet2>, SocketAddr) {
+        let prover = Address::from_private_key(&PrivateKey::new(&mut thread_rng()));
+        (prover, SocketAddr::from(([127, 0, 0, 1], port)))
+    }
+
+    #[tokio::test]
+    async fn test_pool_register_and_share() {
+        let pool = PoolHarness::new(OperatorConfig::default()).await;
+        let template = sample_block_template_with_difficulty(Testnet2::genesis_block().hash(), &[], UNREACHABLE_DIFFICULTY_TARGET);
+        pool.set_block_template(template.clone()).await;
+
+        // A registration adds the worker to the pool.
+        let (prover, peer_ip) = sample_prover(4140);
+        pool.register(peer_ip, prover, Some("rig-1")).await;
+        let workers = pool.operator().get_workers().await;
+        assert_eq!(1, workers.len());
+        assert_eq!((prover, "rig-1"), (workers[0].address, workers[0].worker.as_str()));
+
+        // A share at the base share difficulty is credited with a weight of 1.
+        let share = mine_share(&template);
+        pool.submit(peer_ip, prover, &template, share.clone()).await;
+        assert_eq!(1, pool.stats(&prover).accepted);
+        assert_eq!(1, pool.operator().get_shares_for_prover(&prover));
+
+        // The same share is rejected as a duplicate, and is not credited again.
+        pool.submit(peer_ip, prover, &template, share).await;
+        assert_eq!(1, pool.stats(&prover).duplicate);
+        assert_eq!(1, pool.operator().get_shares_for_prover(&prover));
+        assert!(pool.operator().get_blocks_found().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pool_stale_template() {
+        let genesis_hash = Testnet2::genesis_block().hash();
+        let (prover, peer_ip) = sample_prover(4141);
+
+        // A share for the replaced block template is accepted within the stale template grace period.
+        let pool = PoolHarness::new(OperatorConfig { stale_template_grace: Duration::from_secs(60), ..Default::default() }).await;
+        let stale_template = sample_block_template_with_difficulty(genesis_hash, &[], UNREACHABLE_DIFFICULTY_TARGET);
+        pool.set_block_template(stale_template.clone()).await;
+        pool.register(peer_ip, prover, None).await;
+        pool.set_block_template(sample_block_template_with_difficulty(genesis_hash, &[], UNREACHABLE_DIFFICULTY_TARGET)).await;
+
+        pool.submit(peer_ip, prover, &stale_template, mine_share(&stale_template)).await;
+        assert_eq!(1, pool.stats(&prover).accepted);
+        assert_eq!(0, pool.stats(&prover).stale);
+
+        // Without a grace period, a share for the replaced block template is rejected as stale, and not as invalid.
+        let pool = PoolHarness::new(OperatorConfig { stale_template_grace: Duration::ZERO, ..Default::default() }).await;
+        pool.set_block_template(stale_template.clone()).await;
+        pool.register(peer_ip, prover, None).await;
+        pool.set_block_template(sample_block_template_with_difficulty(genesis_hash, &[], UNREACHABLE_DIFFICULTY_TARGET)).await;
+
+        pool.submit(peer_ip, prover, &stale_template, mine_share(&stale_template)).await;
+        assert_eq!(0, pool.stats(&prover).accepted);
+        assert_eq!(1, pool.stats(&prover).stale);
+        assert_eq!(0, pool.stats(&prover).rejected);
+    }
+
+    #[tokio::test]
+    async fn test_pool_block_found() {
+        let pool = PoolHarness::new(OperatorConfig::default()).await;
+        // Any share meets the maximum difficulty target, so the first share finds the block.
+        let template = sample_block_template_with_difficulty(Testnet2::genesis_block().hash(), &[], u64::MAX);
+        pool.set_block_template(template.clone()).await;
+
+        let (prover, peer_ip) = sample_prover(4142);
+        pool.register(peer_ip, prover, None).await;
+        pool.submit(peer_ip, prover, &template, mine_share(&template)).await;
+
+        // The block is recorded as found by the prover, and the round is closed with its share.
+        let blocks_found = pool.operator().get_blocks_found();
+        assert_eq!(1, blocks_found.len());
+        let (block_height, block_found) = &blocks_found[0];
+        assert_eq!(template.block_height(), *block_height);
+        assert_eq!(prover, block_found.prover);
+        assert_eq!(template.coinbase_record().commitment(), block_found.coinbase_commitment);
+
+        let round_shares = pool.operator().get_shares_for_block(template.block_height(), template.coinbase_record().clone()).unwrap();
+        assert_eq!(Some(&1), round_shares.get(&prover));
+    }
+
+    #[tokio::test]
+    async fn test_pool_unchanged_template() {
+        let pool = PoolHarness::new(OperatorConfig::default()).await;
+        let genesis_hash = Testnet2::genesis_block().hash();
+        let template = sample_block_template(genesis_hash, &[]);
+        pool.operator().propose_block_template(template.clone()).await;
+
+        // Proposing the same memory pool again, with a fresh coinbase, leaves the block template in place.
+        pool.operator().propose_block_template(sample_block_template(genesis_hash, &[])).await;
+        let (current_template, _) = pool.operator().block_template.read().await.clone().expect("Missing the block template");
+        assert_eq!(template.coinbase_record(), current_template.coinbase_record());
+        assert!(pool.operator().stale_block_template.read().await.is_none());
     }
 }
@@ -16,19 +16,28 @@
 
 //! Logic for instantiating the RPC server.
 
-use crate::RpcFunctions;
+use crate::{ProtectedRpcFunctions, RpcError, RpcFunctions};
 use snarkos_environment::Environment;
 use snarkos_network::{ledger::LedgerReader, state::State};
-use snarkvm::prelude::{Address, Network};
+use snarkvm::{
+    console::types::Field,
+    prelude::{Address, Network},
+};
 
 use futures::TryFutureExt;
 use jsonrpsee::{
     core::{middleware::Middleware, Error as JsonrpseeError},
     http_server::{AccessControlBuilder, HttpServerBuilder, RpcModule},
+    ws_server::WsServerBuilder,
 };
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, ops::Deref, sync::Arc, time::Instant};
-use tokio::sync::oneshot;
+use std::{
+    net::{IpAddr, SocketAddr},
+    ops::Deref,
+    sync::Arc,
+    time::Instant,
+};
+use tokio::sync::{broadcast::error::RecvError, oneshot};
 
 // The details on resource-limiting can be found at https://github.com/paritytech/jsonrpsee/blob/master/core/src/server/resource_limiting.rs
 // note: jsonrpsee expects string literals as resource names; we'll be distinguishing
@@ -44,7 +53,6 @@ pub struct RpcInner<N: Network, E: Environment> {
     pub(crate) address: Option<Address<N>>,
     pub(crate) state: State<N, E>,
     /// RPC credentials for accessing guarded endpoints
-    #[allow(unused)]
     pub(crate) credentials: RpcCredentials,
     pub(crate) launched: Instant,
 }
@@ -76,6 +84,14 @@ impl<N: Network, E: Environment> RpcContext<N, E> {
     pub(crate) fn ledger(&self) -> &LedgerReader<N> {
         self.state.ledger().reader()
     }
+
+    /// Returns an error if the given credentials do not match the RPC credentials of the node.
+    pub(crate) fn authenticate(&self, credentials: &RpcCredentials) -> Result<(), RpcError> {
+        match *credentials == self.credentials {
+            true => Ok(()),
+            false => Err(RpcError::Message("Invalid RPC credentials".to_string())),
+        }
+    }
 }
 
 /// Defines the authentication format for accessing private endpoints on the RPC server.
@@ -150,7 +166,38 @@ pub async fn initialize_rpc_server<N: Network, E: Environment>(
     (server_addr, task)
 }
 
-fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>) -> Result<RpcModule<RpcContext<N, E>>, JsonrpseeError> {
+/// Starts a local RPC WebSocket server at `rpc_ws_server_addr` in a dedicated `tokio` task, serving the subscriptions.
+/// RPC failures do not affect the rest of the node.
+pub async fn initialize_rpc_ws_server<N: Network, E: Environment>(
+    rpc_ws_server_addr: SocketAddr,
+    rpc_server_context: RpcContext<N, E>,
+) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let server = WsServerBuilder::default()
+        .set_middleware(RpcMiddleware)
+        .build(rpc_ws_server_addr)
+        .await
+        .expect("Failed to create the RPC WebSocket server");
+
+    let server_addr = server.local_addr().expect("Can't obtain RPC WebSocket server's local address");
+
+    let module = create_rpc_subscriptions_module(rpc_server_context).expect("Failed to start the RPC WebSocket server");
+
+    let (router, handler) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        // Notify the outer function that the task is ready.
+        let _ = router.send(());
+        let server_handle = server.start(module).expect("Failed to start the RPC WebSocket server");
+        server_handle.await
+    });
+    // Wait until the spawned task is ready.
+    let _ = handler.await;
+
+    (server_addr, task)
+}
+
+fn create_rpc_module<N: Network, E: Environment>(
+    rpc_context: RpcContext<N, E>,
+) -> Result<RpcModule<RpcContext<N, E>>, JsonrpseeError> {
     let mut module = RpcModule::new(rpc_context);
 
     // Public methods.
@@ -188,14 +235,6 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
     //     rpc_context.get_block(height).map_err(JsonrpseeError::to_call_error).await
     // })?;
     //
-    // module.register_async_method("getblocks", |rpc_params, rpc_context| async move {
-    //     let [start_height, end_height]: [u32; 2] = rpc_params.parse()?;
-    //     rpc_context
-    //         .get_blocks(start_height, end_height)
-    //         .map_err(JsonrpseeError::to_call_error)
-    //         .await
-    // })?;
-    //
     // module.register_async_method("getblockheight", |rpc_params, rpc_context| async move {
     //     let hash = rpc_params.parse::<[N::BlockHash; 1]>()?[0];
     //     rpc_context.get_block_height(hash).map_err(JsonrpseeError::to_call_error).await
@@ -236,14 +275,6 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
     //     rpc_context.get_ciphertext(commitment).map_err(JsonrpseeError::to_call_error).await
     // })?;
     //
-    // module.register_async_method("getledgerproof", |rpc_params, rpc_context| async move {
-    //     let commitment = rpc_params.parse::<[N::Commitment; 1]>()?[0];
-    //     rpc_context
-    //         .get_ledger_proof(commitment)
-    //         .map_err(JsonrpseeError::to_call_error)
-    //         .await
-    // })?;
-    //
     // module.register_async_method("getmemorypool", |_rpc_params, rpc_context| async move {
     //     rpc_context.get_memory_pool().map_err(JsonrpseeError::to_call_error).await
     // })?;
@@ -258,10 +289,47 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
     //     rpc_context.get_transition(id).map_err(JsonrpseeError::to_call_error).await
     // })?;
 
+    module.register_async_method("getblocks", |rpc_params, rpc_context| async move {
+        let [start_height, end_height]: [u32; 2] = rpc_params.parse()?;
+        rpc_context
+            .get_blocks(start_height, end_height)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("gettransactionsbyaddress", |rpc_params, rpc_context| async move {
+        let (address, offset, limit): (Address<N>, usize, usize) = rpc_params.parse()?;
+        rpc_context
+            .get_transactions_by_address(address, offset, limit)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("getledgerproof", |rpc_params, rpc_context| async move {
+        let commitment = rpc_params.parse::<[Field<N>; 1]>()?[0];
+        rpc_context.get_ledger_proof(commitment).map_err(JsonrpseeError::to_call_error).await
+    })?;
+
     module.register_async_method("getconnectedpeers", |_rpc_params, rpc_context| async move {
         rpc_context.get_connected_peers().map_err(JsonrpseeError::to_call_error).await
     })?;
 
+    module.register_async_method("getcandidatepeers", |_rpc_params, rpc_context| async move {
+        rpc_context.get_candidate_peers().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("getpeerstats", |_rpc_params, rpc_context| async move {
+        rpc_context.get_peer_stats().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("getpeerlatencies", |_rpc_params, rpc_context| async move {
+        rpc_context.get_peer_latencies().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("getsyncstatus", |_rpc_params, rpc_context| async move {
+        rpc_context.get_sync_status().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
     // module.register_async_method("getnodestate", |_rpc_params, rpc_context| async move {
     //     rpc_context.get_node_state().map_err(JsonrpseeError::to_call_error).await
     // })?;
@@ -271,7 +339,28 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
     //     rpc_context.send_transaction(string).map_err(JsonrpseeError::to_call_error).await
     // })?;
 
-    // Private methods.
+    // Private methods, which take the RPC credentials of the node as their first parameter.
+
+    module.register_async_method("listbanned", |rpc_params, rpc_context| async move {
+        let [credentials]: [RpcCredentials; 1] = rpc_params.parse()?;
+        rpc_context.authenticate(&credentials).map_err(JsonrpseeError::to_call_error)?;
+        rpc_context.list_banned().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("setban", |rpc_params, rpc_context| async move {
+        let (credentials, peer_ip, ban_time_in_secs): (RpcCredentials, IpAddr, u64) = rpc_params.parse()?;
+        rpc_context.authenticate(&credentials).map_err(JsonrpseeError::to_call_error)?;
+        rpc_context
+            .set_ban(peer_ip, ban_time_in_secs)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("clearbanned", |rpc_params, rpc_context| async move {
+        let [credentials]: [RpcCredentials; 1] = rpc_params.parse()?;
+        rpc_context.authenticate(&credentials).map_err(JsonrpseeError::to_call_error)?;
+        rpc_context.clear_banned().map_err(JsonrpseeError::to_call_error).await
+    })?;
 
     // "createtransaction" => {
     //     let result = rpc
@@ -308,6 +397,62 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
     //         .map_err(convert_core_err);
     //     result_to_response(&req, result)
     // }
+    // "setproverdifficulty" => {
+    //     let result = rpc
+    //         .set_prover_difficulty_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "refreshblocktemplate" => {
+    //     let result = rpc
+    //         .refresh_block_template_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "setproverthreads" => {
+    //     let result = rpc
+    //         .set_prover_threads_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "pauseprover" => {
+    //     let result = rpc
+    //         .pause_prover_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "resumeprover" => {
+    //     let result = rpc
+    //         .resume_prover_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "getcoinbaserecords" => {
+    //     let result = rpc
+    //         .get_coinbase_records_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "setminingmode" => {
+    //     let result = rpc
+    //         .set_mining_mode_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
+    // "setproverintensity" => {
+    //     let result = rpc
+    //         .set_prover_intensity_protected(Params::Array(params), meta)
+    //         .await
+    //         .map_err(convert_core_err);
+    //     result_to_response(&req, result)
+    // }
     //
     // module.register_async_method("getsharesforprover", |rpc_params, rpc_context| async move {
     //     let prover = rpc_params.parse::<[Address<N>; 1]>()?[0];
@@ -326,6 +471,83 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
     //     let provers = rpc_context.get_provers().await;
     //     Ok(provers)
     // })?;
+    //
+    // module.register_async_method("getpoolstats", |_rpc_params, rpc_context| async move {
+    //     rpc_context.get_pool_stats().map_err(JsonrpseeError::to_call_error).await
+    // })?;
+    //
+    // module.register_async_method("getbannedprovers", |_rpc_params, rpc_context| async move {
+    //     rpc_context.get_banned_provers().map_err(JsonrpseeError::to_call_error).await
+    // })?;
+    //
+    // module.register_async_method("getproverstats", |_rpc_params, rpc_context| async move {
+    //     rpc_context.get_prover_stats().map_err(JsonrpseeError::to_call_error).await
+    // })?;
+
+    Ok(module)
+}
+
+fn create_rpc_subscriptions_module<N: Network, E: Environment>(
+    rpc_context: RpcContext<N, E>,
+) -> Result<RpcModule<RpcContext<N, E>>, JsonrpseeError> {
+    let mut module = RpcModule::new(rpc_context);
+
+    module.register_subscription(
+        "subscribeledgerevents",
+        "ledgerevent",
+        "unsubscribeledgerevents",
+        |_rpc_params, mut sink, rpc_context| {
+            let mut ledger_events = rpc_context.state.ledger_events();
+            tokio::spawn(async move {
+                loop {
+                    match ledger_events.recv().await {
+                        // Forward the ledger event, until the subscriber unsubscribes or disconnects.
+                        Ok(event) => match sink.send(&event) {
+                            Ok(true) => (),
+                            Ok(false) => break,
+                            Err(error) => {
+                                warn!("Failed to send a ledger event to a subscriber: {}", error);
+                                break;
+                            }
+                        },
+                        Err(RecvError::Lagged(num_skipped)) => {
+                            warn!("A ledger event subscriber lagged behind, skipping {} ledger events", num_skipped)
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+            Ok(())
+        },
+    )?;
+
+    module.register_subscription(
+        "subscribecanontip",
+        "canontip",
+        "unsubscribecanontip",
+        |_rpc_params, mut sink, rpc_context| {
+            let mut canon_tip = rpc_context.state.canon_tip();
+            tokio::spawn(async move {
+                loop {
+                    // Forward the latest canon tip, skipping the intermediate tips that a slow subscriber missed.
+                    let (block_height, block_hash) = *canon_tip.borrow_and_update();
+                    match sink.send(&serde_json::json!({ "block_height": block_height, "block_hash": block_hash })) {
+                        Ok(true) => (),
+                        Ok(false) => break,
+                        Err(error) => {
+                            warn!("Failed to send the canon tip to a subscriber: {}", error);
+                            break;
+                        }
+                    }
+                    // Wait for the canon chain to advance or revert, until the ledger shuts down.
+                    if canon_tip.changed().await.is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok(())
+        },
+    )?;
 
     Ok(module)
 }
@@ -14,12 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-//! Implementation of public RPC endpoints.
+//! Implementation of public and private RPC endpoints.
 //!
-//! See [RpcFunctions](../trait.RpcFunctions.html) for documentation of public endpoints.
+//! See [RpcFunctions](../trait.RpcFunctions.html) for documentation of public endpoints,
+//! and [ProtectedRpcFunctions](../trait.ProtectedRpcFunctions.html) for documentation of private endpoints.
 
-use crate::{RpcContext, RpcError, RpcFunctions};
+use crate::{ProtectedRpcFunctions, RpcContext, RpcError, RpcFunctions};
 use snarkos_environment::Environment;
+use snarkos_network::{
+    helpers::{BannedPeer, PeerLatency, PeerSource, PeerSyncStats, SyncStatus},
+    ledger::LedgerRequest,
+};
 use snarkvm::{
     compiler::Transition,
     console::types::Field,
@@ -34,7 +39,16 @@ use snarkvm::{
 use serde_json::Value;
 use time::OffsetDateTime;
 
-use std::{cmp::max, net::SocketAddr};
+use std::{
+    cmp::max,
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// The maximum number of transactions returned in a single page of `gettransactionsbyaddress`.
+const MAXIMUM_TRANSACTIONS_PER_PAGE: usize = 100;
 
 #[async_trait::async_trait]
 impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
@@ -45,7 +59,7 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
 
     /// Returns the latest block height from the canonical chain.
     async fn latest_block_height(&self) -> Result<u32, RpcError> {
-        Ok(self.ledger().latest_block_height())
+        Ok(self.ledger().latest_tip().0)
     }
 
     // /// Returns the latest cumulative weight from the canonical chain.
@@ -55,7 +69,7 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
 
     /// Returns the latest block hash from the canonical chain.
     async fn latest_block_hash(&self) -> Result<N::BlockHash, RpcError> {
-        Ok(self.ledger().latest_block_hash())
+        Ok(self.ledger().latest_tip().1)
     }
 
     /// Returns the latest block header from the canonical chain.
@@ -81,7 +95,17 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
     /// Returns up to `MAXIMUM_BLOCK_REQUEST` blocks from the given `start_block_height` to `end_block_height` (inclusive).
     async fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>, RpcError> {
         let safe_start_height = max(start_block_height, end_block_height.saturating_sub(E::MAXIMUM_BLOCK_REQUEST - 1));
-        Ok(self.ledger().get_blocks(safe_start_height, end_block_height)?)
+
+        // Request the blocks from the ledger, so that they are all read from the same view of the canon chain.
+        let (router, handler) = oneshot::channel();
+        let request = LedgerRequest::GetBlockRange(safe_start_height, end_block_height, router);
+        if let Err(error) = self.state.ledger().router().send(request).await {
+            return Err(RpcError::Message(format!("Failed to request the blocks from the ledger: {}", error)));
+        }
+        match handler.await {
+            Ok(blocks) => Ok(blocks?),
+            Err(error) => Err(RpcError::Message(format!("Failed to receive the blocks from the ledger: {}", error))),
+        }
     }
 
     /// Returns the block height for the given the block hash.
@@ -192,13 +216,23 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
     // async fn get_ciphertext(&self, commitment: Field<N>) -> Result<N::RecordCiphertext, RpcError> {
     //     Ok(self.ledger().get_ciphertext(&commitment)?)
     // }
-    //
-    // /// Returns the ledger proof for a given record commitment.
-    // async fn get_ledger_proof(&self, record_commitment: Field<N>) -> Result<String, RpcError> {
-    //     let ledger_proof = self.ledger().get_ledger_inclusion_proof(record_commitment)?;
-    //     Ok(hex::encode(ledger_proof.to_bytes_le().expect("Failed to serialize ledger proof")))
-    // }
-    //
+
+    /// Returns the ledger proof for a given record commitment, with the block hash and block height it is valid against.
+    async fn get_ledger_proof(&self, record_commitment: Field<N>) -> Result<Value, RpcError> {
+        match self.ledger().get_ledger_proof(&record_commitment) {
+            Ok(ledger_proof) => Ok(serde_json::json!({
+                "block_hash": ledger_proof.block_hash(),
+                "block_height": ledger_proof.block_height(),
+                "ledger_proof": ledger_proof,
+            })),
+            // Distinguish a commitment that is only created by blocks outside of the canon chain from an unknown commitment.
+            Err(error) => match self.state.ledger().contains_non_canon_commitment(&record_commitment).await {
+                true => Err(RpcError::Message(format!("Commitment {} is not in the canon chain yet", record_commitment))),
+                false => Err(error.into()),
+            },
+        }
+    }
+
     // /// Returns transactions in the node's memory pool.
     // async fn get_memory_pool(&self) -> Result<Vec<Transaction<N>>, RpcError> {
     //     Ok(self.state.prover().memory_pool().read().await.transactions())
@@ -214,6 +248,23 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
         // Ok(serde_json::json!({ "transaction": transaction, "metadata": metadata, "decrypted_records": decrypted_records }))
     }
 
+    /// Returns up to `MAXIMUM_TRANSACTIONS_PER_PAGE` transactions that created a record publicly owned by the given address.
+    async fn get_transactions_by_address(&self, address: Address<N>, offset: usize, limit: usize) -> Result<Value, RpcError> {
+        let limit = limit.min(MAXIMUM_TRANSACTIONS_PER_PAGE);
+        let transactions = self.ledger().get_transactions_by_address(&address, offset, limit)?;
+
+        // Include the offset of the next page, if the page is full.
+        let next_offset = match transactions.len() == limit && limit > 0 {
+            true => Some(offset + limit),
+            false => None,
+        };
+        let transactions: Vec<Value> = transactions
+            .into_iter()
+            .map(|(block_height, transaction_id)| serde_json::json!({ "block_height": block_height, "transaction_id": transaction_id }))
+            .collect();
+        Ok(serde_json::json!({ "transactions": transactions, "next_offset": next_offset }))
+    }
+
     /// Returns a transition given the transition ID.
     async fn get_transition(&self, transition_id: Field<N>) -> Result<Transition<N>, RpcError> {
         Ok(self.ledger().get_transition(&transition_id)?)
@@ -224,6 +275,21 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
         Ok(self.state.peers().connected_peers().await)
     }
 
+    /// Returns the candidate peers, with the source each was learned from.
+    async fn get_candidate_peers(&self) -> Result<HashMap<SocketAddr, PeerSource>, RpcError> {
+        Ok(self.state.peers().candidate_peer_sources().await)
+    }
+
+    /// Returns the block download statistics of the connected peers.
+    async fn get_peer_stats(&self) -> Result<Vec<PeerSyncStats>, RpcError> {
+        Ok(self.state.ledger().peer_sync_stats().await)
+    }
+
+    /// Returns the round-trip times of the connected peers.
+    async fn get_peer_latencies(&self) -> Result<Vec<PeerLatency>, RpcError> {
+        Ok(self.state.peers().peer_latencies().await)
+    }
+
     /// Returns the current state of this node.
     async fn get_node_state(&self) -> Result<Value, RpcError> {
         let candidate_peers = self.state.peers().candidate_peers().await;
@@ -232,8 +298,7 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
         let number_of_connected_peers = connected_peers.len();
         let number_of_connected_beacon_nodes = self.state.peers().number_of_connected_beacon_nodes().await;
 
-        let latest_block_hash = self.ledger().latest_block_hash();
-        let latest_block_height = self.ledger().latest_block_height();
+        let (latest_block_height, latest_block_hash, _) = self.ledger().latest_tip();
         // let latest_cumulative_weight = self.ledger().latest_cumulative_weight();
 
         Ok(serde_json::json!({
@@ -247,12 +312,19 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
             "number_of_candidate_peers": number_of_candidate_peers,
             "number_of_connected_peers": number_of_connected_peers,
             "number_of_connected_beacon_nodes": number_of_connected_beacon_nodes,
+            // "mining_mode": self.state.prover().mining_mode().await,
+            // "prover_paused": self.state.prover().is_paused(),
             "software": format!("snarkOS {}", env!("CARGO_PKG_VERSION")),
             "status": E::status().to_string(),
             "type": E::NODE_TYPE,
             "version": E::MESSAGE_VERSION,
         }))
     }
+
+    /// Returns the sync progress of this node.
+    async fn get_sync_status(&self) -> Result<SyncStatus, RpcError> {
+        Ok(self.state.ledger().sync_status().await)
+    }
     //
     // /// Returns the transaction ID. If the given transaction is valid, it is added to the memory pool and propagated to all peers.
     // async fn send_transaction(&self, transaction_hex: String) -> Result<N::TransactionID, RpcError> {
@@ -281,4 +353,173 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
     //     let provers = self.state.operator().get_provers();
     //     serde_json::json!(provers)
     // }
+    //
+    // /// Returns the statistics of the pool run by the operator.
+    // async fn get_pool_stats(&self) -> Result<Value, RpcError> {
+    //     if E::NODE_TYPE != NodeType::Validator {
+    //         return Err(RpcError::Message(format!("Pool statistics are unavailable on a {} node", E::NODE_TYPE)));
+    //     }
+    //
+    //     let operator = self.state.operator();
+    //     let total_shares = operator.to_shares().iter().map(|(_, shares)| shares.values().sum::<u64>()).sum::<u64>();
+    //
+    //     Ok(serde_json::json!({
+    //         "block_template_height": operator.block_template_height().await,
+    //         "blocks_found": operator.get_blocks_found(),
+    //         "dropped_requests": operator.dropped_requests(),
+    //         "estimated_hashrates": operator.get_estimated_hashrates().await,
+    //         "last_share_timestamp": operator.last_share_timestamp().await,
+    //         "number_of_provers": operator.number_of_provers().await,
+    //         "luck": operator.get_average_luck(),
+    //         "peers": operator.get_peer_stats().await,
+    //         "pool_fee_percent": operator.pool_fee_percent(),
+    //         "pool_hashrate": operator.get_estimated_pool_hashrate().await,
+    //         "prover_stats": operator.get_prover_stats(),
+    //         "recipients": operator.recipients(),
+    //         "round_progress": operator.get_round_progress().await,
+    //         "round_recipients": operator.get_round_recipients(),
+    //         "round_shares": operator.get_current_round_shares().await,
+    //         "share_difficulty_decays": operator.share_difficulty_decays(),
+    //         "throttled_registrations": operator.throttled_registrations(),
+    //         "total_shares": total_shares,
+    //         "workers": operator.get_workers().await,
+    //     }))
+    // }
+    //
+    // /// Returns the provers that have been banned by the operator, and the reason for each ban.
+    // async fn get_banned_provers(&self) -> Result<Value, RpcError> {
+    //     if E::NODE_TYPE != NodeType::Validator {
+    //         return Err(RpcError::Message(format!("Banned provers are unavailable on a {} node", E::NODE_TYPE)));
+    //     }
+    //     Ok(serde_json::json!(self.state.operator().get_banned_provers().await))
+    // }
+    //
+    // /// Returns the proof rate and share submissions of the prover.
+    // async fn get_prover_stats(&self) -> Result<Value, RpcError> {
+    //     if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+    //         return Err(RpcError::Message(format!("Prover statistics are unavailable on a {} node", E::NODE_TYPE)));
+    //     }
+    //     Ok(serde_json::json!(self.state.prover().get_stats().await))
+    // }
+}
+
+#[async_trait::async_trait]
+impl<N: Network, E: Environment> ProtectedRpcFunctions<N> for RpcContext<N, E> {
+    /// Returns the banned peer IPs, with the timestamp at which each ban expires.
+    async fn list_banned(&self) -> Result<Vec<BannedPeer>, RpcError> {
+        Ok(self.state.peers().banned_peers().await)
+    }
+
+    /// Bans the given peer IP for the given number of seconds, or lifts its ban if the given number of seconds is 0.
+    async fn set_ban(&self, peer_ip: IpAddr, ban_time_in_secs: u64) -> Result<bool, RpcError> {
+        match ban_time_in_secs {
+            0 => Ok(self.state.peers().unban(peer_ip).await),
+            _ => {
+                self.state.peers().ban(peer_ip, Duration::from_secs(ban_time_in_secs)).await;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Lifts all bans, returning the number of lifted bans.
+    async fn clear_banned(&self) -> Result<usize, RpcError> {
+        Ok(self.state.peers().clear_bans().await)
+    }
+
+//     /// Pins the share difficulty of the given prover, or unpins it if the given share difficulty is 0.
+//     async fn set_prover_difficulty(&self, prover: Address<N>, share_difficulty: u64) -> Result<bool, RpcError> {
+//         if E::NODE_TYPE != NodeType::Validator {
+//             return Err(RpcError::Message(format!("Prover difficulties are unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//
+//         let request = OperatorRequest::SetShareDifficulty(prover, share_difficulty);
+//         Ok(self.state.operator().router().send(request).await.is_ok())
+//     }
+//
+//     /// Forces the operator to rebuild its block template from the latest block and memory pool, and to send it to the provers.
+//     async fn refresh_block_template(&self) -> Result<Value, RpcError> {
+//         if E::NODE_TYPE != NodeType::Validator {
+//             return Err(RpcError::Message(format!("Block template refreshes are unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//
+//         let (router, handler) = oneshot::channel();
+//         let request = OperatorRequest::RefreshTemplate(router);
+//         if self.state.operator().router().send(request).await.is_err() {
+//             return Err(RpcError::Message("The operator is unavailable".to_string()));
+//         }
+//         match handler.await {
+//             Ok(Ok((block_height, number_of_transactions))) => Ok(serde_json::json!({
+//                 "block_height": block_height,
+//                 "transactions": number_of_transactions,
+//             })),
+//             Ok(Err(error)) => Err(RpcError::Message(error)),
+//             Err(_) => Err(RpcError::Message("The operator did not respond".to_string())),
+//         }
+//     }
+//
+//     /// Resizes the thread pool that the prover dedicates to PoSW proving.
+//     async fn set_prover_threads(&self, number_of_threads: usize) -> Result<bool, RpcError> {
+//         if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+//             return Err(RpcError::Message(format!("Prover threads are unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//
+//         let request = ProverRequest::SetThreads(number_of_threads);
+//         Ok(self.state.prover().router().send(request).await.is_ok())
+//     }
+//
+//     /// Pauses the prover, which stops proving until it is resumed, while the node continues to sync and relay.
+//     async fn pause_prover(&self) -> Result<bool, RpcError> {
+//         if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+//             return Err(RpcError::Message(format!("Pausing is unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//
+//         let request = ProverRequest::SetPaused(true);
+//         Ok(self.state.prover().router().send(request).await.is_ok())
+//     }
+//
+//     /// Resumes the prover, which registers with its pool again if it mines for a pool.
+//     async fn resume_prover(&self) -> Result<bool, RpcError> {
+//         if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+//             return Err(RpcError::Message(format!("Resuming is unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//
+//         let request = ProverRequest::SetPaused(false);
+//         Ok(self.state.prover().router().send(request).await.is_ok())
+//     }
+//
+//     /// Returns the coinbase records of the prover, and whether the block of each is on the canonical chain.
+//     async fn get_coinbase_records(&self) -> Result<Value, RpcError> {
+//         if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+//             return Err(RpcError::Message(format!("Coinbase records are unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//         Ok(serde_json::json!(self.state.prover().get_coinbase_records()))
+//     }
+//
+//     /// Switches the prover between solo mining and pool mining, for the given pool if one is given.
+//     async fn set_mining_mode(&self, mining_mode: String, pool_ip: Option<SocketAddr>) -> Result<bool, RpcError> {
+//         if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+//             return Err(RpcError::Message(format!("Mining modes are unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//
+//         let mining_mode = MiningMode::from_str(&mining_mode)?;
+//         if mining_mode == MiningMode::Solo && pool_ip.is_some() {
+//             return Err(RpcError::Message("A pool may only be given for pool mining".to_string()));
+//         }
+//
+//         let request = ProverRequest::SetMiningMode(mining_mode, pool_ip);
+//         Ok(self.state.prover().router().send(request).await.is_ok())
+//     }
+//
+//     /// Sets the percentage of wall-clock time that the prover spends proving.
+//     async fn set_prover_intensity(&self, intensity: u8) -> Result<bool, RpcError> {
+//         if E::NODE_TYPE != NodeType::Prover && E::NODE_TYPE != NodeType::Miner {
+//             return Err(RpcError::Message(format!("Prover intensity is unavailable on a {} node", E::NODE_TYPE)));
+//         }
+//         if !(1..=100).contains(&intensity) {
+//             return Err(RpcError::Message(format!("The prover intensity must be between 1 and 100, found {}", intensity)));
+//         }
+//
+//         let request = ProverRequest::SetIntensity(intensity);
+//         Ok(self.state.prover().router().send(request).await.is_ok())
+//     }
 }
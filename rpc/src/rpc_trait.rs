@@ -17,6 +17,7 @@
 //! Definition of the public and private RPC endpoints.
 
 use crate::RpcError;
+use snarkos_network::helpers::{BannedPeer, PeerLatency, PeerSource, PeerSyncStats, SyncStatus};
 use snarkvm::{
     compiler::Transition,
     console::types::Field,
@@ -27,7 +28,10 @@ use snarkvm::{
     Transactions,
 };
 
-use std::net::SocketAddr;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+};
 
 /// Definition of public RPC endpoints.
 #[async_trait::async_trait]
@@ -79,25 +83,40 @@ pub trait RpcFunctions<N: Network> {
 
     // #[doc = include_str!("../documentation/public_endpoints/getciphertext.md")]
     // async fn get_ciphertext(&self, commitment: N::Commitment) -> Result<N::RecordCiphertext, RpcError>;
-    //
-    // #[doc = include_str!("../documentation/public_endpoints/getledgerproof.md")]
-    // async fn get_ledger_proof(&self, record_commitment: Field<N>) -> Result<String, RpcError>;
-    //
+
+    #[doc = include_str!("../documentation/public_endpoints/getledgerproof.md")]
+    async fn get_ledger_proof(&self, record_commitment: Field<N>) -> Result<serde_json::Value, RpcError>;
+
     // #[doc = include_str!("../documentation/public_endpoints/getmemorypool.md")]
     // async fn get_memory_pool(&self) -> Result<Vec<Transaction<N>>, RpcError>;
 
     #[doc = include_str!("../documentation/public_endpoints/gettransaction.md")]
     async fn get_transaction(&self, transaction_id: N::TransactionID) -> Result<serde_json::Value, RpcError>;
 
+    #[doc = include_str!("../documentation/public_endpoints/gettransactionsbyaddress.md")]
+    async fn get_transactions_by_address(&self, address: Address<N>, offset: usize, limit: usize) -> Result<serde_json::Value, RpcError>;
+
     #[doc = include_str!("../documentation/public_endpoints/gettransition.md")]
     async fn get_transition(&self, transition_id: Field<N>) -> Result<Transition<N>, RpcError>;
 
     #[doc = include_str!("../documentation/public_endpoints/getconnectedpeers.md")]
     async fn get_connected_peers(&self) -> Result<Vec<SocketAddr>, RpcError>;
 
+    #[doc = include_str!("../documentation/public_endpoints/getcandidatepeers.md")]
+    async fn get_candidate_peers(&self) -> Result<HashMap<SocketAddr, PeerSource>, RpcError>;
+
+    #[doc = include_str!("../documentation/public_endpoints/getpeerstats.md")]
+    async fn get_peer_stats(&self) -> Result<Vec<PeerSyncStats>, RpcError>;
+
+    #[doc = include_str!("../documentation/public_endpoints/getpeerlatencies.md")]
+    async fn get_peer_latencies(&self) -> Result<Vec<PeerLatency>, RpcError>;
+
     #[doc = include_str!("../documentation/public_endpoints/getnodestate.md")]
     async fn get_node_state(&self) -> Result<serde_json::Value, RpcError>;
 
+    #[doc = include_str!("../documentation/public_endpoints/getsyncstatus.md")]
+    async fn get_sync_status(&self) -> Result<SyncStatus, RpcError>;
+
     // #[doc = include_str!("../documentation/public_endpoints/sendtransaction.md")]
     // async fn send_transaction(&self, transaction_bytes: String) -> Result<N::TransactionID, RpcError>;
     //
@@ -109,11 +128,29 @@ pub trait RpcFunctions<N: Network> {
     //
     // #[doc = include_str!("../documentation/public_endpoints/getprovers.md")]
     // async fn get_provers(&self) -> serde_json::Value;
+    //
+    // #[doc = include_str!("../documentation/public_endpoints/getpoolstats.md")]
+    // async fn get_pool_stats(&self) -> Result<serde_json::Value, RpcError>;
+    //
+    // #[doc = include_str!("../documentation/public_endpoints/getbannedprovers.md")]
+    // async fn get_banned_provers(&self) -> Result<serde_json::Value, RpcError>;
+    //
+    // #[doc = include_str!("../documentation/public_endpoints/getproverstats.md")]
+    // async fn get_prover_stats(&self) -> Result<serde_json::Value, RpcError>;
 }
 
-// /// Definition of private RPC endpoints that require authentication.
-// #[async_trait::async_trait]
-// pub trait ProtectedRpcFunctions {
+/// Definition of private RPC endpoints that require authentication.
+#[async_trait::async_trait]
+pub trait ProtectedRpcFunctions<N: Network> {
+    #[doc = include_str!("../documentation/private_endpoints/listbanned.md")]
+    async fn list_banned(&self) -> Result<Vec<BannedPeer>, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/setban.md")]
+    async fn set_ban(&self, peer_ip: IpAddr, ban_time_in_secs: u64) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/clearbanned.md")]
+    async fn clear_banned(&self) -> Result<usize, RpcError>;
+
 //     #[doc = include_str!("../documentation/private_endpoints/createtransaction.md")]
 //     async fn create_transaction(
 //         &self,
@@ -132,4 +169,28 @@ pub trait RpcFunctions<N: Network> {
 //
 //     #[doc = include_str!("../documentation/private_endpoints/connect.md")]
 //     async fn connect(&self, addresses: Vec<SocketAddr>);
-// }
+//
+//     #[doc = include_str!("../documentation/private_endpoints/setproverdifficulty.md")]
+//     async fn set_prover_difficulty(&self, prover: Address<N>, share_difficulty: u64) -> Result<bool, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/refreshblocktemplate.md")]
+//     async fn refresh_block_template(&self) -> Result<Value, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/setproverthreads.md")]
+//     async fn set_prover_threads(&self, number_of_threads: usize) -> Result<bool, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/pauseprover.md")]
+//     async fn pause_prover(&self) -> Result<bool, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/resumeprover.md")]
+//     async fn resume_prover(&self) -> Result<bool, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/getcoinbaserecords.md")]
+//     async fn get_coinbase_records(&self) -> Result<Value, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/setminingmode.md")]
+//     async fn set_mining_mode(&self, mining_mode: String, pool_ip: Option<SocketAddr>) -> Result<bool, RpcError>;
+//
+//     #[doc = include_str!("../documentation/private_endpoints/setproverintensity.md")]
+//     async fn set_prover_intensity(&self, intensity: u8) -> Result<bool, RpcError>;
+}
@@ -14,15 +14,29 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{initialize_rpc_server, rpc_trait::RpcFunctions, RpcContext};
-use snarkos_environment::{helpers::Status, Client, Environment};
-use snarkos_network::{ledger::Ledger, Operator, Peers, Prover, State};
+use crate::{initialize_rpc_server, rpc_trait::RpcFunctions, RpcContext, RpcCredentials};
+use snarkos_environment::{
+    helpers::{BlockLocators, NodeType, Status},
+    Client,
+    Environment,
+};
+use snarkos_network::{
+    helpers::{PeerSource, PeerSyncStats},
+    ledger::{Ledger, LedgerConfig, LedgerRequest},
+    peers::PeersRequest,
+    Operator,
+    Peers,
+    Prover,
+    State,
+};
 use snarkos_storage::{
     storage::{rocksdb::RocksDB, ReadWrite, Storage},
+    LedgerProof,
     LedgerState,
 };
 use snarkvm::{
     compiler::Transition,
+    console::types::{field::Zero, Field},
     dpc::Record,
     prelude::{Address, Network},
     utilities::ToBytes,
@@ -43,10 +57,12 @@ use rand::{thread_rng, Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fs,
     net::SocketAddr,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 /// A type alias for the current version of the network.
@@ -97,7 +113,11 @@ async fn new_rpc_context<N: Network, E: Environment, S: Storage, P: AsRef<Path>>
     let (peers, peers_handler) = Peers::new(None, state.clone()).await;
 
     // Initialize a new instance for managing the ledger.
-    let (ledger, ledger_handler) = Ledger::<N, E>::open::<_>(&ledger_path, state.clone())
+    let ledger_config = LedgerConfig {
+        transaction_index: true,
+        ..Default::default()
+    };
+    let (ledger, ledger_handler) = Ledger::<N, E>::open::<_>(&ledger_path, ledger_config, state.clone())
         .await
         .expect("Failed to initialize ledger");
 
@@ -430,38 +450,13 @@ async fn test_get_ciphertext() {
 
 #[tokio::test]
 async fn test_get_ledger_proof() {
-    let mut rng = ChaChaRng::seed_from_u64(thread_rng().gen());
-
     // Initialize a new temporary directory.
     let directory = temp_dir();
 
     // Initialize a new ledger state at the temporary directory.
     let ledger_state = new_ledger_state::<CurrentNetwork, RocksDB, PathBuf>(Some(directory.clone()));
-    assert_eq!(0, ledger_state.latest_block_height());
-
-    // Initialize a new account.
-    let account = Account::<CurrentNetwork>::new(&mut rng);
-    let address = account.address();
-
-    // Mine the next block.
-    let (block_1, _) = ledger_state
-        .mine_next_block(address, true, &[], &Default::default(), &mut rng)
-        .expect("Failed to mine");
-    ledger_state.add_next_block(&block_1).expect("Failed to add next block to ledger");
-    assert_eq!(1, ledger_state.latest_block_height());
-
-    // Get the record commitment.
-    let decrypted_records = block_1
-        .transactions()
-        .first()
-        .unwrap()
-        .to_decrypted_records(&account.view_key().into())
-        .collect::<Vec<_>>();
-    assert!(!decrypted_records.is_empty());
-    let record_commitment = decrypted_records[0].commitment();
-
-    // Get the ledger proof.
-    let ledger_proof = ledger_state.get_ledger_inclusion_proof(record_commitment).unwrap();
+    let genesis = ledger_state.latest_block();
+    let record_commitment = *genesis.transactions().commitments().next().expect("Missing a genesis commitment");
 
     // Drop the handle to ledger_state. Note this does not remove the blocks in the temporary directory.
     drop(ledger_state);
@@ -473,11 +468,18 @@ async fn test_get_ledger_proof() {
 
     // Send the request to the server.
     let params = rpc_params![record_commitment];
-    let response: String = rpc_client.request("getledgerproof", params).await.expect("Invalid response");
-
-    // Check the ledger proof.
-    let expected = hex::encode(ledger_proof.to_bytes_le().expect("Failed to serialize ledger proof"));
-    assert_eq!(response, expected);
+    let response: serde_json::Value = rpc_client.request("getledgerproof", params).await.expect("Invalid response");
+
+    // Check the ledger proof verifies against the reported block hash and block height.
+    let ledger_proof: LedgerProof<CurrentNetwork> = serde_json::from_value(response["ledger_proof"].clone()).expect("Invalid ledger proof");
+    ledger_proof.verify(&record_commitment).expect("Failed to verify the ledger proof");
+    assert_eq!(response["block_hash"], serde_json::json!(genesis.hash()));
+    assert_eq!(response["block_height"], serde_json::json!(0));
+    assert_eq!(ledger_proof.block_hash(), genesis.hash());
+
+    // Check that an unknown commitment has no ledger proof.
+    let params = rpc_params![Field::<CurrentNetwork>::zero()];
+    assert!(rpc_client.request::<serde_json::Value>("getledgerproof", params).await.is_err());
 }
 
 #[tokio::test]
@@ -582,6 +584,26 @@ async fn test_get_transition() {
     );
 }
 
+#[tokio::test]
+async fn test_get_transactions_by_address() {
+    let mut rng = ChaChaRng::seed_from_u64(123456789);
+
+    // Initialize a new account.
+    let account = Account::<CurrentNetwork>::new(&mut rng);
+    let address = account.address();
+
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Send the request to the server.
+    let params = rpc_params![address, 0usize, 10usize];
+    let response: serde_json::Value = rpc_client.request("gettransactionsbyaddress", params).await.expect("Invalid response");
+
+    // Check that a new account does not own any records.
+    assert_eq!(response, serde_json::json!({ "transactions": [], "next_offset": null }));
+}
+
 #[tokio::test]
 async fn test_get_connected_peers() {
     // Initialize a new RPC server and create an associated client.
@@ -595,6 +617,151 @@ async fn test_get_connected_peers() {
     assert!(response.is_empty());
 }
 
+#[tokio::test]
+async fn test_get_candidate_peers() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_context = new_rpc_context::<CurrentNetwork, Client<CurrentNetwork>, RocksDB, PathBuf>(temp_dir()).await;
+    let state = rpc_server_context.state.clone();
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(Some(rpc_server_context)).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Check a new node has no candidate peers.
+    let response: HashMap<SocketAddr, PeerSource> = rpc_client.request("getcandidatepeers", None).await.expect("Invalid response");
+    assert!(response.is_empty());
+
+    // Receive a candidate peer from a connected peer.
+    let peer_ip: SocketAddr = "203.0.113.7:4132".parse().unwrap();
+    let request = PeersRequest::ReceivePeerResponse(vec![peer_ip]);
+    state.peers().router().send(request).await.expect("Failed to send a peer response");
+
+    // Wait until the peers have processed the peer response.
+    let mut response = HashMap::new();
+    for _ in 0..50 {
+        response = rpc_client.request("getcandidatepeers", None).await.expect("Invalid response");
+        if !response.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Check the candidate peer is reported with its source.
+    assert_eq!(response, HashMap::from([(peer_ip, PeerSource::Gossip)]));
+}
+
+#[tokio::test]
+async fn test_get_peer_stats_marks_trusted_peers() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_context = new_rpc_context::<CurrentNetwork, Client<CurrentNetwork>, RocksDB, PathBuf>(temp_dir()).await;
+    let state = rpc_server_context.state.clone();
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(Some(rpc_server_context)).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Trust one of two peers, and let both report their ledger state.
+    let (trusted_peer, untrusted_peer): (SocketAddr, SocketAddr) = ("127.0.0.1:4130".parse().unwrap(), "127.0.0.1:4131".parse().unwrap());
+    state.peers().add_trusted_peers(vec![trusted_peer]).await;
+    for peer_ip in [trusted_peer, untrusted_peer] {
+        let request = LedgerRequest::Pong(
+            peer_ip,
+            NodeType::Client,
+            Status::Ready,
+            Some(false),
+            BlockLocators::default(),
+            Some(Instant::now()),
+        );
+        state.ledger().router().send(request).await.expect("Failed to send a pong");
+    }
+
+    // Wait until the ledger has processed the pongs.
+    let mut response: Vec<PeerSyncStats> = Vec::new();
+    for _ in 0..50 {
+        response = rpc_client.request("getpeerstats", None).await.expect("Invalid response");
+        if response.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Check the trusted peer is marked as such.
+    let is_trusted = response.iter().map(|stats| (stats.peer_ip, stats.is_trusted)).collect::<Vec<_>>();
+    assert_eq!(is_trusted, vec![(trusted_peer, true), (untrusted_peer, false)]);
+}
+
+/// Returns the RPC credentials of the node of `new_rpc_context`.
+fn rpc_credentials() -> RpcCredentials {
+    RpcCredentials {
+        username: "root".to_string(),
+        password: "pass".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_set_ban_and_list_banned() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+    let credentials = rpc_credentials();
+
+    // Ban a peer IP for an hour.
+    let params = rpc_params![&credentials, "203.0.113.7", 3600];
+    let response: bool = rpc_client.request("setban", params).await.expect("Invalid response");
+    assert!(response);
+
+    // Check the ban is listed.
+    let response: Vec<serde_json::Value> = rpc_client.request("listbanned", rpc_params![&credentials]).await.expect("Invalid response");
+    assert_eq!(response.len(), 1);
+    assert_eq!(response[0]["peer_ip"], "203.0.113.7");
+
+    // Lift the ban, and check a peer IP that is not banned is reported as such.
+    let params = rpc_params![&credentials, "203.0.113.7", 0];
+    let response: bool = rpc_client.request("setban", params).await.expect("Invalid response");
+    assert!(response);
+    let params = rpc_params![&credentials, "203.0.113.7", 0];
+    let response: bool = rpc_client.request("setban", params).await.expect("Invalid response");
+    assert!(!response);
+
+    // Ban two peer IPs, and clear the bans.
+    for peer_ip in ["203.0.113.7", "203.0.113.8"] {
+        let _: bool = rpc_client.request("setban", rpc_params![&credentials, peer_ip, 3600]).await.expect("Invalid response");
+    }
+    let response: usize = rpc_client.request("clearbanned", rpc_params![&credentials]).await.expect("Invalid response");
+    assert_eq!(response, 2);
+    let response: Vec<serde_json::Value> = rpc_client.request("listbanned", rpc_params![&credentials]).await.expect("Invalid response");
+    assert!(response.is_empty());
+}
+
+#[tokio::test]
+async fn test_ban_methods_reject_invalid_credentials() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+    let invalid_credentials = RpcCredentials {
+        username: "root".to_string(),
+        password: "wrong".to_string(),
+    };
+
+    // Expect each ban method to reject the invalid credentials.
+    let responses: [Result<serde_json::Value, _>; 3] = [
+        rpc_client.request("setban", rpc_params![&invalid_credentials, "203.0.113.7", 3600]).await,
+        rpc_client.request("listbanned", rpc_params![&invalid_credentials]).await,
+        rpc_client.request("clearbanned", rpc_params![&invalid_credentials]).await,
+    ];
+    for response in responses {
+        match response {
+            Err(JsonrpseeError::Call(CallError::Custom(err))) => assert_eq!(err.message(), "Invalid RPC credentials"),
+            response => panic!("Should have received an error response, got {:?}", response),
+        }
+    }
+
+    // Expect the ban methods to reject a request without credentials.
+    let response: Result<bool, _> = rpc_client.request("setban", rpc_params!["203.0.113.7", 3600]).await;
+    assert!(response.is_err());
+
+    // Check the rejected request did not ban the peer IP.
+    let params = rpc_params![rpc_credentials()];
+    let response: Vec<serde_json::Value> = rpc_client.request("listbanned", params).await.expect("Invalid response");
+    assert!(response.is_empty());
+}
+
 #[tokio::test]
 async fn test_send_transaction() {
     let mut rng = ChaChaRng::seed_from_u64(123456789);
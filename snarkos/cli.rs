@@ -17,13 +17,20 @@
 use crate::{display::Display, Node, Updater};
 use snarkos_consensus::account::Account;
 use snarkos_environment::{helpers::NodeType, Beacon, Client, Environment, Prover, Validator};
+use snarkos_storage::{
+    storage::{rocksdb::RocksDB, ReadOnly, ReadWrite},
+    LedgerState,
+    MINIMUM_PRUNING_DEPTH,
+};
 // use snarkos_storage::storage::{rocksdb::RocksDB, ReadOnly};
+// use snarkos_storage::state::{OperatorState, ProverState};
+// use snarkos_network::{benchmark_proving, proving_backend};
 use snarkvm::prelude::{Address, Network, PrivateKey, ViewKey};
 
 use anyhow::{bail, ensure, Result};
 use clap::Parser;
 use colored::*;
-use std::{fmt::Write, net::SocketAddr, str::FromStr};
+use std::{fmt::Write, net::SocketAddr, path::PathBuf, str::FromStr};
 
 #[derive(Debug, Parser)]
 #[clap(name = "snarkos", author = "The Aleo Team <hello@aleo.org>")]
@@ -43,16 +50,219 @@ pub struct CLI {
     #[clap(hide = true, long)]
     pub beacon: bool,
 
-    /// Specify the IP address and port for the node server.
+    // /// Specify the worker name that a prover registers with its pool.
+    // #[clap(long = "prover-worker")]
+    // pub prover_worker: Option<String>,
+    // /// Specify the comma-separated addresses, or a file with one address per line, that a solo miner rotates each coinbase through.
+    // #[clap(long = "miner-recipients")]
+    // pub miner_recipients: Option<String>,
+    // /// Skip the verification of memory pool transactions against the ledger before a solo miner includes them, for low-power nodes.
+    // #[clap(long = "miner-skip-transaction-checks")]
+    // pub miner_skip_transaction_checks: bool,
+    // /// If the flag is set, a solo miner waits until the node is ready, synced, and connected to enough peers before it mines.
+    // #[clap(long = "miner-wait-for-sync")]
+    // pub miner_wait_for_sync: bool,
+    // /// Specify the number of blocks that the ledger of a solo miner may be behind its highest peer, when waiting for sync.
+    // #[clap(default_value = "1", long = "miner-sync-tolerance")]
+    // pub miner_sync_tolerance: u32,
+    // /// Specify the minimum number of connected peers for a solo miner to mine, when waiting for sync.
+    // #[clap(default_value = "1", long = "miner-minimum-peers")]
+    // pub miner_minimum_peers: usize,
+    // /// If the flag is set, a solo miner mines a coinbase-only block first on each new tip, until its full template is ready.
+    // #[clap(long = "mine-empty-first")]
+    // pub mine_empty_first: bool,
+    // /// Specify the comma-separated IP addresses of the pools that a prover mines for, in order of preference.
+    // #[clap(long = "prover-pools")]
+    // pub prover_pools: Option<String>,
+    // /// Specify the number of seconds without work from its pool after which a prover fails over to the next pool.
+    // #[clap(default_value = "60", long = "prover-pool-timeout")]
+    // pub prover_pool_timeout: u64,
+    // /// Specify the number of seconds after failing over from its primary pool after which a prover returns to it.
+    // #[clap(default_value = "600", long = "prover-primary-pool-retry")]
+    // pub prover_primary_pool_retry: u64,
+    // /// Specify the maximum number of seconds in between attempts by a prover to reconnect to its pool.
+    // #[clap(default_value = "60", long = "prover-max-reconnect-backoff")]
+    // pub prover_max_reconnect_backoff: u64,
+    // /// Specify the number of blocks after which a prover prunes its coinbase records of orphaned blocks.
+    // #[clap(default_value = "100", long = "prover-coinbase-confirmations")]
+    // pub prover_coinbase_confirmations: u32,
+    // /// Specify the number of threads a prover dedicates to PoSW proving, or omit to use one thread per core.
+    // #[clap(long = "prover-threads")]
+    // pub prover_threads: Option<usize>,
+    // /// Specify the number of proof attempts a prover runs concurrently. Each attempt holds its own witness in memory.
+    // #[clap(default_value = "1", long = "prover-parallel-proofs")]
+    // pub prover_parallel_proofs: usize,
+    // /// Specify the percentage of wall-clock time, from 1 to 100, that a prover spends proving for its pool.
+    // #[clap(default_value = "100", long = "prover-intensity")]
+    // pub prover_intensity: u8,
+    // /// Specify the backend that a prover runs its proof attempts on for its pool. The default backend proves on the CPU.
+    // #[clap(default_value = "cpu", long = "prover-backend")]
+    // pub prover_backend: String,
+    // /// Specify whether a prover proves and verifies a share on a synthetic block template at startup, to fail fast if proving is broken.
+    // #[clap(default_value = "true", long = "prover-selftest", parse(try_from_str))]
+    // pub prover_selftest: bool,
+    // /// Specify the percentage of proving time on stale block templates above which a prover logs a warning.
+    // #[clap(default_value = "10", long = "prover-stale-work-threshold")]
+    // pub prover_stale_work_threshold: f64,
+    //
+    // /// Specify the share difficulty that a validator assigns to a prover when it first registers.
+    // #[clap(default_value = "18446744073709551615", long = "operator-base-share-difficulty")]
+    // pub operator_base_share_difficulty: u64,
+    // /// Specify the minimum share difficulty that a validator may retarget a prover to.
+    // #[clap(default_value = "18446744073709", long = "operator-min-share-difficulty")]
+    // pub operator_min_share_difficulty: u64,
+    // /// Specify the number of milliseconds in between iterations of the validator update loop.
+    // #[clap(default_value = "1000", long = "operator-heartbeat-ms")]
+    // pub operator_heartbeat_ms: u64,
+    // /// Specify the number of seconds of inactivity after which a validator evicts a prover.
+    // #[clap(default_value = "600", long = "operator-prover-timeout")]
+    // pub operator_prover_timeout: u64,
+    // /// Specify the number of seconds after a block template is replaced during which a validator still accepts shares for it.
+    // #[clap(default_value = "10", long = "operator-stale-template-grace")]
+    // pub operator_stale_template_grace: u64,
+    // /// Specify the minimum number of seconds in between block template updates for the same block height by a validator.
+    // #[clap(default_value = "0", long = "operator-min-template-interval")]
+    // pub operator_min_template_interval: u64,
+    // /// Specify the number of new memory pool transactions at which a validator rebuilds its block template, or 0 to disable.
+    // #[clap(default_value = "10", long = "operator-template-refresh-threshold")]
+    // pub operator_template_refresh_threshold: usize,
+    // /// Specify the number of seconds after which a validator rebuilds its block template for new transactions, or 0 to disable.
+    // #[clap(default_value = "30", long = "operator-template-refresh-age")]
+    // pub operator_template_refresh_age: u64,
+    // /// Specify the maximum number of transactions in a block template of a validator, or 0 for no limit.
+    // #[clap(default_value = "0", long = "operator-max-template-transactions")]
+    // pub operator_max_template_transactions: usize,
+    // /// Specify the maximum combined size in bytes of the transactions in a block template of a validator, or 0 for no limit.
+    // #[clap(default_value = "0", long = "operator-max-template-size")]
+    // pub operator_max_template_size: usize,
+    // /// Specify the minimum fee of a transaction for a validator to include it in a block template.
+    // #[clap(default_value = "0", long = "operator-min-transaction-fee")]
+    // pub operator_min_transaction_fee: u64,
+    // /// Specify the minimum number of seconds in between block templates a validator resends to a registering prover.
+    // #[clap(default_value = "10", long = "operator-register-interval")]
+    // pub operator_register_interval: u64,
+    // /// Specify the maximum number of distinct prover addresses that may be registered with a validator at once.
+    // #[clap(default_value = "512", long = "operator-max-provers")]
+    // pub operator_max_provers: usize,
+    // /// If the flag is set, a validator disconnects the provers it turns away for exceeding the maximum number of provers.
+    // #[clap(long = "operator-disconnect-excess-provers")]
+    // pub operator_disconnect_excess_provers: bool,
+    // /// If the flag is set, a validator rejects shares from provers that have not sent a pool registration.
+    // #[clap(long = "operator-require-registration")]
+    // pub operator_require_registration: bool,
+    // /// Specify the number of most recent shares a validator credits to each found block (PPLNS), instead of proportional shares.
+    // #[clap(long = "operator-pplns-window")]
+    // pub operator_pplns_window: Option<u32>,
+    // /// Specify the comma-separated addresses a validator rotates the coinbase of each block template through.
+    // #[clap(long = "operator-recipients")]
+    // pub operator_recipients: Option<String>,
+    // /// Specify the percentage of each block reward that a validator keeps as its pool fee.
+    // #[clap(default_value = "0", long = "pool-fee-percent")]
+    // pub pool_fee_percent: f64,
+    // /// Specify the number of confirmations a found block requires before a validator pays out its round.
+    // #[clap(default_value = "10", long = "operator-payout-confirmations")]
+    // pub operator_payout_confirmations: u32,
+    // /// If the flag is set, a validator logs the payouts of each round instead of creating payout transactions.
+    // #[clap(long = "operator-payout-dry-run")]
+    // pub operator_payout_dry_run: bool,
+    // /// If the flag is set, a validator resets the share counters of its provers whenever it finds a block.
+    // #[clap(long = "operator-reset-stats-each-round")]
+    // pub operator_reset_stats_each_round: bool,
+    // /// Specify a URL to POST to, or a command to run, with a JSON payload whenever a validator finds a block.
+    // #[clap(long = "operator-block-hook")]
+    // pub operator_block_hook: Option<String>,
+    // /// Specify the number of blocks after which a validator checks that each block it found is on the canonical chain.
+    // #[clap(default_value = "10", long = "operator-orphan-confirmations")]
+    // pub operator_orphan_confirmations: u32,
+    // /// If the flag is set, a validator adds the shares of its orphaned rounds to the current round, instead of removing them.
+    // #[clap(long = "operator-roll-over-orphaned-shares")]
+    // pub operator_roll_over_orphaned_shares: bool,
+    // /// Specify the number of blocks for which a validator retains its paid and orphaned rounds, or omit to retain them all.
+    // #[clap(long = "operator-round-retention")]
+    // pub operator_round_retention: Option<u32>,
+    // /// Specify the number of invalid shares within the invalid share window after which a validator bans a prover.
+    // #[clap(default_value = "10", long = "operator-invalid-share-limit")]
+    // pub operator_invalid_share_limit: usize,
+    // /// Specify the number of seconds for which an invalid share counts towards the invalid share limit.
+    // #[clap(default_value = "600", long = "operator-invalid-share-window")]
+    // pub operator_invalid_share_window: u64,
+    // /// Specify the number of seconds for which a validator bans a prover or IP address.
+    // #[clap(default_value = "86400", long = "operator-ban-duration")]
+    // pub operator_ban_duration: u64,
+    // /// Specify the number of distinct prover addresses seen from an IP address after which a validator bans it, or 0 for no limit.
+    // #[clap(default_value = "32", long = "operator-max-addresses-per-ip")]
+    // pub operator_max_addresses_per_ip: usize,
+    // /// Specify the fraction of shares from an IP address that a validator rejects as invalid before it bans the IP address.
+    // #[clap(default_value = "0.5", long = "operator-max-reject-rate")]
+    // pub operator_max_reject_rate: f64,
+    // /// Specify the maximum number of share nonces a validator remembers for each block template.
+    // #[clap(default_value = "1000000", long = "operator-max-known-nonces")]
+    // pub operator_max_known_nonces: usize,
+    // /// Specify the maximum number of shares a validator verifies or queues for verification at once.
+    // #[clap(default_value = "256", long = "operator-max-pending-verifications")]
+    // pub operator_max_pending_verifications: usize,
+
+    /// Specify the IP address and port for the node server, e.g. `[::]:4133` to listen on all IPv6 and IPv4 interfaces.
     #[clap(parse(try_from_str), default_value = "0.0.0.0:4133", long = "node")]
     pub node: SocketAddr,
+    /// If the flag is set, the node maps its listener port on the gateway with UPnP, or NAT-PMP, so that peers are able to connect to it.
+    #[clap(long = "upnp")]
+    pub upnp: bool,
     /// Specify the IP address and port of a peer to connect to.
     #[clap(long = "connect")]
     pub connect: Option<String>,
+    /// Specify a comma-separated list of peers that the node keeps permanently connected to, e.g. `--trusted-peers ip:port,ip:port`.
+    /// The trusted peers are exempt from eviction, bans, and rate limits, and are reconnected to with backoff if lost.
+    #[clap(long = "trusted-peers")]
+    pub trusted_peers: Option<String>,
+    /// Specify a comma-separated list of DNS seeds, whose A and AAAA records are candidate peers, e.g. `--dns-seeds host,host:port`.
+    #[clap(long = "dns-seeds")]
+    pub dns_seeds: Option<String>,
+    /// If the flag is set, the node skips loading the peers it knew before it restarted, and discovers peers from the sync nodes.
+    /// The banned peer IPs are loaded regardless.
+    #[clap(long = "fresh-peers")]
+    pub fresh_peers: bool,
+    /// Specify the number of hours after which a known peer that was not seen is dropped when the node restarts.
+    #[clap(default_value = "168", long = "known-peers-max-age")]
+    pub known_peers_max_age: u64,
+    /// Specify the maximum number of inbound connections from a single IP address, or 0 for no limit.
+    #[clap(default_value = "2", long = "max-connections-per-ip")]
+    pub max_connections_per_ip: usize,
+    /// Specify the maximum number of inbound connections from a single /24 IPv4 or /48 IPv6 subnet, or 0 for no limit.
+    #[clap(default_value = "8", long = "max-connections-per-subnet")]
+    pub max_connections_per_subnet: usize,
+    /// Specify the number of seconds to wait for the `Pong` of a `Ping` before it counts as missed.
+    /// A peer that misses 3 consecutive pongs is disconnected.
+    #[clap(default_value = "30", long = "pong-timeout")]
+    pub pong_timeout: u64,
+    /// If set, prunes the transactions of blocks buried beyond the given depth (at least 4096 blocks).
+    #[clap(long = "ledger-pruning-depth")]
+    pub ledger_pruning_depth: Option<u32>,
+    /// Specify the number of recently accessed blocks held in memory by the ledger reader.
+    #[clap(default_value = "256", long = "ledger-cache-size")]
+    pub ledger_cache_size: usize,
+    /// Specify the maximum number of blocks that the ledger reverts in order to switch to a fork.
+    #[clap(default_value = "4096", long = "max-reorg-depth")]
+    pub max_reorg_depth: u32,
+    /// If the flag is set, the ledger switches to a fork beyond the maximum reorg depth, to recover from a deep split of the network.
+    #[clap(long = "allow-deep-reorgs")]
+    pub allow_deep_reorgs: bool,
+    /// If the flag is set, the ledger maintains an index of transactions by record owner and commitment.
+    #[clap(long = "txindex")]
+    pub txindex: bool,
+    /// If the flag is set, each block added to the ledger is synced to disk before the node proceeds, to survive a crash of the machine.
+    #[clap(long = "db-sync-writes")]
+    pub db_sync_writes: bool,
+    /// Specify the number of seconds that the timestamp of an added block may be ahead of the local clock.
+    #[clap(default_value = "90", long = "max-future-block-time")]
+    pub max_future_block_time: u32,
 
     /// Specify the IP address and port for the RPC server.
     #[clap(parse(try_from_str), default_value = "0.0.0.0:3033", long = "rpc")]
     pub rpc: SocketAddr,
+    /// Specify the IP address and port for the RPC WebSocket server, which serves the subscriptions.
+    #[clap(parse(try_from_str), long = "rpc-ws")]
+    pub rpc_ws: Option<SocketAddr>,
     /// Specify the username for the RPC server.
     #[clap(default_value = "root", long = "username")]
     pub rpc_username: String,
@@ -86,6 +296,11 @@ impl CLI {
 
         // Parse optional subcommands first.
         match self.commands {
+            // The ledger maintenance features report through the logger, as they may run for a long time.
+            Some(Command::Ledger(command)) => {
+                info!("{}", command.parse()?);
+                Ok(())
+            }
             Some(command) => {
                 println!("{}", command.parse()?);
                 Ok(())
@@ -200,18 +415,30 @@ impl CLI {
 pub enum Command {
     #[clap(name = "clean", about = "Removes the ledger files from storage")]
     Clean(Clean),
+    #[clap(name = "prune", about = "Prunes the transactions of old blocks from the ledger in storage")]
+    Prune(Prune),
+    #[clap(name = "ledger", about = "Ledger maintenance features")]
+    Ledger(LedgerFeatures),
     #[clap(name = "update", about = "Updates snarkOS to the latest version")]
     Update(Update),
     #[clap(name = "experimental", about = "Experimental features")]
     Experimental(Experimental),
+    // #[clap(name = "operator", about = "Operator features")]
+    // Operator(Operator),
+    // #[clap(name = "prover", about = "Prover features")]
+    // Prover(ProverFeatures),
 }
 
 impl Command {
     pub fn parse(self) -> Result<String> {
         match self {
             Self::Clean(command) => command.parse(),
+            Self::Prune(command) => command.parse(),
+            Self::Ledger(command) => command.parse(),
             Self::Update(command) => command.parse(),
             Self::Experimental(command) => command.parse(),
+            // Self::Operator(command) => command.parse(),
+            // Self::Prover(command) => command.parse(),
         }
     }
 }
@@ -249,6 +476,352 @@ impl Clean {
     }
 }
 
+#[derive(Debug, Parser)]
+pub struct Prune {
+    /// Specify the network of the ledger to prune.
+    #[clap(default_value = "3", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to prune.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the depth beyond which the transactions of blocks are pruned.
+    #[clap(default_value = "4096", long = "depth")]
+    pub depth: u32,
+}
+
+impl Prune {
+    pub fn parse(self) -> Result<String> {
+        // A type for Aleo Testnet3.
+        pub type Testnet3 = snarkvm::prelude::Testnet3;
+
+        ensure!(
+            self.depth >= MINIMUM_PRUNING_DEPTH,
+            "The pruning depth must be at least {}",
+            MINIMUM_PRUNING_DEPTH
+        );
+
+        match self.network {
+            3 => Self::prune_ledger::<Testnet3>(self.network, self.dev, self.depth),
+            _ => bail!("Unsupported network"),
+        }
+    }
+
+    /// Prunes the specified ledger in place.
+    fn prune_ledger<N: Network>(network: u16, dev: Option<u16>, depth: u32) -> Result<String> {
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(network, dev);
+        if !path.exists() {
+            return Ok(format!("No ledger files were found in storage. ({})", path.display()));
+        }
+
+        // Open the ledger and prune it to the given depth.
+        let ledger = LedgerState::<N, ReadWrite>::open_writer::<RocksDB, _>(&path)?;
+        let pruned_block_height = ledger.prune_to_depth(depth, None)?;
+
+        match pruned_block_height {
+            0 => Ok(format!("The ledger has no blocks beyond a depth of {}. ({})", depth, path.display())),
+            _ => Ok(format!(
+                "Successfully pruned the ledger below block {}. ({})",
+                pruned_block_height,
+                path.display()
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct LedgerFeatures {
+    #[clap(subcommand)]
+    commands: LedgerCommands,
+}
+
+impl LedgerFeatures {
+    pub fn parse(self) -> Result<String> {
+        match self.commands {
+            LedgerCommands::Verify(command) => command.parse(),
+            LedgerCommands::Export(command) => command.parse(),
+            LedgerCommands::Import(command) => command.parse(),
+            LedgerCommands::Rollback(command) => command.parse(),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum LedgerCommands {
+    #[clap(name = "verify", about = "Verifies the integrity of the ledger in storage, without starting the node")]
+    Verify(Verify),
+    #[clap(name = "export", about = "Exports the blocks of the ledger in storage to a portable block file")]
+    Export(Export),
+    #[clap(name = "import", about = "Imports the blocks of a portable block file into the ledger in storage")]
+    Import(Import),
+    #[clap(name = "rollback", about = "Reverts the ledger in storage to the given block height, without starting the node")]
+    Rollback(Rollback),
+}
+
+#[derive(Debug, Parser)]
+pub struct Verify {
+    /// Specify the network of the ledger to verify.
+    #[clap(default_value = "3", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to verify.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the block height to start verifying from.
+    #[clap(default_value = "0", long = "from-height")]
+    pub from_height: u32,
+    /// If the flag is set, only the block headers and their linkage are verified.
+    #[clap(long)]
+    pub fast: bool,
+}
+
+impl Verify {
+    pub fn parse(self) -> Result<String> {
+        // A type for Aleo Testnet3.
+        pub type Testnet3 = snarkvm::prelude::Testnet3;
+
+        match self.network {
+            3 => self.verify_ledger::<Testnet3>(),
+            _ => bail!("Unsupported network"),
+        }
+    }
+
+    /// Walks the specified ledger from the starting block height, failing on the first corrupted block.
+    fn verify_ledger<N: Network>(&self) -> Result<String> {
+        // The number of blocks in between progress reports.
+        const PROGRESS_INTERVAL: u32 = 1_000;
+
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        ensure!(path.exists(), "No ledger files were found in storage. ({})", path.display());
+
+        // Open the ledger in read-only mode, dropping its background reader, as the ledger is verified as of now.
+        let (ledger, _reader_resource) = LedgerState::<N, ReadOnly>::open_reader::<RocksDB<ReadOnly>, _>(&path)?;
+        let latest_block_height = ledger.latest_block_height();
+        ensure!(
+            self.from_height <= latest_block_height,
+            "The starting block height {} exceeds the latest block height {}",
+            self.from_height,
+            latest_block_height
+        );
+
+        for block_height in self.from_height..=latest_block_height {
+            if let Err(error) = ledger.verify_block(block_height, self.fast) {
+                bail!("The ledger is corrupted at block {}: {}", block_height, error);
+            }
+
+            if (block_height - self.from_height) % PROGRESS_INTERVAL == 0 || block_height == latest_block_height {
+                let progress = (block_height - self.from_height + 1) as f64 / (latest_block_height - self.from_height + 1) as f64 * 100f64;
+                info!("Verified the ledger up to block {} ({:.2}%)", block_height, progress);
+            }
+        }
+
+        Ok(format!(
+            "Successfully verified blocks {} to {} of the ledger{}. ({})",
+            self.from_height,
+            latest_block_height,
+            if self.fast { " (headers and linkage only)" } else { "" },
+            path.display()
+        ))
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct Export {
+    /// Specify the network of the ledger to export.
+    #[clap(default_value = "3", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to export.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path of the block file to write.
+    #[clap(long = "path")]
+    pub path: PathBuf,
+    /// Specify the block height to export up to, defaulting to the latest block.
+    #[clap(long = "to-height")]
+    pub to_height: Option<u32>,
+}
+
+impl Export {
+    pub fn parse(self) -> Result<String> {
+        // A type for Aleo Testnet3.
+        pub type Testnet3 = snarkvm::prelude::Testnet3;
+
+        match self.network {
+            3 => self.export_ledger::<Testnet3>(),
+            _ => bail!("Unsupported network"),
+        }
+    }
+
+    /// Writes the canon blocks of the specified ledger to the block file.
+    fn export_ledger<N: Network>(&self) -> Result<String> {
+        // The number of blocks in between progress reports.
+        const PROGRESS_INTERVAL: u32 = 1_000;
+
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        ensure!(path.exists(), "No ledger files were found in storage. ({})", path.display());
+
+        // Open the ledger in read-only mode, dropping its background reader, as the ledger is exported as of now.
+        let (ledger, _reader_resource) = LedgerState::<N, ReadOnly>::open_reader::<RocksDB<ReadOnly>, _>(&path)?;
+        let end_block_height = self.to_height.unwrap_or_else(|| ledger.latest_block_height());
+
+        ledger.export_blocks(&self.path, end_block_height, |block_height| {
+            if block_height % PROGRESS_INTERVAL == 0 || block_height == end_block_height {
+                let progress = (block_height + 1) as f64 / (end_block_height + 1) as f64 * 100f64;
+                info!("Exported the ledger up to block {} ({:.2}%)", block_height, progress);
+            }
+        })?;
+
+        Ok(format!(
+            "Successfully exported blocks 0 to {} of the ledger to {}",
+            end_block_height,
+            self.path.display()
+        ))
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct Import {
+    /// Specify the network of the ledger to import into.
+    #[clap(default_value = "3", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to import into.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path of the block file to read.
+    #[clap(long = "path")]
+    pub path: PathBuf,
+}
+
+impl Import {
+    pub fn parse(self) -> Result<String> {
+        // A type for Aleo Testnet3.
+        pub type Testnet3 = snarkvm::prelude::Testnet3;
+
+        match self.network {
+            3 => self.import_ledger::<Testnet3>(),
+            _ => bail!("Unsupported network"),
+        }
+    }
+
+    /// Adds the blocks of the block file to the specified ledger, creating the ledger if it does not exist.
+    fn import_ledger<N: Network>(&self) -> Result<String> {
+        // The number of blocks in between progress reports.
+        const PROGRESS_INTERVAL: u32 = 1_000;
+
+        // Open the ledger, which must not be in use by a running node.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        let ledger = LedgerState::<N, ReadWrite>::open_writer::<RocksDB, _>(&path)?;
+        let thread_pool = rayon::ThreadPoolBuilder::new().build()?;
+
+        let latest_block_height = ledger.import_blocks(&self.path, &thread_pool, |block_height| {
+            if block_height % PROGRESS_INTERVAL == 0 {
+                info!("Imported the ledger up to block {}", block_height);
+            }
+        })?;
+
+        Ok(format!(
+            "Successfully imported {} into the ledger, which is now at block {}. ({})",
+            self.path.display(),
+            latest_block_height,
+            path.display()
+        ))
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct Rollback {
+    /// Specify the network of the ledger to roll back.
+    #[clap(default_value = "3", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to roll back.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the block height to roll the ledger back to, which becomes the latest block.
+    #[clap(long = "to-height")]
+    pub to_height: u32,
+    /// If the flag is set, the blocks that would be removed are reported, without modifying the ledger.
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+impl Rollback {
+    pub fn parse(self) -> Result<String> {
+        // A type for Aleo Testnet3.
+        pub type Testnet3 = snarkvm::prelude::Testnet3;
+
+        match self.network {
+            3 => self.rollback_ledger::<Testnet3>(),
+            _ => bail!("Unsupported network"),
+        }
+    }
+
+    ///
+    /// Reverts the specified ledger to the given block height, removing the later blocks
+    /// along with their entries in the transaction index.
+    ///
+    /// The ledger may not be rolled back below its pruned block height, as the transactions
+    /// of the pruned blocks are no longer available to rebuild the ledger state from.
+    ///
+    fn rollback_ledger<N: Network>(&self) -> Result<String> {
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        ensure!(path.exists(), "No ledger files were found in storage. ({})", path.display());
+
+        // Open the ledger with write access, which fails if a running node holds the lock of the database.
+        let ledger = match LedgerState::<N, ReadWrite>::open_writer::<RocksDB, _>(&path) {
+            Ok(ledger) => ledger,
+            Err(error) if error.to_string().contains("LOCK") => {
+                bail!("The ledger is in use by a running node, stop the node before rolling back. ({})", path.display())
+            }
+            Err(error) => return Err(error),
+        };
+
+        let latest_block_height = ledger.latest_block_height();
+        ensure!(
+            self.to_height < latest_block_height,
+            "The block height {} is not below the latest block height {}",
+            self.to_height,
+            latest_block_height
+        );
+        let pruned_block_height = ledger.pruned_block_height()?;
+        ensure!(
+            self.to_height >= pruned_block_height,
+            "The block height {} is below the pruned block height {}",
+            self.to_height,
+            pruned_block_height
+        );
+
+        if self.dry_run {
+            let block_hashes = ledger.get_block_hashes(self.to_height + 1, latest_block_height)?;
+            for (block_height, block_hash) in (self.to_height + 1..).zip(&block_hashes) {
+                info!("Would remove block {} ({})", block_height, block_hash);
+            }
+            return Ok(format!(
+                "Rolling back would remove {} blocks, leaving block {} ({}) as the latest block. ({})",
+                block_hashes.len(),
+                self.to_height,
+                ledger.get_block_hash(self.to_height)?,
+                path.display()
+            ));
+        }
+
+        // Load the transaction index, if the node maintains one, so that the entries of the removed blocks are removed with them.
+        if ledger.indexed_block_height()?.is_some() {
+            ledger.enable_transaction_index()?;
+        }
+        let removed_blocks = ledger.revert_to_block_height_with_maximum_depth(self.to_height, u32::MAX)?;
+
+        Ok(format!(
+            "Successfully removed {} blocks, leaving block {} ({}) as the latest block. ({})",
+            removed_blocks.len(),
+            ledger.latest_block_height(),
+            ledger.latest_block_hash(),
+            path.display()
+        ))
+    }
+}
+
 #[derive(Debug, Parser)]
 pub struct Update {
     /// Lists all available versions of snarkOS
@@ -336,3 +909,205 @@ impl NewAccount {
         Ok(output)
     }
 }
+
+// #[derive(Debug, Parser)]
+// pub struct Operator {
+//     #[clap(subcommand)]
+//     commands: OperatorCommands,
+// }
+//
+// impl Operator {
+//     pub fn parse(self) -> Result<String> {
+//         match self.commands {
+//             OperatorCommands::Export(command) => command.parse(),
+//         }
+//     }
+// }
+//
+// #[derive(Debug, Parser)]
+// pub enum OperatorCommands {
+//     #[clap(name = "export", about = "Exports the shares of each prover in each round as CSV or JSON")]
+//     Export(Export),
+// }
+//
+// #[derive(Debug, Parser)]
+// pub struct Export {
+//     /// Specify the network of the operator state to export.
+//     #[clap(default_value = "2", long = "network")]
+//     pub network: u16,
+//     /// Enables development mode, specify the unique ID of the local node to export.
+//     #[clap(long)]
+//     pub dev: Option<u16>,
+//     /// Specify the format of the export, either `csv` or `json`.
+//     #[clap(default_value = "csv", long = "format")]
+//     pub format: String,
+//     /// Specify the path of the file to write the export to.
+//     #[clap(long = "path")]
+//     pub path: std::path::PathBuf,
+//     /// Specify the lowest block height of the rounds to export.
+//     #[clap(default_value = "0", long = "start-height")]
+//     pub start_height: u32,
+//     /// Specify the highest block height of the rounds to export.
+//     #[clap(long = "end-height")]
+//     pub end_height: Option<u32>,
+//     /// Specify the address of a prover, to only export its shares.
+//     #[clap(long = "prover")]
+//     pub prover: Option<String>,
+// }
+//
+// impl Export {
+//     pub fn parse(self) -> Result<String> {
+//         use std::io::{BufWriter, Write as _};
+//         type CurrentNetwork = snarkvm::prelude::Testnet3;
+//
+//         ensure!(self.format == "csv" || self.format == "json", "The export format must be either 'csv' or 'json'");
+//         let prover = self.prover.as_deref().map(Address::<CurrentNetwork>::from_str).transpose()?;
+//         let end_height = self.end_height.unwrap_or(u32::MAX);
+//         ensure!(self.start_height <= end_height, "The start height must not exceed the end height");
+//
+//         // Open the operator state as a reader, so that it may be exported while the operator is running.
+//         let storage_path = aleo_std::aleo_operator_dir(self.network, self.dev);
+//         let operator = OperatorState::<CurrentNetwork, ReadOnly>::open_reader::<RocksDB, _>(&storage_path)?;
+//
+//         // Write each row as it is read, so that the operator state is never loaded into memory.
+//         let mut writer = BufWriter::new(std::fs::File::create(&self.path)?);
+//         let mut num_rows = 0usize;
+//         match self.format.as_str() {
+//             "csv" => writeln!(writer, "block_height,coinbase_commitment,prover_address,shares,round_status")?,
+//             _ => write!(writer, "[")?,
+//         }
+//         operator.for_each_share_row(self.start_height..=end_height, prover.as_ref(), |row| {
+//             match self.format.as_str() {
+//                 "csv" => writeln!(
+//                     writer,
+//                     "{},{},{},{},{}",
+//                     row.block_height, row.coinbase_commitment, row.prover, row.shares, row.round_status
+//                 )?,
+//                 _ => {
+//                     if num_rows > 0 {
+//                         write!(writer, ",")?;
+//                     }
+//                     serde_json::to_writer(&mut writer, &row)?;
+//                 }
+//             }
+//             num_rows += 1;
+//             Ok(())
+//         })?;
+//         if self.format == "json" {
+//             writeln!(writer, "]")?;
+//         }
+//         writer.flush()?;
+//
+//         Ok(format!("Exported {} rows to {}", num_rows, self.path.display()))
+//     }
+// }
+//
+// #[derive(Debug, Parser)]
+// pub struct ProverFeatures {
+//     #[clap(subcommand)]
+//     commands: ProverCommands,
+// }
+//
+// impl ProverFeatures {
+//     pub fn parse(self) -> Result<String> {
+//         match self.commands {
+//             ProverCommands::ExportCoinbase(command) => command.parse(),
+//             ProverCommands::Benchmark(command) => command.parse(),
+//         }
+//     }
+// }
+//
+// #[derive(Debug, Parser)]
+// pub enum ProverCommands {
+//     #[clap(name = "export-coinbase", about = "Exports the coinbase records of the prover as JSON")]
+//     ExportCoinbase(ExportCoinbase),
+//     #[clap(name = "benchmark", about = "Measures the proof rate of this machine, without connecting to any network")]
+//     Benchmark(Benchmark),
+// }
+//
+// #[derive(Debug, Parser)]
+// pub struct ExportCoinbase {
+//     /// Specify the network of the prover state to export.
+//     #[clap(default_value = "2", long = "network")]
+//     pub network: u16,
+//     /// Enables development mode, specify the unique ID of the local node to export.
+//     #[clap(long)]
+//     pub dev: Option<u16>,
+//     /// Specify the path of the file to write the export to.
+//     #[clap(long = "path")]
+//     pub path: std::path::PathBuf,
+// }
+//
+// impl ExportCoinbase {
+//     pub fn parse(self) -> Result<String> {
+//         use std::io::{BufWriter, Write as _};
+//         type CurrentNetwork = snarkvm::prelude::Testnet3;
+//
+//         // Open the prover state as a reader, so that it may be exported while the prover is running.
+//         let storage_path = aleo_std::aleo_prover_dir(self.network, self.dev);
+//         let prover = ProverState::<CurrentNetwork, ReadOnly>::open::<RocksDB, _>(&storage_path)?;
+//
+//         let mut records = prover.to_coinbase_records();
+//         records.sort_by_key(|(block_height, _)| *block_height);
+//         let rows: Vec<_> = records
+//             .iter()
+//             .map(|(block_height, record)| {
+//                 serde_json::json!({ "block_height": block_height, "commitment": record.commitment(), "record": record })
+//             })
+//             .collect();
+//
+//         let mut writer = BufWriter::new(std::fs::File::create(&self.path)?);
+//         serde_json::to_writer_pretty(&mut writer, &rows)?;
+//         writer.flush()?;
+//
+//         Ok(format!("Exported {} coinbase records to {}", rows.len(), self.path.display()))
+//     }
+// }
+//
+// #[derive(Debug, Parser)]
+// pub struct Benchmark {
+//     /// Specify the duration of the benchmark in seconds.
+//     #[clap(default_value = "60", long = "duration")]
+//     pub duration: u64,
+//     /// Specify the number of threads to prove with, which defaults to the number of logical cores.
+//     #[clap(long = "threads")]
+//     pub threads: Option<usize>,
+//     /// Specify the number of independent proof attempts to run concurrently.
+//     #[clap(default_value = "1", long = "parallel-proofs")]
+//     pub parallel_proofs: usize,
+//     /// Specify the backend to run the proof attempts on.
+//     #[clap(default_value = "cpu", long = "backend")]
+//     pub backend: String,
+//     /// Enables JSON output, for scripting.
+//     #[clap(long)]
+//     pub json: bool,
+// }
+//
+// impl Benchmark {
+//     pub fn parse(self) -> Result<String> {
+//         type CurrentNetwork = snarkvm::prelude::Testnet3;
+//
+//         // Run the same proving loop as a prover, on a synthetic block template.
+//         let backend = proving_backend::<CurrentNetwork>(&self.backend)?;
+//         let duration = std::time::Duration::from_secs(self.duration);
+//         let report =
+//             benchmark_proving::<CurrentNetwork, Prover<CurrentNetwork>>(&*backend, duration, self.threads, self.parallel_proofs)?;
+//         if self.json {
+//             return Ok(serde_json::to_string(&report)?);
+//         }
+//
+//         let mut output = "".to_string();
+//         writeln!(output, "\n {:>16}  {}", "Backend".cyan().bold(), report.backend)?;
+//         writeln!(output, " {:>16}  {:.2}", "Proofs/s".cyan().bold(), report.proofs_per_second)?;
+//         writeln!(output, " {:>16}  {} in {:.1} seconds", "Proofs".cyan().bold(), report.proofs, report.duration_secs)?;
+//         writeln!(output, " {:>16}  {:.1} ms", "Mean latency".cyan().bold(), report.mean_latency_ms)?;
+//         writeln!(output, " {:>16}  {:.1} ms", "Median latency".cyan().bold(), report.median_latency_ms)?;
+//         writeln!(output, " {:>16}  {} ({} parallel proofs)", "Threads".cyan().bold(), report.threads, report.parallel_proofs)?;
+//         match report.peak_memory_bytes {
+//             Some(bytes) => writeln!(output, " {:>16}  {} MiB", "Peak memory".cyan().bold(), bytes / (1024 * 1024))?,
+//             None => writeln!(output, " {:>16}  unavailable", "Peak memory".cyan().bold())?,
+//         }
+//
+//         Ok(output)
+//     }
+// }
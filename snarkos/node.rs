@@ -18,17 +18,23 @@ use crate::CLI;
 
 use snarkos_consensus::account::Account;
 use snarkos_environment::{helpers::Status, Environment};
-use snarkos_network::{ledger::*, message::*, peers::*, state::State};
+use snarkos_network::{
+    helpers::{ConnectionLimits, ReorgLimit},
+    ledger::*,
+    message::*,
+    peers::*,
+    state::State,
+};
 use snarkvm::prelude::*;
 
 #[cfg(feature = "rpc")]
-use snarkos_rpc::{initialize_rpc_server, RpcContext};
+use snarkos_rpc::{initialize_rpc_server, initialize_rpc_ws_server, RpcContext};
 
 #[cfg(any(feature = "test", feature = "prometheus"))]
 use snarkos_metrics as metrics;
 
 use anyhow::Result;
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 use tokio::sync::oneshot;
 
 #[derive(Clone)]
@@ -47,6 +53,11 @@ impl<N: Network, E: Environment> Node<N, E> {
 
         let node = Self { state: state.clone() };
 
+        // Map the listener port on the gateway, if specified.
+        if cli.upnp {
+            node.state.initialize_port_mapping().await;
+        }
+
         /// Returns the storage path of the ledger.
         pub(crate) fn ledger_storage_path(cli: &CLI, _local_ip: SocketAddr) -> PathBuf {
             if cfg!(feature = "test") {
@@ -58,6 +69,25 @@ impl<N: Network, E: Environment> Node<N, E> {
             }
         }
 
+        /// Returns the trusted peers, from the comma-separated list of IP addresses and ports.
+        pub(crate) fn trusted_peers(cli: &CLI) -> Result<Vec<SocketAddr>> {
+            Ok(cli
+                .trusted_peers
+                .iter()
+                .flat_map(|peers| peers.split(','))
+                .map(|peer_ip| peer_ip.trim().parse())
+                .collect::<Result<_, _>>()?)
+        }
+
+        /// Returns the path of the file that the known peers are written to.
+        pub(crate) fn known_peers_path(cli: &CLI, _local_ip: SocketAddr) -> PathBuf {
+            if cfg!(feature = "test") {
+                PathBuf::from(format!("/tmp/snarkos-test-peers-{}.json", _local_ip.port()))
+            } else {
+                aleo_std::aleo_ledger_dir(cli.network, cli.dev).with_extension("peers.json")
+            }
+        }
+
         // /// Returns the storage path of the validator.
         // pub(crate) fn validator_storage_path(&self, _local_ip: SocketAddr) -> PathBuf {
         //     if cfg!(feature = "test") {
@@ -70,6 +100,16 @@ impl<N: Network, E: Environment> Node<N, E> {
         //     }
         // }
         //
+        // /// Returns the coinbase recipients of the solo miner, from a comma-separated list, or a file with one address per line.
+        // pub(crate) fn miner_recipients<N: Network>(cli: &CLI) -> Result<Vec<Address<N>>> {
+        //     let recipients = match &cli.miner_recipients {
+        //         Some(recipients) if std::path::Path::new(recipients).is_file() => std::fs::read_to_string(recipients)?,
+        //         Some(recipients) => recipients.replace(',', "\n"),
+        //         None => return Ok(vec![]),
+        //     };
+        //     recipients.lines().map(str::trim).filter(|line| !line.is_empty()).map(Address::from_str).collect()
+        // }
+        //
         // /// Returns the storage path of the prover.
         // pub(crate) fn prover_storage_path(&self, _local_ip: SocketAddr) -> PathBuf {
         //     if cfg!(feature = "test") {
@@ -81,6 +121,29 @@ impl<N: Network, E: Environment> Node<N, E> {
         //     }
         // }
 
+        // Keep the node permanently connected to the trusted peers.
+        node.state.peers().add_trusted_peers(trusted_peers(cli)?).await;
+
+        // Discover candidate peers from the DNS seeds, in addition to those of the environment.
+        if let Some(dns_seeds) = &cli.dns_seeds {
+            let dns_seeds = dns_seeds.split(',').map(str::trim).filter(|seed| !seed.is_empty()).map(String::from).collect();
+            node.state.peers().add_dns_seeds(dns_seeds).await;
+        }
+
+        // Limit the inbound connections from a single IP address and from a single subnet.
+        let connection_limits = ConnectionLimits::new(cli.max_connections_per_ip, cli.max_connections_per_subnet);
+        node.state.peers().set_connection_limits(connection_limits).await;
+
+        // Measure the round-trip time of each peer, and disconnect from the peers that stop answering pings.
+        node.state.peers().set_pong_timeout(Duration::from_secs(cli.pong_timeout)).await;
+
+        // Reconnect to the peers known from before the restart, unless the node starts with fresh peers.
+        let known_peers_max_age = Duration::from_secs(cli.known_peers_max_age.saturating_mul(3600));
+        node.state
+            .peers()
+            .initialize_known_peers(known_peers_path(cli, *node.local_ip()), cli.fresh_peers, known_peers_max_age)
+            .await?;
+
         // Initialize the ledger storage path.
         let ledger_storage_path = ledger_storage_path(cli, *node.local_ip());
         // // Initialize the prover storage path.
@@ -89,13 +152,78 @@ impl<N: Network, E: Environment> Node<N, E> {
         // let validator_storage_path = node.validator_storage_path(local_ip);
 
         // Initialize a new instance for managing the ledger.
-        let (ledger, ledger_handler) = Ledger::<N, E>::open::<_>(&ledger_storage_path, state.clone()).await?;
+        let ledger_config = LedgerConfig {
+            pruning_depth: cli.ledger_pruning_depth,
+            block_cache_capacity: cli.ledger_cache_size,
+            reorg_limit: ReorgLimit::new(cli.max_reorg_depth, cli.allow_deep_reorgs),
+            transaction_index: cli.txindex,
+            sync_writes: cli.db_sync_writes,
+            maximum_future_block_time: cli.max_future_block_time,
+        };
+        let (ledger, ledger_handler) = Ledger::<N, E>::open::<_>(&ledger_storage_path, ledger_config, state.clone()).await?;
 
         // // Initialize a new instance for managing the prover.
-        // let (prover, prover_handler) = Prover::open::<_>(&prover_storage_path, state.clone()).await?;
+        // let prover_config = ProverConfig {
+        //     pools: cli.prover_pools.iter().flat_map(|pools| pools.split(',')).map(SocketAddr::from_str).collect::<Result<_, _>>()?,
+        //     pool_timeout: Duration::from_secs(cli.prover_pool_timeout),
+        //     primary_pool_retry: Duration::from_secs(cli.prover_primary_pool_retry),
+        //     maximum_reconnect_backoff: Duration::from_secs(cli.prover_max_reconnect_backoff),
+        //     coinbase_confirmations: cli.prover_coinbase_confirmations,
+        //     worker: cli.prover_worker.clone(),
+        //     miner_recipients: miner_recipients(cli)?,
+        //     verify_transactions: !cli.miner_skip_transaction_checks,
+        //     readiness_gate: cli.miner_wait_for_sync.then(|| ReadinessGate {
+        //         sync_tolerance: cli.miner_sync_tolerance,
+        //         minimum_peers: cli.miner_minimum_peers,
+        //     }),
+        //     mine_empty_first: cli.mine_empty_first,
+        //     threads: cli.prover_threads,
+        //     parallel_proofs: cli.prover_parallel_proofs,
+        //     intensity: cli.prover_intensity,
+        //     backend: proving_backend(&cli.prover_backend)?,
+        //     self_test: cli.prover_selftest,
+        //     stale_work_threshold: cli.prover_stale_work_threshold,
+        // };
+        // let (prover, prover_handler) = Prover::open::<_>(&prover_storage_path, prover_config, state.clone()).await?;
         //
         // // Initialize a new instance for managing the validator.
-        // let (validator, validator_handler) = Operator::open::<_>(&validator_storage_path, state.clone()).await?;
+        // let recipients = cli.operator_recipients.iter().flat_map(|recipients| recipients.split(',')).map(Address::from_str);
+        // let operator_config = OperatorConfig {
+        //     base_share_difficulty: cli.operator_base_share_difficulty,
+        //     minimum_share_difficulty: cli.operator_min_share_difficulty,
+        //     heartbeat: Duration::from_millis(cli.operator_heartbeat_ms),
+        //     prover_timeout: Duration::from_secs(cli.operator_prover_timeout),
+        //     stale_template_grace: Duration::from_secs(cli.operator_stale_template_grace),
+        //     minimum_template_interval: Duration::from_secs(cli.operator_min_template_interval),
+        //     template_refresh_threshold: cli.operator_template_refresh_threshold,
+        //     template_refresh_age: Duration::from_secs(cli.operator_template_refresh_age),
+        //     maximum_template_transactions: cli.operator_max_template_transactions,
+        //     maximum_template_size: cli.operator_max_template_size,
+        //     minimum_transaction_fee: cli.operator_min_transaction_fee,
+        //     pool_register_interval: Duration::from_secs(cli.operator_register_interval),
+        //     maximum_provers: cli.operator_max_provers,
+        //     disconnect_excess_provers: cli.operator_disconnect_excess_provers,
+        //     require_registration: cli.operator_require_registration,
+        //     share_accounting: cli.operator_pplns_window.map_or(ShareAccounting::Proportional, ShareAccounting::Pplns),
+        //     recipients: recipients.collect::<Result<_>>()?,
+        //     private_key: Some(*account.private_key()),
+        //     pool_fee_percent: cli.pool_fee_percent,
+        //     payout_confirmations: cli.operator_payout_confirmations,
+        //     payout_dry_run: cli.operator_payout_dry_run,
+        //     reset_stats_each_round: cli.operator_reset_stats_each_round,
+        //     block_hook: cli.operator_block_hook.clone(),
+        //     orphan_confirmations: cli.operator_orphan_confirmations,
+        //     roll_over_orphaned_shares: cli.operator_roll_over_orphaned_shares,
+        //     round_retention_depth: cli.operator_round_retention,
+        //     invalid_share_limit: cli.operator_invalid_share_limit,
+        //     invalid_share_window: Duration::from_secs(cli.operator_invalid_share_window),
+        //     ban_duration: Duration::from_secs(cli.operator_ban_duration),
+        //     maximum_addresses_per_ip: cli.operator_max_addresses_per_ip,
+        //     maximum_reject_rate: cli.operator_max_reject_rate,
+        //     maximum_known_nonces: cli.operator_max_known_nonces,
+        //     maximum_pending_verifications: cli.operator_max_pending_verifications,
+        // };
+        // let (validator, validator_handler) = Operator::open::<_>(&validator_storage_path, operator_config, state.clone()).await?;
 
         // Initialise the metrics exporter.
         #[cfg(any(feature = "test", feature = "prometheus"))]
@@ -157,12 +285,22 @@ impl<N: Network, E: Environment> Node<N, E> {
         if !cli.norpc {
             // Initialize a new instance of the RPC node.
             let rpc_context = RpcContext::new(cli.rpc_username.clone(), cli.rpc_password.clone(), address, self.state.clone());
-            let (rpc_node_addr, rpc_node_handle) = initialize_rpc_server::<N, E>(cli.rpc, rpc_context).await;
+            let (rpc_node_addr, rpc_node_handle) = initialize_rpc_server::<N, E>(cli.rpc, rpc_context.clone()).await;
 
             debug!("JSON-RPC node listening on {}", rpc_node_addr);
 
             // Register the task; no need to provide an id, as it will run indefinitely.
             E::resources().register_task(None, rpc_node_handle);
+
+            // Initialize a new instance of the RPC WebSocket node for subscriptions, if specified.
+            if let Some(rpc_ws) = cli.rpc_ws {
+                let (rpc_ws_node_addr, rpc_ws_node_handle) = initialize_rpc_ws_server::<N, E>(rpc_ws, rpc_context).await;
+
+                debug!("JSON-RPC WebSocket node listening on {}", rpc_ws_node_addr);
+
+                // Register the task; no need to provide an id, as it will run indefinitely.
+                E::resources().register_task(None, rpc_ws_node_handle);
+            }
         }
     }
 
@@ -190,6 +328,21 @@ impl<N: Network, E: Environment> Node<N, E> {
         trace!("Proceeding to shut down the ledger...");
         // self.state.ledger().shut_down().await;
 
+        // Shut down the prover.
+        // if E::NODE_TYPE == NodeType::Miner || E::NODE_TYPE == NodeType::Prover {
+        //     trace!("Proceeding to shut down the prover...");
+        //     self.state.prover().shut_down();
+        // }
+
+        // Shut down the operator.
+        // if E::NODE_TYPE == NodeType::Validator {
+        //     trace!("Proceeding to shut down the operator...");
+        //     self.state.operator().shut_down().await;
+        // }
+
+        // Remove the port mapping from the gateway.
+        self.state.remove_port_mapping().await;
+
         // Flush the tasks.
         E::resources().shut_down();
         trace!("Node has shut down.");
@@ -76,7 +76,7 @@ impl TestNode {
         let permanent_args = &["snarkos", "--norpc"];
         let combined_args = permanent_args.iter().chain(extra_args.iter());
         let config = crate::CLI::parse_from(combined_args);
-        let node = Node::<CurrentNetwork, TestEnvironment<CurrentNetwork>>::new(config.node, Account::sample().unwrap())
+        let node = Node::<CurrentNetwork, TestEnvironment<CurrentNetwork>>::new(&config, Account::sample().unwrap())
             .await
             .unwrap();
 
@@ -123,6 +123,43 @@ mod tests {
         assert!(test_node2.connected_peers().await.contains(&test_node1.local_ip()));
     }
 
+    #[tokio::test]
+    async fn test_node_connection_over_ipv6() {
+        // Start 2 snarkOS nodes on the IPv6 loopback.
+        let test_node1 = TestNode::new_with_custom_ip("[::1]", 7000).await;
+        let test_node2 = TestNode::new_with_custom_ip("[::1]", 8000).await;
+
+        // Connect one to the other, which completes the challenge handshake over IPv6.
+        test_node1.connect(test_node2.local_ip()).await.unwrap();
+
+        assert_eq!(test_node1.number_of_connected_peers().await, 1);
+        assert!(test_node1.connected_peers().await.contains(&test_node2.local_ip()));
+
+        assert_eq!(test_node2.number_of_connected_peers().await, 1);
+        assert!(test_node2.connected_peers().await.contains(&test_node1.local_ip()));
+
+        // The second connection attempt should fail.
+        assert!(test_node1.connect(test_node2.local_ip()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_node_connection_to_dual_stack_listener() {
+        // Start a snarkOS node on IPv4, and a snarkOS node on all IPv6 and IPv4 interfaces.
+        let test_node1 = TestNode::new_with_custom_ip("127.0.0.1", 9000).await;
+        let test_node2 = TestNode::new_with_custom_ip("[::]", 10000).await;
+
+        // Connect to the dual-stack node over IPv4.
+        let test_node2_ipv4: SocketAddr = "127.0.0.1:10000".parse().unwrap();
+        test_node1.connect(test_node2_ipv4).await.unwrap();
+
+        assert_eq!(test_node1.number_of_connected_peers().await, 1);
+        assert!(test_node1.connected_peers().await.contains(&test_node2_ipv4));
+
+        // The dual-stack node records the IPv4 address of the peer, rather than its IPv4-mapped IPv6 address.
+        assert_eq!(test_node2.number_of_connected_peers().await, 1);
+        assert!(test_node2.connected_peers().await.contains(&test_node1.local_ip()));
+    }
+
     #[tokio::test]
     async fn test_node_cant_connect_twice() {
         // Start 2 snarkOS nodes.
@@ -183,4 +220,61 @@ mod tests {
         // Assert that snarkOS node can't connect to the extra node.
         assert!(main_test_node.connect(extra_test_node.local_ip()).await.is_err());
     }
+
+    #[tokio::test]
+    async fn test_trusted_peer_bypasses_maximum_peers() {
+        const MAXIMUM_NUMBER_OF_PEERS: usize = TestEnvironment::<CurrentNetwork>::MAXIMUM_NUMBER_OF_PEERS as usize;
+
+        // Start a dual-stack snarkOS node that trusts the IPv6 loopback. Nothing listens on the trusted port,
+        // so that the trusted peer only connects inbound.
+        let main_test_node = TestNode::with_args(&["--node", "[::]:11000", "--trusted-peers", "[::1]:11099"]).await;
+        let main_test_node_ipv4: SocketAddr = "127.0.0.1:11000".parse().unwrap();
+        let main_test_node_ipv6: SocketAddr = "[::1]:11000".parse().unwrap();
+
+        // Fill the snarkOS node up to the maximum number of peers over IPv4.
+        let mut test_nodes = Vec::with_capacity(MAXIMUM_NUMBER_OF_PEERS);
+        for i in 0..MAXIMUM_NUMBER_OF_PEERS {
+            let test_node = TestNode::new_with_custom_ip("127.0.0.1", (11010 + i) as u16).await;
+            test_node.connect(main_test_node_ipv4).await.unwrap();
+            test_nodes.push(test_node);
+        }
+        wait_until!(1, main_test_node.number_of_connected_peers().await == MAXIMUM_NUMBER_OF_PEERS);
+
+        // An untrusted peer is dropped, as the snarkOS node has reached the maximum number of peers.
+        let extra_test_node = TestNode::new_with_custom_ip("127.0.0.1", 11020).await;
+        let _ = extra_test_node.connect(main_test_node_ipv4).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!main_test_node.connected_peers().await.contains(&extra_test_node.local_ip()));
+
+        // The trusted peer is accepted beyond the maximum number of peers.
+        let trusted_test_node = TestNode::new_with_custom_ip("[::1]", 11021).await;
+        let _ = trusted_test_node.connect(main_test_node_ipv6).await;
+        wait_until!(1, main_test_node.connected_peers().await.contains(&trusted_test_node.local_ip()));
+        assert_eq!(main_test_node.number_of_connected_peers().await, MAXIMUM_NUMBER_OF_PEERS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_trusted_peer_bypasses_restriction() {
+        // Start a dual-stack snarkOS node that trusts the IPv6 loopback. Nothing listens on the trusted port,
+        // so that the trusted peer only connects inbound.
+        let main_test_node = TestNode::with_args(&["--node", "[::]:11100", "--trusted-peers", "[::1]:11199"]).await;
+        let main_test_node_ipv4: SocketAddr = "127.0.0.1:11100".parse().unwrap();
+        let main_test_node_ipv6: SocketAddr = "[::1]:11100".parse().unwrap();
+
+        // Ban both loopback addresses.
+        for peer_ip in ["127.0.0.1", "::1"] {
+            main_test_node.node.peers().ban(peer_ip.parse().unwrap(), std::time::Duration::from_secs(3600)).await;
+        }
+
+        // An untrusted peer is dropped, as it is banned.
+        let test_node = TestNode::new_with_custom_ip("127.0.0.1", 11101).await;
+        let _ = test_node.connect(main_test_node_ipv4).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(main_test_node.number_of_connected_peers().await, 0);
+
+        // The trusted peer is accepted, even though it is banned.
+        let trusted_test_node = TestNode::new_with_custom_ip("[::1]", 11102).await;
+        let _ = trusted_test_node.connect(main_test_node_ipv6).await;
+        wait_until!(1, main_test_node.connected_peers().await.contains(&trusted_test_node.local_ip()));
+    }
 }
@@ -0,0 +1,103 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_storage::{
+    storage::{rocksdb::RocksDB, ReadWrite, Storage},
+    LedgerState,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use std::time::Duration;
+
+// This value should be no greater than the number of blocks available in the loaded dump.
+const NUM_BLOCKS: u32 = 1_000;
+// The number of blocks processed at once, mirroring a batch of block responses during sync.
+const BATCH_SIZE: u32 = 100;
+
+/// A type alias for the current version of the network.
+pub type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+fn sync(c: &mut Criterion) {
+    let temp_dir1 = tempfile::tempdir().expect("Failed to open temporary directory").into_path();
+    // Create an empty ledger.
+    let ledger1: LedgerState<CurrentNetwork, ReadWrite> =
+        LedgerState::open_writer_with_increment::<RocksDB, _>(&temp_dir1, 1).expect("Failed to initialize ledger");
+    // Import a dump of a ledger containing 1k blocks.
+    ledger1
+        .storage()
+        .import("benches/storage_1k_blocks")
+        .expect("Couldn't import the test ledger");
+    // Reopen the ledger so that it applies the storage changes to its in-memory components.
+    drop(ledger1);
+    let ledger1: LedgerState<CurrentNetwork, ReadWrite> =
+        LedgerState::open_writer_with_increment::<RocksDB, _>(&temp_dir1, NUM_BLOCKS).expect("Failed to initialize ledger");
+
+    // Prepare the batches of blocks that will be imported by the other test ledgers.
+    let batches = (0..(NUM_BLOCKS - 1) / BATCH_SIZE)
+        .map(|i| ledger1.get_blocks(i * BATCH_SIZE + 1, (i + 1) * BATCH_SIZE).expect("Couldn't find the test blocks"))
+        .collect::<Vec<_>>();
+
+    let thread_pool = rayon::ThreadPoolBuilder::new().build().expect("Failed to initialize a thread pool");
+
+    let mut group = c.benchmark_group("sync");
+
+    // Prepare a test ledger that will be importing the blocks one at a time.
+    let temp_dir2 = tempfile::tempdir().expect("Failed to open temporary directory").into_path();
+    let ledger2 = LedgerState::open_writer_with_increment::<RocksDB, _>(temp_dir2, 1).expect("Failed to initialize ledger");
+
+    let mut i = 0;
+    group.bench_function("add_next_block", |b| {
+        b.iter(|| {
+            if i == batches.len() {
+                ledger2.revert_to_block_height(0).expect("Failed to revert the test ledger");
+                i = 0;
+            }
+            for block in &batches[i] {
+                ledger2.add_next_block(block).expect("Failed to add a test block");
+            }
+            i += 1;
+        })
+    });
+
+    // Prepare a test ledger that will be importing the blocks in batches.
+    let temp_dir3 = tempfile::tempdir().expect("Failed to open temporary directory").into_path();
+    let ledger3 = LedgerState::open_writer_with_increment::<RocksDB, _>(temp_dir3, 1).expect("Failed to initialize ledger");
+
+    let mut i = 0;
+    group.bench_function("add_next_blocks", |b| {
+        b.iter(|| {
+            if i == batches.len() {
+                ledger3.revert_to_block_height(0).expect("Failed to revert the test ledger");
+                i = 0;
+            }
+            ledger3
+                .add_next_blocks(&batches[i], &thread_pool)
+                .expect("Failed to add a batch of test blocks");
+            i += 1;
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    // This benchmark needs quite a bit more time than the default 5s.
+    config = Criterion::default().sample_size(10).measurement_time(Duration::from_secs(60));
+    targets = sync
+);
+criterion_main!(benches);
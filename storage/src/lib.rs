@@ -21,6 +21,16 @@
 extern crate tracing;
 
 pub(crate) mod state;
-pub use state::{LedgerState, Metadata};
+#[cfg(feature = "rocks")]
+pub use state::BlockFileHeader;
+pub use state::{
+    LedgerProof,
+    LedgerState,
+    Metadata,
+    ALEO_MAXIMUM_FORK_DEPTH,
+    DEFAULT_BLOCK_CACHE_CAPACITY,
+    DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS,
+    MINIMUM_PRUNING_DEPTH,
+};
 
 pub mod storage;
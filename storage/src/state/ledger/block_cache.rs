@@ -0,0 +1,128 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use lru::LruCache;
+use parking_lot::Mutex;
+
+/// The number of shards of a block cache, so that concurrent readers rarely contend on the same lock.
+const NUM_SHARDS: u32 = 16;
+
+///
+/// A sharded LRU cache of values keyed by block height.
+///
+/// Consecutive block heights are assigned to different shards, each guarded by its own lock,
+/// so that concurrent lookups of recent blocks do not serialize on a single lock.
+///
+#[derive(Debug)]
+pub(crate) struct BlockCache<V: Clone> {
+    /// The shards of the cache, or an empty list if caching is disabled.
+    shards: Vec<Mutex<LruCache<u32, V>>>,
+}
+
+impl<V: Clone> BlockCache<V> {
+    /// Initializes a new cache holding up to approximately the given number of entries, where `0` disables caching.
+    pub(crate) fn new(capacity: usize) -> Self {
+        let shards = match capacity {
+            0 => Vec::new(),
+            capacity => {
+                let shard_capacity = (capacity + NUM_SHARDS as usize - 1) / NUM_SHARDS as usize;
+                (0..NUM_SHARDS).map(|_| Mutex::new(LruCache::new(shard_capacity))).collect()
+            }
+        };
+
+        Self { shards }
+    }
+
+    /// Returns the value for the given block height, if it is cached.
+    pub(crate) fn get(&self, block_height: u32) -> Option<V> {
+        self.shard(block_height)?.lock().get(&block_height).cloned()
+    }
+
+    /// Caches the given value for the given block height.
+    pub(crate) fn insert(&self, block_height: u32, value: V) {
+        if let Some(shard) = self.shard(block_height) {
+            shard.lock().put(block_height, value);
+        }
+    }
+
+    /// Removes the cached values for the block heights that do not satisfy the given predicate.
+    pub(crate) fn retain<F: Fn(u32) -> bool>(&self, predicate: F) {
+        for shard in &self.shards {
+            let mut shard = shard.lock();
+            let removed_block_heights: Vec<u32> =
+                shard.iter().map(|(block_height, _)| *block_height).filter(|block_height| !predicate(*block_height)).collect();
+            for block_height in removed_block_heights {
+                shard.pop(&block_height);
+            }
+        }
+    }
+
+    /// Removes all cached values.
+    pub(crate) fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().clear();
+        }
+    }
+
+    /// Returns the shard for the given block height, or `None` if caching is disabled.
+    fn shard(&self, block_height: u32) -> Option<&Mutex<LruCache<u32, V>>> {
+        match self.shards.is_empty() {
+            true => None,
+            false => Some(&self.shards[(block_height % NUM_SHARDS) as usize]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_cache() {
+        let cache = BlockCache::<u32>::new(2 * NUM_SHARDS as usize);
+        for block_height in 0..10 {
+            cache.insert(block_height, block_height * 2);
+        }
+
+        assert_eq!(cache.get(3), Some(6));
+        assert_eq!(cache.get(10), None);
+
+        // Remove the values above block height 5.
+        cache.retain(|block_height| block_height <= 5);
+        assert_eq!(cache.get(5), Some(10));
+        assert_eq!(cache.get(6), None);
+
+        cache.clear();
+        assert_eq!(cache.get(0), None);
+    }
+
+    #[test]
+    fn test_block_cache_evicts_least_recently_used() {
+        // Each shard holds a single value.
+        let cache = BlockCache::<u32>::new(NUM_SHARDS as usize);
+        cache.insert(0, 0);
+        cache.insert(NUM_SHARDS, 1);
+        assert_eq!(cache.get(0), None);
+        assert_eq!(cache.get(NUM_SHARDS), Some(1));
+    }
+
+    #[test]
+    fn test_disabled_block_cache() {
+        let cache = BlockCache::<u32>::new(0);
+        cache.insert(0, 0);
+        assert_eq!(cache.get(0), None);
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The magic bytes at the start of a block file.
+const BLOCK_FILE_MAGIC: [u8; 8] = *b"ALEOBLKS";
+/// The version of the block file format.
+const BLOCK_FILE_VERSION: u16 = 1;
+
+///
+/// The header of a portable block file, followed by the serialized blocks in the
+/// height range `[start_block_height, end_block_height]`, in order.
+///
+/// *Attention*: Modifications to its layout will impact the compatibility of block files.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockFileHeader {
+    magic: [u8; 8],
+    version: u16,
+    /// The ID of the network of the blocks.
+    pub network_id: u16,
+    /// The height of the first block in the file.
+    pub start_block_height: u32,
+    /// The height of the last block in the file.
+    pub end_block_height: u32,
+    /// The CRC32 checksum of the serialized blocks.
+    pub checksum: u32,
+}
+
+impl BlockFileHeader {
+    /// Returns the number of blocks in the file.
+    pub fn num_blocks(&self) -> u32 {
+        self.end_block_height - self.start_block_height + 1
+    }
+}
+
+/// A writer of a portable block file.
+pub(crate) struct BlockFileWriter {
+    file: BufWriter<File>,
+    header: BlockFileHeader,
+    hasher: crc32fast::Hasher,
+    num_written: u32,
+}
+
+impl BlockFileWriter {
+    /// Creates a new block file at the given path, for the given network and block height range.
+    pub(crate) fn create<P: AsRef<Path>>(path: P, network_id: u16, start_block_height: u32, end_block_height: u32) -> Result<Self> {
+        if start_block_height > end_block_height {
+            return Err(anyhow!("Invalid block range {}..={} for the block file", start_block_height, end_block_height));
+        }
+
+        let header = BlockFileHeader {
+            magic: BLOCK_FILE_MAGIC,
+            version: BLOCK_FILE_VERSION,
+            network_id,
+            start_block_height,
+            end_block_height,
+            checksum: 0,
+        };
+
+        // Write a placeholder header, which is overwritten with the checksum once the file is finished.
+        let mut file = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut file, &header)?;
+
+        Ok(Self {
+            file,
+            header,
+            hasher: crc32fast::Hasher::new(),
+            num_written: 0,
+        })
+    }
+
+    /// Appends the given serializable block to the block file.
+    pub(crate) fn write<T: Serialize>(&mut self, block: &T) -> Result<()> {
+        let bytes = bincode::serialize(block)?;
+        self.hasher.update(&bytes);
+        self.file.write_all(&bytes)?;
+        self.num_written += 1;
+        Ok(())
+    }
+
+    /// Writes the final header to the block file, and flushes it to disk.
+    pub(crate) fn finish(mut self) -> Result<BlockFileHeader> {
+        if self.num_written != self.header.num_blocks() {
+            return Err(anyhow!("Wrote {} of {} blocks to the block file", self.num_written, self.header.num_blocks()));
+        }
+        self.header.checksum = self.hasher.finalize();
+
+        let mut file = self.file.into_inner().map_err(|error| anyhow!("Failed to flush the block file: {}", error))?;
+        file.seek(SeekFrom::Start(0))?;
+        bincode::serialize_into(&mut file, &self.header)?;
+        file.sync_all()?;
+
+        Ok(self.header)
+    }
+}
+
+/// A reader of a portable block file.
+pub(crate) struct BlockFileReader {
+    file: BufReader<File>,
+    header: BlockFileHeader,
+    num_read: u32,
+}
+
+impl BlockFileReader {
+    ///
+    /// Opens the block file at the given path for the given network.
+    ///
+    /// The header is validated and the checksum of the blocks is verified
+    /// upfront, so that a truncated or corrupted file is rejected before any block is read.
+    ///
+    pub(crate) fn open<P: AsRef<Path>>(path: P, network_id: u16) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+
+        let header: BlockFileHeader = bincode::deserialize_from(&mut file).map_err(|_| anyhow!("Invalid block file header"))?;
+        if header.magic != BLOCK_FILE_MAGIC {
+            return Err(anyhow!("The given file is not a block file"));
+        }
+        if header.version != BLOCK_FILE_VERSION {
+            return Err(anyhow!("Unsupported block file version {}", header.version));
+        }
+        if header.network_id != network_id {
+            return Err(anyhow!(
+                "The block file is for network {}, but expected network {}",
+                header.network_id,
+                network_id
+            ));
+        }
+        if header.start_block_height > header.end_block_height {
+            return Err(anyhow!("Invalid block range in the block file header"));
+        }
+
+        // Verify the checksum of the blocks.
+        let payload_offset = file.stream_position()?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut buffer = vec![0u8; 1 << 16];
+        loop {
+            match file.read(&mut buffer)? {
+                0 => break,
+                num_bytes => hasher.update(&buffer[..num_bytes]),
+            }
+        }
+        if hasher.finalize() != header.checksum {
+            return Err(anyhow!("The block file checksum does not match"));
+        }
+        file.seek(SeekFrom::Start(payload_offset))?;
+
+        Ok(Self { file, header, num_read: 0 })
+    }
+
+    /// Returns the header of the block file.
+    pub(crate) fn header(&self) -> &BlockFileHeader {
+        &self.header
+    }
+
+    /// Returns the next block in the block file, or `None` once all blocks are read.
+    pub(crate) fn next_block<T: for<'de> Deserialize<'de>>(&mut self) -> Result<Option<T>> {
+        if self.num_read == self.header.num_blocks() {
+            return Ok(None);
+        }
+
+        let block_height = self.header.start_block_height + self.num_read;
+        let block = bincode::deserialize_from(&mut self.file)
+            .map_err(|error| anyhow!("Failed to read block {} from the block file: {}", block_height, error))?;
+        self.num_read += 1;
+
+        Ok(Some(block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::rocksdb::tests::temp_file;
+
+    #[test]
+    fn test_block_file_round_trip() {
+        let path = temp_file();
+
+        let mut writer = BlockFileWriter::create(&path, 3, 5, 7).unwrap();
+        for value in [5u64, 6, 7] {
+            writer.write(&value).unwrap();
+        }
+        let header = writer.finish().unwrap();
+        assert_eq!(header.num_blocks(), 3);
+
+        let mut reader = BlockFileReader::open(&path, 3).unwrap();
+        assert_eq!(*reader.header(), header);
+        assert_eq!(reader.next_block::<u64>().unwrap(), Some(5));
+        assert_eq!(reader.next_block::<u64>().unwrap(), Some(6));
+        assert_eq!(reader.next_block::<u64>().unwrap(), Some(7));
+        assert_eq!(reader.next_block::<u64>().unwrap(), None);
+
+        // A block file is rejected on another network.
+        assert!(BlockFileReader::open(&path, 2).is_err());
+    }
+
+    #[test]
+    fn test_block_file_rejects_corruption() {
+        let path = temp_file();
+
+        let mut writer = BlockFileWriter::create(&path, 3, 0, 0).unwrap();
+        writer.write(&u64::MAX).unwrap();
+        writer.finish().unwrap();
+
+        // Flip the last byte of the file.
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(BlockFileReader::open(&path, 3).is_err());
+
+        // Truncate the file.
+        bytes.pop();
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(BlockFileReader::open(&path, 3).is_err());
+    }
+}
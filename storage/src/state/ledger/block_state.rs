@@ -30,6 +30,12 @@ pub(crate) struct BlockState<N: Network, SA: StorageAccess> {
     pub(crate) block_headers: DataMap<N::BlockHash, Header<N>, SA>,
     pub(crate) block_transactions: DataMap<N::BlockHash, Vec<N::TransactionID>, SA>,
     pub(crate) transactions: TransactionState<N, SA>,
+    /// The block height below which the transactions of blocks have been pruned.
+    pub(crate) pruned_block_height: DataMap<(), u32, SA>,
+    /// The highest block height of the peers, as last seen by the node before it shut down.
+    pub(crate) sync_target_height: DataMap<(), u32, SA>,
+    /// The header chain ahead of the canon chain := block_height => (previous_block_hash, block_hash, block_header)
+    pub(crate) header_links: DataMap<u32, (N::BlockHash, N::BlockHash, Header<N>), SA>,
 }
 
 impl<N: Network, SA: StorageAccess> BlockState<N, SA> {
@@ -39,6 +45,9 @@ impl<N: Network, SA: StorageAccess> BlockState<N, SA> {
             block_heights: storage.open_map(DataID::BlockHeights)?,
             block_headers: storage.open_map(DataID::BlockHeaders)?,
             block_transactions: storage.open_map(DataID::BlockTransactions)?,
+            pruned_block_height: storage.open_map(DataID::PrunedBlockHeight)?,
+            sync_target_height: storage.open_map(DataID::SyncTargetHeight)?,
+            header_links: storage.open_map(DataID::HeaderLinks)?,
             transactions: TransactionState::open(storage)?,
         })
     }
@@ -78,8 +87,31 @@ impl<N: Network, SA: StorageAccess> BlockState<N, SA> {
         self.transactions.get_transition(transition_id)
     }
 
+    /// Returns the ID of the transaction that created the given commitment, or `None` if the commitment does not exist.
+    pub(crate) fn get_transaction_id_by_commitment(&self, commitment: &Field<N>) -> Result<Option<N::TransactionID>> {
+        self.transactions.get_transaction_id_by_commitment(commitment)
+    }
+
+    /// Returns the block height below which the transactions of blocks have been pruned.
+    pub(crate) fn get_pruned_block_height(&self) -> Result<u32> {
+        Ok(self.pruned_block_height.get(&())?.unwrap_or_default())
+    }
+
+    /// Returns `true` if the transactions of the given block height have been pruned from storage.
+    /// The genesis block is never pruned.
+    pub(crate) fn is_block_pruned(&self, block_height: u32) -> Result<bool> {
+        Ok(block_height != 0 && block_height < self.get_pruned_block_height()?)
+    }
+
     /// Returns the transaction for a given transaction ID.
     pub(crate) fn get_transaction(&self, transaction_id: &N::TransactionID) -> Result<Transaction<N>> {
+        // Ensure the transaction has not been pruned.
+        if self.contains_transaction(transaction_id)? {
+            let block_height = self.get_transaction_metadata(transaction_id)?.block_height();
+            if self.is_block_pruned(block_height)? {
+                return Err(anyhow!("Transaction {} in block {} has been pruned", transaction_id, block_height));
+            }
+        }
         self.transactions.get_transaction(transaction_id)
     }
 
@@ -163,6 +195,11 @@ impl<N: Network, SA: StorageAccess> BlockState<N, SA> {
 
     /// Returns the transactions from the block of the given block height.
     pub(crate) fn get_block_transactions(&self, block_height: u32) -> Result<Transactions<N>> {
+        // Ensure the block transactions have not been pruned.
+        if self.is_block_pruned(block_height)? {
+            return Err(anyhow!("Block {} has been pruned", block_height));
+        }
+
         // Retrieve the block hash.
         let block_hash = self.get_block_hash(block_height)?;
 
@@ -247,6 +284,33 @@ impl<N: Network, SA: StorageReadWrite> BlockState<N, SA> {
         }
     }
 
+    /// Prunes the transactions of the given block height from storage, retaining the block header,
+    /// the transaction IDs, and the serial numbers and commitments of the block.
+    pub(crate) fn prune_block(&self, block_height: u32, batch: Option<usize>) -> Result<()> {
+        // Ensure the block height is not the genesis block.
+        if block_height == 0 {
+            return Err(anyhow!("Block {} cannot be pruned from storage", block_height));
+        }
+
+        // Retrieve the block hash.
+        let block_hash = match self.block_heights.get(&block_height)? {
+            Some(block_hash) => block_hash,
+            None => return Err(anyhow!("Block {} missing from block heights map", block_height)),
+        };
+        // Retrieve the block transaction IDs.
+        let transaction_ids = match self.block_transactions.get(&block_hash)? {
+            Some(transaction_ids) => transaction_ids,
+            None => return Err(anyhow!("Block {} missing from block transactions map", block_hash)),
+        };
+
+        // Prune the transactions.
+        for transaction_id in transaction_ids.iter() {
+            self.transactions.prune_transaction(transaction_id, batch)?;
+        }
+
+        Ok(())
+    }
+
     /// Removes the given block height from storage.
     pub(crate) fn remove_block(&self, block_height: u32, batch: Option<usize>) -> Result<()> {
         // Ensure the block height is not the genesis block.
@@ -295,7 +359,7 @@ impl<N: Network, SA: StorageReadWrite> BlockState<N, SA> {
 mod tests {
     use super::*;
     use crate::{
-        state::ledger::test_helpers::{sample_genesis_block, CurrentNetwork},
+        state::ledger::test_helpers::{sample_blocks, sample_genesis_block, CurrentNetwork, NUM_SAMPLE_BLOCKS},
         storage::{
             rocksdb::{tests::temp_dir, RocksDB},
             ReadWrite,
@@ -383,4 +447,73 @@ mod tests {
         // assert!(!block_state.contains_block_hash(&block.hash()).unwrap());
         // assert!(!block_state.contains_block_height(block.header().height()).unwrap());
     }
+
+    #[test]
+    fn test_prune_block() {
+        let storage = RocksDB::<ReadWrite>::open(temp_dir(), 0).expect("Failed to open storage");
+        let block_state = BlockState::<CurrentNetwork, ReadWrite>::open(storage).expect("Failed to open block state");
+
+        let genesis = sample_genesis_block();
+        let blocks = sample_blocks();
+
+        // Insert the blocks.
+        block_state.add_block(&genesis, None).expect("Failed to add block");
+        for block in &blocks {
+            block_state.add_block(block, None).expect("Failed to add block");
+        }
+        assert_eq!(block_state.get_pruned_block_height().unwrap(), 0);
+
+        // Prune the blocks below height 3, as the ledger does.
+        for block_height in 1..3 {
+            block_state.prune_block(block_height, None).expect("Failed to prune block");
+        }
+        block_state.pruned_block_height.insert(&(), &3, None).unwrap();
+        assert_eq!(block_state.get_pruned_block_height().unwrap(), 3);
+
+        for block in &blocks[..2] {
+            let block_height = block.header().height();
+            assert!(block_state.is_block_pruned(block_height).unwrap());
+
+            // The block header and transaction IDs are retained.
+            assert_eq!(block.hash(), block_state.get_block_hash(block_height).unwrap());
+            assert_eq!(block.header(), &block_state.get_block_header(block_height).unwrap());
+            for (transaction_id, transaction) in (*block.transactions()).iter() {
+                assert!(block_state.contains_transaction(&transaction_id).unwrap());
+                // The transactions and their transitions are gone.
+                assert!(block_state.get_transaction(&transaction_id).is_err());
+                for transition in transaction.transitions() {
+                    assert!(block_state.get_transition(transition.id()).is_err());
+                }
+            }
+            // The serial numbers and commitments are retained.
+            for serial_number in block.transactions().serial_numbers() {
+                assert!(block_state.contains_serial_number(serial_number).unwrap());
+            }
+            for commitment in block.transactions().commitments() {
+                assert!(block_state.contains_commitment(commitment).unwrap());
+            }
+            assert!(block_state.get_block(block_height).is_err());
+        }
+
+        // The blocks at and above the pruned block height are kept in full.
+        for block in &blocks[2..] {
+            let block_height = block.header().height();
+            assert!(!block_state.is_block_pruned(block_height).unwrap());
+            assert_eq!(block, &block_state.get_block(block_height).unwrap());
+            for (transaction_id, _transaction) in (*block.transactions()).iter() {
+                assert!(block_state.get_transaction(&transaction_id).is_ok());
+            }
+        }
+
+        // The genesis block is never pruned.
+        assert!(!block_state.is_block_pruned(0).unwrap());
+        assert!(block_state.prune_block(0, None).is_err());
+        assert_eq!(genesis, block_state.get_block(0).unwrap());
+        for (transaction_id, _transaction) in (*genesis.transactions()).iter() {
+            assert!(block_state.get_transaction(&transaction_id).is_ok());
+        }
+
+        // A block that does not exist cannot be pruned.
+        assert!(block_state.prune_block(NUM_SAMPLE_BLOCKS + 1, None).is_err());
+    }
 }
@@ -0,0 +1,140 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+
+/// The default number of seconds that a block timestamp may be ahead of the local clock.
+pub const DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS: u32 = 90;
+/// The number of previous blocks whose median timestamp a block timestamp must exceed.
+pub(crate) const MEDIAN_TIME_PAST_BLOCKS: usize = 11;
+
+///
+/// The timestamps of up to `MEDIAN_TIME_PAST_BLOCKS` consecutive blocks, ending with the block being extended.
+///
+/// While a group of blocks is checked before it is written, the timestamps of the pending blocks are added
+/// as each block is checked, so that every block is checked against its own ancestors.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockTimestamps {
+    timestamps: VecDeque<i64>,
+}
+
+impl BlockTimestamps {
+    /// Initializes the timestamps from the given consecutive block timestamps, in increasing order of block height.
+    pub(crate) fn new<I: IntoIterator<Item = i64>>(timestamps: I) -> Self {
+        let mut block_timestamps = Self::default();
+        for timestamp in timestamps {
+            block_timestamps.push(timestamp);
+        }
+        block_timestamps
+    }
+
+    /// Adds the timestamp of the next block, evicting the oldest timestamp.
+    pub(crate) fn push(&mut self, timestamp: i64) {
+        if self.timestamps.len() == MEDIAN_TIME_PAST_BLOCKS {
+            self.timestamps.pop_front();
+        }
+        self.timestamps.push_back(timestamp);
+    }
+
+    /// Returns the median timestamp of the blocks, or `None` if there are no blocks.
+    pub(crate) fn median_time_past(&self) -> Option<i64> {
+        let mut timestamps = self.timestamps.iter().copied().collect::<Vec<_>>();
+        timestamps.sort_unstable();
+        timestamps.get(timestamps.len() / 2).copied()
+    }
+
+    ///
+    /// Ensures the timestamp of the next block is greater than the median timestamp of the previous blocks,
+    /// and at most `maximum_future_block_time` seconds ahead of the given local time.
+    ///
+    /// The median time past depends only on the ancestors of the block, so the check is identical
+    /// whether the block is new or is a historical block added during sync.
+    ///
+    pub(crate) fn check_next_timestamp(&self, block_height: u32, timestamp: i64, now: i64, maximum_future_block_time: u32) -> Result<()> {
+        if let Some(median_time_past) = self.median_time_past() {
+            if timestamp <= median_time_past {
+                return Err(anyhow!(
+                    "Block {} has timestamp {}, which is not after the median timestamp {} of the previous blocks",
+                    block_height,
+                    timestamp,
+                    median_time_past
+                ));
+            }
+        }
+
+        if timestamp > now.saturating_add(maximum_future_block_time as i64) {
+            return Err(anyhow!(
+                "Block {} has timestamp {}, which is more than {} seconds ahead of the local time {}",
+                block_height,
+                timestamp,
+                maximum_future_block_time,
+                now
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_650_000_000;
+
+    #[test]
+    fn test_median_time_past() {
+        assert_eq!(BlockTimestamps::default().median_time_past(), None);
+        assert_eq!(BlockTimestamps::new([10]).median_time_past(), Some(10));
+        // The timestamps of the previous blocks need not be in order.
+        assert_eq!(BlockTimestamps::new([10, 30, 20]).median_time_past(), Some(20));
+
+        // Only the latest `MEDIAN_TIME_PAST_BLOCKS` timestamps are considered.
+        let timestamps = BlockTimestamps::new(0..100);
+        assert_eq!(timestamps.median_time_past(), Some(94));
+    }
+
+    #[test]
+    fn test_median_time_past_boundary() {
+        // The median of the 11 previous timestamps is 105.
+        let timestamps = BlockTimestamps::new(100..111);
+        assert!(timestamps.check_next_timestamp(11, 105, NOW, 90).is_err());
+        assert!(timestamps.check_next_timestamp(11, 106, NOW, 90).is_ok());
+
+        // A timestamp before the latest timestamp, but after the median, is accepted.
+        assert!(timestamps.check_next_timestamp(11, 107, NOW, 90).is_ok());
+    }
+
+    #[test]
+    fn test_future_timestamp_boundary() {
+        let timestamps = BlockTimestamps::new([NOW - 10]);
+        assert!(timestamps.check_next_timestamp(1, NOW + 90, NOW, 90).is_ok());
+        assert!(timestamps.check_next_timestamp(1, NOW + 91, NOW, 90).is_err());
+        assert!(timestamps.check_next_timestamp(1, NOW + 1, NOW, 0).is_err());
+    }
+
+    #[test]
+    fn test_historical_blocks() {
+        // A historical block is checked against its own ancestors, long after it was produced.
+        let mut timestamps = BlockTimestamps::new([1000]);
+        for (block_height, timestamp) in (1..20).zip(1001..1020) {
+            timestamps.check_next_timestamp(block_height, timestamp, NOW, 90).unwrap();
+            timestamps.push(timestamp);
+        }
+        assert!(timestamps.check_next_timestamp(20, 1014, NOW, 90).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::{
+    console::types::field::Field,
+    prelude::Network,
+    Block,
+    VM,
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+///
+/// A proof that a record commitment is included in the canon chain, at the given block height and block hash.
+///
+/// TODO: Prove inclusion against the ledger root with a Merkle path, once the ledger tree is reintroduced.
+/// Until then, the proof carries the block that created the commitment, whose block hash recomputes from its
+/// header and transactions, so that a client is able to check the inclusion against a block hash it trusts.
+///
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LedgerProof<N: Network> {
+    /// The block height of the block that created the commitment.
+    block_height: u32,
+    /// The block hash that the proof is valid against.
+    block_hash: N::BlockHash,
+    /// The ID of the transaction that created the commitment.
+    transaction_id: N::TransactionID,
+    /// The block that created the commitment.
+    block: Block<N>,
+}
+
+impl<N: Network> LedgerProof<N> {
+    /// Initializes a new instance of `LedgerProof`.
+    pub fn new(block_height: u32, block_hash: N::BlockHash, transaction_id: N::TransactionID, block: Block<N>) -> Self {
+        Self {
+            block_height,
+            block_hash,
+            transaction_id,
+            block,
+        }
+    }
+
+    /// Returns the block height of the block that created the commitment.
+    pub fn block_height(&self) -> u32 {
+        self.block_height
+    }
+
+    /// Returns the block hash that the proof is valid against.
+    pub fn block_hash(&self) -> N::BlockHash {
+        self.block_hash
+    }
+
+    /// Returns the ID of the transaction that created the commitment.
+    pub fn transaction_id(&self) -> N::TransactionID {
+        self.transaction_id
+    }
+
+    ///
+    /// Verifies the given commitment is created by the transaction of the proof, in a valid block
+    /// with the block height and block hash of the proof.
+    ///
+    pub fn verify(&self, commitment: &Field<N>) -> Result<()> {
+        // Ensure the block recomputes to the block height and block hash of the proof.
+        if self.block.header().height() != self.block_height || self.block.hash() != self.block_hash {
+            return Err(anyhow!("The ledger proof does not match block {} ({})", self.block_height, self.block_hash));
+        }
+
+        // Ensure the header roots and the transactions root of the block are valid.
+        if !self.block.verify(&VM::new()?) {
+            return Err(anyhow!("The ledger proof contains an invalid block {}", self.block_height));
+        }
+
+        // Ensure the transaction of the proof creates the commitment.
+        let is_included = self.block.transactions().iter().any(|(transaction_id, transaction)| {
+            *transaction_id == self.transaction_id && transaction.commitments().any(|candidate| candidate == commitment)
+        });
+        match is_included {
+            true => Ok(()),
+            false => Err(anyhow!("Commitment {} is not created by transaction {}", commitment, self.transaction_id)),
+        }
+    }
+}
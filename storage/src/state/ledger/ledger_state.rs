@@ -16,8 +16,19 @@
 
 #[cfg(any(test, feature = "test"))]
 use crate::storage::rocksdb::RocksDB;
+#[cfg(feature = "rocks")]
+use crate::state::ledger::block_file::{BlockFileHeader, BlockFileReader, BlockFileWriter};
 use crate::{
-    state::ledger::{block_state::BlockState, genesis_block, Metadata},
+    state::ledger::{
+        block_cache::BlockCache,
+        block_state::BlockState,
+        block_timestamps::{BlockTimestamps, DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS, MEDIAN_TIME_PAST_BLOCKS},
+        genesis_block,
+        ledger_proof::LedgerProof,
+        recent_blocks::RecentBlocks,
+        transaction_index::TransactionIndex,
+        Metadata,
+    },
     storage::{DataID, DataMap, MapRead, MapReadWrite, Storage, StorageAccess, StorageReadWrite},
 };
 use snarkos_environment::helpers::{BlockLocators, Resource, MAXIMUM_LINEAR_BLOCK_LOCATORS, MAXIMUM_QUADRATIC_BLOCK_LOCATORS};
@@ -29,21 +40,45 @@ use snarkvm::{
     Block, Header, Transaction, Transactions, VM,
 };
 
+#[cfg(feature = "prometheus")]
+use snarkos_metrics as metrics;
+
 use anyhow::{anyhow, Result};
-use circular_queue::CircularQueue;
 use itertools::Itertools;
 use parking_lot::RwLock;
 use rand::{CryptoRng, Rng};
+use rayon::{prelude::*, ThreadPool};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashSet},
     path::Path,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
     thread,
 };
 use time::OffsetDateTime;
 use tokio::sync::oneshot::{self, error::TryRecvError};
 
+// TODO (raychu86): Fetch ALEO_MAXIMUM_FORK_DEPTH from config.
+/// The maximum number of blocks that the ledger reverts, unless a deeper revert is explicitly requested.
+pub const ALEO_MAXIMUM_FORK_DEPTH: u32 = 4096;
+
+/// The minimum depth at which blocks may be pruned, ensuring a pruned block is never reverted.
+pub const MINIMUM_PRUNING_DEPTH: u32 = ALEO_MAXIMUM_FORK_DEPTH;
+/// The number of blocks pruned in a single storage batch.
+const PRUNING_BATCH_SIZE: u32 = 100;
+/// The default number of recently accessed blocks held in memory.
+pub const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 256;
+/// The number of block headers held in memory for each cached block.
+const HEADERS_PER_CACHED_BLOCK: usize = 16;
+/// The number of latest block hashes and block headers held in memory, which covers the linear block locators
+/// and a maximum block request.
+const RECENT_BLOCKS_CAPACITY: usize = 256;
+/// The number of blocks indexed in a single storage batch, while building the transaction index.
+const TRANSACTION_INDEX_BATCH_SIZE: u32 = 100;
+
 #[derive(Debug)]
 pub struct LedgerState<N: Network, SA: StorageAccess> {
     // /// The current ledger tree of block hashes.
@@ -51,13 +86,25 @@ pub struct LedgerState<N: Network, SA: StorageAccess> {
     /// The latest block of the ledger.
     latest_block: RwLock<Block<N>>,
     /// The latest block hashes and headers in the ledger.
-    latest_block_hashes_and_headers: RwLock<CircularQueue<(N::BlockHash, Header<N>)>>,
+    latest_block_hashes_and_headers: RwLock<RecentBlocks<N::BlockHash, Header<N>>>,
     /// The block locators from the latest block of the ledger.
     latest_block_locators: RwLock<BlockLocators<N>>,
     /// The state root corresponding to each block height.
     state_roots: DataMap<Field<N>, u32, SA>,
     /// The blocks of the ledger in storage.
     blocks: BlockState<N, SA>,
+    /// The cache of recently accessed blocks, keyed by block height.
+    block_cache: BlockCache<Block<N>>,
+    /// The cache of recently accessed block headers, keyed by block height.
+    header_cache: BlockCache<Header<N>>,
+    /// The optional index of transactions by record owner and commitment.
+    transaction_index: TransactionIndex<N, SA>,
+    /// If `true`, the transaction index is maintained as blocks are added and reverted.
+    is_transaction_index_enabled: AtomicBool,
+    /// The number of seconds that the timestamp of an added block may be ahead of the local clock.
+    maximum_future_block_time: AtomicU32,
+    /// If `true`, the node has determined that the ledger is synced with the network.
+    is_synced: AtomicBool,
 }
 
 impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
@@ -69,6 +116,17 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
     /// a read-only instance of `LedgerState` may only call immutable methods.
     ///
     pub fn open_reader<S: Storage<Access = SA>, P: AsRef<Path>>(path: P) -> Result<(Arc<Self>, Resource)> {
+        Self::open_reader_with_cache_capacity::<S, P>(path, DEFAULT_BLOCK_CACHE_CAPACITY)
+    }
+
+    ///
+    /// Opens a read-only instance of `LedgerState` from the given storage path, which holds up to
+    /// `block_cache_capacity` recently accessed blocks in memory, along with a larger number of block headers.
+    ///
+    pub fn open_reader_with_cache_capacity<S: Storage<Access = SA>, P: AsRef<Path>>(
+        path: P,
+        block_cache_capacity: usize,
+    ) -> Result<(Arc<Self>, Resource)> {
         // Open storage.
         let context = N::ID;
         let storage = S::open(path, context)?;
@@ -77,12 +135,16 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
         let ledger = Arc::new(Self {
             // ledger_tree: RwLock::new(LedgerTree::<N>::new()?),
             latest_block: RwLock::new(genesis_block::<N>()),
-            latest_block_hashes_and_headers: RwLock::new(CircularQueue::<(N::BlockHash, Header<N>)>::with_capacity(
-                MAXIMUM_LINEAR_BLOCK_LOCATORS as usize,
-            )),
+            latest_block_hashes_and_headers: RwLock::new(RecentBlocks::new(RECENT_BLOCKS_CAPACITY)),
             latest_block_locators: Default::default(),
             state_roots: storage.open_map(DataID::LedgerRoots)?,
+            transaction_index: TransactionIndex::open(&storage)?,
             blocks: BlockState::<_, _>::open(storage)?,
+            block_cache: BlockCache::new(block_cache_capacity),
+            header_cache: BlockCache::new(block_cache_capacity * HEADERS_PER_CACHED_BLOCK),
+            is_transaction_index_enabled: AtomicBool::new(false),
+            maximum_future_block_time: AtomicU32::new(DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS),
+            is_synced: AtomicBool::new(false),
         });
 
         // Determine the latest block height.
@@ -129,6 +191,20 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
         self.latest_block.read().hash()
     }
 
+    ///
+    /// Returns the latest block height, the latest block hash, and whether the ledger is synced,
+    /// read from a single snapshot of the latest block.
+    ///
+    pub fn latest_tip(&self) -> (u32, N::BlockHash, bool) {
+        let latest_block = self.latest_block.read();
+        (latest_block.header().height(), latest_block.hash(), self.is_synced.load(Ordering::SeqCst))
+    }
+
+    /// Sets whether the ledger is synced with the network.
+    pub fn set_synced(&self, is_synced: bool) {
+        self.is_synced.store(is_synced, Ordering::SeqCst);
+    }
+
     /// Returns the latest block timestamp.
     pub fn latest_block_timestamp(&self) -> i64 {
         self.latest_block.read().header().timestamp()
@@ -199,11 +275,209 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
         self.blocks.contains_commitment(commitment)
     }
 
+    ///
+    /// Returns the transactions of the given blocks, which were disconnected from the canon chain by a reorg,
+    /// that remain valid against the latest block, so that they may be returned to the memory pool.
+    ///
+    /// Coinbase transactions, which spend no records, are excluded, as they are only valid in their own block.
+    /// A transaction is also excluded if it is in the canon chain, or if any of its serial numbers has been spent,
+    /// including by an earlier transaction of the given blocks.
+    ///
+    pub fn get_disconnected_transactions(&self, disconnected_blocks: &[Block<N>]) -> Vec<Transaction<N>> {
+        let mut serial_numbers = HashSet::new();
+        let mut transactions = Vec::new();
+
+        for block in disconnected_blocks.iter().sorted_by_key(|block| block.header().height()) {
+            for (transaction_id, transaction) in block.transactions().iter() {
+                // Exclude the coinbase transactions.
+                if transaction.serial_numbers().next().is_none() {
+                    continue;
+                }
+                // Exclude the transactions that are in the canon chain.
+                if !matches!(self.contains_transaction(transaction_id), Ok(false)) {
+                    continue;
+                }
+                // Exclude the transactions that spend a spent or repeated serial number.
+                let is_unspent = |serial_number: &Field<N>| {
+                    !serial_numbers.contains(serial_number) && matches!(self.contains_serial_number(serial_number), Ok(false))
+                };
+                if !transaction.serial_numbers().all(is_unspent) {
+                    continue;
+                }
+                serial_numbers.extend(transaction.serial_numbers().copied());
+                // TODO (raychu86): Ensure the transaction references a ledger root in the canon chain, once the ledger tree is added.
+                transactions.push(transaction.clone());
+            }
+        }
+
+        transactions
+    }
+
     // /// Returns the record ciphertext for a given commitment.
     // pub fn get_ciphertext(&self, commitment: &N::Commitment) -> Result<N::RecordCiphertext> {
     //     self.blocks.get_ciphertext(commitment)
     // }
 
+    /// Returns the block height below which the transactions of blocks have been pruned, or `0` if the ledger is not pruned.
+    pub fn pruned_block_height(&self) -> Result<u32> {
+        self.blocks.get_pruned_block_height()
+    }
+
+    /// Returns the highest block height of the peers, as last seen by the node before it shut down, if any.
+    pub fn sync_target_height(&self) -> Result<Option<u32>> {
+        self.blocks.sync_target_height.get(&())
+    }
+
+    /// Stores the highest block height of the peers, so that the sync progress is known when the node restarts.
+    pub fn set_sync_target_height(&self, block_height: u32) -> Result<()> {
+        self.blocks.sync_target_height.insert(&(), &block_height, None)
+    }
+
+    /// Returns the stored header chain ahead of the canon chain, ordered by block height.
+    pub fn get_header_links(&self) -> Result<Vec<(N::BlockHash, N::BlockHash, Header<N>)>> {
+        let mut header_links = self.blocks.header_links.iter().collect::<Vec<_>>();
+        header_links.sort_unstable_by_key(|(block_height, _)| *block_height);
+        Ok(header_links.into_iter().map(|(_, header_link)| header_link).collect())
+    }
+
+    /// Stores the given headers of the header chain, so that the header chain is restored when the node restarts.
+    pub fn add_header_links(&self, header_links: &[(N::BlockHash, N::BlockHash, Header<N>)]) -> Result<()> {
+        // Perform all the associated storage operations as an atomic batch.
+        let batch = self.blocks.header_links.prepare_batch();
+        for header_link in header_links {
+            self.blocks.header_links.insert(&header_link.2.height(), header_link, Some(batch))?;
+        }
+        self.blocks.header_links.execute_batch(batch)
+    }
+
+    /// Removes the stored headers of the header chain from the given block heights (inclusive).
+    pub fn remove_header_links(&self, start_block_height: u32, end_block_height: u32) -> Result<()> {
+        // Perform all the associated storage operations as an atomic batch.
+        let batch = self.blocks.header_links.prepare_batch();
+        for block_height in start_block_height..=end_block_height {
+            self.blocks.header_links.remove(&block_height, Some(batch))?;
+        }
+        self.blocks.header_links.execute_batch(batch)
+    }
+
+    /// Removes every stored header of the header chain.
+    pub fn clear_header_links(&self) -> Result<()> {
+        let block_heights = self.blocks.header_links.keys().collect::<Vec<_>>();
+        // Perform all the associated storage operations as an atomic batch.
+        let batch = self.blocks.header_links.prepare_batch();
+        for block_height in block_heights {
+            self.blocks.header_links.remove(&block_height, Some(batch))?;
+        }
+        self.blocks.header_links.execute_batch(batch)
+    }
+
+    /// Returns the block height up to which the transaction index is complete, or `None` if the index is disabled.
+    pub fn indexed_block_height(&self) -> Result<Option<u32>> {
+        self.transaction_index.indexed_block_height()
+    }
+
+    ///
+    /// Returns up to `limit` of the (block_height, transaction_id) of the transactions that created a record
+    /// publicly owned by the given address, in increasing order, skipping the first `offset` transactions.
+    ///
+    pub fn get_transactions_by_address(&self, address: &Address<N>, offset: usize, limit: usize) -> Result<Vec<(u32, N::TransactionID)>> {
+        self.transaction_index.get_transactions_by_address(address, offset, limit)
+    }
+
+    /// Returns the (block_height, transaction_id) of the transaction that created the given commitment.
+    pub fn get_transaction_by_commitment(&self, commitment: &Field<N>) -> Result<(u32, N::TransactionID)> {
+        self.transaction_index.get_transaction_by_commitment(commitment)
+    }
+
+    ///
+    /// Returns the proof that the given record commitment is included in the canon chain,
+    /// which is valid against the block hash of the block that created the commitment.
+    ///
+    pub fn get_ledger_proof(&self, commitment: &Field<N>) -> Result<LedgerProof<N>> {
+        // Retrieve the transaction that created the commitment.
+        let transaction_id = match self.blocks.get_transaction_id_by_commitment(commitment)? {
+            Some(transaction_id) => transaction_id,
+            None => return Err(anyhow!("Commitment {} does not exist in the canon chain", commitment)),
+        };
+
+        // Retrieve the block that created the commitment.
+        let block_height = self.get_transaction_metadata(&transaction_id)?.block_height();
+        let block = self.get_block(block_height)?;
+        Ok(LedgerProof::new(block_height, block.hash(), transaction_id, block))
+    }
+
+    /// Returns `true` if the transactions of the given block height have been pruned from the ledger.
+    pub fn is_block_pruned(&self, block_height: u32) -> Result<bool> {
+        self.blocks.is_block_pruned(block_height)
+    }
+
+    ///
+    /// Verifies the integrity of the block at the given block height in storage.
+    ///
+    /// The block header must be stored under its block height, and link to the previous block.
+    /// Unless `is_fast` is set, the block is also recomputed and verified from its transactions,
+    /// and its serial numbers, commitments, and transactions must be indexed in storage.
+    ///
+    pub fn verify_block(&self, block_height: u32, is_fast: bool) -> Result<()> {
+        // Ensure the block header is stored under its block height.
+        let block_hash = self.get_block_hash(block_height)?;
+        let block_header = self.get_block_header(block_height)?;
+        if block_header.height() != block_height {
+            return Err(anyhow!("Block {} is stored with the header of block {}", block_height, block_header.height()));
+        }
+
+        // Ensure the block links to the previous block.
+        let previous_block_hash = self.get_previous_block_hash(block_height)?;
+        if block_height > 0 && self.get_block_height(&previous_block_hash)? != block_height - 1 {
+            return Err(anyhow!("Block {} does not link to block {}", block_height, block_height - 1));
+        }
+
+        // The transactions of pruned blocks are no longer available.
+        if is_fast || self.is_block_pruned(block_height)? {
+            return Ok(());
+        }
+
+        // Ensure the block hash recomputes from the previous block hash, the header, and the transactions.
+        let block = self.get_block(block_height)?;
+        if block.hash() != block_hash {
+            return Err(anyhow!("Block {} has an incorrect block hash", block_height));
+        }
+
+        // Ensure the header roots and the transactions root of the block are valid.
+        if !block.verify(&VM::new()?) {
+            return Err(anyhow!("Block {} is invalid", block_height));
+        }
+
+        // Ensure the serial numbers of the block are indexed.
+        for serial_number in block.transactions().serial_numbers() {
+            if !self.contains_serial_number(serial_number)? {
+                return Err(anyhow!("Serial number {} in block {} is missing from storage", serial_number, block_height));
+            }
+        }
+
+        // Ensure the commitments of the block are indexed.
+        for commitment in block.transactions().commitments() {
+            if !self.contains_commitment(commitment)? {
+                return Err(anyhow!("Commitment {} in block {} is missing from storage", commitment, block_height));
+            }
+        }
+
+        // Ensure the transactions of the block are indexed under the block.
+        for (transaction_id, _transaction) in block.transactions().iter() {
+            if self.get_transaction_metadata(&transaction_id)?.block_height() != block_height {
+                return Err(anyhow!("Transaction {} in block {} is indexed under another block", transaction_id, block_height));
+            }
+        }
+
+        // TODO (raychu86): Reintroduce ledger tree.
+        // // Ensure the ledger root of the block matches the recomputed ledger tree.
+        // if block.header().previous_ledger_root() != self.ledger_tree.read().root() {
+        //     return Err(anyhow!("Block {} declares an incorrect ledger root", block_height));
+        // }
+
+        Ok(())
+    }
+
     /// Returns the transition for a given transition ID.
     pub fn get_transition(&self, transition_id: &Field<N>) -> Result<Transition<N>> {
         self.blocks.get_transition(transition_id)
@@ -234,9 +508,29 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
         self.blocks.get_block_hash(block_height)
     }
 
+    ///
     /// Returns the block hashes from the given `start_block_height` to `end_block_height` (inclusive).
+    /// The block hashes of the latest blocks are served from memory.
+    ///
     pub fn get_block_hashes(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<N::BlockHash>> {
-        self.blocks.get_block_hashes(start_block_height, end_block_height)
+        match self
+            .latest_block_hashes_and_headers
+            .read()
+            .get_block_hashes(start_block_height, end_block_height)
+        {
+            Some(block_hashes) => Ok(block_hashes),
+            None => self.blocks.get_block_hashes(start_block_height, end_block_height),
+        }
+    }
+
+    /// Returns the block hashes of up to the latest `n` blocks, in increasing order of block height, without reading from storage.
+    pub fn latest_block_hashes(&self, n: usize) -> Vec<N::BlockHash> {
+        self.latest_block_hashes_and_headers.read().latest(n).into_iter().map(|(_, block_hash, _)| block_hash).collect()
+    }
+
+    /// Returns the block headers of up to the latest `n` blocks, in increasing order of block height, without reading from storage.
+    pub fn latest_block_headers(&self, n: usize) -> Vec<Header<N>> {
+        self.latest_block_hashes_and_headers.read().latest(n).into_iter().map(|(_, _, block_header)| block_header).collect()
     }
 
     /// Returns the previous block hash for the given block height.
@@ -246,12 +540,30 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
 
     /// Returns the block header for the given block height.
     pub fn get_block_header(&self, block_height: u32) -> Result<Header<N>> {
-        self.blocks.get_block_header(block_height)
+        if let Some(block_header) = self.header_cache.get(block_height) {
+            #[cfg(feature = "prometheus")]
+            metrics::increment_counter!(metrics::ledger_cache::HEADER_HITS);
+            return Ok(block_header);
+        }
+        #[cfg(feature = "prometheus")]
+        metrics::increment_counter!(metrics::ledger_cache::HEADER_MISSES);
+
+        let block_header = self.blocks.get_block_header(block_height)?;
+        self.header_cache.insert(block_height, block_header.clone());
+        Ok(block_header)
     }
 
     /// Returns the block headers from the given `start_block_height` to `end_block_height` (inclusive).
     pub fn get_block_headers(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Header<N>>> {
-        self.blocks.get_block_headers(start_block_height, end_block_height)
+        // Ensure the starting block height is less than the ending block height.
+        if start_block_height > end_block_height {
+            return Err(anyhow!("Invalid starting and ending block heights"));
+        }
+
+        (start_block_height..=end_block_height)
+            .into_par_iter()
+            .map(|height| self.get_block_header(height))
+            .collect()
     }
 
     /// Returns the transactions from the block of the given block height.
@@ -261,12 +573,30 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
 
     /// Returns the block for a given block height.
     pub fn get_block(&self, block_height: u32) -> Result<Block<N>> {
-        self.blocks.get_block(block_height)
+        if let Some(block) = self.block_cache.get(block_height) {
+            #[cfg(feature = "prometheus")]
+            metrics::increment_counter!(metrics::ledger_cache::BLOCK_HITS);
+            return Ok(block);
+        }
+        #[cfg(feature = "prometheus")]
+        metrics::increment_counter!(metrics::ledger_cache::BLOCK_MISSES);
+
+        let block = self.blocks.get_block(block_height)?;
+        self.block_cache.insert(block_height, block.clone());
+        Ok(block)
     }
 
     /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive).
     pub fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>> {
-        self.blocks.get_blocks(start_block_height, end_block_height)
+        // Ensure the starting block height is less than the ending block height.
+        if start_block_height > end_block_height {
+            return Err(anyhow!("Invalid starting and ending block heights"));
+        }
+
+        (start_block_height..=end_block_height)
+            .into_par_iter()
+            .map(|height| self.get_block(height))
+            .collect()
     }
 
     /// Returns the state root in the block header of the given block height.
@@ -286,11 +616,10 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
         let block_locator_headers = self
             .latest_block_hashes_and_headers
             .read()
-            .asc_iter()
-            .filter(|(_, header)| header.height() != 0) // Skip the genesis block.
-            .take(num_block_headers as usize)
-            .cloned()
-            .map(|(hash, header)| (header.height(), (hash, Some(header))))
+            .latest(num_block_headers as usize)
+            .into_iter()
+            .filter(|(height, ..)| *height != 0) // Skip the genesis block.
+            .map(|(height, hash, header)| (height, (hash, Some(header))))
             .collect::<Vec<_>>();
 
         // Decrement the block locator height by the number of block headers.
@@ -568,25 +897,20 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
     fn regenerate_latest_ledger_state(&self) -> Result<()> {
         // Compute the start block height and end block height (inclusive).
         let end_block_height = self.latest_block_height();
-        let start_block_height = end_block_height.saturating_sub(MAXIMUM_LINEAR_BLOCK_LOCATORS - 1);
+        let start_block_height = end_block_height.saturating_sub(RECENT_BLOCKS_CAPACITY as u32 - 1);
 
-        // Retrieve the latest block hashes and block headers.
-        let block_hashes = self.get_block_hashes(start_block_height, end_block_height)?;
+        // Retrieve the latest block hashes and block headers from storage, as the blocks in memory may have been reverted.
+        let block_hashes = self.blocks.get_block_hashes(start_block_height, end_block_height)?;
         let block_headers = self.get_block_headers(start_block_height, end_block_height)?;
         assert_eq!(block_hashes.len(), block_headers.len());
 
-        {
-            // Acquire the write lock for the latest block hashes and block headers.
-            let mut latest_block_hashes_and_headers = self.latest_block_hashes_and_headers.write();
-
-            // Upon success, clear the latest block hashes and block headers.
-            latest_block_hashes_and_headers.clear();
-
-            // Add the latest block hashes and block headers.
-            for (block_hash, block_header) in block_hashes.into_iter().zip_eq(block_headers) {
-                latest_block_hashes_and_headers.push((block_hash, block_header));
-            }
-        }
+        // Replace the latest block hashes and block headers.
+        self.latest_block_hashes_and_headers.write().reset(
+            (start_block_height..=end_block_height)
+                .zip_eq(block_hashes)
+                .zip_eq(block_headers)
+                .map(|((block_height, block_hash), block_header)| (block_height, block_hash, block_header)),
+        );
 
         *self.latest_block_locators.write() = self.get_block_locators(end_block_height)?;
 
@@ -643,64 +967,9 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
                     _ => (),
                 };
 
-                // Refresh the ledger storage state.
-                if ledger.state_roots.refresh() {
-                    // After catching up the reader, determine the latest block height.
-                    if let Some(latest_block_height) = ledger.blocks.block_heights.keys().max() {
-                        let current_block_height = current_block.header().height();
-                        let current_block_hash = current_block.hash();
-                        trace!(
-                            "[Read-Only] Updating ledger state from block {} to {}",
-                            current_block_height,
-                            latest_block_height
-                        );
-
-                        // Update the last seen block.
-                        let latest_block = ledger.get_block(latest_block_height);
-                        match &latest_block {
-                            Ok(ref block) => *ledger.latest_block.write() = block.clone(),
-                            Err(error) => warn!("[Read-Only] {}", error),
-                        };
+                // Catch the reader up to the writer.
+                ledger.refresh_reader(&mut current_block);
 
-                        // TODO (raychu86): Reintroduce ledger tree.
-                        // // A flag indicating whether a fast ledger tree update is feasible.
-                        // let mut quick_update = false;
-                        //
-                        // // Only consider an update if the latest height is actually greater than the current height.
-                        // if latest_block_height > current_block_height {
-                        //     // If the last known top block hash still exists at the expected height, there was no rollback
-                        //     // beyond it, which means we only need to update the ledger tree with the new hashes.
-                        //     if let Ok(found_block_hash) = ledger.get_block_hash(current_block_height) {
-                        //         if found_block_hash == current_block_hash {
-                        //             // Update the ledger tree.
-                        //             if let Err(error) = ledger.update_ledger_tree(current_block_height, latest_block_height) {
-                        //                 warn!("[Read-Only] {}", error);
-                        //             } else {
-                        //                 quick_update = true;
-                        //             }
-                        //         }
-                        //     }
-                        // }
-                        //
-                        // // If a quick ledger tree update was infeasible, regenerate it in its entirety.
-                        // if !quick_update {
-                        //     // Regenerate the entire ledger tree.
-                        //     if let Err(error) = ledger.regenerate_ledger_tree() {
-                        //         warn!("[Read-Only] {}", error);
-                        //     };
-                        // }
-
-                        // Regenerate the latest ledger state.
-                        if let Err(error) = ledger.regenerate_latest_ledger_state() {
-                            warn!("[Read-Only] {}", error);
-                        };
-
-                        // Update the last known block in the reader.
-                        if let Ok(block) = latest_block {
-                            current_block = block;
-                        }
-                    }
-                }
                 thread::sleep(std::time::Duration::from_secs(6));
             }
         });
@@ -708,6 +977,88 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
         Ok(Resource::Thread(thread_handle, abort_sender))
     }
 
+    ///
+    /// Refreshes the ledger reader from storage, with the last known block of the reader.
+    ///
+    /// Cached blocks that were reverted or pruned by the ledger writer are invalidated.
+    ///
+    fn refresh_reader(&self, current_block: &mut Block<N>) {
+        // Refresh the ledger storage state.
+        if self.state_roots.refresh() {
+            // After catching up the reader, determine the latest block height.
+            if let Some(latest_block_height) = self.blocks.block_heights.keys().max() {
+                let current_block_height = current_block.header().height();
+                let current_block_hash = current_block.hash();
+                trace!("[Read-Only] Updating ledger state from block {} to {}", current_block_height, latest_block_height);
+
+                // If the blocks of the reader were reverted, invalidate the cached blocks and block headers.
+                if latest_block_height < current_block_height
+                    || !matches!(self.get_block_hash(current_block_height), Ok(block_hash) if block_hash == current_block_hash)
+                {
+                    self.block_cache.clear();
+                    self.header_cache.clear();
+                }
+
+                // Evict the cached blocks whose transactions have since been pruned by the ledger writer.
+                if let Err(error) = self.evict_pruned_blocks() {
+                    warn!("[Read-Only] {}", error);
+                }
+
+                // Update the last seen block.
+                let latest_block = self.get_block(latest_block_height);
+                match &latest_block {
+                    Ok(ref block) => *self.latest_block.write() = block.clone(),
+                    Err(error) => warn!("[Read-Only] {}", error),
+                };
+
+                // TODO (raychu86): Reintroduce ledger tree.
+                // // A flag indicating whether a fast ledger tree update is feasible.
+                // let mut quick_update = false;
+                //
+                // // Only consider an update if the latest height is actually greater than the current height.
+                // if latest_block_height > current_block_height {
+                //     // If the last known top block hash still exists at the expected height, there was no rollback
+                //     // beyond it, which means we only need to update the ledger tree with the new hashes.
+                //     if let Ok(found_block_hash) = self.get_block_hash(current_block_height) {
+                //         if found_block_hash == current_block_hash {
+                //             // Update the ledger tree.
+                //             if let Err(error) = self.update_ledger_tree(current_block_height, latest_block_height) {
+                //                 warn!("[Read-Only] {}", error);
+                //             } else {
+                //                 quick_update = true;
+                //             }
+                //         }
+                //     }
+                // }
+                //
+                // // If a quick ledger tree update was infeasible, regenerate it in its entirety.
+                // if !quick_update {
+                //     // Regenerate the entire ledger tree.
+                //     if let Err(error) = self.regenerate_ledger_tree() {
+                //         warn!("[Read-Only] {}", error);
+                //     };
+                // }
+
+                // Regenerate the latest ledger state.
+                if let Err(error) = self.regenerate_latest_ledger_state() {
+                    warn!("[Read-Only] {}", error);
+                };
+
+                // Update the last known block in the reader.
+                if let Ok(block) = latest_block {
+                    *current_block = block;
+                }
+            }
+        }
+    }
+
+    /// Evicts the blocks whose transactions have been pruned from the block cache.
+    fn evict_pruned_blocks(&self) -> Result<()> {
+        let pruned_block_height = self.pruned_block_height()?;
+        self.block_cache.retain(|cached_block_height| cached_block_height == 0 || cached_block_height >= pruned_block_height);
+        Ok(())
+    }
+
     // /// Proposes a new block to the ledger.
     // pub fn propose_new_block<R: Rng + CryptoRng, Private: Visibility>(
     //     &self,
@@ -728,6 +1079,29 @@ impl<N: Network, SA: StorageAccess> LedgerState<N, SA> {
     //     }
     // }
 
+    ///
+    /// Exports the canon blocks up to the given block height to a portable block file at the given path,
+    /// calling `progress` with the height of each block as it is written.
+    ///
+    #[cfg(feature = "rocks")]
+    pub fn export_blocks<P: AsRef<Path>, F: FnMut(u32)>(&self, path: P, end_block_height: u32, mut progress: F) -> Result<BlockFileHeader> {
+        let latest_block_height = self.latest_block_height();
+        if end_block_height > latest_block_height {
+            return Err(anyhow!(
+                "The block height {} exceeds the latest block height {}",
+                end_block_height,
+                latest_block_height
+            ));
+        }
+
+        let mut writer = BlockFileWriter::create(path, N::ID, 0, end_block_height)?;
+        for block_height in 0..=end_block_height {
+            writer.write(&self.get_block(block_height)?)?;
+            progress(block_height);
+        }
+        writer.finish()
+    }
+
     ///
     /// Dump the specified number of blocks to the given location.
     ///
@@ -770,6 +1144,19 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
         Self::open_writer_with_increment::<S, P>(path, 10_000)
     }
 
+    ///
+    /// Sets whether each storage batch, such as the batch of an added block, is synced to disk before it returns.
+    /// Synced writes survive a crash of the machine, at the cost of slower block additions.
+    ///
+    pub fn set_sync_writes(&self, sync_writes: bool) {
+        self.state_roots.set_sync_writes(sync_writes);
+    }
+
+    /// Sets the number of seconds that the timestamp of an added block may be ahead of the local clock.
+    pub fn set_maximum_future_block_time(&self, maximum_future_block_time: u32) {
+        self.maximum_future_block_time.store(maximum_future_block_time, Ordering::SeqCst);
+    }
+
     /// This function is hidden, as it's intended to be used directly in tests only.
     /// The `validation_increment` parameter determines the number of blocks to be
     /// handled during the incremental validation process.
@@ -783,12 +1170,16 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
         let ledger = Self {
             // ledger_tree: RwLock::new(LedgerTree::<N>::new()?),
             latest_block: RwLock::new(genesis_block::<N>()),
-            latest_block_hashes_and_headers: RwLock::new(CircularQueue::<(N::BlockHash, Header<N>)>::with_capacity(
-                MAXIMUM_LINEAR_BLOCK_LOCATORS as usize,
-            )),
+            latest_block_hashes_and_headers: RwLock::new(RecentBlocks::new(RECENT_BLOCKS_CAPACITY)),
             latest_block_locators: Default::default(),
             state_roots: storage.open_map(DataID::LedgerRoots)?,
+            transaction_index: TransactionIndex::open(&storage)?,
             blocks: BlockState::<_, _>::open(storage)?,
+            block_cache: BlockCache::new(DEFAULT_BLOCK_CACHE_CAPACITY),
+            header_cache: BlockCache::new(DEFAULT_BLOCK_CACHE_CAPACITY * HEADERS_PER_CACHED_BLOCK),
+            is_transaction_index_enabled: AtomicBool::new(false),
+            maximum_future_block_time: AtomicU32::new(DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS),
+            is_synced: AtomicBool::new(false),
         };
 
         // Determine the latest block height.
@@ -880,6 +1271,15 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
         //     ledger.ledger_tree.write().add(&genesis_block::<N, A>()?.hash())?;
         // }
 
+        // Ensure the latest block loads and validates, so that the node resumes syncing from a sound tip.
+        if let Err(error) = ledger.verify_block(latest_block_height, false) {
+            return Err(anyhow!(
+                "The latest block {} is invalid ({}), revert it with `snarkos ledger rollback`",
+                latest_block_height,
+                error
+            ));
+        }
+
         // Update the latest ledger state.
         *ledger.latest_block.write() = ledger.get_block(latest_block_height)?;
         ledger.regenerate_latest_ledger_state()?;
@@ -902,34 +1302,219 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
             return Err(anyhow!("Block {} is invalid", block.header().height()));
         }
 
-        // Retrieve the current block.
-        let current_block = self.latest_block();
+        self.add_next_verified_block(block)
+    }
+
+    ///
+    /// Adds the given blocks as the next blocks in the ledger to storage.
+    ///
+    /// The blocks must form a chain that extends the latest block. As the validity of each block
+    /// is independent of the ledger state, the blocks are verified in parallel on the given thread pool,
+    /// after which they are written to storage in a single atomic batch. If any block in the batch is invalid,
+    /// no block from the batch is added to the ledger.
+    ///
+    pub fn add_next_blocks(&self, blocks: &[Block<N>], thread_pool: &ThreadPool) -> Result<()> {
+        // Ensure the blocks form a chain that extends the latest block.
+        let mut previous_block_height = self.latest_block_height();
+        let mut previous_block_hash = self.latest_block_hash();
+        for block in blocks {
+            let block_height = block.header().height();
+            if block_height != previous_block_height + 1 {
+                return Err(anyhow!("Block {} should have block height {}", block_height, previous_block_height + 1));
+            }
+            if block.previous_hash() != previous_block_hash {
+                return Err(anyhow!("Block {} has an incorrect previous block hash in the batch", block_height));
+            }
+            previous_block_height = block_height;
+            previous_block_hash = block.hash();
+        }
+
+        // Ensure each block itself is valid.
+        thread_pool.install(|| {
+            blocks.par_iter().try_for_each(|block| match block.verify(&VM::new()?) {
+                true => Ok(()),
+                false => Err(anyhow!("Block {} is invalid", block.header().height())),
+            })
+        })?;
+
+        // Add the blocks in a single atomic storage batch, so that none of them are added if any of them is rejected.
+        self.add_next_verified_blocks(blocks)
+    }
+
+    ///
+    /// Imports the blocks in the portable block file at the given path into the ledger, calling `progress`
+    /// with the height of each block as it is processed, and returns the latest block height of the ledger.
+    ///
+    /// Blocks that are already in the ledger are skipped, so an interrupted import may simply be restarted.
+    /// The remaining blocks are validated as during sync, in batches on the given thread pool,
+    /// and the import stops at the first invalid block, keeping every block before it.
+    ///
+    #[cfg(feature = "rocks")]
+    pub fn import_blocks<P: AsRef<Path>, F: FnMut(u32)>(&self, path: P, thread_pool: &ThreadPool, mut progress: F) -> Result<u32> {
+        // The number of blocks verified in parallel at a time.
+        const IMPORT_BATCH_SIZE: usize = 100;
+
+        let mut reader = BlockFileReader::open(path, N::ID)?;
+        let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+
+        let mut block_height = reader.header().start_block_height;
+        while let Some(block) = reader.next_block::<Block<N>>()? {
+            if block.header().height() != block_height {
+                return Err(anyhow!("Block {} is out of order in the block file", block.header().height()));
+            }
+
+            // Skip the blocks that are already in the ledger.
+            if block_height <= self.latest_block_height() {
+                if self.get_block_hash(block_height)? != block.hash() {
+                    return Err(anyhow!("Block {} in the block file conflicts with the ledger", block_height));
+                }
+                progress(block_height);
+            } else {
+                batch.push(block);
+                if batch.len() == IMPORT_BATCH_SIZE {
+                    self.import_batch(&batch, thread_pool, &mut progress)?;
+                    batch.clear();
+                }
+            }
+
+            block_height += 1;
+        }
+        self.import_batch(&batch, thread_pool, &mut progress)?;
+
+        Ok(self.latest_block_height())
+    }
+
+    /// Adds the given batch of imported blocks to the ledger, up to the first invalid block.
+    #[cfg(feature = "rocks")]
+    fn import_batch<F: FnMut(u32)>(&self, blocks: &[Block<N>], thread_pool: &ThreadPool, progress: &mut F) -> Result<()> {
+        if blocks.is_empty() {
+            return Ok(());
+        }
+
+        // On failure, the batch is reverted as a whole, so add its blocks one at a time to find the invalid block.
+        if self.add_next_blocks(blocks, thread_pool).is_err() {
+            for block in blocks {
+                let block_height = block.header().height();
+                if let Err(error) = self.add_next_block(block) {
+                    return Err(anyhow!("Block {} in the block file is invalid: {}", block_height, error));
+                }
+                progress(block_height);
+            }
+        } else {
+            blocks.iter().for_each(|block| progress(block.header().height()));
+        }
+
+        Ok(())
+    }
+
+    /// Adds the given block, whose validity has already been verified, as the next block in the ledger to storage.
+    fn add_next_verified_block(&self, block: &Block<N>) -> Result<()> {
+        self.add_next_verified_blocks(std::slice::from_ref(block))
+    }
+
+    ///
+    /// Adds the given blocks, whose validity has already been verified, as the next blocks in the ledger to storage.
+    ///
+    /// Every storage write of the blocks is accumulated into a single atomic batch, which is executed once,
+    /// so that either all of the blocks are in storage, or none of them are, even if the node crashes.
+    ///
+    fn add_next_verified_blocks(&self, blocks: &[Block<N>]) -> Result<()> {
+        if blocks.is_empty() {
+            return Ok(());
+        }
+
+        // Ensure each block extends the block before it, tracking the keys of the pending blocks,
+        // as the batched writes are not visible to reads.
+        let latest_block = self.latest_block();
+        let mut previous_block = &latest_block;
+        let mut pending_keys = PendingKeys::default();
+        let mut block_timestamps =
+            BlockTimestamps::new(self.latest_block_headers(MEDIAN_TIME_PAST_BLOCKS).iter().map(|header| header.timestamp()));
+        for block in blocks {
+            self.check_next_block(block, previous_block, &mut pending_keys, &block_timestamps)?;
+            block_timestamps.push(block.header().timestamp());
+            previous_block = block;
+        }
+
+        // Perform all the associated storage operations as an atomic batch.
+        let batch = self.state_roots.prepare_batch();
+
+        let result = blocks
+            .iter()
+            .try_for_each(|block| {
+                self.blocks.add_block(block, Some(batch))?;
+                self.state_roots
+                    .insert(&block.header().previous_state_root(), &block.header().height(), Some(batch))
+            })
+            .and_then(|()| match self.is_transaction_index_enabled.load(Ordering::SeqCst) {
+                true => self.transaction_index.add_blocks(blocks, Some(batch)),
+                false => Ok(()),
+            });
+        if let Err(error) = result {
+            // Discard the partial batch, so that none of the blocks are written.
+            let _ = self.state_roots.discard_batch(batch);
+            return Err(error);
+        }
+
+        // Execute the pending storage batch.
+        self.state_roots.execute_batch(batch)?;
+
+        // Update the in-memory objects.
+        for block in blocks {
+            // TODO (raychu86): Reintroduce ledger tree.
+            // self.ledger_tree.write().add(&block.hash())?;
+            self.latest_block_hashes_and_headers
+                .write()
+                .push(block.header().height(), block.hash(), block.header().clone());
+            self.block_cache.insert(block.header().height(), block.clone());
+            self.header_cache.insert(block.header().height(), block.header().clone());
+        }
+        let latest_block = &blocks[blocks.len() - 1];
+        *self.latest_block_locators.write() = self.get_block_locators(latest_block.header().height())?;
+        *self.latest_block.write() = latest_block.clone();
+
+        Ok(())
+    }
 
+    ///
+    /// Ensures the given block, whose validity has already been verified, is a valid next block after the given previous block,
+    /// and that none of its keys exist in the canon chain or in the pending keys, to which its keys are then added.
+    ///
+    fn check_next_block(
+        &self,
+        block: &Block<N>,
+        previous_block: &Block<N>,
+        pending_keys: &mut PendingKeys<N>,
+        block_timestamps: &BlockTimestamps,
+    ) -> Result<()> {
         // Ensure the block height increments by one.
         let block_height = block.header().height();
-        if block_height != current_block.header().height() + 1 {
+        if block_height != previous_block.header().height() + 1 {
             return Err(anyhow!(
                 "Block {} should have block height {}",
                 block_height,
-                current_block.header().height() + 1
+                previous_block.header().height() + 1
             ));
         }
 
         // Ensure the previous block hash matches.
-        if block.previous_hash() != current_block.hash() {
+        if block.previous_hash() != previous_block.hash() {
             return Err(anyhow!(
                 "Block {} has an incorrect previous block hash in the canon chain",
                 block_height
             ));
         }
 
-        // Ensure the next block timestamp is within the declared time limit.
-        let now = OffsetDateTime::now_utc().unix_timestamp();
-        // Ensure the next block timestamp is after the current block timestamp.
-        if block.header().timestamp() <= current_block.header().timestamp() {
+        // Ensure the next block timestamp is after the previous block timestamp.
+        if block.header().timestamp() <= previous_block.header().timestamp() {
             return Err(anyhow!("The given block timestamp is before the current timestamp"));
         }
 
+        // Ensure the next block timestamp is after the median time past, and within the declared time limit.
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let maximum_future_block_time = self.maximum_future_block_time.load(Ordering::SeqCst);
+        block_timestamps.check_next_timestamp(block_height, block.header().timestamp(), now, maximum_future_block_time)?;
+
         // TODO (raychu86): Add formal validation of targets.
         let expected_coinbase_target = block.header().coinbase_target();
 
@@ -970,16 +1555,16 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
         //     return Err(anyhow!("Block {} declares an incorrect state root", block_height));
         // }
 
-        // Ensure the canon chain does not already contain the given serial numbers.
+        // Ensure the canon chain and the pending blocks do not already contain the given serial numbers.
         for serial_number in block.transactions().serial_numbers() {
-            if self.contains_serial_number(serial_number)? {
+            if self.contains_serial_number(serial_number)? || !pending_keys.serial_numbers.insert(*serial_number) {
                 return Err(anyhow!("Serial number {} already exists in the ledger", serial_number));
             }
         }
 
-        // Ensure the canon chain does not already contain the given commitments.
+        // Ensure the canon chain and the pending blocks do not already contain the given commitments.
         for commitment in block.transactions().commitments() {
-            if self.contains_commitment(commitment)? {
+            if self.contains_commitment(commitment)? || !pending_keys.commitments.insert(*commitment) {
                 return Err(anyhow!("Commitment {} already exists in the ledger", commitment));
             }
         }
@@ -987,7 +1572,7 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
         // Ensure each transaction in the given block is new to the canon chain.
         for (transaction_id, _transaction) in block.transactions().iter() {
             // Ensure the transactions in the given block do not already exist.
-            if self.contains_transaction(&transaction_id)? {
+            if self.contains_transaction(&transaction_id)? || !pending_keys.transaction_ids.insert(*transaction_id) {
                 return Err(anyhow!(
                     "Transaction {} in block {} has a duplicate transaction in the ledger",
                     transaction_id,
@@ -1007,38 +1592,141 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
             // }
         }
 
-        // Perform all the associated storage operations as an atomic batch.
-        let batch = self.state_roots.prepare_batch();
+        Ok(())
+    }
 
-        self.blocks.add_block(block, Some(batch))?;
-        self.state_roots
-            .insert(&block.header().previous_state_root(), &block.header().height(), Some(batch))?;
+    ///
+    /// Prunes the transactions of every block buried beyond the given depth, returning the new pruned block height.
+    ///
+    /// The block headers, transaction IDs, serial numbers, commitments, and state roots are retained,
+    /// so that the ledger is able to validate new blocks. As blocks are pruned in consecutive batches,
+    /// an existing ledger is pruned in place, and an interrupted pruning resumes from the last batch.
+    /// If `maximum_blocks` is given, at most that many blocks are pruned, and the next call resumes from there.
+    ///
+    pub fn prune_to_depth(&self, depth: u32, maximum_blocks: Option<u32>) -> Result<u32> {
+        // Ensure the pruned blocks can never be reverted.
+        if depth < MINIMUM_PRUNING_DEPTH {
+            return Err(anyhow!("The pruning depth must be at least {}, found {}", MINIMUM_PRUNING_DEPTH, depth));
+        }
 
-        // Execute the pending storage batch.
-        self.state_roots.execute_batch(batch)?;
+        self.prune_to_height(self.latest_block_height().saturating_sub(depth), maximum_blocks)
+    }
 
-        // Update the in-memory objects.
-        // TODO (raychu86): Reintroduce ledger tree.
-        // self.ledger_tree.write().add(&block.hash())?;
-        self.latest_block_hashes_and_headers
-            .write()
-            .push((block.hash(), block.header().clone()));
-        *self.latest_block_locators.write() = self.get_block_locators(block.header().height())?;
-        *self.latest_block.write() = block.clone();
+    /// Prunes the transactions of the blocks below the given block height, pruning at most `maximum_blocks` blocks if given.
+    fn prune_to_height(&self, target_block_height: u32, maximum_blocks: Option<u32>) -> Result<u32> {
+        // Determine the range of blocks to prune, skipping the genesis block.
+        let mut pruned_block_height = self.pruned_block_height()?.max(1);
+        let target_block_height = match maximum_blocks {
+            Some(maximum_blocks) => std::cmp::min(target_block_height, pruned_block_height.saturating_add(maximum_blocks)),
+            None => target_block_height,
+        };
+
+        while pruned_block_height < target_block_height {
+            let end_block_height = std::cmp::min(pruned_block_height.saturating_add(PRUNING_BATCH_SIZE), target_block_height);
+
+            // Perform all the associated storage operations as an atomic batch.
+            let batch = self.state_roots.prepare_batch();
+
+            for block_height in pruned_block_height..end_block_height {
+                self.blocks.prune_block(block_height, Some(batch))?;
+            }
+            self.blocks.pruned_block_height.insert(&(), &end_block_height, Some(batch))?;
+
+            // Execute the pending storage batch.
+            self.state_roots.execute_batch(batch)?;
+            self.evict_pruned_blocks()?;
+
+            trace!("Pruned the ledger up to block {}", end_block_height);
+            pruned_block_height = end_block_height;
+        }
+
+        self.pruned_block_height()
+    }
+
+    ///
+    /// Enables the transaction index, building it from the canon blocks that have not been indexed yet.
+    ///
+    /// As the index is built in consecutive batches, an interrupted build resumes from the last batch.
+    /// The transactions of pruned blocks are not available, and are therefore not indexed.
+    ///
+    pub fn enable_transaction_index(&self) -> Result<()> {
+        let latest_block_height = self.latest_block_height();
+
+        // Determine the first block to index. An index that was previously disabled may be stale, and is rebuilt.
+        let mut start_block_height = match self.transaction_index.indexed_block_height()? {
+            Some(indexed_block_height) => indexed_block_height + 1,
+            None => {
+                let batch = self.state_roots.prepare_batch();
+                self.transaction_index.clear(Some(batch))?;
+                self.state_roots.execute_batch(batch)?;
+                0
+            }
+        };
+
+        let pruned_block_height = self.pruned_block_height()?;
+        if start_block_height < pruned_block_height {
+            warn!("The transactions of blocks 1 to {} have been pruned, and are not indexed", pruned_block_height - 1);
+            if start_block_height == 0 {
+                let batch = self.state_roots.prepare_batch();
+                self.transaction_index.add_blocks(&[self.get_block(0)?], Some(batch))?;
+                self.state_roots.execute_batch(batch)?;
+            }
+            start_block_height = pruned_block_height;
+        }
+
+        if start_block_height <= latest_block_height {
+            info!("Building the transaction index from block {} to {}", start_block_height, latest_block_height);
+        }
+        while start_block_height <= latest_block_height {
+            // Compute the end block height (inclusive) for this iteration.
+            let end_block_height = std::cmp::min(start_block_height.saturating_add(TRANSACTION_INDEX_BATCH_SIZE - 1), latest_block_height);
+
+            // Perform all the associated storage operations as an atomic batch.
+            let batch = self.state_roots.prepare_batch();
+            self.transaction_index
+                .add_blocks(&self.get_blocks(start_block_height, end_block_height)?, Some(batch))?;
+            self.state_roots.execute_batch(batch)?;
 
+            // Log the progress of the build.
+            let progress = (end_block_height as f64 / latest_block_height.max(1) as f64 * 100f64) as u8;
+            debug!("Indexed the transactions up to block {} ({}%)", end_block_height, progress);
+
+            start_block_height = end_block_height.saturating_add(1);
+        }
+
+        self.is_transaction_index_enabled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Disables the transaction index, which is rebuilt if it is enabled again.
+    pub fn disable_transaction_index(&self) -> Result<()> {
+        self.is_transaction_index_enabled.store(false, Ordering::SeqCst);
+        if self.transaction_index.indexed_block_height()?.is_some() {
+            let batch = self.state_roots.prepare_batch();
+            self.transaction_index.disable(Some(batch))?;
+            self.state_roots.execute_batch(batch)?;
+        }
         Ok(())
     }
 
     /// Reverts the ledger state back to the given block height, returning the removed blocks on success.
     pub fn revert_to_block_height(&self, block_height: u32) -> Result<Vec<Block<N>>> {
+        self.revert_to_block_height_with_maximum_depth(block_height, ALEO_MAXIMUM_FORK_DEPTH)
+    }
+
+    ///
+    /// Reverts the ledger state back to the given block height, removing at most `maximum_depth` blocks,
+    /// and returning the removed blocks on success.
+    ///
+    /// *Attention*: Reverting beyond `ALEO_MAXIMUM_FORK_DEPTH` is only intended to recover from a deep split of the network.
+    ///
+    pub fn revert_to_block_height_with_maximum_depth(&self, block_height: u32, maximum_depth: u32) -> Result<Vec<Block<N>>> {
         // Determine the number of blocks to remove.
         let latest_block_height = self.latest_block_height();
         let number_of_blocks = latest_block_height.saturating_sub(block_height);
 
-        // TODO (raychu86): Fetch ALEO_MAXIMUM_FORK_DEPTH from config.
-        const ALEO_MAXIMUM_FORK_DEPTH: u32 = 4096;
         // Ensure the reverted block height is within a permitted range and well-formed.
-        if block_height >= latest_block_height || number_of_blocks > ALEO_MAXIMUM_FORK_DEPTH || self.get_block(block_height).is_err() {
+        if block_height >= latest_block_height || number_of_blocks > maximum_depth || self.get_block(block_height).is_err() {
             return Err(anyhow!("Attempted to return to block height {}, which is invalid", block_height));
         }
 
@@ -1080,9 +1768,22 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
             }
         }
 
+        // Remove the transactions of the removed blocks from the transaction index.
+        if self.is_transaction_index_enabled.load(Ordering::SeqCst) {
+            let removed_blocks: Vec<_> = blocks.range(current_block_height + 1..).map(|(_, block)| block.clone()).collect();
+            if let Err(error) = self.transaction_index.remove_blocks(&removed_blocks, current_block_height, Some(batch)) {
+                self.state_roots.discard_batch(batch)?;
+                return Err(error);
+            }
+        }
+
         // Execute the pending storage batch.
         self.state_roots.execute_batch(batch)?;
 
+        // Invalidate the cached blocks and block headers that were removed.
+        self.block_cache.retain(|cached_block_height| cached_block_height <= current_block_height);
+        self.header_cache.retain(|cached_block_height| cached_block_height <= current_block_height);
+
         // Update the latest block.
         *self.latest_block.write() = self.get_block(current_block_height)?;
         // Regenerate the latest ledger state.
@@ -1160,11 +1861,37 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
                         debug!("Successfully resolved inconsistent ledger state");
                         Ok(current_block_height)
                     } else {
-                        Err(anyhow!(
-                            "Loaded a ledger with inconsistent state ({} != {}) (unable to automatically resolve)",
-                            latest_block_height_0,
-                            latest_block_height_1
-                        ))
+                        debug!("Attempting to automatically resolve inconsistent ledger state");
+                        // The blocks above the ledger roots block height were partly applied, so they are removed.
+                        let batch = if let Some(id) = batch {
+                            id
+                        } else {
+                            self.state_roots.prepare_batch()
+                        };
+
+                        for block_height in (latest_block_height_0 + 1..=latest_block_height_1).rev() {
+                            if let Err(error) = self.blocks.remove_block(block_height, Some(batch)) {
+                                // Discard the in-progress batch if it's a standalone operation.
+                                if !is_part_of_a_batch {
+                                    self.state_roots.discard_batch(batch)?;
+                                }
+
+                                return Err(anyhow!(
+                                    "Loaded a ledger with inconsistent state ({} != {}) (failed to automatically resolve: {})",
+                                    latest_block_height_0,
+                                    latest_block_height_1,
+                                    error
+                                ));
+                            }
+                        }
+
+                        // Execute the pending storage batch if it's a standalone operation.
+                        if !is_part_of_a_batch {
+                            self.state_roots.execute_batch(batch)?;
+                        }
+
+                        debug!("Successfully resolved inconsistent ledger state");
+                        Ok(latest_block_height_0)
                     }
                 }
             },
@@ -1238,14 +1965,36 @@ impl<N: Network, SA: StorageReadWrite> LedgerState<N, SA> {
     }
 }
 
+/// The keys of the blocks pending in a storage batch, which must be unique across the ledger.
+struct PendingKeys<N: Network> {
+    serial_numbers: HashSet<Field<N>>,
+    commitments: HashSet<Field<N>>,
+    transaction_ids: HashSet<N::TransactionID>,
+}
+
+impl<N: Network> Default for PendingKeys<N> {
+    fn default() -> Self {
+        Self {
+            serial_numbers: Default::default(),
+            commitments: Default::default(),
+            transaction_ids: Default::default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::storage::{
-        rocksdb::{tests::temp_dir, RocksDB},
-        ReadOnly, ReadWrite, Storage,
+    use crate::{
+        state::ledger::test_helpers::{sample_blocks, NUM_SAMPLE_BLOCKS},
+        storage::{
+            rocksdb::{tests::temp_dir, RocksDB},
+            ReadOnly,
+            ReadWrite,
+            Storage,
+        },
     };
-    use snarkvm::prelude::Testnet3;
+    use snarkvm::{console::types::field::Zero, prelude::Testnet3};
 
     type CurrentNetwork = Testnet3;
     type A = snarkvm::circuit::AleoV0;
@@ -1267,4 +2016,334 @@ mod tests {
         let _block_state =
             LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
     }
+
+    #[test]
+    fn test_add_next_blocks_rejects_unchained_batch() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+        let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        // A batch that does not extend the latest block is rejected as a whole.
+        let genesis = ledger.latest_block();
+        assert!(ledger.add_next_blocks(&[genesis.clone()], &thread_pool).is_err());
+        assert_eq!(ledger.latest_block_height(), 0);
+        assert_eq!(ledger.latest_block_hash(), genesis.hash());
+
+        // An empty batch is a no-op.
+        assert!(ledger.add_next_blocks(&[], &thread_pool).is_ok());
+        assert_eq!(ledger.latest_block_height(), 0);
+    }
+
+    #[test]
+    fn test_cached_blocks() {
+        let dir = temp_dir();
+        {
+            let _ledger =
+                LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        }
+
+        // A cached read returns the same block as a read from storage.
+        let (ledger, _) = LedgerState::<CurrentNetwork, ReadWrite, A>::open_reader_with_cache_capacity::<RocksDB, _>(&dir, 0)
+            .expect("Failed to open ledger state");
+        let (cached_ledger, _) =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_reader::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        for _ in 0..2 {
+            assert_eq!(cached_ledger.get_block(0).unwrap(), ledger.get_block(0).unwrap());
+            assert_eq!(cached_ledger.get_block_header(0).unwrap(), ledger.get_block_header(0).unwrap());
+        }
+        assert_eq!(cached_ledger.get_blocks(0, 0).unwrap(), ledger.get_blocks(0, 0).unwrap());
+        assert!(cached_ledger.get_blocks(1, 0).is_err());
+        assert!(cached_ledger.get_block(1).is_err());
+    }
+
+    #[test]
+    fn test_transaction_index() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+        let genesis = ledger.latest_block();
+
+        // The transaction index is disabled by default.
+        assert_eq!(ledger.indexed_block_height().unwrap(), None);
+
+        // Enabling the transaction index builds it from the canon blocks.
+        ledger.enable_transaction_index().expect("Failed to enable the transaction index");
+        assert_eq!(ledger.indexed_block_height().unwrap(), Some(0));
+        for (transaction_id, transaction) in genesis.transactions().iter() {
+            for commitment in transaction.commitments() {
+                assert_eq!(ledger.get_transaction_by_commitment(commitment).unwrap(), (0, *transaction_id));
+            }
+        }
+
+        // A disabled transaction index is no longer served.
+        ledger.disable_transaction_index().expect("Failed to disable the transaction index");
+        assert_eq!(ledger.indexed_block_height().unwrap(), None);
+        for commitment in genesis.transactions().commitments() {
+            assert!(ledger.get_transaction_by_commitment(commitment).is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+
+        // The genesis block is intact.
+        ledger.verify_block(0, true).expect("Failed to verify the genesis block headers");
+        ledger.verify_block(0, false).expect("Failed to verify the genesis block");
+
+        // A missing block fails verification.
+        assert!(ledger.verify_block(1, true).is_err());
+    }
+
+    #[test]
+    fn test_get_ledger_proof() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+        let genesis = ledger.latest_block();
+
+        // The proof of each commitment round-trips, and verifies against the reported block hash.
+        for commitment in genesis.transactions().commitments() {
+            let ledger_proof = ledger.get_ledger_proof(commitment).expect("Failed to get the ledger proof");
+            assert_eq!((ledger_proof.block_height(), ledger_proof.block_hash()), (0, genesis.hash()));
+
+            let ledger_proof: LedgerProof<CurrentNetwork> =
+                bincode::deserialize(&bincode::serialize(&ledger_proof).unwrap()).expect("Failed to deserialize the ledger proof");
+            ledger_proof.verify(commitment).expect("Failed to verify the ledger proof");
+            assert_eq!(ledger_proof.block_hash(), ledger.get_block_hash(ledger_proof.block_height()).unwrap());
+        }
+
+        // An unknown commitment has no proof, and a proof does not verify another commitment.
+        let unknown_commitment = Field::<CurrentNetwork>::zero();
+        assert!(ledger.get_ledger_proof(&unknown_commitment).is_err());
+        let commitment = genesis.transactions().commitments().next().unwrap();
+        assert!(ledger.get_ledger_proof(commitment).unwrap().verify(&unknown_commitment).is_err());
+    }
+
+    #[test]
+    fn test_sync_target_height() {
+        let dir = temp_dir();
+        {
+            let ledger =
+                LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+            assert_eq!(ledger.sync_target_height().unwrap(), None);
+            ledger.set_sync_target_height(1234).unwrap();
+        }
+
+        // The sync target height persists across a restart.
+        let ledger = LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        assert_eq!(ledger.sync_target_height().unwrap(), Some(1234));
+        assert_eq!(ledger.latest_block_height(), 0);
+    }
+
+    #[test]
+    fn test_reopen_after_interrupted_block() {
+        let dir = temp_dir();
+        let blocks = sample_blocks();
+        {
+            let ledger =
+                LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+            ledger.add_next_verified_blocks(&blocks[..1]).expect("Failed to add block");
+
+            // Stop partway through applying the next block, after its block data has landed on disk,
+            // but before its ledger root is written.
+            ledger.blocks.add_block(&blocks[1], None).expect("Failed to write block");
+            assert!(ledger.blocks.contains_block_height(2).unwrap());
+        }
+
+        // The reopened ledger is consistent at the last applied block, with no trace of the interrupted block.
+        let ledger = LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        assert_eq!(ledger.latest_block_height(), 1);
+        assert_eq!(ledger.latest_block_hash(), blocks[0].hash());
+        assert_eq!(ledger.get_block(1).unwrap(), blocks[0]);
+        assert!(!ledger.contains_block_height(2).unwrap());
+        assert!(!ledger.contains_block_hash(&blocks[1].hash()).unwrap());
+        for (transaction_id, _transaction) in (*blocks[1].transactions()).iter() {
+            assert!(!ledger.contains_transaction(&transaction_id).unwrap());
+        }
+        for commitment in blocks[1].transactions().commitments() {
+            assert!(!ledger.contains_commitment(commitment).unwrap());
+        }
+
+        // The interrupted block is applied again.
+        ledger.add_next_verified_blocks(&blocks[1..2]).expect("Failed to add block");
+        assert_eq!(ledger.latest_block_height(), 2);
+        assert_eq!(ledger.get_block(2).unwrap(), blocks[1]);
+    }
+
+    #[test]
+    fn test_header_links() {
+        let dir = temp_dir();
+        let header_links = sample_blocks()
+            .iter()
+            .map(|block| (block.previous_hash(), block.hash(), block.header().clone()))
+            .collect::<Vec<_>>();
+        {
+            let ledger =
+                LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+            assert!(ledger.get_header_links().unwrap().is_empty());
+            // Store the headers out of order.
+            ledger.add_header_links(&header_links[2..]).unwrap();
+            ledger.add_header_links(&header_links[..2]).unwrap();
+        }
+
+        // The header chain persists across a restart, ordered by block height.
+        let ledger = LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        assert_eq!(ledger.get_header_links().unwrap(), header_links);
+        assert_eq!(ledger.latest_block_height(), 0);
+
+        // The headers are removed by block height.
+        ledger.remove_header_links(3, NUM_SAMPLE_BLOCKS).unwrap();
+        assert_eq!(ledger.get_header_links().unwrap(), header_links[..2]);
+        ledger.clear_header_links().unwrap();
+        assert!(ledger.get_header_links().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_disconnected_transactions() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+
+        // The transactions of a block in the canon chain are never returned to the memory pool.
+        let genesis = ledger.latest_block();
+        assert!(ledger.get_disconnected_transactions(&[genesis]).is_empty());
+        assert!(ledger.get_disconnected_transactions(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_export_and_import_blocks() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+        let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let path = crate::storage::rocksdb::tests::temp_file();
+
+        // Blocks beyond the latest block height cannot be exported.
+        assert!(ledger.export_blocks(&path, 1, |_| ()).is_err());
+
+        let header = ledger.export_blocks(&path, 0, |_| ()).expect("Failed to export the blocks");
+        assert_eq!(header.network_id, CurrentNetwork::ID);
+        assert_eq!(header.num_blocks(), 1);
+
+        // Importing into a fresh ledger skips the blocks it already has.
+        let other_ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+        let mut imported = vec![];
+        let latest_block_height = other_ledger
+            .import_blocks(&path, &thread_pool, |block_height| imported.push(block_height))
+            .expect("Failed to import the blocks");
+        assert_eq!(latest_block_height, 0);
+        assert_eq!(imported, vec![0]);
+        assert_eq!(other_ledger.latest_block_hash(), ledger.latest_block_hash());
+    }
+
+    #[test]
+    fn test_prune_to_depth() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+
+        // A depth at which blocks may still be reverted is rejected.
+        assert!(ledger.prune_to_depth(MINIMUM_PRUNING_DEPTH - 1, None).is_err());
+
+        // A ledger with no blocks beyond the pruning depth is left as is.
+        assert_eq!(ledger.prune_to_depth(MINIMUM_PRUNING_DEPTH, None).unwrap(), 0);
+        assert_eq!(ledger.prune_to_depth(MINIMUM_PRUNING_DEPTH, Some(1)).unwrap(), 0);
+        assert_eq!(ledger.get_block(0).unwrap(), ledger.latest_block());
+    }
+
+    #[test]
+    fn test_prune_to_height() {
+        let ledger =
+            LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(temp_dir()).expect("Failed to open ledger state");
+
+        // Store a chain of blocks on top of the genesis block.
+        let blocks = sample_blocks();
+        for block in &blocks {
+            ledger.blocks.add_block(block, None).expect("Failed to add block");
+        }
+        let target_block_height = NUM_SAMPLE_BLOCKS;
+
+        // A capped pruning stops after the given number of blocks.
+        assert_eq!(ledger.prune_to_height(target_block_height, Some(2)).unwrap(), 3);
+        assert!(ledger.is_block_pruned(1).unwrap());
+        assert!(ledger.is_block_pruned(2).unwrap());
+        assert!(!ledger.is_block_pruned(3).unwrap());
+        assert!(ledger.get_block(2).is_err());
+        assert_eq!(ledger.get_block(3).unwrap(), blocks[2]);
+
+        // The next pruning resumes from the pruned block height, and stops at the target.
+        assert_eq!(ledger.prune_to_height(target_block_height, Some(2)).unwrap(), target_block_height);
+        assert!(ledger.is_block_pruned(3).unwrap());
+        assert!(ledger.get_block(3).is_err());
+        assert!(!ledger.is_block_pruned(target_block_height).unwrap());
+        assert_eq!(ledger.get_block(target_block_height).unwrap(), blocks[3]);
+
+        // Pruning to the same height again is a no-op.
+        assert_eq!(ledger.prune_to_height(target_block_height, None).unwrap(), target_block_height);
+
+        // The headers of the pruned blocks are retained, and the genesis block is kept in full.
+        for block in &blocks[..3] {
+            assert_eq!(&ledger.get_block_header(block.header().height()).unwrap(), block.header());
+        }
+        assert!(!ledger.is_block_pruned(0).unwrap());
+        assert!(ledger.get_block(0).is_ok());
+    }
+
+    #[test]
+    fn test_reader_evicts_pruned_blocks() {
+        let dir = temp_dir();
+        let ledger = LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+
+        // Store a chain of blocks on top of the genesis block.
+        let blocks = sample_blocks();
+        for block in &blocks {
+            ledger.blocks.add_block(block, None).expect("Failed to add block");
+        }
+
+        // Read a block through the reader, caching it.
+        let (reader, _) = LedgerState::<CurrentNetwork, ReadOnly, A>::open_reader::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        let mut current_block = reader.latest_block();
+        assert_eq!(reader.get_block(2).unwrap(), blocks[1]);
+
+        // Once the writer prunes the block, the reader no longer serves it from its cache.
+        assert_eq!(ledger.prune_to_height(NUM_SAMPLE_BLOCKS, None).unwrap(), NUM_SAMPLE_BLOCKS);
+        reader.refresh_reader(&mut current_block);
+        assert!(reader.get_block(2).is_err());
+        assert!(reader.get_block_header(2).is_ok());
+        assert_eq!(reader.get_block(NUM_SAMPLE_BLOCKS).unwrap(), blocks[3]);
+    }
+
+    #[test]
+    fn test_reader_latest_tip_is_not_torn() {
+        let dir = temp_dir();
+        let ledger = LedgerState::<CurrentNetwork, ReadWrite, A>::open_writer::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        let (reader, _) = LedgerState::<CurrentNetwork, ReadOnly, A>::open_reader::<RocksDB, _>(&dir).expect("Failed to open ledger state");
+        let is_done = Arc::new(AtomicBool::new(false));
+
+        // Read the latest tip of the reader concurrently, while blocks are applied.
+        let readers = (0..4)
+            .map(|_| {
+                let (reader, is_done) = (reader.clone(), is_done.clone());
+                thread::spawn(move || {
+                    while !is_done.load(Ordering::SeqCst) {
+                        let (block_height, block_hash, _) = reader.latest_tip();
+                        assert_eq!(reader.get_block_hash(block_height).unwrap(), block_hash);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut current_block = reader.latest_block();
+        for block in sample_blocks() {
+            ledger.add_next_verified_blocks(&[block.clone()]).expect("Failed to add block");
+            reader.refresh_reader(&mut current_block);
+            assert_eq!(reader.latest_tip(), (block.header().height(), block.hash(), false));
+        }
+        is_done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        // The sync status is read in the same snapshot.
+        reader.set_synced(true);
+        assert_eq!(reader.latest_tip(), (NUM_SAMPLE_BLOCKS, ledger.latest_block_hash(), true));
+    }
 }
@@ -14,8 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub(super) mod block_cache;
+#[cfg(feature = "rocks")]
+pub(super) mod block_file;
 pub(super) mod block_state;
+pub(super) mod block_timestamps;
+pub(super) mod ledger_proof;
 pub(super) mod ledger_state;
+pub(super) mod recent_blocks;
+pub(super) mod transaction_index;
 pub(super) mod transaction_state;
 
 use snarkvm::{
@@ -50,6 +57,11 @@ impl<N: Network> Metadata<N> {
             transaction_index,
         }
     }
+
+    /// Returns the height of the block containing the transaction.
+    pub fn block_height(&self) -> u32 {
+        self.block_height
+    }
 }
 
 // TODO (raychu86): This is a temporary solution.
@@ -70,9 +82,14 @@ pub(crate) mod test_helpers {
     use super::*;
 
     use once_cell::sync::OnceCell;
+    use rand::{rngs::StdRng, SeedableRng};
+    use snarkvm::{Header, Metadata as HeaderMetadata};
 
     pub(crate) type CurrentNetwork = Testnet3;
 
+    /// The number of blocks in the sample chain, following the genesis block.
+    pub(crate) const NUM_SAMPLE_BLOCKS: u32 = 4;
+
     pub(crate) fn sample_genesis_block() -> Block<CurrentNetwork> {
         static INSTANCE: OnceCell<Block<CurrentNetwork>> = OnceCell::new();
         INSTANCE
@@ -88,4 +105,45 @@ pub(crate) mod test_helpers {
             })
             .clone()
     }
+
+    /// Returns a chain of blocks extending the sample genesis block, each holding the transactions of a distinct caller.
+    pub(crate) fn sample_blocks() -> Vec<Block<CurrentNetwork>> {
+        static INSTANCE: OnceCell<Vec<Block<CurrentNetwork>>> = OnceCell::new();
+        INSTANCE
+            .get_or_init(|| {
+                let mut blocks: Vec<Block<CurrentNetwork>> = Vec::with_capacity(NUM_SAMPLE_BLOCKS as usize);
+                let mut previous_block = sample_genesis_block();
+
+                for block_height in 1..=NUM_SAMPLE_BLOCKS {
+                    // Initialize the VM.
+                    let mut vm = VM::<CurrentNetwork>::new().unwrap();
+                    // Initialize an RNG that is distinct from the genesis RNG.
+                    let rng = &mut StdRng::seed_from_u64(block_height as u64);
+                    // Initialize a new caller, and sample its transactions.
+                    let caller_private_key = PrivateKey::<CurrentNetwork>::new(rng).unwrap();
+                    let transactions = Block::genesis(&mut vm, &caller_private_key, rng).unwrap().transactions().clone();
+
+                    // Construct the header of the next block.
+                    let previous_header = previous_block.header();
+                    let metadata = HeaderMetadata::new(
+                        CurrentNetwork::ID,
+                        previous_header.round() + 1,
+                        block_height,
+                        previous_header.coinbase_target(),
+                        previous_header.proof_target(),
+                        previous_header.timestamp() + 1,
+                    )
+                    .unwrap();
+                    let header = Header::from(*previous_header.previous_state_root(), transactions.to_root().unwrap(), metadata).unwrap();
+
+                    // Construct the next block.
+                    let block = Block::from(previous_block.hash(), header, transactions).unwrap();
+                    blocks.push(block.clone());
+                    previous_block = block;
+                }
+
+                blocks
+            })
+            .clone()
+    }
 }
@@ -0,0 +1,144 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+///
+/// A ring buffer of the block hashes and block headers of the latest consecutive blocks,
+/// so that recent block hashes and block headers are served without reading from storage.
+///
+/// The buffer is rebuilt from storage whenever the ledger reverts, as its entries may no longer be canon.
+///
+#[derive(Debug)]
+pub(crate) struct RecentBlocks<H: Copy, V: Clone> {
+    /// The (block_height, block_hash, block_header) of the latest blocks, in increasing order of block height.
+    entries: VecDeque<(u32, H, V)>,
+    /// The maximum number of blocks in the buffer.
+    capacity: usize,
+}
+
+impl<H: Copy, V: Clone> RecentBlocks<H, V> {
+    /// Initializes a new buffer holding up to the given number of blocks.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    ///
+    /// Adds the given block as the latest block, evicting the oldest block if the buffer is full.
+    /// If the block does not extend the latest block in the buffer, the buffer restarts from the given block.
+    ///
+    pub(crate) fn push(&mut self, block_height: u32, block_hash: H, block_header: V) {
+        if let Some((latest_block_height, ..)) = self.entries.back() {
+            if latest_block_height.checked_add(1) != Some(block_height) {
+                self.entries.clear();
+            }
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        if self.capacity > 0 {
+            self.entries.push_back((block_height, block_hash, block_header));
+        }
+    }
+
+    /// Replaces the contents of the buffer with the given consecutive blocks, in increasing order of block height.
+    pub(crate) fn reset<I: IntoIterator<Item = (u32, H, V)>>(&mut self, blocks: I) {
+        self.entries.clear();
+        for (block_height, block_hash, block_header) in blocks {
+            self.push(block_height, block_hash, block_header);
+        }
+    }
+
+    /// Returns the block hashes and block headers of up to the latest `n` blocks, in increasing order of block height.
+    pub(crate) fn latest(&self, n: usize) -> Vec<(u32, H, V)> {
+        self.entries.iter().skip(self.entries.len().saturating_sub(n)).cloned().collect()
+    }
+
+    ///
+    /// Returns the block hashes from the given `start_block_height` to `end_block_height` (inclusive),
+    /// or `None` if any of the blocks are not in the buffer.
+    ///
+    pub(crate) fn get_block_hashes(&self, start_block_height: u32, end_block_height: u32) -> Option<Vec<H>> {
+        let (oldest_block_height, ..) = self.entries.front()?;
+        let (latest_block_height, ..) = self.entries.back()?;
+        if start_block_height > end_block_height || start_block_height < *oldest_block_height || end_block_height > *latest_block_height {
+            return None;
+        }
+
+        let start = (start_block_height - oldest_block_height) as usize;
+        let end = (end_block_height - oldest_block_height) as usize;
+        Some(self.entries.range(start..=end).map(|(_, block_hash, _)| *block_hash).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the sample block hash of the given block height on the given fork.
+    fn block(block_height: u32, fork: u32) -> (u32, u32, u32) {
+        (block_height, 1000 * fork + block_height, block_height)
+    }
+
+    #[test]
+    fn test_push_evicts_oldest_block() {
+        let mut recent_blocks = RecentBlocks::new(4);
+        for block_height in 0..10 {
+            recent_blocks.push(block_height, block_height, block_height);
+        }
+
+        assert_eq!(recent_blocks.latest(2), vec![(8, 8, 8), (9, 9, 9)]);
+        assert_eq!(recent_blocks.latest(10).len(), 4);
+        assert_eq!(recent_blocks.get_block_hashes(6, 9), Some(vec![6, 7, 8, 9]));
+        // Blocks outside of the buffer are read from storage instead.
+        assert_eq!(recent_blocks.get_block_hashes(5, 9), None);
+        assert_eq!(recent_blocks.get_block_hashes(9, 10), None);
+        assert_eq!(recent_blocks.get_block_hashes(9, 8), None);
+    }
+
+    #[test]
+    fn test_interleaved_adds_and_reverts() {
+        let mut recent_blocks = RecentBlocks::new(4);
+        for block_height in 0..6 {
+            let (block_height, block_hash, block_header) = block(block_height, 0);
+            recent_blocks.push(block_height, block_hash, block_header);
+        }
+
+        // Revert to block 3, rebuilding the buffer from the remaining canon blocks.
+        recent_blocks.reset((0..=3).map(|block_height| block(block_height, 0)));
+        assert_eq!(recent_blocks.get_block_hashes(0, 3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(recent_blocks.get_block_hashes(4, 4), None);
+
+        // Add the blocks of a fork, which replace the reverted blocks.
+        for block_height in 4..7 {
+            let (block_height, block_hash, block_header) = block(block_height, 1);
+            recent_blocks.push(block_height, block_hash, block_header);
+        }
+        assert_eq!(recent_blocks.get_block_hashes(3, 6), Some(vec![3, 1004, 1005, 1006]));
+
+        // Revert again, and add a block of another fork.
+        recent_blocks.reset((2..=4).map(|block_height| block(block_height, if block_height < 4 { 0 } else { 1 })));
+        recent_blocks.push(5, 2005, 5);
+        assert_eq!(recent_blocks.latest(4), vec![(2, 2, 2), (3, 3, 3), (4, 1004, 4), (5, 2005, 5)]);
+
+        // A block that does not extend the latest block restarts the buffer, so that it never holds a stale block.
+        recent_blocks.push(3, 3003, 3);
+        assert_eq!(recent_blocks.latest(4), vec![(3, 3003, 3)]);
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::{DataID, DataMap, MapRead, MapReadWrite, Storage, StorageAccess, StorageReadWrite};
+use snarkvm::{
+    compiler::Output,
+    console::{program::Owner, types::field::Field},
+    prelude::{Address, Network},
+    Block, Transaction,
+};
+
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+
+///
+/// The optional secondary index of the ledger, which maps the public owner of each record,
+/// and the commitment of each record, to the `(block_height, transaction_id)` that created it.
+///
+/// The index is maintained as blocks are added and reverted, only while it is enabled.
+///
+#[derive(Clone, Debug)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct TransactionIndex<N: Network, SA: StorageAccess> {
+    /// Map of record owner to the (block_height, transaction_id) of each transaction, in increasing order
+    addresses: DataMap<Address<N>, Vec<(u32, N::TransactionID)>, SA>,
+    /// Map of commitment to (block_height, transaction_id)
+    commitments: DataMap<Field<N>, (u32, N::TransactionID), SA>,
+    /// The block height up to which the index is complete, if the index is enabled.
+    indexed_block_height: DataMap<(), u32, SA>,
+}
+
+impl<N: Network, SA: StorageAccess> TransactionIndex<N, SA> {
+    /// Initializes a new instance of `TransactionIndex`.
+    pub(crate) fn open<S: Storage<Access = SA>>(storage: &S) -> Result<Self> {
+        Ok(Self {
+            addresses: storage.open_map(DataID::AddressTransactions)?,
+            commitments: storage.open_map(DataID::CommitmentTransactions)?,
+            indexed_block_height: storage.open_map(DataID::TransactionIndexHeight)?,
+        })
+    }
+
+    /// Returns the block height up to which the index is complete, or `None` if the index is disabled.
+    pub(crate) fn indexed_block_height(&self) -> Result<Option<u32>> {
+        self.indexed_block_height.get(&())
+    }
+
+    /// Returns up to `limit` of the (block_height, transaction_id) involving the given address, skipping the first `offset`.
+    pub(crate) fn get_transactions_by_address(
+        &self,
+        address: &Address<N>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(u32, N::TransactionID)>> {
+        if self.indexed_block_height()?.is_none() {
+            return Err(anyhow!("The transaction index is not enabled"));
+        }
+
+        match self.addresses.get(address)? {
+            Some(transactions) => Ok(transactions.into_iter().skip(offset).take(limit).collect()),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the (block_height, transaction_id) of the transaction that created the given commitment.
+    pub(crate) fn get_transaction_by_commitment(&self, commitment: &Field<N>) -> Result<(u32, N::TransactionID)> {
+        if self.indexed_block_height()?.is_none() {
+            return Err(anyhow!("The transaction index is not enabled"));
+        }
+
+        match self.commitments.get(commitment)? {
+            Some(entry) => Ok(entry),
+            None => Err(anyhow!("Commitment {} does not exist in the transaction index", commitment)),
+        }
+    }
+}
+
+impl<N: Network, SA: StorageReadWrite> TransactionIndex<N, SA> {
+    ///
+    /// Adds the transactions of the given consecutive blocks to the index, and marks the index
+    /// as complete up to the last block.
+    ///
+    pub(crate) fn add_blocks(&self, blocks: &[Block<N>], batch: Option<usize>) -> Result<()> {
+        // Gather the new entries of each address first, as the batched writes are not visible to reads.
+        let mut address_entries: HashMap<Address<N>, Vec<(u32, N::TransactionID)>> = HashMap::new();
+        for block in blocks {
+            let block_height = block.header().height();
+            for (transaction_id, transaction) in block.transactions().iter() {
+                for (commitment, owner) in records(transaction) {
+                    self.commitments.insert(&commitment, &(block_height, *transaction_id), batch)?;
+                    if let Some(address) = owner {
+                        let entries = address_entries.entry(address).or_default();
+                        // A transaction is listed once per address, even if it creates several records for it.
+                        if entries.last() != Some(&(block_height, *transaction_id)) {
+                            entries.push((block_height, *transaction_id));
+                        }
+                    }
+                }
+            }
+        }
+
+        for (address, entries) in address_entries {
+            let mut transactions = self.addresses.get(&address)?.unwrap_or_default();
+            transactions.extend(entries);
+            self.addresses.insert(&address, &transactions, batch)?;
+        }
+
+        if let Some(block) = blocks.last() {
+            self.indexed_block_height.insert(&(), &block.header().height(), batch)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Removes the transactions of the given disconnected blocks from the index, and marks the index
+    /// as complete up to the given block height.
+    ///
+    pub(crate) fn remove_blocks(&self, blocks: &[Block<N>], block_height: u32, batch: Option<usize>) -> Result<()> {
+        let removed_block_heights: HashSet<u32> = blocks.iter().map(|block| block.header().height()).collect();
+
+        let mut addresses = HashSet::new();
+        for block in blocks {
+            for (_, transaction) in block.transactions().iter() {
+                for (commitment, owner) in records(transaction) {
+                    self.commitments.remove(&commitment, batch)?;
+                    addresses.extend(owner);
+                }
+            }
+        }
+
+        for address in addresses {
+            if let Some(mut transactions) = self.addresses.get(&address)? {
+                transactions.retain(|(block_height, _)| !removed_block_heights.contains(block_height));
+                match transactions.is_empty() {
+                    true => self.addresses.remove(&address, batch)?,
+                    false => self.addresses.insert(&address, &transactions, batch)?,
+                }
+            }
+        }
+
+        self.indexed_block_height.insert(&(), &block_height, batch)
+    }
+
+    /// Removes every entry of the index, leaving the index disabled.
+    pub(crate) fn clear(&self, batch: Option<usize>) -> Result<()> {
+        for address in self.addresses.keys() {
+            self.addresses.remove(&address, batch)?;
+        }
+        for commitment in self.commitments.keys() {
+            self.commitments.remove(&commitment, batch)?;
+        }
+        self.indexed_block_height.remove(&(), batch)
+    }
+
+    /// Marks the index as disabled, so that it is no longer served, and is rebuilt when it is enabled again.
+    pub(crate) fn disable(&self, batch: Option<usize>) -> Result<()> {
+        self.indexed_block_height.remove(&(), batch)
+    }
+}
+
+/// Returns the commitment of each record created by the given transaction, along with its owner, if the owner is public.
+fn records<N: Network>(transaction: &Transaction<N>) -> Vec<(Field<N>, Option<Address<N>>)> {
+    match transaction {
+        // Deployments do not create records.
+        Transaction::Deploy(..) => vec![],
+        Transaction::Execute(_, execution) => execution
+            .iter()
+            .flat_map(|transition| transition.outputs())
+            .filter_map(|output| match output {
+                Output::Record(commitment, _, record) => {
+                    let owner = record.as_ref().and_then(|record| match record.owner() {
+                        Owner::Public(address) => Some(*address),
+                        Owner::Private(..) => None,
+                    });
+                    Some((*commitment, owner))
+                }
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::ledger::test_helpers::{sample_genesis_block, CurrentNetwork},
+        storage::{
+            rocksdb::{tests::temp_dir, RocksDB},
+            ReadWrite,
+            Storage,
+        },
+    };
+
+    #[test]
+    fn test_add_and_remove_blocks() {
+        let storage = RocksDB::<ReadWrite>::open(temp_dir(), 0).expect("Failed to open storage");
+        let index = TransactionIndex::<CurrentNetwork, ReadWrite>::open(&storage).expect("Failed to open transaction index");
+        let block = sample_genesis_block();
+
+        // The index is disabled until it is built.
+        assert_eq!(index.indexed_block_height().unwrap(), None);
+        for commitment in block.transactions().commitments() {
+            assert!(index.get_transaction_by_commitment(commitment).is_err());
+        }
+
+        // Each commitment of the block is indexed.
+        index.add_blocks(&[block.clone()], None).expect("Failed to index the block");
+        assert_eq!(index.indexed_block_height().unwrap(), Some(0));
+        for (transaction_id, transaction) in block.transactions().iter() {
+            for commitment in transaction.commitments() {
+                assert_eq!(index.get_transaction_by_commitment(commitment).unwrap(), (0, *transaction_id));
+            }
+        }
+
+        // The entries of a disconnected block are removed.
+        index.remove_blocks(&[block.clone()], 0, None).expect("Failed to remove the block");
+        for commitment in block.transactions().commitments() {
+            assert!(index.get_transaction_by_commitment(commitment).is_err());
+        }
+
+        // A cleared index is disabled.
+        index.clear(None).expect("Failed to clear the index");
+        assert_eq!(index.indexed_block_height().unwrap(), None);
+    }
+}
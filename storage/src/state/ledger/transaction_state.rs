@@ -76,6 +76,21 @@ impl<N: Network, SA: StorageAccess> TransactionState<N, SA> {
         self.commitments.contains_key(commitment)
     }
 
+    /// Returns the ID of the transaction that created the given commitment, or `None` if the commitment does not exist.
+    pub(crate) fn get_transaction_id_by_commitment(&self, commitment: &Field<N>) -> Result<Option<N::TransactionID>> {
+        // Retrieve the transition ID.
+        let transition_id = match self.commitments.get(commitment)? {
+            Some(transition_id) => transition_id,
+            None => return Ok(None),
+        };
+
+        // Retrieve the transaction ID from the transition.
+        match self.transitions.get(&transition_id)? {
+            Some((transaction_id, _, _)) => Ok(Some(transaction_id)),
+            None => Err(anyhow!("Transition {} of commitment {} is missing from storage", transition_id, commitment)),
+        }
+    }
+
     // /// Returns the record ciphertext for a given commitment.
     // fn get_ciphertext(&self, commitment: &N::Commitment) -> Result<N::RecordCiphertext> {
     //     // Retrieve the transition ID.
@@ -196,6 +211,18 @@ impl<N: Network, SA: StorageReadWrite> TransactionState<N, SA> {
         }
     }
 
+    /// Prunes the transitions of the given transaction ID from storage.
+    /// The transaction entry, its serial numbers, and its commitments are retained.
+    pub(crate) fn prune_transaction(&self, transaction_id: &N::TransactionID, batch: Option<usize>) -> Result<()> {
+        // Deployments are retained, as their programs are required for validation.
+        if let Some((_, transition_ids, _, _)) = self.transactions.get(transaction_id)? {
+            for transition_id in transition_ids.iter() {
+                self.transitions.remove(transition_id, batch)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Removes the given transaction ID from storage.
     pub(crate) fn remove_transaction(&self, transaction_id: &N::TransactionID, batch: Option<usize>) -> Result<()> {
         // Remove an execute transaction.
@@ -15,13 +15,20 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 pub(crate) mod ledger;
-pub use ledger::{ledger_state::LedgerState, Metadata};
+#[cfg(feature = "rocks")]
+pub use ledger::block_file::BlockFileHeader;
+pub use ledger::{
+    block_timestamps::DEFAULT_MAXIMUM_FUTURE_BLOCK_TIME_IN_SECS,
+    ledger_proof::LedgerProof,
+    ledger_state::{LedgerState, ALEO_MAXIMUM_FORK_DEPTH, DEFAULT_BLOCK_CACHE_CAPACITY, MINIMUM_PRUNING_DEPTH},
+    Metadata,
+};
 
 // pub(crate) mod operator;
-// pub use operator::OperatorState;
+// pub use operator::{BlockFound, OperatorState, ProverStats, RoundStatus, ShareAccounting, ShareOutcome, ShareRow};
 //
 // pub(crate) mod prover;
-// pub use prover::ProverState;
+// pub use prover::{ProverLifetimeStats, ProverState};
 
 // #[cfg(test)]
 // mod tests;
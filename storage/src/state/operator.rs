@@ -18,33 +18,196 @@ use crate::storage::{DataMap, DataID, MapRead, MapReadWrite, Storage, StorageAcc
 use snarkvm::prelude::*;
 
 use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
+    fmt,
     iter::FromIterator,
+    ops::RangeInclusive,
     path::Path,
 };
 
+/// The method an operator uses to attribute shares to a round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShareAccounting {
+    /// Each prover is credited one share per valid submission for the current round.
+    Proportional,
+    /// Each round is credited the difficulty-weighted shares in a rolling window of the last N submissions.
+    Pplns(u32),
+}
+
+impl Default for ShareAccounting {
+    fn default() -> Self {
+        Self::Proportional
+    }
+}
+
+/// The outcome of a share submitted to the operator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShareOutcome {
+    /// The share was valid, and credited to the prover.
+    Accepted,
+    /// The share failed PoSW proof verification.
+    Rejected,
+    /// The share was for a block template that is no longer accepted.
+    Stale,
+    /// The share reused a nonce that the operator had already seen.
+    Duplicate,
+}
+
+///
+/// The number of shares submitted by a prover, by outcome.
+///
+/// *Attention*: This data structure is intended for usage in storage only.
+/// Modifications to its layout will impact how the statistics are represented in storage.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverStats {
+    /// The number of accepted shares.
+    pub accepted: u64,
+    /// The number of shares that failed PoSW proof verification.
+    pub rejected: u64,
+    /// The number of shares for a block template that is no longer accepted.
+    pub stale: u64,
+    /// The number of shares with a duplicate nonce.
+    pub duplicate: u64,
+}
+
+impl ProverStats {
+    /// Increments the counter for the given share outcome.
+    fn record(&mut self, outcome: ShareOutcome) {
+        let counter = match outcome {
+            ShareOutcome::Accepted => &mut self.accepted,
+            ShareOutcome::Rejected => &mut self.rejected,
+            ShareOutcome::Stale => &mut self.stale,
+            ShareOutcome::Duplicate => &mut self.duplicate,
+        };
+        *counter = counter.saturating_add(1);
+    }
+}
+
+///
+/// A block found by the operator, and the prover whose share produced it.
+///
+/// *Attention*: This data structure is intended for usage in storage only.
+/// Modifications to its layout will impact how found blocks are represented in storage.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockFound<N: Network> {
+    /// The hash of the block.
+    pub block_hash: N::BlockHash,
+    /// The address of the prover whose share produced the block.
+    pub prover: Address<N>,
+    /// The UNIX timestamp at which the block was found.
+    pub timestamp: i64,
+    /// The commitment of the coinbase record of the block.
+    pub coinbase_commitment: N::Commitment,
+    /// `true` if the block has been confirmed on the canonical chain.
+    pub is_confirmed: bool,
+    /// `true` if the block is no longer on the canonical chain.
+    pub is_orphaned: bool,
+}
+
+/// The status of a round of shares.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundStatus {
+    /// The operator has not found a block for the round.
+    Open,
+    /// The operator has found a block for the round, which has yet to be confirmed.
+    Found,
+    /// The block for the round has been confirmed on the canonical chain.
+    Confirmed,
+    /// The block for the round is no longer on the canonical chain.
+    Orphaned,
+    /// The round has been paid out.
+    Paid,
+}
+
+impl fmt::Display for RoundStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Found => write!(f, "found"),
+            Self::Confirmed => write!(f, "confirmed"),
+            Self::Orphaned => write!(f, "orphaned"),
+            Self::Paid => write!(f, "paid"),
+        }
+    }
+}
+
+/// The shares of a single prover in a single round, as exported from the operator state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct ShareRow<N: Network> {
+    /// The block height of the round.
+    pub block_height: u32,
+    /// The commitment of the coinbase record of the round.
+    pub coinbase_commitment: N::Commitment,
+    /// The address of the prover.
+    pub prover: Address<N>,
+    /// The difficulty-weighted shares of the prover in the round.
+    pub shares: u64,
+    /// The status of the round.
+    pub round_status: RoundStatus,
+}
+
 #[derive(Debug)]
 pub struct OperatorState<N: Network, A: StorageAccess> {
     shares: SharesState<N, A>,
+    payouts: PayoutsState<N, A>,
+    stats: StatsState<N, A>,
+    /// The blocks found by the operator, keyed by block height.
+    blocks_found: DataMap<u32, BlockFound<N>, A>,
+    /// The difficulty-weighted work of the shares, and the expected work, of each round that found a block, keyed by block height.
+    round_work: DataMap<u32, (u64, u64), A>,
+    /// A marker that is `true` while the operator is running, and `false` once it has shut down cleanly.
+    is_running: DataMap<(), bool, A>,
+    accounting: ShareAccounting,
 }
 
 impl<N: Network, A: StorageAccess> OperatorState<N, A> {
+    ///
     /// Opens a new instance of `OperatorState` from the given storage path.
-    pub fn open<S: Storage<Access = A>, P: AsRef<Path>>(path: P) -> Result<Self> {
+    /// Shares are weighted relative to the given base share difficulty, at which a share weighs 1.
+    ///
+    pub fn open<S: Storage<Access = A>, P: AsRef<Path>>(path: P, accounting: ShareAccounting, base_share_difficulty: u64) -> Result<Self> {
         // Open storage.
         let context = N::ID;
         let storage = S::open(path, context)?;
 
         // Initialize the operator.
         let operator = Self {
-            shares: SharesState::open(storage)?,
+            payouts: PayoutsState::open(&storage)?,
+            stats: StatsState::open(&storage)?,
+            blocks_found: storage.open_map(DataID::BlocksFound)?,
+            round_work: storage.open_map(DataID::RoundWork)?,
+            is_running: storage.open_map(DataID::OperatorStatus)?,
+            shares: SharesState::open(storage, base_share_difficulty)?,
+            accounting,
         };
 
-        info!("Operator successfully initialized");
+        info!("Operator successfully initialized ({:?} share accounting)", accounting);
         Ok(operator)
     }
 
+    ///
+    /// Opens a read-only instance of `OperatorState` from the given storage path.
+    /// For a writable instance of `OperatorState`, use `OperatorState::open`.
+    ///
+    /// A read-only instance may be opened while the operator is running, and may only call immutable methods.
+    /// As it never weighs new shares, it is opened with a base share difficulty of 1.
+    ///
+    pub fn open_reader<S: Storage<Access = A>, P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open::<S, P>(path, ShareAccounting::default(), 1)
+    }
+
+    /// Returns the share accounting mode of the operator.
+    pub fn accounting(&self) -> ShareAccounting {
+        self.accounting
+    }
+
     /// Returns all the shares in storage.
     pub fn to_shares(&self) -> Vec<((u32, Record<N>), HashMap<Address<N>, u64>)> {
         self.shares.to_shares()
@@ -55,6 +218,11 @@ impl<N: Network, A: StorageAccess> OperatorState<N, A> {
         self.shares.to_records()
     }
 
+    /// Returns the recipient of the coinbase record of each round, which is the owner of the coinbase record.
+    pub fn to_round_recipients(&self) -> Vec<(u32, Address<N>)> {
+        self.shares.to_records().into_iter().map(|(block_height, record)| (block_height, record.owner())).collect()
+    }
+
     /// Returns the shares for a specific block, given the block height and coinbase record.
     pub fn get_shares_for_block(&self, block_height: u32, coinbase_record: Record<N>) -> Result<HashMap<Address<N>, u64>> {
         self.shares.get_shares_for_block(block_height, coinbase_record)
@@ -65,65 +233,379 @@ impl<N: Network, A: StorageAccess> OperatorState<N, A> {
         self.shares.get_shares_for_prover(prover)
     }
 
+    /// Returns `true` if a share with the given nonce was accepted for the round of the given block height and coinbase commitment.
+    pub fn contains_round_nonce(&self, block_height: u32, coinbase_commitment: N::Commitment, nonce: N::PoSWNonce) -> Result<bool> {
+        self.shares.contains_round_nonce(block_height, coinbase_commitment, nonce)
+    }
+
     /// Returns a list of provers which have submitted shares to an operator.
     pub fn get_provers(&self) -> Vec<Address<N>> {
         self.shares.get_provers()
     }
+
+    /// Returns `true` if the round for the given block height and coinbase record has been paid out.
+    pub fn is_paid(&self, block_height: u32, coinbase_record: Record<N>) -> Result<bool> {
+        self.payouts.is_paid(block_height, coinbase_record)
+    }
+
+    /// Returns the payout transaction IDs for the given block height and coinbase record, if the round has been paid out.
+    pub fn get_payout(&self, block_height: u32, coinbase_record: Record<N>) -> Result<Option<Vec<N::TransactionID>>> {
+        self.payouts.get_payout(block_height, coinbase_record)
+    }
+
+    /// Returns the number of shares submitted by the given prover, by outcome.
+    pub fn get_stats_for_prover(&self, prover: &Address<N>) -> Result<ProverStats> {
+        self.stats.get_stats_for_prover(prover)
+    }
+
+    /// Returns the number of shares submitted by each prover, by outcome.
+    pub fn to_prover_stats(&self) -> Vec<(Address<N>, ProverStats)> {
+        self.stats.to_prover_stats()
+    }
+
+    /// Returns the blocks found by the operator, in order of block height.
+    pub fn get_blocks_found(&self) -> Vec<(u32, BlockFound<N>)> {
+        let mut blocks_found: Vec<_> = self.blocks_found.iter().collect();
+        // Keys are not iterated in numerical order, so sort them.
+        blocks_found.sort_unstable_by_key(|(block_height, _)| *block_height);
+        blocks_found
+    }
+
+    /// Returns the block found by the operator at the given block height, if one exists.
+    pub fn get_block_found(&self, block_height: u32) -> Result<Option<BlockFound<N>>> {
+        self.blocks_found.get(&block_height)
+    }
+
+    ///
+    /// Returns the work and the expected work of the most recent rounds that found a block, up to the given number
+    /// of rounds, in descending order of block height.
+    ///
+    pub fn get_recent_round_work(&self, number_of_rounds: usize) -> Vec<(u32, (u64, u64))> {
+        let mut round_work: Vec<_> = self.round_work.iter().collect();
+        // Keys are not iterated in numerical order, so sort them.
+        round_work.sort_unstable_by_key(|(block_height, _)| std::cmp::Reverse(*block_height));
+        round_work.truncate(number_of_rounds);
+        round_work
+    }
+
+    ///
+    /// Passes the shares of each prover in each round within the given block heights to the given function,
+    /// optionally for the given prover only. The rounds are read from storage one at a time, so that
+    /// exporting a large operator state does not load it into memory. Rounds are not visited in order.
+    ///
+    pub fn for_each_share_row<F: FnMut(ShareRow<N>) -> Result<()>>(
+        &self,
+        block_heights: RangeInclusive<u32>,
+        prover: Option<&Address<N>>,
+        mut f: F,
+    ) -> Result<()> {
+        let mut visit = |(block_height, coinbase_record): (u32, Record<N>), shares: HashMap<Address<N>, u64>| -> Result<()> {
+            let round_status = match self.payouts.is_paid(block_height, coinbase_record.clone())? {
+                true => RoundStatus::Paid,
+                false => match self.blocks_found.get(&block_height)? {
+                    Some(block_found) if block_found.coinbase_commitment == coinbase_record.commitment() => {
+                        match (block_found.is_orphaned, block_found.is_confirmed) {
+                            (true, _) => RoundStatus::Orphaned,
+                            (false, true) => RoundStatus::Confirmed,
+                            (false, false) => RoundStatus::Found,
+                        }
+                    }
+                    _ => RoundStatus::Open,
+                },
+            };
+
+            for (address, shares) in shares {
+                if prover.map_or(true, |prover| *prover == address) {
+                    f(ShareRow {
+                        block_height,
+                        coinbase_commitment: coinbase_record.commitment(),
+                        prover: address,
+                        shares,
+                        round_status,
+                    })?;
+                }
+            }
+            Ok(())
+        };
+
+        let is_within_range = |((block_height, _), _): &((u32, Record<N>), _)| block_heights.contains(block_height);
+
+        // Visit the weighted shares, merged with the unit shares of the same round.
+        for (key, mut shares) in self.shares.shares.iter().filter(is_within_range) {
+            merge_shares(&mut shares, self.shares.unit_shares.get(&key)?.unwrap_or_default());
+            visit(key, shares)?;
+        }
+        // Visit the rounds that only have unit shares.
+        for (key, shares) in self.shares.unit_shares.iter().filter(is_within_range) {
+            if !self.shares.shares.contains_key(&key)? {
+                visit(key, shares)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<N: Network, A: StorageReadWrite> OperatorState<N, A> {
-    /// Increments the share count by one for a given block height, coinbase record and prover address.
-    pub fn increment_share(&self, block_height: u32, coinbase_record: Record<N>, prover: &Address<N>) -> Result<()> {
-        self.shares.increment_share(block_height, coinbase_record, prover)
+    ///
+    /// Records a valid share for a given block height, coinbase record and prover address.
+    ///
+    /// In proportional mode, this increments the shares of the prover for the round by the weight of the share difficulty.
+    /// In PPLNS mode, this appends the share to the share log, and prunes the share that fell out of the window.
+    ///
+    pub fn increment_share(&self, block_height: u32, coinbase_record: Record<N>, prover: &Address<N>, share_difficulty: u64) -> Result<()> {
+        match self.accounting {
+            ShareAccounting::Proportional => self.shares.increment_share(block_height, coinbase_record, prover, share_difficulty, None),
+            ShareAccounting::Pplns(window) => self.shares.append_to_share_log(prover, share_difficulty, window, None),
+        }
+    }
+
+    ///
+    /// Records a valid share as in `increment_share`, and persists the nonce of the share for its round in the same
+    /// storage batch, so that a share accepted before a restart is still known to be a duplicate after it.
+    ///
+    pub fn increment_share_with_nonce(
+        &self,
+        block_height: u32,
+        coinbase_record: Record<N>,
+        prover: &Address<N>,
+        share_difficulty: u64,
+        nonce: N::PoSWNonce,
+    ) -> Result<()> {
+        let round_nonce = (block_height, coinbase_record.commitment(), nonce);
+        match self.accounting {
+            ShareAccounting::Proportional => {
+                self.shares.increment_share(block_height, coinbase_record, prover, share_difficulty, Some(round_nonce))
+            }
+            ShareAccounting::Pplns(window) => self.shares.append_to_share_log(prover, share_difficulty, window, Some(round_nonce)),
+        }
+    }
+
+    /// Removes the persisted nonces of every round other than the given rounds, each given by its block height and coinbase commitment.
+    pub fn clear_round_nonces_except(&self, rounds: &[(u32, N::Commitment)]) -> Result<()> {
+        self.shares.clear_round_nonces_except(rounds)
+    }
+
+    ///
+    /// Closes the round for a given block height and coinbase record, once the operator has found the block.
+    ///
+    /// In PPLNS mode, this stores the difficulty-weighted shares of the current window as the shares for the round.
+    /// In proportional mode, the shares are already stored as they arrive, so this is a no-op.
+    ///
+    pub fn close_round(&self, block_height: u32, coinbase_record: Record<N>) -> Result<()> {
+        match self.accounting {
+            ShareAccounting::Proportional => Ok(()),
+            ShareAccounting::Pplns(_) => self.shares.snapshot_share_log(block_height, coinbase_record),
+        }
     }
 
     /// Removes the shares for a given block height and coinbase record in storage.
     pub fn remove_shares(&self, block_height: u32, coinbase_record: Record<N>) -> Result<()> {
         self.shares.remove_shares(block_height, coinbase_record)
     }
+
+    /// Marks the round for a given block height and coinbase record as paid out, with the given payout transaction IDs.
+    pub fn mark_paid(&self, block_height: u32, coinbase_record: Record<N>, transaction_ids: &[N::TransactionID]) -> Result<()> {
+        self.payouts.mark_paid(block_height, coinbase_record, transaction_ids)
+    }
+
+    /// Increments the share counter of the given prover for the given share outcome.
+    pub fn record_share_outcome(&self, prover: &Address<N>, outcome: ShareOutcome) -> Result<()> {
+        self.stats.record_share_outcome(prover, outcome)
+    }
+
+    /// Resets the share counters of all provers.
+    pub fn reset_prover_stats(&self) -> Result<()> {
+        self.stats.reset_prover_stats()
+    }
+
+    /// Records a block found by the operator at the given block height.
+    pub fn add_block_found(&self, block_height: u32, block_found: &BlockFound<N>) -> Result<()> {
+        self.blocks_found.insert(&block_height, block_found, None)
+    }
+
+    /// Records the work of the shares, and the expected work, of the round that found the block at the given block height.
+    pub fn add_round_work(&self, block_height: u32, work: u64, expected_work: u64) -> Result<()> {
+        self.round_work.insert(&block_height, &(work, expected_work), None)
+    }
+
+    /// Flags the block found by the operator at the given block height as confirmed on the canonical chain.
+    pub fn mark_block_confirmed(&self, block_height: u32) -> Result<()> {
+        match self.blocks_found.get(&block_height)? {
+            Some(mut block_found) => {
+                block_found.is_confirmed = true;
+                self.blocks_found.insert(&block_height, &block_found, None)
+            }
+            None => Err(anyhow!("Block {} was not found by the operator", block_height)),
+        }
+    }
+
+    /// Flags the block found by the operator at the given block height as orphaned.
+    pub fn mark_block_orphaned(&self, block_height: u32) -> Result<()> {
+        match self.blocks_found.get(&block_height)? {
+            Some(mut block_found) => {
+                block_found.is_orphaned = true;
+                self.blocks_found.insert(&block_height, &block_found, None)
+            }
+            None => Err(anyhow!("Block {} was not found by the operator", block_height)),
+        }
+    }
+
+    ///
+    /// Removes the rounds below the given block height that have been paid out or orphaned, so that unpaid
+    /// rounds are never removed. Each round is removed atomically, along with its payout.
+    /// Returns the number of rounds and the number of storage keys that were removed.
+    ///
+    pub fn prune_rounds_below(&self, block_height: u32) -> Result<(usize, usize)> {
+        let (mut num_rounds, mut num_keys) = (0, 0);
+        for (height, coinbase_record) in self.shares.to_records() {
+            if height >= block_height {
+                continue;
+            }
+
+            let is_paid = self.payouts.is_paid(height, coinbase_record.clone())?;
+            let is_orphaned = self.blocks_found.get(&height)?.map_or(false, |block_found| block_found.is_orphaned);
+            if !is_paid && !is_orphaned {
+                continue;
+            }
+
+            let batch = self.shares.prepare_batch();
+            let removed_shares = self.shares.remove_shares_in_batch(height, coinbase_record.clone(), batch)?;
+            let removed_payouts = self.payouts.remove_payout_in_batch(height, coinbase_record, batch)?;
+            self.shares.execute_batch(batch)?;
+
+            num_rounds += 1;
+            num_keys += removed_shares + removed_payouts;
+        }
+        Ok((num_rounds, num_keys))
+    }
+
+    ///
+    /// Removes the shares of an orphaned round, so that they are never paid out. If a round is given
+    /// to roll the shares into, they are added to its shares, so the provers keep credit for their work.
+    ///
+    pub fn remove_orphaned_round(&self, block_height: u32, coinbase_record: Record<N>, roll_into: Option<(u32, Record<N>)>) -> Result<()> {
+        self.shares.roll_over_shares(block_height, coinbase_record, roll_into)
+    }
+
+    /// Marks the operator as running, returning `true` if the previous run did not shut down cleanly.
+    pub fn mark_running(&self) -> Result<bool> {
+        let was_running = self.is_running.get(&())?.unwrap_or(false);
+        self.is_running.insert(&(), &true, None)?;
+        self.is_running.flush()?;
+        Ok(was_running)
+    }
+
+    /// Marks the operator as shut down, and flushes all of its writes to disk.
+    pub fn shut_down(&self) -> Result<()> {
+        self.is_running.insert(&(), &false, None)?;
+        self.is_running.flush()
+    }
 }
 
-#[derive(Clone, Debug)]
+///
+/// Returns the weight of a share with the given share difficulty, where a share at the base share difficulty weighs 1.
+/// A lower share difficulty is harder to meet, so it is worth proportionally more. Every share weighs at least 1.
+///
+fn share_weight(base_share_difficulty: u64, share_difficulty: u64) -> u64 {
+    (base_share_difficulty / share_difficulty.max(1)).max(1)
+}
+
+/// Adds the given shares to the given round of shares.
+fn merge_shares<N: Network>(round: &mut HashMap<Address<N>, u64>, shares: HashMap<Address<N>, u64>) {
+    for (prover, shares) in shares {
+        let entry = round.entry(prover).or_insert(0);
+        *entry = entry.saturating_add(shares);
+    }
+}
+
+#[derive(Debug)]
 #[allow(clippy::type_complexity)]
 struct SharesState<N: Network, A: StorageAccess> {
-    /// The miner shares for each block.
+    /// The difficulty-weighted miner shares for each block.
     shares: DataMap<(u32, Record<N>), HashMap<Address<N>, u64>, A>,
+    /// The miner shares for each block, counted as one per share, from before shares were weighted.
+    /// A share at the base share difficulty weighs 1, so these are merged with the weighted shares as is, and are only read.
+    unit_shares: DataMap<(u32, Record<N>), HashMap<Address<N>, u64>, A>,
+    /// The PPLNS share log, as a map of `sequence number => (prover, share difficulty, timestamp)`.
+    share_log: DataMap<u64, (Address<N>, u64, i64), A>,
+    /// The nonces of the shares accepted for the current round, keyed by block height, coinbase commitment, and nonce.
+    round_nonces: DataMap<(u32, N::Commitment, N::PoSWNonce), (), A>,
+    /// The sequence number of the next share appended to the share log.
+    next_sequence: Mutex<u64>,
+    /// The sequence number of the first share in the share log, which is only updated while `next_sequence` is locked.
+    first_sequence: Mutex<u64>,
+    /// A lock held while the shares of a round are updated, so that concurrent increments are not lost.
+    increment_lock: Mutex<()>,
+    /// The share difficulty at which a share weighs 1.
+    base_share_difficulty: u64,
 }
 
 impl<N: Network, A: StorageAccess> SharesState<N, A> {
     /// Initializes a new instance of `SharesState`.
-    fn open<S: Storage<Access = A>>(storage: S) -> Result<Self> {
+    fn open<S: Storage<Access = A>>(storage: S, base_share_difficulty: u64) -> Result<Self> {
+        let share_log: DataMap<u64, (Address<N>, u64, i64), A> = storage.open_map(DataID::ShareLog)?;
+        // Resume the share log after its last entry. Keys are not iterated in numerical order, so take the maximum.
+        let next_sequence = share_log.keys().max().map(|sequence| sequence + 1).unwrap_or(0);
+        let first_sequence = share_log.keys().min().unwrap_or(next_sequence);
+
         Ok(Self {
-            shares: storage.open_map(DataID::Shares)?,
+            shares: storage.open_map(DataID::WeightedShares)?,
+            unit_shares: storage.open_map(DataID::Shares)?,
+            share_log,
+            round_nonces: storage.open_map(DataID::RoundNonces)?,
+            next_sequence: Mutex::new(next_sequence),
+            first_sequence: Mutex::new(first_sequence),
+            increment_lock: Mutex::new(()),
+            base_share_difficulty,
         })
     }
 
     /// Returns all shares in storage.
     fn to_shares(&self) -> Vec<((u32, Record<N>), HashMap<Address<N>, u64>)> {
-        self.shares.iter().collect()
+        let mut shares: Vec<_> = self.shares.iter().collect();
+        for (key, unit_shares) in self.unit_shares.iter() {
+            match shares.iter_mut().find(|(round, _)| *round == key) {
+                Some((_, round_shares)) => merge_shares(round_shares, unit_shares),
+                None => shares.push((key, unit_shares)),
+            }
+        }
+        shares
     }
 
     /// Returns all records in storage.
     fn to_records(&self) -> Vec<(u32, Record<N>)> {
-        self.shares.keys().collect()
+        self.to_shares().into_iter().map(|(key, _)| key).collect()
     }
 
     /// Returns the shares for a specific block, given the block height and coinbase record.
     fn get_shares_for_block(&self, block_height: u32, coinbase_record: Record<N>) -> Result<HashMap<Address<N>, u64>> {
-        match self.shares.get(&(block_height, coinbase_record))? {
-            Some(shares) => Ok(shares),
-            None => Err(anyhow!("Block {} does not exist in shares storage", block_height)),
+        let key = (block_height, coinbase_record);
+        match (self.shares.get(&key)?, self.unit_shares.get(&key)?) {
+            (None, None) => Err(anyhow!("Block {} does not exist in shares storage", block_height)),
+            (shares, unit_shares) => {
+                let mut shares = shares.unwrap_or_default();
+                merge_shares(&mut shares, unit_shares.unwrap_or_default());
+                Ok(shares)
+            }
         }
     }
 
     /// Returns the shares for a specific prover, given the prover address.
     fn get_shares_for_prover(&self, prover: &Address<N>) -> u64 {
-        self.shares.iter().filter_map(|((_, _), shares)| shares.get(prover).copied()).sum()
+        self.to_shares()
+            .iter()
+            .filter_map(|((_, _), shares)| shares.get(prover).copied())
+            .fold(0u64, |total, shares| total.saturating_add(shares))
+    }
+
+    /// Returns `true` if a share with the given nonce was accepted for the round of the given block height and coinbase commitment.
+    fn contains_round_nonce(&self, block_height: u32, coinbase_commitment: N::Commitment, nonce: N::PoSWNonce) -> Result<bool> {
+        self.round_nonces.contains_key(&(block_height, coinbase_commitment, nonce))
     }
 
     fn get_provers(&self) -> Vec<Address<N>> {
         let set: HashSet<Address<N>> = self
-            .shares
+            .to_shares()
             .iter()
             .flat_map(|((_, _), shares)| shares.keys().copied().collect::<Vec<_>>())
             .collect();
@@ -132,24 +614,493 @@ impl<N: Network, A: StorageAccess> SharesState<N, A> {
 }
 
 impl<N: Network, A: StorageReadWrite> SharesState<N, A> {
-    /// Increments the share count by one for a given block height, coinbase record, and prover address.
-    fn increment_share(&self, block_height: u32, coinbase_record: Record<N>, prover: &Address<N>) -> Result<()> {
+    ///
+    /// Increments the shares by the weight of the share difficulty for a given block height, coinbase record, and prover address.
+    /// The given round nonce, if any, is persisted in the same storage batch.
+    ///
+    fn increment_share(
+        &self,
+        block_height: u32,
+        coinbase_record: Record<N>,
+        prover: &Address<N>,
+        share_difficulty: u64,
+        round_nonce: Option<(u32, N::Commitment, N::PoSWNonce)>,
+    ) -> Result<()> {
+        let _increment_lock = self.increment_lock.lock();
+
         // Retrieve the current shares for a given block height.
         let mut shares = match self.shares.get(&(block_height, coinbase_record.clone()))? {
             Some(shares) => shares,
             None => HashMap::new(),
         };
 
-        // Increment the share count for the given address.
+        // Increment the shares for the given address.
         let entry = shares.entry(*prover).or_insert(0);
-        *entry = entry.saturating_add(1);
+        *entry = entry.saturating_add(share_weight(self.base_share_difficulty, share_difficulty));
 
         // Insert the updated shares for the given block height.
+        let batch = self.shares.prepare_batch();
+        self.shares.insert(&(block_height, coinbase_record), &shares, Some(batch))?;
+        if let Some(round_nonce) = round_nonce {
+            self.round_nonces.insert(&round_nonce, &(), Some(batch))?;
+        }
+        self.shares.execute_batch(batch)
+    }
+
+    ///
+    /// Appends a share to the share log, and removes any shares that are no longer in the window.
+    /// The given round nonce, if any, is persisted in the same storage batch.
+    ///
+    fn append_to_share_log(
+        &self,
+        prover: &Address<N>,
+        share_difficulty: u64,
+        window: u32,
+        round_nonce: Option<(u32, N::Commitment, N::PoSWNonce)>,
+    ) -> Result<()> {
+        let mut next_sequence = self.next_sequence.lock();
+        let mut first_sequence = self.first_sequence.lock();
+        let sequence = *next_sequence;
+        let timestamp = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        let batch = self.share_log.prepare_batch();
+        self.share_log.insert(&sequence, &(*prover, share_difficulty, timestamp), Some(batch))?;
+        // Prune the shares that fell out of the window (including any left over from a larger window size),
+        // by their range of sequence numbers, as the share log is contiguous.
+        let window_start = (sequence + 1).saturating_sub(window as u64).max(*first_sequence);
+        for stale in *first_sequence..window_start {
+            self.share_log.remove(&stale, Some(batch))?;
+        }
+        if let Some(round_nonce) = round_nonce {
+            self.round_nonces.insert(&round_nonce, &(), Some(batch))?;
+        }
+        self.share_log.execute_batch(batch)?;
+
+        *next_sequence = sequence + 1;
+        *first_sequence = window_start;
+        Ok(())
+    }
+
+    /// Stores the difficulty-weighted shares in the share log as the shares for a given block height and coinbase record.
+    fn snapshot_share_log(&self, block_height: u32, coinbase_record: Record<N>) -> Result<()> {
+        // Hold the lock so that no share is appended while the window is read.
+        let _next_sequence = self.next_sequence.lock();
+
+        let mut shares: HashMap<Address<N>, u64> = HashMap::new();
+        for (_, (prover, share_difficulty, _)) in self.share_log.iter() {
+            let entry = shares.entry(prover).or_insert(0);
+            *entry = entry.saturating_add(share_weight(self.base_share_difficulty, share_difficulty));
+        }
+
         self.shares.insert(&(block_height, coinbase_record), &shares, None)
     }
 
+    /// Removes all of the shares for a given block height and coinbase record, and adds them to the given round, if any.
+    fn roll_over_shares(&self, block_height: u32, coinbase_record: Record<N>, roll_into: Option<(u32, Record<N>)>) -> Result<()> {
+        let _increment_lock = self.increment_lock.lock();
+
+        let key = (block_height, coinbase_record);
+        let batch = self.shares.prepare_batch();
+        if let Some(target) = roll_into {
+            let mut shares = self.shares.get(&key)?.unwrap_or_default();
+            merge_shares(&mut shares, self.unit_shares.get(&key)?.unwrap_or_default());
+
+            let mut target_shares = self.shares.get(&target)?.unwrap_or_default();
+            merge_shares(&mut target_shares, shares);
+            self.shares.insert(&target, &target_shares, Some(batch))?;
+        }
+        self.shares.remove(&key, Some(batch))?;
+        self.unit_shares.remove(&key, Some(batch))?;
+        self.shares.execute_batch(batch)
+    }
+
+    /// Removes the persisted nonces of every round other than the given rounds, each given by its block height and coinbase commitment.
+    fn clear_round_nonces_except(&self, rounds: &[(u32, N::Commitment)]) -> Result<()> {
+        let batch = self.round_nonces.prepare_batch();
+        let is_other_round = |(height, commitment, _): &(u32, N::Commitment, N::PoSWNonce)| !rounds.contains(&(*height, *commitment));
+        for key in self.round_nonces.keys().filter(is_other_round) {
+            self.round_nonces.remove(&key, Some(batch))?;
+        }
+        self.round_nonces.execute_batch(batch)
+    }
+
+    /// Prepares a storage batch.
+    fn prepare_batch(&self) -> usize {
+        self.shares.prepare_batch()
+    }
+
+    /// Executes the given storage batch.
+    fn execute_batch(&self, batch: usize) -> Result<()> {
+        self.shares.execute_batch(batch)
+    }
+
+    /// Removes all of the shares for a given block height and coinbase record in the given batch,
+    /// returning the number of storage keys that are removed.
+    fn remove_shares_in_batch(&self, block_height: u32, coinbase_record: Record<N>, batch: usize) -> Result<usize> {
+        let key = (block_height, coinbase_record);
+        let mut num_keys = 0;
+        if self.shares.contains_key(&key)? {
+            self.shares.remove(&key, Some(batch))?;
+            num_keys += 1;
+        }
+        if self.unit_shares.contains_key(&key)? {
+            self.unit_shares.remove(&key, Some(batch))?;
+            num_keys += 1;
+        }
+        Ok(num_keys)
+    }
+
     /// Removes all of the shares for a given block height and coinbase record.
     fn remove_shares(&self, block_height: u32, coinbase_record: Record<N>) -> Result<()> {
-        self.shares.remove(&(block_height, coinbase_record), None)
+        let key = (block_height, coinbase_record);
+        let batch = self.shares.prepare_batch();
+        self.shares.remove(&key, Some(batch))?;
+        self.unit_shares.remove(&key, Some(batch))?;
+        self.shares.execute_batch(batch)
+    }
+}
+
+#[derive(Clone, Debug)]
+#[allow(clippy::type_complexity)]
+struct PayoutsState<N: Network, A: StorageAccess> {
+    /// The payout transaction IDs for each paid round.
+    payouts: DataMap<(u32, Record<N>), Vec<N::TransactionID>, A>,
+}
+
+impl<N: Network, A: StorageAccess> PayoutsState<N, A> {
+    /// Initializes a new instance of `PayoutsState`.
+    fn open<S: Storage<Access = A>>(storage: &S) -> Result<Self> {
+        Ok(Self {
+            payouts: storage.open_map(DataID::Payouts)?,
+        })
+    }
+
+    /// Returns `true` if the given block height and coinbase record has been paid out.
+    fn is_paid(&self, block_height: u32, coinbase_record: Record<N>) -> Result<bool> {
+        self.payouts.contains_key(&(block_height, coinbase_record))
+    }
+
+    /// Returns the payout transaction IDs for the given block height and coinbase record.
+    fn get_payout(&self, block_height: u32, coinbase_record: Record<N>) -> Result<Option<Vec<N::TransactionID>>> {
+        self.payouts.get(&(block_height, coinbase_record))
+    }
+}
+
+impl<N: Network, A: StorageReadWrite> PayoutsState<N, A> {
+    /// Stores the payout transaction IDs for the given block height and coinbase record.
+    fn mark_paid(&self, block_height: u32, coinbase_record: Record<N>, transaction_ids: &[N::TransactionID]) -> Result<()> {
+        self.payouts.insert(&(block_height, coinbase_record), &transaction_ids.to_vec(), None)
+    }
+
+    /// Removes the payout for the given block height and coinbase record in the given batch,
+    /// returning the number of storage keys that are removed.
+    fn remove_payout_in_batch(&self, block_height: u32, coinbase_record: Record<N>, batch: usize) -> Result<usize> {
+        let key = (block_height, coinbase_record);
+        match self.payouts.contains_key(&key)? {
+            true => {
+                self.payouts.remove(&key, Some(batch))?;
+                Ok(1)
+            }
+            false => Ok(0),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StatsState<N: Network, A: StorageAccess> {
+    /// The number of shares submitted by each prover, by outcome.
+    stats: DataMap<Address<N>, ProverStats, A>,
+    /// A lock held while the stats of a prover are updated, so that concurrent increments are not lost.
+    record_lock: Mutex<()>,
+}
+
+impl<N: Network, A: StorageAccess> StatsState<N, A> {
+    /// Initializes a new instance of `StatsState`.
+    fn open<S: Storage<Access = A>>(storage: &S) -> Result<Self> {
+        Ok(Self {
+            stats: storage.open_map(DataID::ProverStats)?,
+            record_lock: Mutex::new(()),
+        })
+    }
+
+    /// Returns the number of shares submitted by the given prover, by outcome.
+    fn get_stats_for_prover(&self, prover: &Address<N>) -> Result<ProverStats> {
+        Ok(self.stats.get(prover)?.unwrap_or_default())
+    }
+
+    /// Returns the number of shares submitted by each prover, by outcome.
+    fn to_prover_stats(&self) -> Vec<(Address<N>, ProverStats)> {
+        self.stats.iter().collect()
+    }
+}
+
+impl<N: Network, A: StorageReadWrite> StatsState<N, A> {
+    /// Increments the share counter of the given prover for the given share outcome.
+    fn record_share_outcome(&self, prover: &Address<N>, outcome: ShareOutcome) -> Result<()> {
+        let _record_lock = self.record_lock.lock();
+
+        let mut stats = self.get_stats_for_prover(prover)?;
+        stats.record(outcome);
+        self.stats.insert(prover, &stats, None)
+    }
+
+    /// Removes the share counters of all provers.
+    fn reset_prover_stats(&self) -> Result<()> {
+        let _record_lock = self.record_lock.lock();
+
+        let batch = self.stats.prepare_batch();
+        for prover in self.stats.keys() {
+            self.stats.remove(&prover, Some(batch))?;
+        }
+        self.stats.execute_batch(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{
+        rocksdb::{tests::temp_dir, RocksDB},
+        ReadOnly,
+        ReadWrite,
+    };
+    use snarkvm::prelude::Testnet3;
+
+    use rand::thread_rng;
+    use std::str::FromStr;
+
+    type CurrentNetwork = Testnet3;
+
+    /// The base share difficulty of the operator in the tests.
+    const BASE_SHARE_DIFFICULTY: u64 = 1 << 40;
+
+    /// Opens the operator state at the given path, with the base share difficulty of the tests.
+    fn open_operator<P: AsRef<Path>>(path: P, accounting: ShareAccounting) -> Result<OperatorState<CurrentNetwork, ReadWrite>> {
+        OperatorState::open::<RocksDB, _>(path, accounting, BASE_SHARE_DIFFICULTY)
+    }
+
+    /// Returns a new prover address and a coinbase record owned by it.
+    fn sample_prover_and_record() -> (Address<CurrentNetwork>, Record<CurrentNetwork>) {
+        let private_key = PrivateKey::<CurrentNetwork>::new(&mut thread_rng()).expect("Failed to sample a private key");
+        let address = Address::try_from(&private_key).expect("Failed to derive an address");
+        let record = Record::from_str(&format!("{{ owner: {}.private, gates: 5u64.private, _nonce: 0group.public }}", address))
+            .expect("Failed to parse a record");
+        (address, record)
+    }
+
+    #[test]
+    fn test_share_weight() {
+        // A share at the base share difficulty weighs 1, and harder shares weigh proportionally more.
+        assert_eq!(1, share_weight(BASE_SHARE_DIFFICULTY, BASE_SHARE_DIFFICULTY));
+        assert_eq!(4, share_weight(BASE_SHARE_DIFFICULTY, BASE_SHARE_DIFFICULTY / 4));
+        // Every share weighs at least 1.
+        assert_eq!(1, share_weight(BASE_SHARE_DIFFICULTY, BASE_SHARE_DIFFICULTY * 2));
+        assert_eq!(BASE_SHARE_DIFFICULTY, share_weight(BASE_SHARE_DIFFICULTY, 0));
+    }
+
+    #[test]
+    fn test_shut_down_and_reopen() {
+        let dir = temp_dir();
+        let (prover, coinbase_record) = sample_prover_and_record();
+
+        {
+            let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to open operator state");
+            assert!(!operator.mark_running().unwrap());
+
+            operator.increment_share(1, coinbase_record.clone(), &prover, BASE_SHARE_DIFFICULTY).unwrap();
+            operator.increment_share(1, coinbase_record.clone(), &prover, BASE_SHARE_DIFFICULTY / 2).unwrap();
+            operator.shut_down().unwrap();
+        }
+
+        // Reopen the operator state, and ensure the shares were kept and the shutdown was clean.
+        let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to reopen operator state");
+        assert!(!operator.mark_running().unwrap());
+        assert_eq!(3, operator.get_shares_for_prover(&prover));
+        assert_eq!(3, operator.get_shares_for_block(1, coinbase_record).unwrap()[&prover]);
+    }
+
+    #[test]
+    fn test_detect_unclean_shutdown() {
+        let dir = temp_dir();
+
+        {
+            let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to open operator state");
+            assert!(!operator.mark_running().unwrap());
+            // Drop the operator state without shutting it down.
+        }
+
+        let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to reopen operator state");
+        assert!(operator.mark_running().unwrap());
+    }
+
+    #[test]
+    fn test_remove_orphaned_round() {
+        let (first_prover, orphaned_record) = sample_prover_and_record();
+        let (second_prover, next_record) = sample_prover_and_record();
+
+        let operator = open_operator(temp_dir(), ShareAccounting::Proportional).expect("Failed to open operator state");
+        operator.increment_share(1, orphaned_record.clone(), &first_prover, BASE_SHARE_DIFFICULTY).unwrap();
+        operator.increment_share(1, orphaned_record.clone(), &second_prover, BASE_SHARE_DIFFICULTY).unwrap();
+        operator.increment_share(2, next_record.clone(), &second_prover, BASE_SHARE_DIFFICULTY).unwrap();
+
+        // Roll the shares of the orphaned round into the next round.
+        operator.remove_orphaned_round(1, orphaned_record.clone(), Some((2, next_record.clone()))).unwrap();
+        assert!(operator.get_shares_for_block(1, orphaned_record).is_err());
+
+        let shares = operator.get_shares_for_block(2, next_record.clone()).unwrap();
+        assert_eq!(1, shares[&first_prover]);
+        assert_eq!(2, shares[&second_prover]);
+
+        // Remove the next round without rolling its shares over.
+        operator.remove_orphaned_round(2, next_record.clone(), None).unwrap();
+        assert!(operator.get_shares_for_block(2, next_record).is_err());
+        assert_eq!(0, operator.get_shares_for_prover(&first_prover));
+    }
+
+    #[test]
+    fn test_prune_rounds_below() {
+        let (prover, first_record) = sample_prover_and_record();
+        let (_, second_record) = sample_prover_and_record();
+        let (_, third_record) = sample_prover_and_record();
+
+        let operator = open_operator(temp_dir(), ShareAccounting::Proportional).expect("Failed to open operator state");
+        operator.increment_share(1, first_record.clone(), &prover, BASE_SHARE_DIFFICULTY).unwrap();
+        operator.increment_share(2, second_record.clone(), &prover, BASE_SHARE_DIFFICULTY).unwrap();
+        operator.increment_share(3, third_record.clone(), &prover, BASE_SHARE_DIFFICULTY).unwrap();
+        operator.mark_paid(1, first_record.clone(), &[]).unwrap();
+        operator.mark_paid(3, third_record.clone(), &[]).unwrap();
+
+        // Ensure only the paid round below the given height is removed, along with its payout.
+        assert_eq!((1, 2), operator.prune_rounds_below(3).unwrap());
+        assert!(operator.get_shares_for_block(1, first_record.clone()).is_err());
+        assert!(!operator.is_paid(1, first_record).unwrap());
+        assert!(operator.get_shares_for_block(2, second_record).is_ok());
+        assert!(operator.get_shares_for_block(3, third_record).is_ok());
+        assert_eq!(2, operator.to_shares().len());
+
+        // Ensure pruning again removes nothing.
+        assert_eq!((0, 0), operator.prune_rounds_below(3).unwrap());
+    }
+
+    #[test]
+    fn test_for_each_share_row() {
+        let dir = temp_dir();
+        let (first_prover, first_record) = sample_prover_and_record();
+        let (second_prover, second_record) = sample_prover_and_record();
+
+        {
+            let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to open operator state");
+            operator.increment_share(1, first_record.clone(), &first_prover, BASE_SHARE_DIFFICULTY).unwrap();
+            operator.increment_share(1, first_record.clone(), &second_prover, BASE_SHARE_DIFFICULTY).unwrap();
+            operator.increment_share(2, second_record.clone(), &second_prover, BASE_SHARE_DIFFICULTY / 2).unwrap();
+            operator.mark_paid(1, first_record.clone(), &[]).unwrap();
+            operator.shut_down().unwrap();
+        }
+
+        // Ensure the rows can be exported from a read-only instance.
+        let operator = OperatorState::<CurrentNetwork, ReadOnly>::open_reader::<RocksDB, _>(&dir).expect("Failed to open operator state");
+        let mut rows = Vec::new();
+        operator
+            .for_each_share_row(0..=u32::MAX, None, |row| {
+                rows.push(row);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(3, rows.len());
+        assert!(rows.contains(&ShareRow {
+            block_height: 2,
+            coinbase_commitment: second_record.commitment(),
+            prover: second_prover,
+            shares: 2,
+            round_status: RoundStatus::Open,
+        }));
+
+        // Ensure the rows are filtered by block height and by prover.
+        let mut rows = Vec::new();
+        operator
+            .for_each_share_row(1..=1, Some(&first_prover), |row| {
+                rows.push(row);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            vec![ShareRow {
+                block_height: 1,
+                coinbase_commitment: first_record.commitment(),
+                prover: first_prover,
+                shares: 1,
+                round_status: RoundStatus::Paid,
+            }],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_recent_round_work() {
+        let operator = open_operator(temp_dir(), ShareAccounting::Proportional).expect("Failed to open operator state");
+        assert!(operator.get_recent_round_work(2).is_empty());
+
+        operator.add_round_work(1, 10, 20).unwrap();
+        operator.add_round_work(300, 30, 20).unwrap();
+        operator.add_round_work(2, 20, 20).unwrap();
+
+        // Ensure the most recent rounds are returned, in descending order of block height.
+        assert_eq!(vec![(300, (30, 20)), (2, (20, 20))], operator.get_recent_round_work(2));
+        assert_eq!(3, operator.get_recent_round_work(10).len());
+    }
+
+    #[test]
+    fn test_prover_stats() {
+        let dir = temp_dir();
+        let (prover, _) = sample_prover_and_record();
+
+        {
+            let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to open operator state");
+            assert_eq!(ProverStats::default(), operator.get_stats_for_prover(&prover).unwrap());
+
+            operator.record_share_outcome(&prover, ShareOutcome::Accepted).unwrap();
+            operator.record_share_outcome(&prover, ShareOutcome::Accepted).unwrap();
+            operator.record_share_outcome(&prover, ShareOutcome::Rejected).unwrap();
+            operator.record_share_outcome(&prover, ShareOutcome::Stale).unwrap();
+            operator.record_share_outcome(&prover, ShareOutcome::Duplicate).unwrap();
+        }
+
+        // Ensure the stats survive a restart.
+        let operator = open_operator(&dir, ShareAccounting::Proportional).expect("Failed to reopen operator state");
+        let expected = ProverStats {
+            accepted: 2,
+            rejected: 1,
+            stale: 1,
+            duplicate: 1,
+        };
+        assert_eq!(expected, operator.get_stats_for_prover(&prover).unwrap());
+        assert_eq!(vec![(prover, expected)], operator.to_prover_stats());
+
+        // Ensure the stats are reset.
+        operator.reset_prover_stats().unwrap();
+        assert_eq!(ProverStats::default(), operator.get_stats_for_prover(&prover).unwrap());
+        assert!(operator.to_prover_stats().is_empty());
+    }
+
+    #[test]
+    fn test_pplns_window() {
+        let dir = temp_dir();
+        let (prover, coinbase_record) = sample_prover_and_record();
+
+        {
+            let operator = open_operator(&dir, ShareAccounting::Pplns(3)).expect("Failed to open operator state");
+            for _ in 0..5 {
+                operator.increment_share(1, coinbase_record.clone(), &prover, BASE_SHARE_DIFFICULTY).unwrap();
+            }
+            operator.close_round(1, coinbase_record.clone()).unwrap();
+            assert_eq!(3, operator.get_shares_for_block(1, coinbase_record.clone()).unwrap()[&prover]);
+        }
+
+        // Reopen the operator state with a smaller window, and ensure the shares left over from the larger window are pruned.
+        let operator = open_operator(&dir, ShareAccounting::Pplns(2)).expect("Failed to reopen operator state");
+        operator.increment_share(2, coinbase_record.clone(), &prover, BASE_SHARE_DIFFICULTY).unwrap();
+        operator.close_round(2, coinbase_record.clone()).unwrap();
+        assert_eq!(2, operator.get_shares_for_block(2, coinbase_record).unwrap()[&prover]);
+        assert_eq!(2, operator.shares.share_log.keys().count());
     }
 }
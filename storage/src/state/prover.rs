@@ -18,12 +18,63 @@ use crate::storage::{DataMap, DataID, MapRead, MapReadWrite, Storage, StorageAcc
 use snarkvm::prelude::*;
 
 use anyhow::{anyhow, Result};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, path::Path};
+
+///
+/// The statistics of a prover, as counted over a period of time.
+///
+/// *Attention*: This data structure is intended for usage in storage only.
+/// Modifications to its layout will impact how the statistics are represented in storage.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverLifetimeStats {
+    /// The number of completed proof attempts, whether or not they met the share difficulty.
+    pub proof_attempts: u64,
+    /// The number of shares sent to a pool. Pools do not acknowledge shares, so some may have been rejected.
+    pub submitted_shares: u64,
+    /// The number of shares that could not be sent to a pool.
+    pub failed_submissions: u64,
+    /// The number of shares that were not sent, for failing to meet the share difficulty most recently assigned by the pool.
+    pub dropped_shares: u64,
+    /// The number of shares that were not sent, as their block template was replaced, or the share queue was full.
+    pub stale_shares: u64,
+    /// The number of blocks found in solo mode.
+    pub blocks_found: u64,
+}
+
+impl ProverLifetimeStats {
+    /// Adds the given statistics to these statistics.
+    pub fn add(&mut self, other: &Self) {
+        self.proof_attempts = self.proof_attempts.saturating_add(other.proof_attempts);
+        self.submitted_shares = self.submitted_shares.saturating_add(other.submitted_shares);
+        self.failed_submissions = self.failed_submissions.saturating_add(other.failed_submissions);
+        self.dropped_shares = self.dropped_shares.saturating_add(other.dropped_shares);
+        self.stale_shares = self.stale_shares.saturating_add(other.stale_shares);
+        self.blocks_found = self.blocks_found.saturating_add(other.blocks_found);
+    }
+
+    /// Returns the statistics counted since the given earlier statistics.
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            proof_attempts: self.proof_attempts.saturating_sub(earlier.proof_attempts),
+            submitted_shares: self.submitted_shares.saturating_sub(earlier.submitted_shares),
+            failed_submissions: self.failed_submissions.saturating_sub(earlier.failed_submissions),
+            dropped_shares: self.dropped_shares.saturating_sub(earlier.dropped_shares),
+            stale_shares: self.stale_shares.saturating_sub(earlier.stale_shares),
+            blocks_found: self.blocks_found.saturating_sub(earlier.blocks_found),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct ProverState<N: Network, A: StorageAccess> {
     /// The coinbase records of the prover in storage.
     coinbase: CoinbaseState<N, A>,
+    /// The lifetime statistics of the prover in storage.
+    stats: DataMap<(), ProverLifetimeStats, A>,
+    /// The lifetime number of shares sent to each pool in storage.
+    pool_shares: DataMap<SocketAddr, u64, A>,
 }
 
 impl<N: Network, A: StorageAccess> ProverState<N, A> {
@@ -35,6 +86,8 @@ impl<N: Network, A: StorageAccess> ProverState<N, A> {
 
         // Initialize the prover.
         let prover = Self {
+            stats: storage.open_map(DataID::ProverLifetimeStats)?,
+            pool_shares: storage.open_map(DataID::PoolShares)?,
             coinbase: CoinbaseState::open(storage)?,
         };
 
@@ -56,6 +109,16 @@ impl<N: Network, A: StorageAccess> ProverState<N, A> {
     pub fn get_coinbase_record(&self, commitment: &N::Commitment) -> Result<(u32, Record<N>)> {
         self.coinbase.get_record(commitment)
     }
+
+    /// Returns the lifetime statistics of the prover.
+    pub fn get_lifetime_stats(&self) -> Result<ProverLifetimeStats> {
+        Ok(self.stats.get(&())?.unwrap_or_default())
+    }
+
+    /// Returns the lifetime number of shares sent to each pool.
+    pub fn to_pool_shares(&self) -> Vec<(SocketAddr, u64)> {
+        self.pool_shares.iter().collect()
+    }
 }
 
 impl<N: Network, A: StorageReadWrite> ProverState<N, A> {
@@ -64,10 +127,56 @@ impl<N: Network, A: StorageReadWrite> ProverState<N, A> {
         self.coinbase.add_record(block_height, record)
     }
 
+    ///
+    /// Adds the given statistics, and the given number of shares sent to each pool, to the lifetime statistics
+    /// of the prover in a single batch.
+    ///
+    pub fn add_lifetime_stats(&self, stats: &ProverLifetimeStats, pool_shares: &[(SocketAddr, u64)]) -> Result<()> {
+        let mut lifetime_stats = self.get_lifetime_stats()?;
+        lifetime_stats.add(stats);
+
+        let batch = self.stats.prepare_batch();
+        self.stats.insert(&(), &lifetime_stats, Some(batch))?;
+        for (pool_ip, num_shares) in pool_shares {
+            let lifetime_shares = self.pool_shares.get(pool_ip)?.unwrap_or_default();
+            self.pool_shares.insert(pool_ip, &lifetime_shares.saturating_add(*num_shares), Some(batch))?;
+        }
+        self.stats.execute_batch(batch)
+    }
+
     /// Removes the given record from storage.
     pub fn remove_coinbase_record(&self, commitment: &N::Commitment) -> Result<()> {
         self.coinbase.remove_record(commitment)
     }
+
+    ///
+    /// Removes the coinbase records below the given block height that are not on the canonical chain,
+    /// and returns the number of records removed. The given closure determines if the record of a block height
+    /// is on the canonical chain. A record is only removed if the closure succeeds, so that a record whose
+    /// status cannot be determined is retained.
+    ///
+    pub fn prune_orphaned_coinbase_records<F: FnMut(u32, &Record<N>) -> Result<bool>>(
+        &self,
+        block_height: u32,
+        mut is_canonical: F,
+    ) -> Result<usize> {
+        let mut num_pruned = 0;
+        for (height, record) in self.coinbase.to_records() {
+            if height >= block_height {
+                continue;
+            }
+
+            match is_canonical(height, &record) {
+                Ok(true) => continue,
+                Ok(false) => {
+                    self.coinbase.remove_record(&record.commitment())?;
+                    num_pruned += 1;
+                }
+                Err(error) => warn!("Failed to determine if the coinbase record for block {} is canonical - {}", height, error),
+            }
+        }
+        Ok(num_pruned)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -124,3 +233,76 @@ impl<N: Network, A: StorageReadWrite> CoinbaseState<N, A> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{
+        rocksdb::{tests::temp_dir, RocksDB},
+        ReadWrite,
+    };
+    use snarkvm::prelude::Testnet3;
+
+    use rand::thread_rng;
+    use std::str::FromStr;
+
+    type CurrentNetwork = Testnet3;
+
+    /// Returns a new coinbase record, owned by a new address.
+    fn sample_record() -> Record<CurrentNetwork> {
+        let private_key = PrivateKey::<CurrentNetwork>::new(&mut thread_rng()).expect("Failed to sample a private key");
+        let address = Address::try_from(&private_key).expect("Failed to derive an address");
+        Record::from_str(&format!("{{ owner: {}.private, gates: 5u64.private, _nonce: 0group.public }}", address))
+            .expect("Failed to parse a record")
+    }
+
+    #[test]
+    fn test_prune_orphaned_coinbase_records() {
+        let prover = ProverState::<CurrentNetwork, ReadWrite>::open::<RocksDB, _>(temp_dir()).expect("Failed to open prover state");
+        let (canonical, orphaned, unknown, recent) = (sample_record(), sample_record(), sample_record(), sample_record());
+        prover.add_coinbase_record(1, canonical.clone()).unwrap();
+        prover.add_coinbase_record(2, orphaned.clone()).unwrap();
+        prover.add_coinbase_record(3, unknown.clone()).unwrap();
+        prover.add_coinbase_record(10, recent.clone()).unwrap();
+
+        let num_pruned = prover
+            .prune_orphaned_coinbase_records(10, |block_height, _| match block_height {
+                1 => Ok(true),
+                2 => Ok(false),
+                _ => Err(anyhow!("Missing block {}", block_height)),
+            })
+            .unwrap();
+
+        // Only the orphaned record is removed. The records that are canonical, of unknown status, or too recent are retained.
+        assert_eq!(1, num_pruned);
+        assert!(prover.contains_coinbase_record(&canonical.commitment()).unwrap());
+        assert!(!prover.contains_coinbase_record(&orphaned.commitment()).unwrap());
+        assert!(prover.contains_coinbase_record(&unknown.commitment()).unwrap());
+        assert!(prover.contains_coinbase_record(&recent.commitment()).unwrap());
+    }
+
+    #[test]
+    fn test_add_lifetime_stats() {
+        let prover = ProverState::<CurrentNetwork, ReadWrite>::open::<RocksDB, _>(temp_dir()).expect("Failed to open prover state");
+        assert_eq!(ProverLifetimeStats::default(), prover.get_lifetime_stats().unwrap());
+        assert!(prover.to_pool_shares().is_empty());
+
+        let (pool_a, pool_b) = ("127.0.0.1:4132".parse().unwrap(), "127.0.0.1:4133".parse().unwrap());
+        let stats = ProverLifetimeStats { proof_attempts: 100, submitted_shares: 3, stale_shares: 1, ..Default::default() };
+        prover.add_lifetime_stats(&stats, &[(pool_a, 3)]).unwrap();
+        let stats = ProverLifetimeStats { proof_attempts: 50, submitted_shares: 2, blocks_found: 1, ..Default::default() };
+        prover.add_lifetime_stats(&stats, &[(pool_a, 1), (pool_b, 1)]).unwrap();
+
+        // The statistics accumulate across flushes.
+        let lifetime_stats = prover.get_lifetime_stats().unwrap();
+        assert_eq!(150, lifetime_stats.proof_attempts);
+        assert_eq!(5, lifetime_stats.submitted_shares);
+        assert_eq!(1, lifetime_stats.stale_shares);
+        assert_eq!(1, lifetime_stats.blocks_found);
+        assert_eq!(lifetime_stats, lifetime_stats.since(&ProverLifetimeStats::default()));
+
+        let mut pool_shares = prover.to_pool_shares();
+        pool_shares.sort();
+        assert_eq!(vec![(pool_a, 4), (pool_b, 1)], pool_shares);
+    }
+}
@@ -37,6 +37,22 @@ pub enum DataID {
     Deployments,
     Programs,
     Shares,
+    ShareLog,
+    Payouts,
+    WeightedShares,
+    OperatorStatus,
+    ProverStats,
+    BlocksFound,
+    RoundWork,
+    RoundNonces,
+    ProverLifetimeStats,
+    PoolShares,
+    PrunedBlockHeight,
+    AddressTransactions,
+    CommitmentTransactions,
+    TransactionIndexHeight,
+    SyncTargetHeight,
+    HeaderLinks,
     #[cfg(test)]
     Test,
 }
@@ -57,6 +73,22 @@ impl From<u16> for DataID {
             9 => Self::Deployments,
             10 => Self::Programs,
             11 => Self::Shares,
+            12 => Self::ShareLog,
+            13 => Self::Payouts,
+            14 => Self::WeightedShares,
+            15 => Self::OperatorStatus,
+            16 => Self::ProverStats,
+            17 => Self::BlocksFound,
+            18 => Self::RoundWork,
+            19 => Self::RoundNonces,
+            20 => Self::ProverLifetimeStats,
+            21 => Self::PoolShares,
+            22 => Self::PrunedBlockHeight,
+            23 => Self::AddressTransactions,
+            24 => Self::CommitmentTransactions,
+            25 => Self::TransactionIndexHeight,
+            26 => Self::SyncTargetHeight,
+            27 => Self::HeaderLinks,
             x => panic!("Unexpected map id: {}", x),
         }
     }
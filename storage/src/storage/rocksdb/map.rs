@@ -201,7 +201,9 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned, A: St
     ///
     fn execute_batch(&self, batch: usize) -> Result<()> {
         if let Some(batch) = self.storage.batches.lock().remove(&batch) {
-            Ok(self.storage.rocksdb.write(batch)?)
+            let mut write_options = rocksdb::WriteOptions::default();
+            write_options.set_sync(self.storage.sync_writes.load(Ordering::SeqCst));
+            Ok(self.storage.rocksdb.write_opt(batch, &write_options)?)
         } else {
             bail!("There is no pending storage batch with id = {}", batch);
         }
@@ -217,4 +219,19 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned, A: St
             Ok(())
         }
     }
+
+    ///
+    /// Flushes all the writes to the underlying storage to disk, including the write-ahead log.
+    ///
+    fn flush(&self) -> Result<()> {
+        self.storage.rocksdb.flush()?;
+        Ok(self.storage.rocksdb.flush_wal(true)?)
+    }
+
+    ///
+    /// Sets whether each executed write batch is synced to disk before it returns, for every map of the storage.
+    ///
+    fn set_sync_writes(&self, sync_writes: bool) {
+        self.storage.sync_writes.store(sync_writes, Ordering::SeqCst);
+    }
 }
@@ -42,7 +42,10 @@ use std::{
     io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     marker::PhantomData,
     path::Path,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 pub const PREFIX_LEN: usize = 4; // N::ID (u16) + DataID (u16)
@@ -55,6 +58,8 @@ pub struct RocksDB<A: StorageAccess = ReadWrite> {
     rocksdb: Arc<rocksdb::DB>,
     context: Vec<u8>,
     batches: Arc<Mutex<HashMap<usize, rocksdb::WriteBatch>>>,
+    /// If `true`, each executed write batch is synced to disk before it returns.
+    sync_writes: Arc<AtomicBool>,
     _phantom: PhantomData<A>,
 }
 
@@ -93,6 +98,7 @@ impl Storage for RocksDB {
             rocksdb,
             context,
             batches: Default::default(),
+            sync_writes: Default::default(),
             _phantom: Default::default(),
         })
     }
@@ -208,6 +214,7 @@ impl Storage for RocksDB<ReadOnly> {
             rocksdb,
             context,
             batches: Default::default(),
+            sync_writes: Default::default(),
             _phantom: Default::default(),
         })
     }
@@ -233,3 +233,75 @@ fn test_export_import() {
         assert_eq!(map.get(&i).expect("Failed to get"), Some(i.to_string()));
     }
 }
+
+/// If set, `test_kill_and_reopen` runs as the writer process on the storage at the given path, until it is killed.
+const KILL_AND_REOPEN_WRITER_PATH: &str = "SNARKOS_KILL_AND_REOPEN_WRITER_PATH";
+/// The number of keys written in each batch of `test_kill_and_reopen`.
+const KEYS_PER_BATCH: u32 = 1000;
+
+#[test]
+fn test_kill_and_reopen() {
+    use std::{
+        collections::BTreeMap,
+        process::{Command, Stdio},
+        time::{Duration, Instant},
+    };
+
+    // In the writer process, write synced batches of keys until the process is killed.
+    if let Ok(directory) = std::env::var(KILL_AND_REOPEN_WRITER_PATH) {
+        let storage = RocksDB::<ReadWrite>::open(&directory, 0).expect("Failed to open storage");
+        let map = storage.open_map::<u32, u32>(DataID::Test).expect("Failed to open data map");
+        map.set_sync_writes(true);
+
+        for batch_index in 0u32.. {
+            let batch = map.prepare_batch();
+            for key in batch_index * KEYS_PER_BATCH..(batch_index + 1) * KEYS_PER_BATCH {
+                map.insert(&key, &batch_index, Some(batch)).expect("Failed to insert");
+            }
+            map.execute_batch(batch).expect("Failed to execute a batch");
+
+            // Signal that the first batch is written.
+            if batch_index == 0 {
+                std::fs::write(format!("{}.ready", directory), []).expect("Failed to signal the first batch");
+            }
+        }
+        return;
+    }
+
+    // Spawn the writer process, and kill it while it is writing.
+    let directory = temp_dir();
+    let ready = format!("{}.ready", directory.display());
+    let mut writer = Command::new(std::env::current_exe().expect("Failed to locate the test binary"))
+        .args(["--exact", "storage::rocksdb::tests::test_kill_and_reopen", "--test-threads=1"])
+        .env(KILL_AND_REOPEN_WRITER_PATH, &directory)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to spawn the writer process");
+
+    let start = Instant::now();
+    while !std::path::Path::new(&ready).exists() {
+        assert!(start.elapsed() < Duration::from_secs(60), "The writer process did not write a batch");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    writer.kill().expect("Failed to kill the writer process");
+    writer.wait().expect("Failed to wait for the writer process");
+
+    // Reopen the storage, and ensure every batch is either entirely present or entirely absent.
+    let storage = RocksDB::<ReadWrite>::open(&directory, 0).expect("Failed to open storage");
+    let map = storage.open_map::<u32, u32>(DataID::Test).expect("Failed to open data map");
+
+    let mut number_of_keys = BTreeMap::<u32, u32>::new();
+    for (key, batch_index) in map.iter() {
+        assert_eq!(key / KEYS_PER_BATCH, batch_index);
+        *number_of_keys.entry(batch_index).or_default() += 1;
+    }
+
+    // As the batches are synced, the batches up to the last one are all present.
+    assert!(!number_of_keys.is_empty());
+    for (expected_batch_index, (batch_index, number_of_keys)) in number_of_keys.into_iter().enumerate() {
+        assert_eq!(batch_index, expected_batch_index as u32);
+        assert_eq!(number_of_keys, KEYS_PER_BATCH);
+    }
+}
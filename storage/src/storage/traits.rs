@@ -152,4 +152,15 @@ pub trait MapReadWrite<'a, K: Serialize + DeserializeOwned, V: Serialize + Deser
     /// Discards a write batch with the given id.
     ///
     fn discard_batch(&self, batch: usize) -> Result<()>;
+
+    ///
+    /// Flushes all the writes to the underlying storage to disk, including the write-ahead log.
+    ///
+    fn flush(&self) -> Result<()>;
+
+    ///
+    /// Sets whether each executed write batch is synced to disk before it returns.
+    /// Without synced writes, a crash of the machine may lose the latest batches, though never part of a batch.
+    ///
+    fn set_sync_writes(&self, sync_writes: bool);
 }